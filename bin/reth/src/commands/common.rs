@@ -15,7 +15,7 @@ use reth_node_core::{
     },
     dirs::{ChainPath, DataDirPath},
 };
-use reth_primitives::B256;
+use reth_primitives::{BlockNumber, B256};
 use reth_provider::{providers::StaticFileProvider, ProviderFactory, StaticFileProviderFactory};
 use reth_stages::{sets::DefaultStages, Pipeline, PipelineTarget};
 use reth_static_file::StaticFileProducer;
@@ -55,6 +55,20 @@ impl EnvironmentArgs {
     /// Initializes environment according to [`AccessRights`] and returns an instance of
     /// [`Environment`].
     pub fn init(&self, access: AccessRights) -> eyre::Result<Environment> {
+        self.init_with(access, true)
+    }
+
+    /// Initializes environment according to [`AccessRights`], without checking or healing the
+    /// consistency between the database and static files.
+    pub fn init_unchecked(&self, access: AccessRights) -> eyre::Result<Environment> {
+        self.init_with(access, false)
+    }
+
+    fn init_with(
+        &self,
+        access: AccessRights,
+        check_consistency: bool,
+    ) -> eyre::Result<Environment> {
         let data_dir = self.datadir.clone().resolve_datadir(self.chain.chain);
         let db_path = data_dir.db();
         let sf_path = data_dir.static_files();
@@ -76,7 +90,8 @@ impl EnvironmentArgs {
         let (db, sfp) = match access {
             AccessRights::RW => (
                 Arc::new(init_db(db_path, self.db.database_args())?),
-                StaticFileProvider::read_write(sf_path)?,
+                StaticFileProvider::read_write(sf_path)?
+                    .with_compression(config.static_files.compression),
             ),
             AccessRights::RO => (
                 Arc::new(open_db_read_only(&db_path, self.db.database_args())?),
@@ -84,7 +99,11 @@ impl EnvironmentArgs {
             ),
         };
 
-        let provider_factory = self.create_provider_factory(&config, db, sfp)?;
+        let provider_factory = if check_consistency {
+            self.create_provider_factory(&config, db, sfp)?
+        } else {
+            ProviderFactory::new(db, self.chain.clone(), sfp)
+        };
         if access.is_read_write() {
             debug!(target: "reth::cli", chain=%self.chain.chain, genesis=?self.chain.genesis_hash(), "Initializing genesis");
             init_genesis(provider_factory.clone())?;
@@ -119,37 +138,54 @@ impl EnvironmentArgs {
                 return Ok(factory)
             }
 
-            let prune_modes = config.prune.clone().map(|prune| prune.segments).unwrap_or_default();
-
             // Highly unlikely to happen, and given its destructive nature, it's better to panic
             // instead.
             assert_ne!(unwind_target, PipelineTarget::Unwind(0), "A static file <> database inconsistency was found that would trigger an unwind to block 0");
 
             info!(target: "reth::cli", unwind_target = %unwind_target, "Executing an unwind after a failed storage consistency check.");
 
-            let (_tip_tx, tip_rx) = watch::channel(B256::ZERO);
-
-            // Builds and executes an unwind-only pipeline
-            let mut pipeline = Pipeline::builder()
-                .add_stages(DefaultStages::new(
-                    factory.clone(),
-                    tip_rx,
-                    Arc::new(EthBeaconConsensus::new(self.chain.clone())),
-                    NoopHeaderDownloader::default(),
-                    NoopBodiesDownloader::default(),
-                    NoopBlockExecutorProvider::default(),
-                    config.stages.clone(),
-                    prune_modes.clone(),
-                ))
-                .build(factory.clone(), StaticFileProducer::new(factory.clone(), prune_modes));
-
-            // Move all applicable data from database to static files.
-            pipeline.move_to_static_files()?;
-            pipeline.unwind(unwind_target.unwind_target().expect("should exist"), None)?;
+            self.unwind_storage(
+                config,
+                factory.clone(),
+                unwind_target.unwind_target().expect("should exist"),
+            )?;
         }
 
         Ok(factory)
     }
+
+    /// Unwinds the database and static files to the given block, by building and executing an
+    /// unwind-only pipeline.
+    pub fn unwind_storage(
+        &self,
+        config: &Config,
+        factory: ProviderFactory<Arc<DatabaseEnv>>,
+        target: BlockNumber,
+    ) -> eyre::Result<()> {
+        let prune_modes = config.prune.clone().map(|prune| prune.segments).unwrap_or_default();
+
+        let (_tip_tx, tip_rx) = watch::channel(B256::ZERO);
+
+        // Builds and executes an unwind-only pipeline
+        let mut pipeline = Pipeline::builder()
+            .add_stages(DefaultStages::new(
+                factory.clone(),
+                tip_rx,
+                Arc::new(EthBeaconConsensus::new(self.chain.clone())),
+                NoopHeaderDownloader::default(),
+                NoopBodiesDownloader::default(),
+                NoopBlockExecutorProvider::default(),
+                config.stages.clone(),
+                prune_modes.clone(),
+            ))
+            .build(factory.clone(), StaticFileProducer::new(factory, prune_modes));
+
+        // Move all applicable data from database to static files.
+        pipeline.move_to_static_files()?;
+        pipeline.unwind(target, None)?;
+
+        Ok(())
+    }
 }
 
 /// Environment built from [`EnvironmentArgs`].
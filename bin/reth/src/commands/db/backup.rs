@@ -0,0 +1,36 @@
+use clap::Parser;
+use reth_db_api::database::{Database, DatabaseCopy};
+use reth_db_common::backup::backup;
+use reth_provider::ProviderFactory;
+use std::path::PathBuf;
+use tracing::info;
+
+/// The arguments for the `reth db backup` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The directory to write the backup to.
+    ///
+    /// The directory must not exist or be empty. The backup has the same layout as a datadir, so a
+    /// node can be started from it with `--datadir`.
+    dest: PathBuf,
+}
+
+impl Command {
+    /// Execute `db backup` command
+    pub fn execute<DB: Database + DatabaseCopy>(
+        self,
+        provider_factory: ProviderFactory<DB>,
+    ) -> eyre::Result<()> {
+        let manifest = backup(&provider_factory, &self.dest)?;
+        info!(
+            target: "reth::cli",
+            tip = manifest.tip,
+            tip_hash = ?manifest.tip_hash,
+            static_files = manifest.static_files.len(),
+            dest = ?self.dest,
+            "Backup written"
+        );
+
+        Ok(())
+    }
+}
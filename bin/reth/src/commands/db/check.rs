@@ -0,0 +1,100 @@
+use crate::commands::common::{Environment, EnvironmentArgs};
+use clap::Parser;
+use eyre::bail;
+use reth_db_common::check::{
+    check_consistency, reset_prune_checkpoints, truncate_static_files, ConsistencyReport,
+};
+use tracing::info;
+
+/// The arguments for the `reth db check` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Repairs the found inconsistencies.
+    ///
+    /// Static files that are ahead of the database are truncated, the node is unwound to the
+    /// highest consistent block, and prune checkpoints beyond the remaining data are reset.
+    #[arg(long, verbatim_doc_comment)]
+    repair: bool,
+}
+
+impl Command {
+    /// Returns `true` if the command repairs the storage, and thus requires read-write access.
+    pub const fn is_repair(&self) -> bool {
+        self.repair
+    }
+
+    /// Execute `db check` command
+    pub fn execute(self, env: &EnvironmentArgs, environment: Environment) -> eyre::Result<()> {
+        let Environment { config, provider_factory, .. } = environment;
+        let has_receipt_pruning =
+            config.prune.as_ref().map_or(false, |prune| prune.has_receipts_pruning());
+
+        let report = check_consistency(&provider_factory, has_receipt_pruning)?;
+        print_report(&report);
+
+        if !self.repair || report.is_consistent() {
+            return Ok(())
+        }
+
+        info!(target: "reth::cli", "Truncating static files ahead of the database");
+        truncate_static_files(&provider_factory, &report)?;
+
+        if let Some(unwind_target) = report.unwind_target() {
+            if unwind_target == 0 {
+                bail!(
+                    "Repairing the storage requires an unwind to block 0, resync the node instead"
+                )
+            }
+
+            info!(target: "reth::cli", unwind_target, "Unwinding to the highest consistent block");
+            env.unwind_storage(&config, provider_factory.clone(), unwind_target)?;
+        }
+
+        // Prune checkpoints are checked against the data that is left after the unwind
+        let report = check_consistency(&provider_factory, has_receipt_pruning)?;
+        reset_prune_checkpoints(&provider_factory, &report)?;
+
+        let report = check_consistency(&provider_factory, has_receipt_pruning)?;
+        println!();
+        print_report(&report);
+        if !report.is_consistent() {
+            bail!("Storage is still inconsistent after the repair")
+        }
+
+        Ok(())
+    }
+}
+
+fn print_report(report: &ConsistencyReport) {
+    println!("Static files:");
+    for height in &report.static_files {
+        let block = height.block.map_or_else(|| "-".to_string(), |block| block.to_string());
+        let tx = height.tx.map_or_else(|| "-".to_string(), |tx| tx.to_string());
+        println!("  {:<14} block {block:<12} tx {tx}", height.segment.to_string());
+    }
+
+    println!("Stage checkpoints:");
+    for (stage, block) in &report.checkpoints {
+        println!("  {:<24} {block}", stage.to_string());
+    }
+
+    println!("Prune checkpoints:");
+    for (segment, checkpoint) in &report.prune_checkpoints {
+        let block =
+            checkpoint.block_number.map_or_else(|| "-".to_string(), |block| block.to_string());
+        println!(
+            "  {:<24} block {block:<12} mode {:?}",
+            segment.to_string(),
+            checkpoint.prune_mode
+        );
+    }
+
+    if report.is_consistent() {
+        println!("No inconsistencies found");
+    } else {
+        println!("Inconsistencies:");
+        for inconsistency in &report.inconsistencies {
+            println!("  {inconsistency}");
+        }
+    }
+}
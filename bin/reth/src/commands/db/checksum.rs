@@ -4,23 +4,45 @@ use crate::{
 };
 use ahash::RandomState;
 use clap::Parser;
+use eyre::{bail, ensure, OptionExt};
 use reth_db::{DatabaseEnv, RawKey, RawTable, RawValue, TableViewer, Tables};
-use reth_db_api::{cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx};
+use reth_db_api::{
+    cursor::{DbCursorRO, RangeWalker},
+    database::Database,
+    table::Table,
+    transaction::DbTx,
+};
+use reth_primitives::{alloy_primitives::Keccak256, keccak256, StaticFileSegment, B256};
+use reth_provider::StaticFileProviderFactory;
+use serde::{Deserialize, Serialize};
 use std::{
     hash::{BuildHasher, Hasher},
+    ops::Bound,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
 use tracing::{info, warn};
 
+/// The default number of entries per chunk of a checksum tree.
+const DEFAULT_CHUNK_SIZE: usize = 100_000;
+
 #[derive(Parser, Debug)]
 /// The arguments for the `reth db checksum` command
 pub struct Command {
     /// The table name
-    table: Tables,
+    #[arg(required_unless_present_any = ["segment", "compare"])]
+    table: Option<Tables>,
+
+    /// The static file segment to checksum instead of a table. Always computes a checksum tree.
+    #[arg(long, conflicts_with = "table")]
+    segment: Option<StaticFileSegment>,
 
     /// The start of the range to checksum.
-    #[arg(long, value_parser = maybe_json_value_parser)]
+    ///
+    /// For a static file segment, this is the first block number for headers and changesets, and
+    /// the first transaction number otherwise.
+    #[arg(long, value_parser = maybe_json_value_parser, verbatim_doc_comment)]
     start_key: Option<String>,
 
     /// The end of the range to checksum.
@@ -31,18 +53,93 @@ pub struct Command {
     /// checksum.
     #[arg(long)]
     limit: Option<usize>,
+
+    /// Computes a Merkle tree over the checksums of consecutive chunks of entries, instead of a
+    /// single checksum of the range.
+    #[arg(long)]
+    tree: bool,
+
+    /// The number of entries per chunk of the checksum tree.
+    #[arg(long, default_value_t = DEFAULT_CHUNK_SIZE, value_parser = clap::value_parser!(u64).range(1..))]
+    chunk_size: u64,
+
+    /// Writes the checksum tree as JSON to the file, to compare it with the tree of another node.
+    #[arg(long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Compares the checksum tree with one written by `--output`, and bisects down to the first
+    /// chunk that differs.
+    ///
+    /// The local tree is computed for the table or segment, range and chunk size of the file.
+    /// If two files are given, they are compared with each other instead.
+    #[arg(long, value_name = "FILE", num_args = 1..=2, verbatim_doc_comment)]
+    compare: Vec<PathBuf>,
 }
 
 impl Command {
     /// Execute `db checksum` command
     pub fn execute(self, tool: &DbTool<Arc<DatabaseEnv>>) -> eyre::Result<()> {
         warn!("This command should be run without the node running!");
-        self.table.view(&ChecksumViewer {
-            tool,
+
+        match self.compare.as_slice() {
+            [] => {}
+            [other] => {
+                let other = ChecksumTree::load(other)?;
+                ensure!(
+                    self.table
+                        .map_or(true, |table| other.params.table.as_deref() == Some(table.name())) &&
+                        self.segment
+                            .map_or(true, |segment| other.params.segment == Some(segment)),
+                    "the checksum tree is of `{}`",
+                    other.params.source()
+                );
+                let local = other.params.compute(tool)?;
+                print_comparison(&local, &other, "local", "other");
+                return Ok(())
+            }
+            [left, right] => {
+                let (left_tree, right_tree) =
+                    (ChecksumTree::load(left)?, ChecksumTree::load(right)?);
+                ensure!(
+                    left_tree.params.source() == right_tree.params.source(),
+                    "the checksum trees are of different sources"
+                );
+                print_comparison(
+                    &left_tree,
+                    &right_tree,
+                    &left.display().to_string(),
+                    &right.display().to_string(),
+                );
+                return Ok(())
+            }
+            _ => unreachable!("clap allows at most two files"),
+        }
+
+        if !self.tree && self.output.is_none() && self.segment.is_none() {
+            let table = self.table.ok_or_eyre("a table is required")?;
+            table.view(&ChecksumViewer {
+                tool,
+                start_key: self.start_key,
+                end_key: self.end_key,
+                limit: self.limit,
+            })?;
+            return Ok(())
+        }
+
+        let params = TreeParams {
+            table: self.table.map(|table| table.name().to_string()),
+            segment: self.segment,
             start_key: self.start_key,
             end_key: self.end_key,
             limit: self.limit,
-        })?;
+            chunk_size: self.chunk_size as usize,
+        };
+        let tree = params.compute(tool)?;
+        if let Some(output) = self.output {
+            reth_fs_util::write(&output, serde_json::to_vec_pretty(&tree)?)?;
+            info!("Checksum tree written to {}", output.display());
+        }
+
         Ok(())
     }
 }
@@ -73,23 +170,11 @@ impl<DB: Database> TableViewer<(u64, Duration)> for ChecksumViewer<'_, DB> {
         );
 
         let mut cursor = tx.cursor_read::<RawTable<T>>()?;
-        let walker = match (self.start_key.as_deref(), self.end_key.as_deref()) {
-            (Some(start), Some(end)) => {
-                let start_key = table_key::<T>(start).map(RawKey::<T::Key>::new)?;
-                let end_key = table_key::<T>(end).map(RawKey::<T::Key>::new)?;
-                cursor.walk_range(start_key..=end_key)?
-            }
-            (None, Some(end)) => {
-                let end_key = table_key::<T>(end).map(RawKey::<T::Key>::new)?;
-
-                cursor.walk_range(..=end_key)?
-            }
-            (Some(start), None) => {
-                let start_key = table_key::<T>(start).map(RawKey::<T::Key>::new)?;
-                cursor.walk_range(start_key..)?
-            }
-            (None, None) => cursor.walk_range(..)?,
-        };
+        let walker = raw_range_walker::<T, _>(
+            &mut cursor,
+            self.start_key.as_deref(),
+            self.end_key.as_deref(),
+        )?;
 
         let start_time = Instant::now();
         let mut hasher = RandomState::with_seeds(1, 2, 3, 4).build_hasher();
@@ -133,3 +218,407 @@ impl<DB: Database> TableViewer<(u64, Duration)> for ChecksumViewer<'_, DB> {
         Ok((checksum, elapsed))
     }
 }
+
+/// Returns a walker over the raw entries of table `T` between the JSON encoded keys.
+fn raw_range_walker<'a, T: Table, C: DbCursorRO<RawTable<T>>>(
+    cursor: &'a mut C,
+    start_key: Option<&str>,
+    end_key: Option<&str>,
+) -> eyre::Result<RangeWalker<'a, RawTable<T>, C>> {
+    let bound = |key: Option<&str>| -> eyre::Result<_> {
+        Ok(match key {
+            Some(key) => Bound::Included(RawKey::<T::Key>::new(table_key::<T>(key)?)),
+            None => Bound::Unbounded,
+        })
+    };
+    Ok(cursor.walk_range((bound(start_key)?, bound(end_key)?))?)
+}
+
+/// The data a checksum tree is computed over.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct TreeParams {
+    table: Option<String>,
+    segment: Option<StaticFileSegment>,
+    start_key: Option<String>,
+    end_key: Option<String>,
+    limit: Option<usize>,
+    chunk_size: usize,
+}
+
+impl TreeParams {
+    /// Returns the name of the table or static file segment.
+    fn source(&self) -> String {
+        self.segment.map_or_else(
+            || self.table.clone().unwrap_or_default(),
+            |segment| format!("{segment} static files"),
+        )
+    }
+
+    /// Computes the checksum tree.
+    fn compute(&self, tool: &DbTool<Arc<DatabaseEnv>>) -> eyre::Result<ChecksumTree> {
+        info!(
+            "Start computing checksum tree of `{}`, start={:?}, end={:?}, limit={:?}, chunk_size={}",
+            self.source(),
+            self.start_key,
+            self.end_key,
+            self.limit,
+            self.chunk_size
+        );
+        let start_time = Instant::now();
+
+        let chunks = if let Some(segment) = self.segment {
+            segment_chunks(tool, segment, self)?
+        } else {
+            let table = self
+                .table
+                .as_deref()
+                .ok_or_eyre("a table or a static file segment is required")?
+                .parse::<Tables>()
+                .map_err(|err| eyre::eyre!(err))?;
+            table.view(&ChecksumTreeViewer { tool, params: self })?
+        };
+
+        let tree = ChecksumTree::new(self.clone(), chunks);
+        info!(
+            "Checksum tree of `{}`: root {} over {} chunks (elapsed: {:?})",
+            self.source(),
+            tree.root,
+            tree.chunks.len(),
+            start_time.elapsed()
+        );
+        Ok(tree)
+    }
+}
+
+/// Merkle tree over the checksums of consecutive chunks of entries of a table or static file
+/// segment.
+///
+/// Two trees that are computed over the same range and chunk size can be compared by descending
+/// from the root into the first child that differs, which finds the first differing chunk without
+/// comparing every chunk.
+#[derive(Debug, Serialize, Deserialize)]
+struct ChecksumTree {
+    #[serde(flatten)]
+    params: TreeParams,
+    root: B256,
+    chunks: Vec<Chunk>,
+}
+
+impl ChecksumTree {
+    fn new(params: TreeParams, chunks: Vec<Chunk>) -> Self {
+        let root = merkle_root(&merkle_levels(chunks.iter().map(|chunk| chunk.hash).collect()));
+        Self { params, root, chunks }
+    }
+
+    /// Loads a checksum tree that was written by `--output`.
+    fn load(path: &Path) -> eyre::Result<Self> {
+        let tree: Self = serde_json::from_slice(&reth_fs_util::read(path)?)?;
+        ensure!(
+            merkle_root(&tree.levels()) == tree.root,
+            "the root of checksum tree {} doesn't match its chunks",
+            path.display()
+        );
+        Ok(tree)
+    }
+
+    fn levels(&self) -> Vec<Vec<B256>> {
+        merkle_levels(self.chunks.iter().map(|chunk| chunk.hash).collect())
+    }
+}
+
+/// Checksum of consecutive entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Chunk {
+    /// The first key of the chunk.
+    start_key: serde_json::Value,
+    /// The last key of the chunk.
+    end_key: serde_json::Value,
+    /// The number of entries in the chunk.
+    entries: usize,
+    /// Keccak-256 hash of the length-prefixed keys and values of the entries.
+    hash: B256,
+}
+
+/// Splits consecutive entries into chunks and hashes every chunk.
+struct ChunkHasher<F> {
+    chunk_size: usize,
+    /// Decodes a raw key into JSON, for the keys that bound a chunk.
+    decode_key: F,
+    chunks: Vec<Chunk>,
+    current: Option<(Keccak256, Vec<u8>, Vec<u8>, usize)>,
+    entries: usize,
+}
+
+impl<F> ChunkHasher<F>
+where
+    F: Fn(&[u8]) -> eyre::Result<serde_json::Value>,
+{
+    const fn new(chunk_size: usize, decode_key: F) -> Self {
+        Self { chunk_size, decode_key, chunks: Vec::new(), current: None, entries: 0 }
+    }
+
+    /// Returns the number of hashed entries.
+    const fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// Adds an entry, made of a key and one or more values.
+    fn push(&mut self, key: &[u8], values: &[&[u8]]) -> eyre::Result<()> {
+        let (hasher, _, end_key, entries) =
+            self.current.get_or_insert_with(|| (Keccak256::new(), key.to_vec(), Vec::new(), 0));
+        for data in std::iter::once(key).chain(values.iter().copied()) {
+            hasher.update((data.len() as u64).to_be_bytes());
+            hasher.update(data);
+        }
+        end_key.clear();
+        end_key.extend_from_slice(key);
+        *entries += 1;
+        let is_full = *entries == self.chunk_size;
+
+        self.entries += 1;
+        if self.entries % 100_000 == 0 {
+            info!("Hashed {} entries.", self.entries);
+        }
+
+        if is_full {
+            self.finish_chunk()?;
+        }
+        Ok(())
+    }
+
+    fn finish_chunk(&mut self) -> eyre::Result<()> {
+        if let Some((hasher, start_key, end_key, entries)) = self.current.take() {
+            self.chunks.push(Chunk {
+                start_key: (self.decode_key)(&start_key)?,
+                end_key: (self.decode_key)(&end_key)?,
+                entries,
+                hash: hasher.finalize(),
+            });
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> eyre::Result<Vec<Chunk>> {
+        self.finish_chunk()?;
+        info!("Hashed {} entries.", self.entries);
+        Ok(self.chunks)
+    }
+}
+
+struct ChecksumTreeViewer<'a, DB: Database> {
+    tool: &'a DbTool<DB>,
+    params: &'a TreeParams,
+}
+
+impl<DB: Database> TableViewer<Vec<Chunk>> for ChecksumTreeViewer<'_, DB> {
+    type Error = eyre::Report;
+
+    fn view<T: Table>(&self) -> Result<Vec<Chunk>, Self::Error> {
+        let provider =
+            self.tool.provider_factory.provider()?.disable_long_read_transaction_safety();
+        let mut cursor = provider.tx_ref().cursor_read::<RawTable<T>>()?;
+        let walker = raw_range_walker::<T, _>(
+            &mut cursor,
+            self.params.start_key.as_deref(),
+            self.params.end_key.as_deref(),
+        )?;
+
+        let mut hasher = ChunkHasher::new(self.params.chunk_size, |key: &[u8]| {
+            Ok(serde_json::to_value(RawKey::<T::Key>::from_vec(key.to_vec()).key()?)?)
+        });
+        for entry in walker.take(self.params.limit.unwrap_or(usize::MAX)) {
+            let (key, value) = entry?;
+            hasher.push(key.raw_key(), &[value.raw_value()])?;
+        }
+        hasher.finish()
+    }
+}
+
+/// Hashes the rows of a static file segment in chunks. The key of a row is its block number for
+/// headers, and its transaction number otherwise.
+fn segment_chunks(
+    tool: &DbTool<Arc<DatabaseEnv>>,
+    segment: StaticFileSegment,
+    params: &TreeParams,
+) -> eyre::Result<Vec<Chunk>> {
+    let parse_number = |key: Option<&str>| key.map(serde_json::from_str::<u64>).transpose();
+    let start = parse_number(params.start_key.as_deref())?;
+    let end = parse_number(params.end_key.as_deref())?;
+    let limit = params.limit.unwrap_or(usize::MAX);
+
+    let mut hasher = ChunkHasher::new(params.chunk_size, |key: &[u8]| {
+        Ok(serde_json::Value::from(u64::from_be_bytes(key.try_into()?)))
+    });
+
+    let static_file_provider = tool.provider_factory.static_file_provider();
+    let highest = if segment.is_block_based() {
+        static_file_provider.get_highest_static_file_block(segment)
+    } else {
+        static_file_provider.get_highest_static_file_tx(segment)
+    };
+    let Some(highest) = highest else { return hasher.finish() };
+    let end = end.map_or(highest, |end| end.min(highest));
+
+    let mut number = start.unwrap_or_default();
+    while number <= end && hasher.entries() < limit {
+        let jar = if segment.is_block_based() {
+            static_file_provider.get_segment_provider_from_block(segment, number, None)?
+        } else {
+            static_file_provider.get_segment_provider_from_transaction(segment, number, None)?
+        };
+        let jar_start = jar.user_header().start().unwrap_or(number);
+        let mut cursor = jar.cursor()?;
+
+        let first = number;
+        let mut row = cursor.row_by_number(number.saturating_sub(jar_start) as usize)?;
+        while let Some(columns) = row {
+            hasher.push(&number.to_be_bytes(), &columns)?;
+            number += 1;
+            if number > end || hasher.entries() >= limit {
+                break
+            }
+            row = cursor.next_row()?;
+        }
+
+        if number == first {
+            bail!("static file of {segment} doesn't contain row {number}")
+        }
+    }
+
+    hasher.finish()
+}
+
+/// Returns the levels of the Merkle tree over the leaves, from the leaves up to the root.
+///
+/// A node without a sibling is moved up to the next level unchanged, so the node at `index` of a
+/// level covers the same leaves in every tree, regardless of the number of leaves.
+fn merkle_levels(leaves: Vec<B256>) -> Vec<Vec<B256>> {
+    let mut levels = vec![leaves];
+    while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+        let parents = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => keccak256([left.as_slice(), right.as_slice()].concat()),
+                _ => pair[0],
+            })
+            .collect();
+        levels.push(parents);
+    }
+    levels
+}
+
+fn merkle_root(levels: &[Vec<B256>]) -> B256 {
+    levels.last().and_then(|level| level.first()).copied().unwrap_or_default()
+}
+
+/// Returns the node at `index` of `level`, where the levels above the root only contain the root.
+fn merkle_node(levels: &[Vec<B256>], level: usize, index: usize) -> Option<B256> {
+    match levels.get(level) {
+        Some(nodes) => nodes.get(index).copied(),
+        None => (index == 0).then(|| merkle_root(levels)),
+    }
+}
+
+/// Bisects two Merkle trees down to the index of the first leaf that differs.
+fn first_difference(left: &[Vec<B256>], right: &[Vec<B256>]) -> Option<usize> {
+    let mut level = left.len().max(right.len()).saturating_sub(1);
+    if merkle_node(left, level, 0) == merkle_node(right, level, 0) {
+        return None
+    }
+
+    let mut index = 0;
+    while level > 0 {
+        level -= 1;
+        index *= 2;
+        if merkle_node(left, level, index) == merkle_node(right, level, index) {
+            index += 1;
+        }
+    }
+    Some(index)
+}
+
+fn print_comparison(left: &ChecksumTree, right: &ChecksumTree, left_name: &str, right_name: &str) {
+    for (name, tree) in [(left_name, left), (right_name, right)] {
+        println!("{name}: root {} over {} chunks", tree.root, tree.chunks.len());
+    }
+
+    let Some(index) = first_difference(&left.levels(), &right.levels()) else {
+        println!("The checksum trees of `{}` match", left.params.source());
+        return
+    };
+
+    println!("First differing chunk of `{}`: #{index}", left.params.source());
+    for (name, tree) in [(left_name, left), (right_name, right)] {
+        match tree.chunks.get(index) {
+            Some(chunk) => println!(
+                "  {name}: keys {} ..= {}, {} entries, hash {}",
+                chunk.start_key, chunk.end_key, chunk.entries, chunk.hash
+            ),
+            None => println!("  {name}: no more entries"),
+        }
+    }
+
+    // The chunks before the differing one are equal, so the first difference is within the
+    // `chunk_size` entries after the end of the previous chunk.
+    let chunk_size = left.params.chunk_size;
+    if chunk_size > 1 {
+        let start_key = index
+            .checked_sub(1)
+            .and_then(|previous| left.chunks.get(previous))
+            .map(|chunk| format!(" --start-key '{}'", chunk.end_key))
+            .unwrap_or_default();
+        println!(
+            "Narrow it down by comparing the trees of both nodes computed with:{start_key} --limit {} --chunk-size {}",
+            chunk_size + 1,
+            (chunk_size / 16).max(1)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn levels(leaves: &[u8]) -> Vec<Vec<B256>> {
+        merkle_levels(leaves.iter().map(|leaf| B256::with_last_byte(*leaf)).collect())
+    }
+
+    #[test]
+    fn merkle_first_difference() {
+        let leaves = (0..9).collect::<Vec<_>>();
+        assert_eq!(first_difference(&levels(&leaves), &levels(&leaves)), None);
+        assert_eq!(first_difference(&levels(&[]), &levels(&[])), None);
+
+        for changed in 0..leaves.len() {
+            let mut other = leaves.clone();
+            other[changed] = 100;
+            other[leaves.len() - 1] = 101;
+            assert_eq!(first_difference(&levels(&leaves), &levels(&other)), Some(changed));
+        }
+
+        // missing and extra chunks
+        assert_eq!(first_difference(&levels(&leaves), &levels(&leaves[..5])), Some(5));
+        assert_eq!(first_difference(&levels(&leaves[..1]), &levels(&leaves)), Some(1));
+        assert_eq!(first_difference(&levels(&[]), &levels(&leaves)), Some(0));
+    }
+
+    #[test]
+    fn chunk_hasher() {
+        let decode = |key: &[u8]| Ok(serde_json::Value::from(key[0]));
+        let mut hasher = ChunkHasher::new(2, decode);
+        for key in 0..5u8 {
+            hasher.push(&[key], &[b"value"]).unwrap();
+        }
+        let chunks = hasher.finish().unwrap();
+        assert_eq!(
+            chunks.iter().map(|chunk| (chunk.start_key.clone(), chunk.entries)).collect::<Vec<_>>(),
+            vec![(0.into(), 2), (2.into(), 2), (4.into(), 1)]
+        );
+
+        // the boundaries between keys and values are part of the hash
+        let mut hasher = ChunkHasher::new(2, decode);
+        hasher.push(&[0], &[b"valu", b"e"]).unwrap();
+        hasher.push(&[1], &[b"value"]).unwrap();
+        assert_ne!(hasher.finish().unwrap()[0].hash, chunks[0].hash);
+    }
+}
@@ -0,0 +1,51 @@
+use clap::Parser;
+use human_bytes::human_bytes;
+use reth_db_api::database::{Database, DatabaseCompact};
+use reth_provider::ProviderFactory;
+use std::time::Duration;
+use tracing::info;
+
+/// The arguments for the `reth db compact` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// Only compacts the database if at least this share of the database file is free, in
+    /// percent.
+    #[arg(long, value_name = "PERCENT", default_value_t = 0)]
+    min_free_percent: u8,
+}
+
+impl Command {
+    /// Execute `db compact` command
+    pub fn execute<DB: Database + DatabaseCompact>(
+        self,
+        provider_factory: ProviderFactory<DB>,
+    ) -> eyre::Result<()> {
+        let db = provider_factory.db_ref();
+
+        let free_space = db.free_space()?;
+        info!(
+            target: "reth::cli",
+            free = human_bytes(free_space.free as f64),
+            total = human_bytes(free_space.total as f64),
+            percent = free_space.percent(),
+            "Database free space"
+        );
+        if free_space.percent() < self.min_free_percent {
+            info!(target: "reth::cli", min_free_percent = self.min_free_percent, "Not enough free space, skipping compaction");
+            return Ok(())
+        }
+
+        // The swap is refused if other processes have the database open, so there are no open
+        // transactions to wait for.
+        let outcome = db.compact(Duration::from_secs(10))?;
+        info!(
+            target: "reth::cli",
+            size_before = human_bytes(outcome.size_before as f64),
+            size_after = human_bytes(outcome.size_after as f64),
+            reclaimed = human_bytes(outcome.reclaimed() as f64),
+            "Database compacted"
+        );
+
+        Ok(())
+    }
+}
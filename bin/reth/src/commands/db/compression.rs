@@ -0,0 +1,297 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use comfy_table::{Cell, Row, Table as ComfyTable};
+use eyre::{ensure, OptionExt};
+use human_bytes::human_bytes;
+use reth_db::{static_file::iter_static_files, DatabaseEnv};
+use reth_nippy_jar::{
+    compression::Zstd, ColumnResult, ConsistencyFailStrategy, NippyJar, NippyJarCursor,
+    NippyJarWriter,
+};
+use reth_primitives::{
+    static_file::{find_fixed_range, Compression, SegmentHeader},
+    BlockNumber, StaticFileSegment,
+};
+use reth_provider::{providers::StaticFileProvider, ProviderFactory, StaticFileProviderFactory};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+#[derive(Subcommand, Debug)]
+/// `reth db compression` subcommands
+pub enum Subcommands {
+    /// Trains zstd dictionaries for a static file segment from its existing static files.
+    ///
+    /// Static files created afterwards use them if the segment is configured with the
+    /// `zstd-with-dictionary` compression.
+    Train(TrainCommand),
+    /// Compares the size and decoding speed of existing static files when compressed with each
+    /// compression.
+    Benchmark(BenchmarkCommand),
+}
+
+/// The static files of a segment over a block range.
+#[derive(Parser, Debug)]
+pub struct SegmentArgs {
+    /// The static file segment.
+    #[arg(long, value_enum)]
+    segment: StaticFileSegment,
+
+    /// The first block of the static files to read.
+    #[arg(long, value_name = "BLOCK", default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block of the static files to read.
+    ///
+    /// Defaults to the highest block of the segment.
+    #[arg(long, value_name = "BLOCK", verbatim_doc_comment)]
+    to: Option<BlockNumber>,
+}
+
+impl SegmentArgs {
+    /// Returns the paths of the static files of the segment that overlap with the block range.
+    fn static_files(
+        &self,
+        static_file_provider: &StaticFileProvider,
+    ) -> eyre::Result<Vec<PathBuf>> {
+        let to = self.to.unwrap_or(BlockNumber::MAX);
+        ensure!(self.from <= to, "--from must not be greater than --to");
+
+        let static_files = iter_static_files(static_file_provider.directory())?;
+        let paths = static_files
+            .get(&self.segment)
+            .into_iter()
+            .flatten()
+            .filter(|(block_range, _)| block_range.start() <= to && block_range.end() >= self.from)
+            .map(|(block_range, _)| {
+                static_file_provider
+                    .directory()
+                    .join(self.segment.filename(&find_fixed_range(block_range.start())))
+            })
+            .collect::<Vec<_>>();
+
+        ensure!(!paths.is_empty(), "no {} static files in the block range", self.segment);
+        Ok(paths)
+    }
+}
+
+/// The arguments for the `reth db compression train` command
+#[derive(Parser, Debug)]
+pub struct TrainCommand {
+    #[command(flatten)]
+    segment: SegmentArgs,
+
+    /// The maximum number of rows sampled to train the dictionaries.
+    ///
+    /// Rows are sampled evenly across the static files of the block range.
+    #[arg(long, default_value_t = 100_000, verbatim_doc_comment)]
+    samples: usize,
+
+    /// The maximum size in bytes of each column dictionary.
+    #[arg(long, value_name = "BYTES", default_value_t = 112_640)]
+    max_dict_size: usize,
+}
+
+impl TrainCommand {
+    /// Execute `db compression train` command
+    pub fn execute(self, provider_factory: ProviderFactory<Arc<DatabaseEnv>>) -> eyre::Result<()> {
+        let static_file_provider = provider_factory.static_file_provider();
+        let segment = self.segment.segment;
+
+        let jars = self
+            .segment
+            .static_files(&static_file_provider)?
+            .into_iter()
+            .map(|path| NippyJar::<SegmentHeader>::load(&path))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total_rows = jars.iter().map(|jar| jar.rows()).sum::<usize>();
+        ensure!(total_rows > 0, "no {segment} rows to sample");
+        let step = (total_rows / self.samples.max(1)).max(1);
+
+        info!(target: "reth::cli", ?segment, total_rows, step, "Sampling rows");
+
+        // Rows are numbered across all static files, so that the sampling step carries over
+        // from one static file to the next.
+        let mut samples = vec![Vec::new(); segment.columns()];
+        let mut next_sample = 0;
+        let mut offset = 0;
+        for jar in &jars {
+            let mut cursor = NippyJarCursor::new(jar)?;
+            while next_sample < offset + jar.rows() {
+                let row = cursor
+                    .row_by_number(next_sample - offset)?
+                    .ok_or_eyre("sampled row does not exist")?;
+                for (column, value) in row.into_iter().enumerate() {
+                    samples[column].push(value.to_vec());
+                }
+                next_sample += step;
+            }
+            offset += jar.rows();
+        }
+
+        let dictionaries = samples
+            .into_iter()
+            .enumerate()
+            .map(|(column, samples)| {
+                info!(target: "reth::cli", ?segment, column, samples = samples.len(), "Training dictionary");
+                Zstd::train_dictionary(samples, self.max_dict_size)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        static_file_provider.write_dictionaries(segment, &dictionaries)?;
+
+        for (column, dictionary) in dictionaries.iter().enumerate() {
+            info!(
+                target: "reth::cli",
+                ?segment,
+                column,
+                size = dictionary.len(),
+                path = ?static_file_provider.dictionary_path(segment, column),
+                "Wrote dictionary"
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The arguments for the `reth db compression benchmark` command
+#[derive(Parser, Debug)]
+pub struct BenchmarkCommand {
+    #[command(flatten)]
+    segment: SegmentArgs,
+
+    /// The compressions to compare.
+    ///
+    /// `zstd-with-dictionary` requires the dictionaries of the segment to be trained with
+    /// `reth db compression train`.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_values = ["uncompressed", "lz4", "zstd", "zstd-with-dictionary"],
+        verbatim_doc_comment
+    )]
+    compression: Vec<Compression>,
+}
+
+impl BenchmarkCommand {
+    /// Execute `db compression benchmark` command
+    pub fn execute(self, provider_factory: ProviderFactory<Arc<DatabaseEnv>>) -> eyre::Result<()> {
+        let static_file_provider = provider_factory.static_file_provider();
+        let segment = self.segment.segment;
+        let paths = self.segment.static_files(&static_file_provider)?;
+
+        let mut table = ComfyTable::new();
+        table.load_preset(comfy_table::presets::ASCII_MARKDOWN);
+        table.set_header(["Compression", "Size", "Ratio", "Encode Time", "Decode Time"]);
+
+        let mut current = Report::default();
+        let jars = paths
+            .iter()
+            .map(|path| {
+                let jar = NippyJar::<SegmentHeader>::load(path)?;
+                current.size += reth_fs_util::metadata(jar.data_path())?.len();
+                current.decode_time += decode(&jar)?;
+                Ok(jar)
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        table.add_row(current.row("current", current.size));
+
+        for compression in self.compression {
+            let dictionaries = if compression == Compression::ZstdWithDictionary {
+                let Some(dictionaries) = static_file_provider.dictionaries(segment)? else {
+                    warn!(target: "reth::cli", ?segment, "No trained zstd dictionaries found, skipping zstd-with-dictionary");
+                    continue
+                };
+                Some(dictionaries)
+            } else {
+                None
+            };
+
+            info!(target: "reth::cli", ?segment, ?compression, "Benchmarking");
+
+            let dir = tempfile::tempdir()?;
+            let mut report = Report::default();
+            for jar in &jars {
+                let path = dir.path().join(
+                    jar.data_path().file_name().ok_or_eyre("static file path has no file name")?,
+                );
+                let mut new_jar =
+                    NippyJar::new(segment.columns(), &path, jar.user_header().clone());
+                new_jar = match compression {
+                    Compression::Lz4 => new_jar.with_lz4(),
+                    Compression::Zstd => new_jar.with_zstd(false, 0),
+                    Compression::ZstdWithDictionary => {
+                        new_jar.with_zstd_dictionaries(dictionaries.clone().unwrap_or_default())
+                    }
+                    Compression::Uncompressed => new_jar,
+                };
+
+                let start = Instant::now();
+                let new_jar = encode(jar, new_jar)?;
+                report.encode_time += start.elapsed();
+                report.size += reth_fs_util::metadata(new_jar.data_path())?.len();
+                report.decode_time += decode(&new_jar)?;
+            }
+
+            let name = compression.to_possible_value().expect("no skipped variants");
+            table.add_row(report.row(name.get_name(), current.size));
+        }
+
+        println!("{table}");
+
+        Ok(())
+    }
+}
+
+/// Sizes and timings of the static files compressed with a compression.
+#[derive(Debug, Default)]
+struct Report {
+    size: u64,
+    encode_time: Duration,
+    decode_time: Duration,
+}
+
+impl Report {
+    fn row(&self, name: &str, current_size: u64) -> Row {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(name))
+            .add_cell(Cell::new(human_bytes(self.size as f64)))
+            .add_cell(Cell::new(format!("{:.3}", self.size as f64 / current_size.max(1) as f64)))
+            .add_cell(Cell::new(if self.encode_time.is_zero() {
+                "N/A".to_string()
+            } else {
+                format!("{:?}", self.encode_time)
+            }))
+            .add_cell(Cell::new(format!("{:?}", self.decode_time)));
+        row
+    }
+}
+
+/// Writes all rows of `jar` into `new_jar`.
+fn encode(
+    jar: &NippyJar<SegmentHeader>,
+    new_jar: NippyJar<SegmentHeader>,
+) -> eyre::Result<NippyJar<SegmentHeader>> {
+    let mut writer = NippyJarWriter::new(new_jar, ConsistencyFailStrategy::Heal)?;
+    let mut cursor = NippyJarCursor::new(jar)?;
+    while let Some(row) = cursor.next_row()? {
+        for value in row {
+            writer.append_column(Some(ColumnResult::Ok(value)))?;
+        }
+    }
+    writer.commit()?;
+    Ok(writer.into_jar())
+}
+
+/// Reads all rows of `jar`, returning the time it took.
+fn decode(jar: &NippyJar<SegmentHeader>) -> eyre::Result<Duration> {
+    let start = Instant::now();
+    let mut cursor = NippyJarCursor::new(jar)?;
+    while cursor.next_row()?.is_some() {}
+    Ok(start.elapsed())
+}
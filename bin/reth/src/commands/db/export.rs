@@ -0,0 +1,890 @@
+use arrow_json::reader::Decoder;
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use clap::{Parser, ValueEnum};
+use eyre::{bail, OptionExt};
+use parquet::{
+    arrow::ArrowWriter,
+    basic::{Compression, ZstdLevel},
+    file::properties::WriterProperties,
+};
+use reth_db::{tables, DatabaseEnv, Tables};
+use reth_db_api::{
+    cursor::DbCursorRO, database::Database, models::BlockNumberAddress, transaction::DbTx,
+};
+use reth_primitives::{Address, BlockNumber, Bytes, StaticFileSegment, TxNumber, B256};
+use reth_provider::{
+    BlockReader, DatabaseProvider, HeaderProvider, ProviderFactory, ReceiptProvider,
+    StageCheckpointReader, TransactionsProvider,
+};
+use reth_stages::StageId;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tracing::info;
+
+/// The number of blocks that are read with a single database transaction.
+///
+/// Read transactions are kept short, so that the export doesn't prevent a running node from
+/// reusing freed database pages.
+const BLOCKS_PER_READ: u64 = 1_000;
+
+/// The maximum number of rows of a Parquet record batch.
+const ROWS_PER_BATCH: usize = 8_192;
+
+/// The tables that can be exported, all of them are keyed by block or transaction.
+const EXPORTABLE_TABLES: [Tables; 4] = [
+    Tables::TransactionSenders,
+    Tables::AccountChangeSets,
+    Tables::StorageChangeSets,
+    Tables::BlockWithdrawals,
+];
+
+/// The arguments for the `reth db export` command
+#[derive(Parser, Debug)]
+pub struct Command {
+    /// The static file segments to export.
+    #[arg(long, value_delimiter = ',')]
+    segments: Vec<StaticFileSegment>,
+
+    /// The tables to export.
+    ///
+    /// Supported tables are `TransactionSenders`, `AccountChangeSets`, `StorageChangeSets` and
+    /// `BlockWithdrawals`.
+    ///
+    /// The rows of `TransactionSenders`, `AccountChangeSets` and `StorageChangeSets` that were
+    /// moved to static files are exported too, like with the segments of the same name.
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    tables: Vec<Tables>,
+
+    /// Exports the logs of the receipts, one row per log.
+    #[arg(long)]
+    logs: bool,
+
+    /// The first block to export.
+    #[arg(long, value_name = "BLOCK", default_value_t = 0)]
+    from: BlockNumber,
+
+    /// The last block to export.
+    ///
+    /// Defaults to the highest fully synced block.
+    #[arg(long, value_name = "BLOCK", verbatim_doc_comment)]
+    to: Option<BlockNumber>,
+
+    /// The format of the exported files.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Parquet)]
+    format: ExportFormat,
+
+    /// The number of blocks per exported file.
+    #[arg(long, value_name = "BLOCKS", default_value_t = 100_000)]
+    blocks_per_file: u64,
+
+    /// The directory to write the exported files to.
+    #[arg(value_name = "EXPORT_DIR")]
+    dir: PathBuf,
+}
+
+/// The format of the exported files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Zstd compressed Apache Parquet.
+    Parquet,
+    /// Newline-delimited JSON, one object per row.
+    Ndjson,
+}
+
+impl ExportFormat {
+    const fn extension(&self) -> &'static str {
+        match self {
+            Self::Parquet => "parquet",
+            Self::Ndjson => "ndjson",
+        }
+    }
+}
+
+impl Command {
+    /// Execute `db export` command
+    pub fn execute(self, provider_factory: ProviderFactory<Arc<DatabaseEnv>>) -> eyre::Result<()> {
+        let mut datasets = self.segments.iter().copied().map(Dataset::Segment).collect::<Vec<_>>();
+        for table in &self.tables {
+            if !EXPORTABLE_TABLES.contains(table) {
+                bail!("table {table} can't be exported, supported tables are {EXPORTABLE_TABLES:?}")
+            }
+            datasets.push(Dataset::Table(*table));
+        }
+        if self.logs {
+            datasets.push(Dataset::Logs);
+        }
+        if datasets.is_empty() {
+            bail!("nothing to export, pass at least one of --segments, --tables or --logs")
+        }
+        if self.blocks_per_file == 0 {
+            bail!("--blocks-per-file must be greater than zero")
+        }
+
+        let to = match self.to {
+            Some(to) => to,
+            None => {
+                provider_factory
+                    .provider()?
+                    .get_stage_checkpoint(StageId::Finish)?
+                    .unwrap_or_default()
+                    .block_number
+            }
+        };
+        if to < self.from {
+            bail!("last block {to} is before the first block {}", self.from)
+        }
+
+        reth_fs_util::create_dir_all(&self.dir)?;
+
+        let mut start = self.from;
+        loop {
+            let end = start.saturating_add(self.blocks_per_file - 1).min(to);
+            for dataset in &datasets {
+                let path = self.dir.join(format!(
+                    "{}-{start}-{end}.{}",
+                    dataset.name(),
+                    self.format.extension()
+                ));
+                let mut writer = DatasetWriter::new(&path, self.format, dataset.schema())?;
+                let rows = export_dataset(&provider_factory, *dataset, start..=end, &mut writer)?;
+                writer.finish()?;
+                info!(target: "reth::cli", ?path, rows, "Exported file");
+            }
+
+            if end == to {
+                break
+            }
+            start = end + 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// A set of rows that can be exported.
+#[derive(Debug, Clone, Copy)]
+enum Dataset {
+    Segment(StaticFileSegment),
+    Table(Tables),
+    Logs,
+}
+
+impl Dataset {
+    fn name(&self) -> String {
+        match self {
+            Self::Segment(segment) => segment.as_str().to_string(),
+            Self::Table(table) => table.name().to_string(),
+            Self::Logs => "logs".to_string(),
+        }
+    }
+
+    /// Returns the columns of the rows of the dataset, matching the serialized fields of the row
+    /// types below.
+    fn schema(&self) -> SchemaRef {
+        let u64 = |name| Field::new(name, DataType::UInt64, false);
+        let u64_opt = |name| Field::new(name, DataType::UInt64, true);
+        let str = |name| Field::new(name, DataType::Utf8, false);
+        let str_opt = |name| Field::new(name, DataType::Utf8, true);
+
+        let fields = match self {
+            Self::Segment(StaticFileSegment::Headers) => vec![
+                u64("number"),
+                str("hash"),
+                str("parent_hash"),
+                str("miner"),
+                str("state_root"),
+                str("transactions_root"),
+                str("receipts_root"),
+                u64("timestamp"),
+                u64("gas_limit"),
+                u64("gas_used"),
+                u64_opt("base_fee_per_gas"),
+                u64_opt("blob_gas_used"),
+                u64_opt("excess_blob_gas"),
+                str("difficulty"),
+                str("total_difficulty"),
+                str("extra_data"),
+            ],
+            Self::Segment(StaticFileSegment::Transactions) => vec![
+                u64("block_number"),
+                u64("tx_number"),
+                str("hash"),
+                Field::new("tx_type", DataType::UInt8, false),
+                u64_opt("chain_id"),
+                u64("nonce"),
+                str_opt("to"),
+                str("value"),
+                u64("gas_limit"),
+                str("max_fee_per_gas"),
+                str_opt("max_priority_fee_per_gas"),
+                str("input"),
+            ],
+            Self::Segment(StaticFileSegment::Receipts) => vec![
+                u64("block_number"),
+                u64("tx_number"),
+                Field::new("tx_type", DataType::UInt8, false),
+                Field::new("success", DataType::Boolean, false),
+                u64("cumulative_gas_used"),
+                u64("logs"),
+            ],
+            Self::Logs => vec![
+                u64("block_number"),
+                u64("tx_number"),
+                u64("log_index"),
+                str("address"),
+                str_opt("topic0"),
+                str_opt("topic1"),
+                str_opt("topic2"),
+                str_opt("topic3"),
+                str("data"),
+            ],
+            Self::Segment(StaticFileSegment::TransactionSenders) |
+            Self::Table(Tables::TransactionSenders) => {
+                vec![u64("block_number"), u64("tx_number"), str("sender")]
+            }
+            Self::Segment(StaticFileSegment::AccountChangeSets) |
+            Self::Table(Tables::AccountChangeSets) => vec![
+                u64("block_number"),
+                str("address"),
+                u64_opt("nonce"),
+                str_opt("balance"),
+                str_opt("bytecode_hash"),
+            ],
+            Self::Segment(StaticFileSegment::StorageChangeSets) |
+            Self::Table(Tables::StorageChangeSets) => {
+                vec![u64("block_number"), str("address"), str("slot"), str("value")]
+            }
+            Self::Table(Tables::BlockWithdrawals) => vec![
+                u64("block_number"),
+                u64("index"),
+                u64("validator_index"),
+                str("address"),
+                u64("amount"),
+            ],
+            Self::Table(table) => unreachable!("table {table} is not exportable"),
+        };
+
+        Arc::new(Schema::new(fields))
+    }
+}
+
+/// Writes the rows of a dataset into a single file.
+enum DatasetWriter {
+    Ndjson(BufWriter<File>),
+    Parquet { decoder: Decoder, writer: ArrowWriter<File> },
+}
+
+impl DatasetWriter {
+    fn new(path: &Path, format: ExportFormat, schema: SchemaRef) -> eyre::Result<Self> {
+        let file = reth_fs_util::create_file(path)?;
+        Ok(match format {
+            ExportFormat::Ndjson => Self::Ndjson(BufWriter::new(file)),
+            ExportFormat::Parquet => {
+                let decoder = arrow_json::ReaderBuilder::new(schema.clone())
+                    .with_batch_size(ROWS_PER_BATCH)
+                    .build_decoder()?;
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::ZSTD(ZstdLevel::default()))
+                    .build();
+                let writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+                Self::Parquet { decoder, writer }
+            }
+        })
+    }
+
+    fn write<R: Serialize>(&mut self, rows: &[R]) -> eyre::Result<()> {
+        match self {
+            Self::Ndjson(writer) => {
+                for row in rows {
+                    serde_json::to_writer(&mut *writer, row)?;
+                    writer.write_all(b"\n")?;
+                }
+            }
+            Self::Parquet { decoder, writer } => {
+                for rows in rows.chunks(ROWS_PER_BATCH) {
+                    decoder.serialize(rows)?;
+                    if let Some(batch) = decoder.flush()? {
+                        writer.write(&batch)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> eyre::Result<()> {
+        match self {
+            Self::Ndjson(mut writer) => writer.flush()?,
+            Self::Parquet { writer, .. } => {
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Exports the rows of the dataset in the given block range, and returns the number of rows.
+fn export_dataset<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    dataset: Dataset,
+    blocks: RangeInclusive<BlockNumber>,
+    writer: &mut DatasetWriter,
+) -> eyre::Result<usize> {
+    let mut rows = 0;
+    let mut start = *blocks.start();
+    loop {
+        let end = start.saturating_add(BLOCKS_PER_READ - 1).min(*blocks.end());
+        let provider = provider_factory.provider()?;
+        let range = start..=end;
+
+        rows += match dataset {
+            Dataset::Segment(StaticFileSegment::Headers) => {
+                write_rows(writer, header_rows(&provider, range)?)?
+            }
+            Dataset::Segment(StaticFileSegment::Transactions) => {
+                write_rows(writer, transaction_rows(&provider, range)?)?
+            }
+            Dataset::Segment(StaticFileSegment::Receipts) => {
+                write_rows(writer, receipt_rows(&provider, range)?)?
+            }
+            Dataset::Logs => write_rows(writer, log_rows(&provider, range)?)?,
+            Dataset::Segment(StaticFileSegment::TransactionSenders) |
+            Dataset::Table(Tables::TransactionSenders) => {
+                write_rows(writer, sender_rows(&provider, range)?)?
+            }
+            Dataset::Segment(StaticFileSegment::AccountChangeSets) |
+            Dataset::Table(Tables::AccountChangeSets) => {
+                write_rows(writer, account_change_rows(&provider, range)?)?
+            }
+            Dataset::Segment(StaticFileSegment::StorageChangeSets) |
+            Dataset::Table(Tables::StorageChangeSets) => {
+                write_rows(writer, storage_change_rows(&provider, range)?)?
+            }
+            Dataset::Table(Tables::BlockWithdrawals) => {
+                write_rows(writer, withdrawal_rows(&provider, range)?)?
+            }
+            Dataset::Table(table) => unreachable!("table {table} is not exportable"),
+        };
+
+        if end == *blocks.end() {
+            break
+        }
+        start = end + 1;
+    }
+
+    Ok(rows)
+}
+
+fn write_rows<R: Serialize>(writer: &mut DatasetWriter, rows: Vec<R>) -> eyre::Result<usize> {
+    writer.write(&rows)?;
+    Ok(rows.len())
+}
+
+/// Returns the transaction numbers of every block in the range.
+fn block_transactions<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<(BlockNumber, RangeInclusive<TxNumber>)>> {
+    let mut transactions = Vec::new();
+    for number in blocks {
+        let indices = provider
+            .block_body_indices(number)?
+            .ok_or_eyre(format!("missing body indices of block {number}"))?;
+        if !indices.is_empty() {
+            transactions.push((number, indices.first_tx_num()..=indices.last_tx_num()));
+        }
+    }
+    Ok(transactions)
+}
+
+#[derive(Serialize)]
+struct HeaderRow {
+    number: BlockNumber,
+    hash: B256,
+    parent_hash: B256,
+    miner: Address,
+    state_root: B256,
+    transactions_root: B256,
+    receipts_root: B256,
+    timestamp: u64,
+    gas_limit: u64,
+    gas_used: u64,
+    base_fee_per_gas: Option<u64>,
+    blob_gas_used: Option<u64>,
+    excess_blob_gas: Option<u64>,
+    difficulty: String,
+    total_difficulty: String,
+    extra_data: Bytes,
+}
+
+fn header_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<HeaderRow>> {
+    let mut rows = Vec::new();
+    for header in provider.sealed_headers_range(blocks)? {
+        let total_difficulty = provider
+            .header_td_by_number(header.number)?
+            .ok_or_eyre(format!("missing total difficulty of block {}", header.number))?;
+        rows.push(HeaderRow {
+            number: header.number,
+            hash: header.hash(),
+            parent_hash: header.parent_hash,
+            miner: header.beneficiary,
+            state_root: header.state_root,
+            transactions_root: header.transactions_root,
+            receipts_root: header.receipts_root,
+            timestamp: header.timestamp,
+            gas_limit: header.gas_limit,
+            gas_used: header.gas_used,
+            base_fee_per_gas: header.base_fee_per_gas,
+            blob_gas_used: header.blob_gas_used,
+            excess_blob_gas: header.excess_blob_gas,
+            difficulty: header.difficulty.to_string(),
+            total_difficulty: total_difficulty.to_string(),
+            extra_data: header.extra_data.clone(),
+        });
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct TransactionRow {
+    block_number: BlockNumber,
+    tx_number: TxNumber,
+    hash: B256,
+    tx_type: u8,
+    chain_id: Option<u64>,
+    nonce: u64,
+    to: Option<Address>,
+    value: String,
+    gas_limit: u64,
+    max_fee_per_gas: String,
+    max_priority_fee_per_gas: Option<String>,
+    input: Bytes,
+}
+
+fn transaction_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<TransactionRow>> {
+    let mut rows = Vec::new();
+    for (block_number, tx_range) in block_transactions(provider, blocks)? {
+        let transactions = provider.transactions_by_tx_range(tx_range.clone())?;
+        for (tx_number, tx) in tx_range.zip(transactions) {
+            rows.push(TransactionRow {
+                block_number,
+                tx_number,
+                hash: tx.hash(),
+                tx_type: tx.transaction.tx_type() as u8,
+                chain_id: tx.transaction.chain_id(),
+                nonce: tx.transaction.nonce(),
+                to: tx.transaction.to(),
+                value: tx.transaction.value().to_string(),
+                gas_limit: tx.transaction.gas_limit(),
+                max_fee_per_gas: tx.transaction.max_fee_per_gas().to_string(),
+                max_priority_fee_per_gas: tx
+                    .transaction
+                    .max_priority_fee_per_gas()
+                    .map(|fee| fee.to_string()),
+                input: tx.transaction.input().clone(),
+            });
+        }
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct ReceiptRow {
+    block_number: BlockNumber,
+    tx_number: TxNumber,
+    tx_type: u8,
+    success: bool,
+    cumulative_gas_used: u64,
+    logs: u64,
+}
+
+fn receipt_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<ReceiptRow>> {
+    let mut rows = Vec::new();
+    for (block_number, tx_range) in block_transactions(provider, blocks)? {
+        let receipts = provider.receipts_by_tx_range(tx_range.clone())?;
+        for (tx_number, receipt) in tx_range.zip(receipts) {
+            rows.push(ReceiptRow {
+                block_number,
+                tx_number,
+                tx_type: receipt.tx_type as u8,
+                success: receipt.success,
+                cumulative_gas_used: receipt.cumulative_gas_used,
+                logs: receipt.logs.len() as u64,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct LogRow {
+    block_number: BlockNumber,
+    tx_number: TxNumber,
+    log_index: u64,
+    address: Address,
+    topic0: Option<B256>,
+    topic1: Option<B256>,
+    topic2: Option<B256>,
+    topic3: Option<B256>,
+    data: Bytes,
+}
+
+fn log_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<LogRow>> {
+    let mut rows = Vec::new();
+    for (block_number, tx_range) in block_transactions(provider, blocks)? {
+        let receipts = provider.receipts_by_tx_range(tx_range.clone())?;
+        let mut log_index = 0;
+        for (tx_number, receipt) in tx_range.zip(receipts) {
+            for log in receipt.logs {
+                let topics = log.topics();
+                rows.push(LogRow {
+                    block_number,
+                    tx_number,
+                    log_index,
+                    address: log.address,
+                    topic0: topics.first().copied(),
+                    topic1: topics.get(1).copied(),
+                    topic2: topics.get(2).copied(),
+                    topic3: topics.get(3).copied(),
+                    data: log.data.data,
+                });
+                log_index += 1;
+            }
+        }
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct SenderRow {
+    block_number: BlockNumber,
+    tx_number: TxNumber,
+    sender: Address,
+}
+
+fn sender_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<SenderRow>> {
+    let transactions = block_transactions(provider, blocks)?;
+    let (Some((_, first)), Some((_, last))) = (transactions.first(), transactions.last()) else {
+        return Ok(Vec::new())
+    };
+
+    // senders of pruned transactions are missing, and senders that were moved to static files are
+    // read from them
+    let mut senders =
+        provider.transaction_senders_range(*first.start()..=*last.end())?.into_iter().peekable();
+    let mut rows = Vec::new();
+    for (block_number, tx_range) in transactions {
+        while let Some((tx_number, sender)) =
+            senders.next_if(|(tx_number, _)| tx_range.contains(tx_number))
+        {
+            rows.push(SenderRow { block_number, tx_number, sender });
+        }
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct AccountChangeRow {
+    block_number: BlockNumber,
+    address: Address,
+    nonce: Option<u64>,
+    balance: Option<String>,
+    bytecode_hash: Option<B256>,
+}
+
+fn account_change_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<AccountChangeRow>> {
+    let mut rows = Vec::new();
+    for (block_number, change) in provider.account_changesets_range(blocks)? {
+        rows.push(AccountChangeRow {
+            block_number,
+            address: change.address,
+            nonce: change.info.map(|account| account.nonce),
+            balance: change.info.map(|account| account.balance.to_string()),
+            bytecode_hash: change.info.and_then(|account| account.bytecode_hash),
+        });
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct StorageChangeRow {
+    block_number: BlockNumber,
+    address: Address,
+    slot: B256,
+    value: String,
+}
+
+fn storage_change_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<StorageChangeRow>> {
+    let mut rows = Vec::new();
+    for (BlockNumberAddress((block_number, address)), change) in
+        provider.storage_changesets_range(blocks)?
+    {
+        rows.push(StorageChangeRow {
+            block_number,
+            address,
+            slot: change.key,
+            value: change.value.to_string(),
+        });
+    }
+    Ok(rows)
+}
+
+#[derive(Serialize)]
+struct WithdrawalRow {
+    block_number: BlockNumber,
+    index: u64,
+    validator_index: u64,
+    address: Address,
+    amount: u64,
+}
+
+fn withdrawal_rows<TX: DbTx>(
+    provider: &DatabaseProvider<TX>,
+    blocks: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Vec<WithdrawalRow>> {
+    let mut rows = Vec::new();
+    for entry in provider.tx_ref().cursor_read::<tables::BlockWithdrawals>()?.walk_range(blocks)? {
+        let (block_number, withdrawals) = entry?;
+        for withdrawal in withdrawals.withdrawals {
+            rows.push(WithdrawalRow {
+                block_number,
+                index: withdrawal.index,
+                validator_index: withdrawal.validator_index,
+                address: withdrawal.address,
+                amount: withdrawal.amount,
+            });
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_json::writer::LineDelimited;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use reth_db_api::{
+        models::{AccountBeforeTx, StoredBlockBodyIndices},
+        transaction::DbTxMut,
+    };
+    use reth_primitives::{Account, StorageEntry, U256};
+    use reth_provider::test_utils::create_test_provider_factory;
+    use reth_prune_types::{PruneLimiter, PruneModes};
+    use reth_static_file::{HighestStaticFiles, StaticFileProducer};
+
+    #[test]
+    fn write_parquet_and_ndjson() {
+        let dir = tempfile::tempdir().unwrap();
+        let rows = vec![
+            AccountChangeRow {
+                block_number: 1,
+                address: Address::random(),
+                nonce: Some(1),
+                balance: Some(U256::from(10).to_string()),
+                bytecode_hash: None,
+            },
+            AccountChangeRow {
+                block_number: 2,
+                address: Address::random(),
+                nonce: None,
+                balance: None,
+                bytecode_hash: None,
+            },
+        ];
+
+        let dataset = Dataset::Table(Tables::AccountChangeSets);
+        for format in [ExportFormat::Parquet, ExportFormat::Ndjson] {
+            let path = dir.path().join(format!("{}.{}", dataset.name(), format.extension()));
+            let mut writer = DatasetWriter::new(&path, format, dataset.schema()).unwrap();
+            writer.write(&rows).unwrap();
+            writer.finish().unwrap();
+            assert!(reth_fs_util::metadata(&path).unwrap().len() > 0);
+        }
+        let expected =
+            rows.iter().map(|row| serde_json::to_value(row).unwrap()).collect::<Vec<_>>();
+
+        // read the parquet file back and convert its batches to json rows
+        let file = File::open(dir.path().join("AccountChangeSets.parquet")).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+        assert_eq!(reader.schema().fields(), dataset.schema().fields());
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        let mut json = arrow_json::WriterBuilder::new()
+            .with_explicit_nulls(true)
+            .build::<_, LineDelimited>(Vec::new());
+        json.write_batches(&batches.iter().collect::<Vec<_>>()).unwrap();
+        json.finish().unwrap();
+        let parquet = String::from_utf8(json.into_inner()).unwrap();
+        let parquet = parquet
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(parquet, expected);
+
+        let ndjson = std::fs::read_to_string(dir.path().join("AccountChangeSets.ndjson")).unwrap();
+        let ndjson = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(ndjson, expected);
+    }
+
+    #[test]
+    fn export_rows_moved_to_static_files() {
+        let factory = create_test_provider_factory();
+        let (mut senders, mut account_changes, mut storage_changes) =
+            (Vec::new(), Vec::new(), Vec::new());
+
+        let provider = factory.provider_rw().unwrap();
+        for block_number in 0..4 {
+            let indices = StoredBlockBodyIndices { first_tx_num: block_number * 2, tx_count: 2 };
+            provider
+                .tx_ref()
+                .put::<tables::BlockBodyIndices>(block_number, indices.clone())
+                .unwrap();
+            for tx_number in indices.tx_num_range() {
+                let sender = Address::with_last_byte(tx_number as u8);
+                provider.tx_ref().put::<tables::TransactionSenders>(tx_number, sender).unwrap();
+                senders.push(SenderRow { block_number, tx_number, sender });
+            }
+
+            let address = Address::with_last_byte(block_number as u8);
+            let account = Account { nonce: block_number, ..Default::default() };
+            provider
+                .tx_ref()
+                .put::<tables::AccountChangeSets>(
+                    block_number,
+                    AccountBeforeTx { address, info: Some(account) },
+                )
+                .unwrap();
+            account_changes.push(AccountChangeRow {
+                block_number,
+                address,
+                nonce: Some(block_number),
+                balance: Some(U256::ZERO.to_string()),
+                bytecode_hash: None,
+            });
+
+            let entry =
+                StorageEntry { key: B256::with_last_byte(1), value: U256::from(block_number) };
+            provider
+                .tx_ref()
+                .put::<tables::StorageChangeSets>(
+                    BlockNumberAddress((block_number, address)),
+                    entry,
+                )
+                .unwrap();
+            storage_changes.push(StorageChangeRow {
+                block_number,
+                address,
+                slot: entry.key,
+                value: entry.value.to_string(),
+            });
+        }
+        provider.commit().unwrap();
+
+        // move the first two blocks to static files, and prune them from the database
+        let static_file_producer = StaticFileProducer::new(factory.clone(), PruneModes::default());
+        let static_file_producer = static_file_producer.lock();
+        let targets = static_file_producer
+            .get_static_file_targets(HighestStaticFiles {
+                account_change_sets: Some(1),
+                storage_change_sets: Some(1),
+                transaction_senders: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        static_file_producer.run(targets).unwrap();
+
+        let provider = factory.provider_rw().unwrap();
+        let mut limiter = PruneLimiter::default();
+        provider
+            .prune_table_with_range::<tables::TransactionSenders>(
+                0..=3,
+                &mut limiter,
+                |_| false,
+                |_| {},
+            )
+            .unwrap();
+        provider
+            .prune_table_with_range::<tables::AccountChangeSets>(
+                0..=1,
+                &mut limiter,
+                |_| false,
+                |_| {},
+            )
+            .unwrap();
+        provider
+            .prune_table_with_range::<tables::StorageChangeSets>(
+                BlockNumberAddress::range(0..=1),
+                &mut limiter,
+                |_| false,
+                |_| {},
+            )
+            .unwrap();
+        provider.commit().unwrap();
+        assert_eq!(
+            factory.provider().unwrap().tx_ref().entries::<tables::AccountChangeSets>().unwrap(),
+            2
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let export = |dataset: Dataset| {
+            let path = dir.path().join(format!("{}.ndjson", dataset.name()));
+            let mut writer =
+                DatasetWriter::new(&path, ExportFormat::Ndjson, dataset.schema()).unwrap();
+            let rows = export_dataset(&factory, dataset, 0..=3, &mut writer).unwrap();
+            writer.finish().unwrap();
+            let lines = std::fs::read_to_string(&path)
+                .unwrap()
+                .lines()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(lines.len(), rows);
+            lines
+        };
+        fn lines<R: Serialize>(rows: &[R]) -> Vec<String> {
+            rows.iter().map(|row| serde_json::to_string(row).unwrap()).collect()
+        }
+
+        for (segment, table) in [
+            (StaticFileSegment::TransactionSenders, Tables::TransactionSenders),
+            (StaticFileSegment::AccountChangeSets, Tables::AccountChangeSets),
+            (StaticFileSegment::StorageChangeSets, Tables::StorageChangeSets),
+        ] {
+            let expected = match table {
+                Tables::TransactionSenders => lines(&senders),
+                Tables::AccountChangeSets => lines(&account_changes),
+                _ => lines(&storage_changes),
+            };
+            assert_eq!(export(Dataset::Segment(segment)), expected);
+            assert_eq!(export(Dataset::Table(table)), expected);
+        }
+    }
+}
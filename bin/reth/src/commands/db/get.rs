@@ -1,11 +1,15 @@
 use crate::utils::DbTool;
 use clap::Parser;
 use reth_db::{
-    static_file::{ColumnSelectorOne, ColumnSelectorTwo, HeaderMask, ReceiptMask, TransactionMask},
-    tables, RawKey, RawTable, Receipts, TableViewer, Transactions,
+    static_file::{
+        AccountChangeSetMask, ColumnSelectorOne, ColumnSelectorTwo, HeaderMask, ReceiptMask,
+        StorageChangeSetMask, TransactionMask, TransactionSenderMask,
+    },
+    tables, RawKey, RawTable, Receipts, TableViewer, TransactionSenders, Transactions,
 };
 use reth_db_api::{
     database::Database,
+    models::{BlockAccountChangeSets, BlockStorageChangeSets},
     table::{Decompress, DupSort, Table},
 };
 use reth_primitives::{BlockHash, Header, StaticFileSegment};
@@ -71,6 +75,20 @@ impl Command {
                         table_key::<tables::Receipts>(&key)?,
                         <ReceiptMask<<Receipts as Table>::Value>>::MASK,
                     ),
+                    StaticFileSegment::AccountChangeSets => (
+                        table_key::<tables::AccountChangeSets>(&key)?,
+                        <AccountChangeSetMask<BlockAccountChangeSets>>::MASK,
+                    ),
+                    // Storage changesets are stored per block, so they're also keyed by block
+                    // number in static files
+                    StaticFileSegment::StorageChangeSets => (
+                        table_key::<tables::AccountChangeSets>(&key)?,
+                        <StorageChangeSetMask<BlockStorageChangeSets>>::MASK,
+                    ),
+                    StaticFileSegment::TransactionSenders => (
+                        table_key::<tables::TransactionSenders>(&key)?,
+                        <TransactionSenderMask<<TransactionSenders as Table>::Value>>::MASK,
+                    ),
                 };
 
                 let content = tool.provider_factory.static_file_provider().find_static_file(
@@ -112,6 +130,23 @@ impl Command {
                                     )?;
                                     println!("{}", serde_json::to_string_pretty(&receipt)?);
                                 }
+                                StaticFileSegment::AccountChangeSets => {
+                                    let changeset =
+                                        BlockAccountChangeSets::decompress(content[0].as_slice())?;
+                                    println!("{}", serde_json::to_string_pretty(&changeset)?);
+                                }
+                                StaticFileSegment::StorageChangeSets => {
+                                    let changeset =
+                                        BlockStorageChangeSets::decompress(content[0].as_slice())?;
+                                    println!("{}", serde_json::to_string_pretty(&changeset)?);
+                                }
+                                StaticFileSegment::TransactionSenders => {
+                                    let sender =
+                                        <<TransactionSenders as Table>::Value>::decompress(
+                                            content[0].as_slice(),
+                                        )?;
+                                    println!("{}", serde_json::to_string_pretty(&sender)?);
+                                }
                             }
                         }
                     }
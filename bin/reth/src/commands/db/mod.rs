@@ -8,9 +8,14 @@ use clap::{Parser, Subcommand};
 use reth_db::version::{get_db_version, DatabaseVersionError, DB_VERSION};
 use std::io::{self, Write};
 
+mod backup;
+mod check;
 mod checksum;
 mod clear;
+mod compact;
+mod compression;
 mod diff;
+mod export;
 mod get;
 mod list;
 mod stats;
@@ -40,6 +45,8 @@ pub enum Subcommands {
     Diff(diff::Command),
     /// Gets the content of a table for the given key
     Get(get::Command),
+    /// Exports static file segments and tables over a block range into Parquet or NDJSON files
+    Export(export::Command),
     /// Deletes all database entries
     Drop {
         /// Bypasses the interactive confirmation and drops the database directly
@@ -48,6 +55,15 @@ pub enum Subcommands {
     },
     /// Deletes all table entries
     Clear(clear::Command),
+    /// Takes a backup of the database and static files, while the node is running
+    Backup(backup::Command),
+    /// Checks the consistency of the database and static files
+    Check(check::Command),
+    /// Rewrites the database without free pages, returning them to the file system
+    Compact(compact::Command),
+    /// Trains and benchmarks static file compression
+    #[command(subcommand)]
+    Compression(compression::Subcommands),
     /// Lists current and local database versions
     Version,
     /// Returns the full database path
@@ -98,6 +114,10 @@ impl Command {
                     command.execute(&tool)?;
                 });
             }
+            Subcommands::Export(command) => {
+                let Environment { provider_factory, .. } = self.env.init(AccessRights::RO)?;
+                command.execute(provider_factory)?;
+            }
             Subcommands::Drop { force } => {
                 if !force {
                     // Ask for confirmation
@@ -122,6 +142,30 @@ impl Command {
                 let Environment { provider_factory, .. } = self.env.init(AccessRights::RW)?;
                 command.execute(provider_factory)?;
             }
+            Subcommands::Compact(command) => {
+                let Environment { provider_factory, .. } = self.env.init(AccessRights::RW)?;
+                command.execute(provider_factory)?;
+            }
+            Subcommands::Compression(command) => match command {
+                // training stores the dictionaries in the static files directory
+                compression::Subcommands::Train(command) => {
+                    let Environment { provider_factory, .. } = self.env.init(AccessRights::RW)?;
+                    command.execute(provider_factory)?
+                }
+                compression::Subcommands::Benchmark(command) => {
+                    let Environment { provider_factory, .. } = self.env.init(AccessRights::RO)?;
+                    command.execute(provider_factory)?
+                }
+            },
+            Subcommands::Backup(command) => {
+                let Environment { provider_factory, .. } = self.env.init(AccessRights::RO)?;
+                command.execute(provider_factory)?;
+            }
+            Subcommands::Check(command) => {
+                let access = if command.is_repair() { AccessRights::RW } else { AccessRights::RO };
+                let environment = self.env.init_unchecked(access)?;
+                command.execute(&self.env, environment)?;
+            }
             Subcommands::Version => {
                 let local_db_version = match get_db_version(&db_path) {
                     Ok(version) => Some(version),
@@ -152,6 +196,63 @@ mod tests {
     use reth_node_core::args::utils::SUPPORTED_CHAINS;
     use std::path::Path;
 
+    #[test]
+    fn parse_backup() {
+        let cmd = Command::try_parse_from(["reth", "backup", "/tmp/backup"]).unwrap();
+        assert!(matches!(cmd.command, Subcommands::Backup(_)));
+    }
+
+    #[test]
+    fn parse_export() {
+        let cmd = Command::try_parse_from([
+            "reth",
+            "export",
+            "--segments",
+            "headers,receipts",
+            "--tables",
+            "AccountChangeSets",
+            "--format",
+            "ndjson",
+            "/tmp/export",
+        ])
+        .unwrap();
+        assert!(matches!(cmd.command, Subcommands::Export(_)));
+    }
+
+    #[test]
+    fn parse_checksum_tree() {
+        let cmd = Command::try_parse_from([
+            "reth",
+            "checksum",
+            "--segment",
+            "headers",
+            "--chunk-size",
+            "1000",
+            "--output",
+            "/tmp/headers.json",
+        ])
+        .unwrap();
+        assert!(matches!(cmd.command, Subcommands::Checksum(_)));
+
+        let cmd = Command::try_parse_from([
+            "reth",
+            "checksum",
+            "--compare",
+            "/tmp/a.json",
+            "/tmp/b.json",
+        ])
+        .unwrap();
+        assert!(matches!(cmd.command, Subcommands::Checksum(_)));
+
+        assert!(Command::try_parse_from(["reth", "checksum"]).is_err());
+    }
+
+    #[test]
+    fn parse_check() {
+        let cmd = Command::try_parse_from(["reth", "check", "--repair"]).unwrap();
+        assert!(matches!(cmd.command, Subcommands::Check(ref command) if command.is_repair()));
+    }
+
     #[test]
     fn parse_stats_globals() {
         let path = format!("../{}", SUPPORTED_CHAINS[0]);
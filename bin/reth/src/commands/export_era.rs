@@ -0,0 +1,178 @@
+//! Command that exports pre-merge history into Era1 files.
+
+use crate::{
+    commands::common::{AccessRights, Environment, EnvironmentArgs},
+    version::SHORT_VERSION,
+};
+use clap::Parser;
+use eyre::bail;
+use reth_db_api::database::Database;
+use reth_era::{Era1Block, Era1File, MAX_BLOCKS_PER_ERA1};
+use reth_primitives::{BlockBody, BlockNumber, SealedHeader};
+use reth_provider::{
+    BlockReader, ChainSpecProvider, HeaderProvider, ProviderError, ProviderFactory,
+    ReceiptProvider, StageCheckpointReader, StaticFileProviderFactory, TransactionsProvider,
+};
+use reth_stages::StageId;
+use std::{
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Exports pre-merge history into Era1 files.
+#[derive(Debug, Parser)]
+pub struct ExportEraCommand {
+    #[command(flatten)]
+    env: EnvironmentArgs,
+
+    /// The first epoch to export.
+    #[arg(long, value_name = "EPOCH", default_value_t = 0)]
+    first_epoch: u64,
+
+    /// The last epoch to export.
+    ///
+    /// Defaults to the last epoch that is fully synced, or that ends with the merge.
+    #[arg(long, value_name = "EPOCH", verbatim_doc_comment)]
+    last_epoch: Option<u64>,
+
+    /// The directory to write the Era1 files to.
+    #[arg(value_name = "EXPORT_DIR")]
+    dir: PathBuf,
+}
+
+impl ExportEraCommand {
+    /// Execute `export-era` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        info!(target: "reth::cli", "reth {} starting", SHORT_VERSION);
+
+        let Environment { provider_factory, .. } = self.env.init(AccessRights::RO)?;
+
+        let last_epoch = self.last_epoch.unwrap_or(u64::MAX);
+        if last_epoch < self.first_epoch {
+            bail!("last epoch {last_epoch} is before the first epoch {}", self.first_epoch)
+        }
+
+        let files = export_era1_files(&provider_factory, self.first_epoch..=last_epoch, &self.dir)?;
+        info!(target: "reth::cli", files = files.len(), dir = ?self.dir, "Era1 files exported");
+
+        Ok(())
+    }
+}
+
+/// Exports the given epochs into Era1 files in `dir` and returns the paths of the written files.
+///
+/// Epochs are exported as long as they are fully synced. The epoch of the merge is exported up to
+/// the last pre-merge block, after which the export stops.
+pub fn export_era1_files<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    epochs: impl Iterator<Item = u64>,
+    dir: &Path,
+) -> eyre::Result<Vec<PathBuf>> {
+    reth_fs_util::create_dir_all(dir)?;
+
+    let network = provider_factory.chain_spec().chain.to_string();
+    let provider = provider_factory.provider()?;
+    let static_file_provider = provider_factory.static_file_provider();
+    let tip = provider.get_stage_checkpoint(StageId::Finish)?.unwrap_or_default().block_number;
+
+    let mut files = Vec::new();
+    for epoch in epochs {
+        let start = epoch.saturating_mul(MAX_BLOCKS_PER_ERA1 as u64);
+        let end = start.saturating_add(MAX_BLOCKS_PER_ERA1 as u64 - 1);
+
+        let mut blocks = Vec::with_capacity(MAX_BLOCKS_PER_ERA1);
+        let mut merged = false;
+        for number in start..=end.min(tip) {
+            let header = static_file_provider
+                .sealed_header(number)?
+                .ok_or(ProviderError::HeaderNotFound(number.into()))?;
+            if number > 0 && header.difficulty.is_zero() {
+                merged = true;
+                break
+            }
+            blocks.push(era1_block(&provider, &static_file_provider, number, header)?);
+        }
+
+        if blocks.last().map(Era1Block::number) != Some(end) && !merged {
+            info!(target: "reth::cli", epoch, tip, "Epoch is not fully synced, stopping export");
+            break
+        }
+        if blocks.is_empty() {
+            break
+        }
+
+        let file = Era1File::new(blocks)?;
+        file.verify()?;
+
+        let path = dir.join(file.file_name(&network));
+        let mut writer = BufWriter::new(reth_fs_util::create_file(&path)?);
+        file.write(&mut writer)?;
+        writer.flush()?;
+        info!(target: "reth::cli", epoch, path = ?path, "Exported Era1 file");
+
+        files.push(path);
+        if merged {
+            break
+        }
+    }
+
+    Ok(files)
+}
+
+/// Reads the block with the given header from the static files and the database.
+fn era1_block<P, S>(
+    provider: &P,
+    static_file_provider: &S,
+    number: BlockNumber,
+    header: SealedHeader,
+) -> eyre::Result<Era1Block>
+where
+    P: BlockReader,
+    S: HeaderProvider + TransactionsProvider + ReceiptProvider,
+{
+    let total_difficulty = static_file_provider
+        .header_td_by_number(number)?
+        .ok_or(ProviderError::TotalDifficultyNotFound(number))?;
+    let block_indices = provider
+        .block_body_indices(number)?
+        .ok_or(ProviderError::BlockBodyIndicesNotFound(number))?;
+
+    let transactions = static_file_provider
+        .transactions_by_tx_range(block_indices.tx_num_range())?
+        .into_iter()
+        .map(|tx| tx.with_hash())
+        .collect::<Vec<_>>();
+    let receipts = static_file_provider
+        .receipts_by_tx_range(block_indices.tx_num_range())?
+        .into_iter()
+        .map(|receipt| receipt.with_bloom())
+        .collect::<Vec<_>>();
+    if transactions.len() as u64 != block_indices.tx_count ||
+        receipts.len() as u64 != block_indices.tx_count
+    {
+        bail!("missing transactions or receipts of block {number} in static files")
+    }
+    let ommers = provider.ommers(number.into())?.unwrap_or_default();
+
+    Ok(Era1Block {
+        header,
+        body: BlockBody { transactions, ommers, withdrawals: None, requests: None },
+        receipts,
+        total_difficulty,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_export_era_command() {
+        let args: ExportEraCommand =
+            ExportEraCommand::parse_from(["reth", "--last-epoch", "10", "era1"]);
+        assert_eq!(args.first_epoch, 0);
+        assert_eq!(args.last_epoch, Some(10));
+        assert_eq!(args.dir, PathBuf::from("era1"));
+    }
+}
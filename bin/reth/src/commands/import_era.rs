@@ -0,0 +1,331 @@
+//! Command that imports pre-merge history from Era1 files.
+
+use crate::{
+    commands::common::{AccessRights, Environment, EnvironmentArgs},
+    version::SHORT_VERSION,
+};
+use clap::Parser;
+use eyre::{bail, OptionExt};
+use reth_db::tables;
+use reth_db_api::{
+    database::Database,
+    models::{StoredBlockBodyIndices, StoredBlockOmmers},
+    transaction::DbTxMut,
+};
+use reth_era::{parse_era1_file_name, Era1Block, Era1File, ERA1_FILE_EXTENSION};
+use reth_primitives::{BlockNumber, StaticFileSegment};
+use reth_provider::{
+    BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, HeaderProvider,
+    ProviderFactory, StageCheckpointWriter, StaticFileProviderFactory, StaticFileWriter,
+};
+use reth_stages::{StageCheckpoint, StageId};
+use std::{
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+use tracing::info;
+
+/// Imports pre-merge history from Era1 files.
+#[derive(Debug, Parser)]
+pub struct ImportEraCommand {
+    #[command(flatten)]
+    env: EnvironmentArgs,
+
+    /// The path to an Era1 file or to a directory of Era1 files.
+    ///
+    /// The headers and bodies of the files are verified against their accumulator and written
+    /// straight into the static files, after which the node executes the remaining stages on its
+    /// next start. The receipts are verified, and then produced again by the execution stage.
+    #[arg(value_name = "IMPORT_PATH", verbatim_doc_comment)]
+    path: PathBuf,
+}
+
+impl ImportEraCommand {
+    /// Execute `import-era` command
+    pub async fn execute(self) -> eyre::Result<()> {
+        info!(target: "reth::cli", "reth {} starting", SHORT_VERSION);
+
+        let Environment { provider_factory, .. } = self.env.init(AccessRights::RW)?;
+
+        let network = provider_factory.chain_spec().chain.to_string();
+        let files = era1_files(&self.path, &network)?;
+        info!(target: "reth::cli", files = files.len(), "Importing Era1 files");
+
+        let tip = import_era1_files(&provider_factory, &files)?;
+        info!(target: "reth::cli", tip, "Era1 files imported");
+
+        Ok(())
+    }
+}
+
+/// Returns the Era1 files of the given network at `path`, sorted by epoch.
+///
+/// `path` is either a single Era1 file or a directory of Era1 files.
+pub fn era1_files(path: &Path, network: &str) -> eyre::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()])
+    }
+
+    let mut files = Vec::new();
+    for entry in reth_fs_util::read_dir(path)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+        let Some((file_network, epoch)) = parse_era1_file_name(name) else {
+            if path.extension().is_some_and(|extension| extension == ERA1_FILE_EXTENSION) {
+                bail!("invalid Era1 file name {name}")
+            }
+            continue
+        };
+        if file_network != network {
+            bail!("Era1 file {name} is not of network {network}")
+        }
+        files.push((epoch, path));
+    }
+    files.sort_unstable();
+
+    Ok(files.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Imports the blocks of the given Era1 files, which must be sorted by epoch, and returns the new
+/// tip.
+///
+/// Blocks up to the current tip are skipped, the remaining blocks must extend it. Each file is
+/// verified before any of its blocks are written, and is committed on its own, so an interrupted
+/// import can be resumed.
+///
+/// Headers and transactions are written to static files, and the `Headers` and `Bodies` stage
+/// checkpoints are moved to the new tip. The receipts are only verified: the execution stage
+/// writes the receipts of the blocks it executes, and the receipts static files must not get ahead
+/// of its checkpoint, or they are pruned back to it on the next start of the node. The other stages
+/// (e.g. sender recovery and execution) pick up the imported blocks on the next start of the node.
+pub fn import_era1_files<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    files: &[PathBuf],
+) -> eyre::Result<BlockNumber> {
+    let static_file_provider = provider_factory.static_file_provider();
+
+    let provider = provider_factory.provider()?;
+    let mut tip = provider.last_block_number()?;
+    for segment in [StaticFileSegment::Headers, StaticFileSegment::Transactions] {
+        match static_file_provider.get_highest_static_file_block(segment) {
+            Some(highest) if highest == tip => {}
+            // transactions are only written for the genesis block once the pipeline runs for the
+            // first time
+            None if tip == 0 && !segment.is_headers() => {
+                static_file_provider.latest_writer(segment)?.increment_block(segment, 0)?;
+            }
+            highest => bail!(
+                "{segment} static files end at block {highest:?}, expected them to end at the tip {tip}"
+            ),
+        }
+    }
+    let mut tip_hash = provider.block_hash(tip)?.ok_or_eyre("missing tip hash")?;
+    let mut tip_td = provider.header_td_by_number(tip)?.ok_or_eyre("missing tip difficulty")?;
+    let mut next_tx_num =
+        provider.block_body_indices(tip)?.ok_or_eyre("missing tip body indices")?.next_tx_num();
+    drop(provider);
+
+    for path in files {
+        let file = Era1File::read(BufReader::new(reth_fs_util::open(path)?))?;
+        file.verify()?;
+
+        let blocks =
+            file.blocks.into_iter().filter(|block| block.number() > tip).collect::<Vec<_>>();
+        let Some(first) = blocks.first() else {
+            info!(target: "reth::cli", ?path, "Skipping Era1 file below the tip");
+            continue
+        };
+        if first.number() != tip + 1 {
+            bail!("Era1 file {path:?} starts at block {}, expected {}", first.number(), tip + 1)
+        }
+        if first.header.parent_hash != tip_hash {
+            bail!("block {} of Era1 file {path:?} does not extend the tip", first.number())
+        }
+        if first.total_difficulty != tip_td + first.header.difficulty {
+            bail!("total difficulty of block {} of Era1 file {path:?} is inconsistent", tip + 1)
+        }
+
+        let last = blocks.last().expect("not empty");
+        let (last_number, last_hash, last_td) =
+            (last.number(), last.header.hash(), last.total_difficulty);
+        info!(target: "reth::cli", ?path, from = tip + 1, to = last_number, "Importing Era1 file");
+
+        let provider_rw = provider_factory.provider_rw()?;
+        let tx = provider_rw.tx_ref();
+
+        {
+            let mut writer = static_file_provider.latest_writer(StaticFileSegment::Headers)?;
+            for block in &blocks {
+                writer.append_header(
+                    block.header.header().clone(),
+                    block.total_difficulty,
+                    block.header.hash(),
+                )?;
+                tx.put::<tables::HeaderNumbers>(block.header.hash(), block.number())?;
+            }
+        }
+
+        {
+            let mut writer = static_file_provider.latest_writer(StaticFileSegment::Transactions)?;
+            for Era1Block { header, body, .. } in blocks {
+                writer.increment_block(StaticFileSegment::Transactions, header.number)?;
+
+                let block_indices = StoredBlockBodyIndices {
+                    first_tx_num: next_tx_num,
+                    tx_count: body.transactions.len() as u64,
+                };
+                if !body.transactions.is_empty() {
+                    tx.put::<tables::TransactionBlocks>(
+                        block_indices.last_tx_num(),
+                        header.number,
+                    )?;
+                }
+                for transaction in body.transactions {
+                    writer.append_transaction(next_tx_num, transaction.into())?;
+                    next_tx_num += 1;
+                }
+                if !body.ommers.is_empty() {
+                    tx.put::<tables::BlockOmmers>(
+                        header.number,
+                        StoredBlockOmmers { ommers: body.ommers },
+                    )?;
+                }
+                tx.put::<tables::BlockBodyIndices>(header.number, block_indices)?;
+            }
+        }
+
+        for stage in [StageId::Headers, StageId::Bodies] {
+            provider_rw.save_stage_checkpoint(stage, StageCheckpoint::new(last_number))?;
+        }
+
+        // static files are committed first, so that the database never refers to missing data
+        static_file_provider.commit()?;
+        provider_rw.commit()?;
+
+        (tip, tip_hash, tip_td) = (last_number, last_hash, last_td);
+    }
+
+    Ok(tip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_chainspec::MAINNET;
+    use reth_db_common::init::init_genesis;
+    use reth_primitives::{proofs::calculate_receipt_root, BlockBody, B256, U256};
+    use reth_provider::{
+        providers::StaticFileProvider, test_utils::create_test_provider_factory,
+        StageCheckpointReader, TransactionsProvider,
+    };
+    use reth_testing_utils::generators::{self, random_block, random_receipt};
+    use std::{fs::File, ops::RangeInclusive};
+
+    /// Writes an Era1 file of random blocks on top of the given parent hash and total difficulty,
+    /// and returns its path and the hash and total difficulty of its last block.
+    fn write_era1_file(
+        dir: &Path,
+        numbers: RangeInclusive<BlockNumber>,
+        mut parent: (B256, U256),
+    ) -> (PathBuf, (B256, U256)) {
+        let mut rng = generators::rng();
+        let mut blocks = Vec::new();
+        for number in numbers.clone() {
+            let block = random_block(&mut rng, number, Some(parent.0), Some(2), Some(0));
+            let receipts = block
+                .body
+                .iter()
+                .map(|tx| random_receipt(&mut rng, tx, Some(1)).with_bloom())
+                .collect::<Vec<_>>();
+
+            let mut header = block.header.unseal();
+            header.difficulty = U256::from(1);
+            header.receipts_root = calculate_receipt_root(&receipts);
+            let header = header.seal_slow();
+            let total_difficulty = parent.1 + header.difficulty;
+            parent = (header.hash(), total_difficulty);
+
+            blocks.push(Era1Block {
+                header,
+                body: BlockBody {
+                    transactions: block.body,
+                    ommers: block.ommers,
+                    withdrawals: None,
+                    requests: None,
+                },
+                receipts,
+                total_difficulty,
+            });
+        }
+
+        let path = dir.join(format!("{}-{}.{ERA1_FILE_EXTENSION}", numbers.start(), numbers.end()));
+        Era1File::new(blocks).unwrap().write(File::create(&path).unwrap()).unwrap();
+        (path, parent)
+    }
+
+    #[test]
+    fn import_restart_and_resume() {
+        let factory = create_test_provider_factory();
+        init_genesis(factory.clone()).unwrap();
+        let genesis = {
+            let provider = factory.provider().unwrap();
+            (
+                provider.block_hash(0).unwrap().unwrap(),
+                provider.header_td_by_number(0).unwrap().unwrap(),
+            )
+        };
+
+        let dir = tempfile::tempdir().unwrap();
+        let (first, tip) = write_era1_file(dir.path(), 1..=3, genesis);
+        let (second, _) = write_era1_file(dir.path(), 4..=6, tip);
+        assert_eq!(import_era1_files(&factory, &[first.clone()]).unwrap(), 3);
+
+        // restart on the same database and static files, the node checks their consistency first
+        let db = factory.db_ref().clone();
+        let static_files_path = factory.static_file_provider().path().to_path_buf();
+        drop(factory);
+        let factory = ProviderFactory::new(
+            db,
+            MAINNET.clone(),
+            StaticFileProvider::read_write(static_files_path).unwrap(),
+        );
+        let static_file_provider = factory.static_file_provider();
+        assert_eq!(
+            static_file_provider.check_consistency(&factory.provider().unwrap(), false).unwrap(),
+            None
+        );
+
+        // the first file is skipped and the second one extends the imported blocks
+        assert_eq!(import_era1_files(&factory, &[first, second]).unwrap(), 6);
+        assert_eq!(
+            static_file_provider.check_consistency(&factory.provider().unwrap(), false).unwrap(),
+            None
+        );
+
+        let provider = factory.provider().unwrap();
+        assert_eq!(provider.last_block_number().unwrap(), 6);
+        for stage in [StageId::Headers, StageId::Bodies] {
+            assert_eq!(
+                provider.get_stage_checkpoint(stage).unwrap(),
+                Some(StageCheckpoint::new(6))
+            );
+        }
+        assert_eq!(
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::Transactions),
+            Some(6)
+        );
+        assert!(provider.transaction_by_id(11).unwrap().is_some());
+        // the receipts are left to the execution stage
+        assert_eq!(
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::Receipts),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_import_era_command() {
+        let args: ImportEraCommand =
+            ImportEraCommand::parse_from(["reth", "--chain", "sepolia", "era1"]);
+        assert_eq!(args.path, PathBuf::from("era1"));
+    }
+}
@@ -2,8 +2,8 @@
 
 use crate::args::{
     utils::{chain_help, chain_value_parser, parse_socket_address, SUPPORTED_CHAINS},
-    DatabaseArgs, DatadirArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs, PruningArgs,
-    RpcServerArgs, TxPoolArgs,
+    DatabaseArgs, DatadirArgs, DebugArgs, DevArgs, ExExArgs, NetworkArgs, PayloadBuilderArgs,
+    PruningArgs, RpcServerArgs, TxPoolArgs,
 };
 use clap::{value_parser, Args, Parser};
 use reth_chainspec::ChainSpec;
@@ -13,6 +13,9 @@ use reth_node_builder::{NodeBuilder, WithLaunchContext};
 use reth_node_core::{node_config::NodeConfig, version};
 use std::{ffi::OsString, fmt, future::Future, net::SocketAddr, path::PathBuf, sync::Arc};
 
+mod secondary;
+pub use secondary::launch_secondary;
+
 /// Start the node
 #[derive(Debug, Parser)]
 pub struct NodeCommand<Ext: clap::Args + fmt::Debug = NoArgs> {
@@ -63,6 +66,14 @@ pub struct NodeCommand<Ext: clap::Args + fmt::Debug = NoArgs> {
     #[arg(long, conflicts_with = "instance", global = true)]
     pub with_unused_ports: bool,
 
+    /// Runs a read-only secondary node over the datadir of a running primary node.
+    ///
+    /// The secondary opens the database read-only, follows the canonical tip of the primary and
+    /// only serves the read-only RPC namespaces. It doesn't connect to the network or run the
+    /// engine API, so it requires no consensus client.
+    #[arg(long, conflicts_with = "dev", verbatim_doc_comment)]
+    pub secondary: bool,
+
     /// All datadir related arguments
     #[command(flatten)]
     pub datadir: DatadirArgs,
@@ -99,6 +110,10 @@ pub struct NodeCommand<Ext: clap::Args + fmt::Debug = NoArgs> {
     #[command(flatten)]
     pub pruning: PruningArgs,
 
+    /// All execution extension related arguments with --exex prefix
+    #[command(flatten)]
+    pub exex: ExExArgs,
+
     /// Additional cli arguments
     #[command(flatten, next_help_heading = "Extension")]
     pub ext: Ext,
@@ -139,6 +154,7 @@ impl<Ext: clap::Args + fmt::Debug> NodeCommand<Ext> {
             metrics,
             instance,
             with_unused_ports,
+            secondary,
             network,
             rpc,
             txpool,
@@ -147,6 +163,7 @@ impl<Ext: clap::Args + fmt::Debug> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            exex,
             ext,
         } = self;
 
@@ -165,22 +182,27 @@ impl<Ext: clap::Args + fmt::Debug> NodeCommand<Ext> {
             db,
             dev,
             pruning,
+            exex,
         };
 
         // Register the prometheus recorder before creating the database,
         // because database init needs it to register metrics.
         let _ = node_config.install_prometheus_recorder()?;
 
+        if with_unused_ports {
+            node_config = node_config.with_unused_ports();
+        }
+
+        if secondary {
+            return launch_secondary(node_config, ctx.task_executor).await
+        }
+
         let data_dir = node_config.datadir();
         let db_path = data_dir.db();
 
         tracing::info!(target: "reth::cli", path = ?db_path, "Opening database");
         let database = Arc::new(init_db(db_path.clone(), self.db.database_args())?.with_metrics());
 
-        if with_unused_ports {
-            node_config = node_config.with_unused_ports();
-        }
-
         let builder = NodeBuilder::new(node_config)
             .with_database(database)
             .with_launch_context(ctx.task_executor);
@@ -253,6 +275,14 @@ mod tests {
         assert_eq!(cmd.network.port, 99);
     }
 
+    #[test]
+    fn parse_secondary() {
+        let cmd = NodeCommand::try_parse_args_from(["reth", "--secondary"]).unwrap();
+        assert!(cmd.secondary);
+
+        assert!(NodeCommand::try_parse_args_from(["reth", "--secondary", "--dev"]).is_err());
+    }
+
     #[test]
     fn parse_metrics_port() {
         let cmd = NodeCommand::try_parse_args_from(["reth", "--metrics", "9001"]).unwrap();
@@ -0,0 +1,256 @@
+//! Read-only secondary node that serves RPC from the datadir of a running primary node.
+
+use eyre::bail;
+use reth_blockchain_tree::noop::NoopBlockchainTree;
+use reth_db::{open_db_read_only, DatabaseEnv};
+use reth_db_api::database::Database;
+use reth_network_api::noop::NoopNetwork;
+use reth_node_core::node_config::NodeConfig;
+use reth_node_ethereum::EthEvmConfig;
+use reth_primitives::SealedHeader;
+use reth_provider::{
+    providers::{BlockchainProvider, StaticFileProvider},
+    BlockHashReader, BlockNumReader, CanonChainTracker, FinalizedBlockReader, HeaderProvider,
+    ProviderFactory, ProviderResult, StaticFileProviderFactory,
+};
+use reth_rpc_builder::{
+    config::RethRpcServerConfig, RethRpcModule, RpcModuleBuilder, RpcModuleSelection,
+};
+use reth_tasks::TaskExecutor;
+use reth_transaction_pool::noop::NoopTransactionPool;
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tracing::{info, warn};
+
+/// How often the secondary node checks the database for a new canonical tip of the primary.
+const TIP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The RPC modules that only read chain data, and can be served without a network, transaction
+/// pool or consensus engine.
+const READ_ONLY_RPC_MODULES: [RethRpcModule; 8] = [
+    RethRpcModule::Eth,
+    RethRpcModule::Net,
+    RethRpcModule::Web3,
+    RethRpcModule::Rpc,
+    RethRpcModule::Debug,
+    RethRpcModule::Trace,
+    RethRpcModule::Ots,
+    RethRpcModule::Reth,
+];
+
+/// Launches a secondary node, which opens the datadir of a primary node read-only, follows its
+/// canonical tip and serves the read-only RPC namespaces.
+///
+/// The secondary node doesn't connect to the network, doesn't run the engine API and doesn't
+/// accept transactions. It never writes to the datadir, so any number of secondary nodes can run
+/// next to the primary.
+pub async fn launch_secondary(config: NodeConfig, executor: TaskExecutor) -> eyre::Result<()> {
+    if config.chain.is_optimism() {
+        bail!("secondary nodes are not supported on optimism chains")
+    }
+
+    let data_dir = config.datadir();
+    info!(target: "reth::cli", path = ?data_dir.db(), "Opening database of the primary read-only");
+    let database =
+        Arc::new(open_db_read_only(&data_dir.db(), config.db.database_args())?.with_metrics());
+    let static_file_provider =
+        StaticFileProvider::read_only(data_dir.static_files())?.with_metrics();
+    let provider_factory =
+        ProviderFactory::new(database.clone(), config.chain.clone(), static_file_provider.clone());
+
+    let genesis_hash = provider_factory.block_hash(0)?;
+    if genesis_hash != Some(config.chain.genesis_hash()) {
+        bail!(
+            "datadir genesis {genesis_hash:?} does not match the genesis {} of the chain, is the primary initialized?",
+            config.chain.genesis_hash()
+        )
+    }
+
+    let blockchain_db =
+        BlockchainProvider::new(provider_factory.clone(), Arc::new(NoopBlockchainTree::default()))?;
+    let tip = blockchain_db.chain_info()?;
+    info!(target: "reth::cli", number = tip.best_number, hash = ?tip.best_hash, "Following the primary");
+
+    let prometheus_handle = config.install_prometheus_recorder()?;
+    config
+        .start_metrics_endpoint(prometheus_handle, database, static_file_provider, executor.clone())
+        .await?;
+
+    executor.spawn_critical(
+        "secondary tip follower",
+        follow_primary(provider_factory, blockchain_db.clone()),
+    );
+
+    let mut module_config = config.rpc.transport_rpc_module_config();
+    retain_read_only_modules(module_config.http_mut());
+    retain_read_only_modules(module_config.ws_mut());
+    retain_read_only_modules(module_config.ipc_mut());
+
+    let modules = RpcModuleBuilder::default()
+        .with_provider(blockchain_db.clone())
+        .with_pool(NoopTransactionPool::default())
+        .with_network(NoopNetwork::default())
+        .with_events(blockchain_db)
+        .with_executor(executor)
+        .with_evm_config(EthEvmConfig::default())
+        .build(module_config);
+
+    // the servers are stopped once the handle is dropped
+    let handle = modules.start_server(config.rpc.rpc_server_config()).await?;
+    if let Some(path) = handle.ipc_endpoint() {
+        info!(target: "reth::cli", %path, "RPC IPC server started");
+    }
+    if let Some(addr) = handle.http_local_addr() {
+        info!(target: "reth::cli", url=%addr, "RPC HTTP server started");
+    }
+    if let Some(addr) = handle.ws_local_addr() {
+        info!(target: "reth::cli", url=%addr, "RPC WS server started");
+    }
+
+    std::future::pending::<()>().await;
+    drop(handle);
+    Ok(())
+}
+
+/// Removes the modules from the selection that aren't read-only.
+fn retain_read_only_modules(selection: &mut Option<RpcModuleSelection>) {
+    let Some(modules) = selection.take() else { return };
+    let (modules, skipped): (HashSet<_>, HashSet<_>) = modules
+        .into_selection()
+        .into_iter()
+        .partition(|module| READ_ONLY_RPC_MODULES.contains(module));
+    if !skipped.is_empty() {
+        warn!(target: "reth::cli", ?skipped, "Skipping RPC modules that aren't supported by secondary nodes");
+    }
+    *selection = Some(modules.into());
+}
+
+/// Polls the database for the canonical tip and the finalized block of the primary, and updates
+/// the chain info of the provider.
+///
+/// The static file index is refreshed whenever the highest static file blocks on disk change,
+/// before the new tip is exposed.
+async fn follow_primary(
+    provider_factory: ProviderFactory<Arc<DatabaseEnv>>,
+    blockchain_db: BlockchainProvider<Arc<DatabaseEnv>>,
+) {
+    let mut interval = tokio::time::interval(TIP_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(err) = refresh_tip(&provider_factory, &blockchain_db) {
+            warn!(target: "reth::cli", %err, "Failed to refresh the canonical tip of the primary");
+        }
+    }
+}
+
+fn refresh_tip<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    blockchain_db: &BlockchainProvider<DB>,
+) -> ProviderResult<()> {
+    let provider = provider_factory.provider()?;
+
+    // The primary may append to or truncate static files without moving its tip, so the index is
+    // checked on every poll. Static files are committed before the database, so once refreshed
+    // after the database transaction was opened, the index covers the tip it reads.
+    let static_file_provider = provider_factory.static_file_provider();
+    if static_file_provider.is_index_stale()? {
+        static_file_provider.initialize_index()?;
+    }
+
+    let chain_info = provider.chain_info()?;
+    if chain_info == blockchain_db.chain_info()? {
+        return Ok(())
+    }
+
+    let Some(header) = provider.header_by_number(chain_info.best_number)? else { return Ok(()) };
+    let header = SealedHeader::new(header, chain_info.best_hash);
+    info!(target: "reth::cli", number = header.number, hash = ?header.hash(), "New canonical tip of the primary");
+    blockchain_db.set_canonical_head(header);
+
+    let finalized = provider.last_finalized_block_number()?;
+    if finalized > 0 {
+        if let Some(header) = provider.sealed_header(finalized)? {
+            blockchain_db.set_finalized(header);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_chainspec::ChainInfo;
+    use reth_primitives::{StaticFileSegment, B256, U256};
+    use reth_provider::{
+        test_utils::create_test_provider_factory, StageCheckpointWriter, StaticFileWriter,
+    };
+    use reth_stages::{StageCheckpoint, StageId};
+    use reth_testing_utils::generators::{self, random_header_range};
+
+    #[test]
+    fn refresh_tip_reindexes_changed_static_files() {
+        let mut rng = generators::rng();
+        let primary = create_test_provider_factory();
+        let secondary = ProviderFactory::new(
+            primary.db_ref().clone(),
+            primary.chain_spec(),
+            StaticFileProvider::read_only(primary.static_file_provider().path()).unwrap(),
+        );
+        let blockchain_db = BlockchainProvider::with_latest(
+            secondary.clone(),
+            Arc::new(NoopBlockchainTree::default()),
+            SealedHeader::default(),
+        );
+        let set_tip = |number| {
+            let provider_rw = primary.provider_rw().unwrap();
+            provider_rw
+                .save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(number))
+                .unwrap();
+            provider_rw.commit().unwrap();
+        };
+
+        // another writer appends headers
+        let headers = random_header_range(&mut rng, 0..10, B256::ZERO);
+        let mut writer =
+            primary.static_file_provider().get_writer(0, StaticFileSegment::Headers).unwrap();
+        for header in &headers {
+            writer.append_header(header.header().clone(), U256::ZERO, header.hash()).unwrap();
+        }
+        writer.commit().unwrap();
+        drop(writer);
+        set_tip(9);
+
+        let static_file_provider = secondary.static_file_provider();
+        assert!(static_file_provider.is_index_stale().unwrap());
+        refresh_tip(&secondary, &blockchain_db).unwrap();
+        assert!(!static_file_provider.is_index_stale().unwrap());
+        assert_eq!(
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::Headers),
+            Some(9)
+        );
+        assert_eq!(
+            blockchain_db.chain_info().unwrap(),
+            ChainInfo { best_hash: headers[9].hash(), best_number: 9 }
+        );
+
+        // another writer truncates the headers
+        let mut writer =
+            primary.static_file_provider().latest_writer(StaticFileSegment::Headers).unwrap();
+        writer.prune_headers(5).unwrap();
+        writer.commit().unwrap();
+        drop(writer);
+        set_tip(4);
+
+        assert!(static_file_provider.is_index_stale().unwrap());
+        refresh_tip(&secondary, &blockchain_db).unwrap();
+        assert!(!static_file_provider.is_index_stale().unwrap());
+        assert_eq!(
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::Headers),
+            Some(4)
+        );
+        assert_eq!(
+            blockchain_db.chain_info().unwrap(),
+            ChainInfo { best_hash: headers[4].hash(), best_number: 4 }
+        );
+    }
+}
@@ -11,7 +11,9 @@ use crate::{
 use clap::Parser;
 use reth_beacon_consensus::EthBeaconConsensus;
 use reth_cli_runner::CliContext;
-use reth_config::config::{HashingConfig, SenderRecoveryConfig, TransactionLookupConfig};
+use reth_config::config::{
+    HashingConfig, IndexAddressTransactionsConfig, SenderRecoveryConfig, TransactionLookupConfig,
+};
 use reth_downloaders::bodies::bodies::BodiesDownloaderBuilder;
 use reth_exex::ExExManagerHandle;
 use reth_provider::{
@@ -21,8 +23,8 @@ use reth_provider::{
 use reth_stages::{
     stages::{
         AccountHashingStage, BodyStage, ExecutionStage, ExecutionStageThresholds,
-        IndexAccountHistoryStage, IndexStorageHistoryStage, MerkleStage, SenderRecoveryStage,
-        StorageHashingStage, TransactionLookupStage,
+        IndexAccountHistoryStage, IndexAddressTransactionsStage, IndexStorageHistoryStage,
+        MerkleStage, SenderRecoveryStage, StorageHashingStage, TransactionLookupStage,
     },
     ExecInput, ExecOutput, Stage, StageExt, UnwindInput, UnwindOutput,
 };
@@ -216,7 +218,9 @@ impl Command {
                     Box::new(IndexAccountHistoryStage::new(
                         config.stages.index_account_history,
                         etl_config,
-                        prune_modes.account_history,
+                        prune_modes
+                            .account_history
+                            .filter(|_| prune_modes.account_history_retention.is_empty()),
                     )),
                     None,
                 ),
@@ -224,7 +228,17 @@ impl Command {
                     Box::new(IndexStorageHistoryStage::new(
                         config.stages.index_storage_history,
                         etl_config,
-                        prune_modes.storage_history,
+                        prune_modes
+                            .storage_history
+                            .filter(|_| prune_modes.storage_history_retention.is_empty()),
+                    )),
+                    None,
+                ),
+                StageEnum::AddressTransactions => (
+                    Box::new(IndexAddressTransactionsStage::new(
+                        IndexAddressTransactionsConfig { enabled: true, chunk_size: batch_size },
+                        etl_config,
+                        prune_modes.address_transactions,
                     )),
                     None,
                 ),
@@ -2,6 +2,7 @@
 
 use reth_network::{PeersConfig, SessionsConfig};
 use reth_prune_types::PruneModes;
+use reth_static_file_types::SegmentsCompression;
 use serde::{Deserialize, Deserializer, Serialize};
 use std::{
     ffi::OsStr,
@@ -25,6 +26,11 @@ pub struct Config {
     pub peers: PeersConfig,
     /// Configuration for peer sessions.
     pub sessions: SessionsConfig,
+    /// Configuration for static files.
+    pub static_files: StaticFilesConfig,
+    /// Configuration for the scheduled database compaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compaction: Option<CompactionConfig>,
 }
 
 impl Config {
@@ -82,6 +88,10 @@ pub struct StageConfig {
     pub index_account_history: IndexHistoryConfig,
     /// Index Storage History stage configuration.
     pub index_storage_history: IndexHistoryConfig,
+    /// Index Address Transactions stage configuration.
+    pub index_address_transactions: IndexAddressTransactionsConfig,
+    /// Snap Sync stage configuration.
+    pub snap_sync: SnapSyncConfig,
     /// Common ETL related configuration.
     pub etl: EtlConfig,
 }
@@ -310,6 +320,54 @@ impl Default for IndexHistoryConfig {
     }
 }
 
+/// Address transactions index stage configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct IndexAddressTransactionsConfig {
+    /// Whether the stage should run. The index is not built by default.
+    pub enabled: bool,
+    /// The maximum number of transactions to process before writing to disk.
+    pub chunk_size: u64,
+}
+
+impl Default for IndexAddressTransactionsConfig {
+    fn default() -> Self {
+        Self { enabled: false, chunk_size: 5_000_000 }
+    }
+}
+
+/// Snap sync stage configuration.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct SnapSyncConfig {
+    /// Whether the state should be downloaded over `snap` instead of executing all blocks.
+    pub enabled: bool,
+    /// The minimum number of blocks the sync target has to be ahead of genesis to use snap sync.
+    pub min_distance: u64,
+    /// The maximum number of concurrent requests to have in flight at a time.
+    pub max_concurrent_requests: usize,
+    /// The soft limit for the size of a single response in bytes.
+    pub response_bytes: u64,
+    /// The maximum number of state entries to download before writing them to disk.
+    pub commit_threshold: u64,
+    /// The maximum number of rounds to heal the trie against a pivot, before waiting for a new
+    /// sync target to use as the pivot.
+    pub max_heal_rounds: usize,
+}
+
+impl Default for SnapSyncConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_distance: 100_000,
+            max_concurrent_requests: 16,
+            response_bytes: 512 * 1024,
+            commit_threshold: 100_000,
+            max_heal_rounds: 8,
+        }
+    }
+}
+
 /// Pruning configuration.
 #[derive(Debug, Clone, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default)]
@@ -334,6 +392,98 @@ impl PruneConfig {
     }
 }
 
+/// Static files configuration.
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct StaticFilesConfig {
+    /// Compression of newly created static files, per segment.
+    ///
+    /// `zstd-with-dictionary` requires a dictionary trained with `reth db compression train`,
+    /// otherwise plain `zstd` is used.
+    pub compression: SegmentsCompression,
+}
+
+/// Scheduled database compaction configuration.
+///
+/// The database is compacted by the engine, and the compacted copy is only swapped in if the engine
+/// didn't write to the database while it was written. Compaction returns the free pages of the
+/// database file to the file system.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default)]
+pub struct CompactionConfig {
+    /// Minimum time between two compactions.
+    #[serde(with = "humantime_serde")]
+    pub interval: Duration,
+    /// Minimum share of the database file that is free, in percent, for a compaction to run.
+    pub min_free_percent: u8,
+    /// Hours of the day, in UTC, during which compactions are allowed to start.
+    ///
+    /// If not set, compactions can start at any time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<MaintenanceWindow>,
+    /// Maximum time to wait for open transactions to finish before swapping the compacted
+    /// database in. New transactions are blocked while waiting.
+    #[serde(with = "humantime_serde")]
+    pub swap_timeout: Duration,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(24 * 60 * 60),
+            min_free_percent: 25,
+            window: None,
+            swap_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Hours of the day, in UTC, during which maintenance is allowed to start.
+///
+/// The window wraps around midnight if `end_hour` is lower than `start_hour`. Both hours must be
+/// lower than 24.
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(try_from = "RawMaintenanceWindow")]
+pub struct MaintenanceWindow {
+    /// First hour of the window, inclusive.
+    pub start_hour: u8,
+    /// Last hour of the window, exclusive.
+    pub end_hour: u8,
+}
+
+/// [`MaintenanceWindow`] before its hours are validated.
+#[derive(Deserialize)]
+struct RawMaintenanceWindow {
+    start_hour: u8,
+    end_hour: u8,
+}
+
+impl TryFrom<RawMaintenanceWindow> for MaintenanceWindow {
+    type Error = String;
+
+    fn try_from(window: RawMaintenanceWindow) -> Result<Self, Self::Error> {
+        let RawMaintenanceWindow { start_hour, end_hour } = window;
+        if start_hour >= 24 || end_hour >= 24 {
+            return Err(format!(
+                "maintenance window hours must be lower than 24, got {start_hour}..{end_hour}"
+            ))
+        }
+        Ok(Self { start_hour, end_hour })
+    }
+}
+
+impl MaintenanceWindow {
+    /// Returns `true` if the window contains the hour of the given UNIX timestamp.
+    pub const fn contains(&self, timestamp: u64) -> bool {
+        let hour = ((timestamp / 3600) % 24) as u8;
+        if self.start_hour <= self.end_hour {
+            self.start_hour <= hour && hour < self.end_hour
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
+
 /// Helper type to support older versions of Duration deserialization.
 fn deserialize_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
 where
@@ -355,6 +505,8 @@ where
 #[cfg(test)]
 mod tests {
     use super::{Config, EXTENSION};
+    use reth_prune_types::PruneModes;
+    use reth_static_file_types::{Compression, SegmentsCompression, StaticFileSegment};
     use std::time::Duration;
 
     fn with_tempdir(filename: &str, proc: fn(&std::path::Path)) {
@@ -723,4 +875,79 @@ connect_trusted_nodes_only = true
         let conf: Config = toml::from_str(trusted_nodes_only).unwrap();
         assert!(conf.peers.trusted_nodes_only);
     }
+
+    #[test]
+    fn test_conf_compaction() {
+        let conf = r#"
+[compaction]
+interval = "12h"
+min_free_percent = 10
+window = { start_hour = 22, end_hour = 4 }
+swap_timeout = "5s"
+"#;
+        let conf: Config = toml::from_str(conf).unwrap();
+        let compaction = conf.compaction.unwrap();
+        assert_eq!(compaction.interval, Duration::from_secs(12 * 60 * 60));
+        assert_eq!(compaction.min_free_percent, 10);
+        assert_eq!(compaction.swap_timeout, Duration::from_secs(5));
+
+        let window = compaction.window.unwrap();
+        assert!(window.contains(23 * 3600));
+        assert!(window.contains(86_400 + 3 * 3600));
+        assert!(!window.contains(4 * 3600));
+        assert!(!window.contains(12 * 3600));
+
+        let invalid_window = r#"
+[compaction]
+window = { start_hour = 22, end_hour = 24 }
+"#;
+        assert!(toml::from_str::<Config>(invalid_window).is_err());
+    }
+
+    #[test]
+    fn test_conf_static_files_compression() {
+        let compression = r#"
+[static_files.compression]
+headers = "zstd-with-dictionary"
+transactions = "zstd"
+"#;
+        let conf: Config = toml::from_str(compression).unwrap();
+        assert_eq!(
+            conf.static_files.compression.get(StaticFileSegment::Headers),
+            Compression::ZstdWithDictionary
+        );
+        assert_eq!(
+            conf.static_files.compression.get(StaticFileSegment::Transactions),
+            Compression::Zstd
+        );
+        // Unset segments keep their defaults
+        assert_eq!(
+            conf.static_files.compression.get(StaticFileSegment::Receipts),
+            SegmentsCompression::default().receipts
+        );
+    }
+
+    #[test]
+    fn test_conf_prune_history_retention() {
+        let conf = r#"
+[prune.segments]
+account_history = { distance = 16384 }
+storage_history = { distance = 16384 }
+account_history_retention = ["0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48"]
+storage_history_retention = [
+    "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48",
+    "0xdac17f958d2ee523a2206206994597c13d831ec7",
+]
+"#;
+        let conf: Config = toml::from_str(conf).unwrap();
+        let segments = conf.prune.unwrap().segments;
+        assert!(segments
+            .account_history_retention
+            .contains(&"0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse().unwrap()));
+        assert_eq!(segments.storage_history_retention.0.len(), 2);
+
+        // Empty retention rules are not serialized
+        let serialized = toml::to_string(&PruneModes::none()).unwrap();
+        assert!(!serialized.contains("retention"));
+    }
 }
@@ -9,4 +9,6 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 pub mod config;
-pub use config::{BodiesConfig, Config, PruneConfig};
+pub use config::{
+    BodiesConfig, CompactionConfig, Config, MaintenanceWindow, PruneConfig, StaticFilesConfig,
+};
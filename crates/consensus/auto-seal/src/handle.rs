@@ -0,0 +1,154 @@
+//! A handle to control the auto seal miner.
+
+use crate::Storage;
+use reth_primitives::{Address, SealedHeader, B256, U256};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
+
+/// Commands sent from the [`AutoSealHandle`] to the [`MiningTask`](crate::MiningTask).
+#[derive(Debug)]
+pub(crate) enum MiningCommand {
+    /// Mines a single block, optionally including ready transactions from the pool.
+    Mine {
+        /// Whether ready transactions should be included in the block.
+        include_transactions: bool,
+        /// Receives the header of the mined block, if it was mined successfully.
+        tx: oneshot::Sender<Option<SealedHeader>>,
+    },
+    /// Enables or disables mining a block as soon as transactions arrive.
+    SetAutomine(bool),
+    /// Returns whether blocks are mined as soon as transactions arrive.
+    Automine(oneshot::Sender<bool>),
+    /// Mines a block every interval, or stops interval mining if `None`.
+    SetInterval(Option<Duration>),
+}
+
+/// A handle to control block production and the timestamps of the auto seal miner.
+///
+/// This is used by the development RPC namespaces.
+#[derive(Debug, Clone)]
+pub struct AutoSealHandle {
+    /// Shared storage of the chain the miner is building.
+    storage: Storage,
+    /// Sends commands to the mining task.
+    to_task: UnboundedSender<MiningCommand>,
+}
+
+// === impl AutoSealHandle ===
+
+impl AutoSealHandle {
+    /// Creates a new handle.
+    pub(crate) const fn new(storage: Storage, to_task: UnboundedSender<MiningCommand>) -> Self {
+        Self { storage, to_task }
+    }
+
+    /// Mines a single block and waits until it's canonical.
+    ///
+    /// If `include_transactions` is false an empty block is mined. Returns `None` if the block
+    /// could not be mined.
+    pub async fn mine(&self, include_transactions: bool) -> Option<SealedHeader> {
+        let (tx, rx) = oneshot::channel();
+        self.to_task.send(MiningCommand::Mine { include_transactions, tx }).ok()?;
+        rx.await.ok().flatten()
+    }
+
+    /// Enables or disables mining a block as soon as transactions arrive.
+    pub fn set_automine(&self, enabled: bool) {
+        let _ = self.to_task.send(MiningCommand::SetAutomine(enabled));
+    }
+
+    /// Returns whether blocks are mined as soon as transactions arrive.
+    pub async fn is_automine(&self) -> bool {
+        let (tx, rx) = oneshot::channel();
+        if self.to_task.send(MiningCommand::Automine(tx)).is_err() {
+            return false
+        }
+        rx.await.unwrap_or_default()
+    }
+
+    /// Mines a block every `interval`, or stops interval mining if `None`.
+    pub fn set_interval_mining(&self, interval: Option<Duration>) {
+        let _ = self.to_task.send(MiningCommand::SetInterval(interval));
+    }
+
+    /// Moves the clock used for block timestamps forward by `seconds`.
+    ///
+    /// Returns the total offset to the wall clock in seconds.
+    pub async fn increase_time(&self, seconds: u64) -> i64 {
+        let mut storage = self.storage.write().await;
+        storage.time_offset = storage.time_offset.saturating_add_unsigned(seconds);
+        storage.time_offset
+    }
+
+    /// Sets the clock used for block timestamps to `timestamp`.
+    ///
+    /// Returns the offset to the wall clock in seconds.
+    pub async fn set_time(&self, timestamp: u64) -> i64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut storage = self.storage.write().await;
+        storage.time_offset = timestamp as i64 - now as i64;
+        storage.time_offset
+    }
+
+    /// Sets the timestamp of the next block.
+    ///
+    /// The clock continues from this timestamp for following blocks.
+    pub async fn set_next_block_timestamp(&self, timestamp: u64) {
+        self.storage.write().await.next_timestamp = Some(timestamp);
+    }
+
+    /// Sets the number of seconds between the timestamps of consecutive blocks, or removes the
+    /// interval if `None`.
+    ///
+    /// Returns whether an interval was set before.
+    pub async fn set_block_timestamp_interval(&self, interval: Option<u64>) -> bool {
+        let mut storage = self.storage.write().await;
+        std::mem::replace(&mut storage.timestamp_interval, interval).is_some()
+    }
+
+    /// Sets the beneficiary of new blocks.
+    pub async fn set_coinbase(&self, coinbase: Address) {
+        self.storage.write().await.coinbase = coinbase;
+    }
+
+    /// Sets the gas limit of new blocks.
+    pub async fn set_block_gas_limit(&self, gas_limit: u64) {
+        self.storage.write().await.gas_limit = Some(gas_limit);
+    }
+
+    /// Sets the base fee of the next block.
+    ///
+    /// This has no effect before london.
+    pub async fn set_next_block_base_fee(&self, base_fee: u64) {
+        self.storage.write().await.next_base_fee = Some(base_fee);
+    }
+
+    /// Sets the prev randao of new blocks.
+    pub async fn set_prev_randao(&self, prev_randao: B256) {
+        self.storage.write().await.prev_randao = prev_randao;
+    }
+
+    /// Takes a snapshot of the current chain and returns its id.
+    ///
+    /// The snapshot is taken of the best block, without mining a new block.
+    pub async fn snapshot(&self) -> U256 {
+        self.storage.write().await.snapshot()
+    }
+
+    /// Reverts the chain to the snapshot with the given id.
+    ///
+    /// This removes the snapshot and all snapshots taken after it. If blocks were mined after the
+    /// snapshot, an empty block is mined on top of the snapshot block which reorgs the chain.
+    /// Returns `false` if the snapshot does not exist.
+    pub async fn revert(&self, id: U256) -> bool {
+        let mut storage = self.storage.write().await;
+        let best_block = storage.best_block;
+        let Some(header) = storage.revert(id) else { return false };
+        drop(storage);
+
+        if header.number == best_block {
+            return true
+        }
+        self.mine(false).await.is_some()
+    }
+}
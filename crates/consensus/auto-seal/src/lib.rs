@@ -22,15 +22,15 @@ use reth_engine_primitives::EngineTypes;
 use reth_execution_errors::{BlockExecutionError, BlockValidationError};
 use reth_execution_types::ExecutionOutcome;
 use reth_primitives::{
-    constants::ETHEREUM_BLOCK_GAS_LIMIT, eip4844::calculate_excess_blob_gas, proofs, Block,
-    BlockBody, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Bloom, Header,
+    constants::ETHEREUM_BLOCK_GAS_LIMIT, eip4844::calculate_excess_blob_gas, proofs, Address,
+    Block, BlockBody, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Bloom, Header,
     Requests, SealedBlock, SealedHeader, TransactionSigned, Withdrawals, B256, U256,
 };
 use reth_provider::{BlockReaderIdExt, StateProviderFactory, StateRootProvider};
 use reth_revm::database::StateProviderDatabase;
 use reth_transaction_pool::TransactionPool;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
@@ -38,10 +38,12 @@ use tokio::sync::{mpsc::UnboundedSender, RwLock, RwLockReadGuard, RwLockWriteGua
 use tracing::trace;
 
 mod client;
+mod handle;
 mod mode;
 mod task;
 
 pub use crate::client::AutoSealClient;
+pub use handle::AutoSealHandle;
 pub use mode::{FixedBlockTimeMiner, MiningMode, ReadyTransactionMiner};
 use reth_evm::execute::{BlockExecutionOutput, BlockExecutorProvider, Executor};
 pub use task::MiningTask;
@@ -217,6 +219,35 @@ pub(crate) struct StorageInner {
     pub(crate) best_hash: B256,
     /// The total difficulty of the chain until this block
     pub(crate) total_difficulty: U256,
+    /// Offset in seconds applied to the wall clock when timestamping new blocks
+    pub(crate) time_offset: i64,
+    /// Timestamp of the next block, if set
+    pub(crate) next_timestamp: Option<u64>,
+    /// Snapshots of the chain that can be reverted to, by id
+    pub(crate) snapshots: BTreeMap<U256, Snapshot>,
+    /// Id of the most recent snapshot
+    pub(crate) last_snapshot_id: U256,
+    /// Seconds between the timestamps of consecutive blocks, if set
+    pub(crate) timestamp_interval: Option<u64>,
+    /// Beneficiary of new blocks
+    pub(crate) coinbase: Address,
+    /// Gas limit of new blocks, if set
+    pub(crate) gas_limit: Option<u64>,
+    /// Base fee of the next block, if set
+    pub(crate) next_base_fee: Option<u64>,
+    /// Prev randao of new blocks
+    pub(crate) prev_randao: B256,
+}
+
+/// The state of the chain at the time a snapshot was taken.
+#[derive(Debug, Clone)]
+pub(crate) struct Snapshot {
+    /// The best block at the time of the snapshot
+    pub(crate) header: SealedHeader,
+    /// The total difficulty of the chain until the best block
+    pub(crate) total_difficulty: U256,
+    /// The offset to the wall clock at the time of the snapshot
+    pub(crate) time_offset: i64,
 }
 
 // === impl StorageInner ===
@@ -239,6 +270,83 @@ impl StorageInner {
         self.headers.get(&num).cloned()
     }
 
+    /// Returns the timestamp for the next block.
+    ///
+    /// This is the wall clock adjusted by the configured offset, unless a timestamp was set for the
+    /// next block, in which case the clock continues from that timestamp. If a timestamp interval
+    /// is set, the timestamp of the best block is advanced by the interval instead.
+    pub(crate) fn next_block_timestamp(&mut self) -> u64 {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        if let Some(timestamp) = self.next_timestamp.take() {
+            self.time_offset = timestamp as i64 - now as i64;
+            return timestamp
+        }
+        if let Some((interval, parent)) =
+            self.timestamp_interval.zip(self.headers.get(&self.best_block))
+        {
+            return parent.timestamp.saturating_add(interval)
+        }
+        now.saturating_add_signed(self.time_offset)
+    }
+
+    /// Returns the gas limit of new blocks.
+    pub(crate) fn block_gas_limit(&self) -> u64 {
+        self.gas_limit.unwrap_or(ETHEREUM_BLOCK_GAS_LIMIT)
+    }
+
+    /// Records a snapshot of the best block and returns its id.
+    pub(crate) fn snapshot(&mut self) -> U256 {
+        let header = self.headers.get(&self.best_block).cloned().unwrap_or_default();
+        self.last_snapshot_id += U256::from(1);
+        self.snapshots.insert(
+            self.last_snapshot_id,
+            Snapshot {
+                header: header.seal(self.best_hash),
+                total_difficulty: self.total_difficulty,
+                time_offset: self.time_offset,
+            },
+        );
+        self.last_snapshot_id
+    }
+
+    /// Resets the best block to the snapshot with the given id, removing all blocks above it.
+    ///
+    /// The snapshot and all snapshots taken after it are discarded. Returns the header of the
+    /// snapshot block, or `None` if there's no snapshot with the given id.
+    pub(crate) fn revert(&mut self, id: U256) -> Option<SealedHeader> {
+        let snapshot = self.snapshots.remove(&id)?;
+        self.snapshots.retain(|snapshot_id, _| *snapshot_id < id);
+
+        let number = snapshot.header.number;
+        self.headers.retain(|num, _| *num <= number);
+        self.hash_to_number.retain(|_, num| *num <= number);
+        let hash_to_number = &self.hash_to_number;
+        let best_hash = snapshot.header.hash();
+        self.bodies.retain(|hash, _| *hash == best_hash || hash_to_number.contains_key(hash));
+
+        self.best_block = number;
+        self.best_hash = best_hash;
+        self.total_difficulty = snapshot.total_difficulty;
+        self.time_offset = snapshot.time_offset;
+        self.next_timestamp = None;
+
+        trace!(target: "consensus::auto", num=number, hash=?best_hash, "reverted to snapshot");
+        Some(snapshot.header)
+    }
+
+    /// Returns the header that should be marked as finalized when `head` becomes canonical.
+    ///
+    /// This is the oldest snapshot block, so that the chain can still be reorged back to it, or
+    /// `head` if there are no snapshots.
+    pub(crate) fn finalized_header(&self, head: &SealedHeader) -> SealedHeader {
+        self.snapshots
+            .values()
+            .map(|snapshot| &snapshot.header)
+            .min_by_key(|header| header.number)
+            .unwrap_or(head)
+            .clone()
+    }
+
     /// Inserts a new header+body pair
     pub(crate) fn insert_new_block(&mut self, mut header: Header, body: BlockBody) {
         header.number = self.best_block + 1;
@@ -285,7 +393,7 @@ impl StorageInner {
         let mut header = Header {
             parent_hash: self.best_hash,
             ommers_hash: proofs::calculate_ommers_root(ommers),
-            beneficiary: Default::default(),
+            beneficiary: self.coinbase,
             state_root: Default::default(),
             transactions_root: proofs::calculate_transaction_root(transactions),
             receipts_root: Default::default(),
@@ -293,10 +401,10 @@ impl StorageInner {
             logs_bloom: Default::default(),
             difficulty: U256::from(2),
             number: self.best_block + 1,
-            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            gas_limit: self.block_gas_limit(),
             gas_used: 0,
             timestamp,
-            mix_hash: Default::default(),
+            mix_hash: self.prev_randao,
             nonce: 0,
             base_fee_per_gas,
             blob_gas_used,
@@ -346,7 +454,7 @@ impl StorageInner {
         Executor: BlockExecutorProvider,
         Provider: StateProviderFactory,
     {
-        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let timestamp = self.next_block_timestamp();
 
         // if shanghai is active, include empty withdrawals
         let withdrawals =
@@ -355,7 +463,7 @@ impl StorageInner {
         let requests =
             chain_spec.is_prague_active_at_timestamp(timestamp).then_some(Requests::default());
 
-        let header = self.build_header_template(
+        let mut header = self.build_header_template(
             timestamp,
             &transactions,
             &ommers,
@@ -363,6 +471,10 @@ impl StorageInner {
             requests.as_ref(),
             &chain_spec,
         );
+        // the base fee can only be overridden once london is active
+        if let Some(base_fee) = self.next_base_fee.take() {
+            header.base_fee_per_gas = header.base_fee_per_gas.map(|_| base_fee);
+        }
 
         let block = Block {
             header,
@@ -439,3 +551,80 @@ impl StorageInner {
         Ok((new_header, execution_outcome))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_blocks(storage: &mut StorageInner, timestamps: impl IntoIterator<Item = u64>) {
+        for timestamp in timestamps {
+            storage.insert_new_block(
+                Header { timestamp, difficulty: U256::from(2), ..Default::default() },
+                BlockBody::default(),
+            );
+        }
+    }
+
+    #[test]
+    fn next_block_timestamp() {
+        let mut storage = StorageInner::default();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        // the clock continues from the timestamp set for the next block
+        storage.next_timestamp = Some(now + 1000);
+        assert_eq!(storage.next_block_timestamp(), now + 1000);
+        assert_eq!(storage.next_timestamp, None);
+        assert!((999..=1000).contains(&storage.time_offset));
+        assert!(storage.next_block_timestamp() >= now + 1000);
+
+        // the interval is applied to the timestamp of the best block
+        insert_blocks(&mut storage, [100]);
+        storage.timestamp_interval = Some(12);
+        assert_eq!(storage.next_block_timestamp(), 112);
+
+        // a timestamp set for the next block takes precedence over the interval
+        storage.next_timestamp = Some(50);
+        assert_eq!(storage.next_block_timestamp(), 50);
+        assert_eq!(storage.next_block_timestamp(), 112);
+    }
+
+    #[test]
+    fn snapshot_and_revert() {
+        let mut storage = StorageInner::default();
+        insert_blocks(&mut storage, [1, 2]);
+        let snapshot_hash = storage.best_hash;
+        let snapshot_td = storage.total_difficulty;
+
+        let first = storage.snapshot();
+        storage.time_offset = 100;
+        insert_blocks(&mut storage, [3, 4]);
+        let second = storage.snapshot();
+        insert_blocks(&mut storage, [5]);
+        assert_eq!(storage.snapshots.len(), 2);
+
+        // the oldest snapshot is kept unfinalized
+        let head = storage.headers[&storage.best_block].clone().seal(storage.best_hash);
+        assert_eq!(storage.finalized_header(&head).hash(), snapshot_hash);
+
+        // reverting removes all blocks and snapshots after the snapshot
+        let header = storage.revert(first).unwrap();
+        assert_eq!((header.number, header.hash()), (2, snapshot_hash));
+        assert_eq!((storage.best_block, storage.best_hash), (2, snapshot_hash));
+        assert_eq!(storage.total_difficulty, snapshot_td);
+        assert_eq!(storage.time_offset, 0);
+        assert_eq!(storage.headers.len(), 2);
+        assert_eq!(storage.hash_to_number.len(), 2);
+        assert_eq!(storage.bodies.len(), 2);
+        assert!(storage.snapshots.is_empty());
+
+        // reverted snapshots can't be reverted to again
+        assert_eq!(storage.revert(first), None);
+        assert_eq!(storage.revert(second), None);
+
+        // the chain continues from the snapshot block
+        insert_blocks(&mut storage, [3]);
+        assert_eq!(storage.best_block, 3);
+        assert_eq!(storage.headers[&3].parent_hash, snapshot_hash);
+        assert_eq!(storage.finalized_header(&head).hash(), head.hash());
+    }
+}
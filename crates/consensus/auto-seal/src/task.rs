@@ -1,10 +1,14 @@
-use crate::{mode::MiningMode, Storage};
+use crate::{
+    handle::{AutoSealHandle, MiningCommand},
+    mode::MiningMode,
+    Storage,
+};
 use futures_util::{future::BoxFuture, FutureExt};
 use reth_beacon_consensus::{BeaconEngineMessage, ForkchoiceStatus};
 use reth_chainspec::ChainSpec;
 use reth_engine_primitives::EngineTypes;
 use reth_evm::execute::BlockExecutorProvider;
-use reth_primitives::IntoRecoveredTransaction;
+use reth_primitives::{IntoRecoveredTransaction, SealedHeader};
 use reth_provider::{CanonChainTracker, StateProviderFactory};
 use reth_rpc_types::engine::ForkchoiceState;
 use reth_stages_api::PipelineEvent;
@@ -17,7 +21,10 @@ use std::{
     sync::Arc,
     task::{Context, Poll},
 };
-use tokio::sync::{mpsc::UnboundedSender, oneshot};
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
 use tracing::{debug, error, warn};
 
 /// A Future that listens for new ready transactions and puts new blocks into storage
@@ -34,14 +41,22 @@ pub struct MiningTask<Client, Pool: TransactionPool, Executor, Engine: EngineTyp
     storage: Storage,
     /// Pool where transactions are stored
     pool: Pool,
-    /// backlog of sets of transactions ready to be mined
-    queued: VecDeque<Vec<Arc<ValidPoolTransaction<<Pool as TransactionPool>::Transaction>>>>,
+    /// backlog of sets of transactions ready to be mined, with an optional listener for the mined
+    /// block
+    queued: VecDeque<(
+        Vec<Arc<ValidPoolTransaction<<Pool as TransactionPool>::Transaction>>>,
+        Option<oneshot::Sender<Option<SealedHeader>>>,
+    )>,
     // TODO: ideally this would just be a sender of hashes
     to_engine: UnboundedSender<BeaconEngineMessage<Engine>>,
     /// The pipeline events to listen on
     pipe_line_events: Option<EventStream<PipelineEvent>>,
     /// The type used for block execution
     block_executor: Executor,
+    /// Sender half of the command channel, handed out to [`AutoSealHandle`]s
+    to_task: UnboundedSender<MiningCommand>,
+    /// Receives commands from [`AutoSealHandle`]s
+    commands: UnboundedReceiver<MiningCommand>,
 }
 
 // === impl MiningTask ===
@@ -60,6 +75,7 @@ impl<Executor, Client, Pool: TransactionPool, Engine: EngineTypes>
         pool: Pool,
         block_executor: Executor,
     ) -> Self {
+        let (to_task, commands) = unbounded_channel();
         Self {
             chain_spec,
             client,
@@ -71,13 +87,48 @@ impl<Executor, Client, Pool: TransactionPool, Engine: EngineTypes>
             queued: Default::default(),
             pipe_line_events: None,
             block_executor,
+            to_task,
+            commands,
         }
     }
 
+    /// Returns a handle to control block production.
+    pub fn handle(&self) -> AutoSealHandle {
+        AutoSealHandle::new(self.storage.clone(), self.to_task.clone())
+    }
+
     /// Sets the pipeline events to listen on.
     pub fn set_pipeline_events(&mut self, events: EventStream<PipelineEvent>) {
         self.pipe_line_events = Some(events);
     }
+
+    /// Handles a command sent by an [`AutoSealHandle`].
+    fn on_command(&mut self, command: MiningCommand) {
+        match command {
+            MiningCommand::Mine { include_transactions, tx } => {
+                let transactions = if include_transactions {
+                    self.pool.best_transactions().collect()
+                } else {
+                    Vec::new()
+                };
+                self.queued.push_back((transactions, Some(tx)));
+            }
+            MiningCommand::SetAutomine(enabled) => match (&self.miner, enabled) {
+                (MiningMode::Auto(_), true) => {}
+                (_, true) => {
+                    self.miner = MiningMode::instant(1, self.pool.pending_transactions_listener())
+                }
+                (MiningMode::Auto(_), false) => self.miner = MiningMode::None,
+                (_, false) => {}
+            },
+            MiningCommand::Automine(tx) => {
+                let _ = tx.send(matches!(self.miner, MiningMode::Auto(_)));
+            }
+            MiningCommand::SetInterval(interval) => {
+                self.miner = interval.map(MiningMode::interval).unwrap_or(MiningMode::None)
+            }
+        }
+    }
 }
 
 impl<Executor, Client, Pool, Engine> Future for MiningTask<Client, Pool, Executor, Engine>
@@ -95,9 +146,13 @@ where
 
         // this drives block production and
         loop {
+            while let Poll::Ready(Some(command)) = this.commands.poll_recv(cx) {
+                this.on_command(command);
+            }
+
             if let Poll::Ready(transactions) = this.miner.poll(&this.pool, cx) {
                 // miner returned a set of transaction that we feed to the producer
-                this.queued.push_back(transactions);
+                this.queued.push_back((transactions, None));
             }
 
             if this.insert_task.is_none() {
@@ -108,7 +163,7 @@ where
 
                 // ready to queue in new insert task
                 let storage = this.storage.clone();
-                let transactions = this.queued.pop_front().expect("not empty");
+                let (transactions, mined_tx) = this.queued.pop_front().expect("not empty");
 
                 let to_engine = this.to_engine.clone();
                 let client = this.client.clone();
@@ -122,14 +177,24 @@ where
                 this.insert_task = Some(Box::pin(async move {
                     let mut storage = storage.write().await;
 
+                    // only include the transactions that fit into the block, the remaining ones
+                    // stay in the pool
+                    let gas_limit = storage.block_gas_limit();
+                    let mut cumulative_gas_limit = 0u64;
                     let transactions: Vec<_> = transactions
                         .into_iter()
+                        .take_while(|tx| {
+                            cumulative_gas_limit =
+                                cumulative_gas_limit.saturating_add(tx.gas_limit());
+                            cumulative_gas_limit <= gas_limit
+                        })
                         .map(|tx| {
                             let recovered = tx.to_recovered_transaction();
                             recovered.into_signed()
                         })
                         .collect();
                     let ommers = vec![];
+                    let mut mined = None;
 
                     match storage.build_and_execute(
                         transactions.clone(),
@@ -144,10 +209,13 @@ where
                                 transactions.iter().map(|tx| tx.hash()).collect(),
                             );
 
+                            // keep the oldest snapshot unfinalized so the chain can be reverted to
+                            // it
+                            let finalized = storage.finalized_header(&new_header);
                             let state = ForkchoiceState {
                                 head_block_hash: new_header.hash(),
-                                finalized_block_hash: new_header.hash(),
-                                safe_block_hash: new_header.hash(),
+                                finalized_block_hash: finalized.hash(),
+                                safe_block_hash: finalized.hash(),
                             };
                             drop(storage);
 
@@ -188,14 +256,19 @@ where
 
                             // update canon chain for rpc
                             client.set_canonical_head(new_header.clone());
-                            client.set_safe(new_header.clone());
-                            client.set_finalized(new_header.clone());
+                            client.set_safe(finalized.clone());
+                            client.set_finalized(finalized);
+                            mined = Some(new_header);
                         }
                         Err(err) => {
                             warn!(target: "consensus::auto", %err, "failed to execute block")
                         }
                     }
 
+                    if let Some(tx) = mined_tx {
+                        let _ = tx.send(mined);
+                    }
+
                     events
                 }));
             }
@@ -0,0 +1,445 @@
+//! Database compaction hook for the engine implementation.
+
+use crate::{
+    engine::hooks::{EngineHook, EngineHookContext, EngineHookError, EngineHookEvent},
+    hooks::EngineHookDBAccessLevel,
+};
+use futures::FutureExt;
+use metrics::{Counter, Gauge, Histogram};
+use reth_config::CompactionConfig;
+use reth_db_api::{
+    database::{CompactedCopy, CompactionOutcome, DatabaseCompact},
+    DatabaseError,
+};
+use reth_errors::{RethError, RethResult};
+use reth_tasks::TaskSpawner;
+use std::{
+    fmt,
+    task::{ready, Context, Poll},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::oneshot;
+use tracing::{debug, error};
+
+/// Minimum time between two checks of the database free space, which traverses the freelist.
+const FREE_SPACE_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Manages scheduled database compactions under the control of the engine.
+///
+/// The database is compacted once the [interval][`CompactionConfig::interval`] has passed since
+/// the last compaction, the current time is inside the
+/// [maintenance window][`CompactionConfig::window`] and enough of the database file is free.
+///
+/// The compacted copy is written by [`DatabaseCompact::copy_compacted`] with read-only database
+/// access, so it doesn't block the engine. Only the swap of the copy with
+/// [`DatabaseCompact::swap_compacted`] needs read-write access. The copy is reconciled with the
+/// database when it's swapped in: if the engine committed blocks while it was written, the copy is
+/// stale and discarded, and the compaction is retried at the next free space check.
+pub struct CompactHook<DB> {
+    /// The current state of the compaction.
+    state: CompactState<DB>,
+    /// The compaction schedule.
+    config: CompactionConfig,
+    /// When the last compaction was started, if any.
+    last_run: Option<Instant>,
+    /// When the database free space was last checked, if any.
+    last_check: Option<Instant>,
+    /// Minimum time between two checks of the database free space.
+    check_interval: Duration,
+    /// The type that can spawn the compaction task.
+    task_spawner: Box<dyn TaskSpawner>,
+    metrics: Metrics,
+}
+
+impl<DB: fmt::Debug> fmt::Debug for CompactHook<DB> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompactHook")
+            .field("state", &self.state)
+            .field("config", &self.config)
+            .field("last_run", &self.last_run)
+            .field("last_check", &self.last_check)
+            .field("check_interval", &self.check_interval)
+            .field("metrics", &self.metrics)
+            .finish()
+    }
+}
+
+impl<DB: DatabaseCompact + Send + Sync + 'static> CompactHook<DB> {
+    /// Create a new instance
+    pub fn new(db: DB, config: CompactionConfig, task_spawner: Box<dyn TaskSpawner>) -> Self {
+        Self {
+            state: CompactState::Idle(Some(db)),
+            config,
+            last_run: None,
+            last_check: None,
+            check_interval: FREE_SPACE_CHECK_INTERVAL,
+            task_spawner,
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Advances the compaction state.
+    ///
+    /// This checks for the result of the running copy or swap in the channel, or returns pending
+    /// if no task is running.
+    fn poll_compaction(&mut self, cx: &mut Context<'_>) -> Poll<RethResult<EngineHookEvent>> {
+        let event = match self.state {
+            CompactState::Idle(_) | CompactState::Copied(_) => return Poll::Pending,
+            CompactState::Copying(ref mut fut) => match ready!(fut.poll_unpin(cx)) {
+                Ok((db, Ok(copy))) => {
+                    debug!(
+                        target: "consensus::engine::hooks::compact",
+                        size_before = copy.size_before,
+                        size_after = copy.size_after,
+                        "Copied database without free pages"
+                    );
+                    self.state = CompactState::Copied(Some((db, copy)));
+                    EngineHookEvent::NotReady
+                }
+                Ok((db, Err(error))) => {
+                    // Not reported as finished, the hook never had read-write access
+                    error!(target: "consensus::engine::hooks::compact", %error, "Failed to copy database");
+                    self.metrics.failures_total.increment(1);
+                    self.state = CompactState::Idle(Some(db));
+                    EngineHookEvent::NotReady
+                }
+                Err(_) => {
+                    // failed to receive the database
+                    error!(target: "consensus::engine::hooks::compact", "Database copy task channel closed");
+                    self.state = CompactState::Idle(None);
+                    EngineHookEvent::NotReady
+                }
+            },
+            CompactState::Swapping(ref mut fut) => match ready!(fut.poll_unpin(cx)) {
+                Ok((db, result)) => {
+                    self.state = CompactState::Idle(Some(db));
+
+                    match result {
+                        Ok(outcome) => {
+                            self.metrics.record(&outcome);
+                            EngineHookEvent::Finished(Ok(()))
+                        }
+                        Err(err) => {
+                            self.metrics.failures_total.increment(1);
+                            EngineHookEvent::Finished(Err(RethError::Database(err).into()))
+                        }
+                    }
+                }
+                Err(_) => {
+                    // failed to receive the database
+                    self.state = CompactState::Idle(None);
+                    EngineHookEvent::Finished(Err(EngineHookError::ChannelClosed))
+                }
+            },
+        };
+
+        Poll::Ready(Ok(event))
+    }
+
+    /// Returns `true` if the database should be compacted now.
+    fn is_compaction_needed(&mut self, db: &DB) -> bool {
+        if self.last_run.is_some_and(|last_run| last_run.elapsed() < self.config.interval) ||
+            self.last_check.is_some_and(|last_check| last_check.elapsed() < self.check_interval)
+        {
+            return false
+        }
+
+        if let Some(window) = self.config.window {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+            if !window.contains(now) {
+                return false
+            }
+        }
+
+        self.last_check = Some(Instant::now());
+
+        match db.free_space() {
+            Ok(free_space) => {
+                self.metrics.free_bytes.set(free_space.free as f64);
+                debug!(
+                    target: "consensus::engine::hooks::compact",
+                    free = free_space.free,
+                    total = free_space.total,
+                    min_free_percent = self.config.min_free_percent,
+                    "Checked database free space"
+                );
+                free_space.percent() >= self.config.min_free_percent
+            }
+            Err(error) => {
+                debug!(target: "consensus::engine::hooks::compact", %error, "Failed to read database free space");
+                false
+            }
+        }
+    }
+
+    /// This will try to spawn the next compaction task:
+    /// 1. If the compaction is idle, check if compaction is needed through
+    ///    [`CompactHook::is_compaction_needed`].
+    ///
+    ///    1.1. If compaction is needed, spawn [`DatabaseCompact::copy_compacted`] in a separate
+    ///         task. Set compaction state to [`CompactState::Copying`].
+    ///    1.2. If compaction is not needed, set compaction state back to [`CompactState::Idle`].
+    /// 2. If the database is copied, check if the copy is stale.
+    ///
+    ///    2.1. If the database was written to since the copy was taken, discard the copy with
+    ///         [`DatabaseCompact::discard_compacted`]. Set compaction state to
+    ///         [`CompactState::Idle`], so the compaction is retried at the next check.
+    ///    2.2. Otherwise, spawn [`DatabaseCompact::swap_compacted`] in a separate task. Set
+    ///         compaction state to [`CompactState::Swapping`].
+    ///
+    /// If a copy or swap is already running, do nothing.
+    fn try_spawn_compaction(&mut self) -> Option<EngineHookEvent> {
+        match &mut self.state {
+            CompactState::Idle(db) => {
+                let db = db.take()?;
+
+                if self.is_compaction_needed(&db) {
+                    let (tx, rx) = oneshot::channel();
+                    self.task_spawner.spawn_critical_blocking(
+                        "database compaction copy task",
+                        Box::pin(async move {
+                            let result = db.copy_compacted();
+                            let _ = tx.send((db, result));
+                        }),
+                    );
+                    self.metrics.runs_total.increment(1);
+                    self.last_run = Some(Instant::now());
+                    self.state = CompactState::Copying(rx);
+
+                    Some(EngineHookEvent::Started)
+                } else {
+                    self.state = CompactState::Idle(Some(db));
+                    Some(EngineHookEvent::NotReady)
+                }
+            }
+            CompactState::Copied(copied) => {
+                let (db, copy) = copied.take()?;
+
+                if db.last_txn_id().ok() != Some(copy.txn_id) {
+                    debug!(target: "consensus::engine::hooks::compact", "Database was written to during the copy, discarding it");
+                    if let Err(error) = db.discard_compacted(copy) {
+                        debug!(target: "consensus::engine::hooks::compact", %error, "Failed to discard database copy");
+                    }
+                    self.metrics.failures_total.increment(1);
+                    self.last_run = None;
+                    self.state = CompactState::Idle(Some(db));
+                    return Some(EngineHookEvent::NotReady)
+                }
+
+                let swap_timeout = self.config.swap_timeout;
+                let (tx, rx) = oneshot::channel();
+                self.task_spawner.spawn_critical_blocking(
+                    "database compaction swap task",
+                    Box::pin(async move {
+                        let result = db.swap_compacted(copy, swap_timeout);
+                        let _ = tx.send((db, result));
+                    }),
+                );
+                self.state = CompactState::Swapping(rx);
+
+                Some(EngineHookEvent::Started)
+            }
+            CompactState::Copying(_) | CompactState::Swapping(_) => None,
+        }
+    }
+}
+
+impl<DB: DatabaseCompact + Send + Sync + 'static> EngineHook for CompactHook<DB> {
+    fn name(&self) -> &'static str {
+        "Compact"
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+        _ctx: EngineHookContext,
+    ) -> Poll<RethResult<EngineHookEvent>> {
+        // Try to spawn a copy or swap
+        match self.try_spawn_compaction() {
+            Some(EngineHookEvent::NotReady) => return Poll::Pending,
+            Some(event) => return Poll::Ready(Ok(event)),
+            None => (),
+        }
+
+        // Poll compaction and check its status
+        self.poll_compaction(cx)
+    }
+
+    /// The copy is written with read-only access, so that the engine keeps writing to the
+    /// database. Starting the copy and the swap need read-write access, which makes the engine
+    /// start them only if it doesn't write to the database, and pause writes during the swap.
+    fn db_access_level(&self) -> EngineHookDBAccessLevel {
+        match self.state {
+            CompactState::Copying(_) => EngineHookDBAccessLevel::ReadOnly,
+            CompactState::Idle(_) | CompactState::Copied(_) | CompactState::Swapping(_) => {
+                EngineHookDBAccessLevel::ReadWrite
+            }
+        }
+    }
+}
+
+/// The possible compaction states within the sync controller.
+///
+/// [`CompactState::Idle`] means that the compaction is currently idle.
+/// [`CompactState::Copying`] means that the compacted copy is being written.
+/// [`CompactState::Copied`] means that the compacted copy is waiting to be swapped in.
+/// [`CompactState::Swapping`] means that the compacted copy is being swapped in.
+///
+/// NOTE: The swap is aborted if the database is written to while the copy is written, so it's only
+/// swapped in if the engine didn't commit any blocks in the meantime.
+#[derive(Debug)]
+enum CompactState<DB> {
+    /// Compaction is idle.
+    Idle(Option<DB>),
+    /// Copy is running and waiting for a response
+    Copying(oneshot::Receiver<(DB, Result<CompactedCopy, DatabaseError>)>),
+    /// Copy is written and waiting to be swapped in
+    Copied(Option<(DB, CompactedCopy)>),
+    /// Swap is running and waiting for a response
+    Swapping(oneshot::Receiver<(DB, Result<CompactionOutcome, DatabaseError>)>),
+}
+
+#[derive(reth_metrics::Metrics)]
+#[metrics(scope = "consensus.engine.compact")]
+struct Metrics {
+    /// The number of times the compaction was run.
+    runs_total: Counter,
+    /// The number of times the compaction failed or was aborted.
+    failures_total: Counter,
+    /// The total number of bytes returned to the file system by compactions.
+    reclaimed_bytes_total: Counter,
+    /// The number of bytes returned to the file system by the last compaction.
+    last_reclaimed_bytes: Gauge,
+    /// The size of the database file after the last compaction.
+    last_size_after_bytes: Gauge,
+    /// The free space of the database file when it was last checked.
+    free_bytes: Gauge,
+    /// The ratio of the database file size after and before a compaction.
+    size_ratio: Histogram,
+}
+
+impl Metrics {
+    fn record(&self, outcome: &CompactionOutcome) {
+        self.reclaimed_bytes_total.increment(outcome.reclaimed());
+        self.last_reclaimed_bytes.set(outcome.reclaimed() as f64);
+        self.last_size_after_bytes.set(outcome.size_after as f64);
+        self.free_bytes.set(0.0);
+        if outcome.size_before > 0 {
+            self.size_ratio.record(outcome.size_after as f64 / outcome.size_before as f64);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::poll;
+    use reth_config::MaintenanceWindow;
+    use reth_db::{
+        tables,
+        test_utils::{create_test_rw_db, TempDatabase},
+        DatabaseEnv,
+    };
+    use reth_db_api::{
+        database::Database,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::Header;
+    use reth_tasks::TokioTaskExecutor;
+    use std::{future::poll_fn, sync::Arc};
+
+    const CONTEXT: EngineHookContext =
+        EngineHookContext { tip_block_number: 0, finalized_block_number: Some(0) };
+
+    fn compact_hook(
+    ) -> (Arc<TempDatabase<DatabaseEnv>>, CompactHook<Arc<TempDatabase<DatabaseEnv>>>) {
+        let db = create_test_rw_db();
+
+        // Fill the database and delete most of it, so that its pages end up on the freelist
+        let tx = db.tx_mut().unwrap();
+        for number in 0..10_000 {
+            tx.put::<tables::Headers>(
+                number,
+                Header { number, extra_data: vec![0; 256].into(), ..Default::default() },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+        let tx = db.tx_mut().unwrap();
+        tx.clear::<tables::Headers>().unwrap();
+        tx.commit().unwrap();
+
+        let config = CompactionConfig {
+            interval: Duration::ZERO,
+            min_free_percent: 0,
+            window: None,
+            swap_timeout: Duration::from_secs(1),
+        };
+        let mut hook = CompactHook::new(db.clone(), config, Box::<TokioTaskExecutor>::default());
+        hook.check_interval = Duration::ZERO;
+        (db, hook)
+    }
+
+    async fn poll_hook<DB: DatabaseCompact + Send + Sync + 'static>(
+        hook: &mut CompactHook<DB>,
+    ) -> EngineHookEvent {
+        poll_fn(|cx| hook.poll(cx, CONTEXT)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn compacts_database() {
+        let (db, mut hook) = compact_hook();
+        let free_before = db.free_space().unwrap();
+
+        // The copy is written without read-write access
+        assert!(poll_hook(&mut hook).await.is_started());
+        assert!(hook.db_access_level().is_read_only());
+        assert!(matches!(poll_hook(&mut hook).await, EngineHookEvent::NotReady));
+        assert!(matches!(hook.state, CompactState::Copied(Some(_))));
+
+        // The swap needs read-write access
+        assert!(hook.db_access_level().is_read_write());
+        assert!(poll_hook(&mut hook).await.is_started());
+        assert!(matches!(poll_hook(&mut hook).await, EngineHookEvent::Finished(Ok(()))));
+        assert!(hook.db_access_level().is_read_write());
+        assert!(matches!(hook.state, CompactState::Idle(Some(_))));
+
+        assert!(db.free_space().unwrap().total < free_before.total);
+        db.tx_mut().unwrap().commit().unwrap();
+    }
+
+    #[tokio::test]
+    async fn waits_for_window() {
+        let (_db, mut hook) = compact_hook();
+        let hour = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() / 3600 % 24;
+        let next_hour = ((hour + 1) % 24) as u8;
+        hook.config.window = Some(MaintenanceWindow { start_hour: next_hour, end_hour: next_hour });
+
+        assert!(poll!(poll_fn(|cx| hook.poll(cx, CONTEXT))).is_pending());
+        assert!(matches!(hook.state, CompactState::Idle(Some(_))));
+        assert!(hook.last_check.is_none());
+    }
+
+    #[tokio::test]
+    async fn discards_stale_copy() {
+        let (db, mut hook) = compact_hook();
+
+        assert!(poll_hook(&mut hook).await.is_started());
+        assert!(matches!(poll_hook(&mut hook).await, EngineHookEvent::NotReady));
+
+        // The engine commits a block after the copy was taken
+        let tx = db.tx_mut().unwrap();
+        tx.put::<tables::Headers>(0, Header::default()).unwrap();
+        tx.commit().unwrap();
+
+        // The copy is discarded without starting the swap
+        assert!(poll!(poll_fn(|cx| hook.poll(cx, CONTEXT))).is_pending());
+        assert!(matches!(hook.state, CompactState::Idle(Some(_))));
+        assert!(!db.path().join("compact").exists());
+        assert_eq!(db.tx().unwrap().entries::<tables::Headers>().unwrap(), 1);
+
+        // The compaction is retried without waiting for the interval
+        hook.config.interval = Duration::MAX;
+        assert!(poll_hook(&mut hook).await.is_started());
+    }
+}
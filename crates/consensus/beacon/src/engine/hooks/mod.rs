@@ -8,6 +8,9 @@ use std::{
 mod controller;
 pub(crate) use controller::{EngineHooksController, PolledHook};
 
+mod compact;
+pub use compact::CompactHook;
+
 mod prune;
 pub use prune::PruneHook;
 
@@ -101,6 +101,9 @@ impl<DB: Database + 'static> StaticFileHook<DB> {
                         headers: Some(finalized_block_number),
                         receipts: Some(finalized_block_number),
                         transactions: Some(finalized_block_number),
+                        account_change_sets: Some(finalized_block_number),
+                        storage_change_sets: Some(finalized_block_number),
+                        transaction_senders: Some(finalized_block_number),
                     })?;
 
                 // Check if the moving data to static files has been requested.
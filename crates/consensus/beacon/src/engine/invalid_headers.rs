@@ -2,7 +2,8 @@ use reth_metrics::{
     metrics::{Counter, Gauge},
     Metrics,
 };
-use reth_primitives::{Header, SealedHeader, B256};
+use reth_primitives::{Header, SealedBlock, SealedHeader, B256};
+use reth_provider::BadBlocks;
 use schnellru::{ByLength, LruMap};
 use std::sync::Arc;
 use tracing::warn;
@@ -14,16 +15,25 @@ use tracing::warn;
 const INVALID_HEADER_HIT_EVICTION_THRESHOLD: u8 = 128;
 
 /// Keeps track of invalid headers.
+///
+/// Full blocks that failed validation are additionally retained in [`BadBlocks`], which is shared
+/// with the rest of the node so they can be served over RPC.
 pub(crate) struct InvalidHeaderCache {
     /// This maps a header hash to a reference to its invalid ancestor.
     headers: LruMap<B256, HeaderEntry>,
+    /// The most recent invalid blocks.
+    bad_blocks: BadBlocks,
     /// Metrics for the cache.
     metrics: InvalidHeaderCacheMetrics,
 }
 
 impl InvalidHeaderCache {
-    pub(crate) fn new(max_length: u32) -> Self {
-        Self { headers: LruMap::new(ByLength::new(max_length)), metrics: Default::default() }
+    pub(crate) fn new(max_length: u32, bad_blocks: BadBlocks) -> Self {
+        Self {
+            headers: LruMap::new(ByLength::new(max_length)),
+            bad_blocks,
+            metrics: Default::default(),
+        }
     }
 
     fn insert_entry(&mut self, hash: B256, header: Arc<Header>) {
@@ -77,6 +87,12 @@ impl InvalidHeaderCache {
             self.metrics.count.set(self.headers.len() as f64);
         }
     }
+
+    /// Inserts an invalid block into the map and retains the full block.
+    pub(crate) fn insert_block(&mut self, invalid_block: SealedBlock) {
+        self.insert(invalid_block.header.clone());
+        self.bad_blocks.insert(invalid_block);
+    }
 }
 
 struct HeaderEntry {
@@ -106,7 +122,7 @@ mod tests {
 
     #[test]
     fn test_hit_eviction() {
-        let mut cache = InvalidHeaderCache::new(10);
+        let mut cache = InvalidHeaderCache::new(10, BadBlocks::default());
         let header = Header::default().seal_slow();
         cache.insert(header.clone());
         assert_eq!(cache.headers.get(&header.hash()).unwrap().hit_count, 0);
@@ -118,4 +134,15 @@ mod tests {
 
         assert!(cache.get(&header.hash()).is_none());
     }
+
+    #[test]
+    fn test_insert_block_retains_block() {
+        let bad_blocks = BadBlocks::default();
+        let mut cache = InvalidHeaderCache::new(10, bad_blocks.clone());
+        let block = SealedBlock { header: Header::default().seal_slow(), ..Default::default() };
+        cache.insert_block(block.clone());
+
+        assert!(cache.get(&block.hash()).is_some());
+        assert_eq!(bad_blocks.blocks(), vec![Arc::new(block)]);
+    }
 }
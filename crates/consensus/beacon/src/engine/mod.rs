@@ -20,8 +20,8 @@ use reth_primitives::{
     B256,
 };
 use reth_provider::{
-    BlockIdReader, BlockReader, BlockSource, CanonChainTracker, ChainSpecProvider, ProviderError,
-    StageCheckpointReader,
+    BadBlocksProvider, BlockIdReader, BlockReader, BlockSource, CanonChainTracker,
+    ChainSpecProvider, ProviderError, StageCheckpointReader,
 };
 use reth_rpc_types::engine::{
     CancunPayloadFields, ExecutionPayload, ForkchoiceState, PayloadStatus, PayloadStatusEnum,
@@ -165,6 +165,7 @@ where
         + BlockReader
         + BlockIdReader
         + CanonChainTracker
+        + BadBlocksProvider
         + StageCheckpointReader,
     EngineT: EngineTypes,
 {
@@ -225,6 +226,7 @@ where
         + BlockReader
         + BlockIdReader
         + CanonChainTracker
+        + BadBlocksProvider
         + StageCheckpointReader
         + ChainSpecProvider
         + 'static,
@@ -300,6 +302,7 @@ where
             blockchain.chain_spec(),
             event_sender.clone(),
         );
+        let bad_blocks = blockchain.bad_blocks();
         let mut this = Self {
             sync,
             payload_validator: ExecutionPayloadValidator::new(blockchain.chain_spec()),
@@ -309,7 +312,7 @@ where
             handle: handle.clone(),
             forkchoice_state_tracker: Default::default(),
             payload_builder,
-            invalid_headers: InvalidHeaderCache::new(MAX_INVALID_HEADERS),
+            invalid_headers: InvalidHeaderCache::new(MAX_INVALID_HEADERS, bad_blocks),
             blockchain_tree_action: None,
             pending_forkchoice_update: None,
             pipeline_run_threshold,
@@ -1657,7 +1660,7 @@ where
                             self.latest_valid_hash_for_invalid_payload(block.parent_hash)?
                         };
                         // keep track of the invalid header
-                        self.invalid_headers.insert(block.header);
+                        self.invalid_headers.insert_block(block);
                         PayloadStatus::new(
                             PayloadStatusEnum::Invalid { validation_error: error.to_string() },
                             latest_valid_hash,
@@ -1766,7 +1769,7 @@ where
                             let (block, err) = err.split();
                             warn!(target: "consensus::engine", invalid_number=?block.number, invalid_hash=?block.hash(), %err, "Marking block as invalid");
 
-                            self.invalid_headers.insert(block.header);
+                            self.invalid_headers.insert_block(block);
                         }
                     }
                 }
@@ -1791,6 +1794,7 @@ where
         + BlockReader
         + BlockIdReader
         + CanonChainTracker
+        + BadBlocksProvider
         + StageCheckpointReader
         + ChainSpecProvider
         + Unpin
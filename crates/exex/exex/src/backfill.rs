@@ -0,0 +1,490 @@
+//! Re-execution of historical blocks for `ExEx`'s.
+
+use crate::ExExNotification;
+use eyre::OptionExt;
+use futures_util::{stream::FuturesOrdered, Stream, StreamExt};
+use reth_evm::execute::{BatchExecutor, BlockExecutorProvider};
+use reth_primitives::BlockNumber;
+use reth_provider::{BlockReader, Chain, ChainSplit, HeaderProvider, StateProviderFactory};
+use reth_prune_types::PruneModes;
+use reth_revm::database::StateProviderDatabase;
+use reth_tracing::tracing::debug;
+use std::{
+    num::NonZeroUsize,
+    ops::RangeInclusive,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    thread::available_parallelism,
+};
+use tokio::{sync::mpsc::Receiver, task::JoinHandle};
+
+/// The default number of blocks executed in a single batch.
+const DEFAULT_BATCH_SIZE: u64 = 100;
+
+/// Creates [`BackfillJob`]s that re-execute historical blocks with a [`BlockExecutorProvider`].
+#[derive(Debug, Clone)]
+pub struct BackfillJobFactory<E, P> {
+    executor: E,
+    provider: P,
+    prune_modes: PruneModes,
+    batch_size: u64,
+    parallelism: usize,
+}
+
+impl<E, P> BackfillJobFactory<E, P> {
+    /// Creates a new [`BackfillJobFactory`].
+    ///
+    /// By default, batches of 100 blocks are executed on as many threads as there are available
+    /// cores.
+    pub fn new(executor: E, provider: P) -> Self {
+        Self {
+            executor,
+            provider,
+            prune_modes: PruneModes::none(),
+            batch_size: DEFAULT_BATCH_SIZE,
+            parallelism: available_parallelism().map_or(1, NonZeroUsize::get),
+        }
+    }
+
+    /// Returns the provider the blocks are read from.
+    pub const fn provider(&self) -> &P {
+        &self.provider
+    }
+
+    /// Sets the prune modes passed to the batch executors.
+    pub fn with_prune_modes(mut self, prune_modes: PruneModes) -> Self {
+        self.prune_modes = prune_modes;
+        self
+    }
+
+    /// Sets the number of blocks executed in a single batch.
+    ///
+    /// Each batch is yielded as a single [`Chain`] with the execution outcome of all its blocks.
+    pub fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the maximum number of batches executed in parallel.
+    pub fn with_parallelism(mut self, parallelism: usize) -> Self {
+        self.parallelism = parallelism.max(1);
+        self
+    }
+}
+
+impl<E: Clone, P: Clone> BackfillJobFactory<E, P> {
+    /// Creates a new [`BackfillJob`] for the given block range.
+    pub fn backfill(&self, range: RangeInclusive<BlockNumber>) -> BackfillJob<E, P> {
+        BackfillJob {
+            executor: self.executor.clone(),
+            provider: self.provider.clone(),
+            prune_modes: self.prune_modes.clone(),
+            batches: batches(range, self.batch_size),
+            parallelism: self.parallelism,
+            tasks: FuturesOrdered::new(),
+        }
+    }
+}
+
+/// Re-executes a range of historical blocks in batches.
+///
+/// Each batch is executed on top of the historical state at its first block, so batches are
+/// independent of each other and are executed in parallel on blocking threads. The executed
+/// batches are yielded in order as [`Chain`]s, and can be turned into
+/// [`ExExNotification::ChainCommitted`] notifications.
+#[derive(Debug)]
+pub struct BackfillJob<E, P> {
+    executor: E,
+    provider: P,
+    prune_modes: PruneModes,
+    /// The batches that haven't been spawned yet.
+    batches: std::vec::IntoIter<RangeInclusive<BlockNumber>>,
+    /// Maximum number of spawned batches.
+    parallelism: usize,
+    /// The spawned batches, in block order.
+    tasks: FuturesOrdered<JoinHandle<eyre::Result<Chain>>>,
+}
+
+impl<E, P> BackfillJob<E, P>
+where
+    E: BlockExecutorProvider,
+    P: HeaderProvider + BlockReader + StateProviderFactory + Clone + Send + Sync + 'static,
+{
+    /// Spawns batches until the parallelism limit is reached.
+    fn spawn_batches(&mut self) {
+        while self.tasks.len() < self.parallelism {
+            let Some(range) = self.batches.next() else { break };
+
+            let executor = self.executor.clone();
+            let provider = self.provider.clone();
+            let prune_modes = self.prune_modes.clone();
+            self.tasks.push_back(tokio::task::spawn_blocking(move || {
+                execute_range(&executor, &provider, prune_modes, range)
+            }));
+        }
+    }
+}
+
+impl<E, P> Stream for BackfillJob<E, P>
+where
+    E: BlockExecutorProvider,
+    P: HeaderProvider + BlockReader + StateProviderFactory + Clone + Send + Sync + Unpin + 'static,
+{
+    type Item = eyre::Result<Chain>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        this.spawn_batches();
+
+        let result = ready!(this.tasks.poll_next_unpin(cx));
+        // Keep the parallelism limit saturated while the yielded batch is consumed
+        this.spawn_batches();
+
+        Poll::Ready(
+            result.map(|result| result.map_err(eyre::Report::from).and_then(|result| result)),
+        )
+    }
+}
+
+/// Executes the blocks in the range on top of the historical state at its first block.
+fn execute_range<E, P>(
+    executor: &E,
+    provider: &P,
+    prune_modes: PruneModes,
+    range: RangeInclusive<BlockNumber>,
+) -> eyre::Result<Chain>
+where
+    E: BlockExecutorProvider,
+    P: HeaderProvider + BlockReader + StateProviderFactory,
+{
+    eyre::ensure!(*range.start() > 0, "the genesis block can't be executed");
+    debug!(target: "exex::backfill", ?range, "Executing block range");
+
+    let db = StateProviderDatabase::new(provider.history_by_block_number(range.start() - 1)?);
+    let mut executor = executor.batch_executor(db, prune_modes);
+    executor.set_tip(*range.end());
+
+    let blocks = provider.sealed_block_with_senders_range(range.clone())?;
+    eyre::ensure!(
+        blocks.len() as u64 == range.end() - range.start() + 1,
+        "missing blocks in range {range:?}"
+    );
+
+    for block in &blocks {
+        let td = provider
+            .header_td_by_number(block.number)?
+            .ok_or_eyre(format!("missing total difficulty of block {}", block.number))?;
+        executor.execute_and_verify_one((&block.clone().unseal(), td).into())?;
+    }
+
+    Ok(Chain::new(blocks, executor.finalize(), None))
+}
+
+/// Splits the range into consecutive ranges of at most `batch_size` blocks.
+fn batches(
+    range: RangeInclusive<BlockNumber>,
+    batch_size: u64,
+) -> std::vec::IntoIter<RangeInclusive<BlockNumber>> {
+    let (start, end) = range.into_inner();
+    let mut batches = Vec::new();
+    let mut batch_start = start;
+    while batch_start <= end {
+        let batch_end = batch_start.saturating_add(batch_size - 1).min(end);
+        batches.push(batch_start..=batch_end);
+        if batch_end == BlockNumber::MAX {
+            break
+        }
+        batch_start = batch_end + 1;
+    }
+    batches.into_iter()
+}
+
+/// Stream of [`ExExNotification`]s that yields the backfilled blocks as
+/// [`ExExNotification::ChainCommitted`] notifications, and then the live notifications of the
+/// `ExEx`.
+///
+/// The blocks of live [`ExExNotification::ChainCommitted`] notifications at or below the last
+/// backfilled block are skipped, because they were already yielded. Live notifications
+/// that are received while backfilling are held back in the [`ExExManager`](crate::ExExManager)
+/// buffer.
+#[derive(Debug)]
+pub struct BackfillThenLiveStream<'a, E, P> {
+    /// The backfill job, until it's finished.
+    backfill: Option<BackfillJob<E, P>>,
+    /// The last block of the backfilled range.
+    backfilled_height: BlockNumber,
+    /// The live notifications of the `ExEx`.
+    live: &'a mut Receiver<ExExNotification>,
+}
+
+impl<'a, E, P> BackfillThenLiveStream<'a, E, P> {
+    /// Creates a new stream that yields the blocks of the backfill job for the given range, and
+    /// then the live notifications.
+    pub fn new(
+        backfill: BackfillJob<E, P>,
+        range: &RangeInclusive<BlockNumber>,
+        live: &'a mut Receiver<ExExNotification>,
+    ) -> Self {
+        Self { backfill: Some(backfill), backfilled_height: *range.end(), live }
+    }
+}
+
+impl<E, P> Stream for BackfillThenLiveStream<'_, E, P>
+where
+    E: BlockExecutorProvider,
+    P: HeaderProvider + BlockReader + StateProviderFactory + Clone + Send + Sync + Unpin + 'static,
+{
+    type Item = eyre::Result<ExExNotification>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if let Some(backfill) = &mut this.backfill {
+            match ready!(backfill.poll_next_unpin(cx)) {
+                Some(result) => {
+                    return Poll::Ready(Some(
+                        result
+                            .map(|chain| ExExNotification::ChainCommitted { new: Arc::new(chain) }),
+                    ))
+                }
+                None => {
+                    debug!(target: "exex::backfill", backfilled_height = this.backfilled_height, "Backfill finished, switching to live notifications");
+                    this.backfill = None;
+                }
+            }
+        }
+
+        loop {
+            let Some(notification) = ready!(this.live.poll_recv(cx)) else {
+                return Poll::Ready(None)
+            };
+
+            if let Some(notification) = skip_backfilled(notification, this.backfilled_height) {
+                return Poll::Ready(Some(Ok(notification)))
+            }
+        }
+    }
+}
+
+/// Removes the blocks at or below the backfilled height from a live
+/// [`ExExNotification::ChainCommitted`] notification, because they were already yielded.
+///
+/// Returns `None` if no blocks are left.
+pub(crate) fn skip_backfilled(
+    notification: ExExNotification,
+    backfilled_height: BlockNumber,
+) -> Option<ExExNotification> {
+    let ExExNotification::ChainCommitted { new } = notification else { return Some(notification) };
+
+    if new.tip().number <= backfilled_height {
+        return None
+    }
+    if new.first().number > backfilled_height {
+        return Some(ExExNotification::ChainCommitted { new })
+    }
+
+    let new = match Arc::unwrap_or_clone(new).split(backfilled_height.into()) {
+        ChainSplit::Split { pending, .. } => pending,
+        ChainSplit::NoSplitPending(chain) | ChainSplit::NoSplitCanonical(chain) => chain,
+    };
+    Some(ExExNotification::ChainCommitted { new: Arc::new(new) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::TryStreamExt;
+    use reth_blockchain_tree::noop::NoopBlockchainTree;
+    use reth_chainspec::{ChainSpec, ChainSpecBuilder, MAINNET};
+    use reth_db::{test_utils::TempDatabase, DatabaseEnv};
+    use reth_db_common::init::init_genesis;
+    use reth_evm_ethereum::execute::EthExecutorProvider;
+    use reth_primitives::{
+        constants::{EMPTY_OMMER_ROOT_HASH, ETHEREUM_BLOCK_GAS_LIMIT, ETH_TO_WEI},
+        proofs, Address, Genesis, Header, SealedBlock, SealedBlockWithSenders, Transaction, TxKind,
+        TxLegacy, U256,
+    };
+    use reth_provider::{
+        providers::BlockchainProvider, test_utils::create_test_provider_factory_with_chain_spec,
+        BlockWriter, ExecutionOutcome,
+    };
+    use reth_testing_utils::{
+        generators::{self, sign_tx_with_key_pair},
+        GenesisAllocator,
+    };
+    use tokio::sync::mpsc;
+
+    /// Blocks with a transfer each, and the outcome of executing them.
+    struct TestChain {
+        chain_spec: Arc<ChainSpec>,
+        provider: BlockchainProvider<Arc<TempDatabase<DatabaseEnv>>>,
+        blocks: Vec<SealedBlockWithSenders>,
+        outcome: ExecutionOutcome,
+        sender: Address,
+    }
+
+    /// Executes and inserts three blocks on top of genesis.
+    fn test_chain() -> TestChain {
+        let mut rng = generators::rng();
+        let mut allocator = GenesisAllocator::default().with_rng(&mut rng);
+        let (key_pair, sender) = allocator.new_funded_account(U256::from(ETH_TO_WEI));
+        let genesis = Genesis { gas_limit: ETHEREUM_BLOCK_GAS_LIMIT as u128, ..Default::default() }
+            .extend_accounts(allocator.build());
+        // pre-Byzantium blocks don't commit to their receipts, so only the gas used must match
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::default()
+                .chain(MAINNET.chain)
+                .genesis(genesis)
+                .frontier_activated()
+                .build(),
+        );
+
+        let factory = create_test_provider_factory_with_chain_spec(chain_spec.clone());
+        init_genesis(factory.clone()).unwrap();
+
+        let mut parent = chain_spec.sealed_genesis_header();
+        let mut blocks = Vec::new();
+        for nonce in 0..3 {
+            let transfer = sign_tx_with_key_pair(
+                key_pair,
+                Transaction::Legacy(TxLegacy {
+                    nonce,
+                    gas_price: 1,
+                    gas_limit: 21_000,
+                    to: TxKind::Call(Address::repeat_byte(0xaa)),
+                    value: U256::from(1),
+                    ..Default::default()
+                }),
+            );
+            let header = Header {
+                parent_hash: parent.hash(),
+                number: parent.number + 1,
+                gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+                gas_used: 21_000,
+                timestamp: parent.timestamp + 12,
+                ommers_hash: EMPTY_OMMER_ROOT_HASH,
+                transactions_root: proofs::calculate_transaction_root(&[&transfer]),
+                ..Default::default()
+            }
+            .seal_slow();
+            parent = header.clone();
+            blocks.push(
+                SealedBlock {
+                    header,
+                    body: vec![transfer],
+                    ommers: Vec::new(),
+                    withdrawals: None,
+                    requests: None,
+                }
+                .try_seal_with_senders()
+                .unwrap(),
+            );
+        }
+
+        let executor_provider = EthExecutorProvider::ethereum(chain_spec.clone());
+        let mut executor = executor_provider.batch_executor(
+            StateProviderDatabase::new(factory.latest().unwrap()),
+            PruneModes::none(),
+        );
+        for block in &blocks {
+            executor.execute_and_verify_one((&block.clone().unseal(), U256::ZERO).into()).unwrap();
+        }
+        let outcome = executor.finalize();
+
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw
+            .append_blocks_with_state(
+                blocks.clone(),
+                outcome.clone(),
+                outcome.hash_state_slow(),
+                Default::default(),
+                None,
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider =
+            BlockchainProvider::new(factory, Arc::new(NoopBlockchainTree::default())).unwrap();
+        TestChain { chain_spec, provider, blocks, outcome, sender }
+    }
+
+    /// Returns the block numbers of the committed chain of the notification.
+    fn committed_blocks(notification: &ExExNotification) -> Vec<BlockNumber> {
+        notification.committed_chain().unwrap().blocks().keys().copied().collect()
+    }
+
+    #[tokio::test]
+    async fn backfill_executes_blocks_in_batches() {
+        let TestChain { chain_spec, provider, blocks, outcome, sender } = test_chain();
+        let factory = BackfillJobFactory::new(EthExecutorProvider::ethereum(chain_spec), provider)
+            .with_batch_size(2);
+
+        let chains = factory.backfill(1..=3).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(
+            chains
+                .iter()
+                .map(|chain| chain.blocks().keys().copied().collect())
+                .collect::<Vec<Vec<_>>>(),
+            vec![vec![1, 2], vec![3]]
+        );
+        for block in &blocks {
+            let chain =
+                chains.iter().find(|chain| chain.blocks().contains_key(&block.number)).unwrap();
+            assert_eq!(chain.blocks()[&block.number], *block);
+            assert_eq!(
+                chain.execution_outcome().receipts_by_block(block.number),
+                outcome.receipts_by_block(block.number)
+            );
+        }
+        // the last batch is executed on top of the historical state of the previous batch
+        assert_eq!(chains[1].execution_outcome().account(&sender), outcome.account(&sender));
+
+        // a single batch reproduces the original execution
+        let chains =
+            factory.with_batch_size(3).backfill(1..=3).try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(chains.len(), 1);
+        assert_eq!(*chains[0].execution_outcome(), outcome);
+    }
+
+    #[tokio::test]
+    async fn backfill_then_live_trims_backfilled_blocks() {
+        let TestChain { chain_spec, provider, outcome, .. } = test_chain();
+        let factory = BackfillJobFactory::new(EthExecutorProvider::ethereum(chain_spec), provider);
+
+        // live notifications of blocks that were backfilled, either fully or in part
+        let below = factory.backfill(1..=1).try_next().await.unwrap().unwrap();
+        let straddling = factory.backfill(2..=3).try_next().await.unwrap().unwrap();
+        let (live_tx, mut live_rx) = mpsc::channel(2);
+        live_tx.send(ExExNotification::ChainCommitted { new: Arc::new(below) }).await.unwrap();
+        live_tx.send(ExExNotification::ChainCommitted { new: Arc::new(straddling) }).await.unwrap();
+        drop(live_tx);
+
+        let range = 1..=2;
+        let notifications =
+            BackfillThenLiveStream::new(factory.backfill(range.clone()), &range, &mut live_rx)
+                .try_collect::<Vec<_>>()
+                .await
+                .unwrap();
+        assert_eq!(
+            notifications.iter().map(committed_blocks).collect::<Vec<_>>(),
+            vec![vec![1, 2], vec![3]]
+        );
+
+        let trimmed = notifications[1].committed_chain().unwrap();
+        assert_eq!(trimmed.execution_outcome().first_block(), 3);
+        assert_eq!(trimmed.execution_outcome().receipts_by_block(3), outcome.receipts_by_block(3));
+    }
+
+    #[test]
+    fn batches_cover_range() {
+        assert_eq!(batches(1..=10, 4).collect::<Vec<_>>(), vec![1..=4, 5..=8, 9..=10]);
+        assert_eq!(batches(1..=4, 4).collect::<Vec<_>>(), vec![1..=4]);
+        assert_eq!(batches(5..=5, 100).collect::<Vec<_>>(), vec![5..=5]);
+        assert!(batches(5..=4, 100).next().is_none());
+        assert_eq!(
+            batches(BlockNumber::MAX - 1..=BlockNumber::MAX, 1).collect::<Vec<_>>(),
+            vec![BlockNumber::MAX - 1..=BlockNumber::MAX - 1, BlockNumber::MAX..=BlockNumber::MAX]
+        );
+    }
+}
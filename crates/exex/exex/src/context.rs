@@ -1,9 +1,9 @@
-use crate::{ExExEvent, ExExNotification};
+use crate::{BackfillJobFactory, BackfillThenLiveStream, ExExEvent, ExExNotification};
 use reth_node_api::FullNodeComponents;
 use reth_node_core::node_config::NodeConfig;
-use reth_primitives::Head;
+use reth_primitives::{BlockNumber, Head};
 use reth_tasks::TaskExecutor;
-use std::fmt::Debug;
+use std::{fmt::Debug, ops::RangeInclusive};
 use tokio::sync::mpsc::{Receiver, UnboundedSender};
 
 /// Captures the context that an `ExEx` has access to.
@@ -82,4 +82,24 @@ impl<Node: FullNodeComponents> ExExContext<Node> {
     pub fn task_executor(&self) -> &TaskExecutor {
         self.components.task_executor()
     }
+
+    /// Returns a factory of backfill jobs that re-execute historical blocks with the node's
+    /// executor.
+    pub fn backfill_job_factory(&self) -> BackfillJobFactory<Node::Executor, Node::Provider> {
+        BackfillJobFactory::new(self.block_executor().clone(), self.provider().clone())
+    }
+
+    /// Re-executes the given range of historical blocks and yields them as
+    /// [`ExExNotification::ChainCommitted`] notifications, and then switches to the live
+    /// notifications.
+    ///
+    /// This is used to catch up an `ExEx` that was installed on an already synced node, e.g. with
+    /// the range from its first block to [`Self::head`].
+    pub fn backfill_then_live(
+        &mut self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> BackfillThenLiveStream<'_, Node::Executor, Node::Provider> {
+        let backfill = self.backfill_job_factory().backfill(range.clone());
+        BackfillThenLiveStream::new(backfill, &range, &mut self.notifications)
+    }
 }
@@ -34,6 +34,9 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![cfg_attr(not(test), warn(unused_crate_dependencies))]
 
+mod backfill;
+pub use backfill::*;
+
 mod context;
 pub use context::*;
 
@@ -46,6 +49,16 @@ pub use manager::*;
 mod notification;
 pub use notification::*;
 
+#[cfg(feature = "serde")]
+mod remote;
+#[cfg(feature = "serde")]
+pub use remote::*;
+
+#[cfg(feature = "serde")]
+mod wal;
+#[cfg(feature = "serde")]
+pub use wal::*;
+
 // Re-export exex types
 #[doc(inline)]
 pub use reth_exex_types::*;
@@ -1,8 +1,12 @@
 use crate::{ExExEvent, ExExNotification, FinishedExExHeight};
+#[cfg(feature = "serde")]
+use crate::{Wal, WalWriter};
 use metrics::Gauge;
 use reth_metrics::{metrics::Counter, Metrics};
 use reth_primitives::BlockNumber;
 use reth_tracing::tracing::debug;
+#[cfg(feature = "serde")]
+use std::collections::HashMap;
 use std::{
     collections::VecDeque,
     future::{poll_fn, Future},
@@ -153,6 +157,8 @@ pub struct ExExManagerMetrics {
     buffer_size: Gauge,
     /// Current number of `ExEx`'s on the node.
     num_exexs: Gauge,
+    /// Current number of notifications in the write-ahead log.
+    wal_entries: Gauge,
 }
 
 /// The execution extension manager.
@@ -194,6 +200,10 @@ pub struct ExExManager {
     /// The finished height of all `ExEx`'s.
     finished_height: watch::Sender<FinishedExExHeight>,
 
+    /// The writer of the write-ahead log of the notifications, if any.
+    #[cfg(feature = "serde")]
+    wal: Option<WalWriter>,
+
     /// A handle to the `ExEx` manager.
     handle: ExExManagerHandle,
     /// Metrics for the `ExEx` manager.
@@ -239,6 +249,9 @@ impl ExExManager {
             is_ready: is_ready_tx,
             finished_height: finished_height_tx,
 
+            #[cfg(feature = "serde")]
+            wal: None,
+
             handle: ExExManagerHandle {
                 exex_tx: handle_tx,
                 num_exexs,
@@ -251,6 +264,42 @@ impl ExExManager {
         }
     }
 
+    /// Sets the write-ahead log of the notifications.
+    ///
+    /// Every notification is appended to the log before it's sent to the `ExEx`'s, and is
+    /// removed once all `ExEx`'s have finished its height. The log is written on a dedicated
+    /// thread, and notifications are only sent once they're synced to disk.
+    ///
+    /// The notifications already in the log are buffered, and each `ExEx` is replayed the
+    /// notifications above the finished height it had last emitted. `ExEx`'s that have never
+    /// emitted a finished height are replayed the whole log.
+    #[cfg(feature = "serde")]
+    pub fn with_wal(mut self, wal: Wal) -> eyre::Result<Self> {
+        let notifications = wal.read()?;
+
+        self.min_id = notifications.first().map_or_else(|| wal.next_id(), |(id, _)| *id);
+        self.next_id = wal.next_id();
+        for exex in &mut self.exex_handles {
+            exex.finished_height = wal.finished_heights().get(&exex.id).copied();
+            exex.next_notification_id =
+                exex.finished_height.map_or(self.min_id, |height| wal.first_id_above(height));
+        }
+        if !notifications.is_empty() {
+            debug!(
+                min_id = self.min_id,
+                next_id = self.next_id,
+                "Replaying notifications from the write-ahead log"
+            );
+        }
+        self.buffer.extend(notifications);
+
+        self.metrics.wal_entries.set(wal.len() as f64);
+        self.wal = Some(WalWriter::spawn(wal)?);
+        self.update_capacity();
+
+        Ok(self)
+    }
+
     /// Returns the handle to the manager.
     pub fn handle(&self) -> ExExManagerHandle {
         self.handle.clone()
@@ -271,10 +320,51 @@ impl ExExManager {
 
     /// Pushes a new notification into the managers internal buffer, assigning the notification a
     /// unique ID.
-    fn push_notification(&mut self, notification: ExExNotification) {
+    ///
+    /// The notification is appended to the write-ahead log first, if any.
+    fn push_notification(&mut self, notification: ExExNotification) -> eyre::Result<()> {
         let next_id = self.next_id;
+        #[cfg(feature = "serde")]
+        if let Some(wal) = &self.wal {
+            wal.append(next_id, notification.clone())?;
+        }
         self.buffer.push_back((next_id, notification));
         self.next_id += 1;
+        Ok(())
+    }
+
+    /// Returns the ID following the last notification that can be sent to the `ExEx`'s, which is
+    /// the last notification that was synced to the write-ahead log, if any.
+    fn sendable_id(&self) -> usize {
+        #[cfg(feature = "serde")]
+        if let Some(wal) = &self.wal {
+            return wal.persisted_id()
+        }
+        self.next_id
+    }
+
+    /// Receives the notifications that were synced to the write-ahead log, if any.
+    #[cfg(feature = "serde")]
+    fn poll_wal(&mut self, cx: &mut Context<'_>) -> eyre::Result<()> {
+        let Some(wal) = &mut self.wal else { return Ok(()) };
+        if let Some(len) = wal.poll_written(cx)? {
+            self.metrics.wal_entries.set(len as f64);
+        }
+        Ok(())
+    }
+
+    /// Persists the finished heights of the `ExEx`'s to the write-ahead log, and removes the
+    /// notifications that all `ExEx`'s have finished.
+    #[cfg(feature = "serde")]
+    fn update_wal(&mut self, finished_height: Option<BlockNumber>) -> eyre::Result<()> {
+        let Some(wal) = &mut self.wal else { return Ok(()) };
+
+        let finished_heights = self
+            .exex_handles
+            .iter()
+            .filter_map(|exex| Some((exex.id.clone(), exex.finished_height?)))
+            .collect::<HashMap<_, _>>();
+        wal.update(finished_heights, finished_height)
     }
 }
 
@@ -290,7 +380,11 @@ impl Future for ExExManager {
                     reverted_tip = ?notification.reverted_chain().map(|chain| chain.tip().number),
                     "Received new notification"
                 );
-                self.push_notification(notification);
+                if let Err(err) = self.push_notification(notification) {
+                    // the notification could not be persisted, which is irrecoverable for the
+                    // manager
+                    return Poll::Ready(Err(err))
+                }
                 continue
             }
             break
@@ -299,6 +393,14 @@ impl Future for ExExManager {
         // update capacity
         self.update_capacity();
 
+        // receive the notifications that were written to the write-ahead log
+        #[cfg(feature = "serde")]
+        if let Err(err) = self.poll_wal(cx) {
+            // the notifications could not be persisted, which is irrecoverable for the manager
+            return Poll::Ready(Err(err))
+        }
+        let sendable_id = self.sendable_id();
+
         // advance all poll senders
         let mut min_id = usize::MAX;
         for idx in (0..self.exex_handles.len()).rev() {
@@ -310,7 +412,9 @@ impl Future for ExExManager {
                 .next_notification_id
                 .checked_sub(self.min_id)
                 .expect("exex expected notification ID outside the manager's range");
-            if let Some(notification) = self.buffer.get(notification_index) {
+            if let Some(notification) =
+                self.buffer.get(notification_index).filter(|(id, _)| *id < sendable_id)
+            {
                 if let Poll::Ready(Err(err)) = exex.send(cx, notification) {
                     // the channel was closed, which is irrecoverable for the manager
                     return Poll::Ready(Err(err.into()))
@@ -356,6 +460,12 @@ impl Future for ExExManager {
             let _ = self.finished_height.send(FinishedExExHeight::Height(finished_height));
         }
 
+        // persist the finished heights and prune the write-ahead log
+        #[cfg(feature = "serde")]
+        if let Err(err) = self.update_wal(finished_height.ok()) {
+            return Poll::Ready(Err(err))
+        }
+
         Poll::Pending
     }
 }
@@ -481,6 +591,105 @@ impl Clone for ExExManagerHandle {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    #[cfg(feature = "serde")]
+    use reth_primitives::{Header, SealedBlockWithSenders};
+    #[cfg(feature = "serde")]
+    use reth_provider::Chain;
+
+    #[cfg(feature = "serde")]
+    fn commit(number: BlockNumber) -> ExExNotification {
+        let mut block = SealedBlockWithSenders::default();
+        block.block.header = Header { number, ..Default::default() }.seal_slow();
+        ExExNotification::ChainCommitted {
+            new: Arc::new(Chain::new([block], Default::default(), None)),
+        }
+    }
+
+    /// Polls the manager until the `ExEx` receives a notification.
+    #[cfg(feature = "serde")]
+    async fn recv(
+        manager: &mut ExExManager,
+        notifications: &mut Receiver<ExExNotification>,
+    ) -> ExExNotification {
+        tokio::select! {
+            notification = notifications.recv() => notification.unwrap(),
+            result = poll_fn(|cx| Pin::new(&mut *manager).poll(cx)) => {
+                panic!("manager exited: {result:?}")
+            }
+        }
+    }
+
+    /// Polls the manager until the write-ahead log on disk has the given notifications and
+    /// finished heights.
+    #[cfg(feature = "serde")]
+    async fn wait_for_wal(
+        manager: &mut ExExManager,
+        dir: &std::path::Path,
+        notifications: Vec<(usize, ExExNotification)>,
+        finished_heights: HashMap<String, BlockNumber>,
+    ) {
+        let written = async {
+            loop {
+                let wal = Wal::new(dir).unwrap();
+                if wal.read().ok().as_ref() == Some(&notifications) &&
+                    wal.finished_heights() == &finished_heights
+                {
+                    return
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        };
+        tokio::select! {
+            _ = tokio::time::timeout(std::time::Duration::from_secs(10), written) => {}
+            result = poll_fn(|cx| Pin::new(&mut *manager).poll(cx)) => {
+                panic!("manager exited: {result:?}")
+            }
+        }
+        let wal = Wal::new(dir).unwrap();
+        assert_eq!(wal.read().unwrap(), notifications);
+        assert_eq!(wal.finished_heights(), &finished_heights);
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn replays_wal() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::new(dir.path()).unwrap();
+        wal.append(0, &commit(1)).unwrap();
+        wal.append(1, &commit(2)).unwrap();
+        wal.set_finished_heights(HashMap::from([("a".to_string(), 1)])).unwrap();
+
+        let (handle, events, mut notifications) = ExExHandle::new("a".to_string());
+        let mut manager =
+            ExExManager::new(vec![handle], 1).with_wal(Wal::new(dir.path()).unwrap()).unwrap();
+
+        // Only the notification above the finished height is replayed
+        assert_eq!(recv(&mut manager, &mut notifications).await, commit(2));
+
+        // New notifications are appended after the replayed ones, and are only sent once they're
+        // written to the log. The notification the ExEx has already finished is pruned
+        manager.handle().send(commit(3)).unwrap();
+        assert_eq!(recv(&mut manager, &mut notifications).await, commit(3));
+        wait_for_wal(
+            &mut manager,
+            dir.path(),
+            vec![(1, commit(2)), (2, commit(3))],
+            HashMap::from([("a".to_string(), 1)]),
+        )
+        .await;
+
+        // The log is pruned up to the finished height
+        events.send(ExExEvent::FinishedHeight(2)).unwrap();
+        wait_for_wal(
+            &mut manager,
+            dir.path(),
+            vec![(2, commit(3))],
+            HashMap::from([("a".to_string(), 2)]),
+        )
+        .await;
+    }
+
     #[tokio::test]
     async fn delivers_events() {}
 
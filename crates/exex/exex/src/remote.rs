@@ -0,0 +1,598 @@
+//! Execution extension that streams notifications to other processes over a local socket.
+
+use crate::{
+    backfill::skip_backfilled, notification_height, BackfillJobFactory, ExExContext, ExExEvent,
+    ExExNotification,
+};
+use futures_util::{stream, SinkExt, Stream, StreamExt, TryStreamExt};
+use interprocess::local_socket::{
+    tokio::{
+        prelude::{LocalSocketListener, LocalSocketStream},
+        RecvHalf, SendHalf,
+    },
+    traits::tokio::{Listener, Stream as _},
+    GenericFilePath, ListenerOptions, ToFsName,
+};
+use reth_evm::execute::BlockExecutorProvider;
+use reth_node_api::FullNodeComponents;
+use reth_primitives::BlockNumber;
+use reth_provider::{
+    BlockReader, HeaderProvider, ProviderResult, PruneCheckpointReader, StateProviderFactory,
+};
+use reth_prune_types::PruneSegment;
+use reth_tasks::TaskExecutor;
+use reth_tracing::tracing::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, future, pin::pin, sync::Arc};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc::{self, Receiver, UnboundedSender},
+};
+use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
+
+/// The ID of the remote `ExEx`.
+pub const REMOTE_EXEX_ID: &str = "remote";
+
+/// The number of live notifications buffered for each client.
+///
+/// Clients that fall further behind are disconnected.
+const CLIENT_BUFFER: usize = 1024;
+
+/// The maximum length of a request line.
+const MAX_REQUEST_LENGTH: usize = 1024;
+
+/// A message sent by a client of the [`RemoteExEx`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteExExRequest {
+    /// Subscribes to the notifications above the given height.
+    ///
+    /// This must be the first message of a connection.
+    Subscribe {
+        /// The height the client has processed up to.
+        from_height: BlockNumber,
+    },
+    /// Acknowledges that the client has processed all blocks up to the given height.
+    FinishedHeight {
+        /// The processed height.
+        height: BlockNumber,
+    },
+}
+
+/// A message sent by the [`RemoteExEx`] to its clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RemoteExExResponse {
+    /// A notification of the node.
+    Notification {
+        /// The notification.
+        notification: Arc<ExExNotification>,
+    },
+    /// The connection is closed because of an error.
+    Error {
+        /// The error message.
+        message: String,
+    },
+}
+
+/// An `ExEx` that streams the notifications of the node to other processes over a local socket.
+///
+/// The protocol is newline-delimited JSON. A client connects to the socket and sends a
+/// [`RemoteExExRequest::Subscribe`] message with the height it has processed up to. The server
+/// then streams [`RemoteExExResponse::Notification`] messages: first the re-executed blocks above
+/// the height up to the tip, and then the live notifications of the node. The client acknowledges
+/// processed blocks with [`RemoteExExRequest::FinishedHeight`] messages, and the node doesn't
+/// prune blocks above the lowest height acknowledged by a connected client, or above the retention
+/// floor of [`RemoteExEx::with_retention`] blocks below the tip. Clients can't subscribe from a
+/// height whose state was already pruned.
+///
+/// Clients that fall more than 1024 notifications behind are disconnected, so that a slow client
+/// can't hold back the node.
+#[derive(Debug)]
+pub struct RemoteExEx<E, P> {
+    /// The path of the local socket.
+    endpoint: String,
+    /// The head of the node at launch.
+    head: BlockNumber,
+    /// The number of blocks below the tip that are not pruned, for clients that subscribe later.
+    retention: u64,
+    /// Channel to receive the notifications of the node.
+    notifications: Receiver<ExExNotification>,
+    /// Channel to send the finished height of the clients to the node.
+    events: UnboundedSender<ExExEvent>,
+    /// Re-executes the blocks clients subscribed to below the tip.
+    backfill: BackfillJobFactory<E, P>,
+    /// Spawns the client connections.
+    task_executor: TaskExecutor,
+}
+
+impl<Node: FullNodeComponents> RemoteExEx<Node::Executor, Node::Provider> {
+    /// Creates a new [`RemoteExEx`] from the context of the `ExEx`, listening on the given
+    /// endpoint.
+    pub fn from_context(ctx: ExExContext<Node>, endpoint: impl Into<String>) -> Self {
+        Self::new(
+            endpoint,
+            ctx.head.number,
+            ctx.backfill_job_factory(),
+            ctx.task_executor().clone(),
+            ctx.notifications,
+            ctx.events,
+        )
+    }
+}
+
+impl<E, P> RemoteExEx<E, P>
+where
+    E: BlockExecutorProvider,
+    P: HeaderProvider
+        + BlockReader
+        + StateProviderFactory
+        + PruneCheckpointReader
+        + Clone
+        + Send
+        + Sync
+        + Unpin
+        + 'static,
+{
+    /// Creates a new [`RemoteExEx`].
+    pub fn new(
+        endpoint: impl Into<String>,
+        head: BlockNumber,
+        backfill: BackfillJobFactory<E, P>,
+        task_executor: TaskExecutor,
+        notifications: Receiver<ExExNotification>,
+        events: UnboundedSender<ExExEvent>,
+    ) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            head,
+            retention: 0,
+            notifications,
+            events,
+            backfill,
+            task_executor,
+        }
+    }
+
+    /// Sets the number of blocks below the tip that the node keeps for clients to subscribe from,
+    /// even if no client is connected.
+    ///
+    /// By default, the node may prune all blocks up to the tip when no client is connected.
+    pub const fn with_retention(mut self, retention: u64) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Binds the local socket.
+    fn bind(&self) -> eyre::Result<LocalSocketListener> {
+        if cfg!(unix) {
+            // ensure the file does not exist
+            if reth_fs_util::remove_file(&self.endpoint).is_ok() {
+                debug!(target: "exex::remote", endpoint = %self.endpoint, "Removed existing socket file");
+            }
+        }
+
+        let listener = self
+            .endpoint
+            .as_str()
+            .to_fs_name::<GenericFilePath>()
+            .and_then(|name| ListenerOptions::new().name(name).create_tokio())
+            .map_err(|err| {
+                eyre::eyre!("failed to listen on remote ExEx endpoint `{}`: {err}", self.endpoint)
+            })?;
+        info!(target: "exex::remote", endpoint = %self.endpoint, "Listening for remote ExEx clients");
+
+        Ok(listener)
+    }
+
+    /// Runs the `ExEx` until the node shuts down.
+    pub async fn run(mut self) -> eyre::Result<()> {
+        let listener = self.bind()?;
+
+        let (notifications_tx, _) = broadcast::channel(CLIENT_BUFFER);
+        let (heights_tx, mut heights_rx) = mpsc::unbounded_channel();
+        let mut tip = self.head;
+
+        let mut next_client_id = 0;
+        let mut heights = HashMap::new();
+        let mut finished_height = None;
+        self.send_finished_height(tip, &heights, &mut finished_height)?;
+
+        loop {
+            tokio::select! {
+                notification = self.notifications.recv() => {
+                    let Some(notification) = notification else { return Ok(()) };
+                    tip = notification_height(&notification);
+                    // there may be no clients
+                    let _ = notifications_tx.send(Arc::new(notification));
+                    self.send_finished_height(tip, &heights, &mut finished_height)?;
+                }
+                stream = listener.accept() => {
+                    let stream = match stream {
+                        Ok(stream) => stream,
+                        Err(error) => {
+                            warn!(target: "exex::remote", %error, "Failed to accept remote ExEx client");
+                            continue
+                        }
+                    };
+
+                    let client = Client {
+                        id: next_client_id,
+                        tip,
+                        live: notifications_tx.subscribe(),
+                        backfill: self.backfill.clone(),
+                        heights: heights_tx.clone(),
+                    };
+                    next_client_id += 1;
+                    self.task_executor.spawn(client.run(stream));
+                }
+                Some((client_id, height)) = heights_rx.recv() => {
+                    match height {
+                        Some(height) => heights.insert(client_id, height),
+                        None => heights.remove(&client_id),
+                    };
+                    self.send_finished_height(tip, &heights, &mut finished_height)?;
+                }
+            }
+        }
+    }
+
+    /// Sends the lowest height acknowledged by the clients, bounded by the retention floor below
+    /// the tip, to the node if it changed.
+    fn send_finished_height(
+        &self,
+        tip: BlockNumber,
+        heights: &HashMap<usize, BlockNumber>,
+        finished_height: &mut Option<BlockNumber>,
+    ) -> eyre::Result<()> {
+        let floor = tip.saturating_sub(self.retention);
+        let height = heights.values().min().map_or(floor, |height| floor.min(*height));
+        if *finished_height != Some(height) {
+            *finished_height = Some(height);
+            self.events.send(ExExEvent::FinishedHeight(height))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the lowest height that clients can subscribe from.
+///
+/// The blocks above the height are re-executed on top of its state, which is not available
+/// below the account and storage history prune checkpoints.
+fn lowest_available_height<P: PruneCheckpointReader>(provider: &P) -> ProviderResult<BlockNumber> {
+    let mut lowest = 0;
+    for segment in [PruneSegment::AccountHistory, PruneSegment::StorageHistory] {
+        if let Some(block_number) =
+            provider.get_prune_checkpoint(segment)?.and_then(|checkpoint| checkpoint.block_number)
+        {
+            lowest = lowest.max(block_number);
+        }
+    }
+    Ok(lowest)
+}
+
+/// A connection to a client of the [`RemoteExEx`].
+struct Client<E, P> {
+    id: usize,
+    /// The height of the node when the client connected.
+    tip: BlockNumber,
+    /// The live notifications, subscribed to when the client connected.
+    live: broadcast::Receiver<Arc<ExExNotification>>,
+    backfill: BackfillJobFactory<E, P>,
+    /// Channel to send the finished heights of the client, or `None` when it disconnects.
+    heights: UnboundedSender<(usize, Option<BlockNumber>)>,
+}
+
+impl<E, P> Client<E, P>
+where
+    E: BlockExecutorProvider,
+    P: HeaderProvider
+        + BlockReader
+        + StateProviderFactory
+        + PruneCheckpointReader
+        + Clone
+        + Send
+        + Sync
+        + Unpin
+        + 'static,
+{
+    async fn run(self, stream: LocalSocketStream) {
+        let id = self.id;
+        let heights = self.heights.clone();
+        debug!(target: "exex::remote", id, "Remote ExEx client connected");
+
+        let (recv, send) = stream.split();
+        let requests = FramedRead::new(recv, LinesCodec::new_with_max_length(MAX_REQUEST_LENGTH));
+        let mut responses = FramedWrite::new(send, LinesCodec::new());
+
+        if let Err(error) = self.serve(requests, &mut responses).await {
+            debug!(target: "exex::remote", id, %error, "Remote ExEx client failed");
+            if let Ok(message) =
+                serde_json::to_string(&RemoteExExResponse::Error { message: error.to_string() })
+            {
+                let _ = responses.send(message).await;
+            }
+        }
+
+        let _ = heights.send((id, None));
+        debug!(target: "exex::remote", id, "Remote ExEx client disconnected");
+    }
+
+    async fn serve<R, W>(self, requests: R, responses: &mut W) -> eyre::Result<()>
+    where
+        R: Stream<Item = Result<String, tokio_util::codec::LinesCodecError>> + Unpin,
+        W: futures_util::Sink<String, Error = tokio_util::codec::LinesCodecError> + Unpin,
+    {
+        let Self { id, tip, live, backfill, heights } = self;
+        let mut requests = requests
+            .map(|line| Ok::<_, eyre::Report>(serde_json::from_str::<RemoteExExRequest>(&line?)?));
+
+        // handshake
+        let from_height = match requests.next().await.transpose()? {
+            Some(RemoteExExRequest::Subscribe { from_height }) => from_height,
+            Some(request) => eyre::bail!("expected a subscribe request, got {request:?}"),
+            None => return Ok(()),
+        };
+        // register the height before checking the prune checkpoints, so that the history above it
+        // can't be pruned once the check passed; the height is removed again if the check fails
+        heights.send((id, Some(from_height)))?;
+        let lowest_height = lowest_available_height(backfill.provider())?;
+        if from_height < lowest_height {
+            eyre::bail!(
+                "cannot subscribe from height {from_height}, the history below {lowest_height} is pruned"
+            )
+        }
+        debug!(target: "exex::remote", id, from_height, tip, "Remote ExEx client subscribed");
+
+        // re-execute the blocks between the subscribed height and the tip, and then stream the
+        // live notifications that were not re-executed
+        let backfilled_height = from_height.max(tip);
+        let backfill = (from_height < tip)
+            .then(|| backfill.backfill(from_height + 1..=tip))
+            .into_iter()
+            .map(|job| {
+                job.map_ok(|chain| {
+                    Arc::new(ExExNotification::ChainCommitted { new: Arc::new(chain) })
+                })
+            });
+        let live = stream::unfold(live, |mut live| async move {
+            match live.recv().await {
+                Ok(notification) => Some((Ok(notification), live)),
+                Err(RecvError::Lagged(skipped)) => Some((
+                    Err(eyre::eyre!("client is too slow, {skipped} notifications skipped")),
+                    live,
+                )),
+                Err(RecvError::Closed) => None,
+            }
+        })
+        .try_filter_map(move |notification| {
+            // only chains with backfilled blocks are cloned to be trimmed
+            let notification = match notification.committed_chain() {
+                Some(chain) if chain.first().number <= backfilled_height => {
+                    skip_backfilled((*notification).clone(), backfilled_height).map(Arc::new)
+                }
+                _ => Some(notification),
+            };
+            future::ready(Ok(notification))
+        });
+        let mut notifications = pin!(stream::iter(backfill).flatten().chain(live));
+
+        loop {
+            tokio::select! {
+                notification = notifications.next() => {
+                    let Some(notification) = notification.transpose()? else { return Ok(()) };
+                    responses
+                        .send(serde_json::to_string(&RemoteExExResponse::Notification {
+                            notification,
+                        })?)
+                        .await?;
+                }
+                request = requests.next() => match request.transpose()? {
+                    Some(RemoteExExRequest::FinishedHeight { height }) => {
+                        heights.send((id, Some(height)))?
+                    }
+                    Some(request) => eyre::bail!("unexpected request {request:?}"),
+                    None => return Ok(()),
+                },
+            }
+        }
+    }
+}
+
+/// A client of the [`RemoteExEx`].
+#[derive(Debug)]
+pub struct RemoteExExClient {
+    requests: FramedWrite<SendHalf, LinesCodec>,
+    responses: FramedRead<RecvHalf, LinesCodec>,
+}
+
+impl RemoteExExClient {
+    /// Connects to the [`RemoteExEx`] at the given endpoint, and subscribes to the notifications
+    /// above the given height.
+    pub async fn connect(endpoint: &str, from_height: BlockNumber) -> eyre::Result<Self> {
+        let stream = LocalSocketStream::connect(endpoint.to_fs_name::<GenericFilePath>()?).await?;
+        let (recv, send) = stream.split();
+        let mut client = Self {
+            requests: FramedWrite::new(send, LinesCodec::new()),
+            responses: FramedRead::new(recv, LinesCodec::new()),
+        };
+        client.send(&RemoteExExRequest::Subscribe { from_height }).await?;
+
+        Ok(client)
+    }
+
+    /// Returns the next notification, or `None` if the connection was closed.
+    pub async fn next(&mut self) -> eyre::Result<Option<Arc<ExExNotification>>> {
+        let Some(line) = self.responses.next().await.transpose()? else { return Ok(None) };
+        match serde_json::from_str(&line)? {
+            RemoteExExResponse::Notification { notification } => Ok(Some(notification)),
+            RemoteExExResponse::Error { message } => Err(eyre::eyre!(message)),
+        }
+    }
+
+    /// Acknowledges that all blocks up to the given height were processed.
+    pub async fn finished_height(&mut self, height: BlockNumber) -> eyre::Result<()> {
+        self.send(&RemoteExExRequest::FinishedHeight { height }).await
+    }
+
+    async fn send(&mut self, request: &RemoteExExRequest) -> eyre::Result<()> {
+        self.requests.send(serde_json::to_string(request)?).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_blockchain_tree::noop::NoopBlockchainTree;
+    use reth_evm::test_utils::MockExecutorProvider;
+    use reth_primitives::{Header, SealedBlockWithSenders, SealedHeader};
+    use reth_provider::{
+        providers::BlockchainProvider,
+        test_utils::{create_test_provider_factory, NoopProvider},
+        Chain, PruneCheckpointWriter,
+    };
+    use reth_prune_types::{PruneCheckpoint, PruneMode};
+    use reth_tasks::TaskManager;
+    use tempfile::TempDir;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn commit(number: BlockNumber) -> ExExNotification {
+        let mut block = SealedBlockWithSenders::default();
+        block.block.header = Header { number, ..Default::default() }.seal_slow();
+        ExExNotification::ChainCommitted {
+            new: Arc::new(Chain::new([block], Default::default(), None)),
+        }
+    }
+
+    struct TestExEx {
+        _dir: TempDir,
+        _tasks: TaskManager,
+        endpoint: String,
+        notifications: mpsc::Sender<ExExNotification>,
+        events: UnboundedReceiver<ExExEvent>,
+    }
+
+    /// Spawns a [`RemoteExEx`] with the given head and retention.
+    fn spawn_exex<P>(provider: P, head: BlockNumber, retention: u64) -> TestExEx
+    where
+        P: HeaderProvider
+            + BlockReader
+            + StateProviderFactory
+            + PruneCheckpointReader
+            + Clone
+            + Send
+            + Sync
+            + Unpin
+            + 'static,
+    {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = dir.path().join("exex.ipc").to_string_lossy().into_owned();
+        let tasks = TaskManager::current();
+
+        let (notifications_tx, notifications_rx) = mpsc::channel(1);
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let exex = RemoteExEx::new(
+            endpoint.clone(),
+            head,
+            BackfillJobFactory::new(MockExecutorProvider::default(), provider),
+            tasks.executor(),
+            notifications_rx,
+            events_tx,
+        )
+        .with_retention(retention);
+        tokio::spawn(exex.run());
+
+        TestExEx {
+            _dir: dir,
+            _tasks: tasks,
+            endpoint,
+            notifications: notifications_tx,
+            events: events_rx,
+        }
+    }
+
+    /// Connects to the [`RemoteExEx`] once its socket is bound.
+    async fn connect(endpoint: &str, from_height: BlockNumber) -> RemoteExExClient {
+        loop {
+            if let Ok(client) = RemoteExExClient::connect(endpoint, from_height).await {
+                return client
+            }
+            tokio::task::yield_now().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn streams_notifications_and_acknowledges_heights() {
+        let mut exex = spawn_exex(NoopProvider::default(), 1, 0);
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(1)));
+
+        let mut client = connect(&exex.endpoint, 1).await;
+
+        // committed blocks at or below the subscribed height are skipped
+        exex.notifications.send(commit(1)).await.unwrap();
+        exex.notifications.send(commit(2)).await.unwrap();
+        assert_eq!(client.next().await.unwrap().as_deref(), Some(&commit(2)));
+
+        client.finished_height(2).await.unwrap();
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(2)));
+
+        // invalid requests close the connection with an error
+        client.send(&RemoteExExRequest::Subscribe { from_height: 0 }).await.unwrap();
+        assert!(client.next().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn finished_height_follows_retention_without_clients() {
+        let mut exex = spawn_exex(NoopProvider::default(), 5, 2);
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(3)));
+
+        exex.notifications.send(commit(6)).await.unwrap();
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(4)));
+
+        // a client holds the finished height below the retention floor
+        let mut client = connect(&exex.endpoint, 6).await;
+        client.finished_height(3).await.unwrap();
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(3)));
+        client.finished_height(6).await.unwrap();
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(4)));
+
+        // and releases it when it disconnects
+        drop(client);
+        exex.notifications.send(commit(7)).await.unwrap();
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(5)));
+    }
+
+    #[tokio::test]
+    async fn rejects_subscriptions_below_pruned_history() {
+        let factory = create_test_provider_factory();
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw
+            .save_prune_checkpoint(
+                PruneSegment::StorageHistory,
+                PruneCheckpoint {
+                    block_number: Some(5),
+                    tx_number: None,
+                    prune_mode: PruneMode::Before(6),
+                },
+            )
+            .unwrap();
+        provider_rw.commit().unwrap();
+        let provider = BlockchainProvider::with_latest(
+            factory,
+            Arc::new(NoopBlockchainTree::default()),
+            SealedHeader::default(),
+        );
+
+        let mut exex = spawn_exex(provider, 10, 0);
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(10)));
+
+        let mut client = connect(&exex.endpoint, 4).await;
+        let error = client.next().await.unwrap_err();
+        assert!(error.to_string().contains("history below 5 is pruned"), "{error}");
+
+        // the state at the prune checkpoint is still available
+        let _client = connect(&exex.endpoint, 5).await;
+        assert_eq!(exex.events.recv().await, Some(ExExEvent::FinishedHeight(5)));
+    }
+}
@@ -0,0 +1,385 @@
+//! Write-ahead log of [`ExExNotification`]s.
+
+use crate::ExExNotification;
+use eyre::WrapErr;
+use reth_primitives::BlockNumber;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// The extension of the notification files.
+const ENTRY_EXTENSION: &str = "wal";
+
+/// The name of the file the finished heights of the `ExEx`'s are written to.
+const FINISHED_HEIGHTS_FILE: &str = "finished_heights.json";
+
+/// Persistent write-ahead log of the notifications sent to `ExEx`'s.
+///
+/// Every notification is written to its own file before it's sent to the `ExEx`'s, and is kept
+/// until all `ExEx`'s have emitted a [`FinishedHeight`][crate::ExExEvent::FinishedHeight] event
+/// at or above its [height][`notification_height`]. The finished heights are written alongside,
+/// so that on restart each `ExEx` is replayed the notifications it hasn't acknowledged yet.
+///
+/// The files are named `<ID>-<HEIGHT>.wal`, so that the log can be indexed without decoding the
+/// notifications.
+#[derive(Debug)]
+pub struct Wal {
+    /// The directory the log is stored in.
+    dir: PathBuf,
+    /// The IDs and heights of the notifications in the log, in ID order.
+    entries: VecDeque<(usize, BlockNumber)>,
+    /// The last persisted finished heights of the `ExEx`'s.
+    finished_heights: HashMap<String, BlockNumber>,
+}
+
+impl Wal {
+    /// Opens the log in the given directory, creating it if it doesn't exist.
+    pub fn new(dir: impl Into<PathBuf>) -> eyre::Result<Self> {
+        let dir = dir.into();
+        reth_fs_util::create_dir_all(&dir)?;
+
+        let mut entries = Vec::new();
+        for entry in reth_fs_util::read_dir(&dir)? {
+            let path = entry.wrap_err("failed to read WAL directory entry")?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(ENTRY_EXTENSION) {
+                continue
+            }
+
+            let (id, height) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.split_once('-'))
+                .and_then(|(id, height)| Some((id.parse().ok()?, height.parse().ok()?)))
+                .ok_or_else(|| eyre::eyre!("invalid WAL entry file name: {path:?}"))?;
+            entries.push((id, height));
+        }
+        entries.sort_unstable();
+
+        let finished_heights_path = dir.join(FINISHED_HEIGHTS_FILE);
+        let finished_heights = if finished_heights_path.exists() {
+            serde_json::from_slice(&reth_fs_util::read(&finished_heights_path)?)
+                .wrap_err_with(|| format!("failed to decode {finished_heights_path:?}"))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self { dir, entries: entries.into(), finished_heights })
+    }
+
+    /// Returns the number of notifications in the log.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no notifications in the log.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the ID following the ID of the last notification in the log.
+    pub fn next_id(&self) -> usize {
+        self.entries.back().map_or(0, |(id, _)| id + 1)
+    }
+
+    /// Returns the persisted finished heights of the `ExEx`'s.
+    pub const fn finished_heights(&self) -> &HashMap<String, BlockNumber> {
+        &self.finished_heights
+    }
+
+    /// Returns the ID of the first notification in the log above the given height, or
+    /// [`Self::next_id`] if there is none.
+    ///
+    /// This is the notification an `ExEx` with the given finished height is replayed from.
+    pub fn first_id_above(&self, height: BlockNumber) -> usize {
+        self.entries
+            .iter()
+            .find(|(_, entry_height)| *entry_height > height)
+            .map_or_else(|| self.next_id(), |(id, _)| *id)
+    }
+
+    /// Reads and decodes all notifications in the log, in ID order.
+    pub fn read(&self) -> eyre::Result<Vec<(usize, ExExNotification)>> {
+        self.entries
+            .iter()
+            .map(|&(id, height)| {
+                let path = self.entry_path(id, height);
+                let notification = serde_json::from_slice(&reth_fs_util::read(&path)?)
+                    .wrap_err_with(|| format!("failed to decode WAL entry {path:?}"))?;
+                Ok((id, notification))
+            })
+            .collect()
+    }
+
+    /// Appends a notification to the log.
+    ///
+    /// The notification is synced to disk before this returns.
+    pub fn append(&mut self, id: usize, notification: &ExExNotification) -> eyre::Result<()> {
+        if id < self.next_id() {
+            eyre::bail!("WAL entry {id} is not above the last entry {}", self.next_id() - 1)
+        }
+
+        let height = notification_height(notification);
+        write_synced(&self.entry_path(id, height), &serde_json::to_vec(notification)?)?;
+        self.entries.push_back((id, height));
+
+        Ok(())
+    }
+
+    /// Persists the finished heights of the `ExEx`'s, if they changed.
+    pub fn set_finished_heights(
+        &mut self,
+        finished_heights: HashMap<String, BlockNumber>,
+    ) -> eyre::Result<()> {
+        if finished_heights == self.finished_heights {
+            return Ok(())
+        }
+
+        write_synced(
+            &self.dir.join(FINISHED_HEIGHTS_FILE),
+            &serde_json::to_vec(&finished_heights)?,
+        )?;
+        self.finished_heights = finished_heights;
+
+        Ok(())
+    }
+
+    /// Removes the notifications at the start of the log with a height lower than or equal to the
+    /// given finished height.
+    ///
+    /// Returns the number of removed notifications.
+    pub fn prune(&mut self, finished_height: BlockNumber) -> eyre::Result<usize> {
+        let mut pruned = 0;
+        while let Some(&(id, height)) = self.entries.front() {
+            if height > finished_height {
+                break
+            }
+
+            reth_fs_util::remove_file(self.entry_path(id, height))?;
+            self.entries.pop_front();
+            pruned += 1;
+        }
+
+        Ok(pruned)
+    }
+
+    fn entry_path(&self, id: usize, height: BlockNumber) -> PathBuf {
+        self.dir.join(format!("{id}-{height}.{ENTRY_EXTENSION}"))
+    }
+}
+
+/// Commands processed by the [`WalWriter`] thread.
+#[derive(Debug)]
+enum WalCommand {
+    /// Append the notification with the given ID to the log.
+    Append(usize, ExExNotification),
+    /// Persist the finished heights of the `ExEx`'s, and remove the notifications that all
+    /// `ExEx`'s have finished.
+    Update { finished_heights: HashMap<String, BlockNumber>, finished_height: Option<BlockNumber> },
+}
+
+/// Writes to a [`Wal`] on a dedicated thread, so that encoding the notifications and syncing them
+/// to disk doesn't block the [`ExExManager`](crate::ExExManager).
+///
+/// The writer reports the notifications that were synced to disk, which must not be sent to the
+/// `ExEx`'s before.
+#[derive(Debug)]
+pub(crate) struct WalWriter {
+    /// Sends commands to the writer thread.
+    commands: UnboundedSender<WalCommand>,
+    /// Receives the ID of the appended notification, if any, and the length of the log after each
+    /// command, or the error the writer thread stopped with.
+    events: UnboundedReceiver<eyre::Result<(Option<usize>, usize)>>,
+    /// The ID following the last notification that was synced to disk.
+    persisted_id: usize,
+    /// The finished heights last sent to the writer thread.
+    finished_heights: HashMap<String, BlockNumber>,
+    /// The finished height of all `ExEx`'s last sent to the writer thread.
+    finished_height: Option<BlockNumber>,
+}
+
+impl WalWriter {
+    /// Spawns the writer thread for the given log.
+    pub(crate) fn spawn(mut wal: Wal) -> eyre::Result<Self> {
+        let persisted_id = wal.next_id();
+        let finished_heights = wal.finished_heights().clone();
+        let (commands_tx, mut commands_rx) = mpsc::unbounded_channel();
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+
+        std::thread::Builder::new().name("exex-wal".to_string()).spawn(move || {
+            let mut finished_height = None;
+            // exits once the writer is dropped
+            while let Some(command) = commands_rx.blocking_recv() {
+                let result = match command {
+                    WalCommand::Append(id, notification) => {
+                        wal.append(id, &notification).and_then(|()| {
+                            // remove the notification right away if it's already finished
+                            finished_height.map_or(Ok(0), |height| wal.prune(height))?;
+                            Ok(Some(id))
+                        })
+                    }
+                    WalCommand::Update { finished_heights, finished_height: height } => {
+                        finished_height = height;
+                        wal.set_finished_heights(finished_heights).and_then(|()| {
+                            finished_height.map_or(Ok(0), |height| wal.prune(height))?;
+                            Ok(None)
+                        })
+                    }
+                };
+
+                let failed = result.is_err();
+                let _ = events_tx.send(result.map(|appended| (appended, wal.len())));
+                if failed {
+                    break
+                }
+            }
+        })?;
+
+        Ok(Self {
+            commands: commands_tx,
+            events: events_rx,
+            persisted_id,
+            finished_heights,
+            finished_height: None,
+        })
+    }
+
+    /// Returns the ID following the last notification that was synced to disk.
+    pub(crate) const fn persisted_id(&self) -> usize {
+        self.persisted_id
+    }
+
+    /// Appends the notification to the log.
+    pub(crate) fn append(&self, id: usize, notification: ExExNotification) -> eyre::Result<()> {
+        self.commands
+            .send(WalCommand::Append(id, notification))
+            .map_err(|_| eyre::eyre!("WAL writer thread exited"))
+    }
+
+    /// Persists the finished heights of the `ExEx`'s, and removes the notifications at or below
+    /// the finished height of all `ExEx`'s, if they changed.
+    pub(crate) fn update(
+        &mut self,
+        finished_heights: HashMap<String, BlockNumber>,
+        finished_height: Option<BlockNumber>,
+    ) -> eyre::Result<()> {
+        if finished_heights == self.finished_heights && finished_height == self.finished_height {
+            return Ok(())
+        }
+
+        self.finished_heights.clone_from(&finished_heights);
+        self.finished_height = finished_height;
+        self.commands
+            .send(WalCommand::Update { finished_heights, finished_height })
+            .map_err(|_| eyre::eyre!("WAL writer thread exited"))
+    }
+
+    /// Receives the results of the commands processed by the writer thread.
+    ///
+    /// Returns the length of the log after the last processed command, if any command was
+    /// processed.
+    pub(crate) fn poll_written(&mut self, cx: &mut Context<'_>) -> eyre::Result<Option<usize>> {
+        let mut len = None;
+        loop {
+            match self.events.poll_recv(cx) {
+                Poll::Ready(Some(result)) => {
+                    let (appended, wal_len) = result?;
+                    if let Some(id) = appended {
+                        self.persisted_id = id + 1;
+                    }
+                    len = Some(wal_len);
+                }
+                Poll::Ready(None) => eyre::bail!("WAL writer thread exited"),
+                Poll::Pending => return Ok(len),
+            }
+        }
+    }
+}
+
+/// Returns the height of the chain after the notification is applied.
+///
+/// This is the tip of the committed chain, or the parent of the first reverted block if nothing
+/// was committed.
+pub fn notification_height(notification: &ExExNotification) -> BlockNumber {
+    notification.committed_chain().map(|chain| chain.tip().number).unwrap_or_else(|| {
+        notification
+            .reverted_chain()
+            .map(|chain| chain.first().number.saturating_sub(1))
+            .unwrap_or_default()
+    })
+}
+
+/// Writes the contents to a temporary file, syncs it and renames it over the given path.
+fn write_synced(path: &Path, contents: &[u8]) -> eyre::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut file = File::create(&tmp_path)
+        .map_err(|err| reth_fs_util::FsPathError::create_file(err, &tmp_path))?;
+    file.write_all(contents).map_err(|err| reth_fs_util::FsPathError::write(err, &tmp_path))?;
+    file.sync_all().map_err(|err| reth_fs_util::FsPathError::write(err, &tmp_path))?;
+    reth_fs_util::rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::{Header, SealedBlockWithSenders};
+    use reth_provider::Chain;
+    use std::sync::Arc;
+
+    fn chain(numbers: std::ops::RangeInclusive<BlockNumber>) -> Arc<Chain> {
+        let blocks = numbers.map(|number| {
+            let mut block = SealedBlockWithSenders::default();
+            block.block.header = Header { number, ..Default::default() }.seal_slow();
+            block
+        });
+        Arc::new(Chain::new(blocks, Default::default(), None))
+    }
+
+    #[test]
+    fn append_read_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::new(dir.path()).unwrap();
+        assert!(wal.is_empty());
+
+        let notifications = vec![
+            (0, ExExNotification::ChainCommitted { new: chain(1..=2) }),
+            (1, ExExNotification::ChainReverted { old: chain(2..=2) }),
+            (2, ExExNotification::ChainCommitted { new: chain(2..=4) }),
+        ];
+        for (id, notification) in &notifications {
+            wal.append(*id, notification).unwrap();
+        }
+        assert!(wal.append(1, &notifications[0].1).is_err());
+
+        // Reopened log has the same entries
+        let mut wal = Wal::new(dir.path()).unwrap();
+        assert_eq!(wal.next_id(), 3);
+        assert_eq!(wal.read().unwrap(), notifications);
+        assert_eq!(wal.first_id_above(1), 0);
+        assert_eq!(wal.first_id_above(2), 2);
+        assert_eq!(wal.first_id_above(4), 3);
+
+        // The revert to block 1 is kept, because it's behind a commit of block 2
+        assert_eq!(wal.prune(1).unwrap(), 0);
+        assert_eq!(wal.prune(2).unwrap(), 2);
+        assert_eq!(wal.len(), 1);
+        assert_eq!(Wal::new(dir.path()).unwrap().read().unwrap(), notifications[2..]);
+    }
+
+    #[test]
+    fn finished_heights() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut wal = Wal::new(dir.path()).unwrap();
+        assert!(wal.finished_heights().is_empty());
+
+        let finished_heights = HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]);
+        wal.set_finished_heights(finished_heights.clone()).unwrap();
+        assert_eq!(Wal::new(dir.path()).unwrap().finished_heights(), &finished_heights);
+    }
+}
@@ -48,6 +48,9 @@ use std::{
 use thiserror::Error;
 use tokio::sync::mpsc::{Sender, UnboundedReceiver};
 
+mod scenario;
+pub use scenario::*;
+
 /// A test [`PoolBuilder`] that builds a [`TestPool`].
 #[derive(Debug, Default, Clone, Copy)]
 #[non_exhaustive]
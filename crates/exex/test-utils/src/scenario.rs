@@ -0,0 +1,575 @@
+//! Scenarios of chain commits, reorgs and reverts built from executed blocks.
+
+use crate::{test_exex_context_with_chain_spec, PollOnce, TestExExContext, TestExExHandle};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reth_chainspec::{ChainSpec, ChainSpecBuilder, MAINNET};
+use reth_evm::execute::{BlockExecutorProvider, Executor};
+use reth_evm_ethereum::execute::EthExecutorProvider;
+use reth_execution_types::{Chain, ExecutionOutcome};
+use reth_exex::ExExNotification;
+use reth_primitives::{
+    constants::{EMPTY_OMMER_ROOT_HASH, EMPTY_WITHDRAWALS, ETHEREUM_BLOCK_GAS_LIMIT},
+    logs_bloom,
+    proofs::{calculate_receipt_root_no_memo, calculate_transaction_root},
+    Account, Address, Block, BlockNumber, BlockWithSenders, Bytes, Genesis, Header, Receipts,
+    Requests, SealedBlockWithSenders, SealedHeader, Transaction, TxEip1559, TxKind, Withdrawals,
+    B256, U256,
+};
+use reth_revm::{database::StateProviderDatabase, db::BundleState, test_utils::StateProviderTest};
+use reth_testing_utils::{generators::sign_tx_with_key_pair, GenesisAllocator};
+use secp256k1::Keypair;
+use std::{collections::HashMap, future::Future, pin::pin, sync::Arc};
+
+/// The base fee of the generated blocks.
+const BASE_FEE: u64 = 7;
+
+/// The balance of each funded account in the genesis.
+const ACCOUNT_BALANCE: u128 = 1_000_000_000_000_000_000_000;
+
+/// The maximum number of transactions in a generated block.
+const MAX_TRANSACTIONS: usize = 8;
+
+/// Init code of a contract that writes `1` to storage slot `0` and returns runtime code that
+/// returns `42`.
+const CONTRACT_INIT_CODE: [u8; 27] = [
+    0x60, 0x01, 0x60, 0x00, 0x55, // SSTORE(0, 1)
+    0x60, 0x0a, 0x60, 0x11, 0x60, 0x00, 0x39, // CODECOPY(0, 17, 10)
+    0x60, 0x0a, 0x60, 0x00, 0xf3, // RETURN(0, 10)
+    0x60, 0x2a, 0x60, 0x00, 0x52, 0x60, 0x20, 0x60, 0x00, 0xf3, // runtime code
+];
+
+/// An in-memory chain of blocks executed with the [`EthExecutorProvider`].
+///
+/// The blocks contain random transfers between the accounts funded in the genesis, and
+/// deployments of a contract that writes to its storage. The post-execution fields of the headers
+/// (gas used, receipts root and logs bloom) are filled from the execution output, but the state
+/// root is not computed.
+///
+/// Blocks are generated from a seeded rng, so the same seed always generates the same chain.
+#[derive(Debug)]
+pub struct TestChain {
+    chain_spec: Arc<ChainSpec>,
+    executor: EthExecutorProvider,
+    rng: StdRng,
+    /// The funded accounts.
+    signers: Vec<(Keypair, Address)>,
+    /// The canonical blocks after the genesis, with their execution outcome.
+    blocks: Vec<(SealedBlockWithSenders, ExecutionOutcome)>,
+    /// The state after each canonical block, starting with the genesis.
+    states: Vec<TestState>,
+    /// The headers of the canonical chain, starting with the genesis.
+    headers: Vec<SealedHeader>,
+}
+
+impl TestChain {
+    /// Creates a new chain with the given number of funded accounts, generated from the given
+    /// seed.
+    pub fn new(seed: u64, accounts: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut allocator = GenesisAllocator::new_with_rng(&mut rng);
+        let signers = (0..accounts.max(1))
+            .map(|_| allocator.new_funded_account(U256::from(ACCOUNT_BALANCE)))
+            .collect::<Vec<_>>();
+        let genesis = Genesis::default().extend_accounts(allocator.build());
+
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::default()
+                .chain(MAINNET.chain)
+                .genesis(genesis)
+                .shanghai_activated()
+                .build(),
+        );
+        let genesis_header = chain_spec.sealed_genesis_header();
+        let state = TestState::from_genesis(chain_spec.genesis(), &genesis_header);
+
+        Self {
+            executor: EthExecutorProvider::ethereum(chain_spec.clone()),
+            chain_spec,
+            rng,
+            signers,
+            blocks: Vec::new(),
+            states: vec![state],
+            headers: vec![genesis_header],
+        }
+    }
+
+    /// Returns the chain spec with the funded accounts in the genesis.
+    pub fn chain_spec(&self) -> Arc<ChainSpec> {
+        self.chain_spec.clone()
+    }
+
+    /// Returns the number of the canonical tip.
+    pub fn tip(&self) -> BlockNumber {
+        self.headers.last().expect("genesis header").number
+    }
+
+    /// Generates and executes the given number of blocks on top of the canonical tip.
+    ///
+    /// Returns the [`ExExNotification::ChainCommitted`] notification of the new blocks.
+    pub fn commit(&mut self, blocks: usize) -> eyre::Result<ExExNotification> {
+        let new = self.extend(blocks)?;
+        Ok(ExExNotification::ChainCommitted { new: Arc::new(new) })
+    }
+
+    /// Removes the given number of blocks from the canonical tip.
+    ///
+    /// Returns the [`ExExNotification::ChainReverted`] notification of the removed blocks.
+    pub fn revert(&mut self, blocks: usize) -> eyre::Result<ExExNotification> {
+        let old = self.truncate(blocks)?;
+        Ok(ExExNotification::ChainReverted { old: Arc::new(old) })
+    }
+
+    /// Removes the given number of blocks from the canonical tip, and generates and executes the
+    /// given number of new blocks in their place.
+    ///
+    /// Returns the [`ExExNotification::ChainReorged`] notification of the removed and new blocks.
+    pub fn reorg(&mut self, depth: usize, blocks: usize) -> eyre::Result<ExExNotification> {
+        let old = self.truncate(depth)?;
+        let new = self.extend(blocks)?;
+        Ok(ExExNotification::ChainReorged { old: Arc::new(old), new: Arc::new(new) })
+    }
+
+    /// Generates and executes blocks on top of the canonical tip, and returns them as a chain.
+    fn extend(&mut self, blocks: usize) -> eyre::Result<Chain> {
+        eyre::ensure!(blocks > 0, "at least one block must be generated");
+
+        let first = self.blocks.len();
+        for _ in 0..blocks {
+            let (block, outcome, state) = self.execute_next_block()?;
+            self.headers.push(block.header.clone());
+            self.blocks.push((block, outcome));
+            self.states.push(state);
+        }
+
+        Ok(chain(&self.blocks[first..]))
+    }
+
+    /// Removes blocks from the canonical tip, and returns them as a chain.
+    fn truncate(&mut self, blocks: usize) -> eyre::Result<Chain> {
+        eyre::ensure!(blocks > 0, "at least one block must be removed");
+        eyre::ensure!(blocks <= self.blocks.len(), "the genesis block can't be removed");
+
+        let first = self.blocks.len() - blocks;
+        let removed = self.blocks.split_off(first);
+        self.headers.truncate(first + 1);
+        self.states.truncate(first + 1);
+
+        Ok(chain(&removed))
+    }
+
+    /// Generates a block with random transactions on top of the canonical tip, and executes it.
+    fn execute_next_block(
+        &mut self,
+    ) -> eyre::Result<(SealedBlockWithSenders, ExecutionOutcome, TestState)> {
+        let parent = self.headers.last().expect("genesis header");
+        let state = self.states.last().expect("genesis state");
+        let number = parent.number + 1;
+
+        let mut nonces = HashMap::new();
+        let mut transactions = Vec::new();
+        let mut senders = Vec::new();
+        for _ in 0..self.rng.gen_range(1..=MAX_TRANSACTIONS) {
+            let (key_pair, sender) = self.signers[self.rng.gen_range(0..self.signers.len())];
+            let nonce = nonces.entry(sender).or_insert_with(|| state.nonce(&sender));
+
+            let (to, value, input, gas_limit) = if self.rng.gen_bool(0.25) {
+                (TxKind::Create, U256::ZERO, Bytes::from_static(&CONTRACT_INIT_CODE), 200_000)
+            } else {
+                let (_, to) = self.signers[self.rng.gen_range(0..self.signers.len())];
+                (
+                    TxKind::Call(to),
+                    U256::from(self.rng.gen_range(1..1_000_000u64)),
+                    Bytes::new(),
+                    21_000,
+                )
+            };
+            let transaction = Transaction::Eip1559(TxEip1559 {
+                chain_id: self.chain_spec.chain.id(),
+                nonce: *nonce,
+                gas_limit,
+                max_fee_per_gas: 1_000_000_000,
+                max_priority_fee_per_gas: 1_000_000,
+                to,
+                value,
+                input,
+                ..Default::default()
+            });
+            *nonce += 1;
+
+            transactions.push(sign_tx_with_key_pair(key_pair, transaction));
+            senders.push(sender);
+        }
+
+        let header = Header {
+            parent_hash: parent.hash(),
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            beneficiary: Address::with_last_byte(0xbe),
+            transactions_root: calculate_transaction_root(&transactions),
+            withdrawals_root: Some(EMPTY_WITHDRAWALS),
+            number,
+            gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+            timestamp: parent.timestamp + 12,
+            base_fee_per_gas: Some(BASE_FEE),
+            ..Default::default()
+        };
+        let mut block = BlockWithSenders {
+            block: Block {
+                header,
+                body: transactions,
+                ommers: Vec::new(),
+                withdrawals: Some(Withdrawals::default()),
+                requests: None,
+            },
+            senders,
+        };
+
+        let output = self
+            .executor
+            .executor(StateProviderDatabase::new(state.provider()))
+            .execute((&block, U256::ZERO).into())?;
+
+        let header = &mut block.block.header;
+        header.gas_used = output.gas_used;
+        header.receipts_root =
+            calculate_receipt_root_no_memo(&output.receipts.iter().collect::<Vec<_>>());
+        header.logs_bloom = logs_bloom(output.receipts.iter().flat_map(|receipt| &receipt.logs));
+        let block = block.seal_slow();
+
+        let mut state = state.clone();
+        state.apply(&output.state);
+        state.block_hashes.insert(number, block.hash());
+
+        let outcome = ExecutionOutcome::new(
+            output.state,
+            Receipts::from(output.receipts),
+            number,
+            vec![Requests(output.requests)],
+        );
+
+        Ok((block, outcome, state))
+    }
+}
+
+/// Merges consecutive executed blocks into a chain.
+fn chain(blocks: &[(SealedBlockWithSenders, ExecutionOutcome)]) -> Chain {
+    let mut blocks = blocks.iter().cloned();
+    let (block, outcome) = blocks.next().expect("at least one block");
+    let mut chain = Chain::from_block(block, outcome, None);
+    for (block, outcome) in blocks {
+        chain.append_block(block, outcome);
+    }
+    chain
+}
+
+/// The state of the accounts after a block.
+#[derive(Debug, Clone, Default)]
+struct TestState {
+    /// The accounts, with their code and storage.
+    accounts: HashMap<Address, (Account, Option<Bytes>, HashMap<B256, U256>)>,
+    /// The hashes of the canonical blocks.
+    block_hashes: HashMap<BlockNumber, B256>,
+}
+
+impl TestState {
+    fn from_genesis(genesis: &Genesis, header: &SealedHeader) -> Self {
+        let accounts = genesis
+            .alloc
+            .iter()
+            .map(|(address, account)| {
+                let storage = account.storage.clone().unwrap_or_default();
+                (
+                    *address,
+                    (
+                        Account {
+                            nonce: account.nonce.unwrap_or_default(),
+                            balance: account.balance,
+                            bytecode_hash: None,
+                        },
+                        account.code.clone(),
+                        storage
+                            .into_iter()
+                            .map(|(slot, value)| (slot, U256::from_be_bytes(value.0)))
+                            .collect(),
+                    ),
+                )
+            })
+            .collect();
+
+        Self { accounts, block_hashes: HashMap::from([(header.number, header.hash())]) }
+    }
+
+    fn nonce(&self, address: &Address) -> u64 {
+        self.accounts.get(address).map_or(0, |(account, _, _)| account.nonce)
+    }
+
+    fn provider(&self) -> StateProviderTest {
+        let mut provider = StateProviderTest::default();
+        for (address, (account, code, storage)) in &self.accounts {
+            provider.insert_account(*address, *account, code.clone(), storage.clone());
+        }
+        for (number, hash) in &self.block_hashes {
+            provider.insert_block_hash(*number, *hash);
+        }
+        provider
+    }
+
+    fn apply(&mut self, bundle: &BundleState) {
+        for (address, bundle_account) in bundle.state() {
+            let Some(info) = bundle_account.info.clone() else {
+                self.accounts.remove(address);
+                continue
+            };
+
+            let (_, code, storage) = self.accounts.entry(*address).or_default();
+            if bundle_account.was_destroyed() {
+                storage.clear();
+            }
+            if let Some(bytecode) = info.code.as_ref().filter(|bytecode| !bytecode.is_empty()) {
+                *code = Some(bytecode.original_bytes());
+            }
+            for (slot, value) in &bundle_account.storage {
+                let slot = B256::new(slot.to_be_bytes());
+                if value.present_value.is_zero() {
+                    storage.remove(&slot);
+                } else {
+                    storage.insert(slot, value.present_value);
+                }
+            }
+
+            let account = Account::from(info);
+            self.accounts.get_mut(address).expect("inserted above").0 =
+                Account { bytecode_hash: None, ..account };
+        }
+    }
+}
+
+/// A step of an [`ExExScenario`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScenarioStep {
+    Commit(usize),
+    Revert(usize),
+    Reorg { depth: usize, blocks: usize },
+    ExpectFinishedHeight(BlockNumber),
+    ExpectNoEvents,
+}
+
+/// Drives an `ExEx` through a sequence of chain commits, reorgs and reverts of a [`TestChain`],
+/// and asserts the events it emits.
+///
+/// The notification of each step is sent to the `ExEx`, which is then polled until it has
+/// received it. Expectations poll the `ExEx` once more and check its next event, so the order of
+/// the steps determines when the events must have been emitted.
+///
+/// # Example
+///
+/// ```no_run
+/// # use reth_exex_test_utils::{ExExScenario, TestExExContext};
+/// # async fn my_exex(ctx: TestExExContext) -> eyre::Result<()> { Ok(()) }
+/// # async fn test() -> eyre::Result<()> {
+/// ExExScenario::default()
+///     .commit(3)
+///     .expect_finished_height(3)
+///     .reorg(2, 3)
+///     .expect_finished_height(4)
+///     .revert(1)
+///     .expect_finished_height(3)
+///     .run(|ctx| async move { Ok(my_exex(ctx)) })
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExExScenario {
+    seed: u64,
+    accounts: usize,
+    steps: Vec<ScenarioStep>,
+}
+
+impl Default for ExExScenario {
+    fn default() -> Self {
+        Self { seed: 0, accounts: 16, steps: Vec::new() }
+    }
+}
+
+impl ExExScenario {
+    /// Sets the seed the blocks are generated from.
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the number of accounts funded in the genesis, that send the transactions.
+    pub const fn with_accounts(mut self, accounts: usize) -> Self {
+        self.accounts = accounts;
+        self
+    }
+
+    /// Commits the given number of new blocks.
+    pub fn commit(mut self, blocks: usize) -> Self {
+        self.steps.push(ScenarioStep::Commit(blocks));
+        self
+    }
+
+    /// Reverts the given number of blocks.
+    pub fn revert(mut self, blocks: usize) -> Self {
+        self.steps.push(ScenarioStep::Revert(blocks));
+        self
+    }
+
+    /// Reorgs the given number of blocks, replacing them with the given number of new blocks.
+    pub fn reorg(mut self, depth: usize, blocks: usize) -> Self {
+        self.steps.push(ScenarioStep::Reorg { depth, blocks });
+        self
+    }
+
+    /// Expects the next event of the `ExEx` to be a `FinishedHeight` event with the given height.
+    pub fn expect_finished_height(mut self, height: BlockNumber) -> Self {
+        self.steps.push(ScenarioStep::ExpectFinishedHeight(height));
+        self
+    }
+
+    /// Expects the `ExEx` to have no pending events.
+    pub fn expect_no_events(mut self) -> Self {
+        self.steps.push(ScenarioStep::ExpectNoEvents);
+        self
+    }
+
+    /// Launches the `ExEx` with a [`TestExExContext`] for the chain spec of the scenario, and
+    /// runs the steps.
+    ///
+    /// The generated blocks are only sent as notifications, they are not inserted into the
+    /// provider of the context.
+    ///
+    /// Returns the handle of the context and the chain of the scenario, for further assertions.
+    pub async fn run<F, Fut, E>(self, exex: F) -> eyre::Result<(TestExExHandle, TestChain)>
+    where
+        F: FnOnce(TestExExContext) -> Fut,
+        Fut: Future<Output = eyre::Result<E>>,
+        E: Future<Output = eyre::Result<()>> + Send,
+    {
+        let mut chain = TestChain::new(self.seed, self.accounts);
+        let (ctx, mut handle) = test_exex_context_with_chain_spec(chain.chain_spec()).await?;
+        let mut exex = pin!(exex(ctx).await?);
+
+        for step in self.steps {
+            let notification = match step {
+                ScenarioStep::Commit(blocks) => chain.commit(blocks)?,
+                ScenarioStep::Revert(blocks) => chain.revert(blocks)?,
+                ScenarioStep::Reorg { depth, blocks } => chain.reorg(depth, blocks)?,
+                ScenarioStep::ExpectFinishedHeight(height) => {
+                    exex.poll_once().await?;
+                    handle.assert_event_finished_height(height)?;
+                    continue
+                }
+                ScenarioStep::ExpectNoEvents => {
+                    exex.poll_once().await?;
+                    handle.assert_events_empty();
+                    continue
+                }
+            };
+
+            // the channel has a capacity of one, so the notification is received once there's
+            // capacity again
+            handle.notifications_tx.send(notification).await?;
+            while handle.notifications_tx.capacity() == 0 {
+                exex.poll_once().await?;
+                tokio::task::yield_now().await;
+            }
+        }
+
+        Ok((handle, chain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_exex::ExExEvent;
+
+    /// An `ExEx` that emits the height of the chain after each notification.
+    async fn height_exex(mut ctx: TestExExContext) -> eyre::Result<()> {
+        while let Some(notification) = ctx.notifications.recv().await {
+            if let Some(chain) = notification.committed_chain() {
+                ctx.events.send(ExExEvent::FinishedHeight(chain.tip().number))?;
+            } else if let Some(chain) = notification.reverted_chain() {
+                ctx.events.send(ExExEvent::FinishedHeight(chain.first().number - 1))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn chain_blocks_are_executed() {
+        let mut chain = TestChain::new(0, 4);
+        let ExExNotification::ChainCommitted { new } = chain.commit(5).unwrap() else {
+            panic!("expected a commit")
+        };
+        assert_eq!(new.range(), 1..=5);
+        assert_eq!(chain.tip(), 5);
+
+        // every block has transactions with receipts
+        for (number, block) in new.blocks() {
+            let receipts = new.receipts_by_block_hash(block.hash()).unwrap();
+            assert!(!block.body.is_empty());
+            assert_eq!(receipts.len(), block.body.len());
+            assert!(receipts.iter().all(|receipt| receipt.success), "block {number}");
+            assert_eq!(block.gas_used, receipts.last().unwrap().cumulative_gas_used);
+        }
+
+        // the reorged blocks are the reverted ones, and the new blocks continue from the fork
+        let ExExNotification::ChainReorged { old, new: reorged } = chain.reorg(2, 3).unwrap()
+        else {
+            panic!("expected a reorg")
+        };
+        assert_eq!(old.range(), 4..=5);
+        assert_eq!(old.tip().hash(), new.tip().hash());
+        assert_eq!(reorged.range(), 4..=6);
+        assert_eq!(reorged.first().parent_hash, new.blocks()[&3].hash());
+
+        // the same seed generates the same chain
+        let mut other = TestChain::new(0, 4);
+        assert_eq!(
+            other.commit(5).unwrap().committed_chain().unwrap().tip().hash(),
+            new.tip().hash()
+        );
+    }
+
+    #[tokio::test]
+    async fn scenario_asserts_finished_heights() {
+        let (_, chain) = ExExScenario::default()
+            .commit(3)
+            .expect_finished_height(3)
+            .reorg(2, 3)
+            .expect_finished_height(4)
+            .revert(1)
+            .expect_finished_height(3)
+            .expect_no_events()
+            .run(|ctx| async move { Ok(height_exex(ctx)) })
+            .await
+            .unwrap();
+        assert_eq!(chain.tip(), 3);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "FinishedHeight(3)")]
+    async fn scenario_fails_on_mismatched_finished_height() {
+        let _ = ExExScenario::default()
+            .commit(3)
+            .expect_finished_height(2)
+            .run(|ctx| async move { Ok(height_exex(ctx)) })
+            .await;
+    }
+
+    #[tokio::test]
+    async fn scenario_fails_on_missing_event() {
+        // the exex emits a single event for the commit
+        let res = ExExScenario::default()
+            .commit(3)
+            .expect_finished_height(3)
+            .expect_finished_height(3)
+            .run(|ctx| async move { Ok(height_exex(ctx)) })
+            .await;
+        assert!(res.is_err());
+    }
+}
@@ -12,7 +12,7 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
 mod status;
-pub use status::{Status, StatusBuilder};
+pub use status::{BlockRangeUpdate, Status, StatusBuilder, StatusEth69};
 
 pub mod version;
 pub use version::EthVersion;
@@ -34,3 +34,6 @@ pub use state::*;
 
 pub mod receipts;
 pub use receipts::*;
+
+pub mod snap;
+pub use snap::{SnapMessageId, SnapProtocolMessage};
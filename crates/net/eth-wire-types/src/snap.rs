@@ -0,0 +1,397 @@
+//! Implements the `snap/1` protocol messages: <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+//!
+//! Unlike the `eth` protocol, the request id is the first field of every `snap` message rather
+//! than a wrapper around the message.
+
+use alloy_rlp::{Decodable, Encodable, RlpDecodable, RlpEncodable};
+use bytes::{BufMut, BytesMut};
+use reth_codecs_derive::derive_arbitrary;
+use reth_primitives::{Bytes, B256};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The version of the `snap` protocol.
+pub const SNAP_VERSION: usize = 1;
+
+/// Requests an unknown number of accounts from a given account trie, starting at the specified
+/// account hash and capped by the maximum allowed response size in bytes.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetAccountRange {
+    /// The request id.
+    pub request_id: u64,
+    /// The root hash of the account trie to serve.
+    pub root_hash: B256,
+    /// The account hash of the first account to retrieve.
+    pub starting_hash: B256,
+    /// The account hash after which to stop serving data.
+    pub limit_hash: B256,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// An account in the range response, the body is the RLP encoded account in the "slim" format,
+/// i.e. with empty bytes in place of the empty storage root and the empty code hash.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountData {
+    /// The hash of the account address.
+    pub hash: B256,
+    /// The account body in the slim format.
+    pub body: Bytes,
+}
+
+/// The response to [`GetAccountRange`], containing a consecutive list of accounts and the merkle
+/// proofs for the boundaries of the range.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountRange {
+    /// The request id.
+    pub request_id: u64,
+    /// The list of consecutive accounts from the trie.
+    pub accounts: Vec<AccountData>,
+    /// The list of trie nodes proving the account range.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests the storage slots of multiple accounts' storage tries.
+///
+/// The starting and limit hashes only apply to the first account, they are used to continue the
+/// retrieval of a large storage trie. Empty bytes are treated as the zero hash and the max hash
+/// respectively.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetStorageRanges {
+    /// The request id.
+    pub request_id: u64,
+    /// The root hash of the account trie to serve.
+    pub root_hash: B256,
+    /// The account hashes of the storage tries to serve.
+    pub account_hashes: Vec<B256>,
+    /// The storage slot hash of the first slot to retrieve.
+    pub starting_hash: Bytes,
+    /// The storage slot hash after which to stop serving.
+    pub limit_hash: Bytes,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// A storage slot in the range response, the data is the RLP encoded slot value.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageData {
+    /// The hash of the storage slot key.
+    pub hash: B256,
+    /// The RLP encoded slot value.
+    pub data: Bytes,
+}
+
+/// The response to [`GetStorageRanges`], containing the consecutive storage slots of the requested
+/// accounts.
+///
+/// If the last storage range is incomplete or started at a non-zero hash, the proof for its
+/// boundaries is attached.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageRanges {
+    /// The request id.
+    pub request_id: u64,
+    /// The list of consecutive slots for every requested account.
+    pub slots: Vec<Vec<StorageData>>,
+    /// The list of trie nodes proving the last storage range.
+    pub proof: Vec<Bytes>,
+}
+
+/// Requests a number of contract byte codes by hash.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetByteCodes {
+    /// The request id.
+    pub request_id: u64,
+    /// The code hashes to retrieve the code for.
+    pub hashes: Vec<B256>,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetByteCodes`], containing the requested byte codes in request order.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ByteCodes {
+    /// The request id.
+    pub request_id: u64,
+    /// The requested byte codes.
+    pub codes: Vec<Bytes>,
+}
+
+/// Requests a number of state (either account or storage) trie nodes by path.
+///
+/// Every path set is a list whose first element is the compact encoded path in the account trie
+/// and the remaining elements are compact encoded paths in the storage trie of that account.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GetTrieNodes {
+    /// The request id.
+    pub request_id: u64,
+    /// The root hash of the account trie to serve.
+    pub root_hash: B256,
+    /// The trie node path sets to retrieve.
+    pub paths: Vec<Vec<Bytes>>,
+    /// Soft limit at which to stop returning data.
+    pub response_bytes: u64,
+}
+
+/// The response to [`GetTrieNodes`], containing the requested trie nodes in request order.
+#[derive_arbitrary(rlp)]
+#[derive(Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrieNodes {
+    /// The request id.
+    pub request_id: u64,
+    /// The requested trie nodes.
+    pub nodes: Vec<Bytes>,
+}
+
+/// Represents message IDs for `snap` protocol messages.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapMessageId {
+    /// Request for a range of accounts.
+    GetAccountRange = 0x00,
+    /// Response with a range of accounts.
+    AccountRange = 0x01,
+    /// Request for ranges of storage slots.
+    GetStorageRanges = 0x02,
+    /// Response with ranges of storage slots.
+    StorageRanges = 0x03,
+    /// Request for byte codes.
+    GetByteCodes = 0x04,
+    /// Response with byte codes.
+    ByteCodes = 0x05,
+    /// Request for trie nodes.
+    GetTrieNodes = 0x06,
+    /// Response with trie nodes.
+    TrieNodes = 0x07,
+}
+
+impl SnapMessageId {
+    /// Returns the number of messages of the `snap/1` protocol.
+    pub const fn message_count() -> u8 {
+        Self::TrieNodes as u8 + 1
+    }
+}
+
+impl TryFrom<u8> for SnapMessageId {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(id: u8) -> Result<Self, Self::Error> {
+        match id {
+            0x00 => Ok(Self::GetAccountRange),
+            0x01 => Ok(Self::AccountRange),
+            0x02 => Ok(Self::GetStorageRanges),
+            0x03 => Ok(Self::StorageRanges),
+            0x04 => Ok(Self::GetByteCodes),
+            0x05 => Ok(Self::ByteCodes),
+            0x06 => Ok(Self::GetTrieNodes),
+            0x07 => Ok(Self::TrieNodes),
+            _ => Err(alloy_rlp::Error::Custom("invalid snap message id")),
+        }
+    }
+}
+
+/// Represents all `snap/1` protocol messages.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SnapProtocolMessage {
+    /// Represents a `GetAccountRange` request.
+    GetAccountRange(GetAccountRange),
+    /// Represents an `AccountRange` response.
+    AccountRange(AccountRange),
+    /// Represents a `GetStorageRanges` request.
+    GetStorageRanges(GetStorageRanges),
+    /// Represents a `StorageRanges` response.
+    StorageRanges(StorageRanges),
+    /// Represents a `GetByteCodes` request.
+    GetByteCodes(GetByteCodes),
+    /// Represents a `ByteCodes` response.
+    ByteCodes(ByteCodes),
+    /// Represents a `GetTrieNodes` request.
+    GetTrieNodes(GetTrieNodes),
+    /// Represents a `TrieNodes` response.
+    TrieNodes(TrieNodes),
+}
+
+impl SnapProtocolMessage {
+    /// Returns the message's ID.
+    pub const fn message_id(&self) -> SnapMessageId {
+        match self {
+            Self::GetAccountRange(_) => SnapMessageId::GetAccountRange,
+            Self::AccountRange(_) => SnapMessageId::AccountRange,
+            Self::GetStorageRanges(_) => SnapMessageId::GetStorageRanges,
+            Self::StorageRanges(_) => SnapMessageId::StorageRanges,
+            Self::GetByteCodes(_) => SnapMessageId::GetByteCodes,
+            Self::ByteCodes(_) => SnapMessageId::ByteCodes,
+            Self::GetTrieNodes(_) => SnapMessageId::GetTrieNodes,
+            Self::TrieNodes(_) => SnapMessageId::TrieNodes,
+        }
+    }
+
+    /// Returns the request id of the message.
+    pub const fn request_id(&self) -> u64 {
+        match self {
+            Self::GetAccountRange(msg) => msg.request_id,
+            Self::AccountRange(msg) => msg.request_id,
+            Self::GetStorageRanges(msg) => msg.request_id,
+            Self::StorageRanges(msg) => msg.request_id,
+            Self::GetByteCodes(msg) => msg.request_id,
+            Self::ByteCodes(msg) => msg.request_id,
+            Self::GetTrieNodes(msg) => msg.request_id,
+            Self::TrieNodes(msg) => msg.request_id,
+        }
+    }
+
+    /// Sets the request id of the message.
+    pub fn set_request_id(&mut self, request_id: u64) {
+        match self {
+            Self::GetAccountRange(msg) => msg.request_id = request_id,
+            Self::AccountRange(msg) => msg.request_id = request_id,
+            Self::GetStorageRanges(msg) => msg.request_id = request_id,
+            Self::StorageRanges(msg) => msg.request_id = request_id,
+            Self::GetByteCodes(msg) => msg.request_id = request_id,
+            Self::ByteCodes(msg) => msg.request_id = request_id,
+            Self::GetTrieNodes(msg) => msg.request_id = request_id,
+            Self::TrieNodes(msg) => msg.request_id = request_id,
+        }
+    }
+
+    /// Returns `true` if the message is a request.
+    pub const fn is_request(&self) -> bool {
+        matches!(
+            self,
+            Self::GetAccountRange(_) |
+                Self::GetStorageRanges(_) |
+                Self::GetByteCodes(_) |
+                Self::GetTrieNodes(_)
+        )
+    }
+
+    /// Encodes the message prefixed with its message id.
+    pub fn encoded(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(self.message_id() as u8);
+        match self {
+            Self::GetAccountRange(msg) => msg.encode(&mut buf),
+            Self::AccountRange(msg) => msg.encode(&mut buf),
+            Self::GetStorageRanges(msg) => msg.encode(&mut buf),
+            Self::StorageRanges(msg) => msg.encode(&mut buf),
+            Self::GetByteCodes(msg) => msg.encode(&mut buf),
+            Self::ByteCodes(msg) => msg.encode(&mut buf),
+            Self::GetTrieNodes(msg) => msg.encode(&mut buf),
+            Self::TrieNodes(msg) => msg.encode(&mut buf),
+        }
+        buf
+    }
+
+    /// Decodes a message that is prefixed with its message id.
+    pub fn decode_message(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let (&id, rest) = buf.split_first().ok_or(alloy_rlp::Error::InputTooShort)?;
+        *buf = rest;
+        let message = match SnapMessageId::try_from(id)? {
+            SnapMessageId::GetAccountRange => Self::GetAccountRange(Decodable::decode(buf)?),
+            SnapMessageId::AccountRange => Self::AccountRange(Decodable::decode(buf)?),
+            SnapMessageId::GetStorageRanges => Self::GetStorageRanges(Decodable::decode(buf)?),
+            SnapMessageId::StorageRanges => Self::StorageRanges(Decodable::decode(buf)?),
+            SnapMessageId::GetByteCodes => Self::GetByteCodes(Decodable::decode(buf)?),
+            SnapMessageId::ByteCodes => Self::ByteCodes(Decodable::decode(buf)?),
+            SnapMessageId::GetTrieNodes => Self::GetTrieNodes(Decodable::decode(buf)?),
+            SnapMessageId::TrieNodes => Self::TrieNodes(Decodable::decode(buf)?),
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::hex;
+
+    #[test]
+    fn roundtrip_snap_messages() {
+        let messages = vec![
+            SnapProtocolMessage::GetAccountRange(GetAccountRange {
+                request_id: 1,
+                root_hash: B256::repeat_byte(1),
+                starting_hash: B256::ZERO,
+                limit_hash: B256::repeat_byte(0xff),
+                response_bytes: 500 * 1024,
+            }),
+            SnapProtocolMessage::AccountRange(AccountRange {
+                request_id: 1,
+                accounts: vec![AccountData {
+                    hash: B256::repeat_byte(2),
+                    body: Bytes::from_static(&hex!("c58001808080")),
+                }],
+                proof: vec![Bytes::from_static(&[0xc0])],
+            }),
+            SnapProtocolMessage::GetStorageRanges(GetStorageRanges {
+                request_id: 2,
+                root_hash: B256::repeat_byte(1),
+                account_hashes: vec![B256::repeat_byte(2)],
+                starting_hash: Bytes::new(),
+                limit_hash: Bytes::new(),
+                response_bytes: 1024,
+            }),
+            SnapProtocolMessage::StorageRanges(StorageRanges {
+                request_id: 2,
+                slots: vec![vec![StorageData {
+                    hash: B256::repeat_byte(3),
+                    data: Bytes::from_static(&[0x01]),
+                }]],
+                proof: vec![],
+            }),
+            SnapProtocolMessage::GetByteCodes(GetByteCodes {
+                request_id: 3,
+                hashes: vec![B256::repeat_byte(4)],
+                response_bytes: 1024,
+            }),
+            SnapProtocolMessage::ByteCodes(ByteCodes {
+                request_id: 3,
+                codes: vec![Bytes::from_static(&[0x60, 0x00])],
+            }),
+            SnapProtocolMessage::GetTrieNodes(GetTrieNodes {
+                request_id: 4,
+                root_hash: B256::repeat_byte(1),
+                paths: vec![vec![Bytes::from_static(&[0x00])]],
+                response_bytes: 1024,
+            }),
+            SnapProtocolMessage::TrieNodes(TrieNodes {
+                request_id: 4,
+                nodes: vec![Bytes::from_static(&[0xc0])],
+            }),
+        ];
+
+        for message in messages {
+            let encoded = message.encoded();
+            assert_eq!(encoded[0], message.message_id() as u8);
+            let decoded = SnapProtocolMessage::decode_message(&mut &encoded[..]).unwrap();
+            assert_eq!(decoded, message);
+        }
+    }
+
+    #[test]
+    fn reject_unknown_snap_message() {
+        let encoded = [SnapMessageId::message_count(), 0xc0];
+        assert!(SnapProtocolMessage::decode_message(&mut &encoded[..]).is_err());
+    }
+}
@@ -148,6 +148,108 @@ impl Default for Status {
     }
 }
 
+/// The status message of `eth/69`, used in the eth protocol handshake.
+///
+/// Compared to [`Status`], the total difficulty is dropped and the range of blocks the peer can
+/// serve is announced instead, see [EIP-7642](https://eips.ethereum.org/EIPS/eip-7642).
+#[derive_arbitrary(rlp)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StatusEth69 {
+    /// The current protocol version, 69.
+    pub version: u8,
+
+    /// The chain id, as introduced in
+    /// [EIP155](https://eips.ethereum.org/EIPS/eip-155#list-of-chain-ids).
+    pub chain: Chain,
+
+    /// The genesis hash of the peer's chain.
+    pub genesis: B256,
+
+    /// The fork identifier as defined by
+    /// [EIP-2124](https://github.com/ethereum/EIPs/blob/master/EIPS/eip-2124.md).
+    pub forkid: ForkId,
+
+    /// The earliest block the peer can serve.
+    pub earliest: u64,
+
+    /// The latest block the peer can serve.
+    pub latest: u64,
+
+    /// The hash of the latest block.
+    pub blockhash: B256,
+}
+
+impl StatusEth69 {
+    /// Creates the `eth/69` status from the given [`Status`] and the range of blocks the node can
+    /// serve.
+    pub const fn new(status: &Status, block_range: BlockRangeUpdate) -> Self {
+        Self {
+            version: status.version,
+            chain: status.chain,
+            genesis: status.genesis,
+            forkid: status.forkid,
+            earliest: block_range.earliest,
+            latest: block_range.latest,
+            blockhash: block_range.latest_hash,
+        }
+    }
+
+    /// Returns the range of blocks the peer can serve.
+    pub const fn block_range(&self) -> BlockRangeUpdate {
+        BlockRangeUpdate {
+            earliest: self.earliest,
+            latest: self.latest,
+            latest_hash: self.blockhash,
+        }
+    }
+
+    /// Converts this into a [`Status`].
+    ///
+    /// The total difficulty is not part of the `eth/69` status and is set to zero.
+    pub const fn into_status(self) -> Status {
+        Status {
+            version: self.version,
+            chain: self.chain,
+            total_difficulty: U256::ZERO,
+            blockhash: self.blockhash,
+            genesis: self.genesis,
+            forkid: self.forkid,
+        }
+    }
+}
+
+impl Display for StatusEth69 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Status {{ version: {}, chain: {}, genesis: {}, forkid: {:X?}, earliest: {}, latest: {}, blockhash: {} }}",
+            self.version,
+            self.chain,
+            hex::encode(self.genesis),
+            self.forkid,
+            self.earliest,
+            self.latest,
+            hex::encode(self.blockhash),
+        )
+    }
+}
+
+/// Announces the range of blocks a peer can serve, introduced in `eth/69`.
+///
+/// This is sent whenever the range changes, for example after a new block was imported.
+#[derive_arbitrary(rlp)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BlockRangeUpdate {
+    /// The earliest block the peer can serve.
+    pub earliest: u64,
+    /// The latest block the peer can serve.
+    pub latest: u64,
+    /// The hash of the latest block.
+    pub latest_hash: B256,
+}
+
 /// Builder for [`Status`] messages.
 ///
 /// # Example
@@ -228,7 +330,7 @@ impl StatusBuilder {
 
 #[cfg(test)]
 mod tests {
-    use crate::{EthVersion, Status};
+    use crate::{BlockRangeUpdate, EthVersion, Status, StatusEth69};
     use alloy_genesis::Genesis;
     use alloy_rlp::{Decodable, Encodable};
     use rand::Rng;
@@ -404,4 +506,24 @@ mod tests {
         assert_eq!(status.blockhash, head_hash);
         assert_eq!(status.genesis, genesis_hash);
     }
+
+    #[test]
+    fn eth69_status_roundtrip() {
+        let status = Status { version: EthVersion::Eth69 as u8, ..Default::default() };
+        let range =
+            BlockRangeUpdate { earliest: 0, latest: 100, latest_hash: B256::repeat_byte(1) };
+        let status69 = StatusEth69::new(&status, range);
+        assert_eq!(status69.block_range(), range);
+
+        let mut rlp_status = vec![];
+        status69.encode(&mut rlp_status);
+        let decoded = StatusEth69::decode(&mut &rlp_status[..]).unwrap();
+        assert_eq!(decoded, status69);
+
+        let converted = decoded.into_status();
+        assert_eq!(converted.genesis, status.genesis);
+        assert_eq!(converted.forkid, status.forkid);
+        assert_eq!(converted.blockhash, range.latest_hash);
+        assert_eq!(converted.total_difficulty, U256::ZERO);
+    }
 }
@@ -99,4 +99,12 @@ pub enum EthHandshakeError {
         /// The maximum allowed bit length for the total difficulty.
         maximum: usize,
     },
+    #[error("invalid block range in status message: earliest {earliest} > latest {latest}")]
+    /// The block range announced in an eth/69 status message is invalid.
+    InvalidBlockRange {
+        /// The earliest block the peer can serve.
+        earliest: u64,
+        /// The latest block the peer can serve.
+        latest: u64,
+    },
 }
@@ -2,7 +2,8 @@ use crate::{
     errors::{EthHandshakeError, EthStreamError},
     message::{EthBroadcastMessage, ProtocolBroadcastMessage},
     p2pstream::HANDSHAKE_TIMEOUT,
-    CanDisconnect, DisconnectReason, EthMessage, EthVersion, ProtocolMessage, Status,
+    BlockRangeUpdate, CanDisconnect, DisconnectReason, EthMessage, EthVersion, ProtocolMessage,
+    Status, StatusEth69,
 };
 use futures::{ready, Sink, SinkExt, StreamExt};
 use pin_project::pin_project;
@@ -30,12 +31,23 @@ pub const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
 pub struct UnauthedEthStream<S> {
     #[pin]
     inner: S,
+    /// The range of blocks the local node can serve, announced to `eth/69` peers.
+    block_range: Option<BlockRangeUpdate>,
 }
 
 impl<S> UnauthedEthStream<S> {
     /// Create a new `UnauthedEthStream` from a type `S` which implements `Stream` and `Sink`.
     pub const fn new(inner: S) -> Self {
-        Self { inner }
+        Self { inner, block_range: None }
+    }
+
+    /// Sets the range of blocks the local node can serve.
+    ///
+    /// This is announced in the `Status` message if `eth/69` is negotiated. If it's not set, the
+    /// block hash of the `Status` is announced as latest block with number 0.
+    pub fn with_block_range(mut self, block_range: BlockRangeUpdate) -> Self {
+        self.block_range = Some(block_range);
+        self
     }
 
     /// Consumes the type and returns the wrapped stream
@@ -83,11 +95,22 @@ where
             "sending eth status to peer"
         );
 
+        let version = EthVersion::try_from(status.version)?;
+
+        // eth/69 replaces the total difficulty with the range of blocks we can serve
+        let our_status = if version >= EthVersion::Eth69 {
+            let block_range = self.block_range.unwrap_or(BlockRangeUpdate {
+                latest_hash: status.blockhash,
+                ..Default::default()
+            });
+            EthMessage::Status69(StatusEth69::new(&status, block_range))
+        } else {
+            EthMessage::Status(status)
+        };
+
         // we need to encode and decode here on our own because we don't have an `EthStream` yet
         // The max length for a status with TTD is: <msg id = 1 byte> + <rlp(status) = 88 byte>
-        self.inner
-            .send(alloy_rlp::encode(ProtocolMessage::from(EthMessage::Status(status))).into())
-            .await?;
+        self.inner.send(alloy_rlp::encode(ProtocolMessage::from(our_status)).into()).await?;
 
         let their_msg_res = self.inner.next().await;
 
@@ -104,7 +127,6 @@ where
             return Err(EthStreamError::MessageTooBig(their_msg.len()))
         }
 
-        let version = EthVersion::try_from(status.version)?;
         let msg = match ProtocolMessage::decode_message(version, &mut their_msg.as_ref()) {
             Ok(m) => m,
             Err(err) => {
@@ -114,71 +136,87 @@ where
             }
         };
 
-        // The following checks should match the checks in go-ethereum:
-        // https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/handshake.go#L87-L89
-        match msg.message {
+        let resp = match msg.message {
             EthMessage::Status(resp) => {
                 trace!(
                     status=%resp,
                     "validating incoming eth status from peer"
                 );
-                if status.genesis != resp.genesis {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::MismatchedGenesis(
-                        GotExpected { expected: status.genesis, got: resp.genesis }.into(),
-                    )
-                    .into())
-                }
-
-                if status.version != resp.version {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::MismatchedProtocolVersion(GotExpected {
-                        got: resp.version,
-                        expected: status.version,
-                    })
-                    .into())
-                }
-
-                if status.chain != resp.chain {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::MismatchedChain(GotExpected {
-                        got: resp.chain,
-                        expected: status.chain,
-                    })
-                    .into())
-                }
-
-                // TD at mainnet block #7753254 is 76 bits. If it becomes 100 million times
-                // larger, it will still fit within 100 bits
-                if status.total_difficulty.bit_len() > 100 {
+                resp
+            }
+            EthMessage::Status69(resp) => {
+                trace!(
+                    status=%resp,
+                    "validating incoming eth/69 status from peer"
+                );
+                if resp.earliest > resp.latest {
                     self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(EthHandshakeError::TotalDifficultyBitLenTooLarge {
-                        got: status.total_difficulty.bit_len(),
-                        maximum: 100,
+                    return Err(EthHandshakeError::InvalidBlockRange {
+                        earliest: resp.earliest,
+                        latest: resp.latest,
                     }
                     .into())
                 }
-
-                if let Err(err) =
-                    fork_filter.validate(resp.forkid).map_err(EthHandshakeError::InvalidFork)
-                {
-                    self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                    return Err(err.into())
-                }
-
-                // now we can create the `EthStream` because the peer has successfully completed
-                // the handshake
-                let stream = EthStream::new(version, self.inner);
-
-                Ok((stream, resp))
+                resp.into_status()
             }
             _ => {
                 self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
-                Err(EthStreamError::EthHandshakeError(
+                return Err(EthStreamError::EthHandshakeError(
                     EthHandshakeError::NonStatusMessageInHandshake,
                 ))
             }
+        };
+
+        // The following checks should match the checks in go-ethereum:
+        // https://github.com/ethereum/go-ethereum/blob/9244d5cd61f3ea5a7645fdf2a1a96d53421e412f/eth/protocols/eth/handshake.go#L87-L89
+        if status.genesis != resp.genesis {
+            self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
+            return Err(EthHandshakeError::MismatchedGenesis(
+                GotExpected { expected: status.genesis, got: resp.genesis }.into(),
+            )
+            .into())
         }
+
+        if status.version != resp.version {
+            self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
+            return Err(EthHandshakeError::MismatchedProtocolVersion(GotExpected {
+                got: resp.version,
+                expected: status.version,
+            })
+            .into())
+        }
+
+        if status.chain != resp.chain {
+            self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
+            return Err(EthHandshakeError::MismatchedChain(GotExpected {
+                got: resp.chain,
+                expected: status.chain,
+            })
+            .into())
+        }
+
+        // TD at mainnet block #7753254 is 76 bits. If it becomes 100 million times
+        // larger, it will still fit within 100 bits
+        if status.total_difficulty.bit_len() > 100 {
+            self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
+            return Err(EthHandshakeError::TotalDifficultyBitLenTooLarge {
+                got: status.total_difficulty.bit_len(),
+                maximum: 100,
+            }
+            .into())
+        }
+
+        if let Err(err) = fork_filter.validate(resp.forkid).map_err(EthHandshakeError::InvalidFork)
+        {
+            self.inner.disconnect(DisconnectReason::ProtocolBreach).await?;
+            return Err(err.into())
+        }
+
+        // now we can create the `EthStream` because the peer has successfully completed
+        // the handshake
+        let stream = EthStream::new(version, self.inner);
+
+        Ok((stream, resp))
     }
 }
 
@@ -281,7 +319,7 @@ where
             }
         };
 
-        if matches!(msg.message, EthMessage::Status(_)) {
+        if matches!(msg.message, EthMessage::Status(_) | EthMessage::Status69(_)) {
             return Poll::Ready(Some(Err(EthStreamError::EthHandshakeError(
                 EthHandshakeError::StatusNotInHandshake,
             ))))
@@ -303,7 +341,7 @@ where
     }
 
     fn start_send(self: Pin<&mut Self>, item: EthMessage) -> Result<(), Self::Error> {
-        if matches!(item, EthMessage::Status(_)) {
+        if matches!(item, EthMessage::Status(_) | EthMessage::Status69(_)) {
             // TODO: to disconnect here we would need to do something similar to P2PStream's
             // start_disconnect, which would ideally be a part of the CanDisconnect trait, or at
             // least similar.
@@ -349,7 +387,8 @@ mod tests {
         broadcast::BlockHashNumber,
         errors::{EthHandshakeError, EthStreamError},
         p2pstream::{ProtocolVersion, UnauthedP2PStream},
-        EthMessage, EthStream, EthVersion, HelloMessageWithProtocols, PassthroughCodec, Status,
+        BlockRangeUpdate, EthMessage, EthStream, EthVersion, HelloMessageWithProtocols,
+        PassthroughCodec, Status,
     };
     use futures::{SinkExt, StreamExt};
     use reth_chainspec::NamedChain;
@@ -409,6 +448,54 @@ mod tests {
         handle.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn can_handshake_eth69() {
+        let genesis = B256::random();
+        let fork_filter = ForkFilter::new(Head::default(), genesis, 0, Vec::new());
+
+        let status = Status {
+            version: EthVersion::Eth69 as u8,
+            chain: NamedChain::Mainnet.into(),
+            total_difficulty: U256::ZERO,
+            blockhash: B256::random(),
+            genesis,
+            forkid: fork_filter.current(),
+        };
+        let block_range =
+            BlockRangeUpdate { earliest: 0, latest: 100, latest_hash: status.blockhash };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let local_addr = listener.local_addr().unwrap();
+
+        let fork_filter_clone = fork_filter.clone();
+        let handle = tokio::spawn(async move {
+            let (incoming, _) = listener.accept().await.unwrap();
+            let stream = PassthroughCodec::default().framed(incoming);
+            let (_, their_status) = UnauthedEthStream::new(stream)
+                .with_block_range(block_range)
+                .handshake(status, fork_filter_clone)
+                .await
+                .unwrap();
+
+            // eth/69 does not carry the total difficulty
+            assert_eq!(their_status, status);
+        });
+
+        let outgoing = TcpStream::connect(local_addr).await.unwrap();
+        let sink = PassthroughCodec::default().framed(outgoing);
+
+        let (stream, their_status) = UnauthedEthStream::new(sink)
+            .with_block_range(block_range)
+            .handshake(status, fork_filter)
+            .await
+            .unwrap();
+
+        assert_eq!(their_status, status);
+        assert_eq!(stream.version(), EthVersion::Eth69);
+
+        handle.await.unwrap();
+    }
+
     #[tokio::test]
     async fn pass_handshake_on_low_td_bitlen() {
         let genesis = B256::random();
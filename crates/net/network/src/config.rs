@@ -15,9 +15,9 @@ use reth_chainspec::{
 use reth_discv4::{Discv4Config, Discv4ConfigBuilder, NatResolver, DEFAULT_DISCOVERY_ADDRESS};
 use reth_discv5::NetworkStackId;
 use reth_dns_discovery::DnsDiscoveryConfig;
-use reth_eth_wire::{HelloMessage, HelloMessageWithProtocols, Status};
+use reth_eth_wire::{BlockRangeUpdate, HelloMessage, HelloMessageWithProtocols, Status};
 use reth_network_peers::{pk2id, PeerId};
-use reth_primitives::{ForkFilter, Head};
+use reth_primitives::{BlockNumber, ForkFilter, Head};
 use reth_provider::{BlockReader, HeaderProvider};
 use reth_tasks::{TaskSpawner, TokioTaskExecutor};
 use secp256k1::SECP256K1;
@@ -75,6 +75,8 @@ pub struct NetworkConfig<C> {
     pub executor: Box<dyn TaskSpawner>,
     /// The `Status` message to send to peers at the beginning.
     pub status: Status,
+    /// The range of blocks announced to `eth/69` peers at the beginning.
+    pub block_range: BlockRangeUpdate,
     /// Sets the hello message for the p2p handshake in `RLPx`
     pub hello_message: HelloMessageWithProtocols,
     /// Additional protocols to announce and handle in `RLPx`
@@ -168,6 +170,8 @@ pub struct NetworkConfigBuilder {
     extra_protocols: RlpxSubProtocols,
     /// Head used to start set for the fork filter and status.
     head: Option<Head>,
+    /// The lowest block the node serves, announced to `eth/69` peers.
+    earliest_block: BlockNumber,
     /// Whether tx gossip is disabled
     tx_gossip_disabled: bool,
     /// The block importer type
@@ -197,6 +201,7 @@ impl NetworkConfigBuilder {
             hello_message: None,
             extra_protocols: Default::default(),
             head: None,
+            earliest_block: 0,
             tx_gossip_disabled: false,
             block_import: None,
             transactions_manager_config: Default::default(),
@@ -238,6 +243,15 @@ impl NetworkConfigBuilder {
         self
     }
 
+    /// Sets the lowest block the node can serve headers, bodies and receipts for.
+    ///
+    /// This is the start of the block range announced to `eth/69` peers. If not set, this defaults
+    /// to the genesis block.
+    pub const fn set_earliest_block(mut self, earliest_block: BlockNumber) -> Self {
+        self.earliest_block = earliest_block;
+        self
+    }
+
     /// Sets the `HelloMessage` to send when connecting to peers.
     ///
     /// ```
@@ -492,6 +506,7 @@ impl NetworkConfigBuilder {
             hello_message,
             extra_protocols,
             head,
+            earliest_block,
             tx_gossip_disabled,
             block_import,
             transactions_manager_config,
@@ -513,6 +528,11 @@ impl NetworkConfigBuilder {
 
         // set the status
         let status = Status::spec_builder(&chain_spec, &head).build();
+        let block_range = BlockRangeUpdate {
+            earliest: earliest_block.min(head.number),
+            latest: head.number,
+            latest_hash: head.hash,
+        };
 
         // set a fork filter based on the chain spec and head
         let fork_filter = chain_spec.fork_filter(head);
@@ -544,6 +564,7 @@ impl NetworkConfigBuilder {
             network_mode,
             executor: executor.unwrap_or_else(|| Box::<TokioTaskExecutor>::default()),
             status,
+            block_range,
             hello_message,
             extra_protocols,
             fork_filter,
@@ -583,7 +604,7 @@ mod tests {
     use rand::thread_rng;
     use reth_chainspec::Chain;
     use reth_dns_discovery::tree::LinkEntry;
-    use reth_primitives::ForkHash;
+    use reth_primitives::{ForkHash, B256};
     use reth_provider::test_utils::NoopProvider;
     use std::collections::BTreeMap;
 
@@ -630,4 +651,23 @@ mod tests {
         assert_eq!(status.forkid.hash, genesis_fork_hash);
         assert_eq!(fork_filter.current().hash, genesis_fork_hash);
     }
+
+    #[test]
+    fn test_network_block_range() {
+        let head = Head { number: 100, hash: B256::random(), ..Default::default() };
+
+        let config = builder().set_head(head).build(NoopProvider::default());
+        assert_eq!(
+            config.block_range,
+            BlockRangeUpdate { earliest: 0, latest: 100, latest_hash: head.hash }
+        );
+
+        let config = builder().set_head(head).set_earliest_block(90).build(NoopProvider::default());
+        assert_eq!(config.block_range.earliest, 90);
+
+        // the range never starts above the head
+        let config =
+            builder().set_head(head).set_earliest_block(200).build(NoopProvider::default());
+        assert_eq!(config.block_range.earliest, 100);
+    }
 }
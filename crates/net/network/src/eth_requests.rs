@@ -8,11 +8,11 @@ use alloy_rlp::Encodable;
 use futures::StreamExt;
 use reth_eth_wire::{
     BlockBodies, BlockHeaders, GetBlockBodies, GetBlockHeaders, GetNodeData, GetReceipts, NodeData,
-    Receipts,
+    Receipts, Receipts69,
 };
 use reth_network_p2p::error::RequestResult;
 use reth_network_peers::PeerId;
-use reth_primitives::{BlockBody, BlockHashOrNumber, Header, HeadersDirection};
+use reth_primitives::{BlockBody, BlockHashOrNumber, Header, HeadersDirection, Receipt};
 use reth_provider::{BlockReader, HeaderProvider, ReceiptProvider};
 use std::{
     future::Future,
@@ -196,6 +196,32 @@ where
     ) {
         self.metrics.eth_receipts_requests_received_total.increment(1);
 
+        let receipts = self.get_receipts_response(request, Receipt::with_bloom);
+
+        let _ = response.send(Ok(Receipts(receipts)));
+    }
+
+    /// Handles an `eth/69` receipts request, these receipts are served without bloom filters.
+    fn on_receipts69_request(
+        &self,
+        _peer_id: PeerId,
+        request: GetReceipts,
+        response: oneshot::Sender<RequestResult<Receipts69>>,
+    ) {
+        self.metrics.eth_receipts_requests_received_total.increment(1);
+
+        let receipts = self.get_receipts_response(request, |receipt| receipt);
+
+        let _ = response.send(Ok(Receipts69(receipts)));
+    }
+
+    /// Looks up the receipts of the requested blocks and converts every receipt with the given
+    /// function, respecting the serve limits.
+    fn get_receipts_response<T, F>(&self, request: GetReceipts, transform: F) -> Vec<Vec<T>>
+    where
+        T: Encodable,
+        F: Fn(Receipt) -> T,
+    {
         let mut receipts = Vec::new();
 
         let mut total_bytes = 0;
@@ -204,10 +230,7 @@ where
             if let Some(receipts_by_block) =
                 self.client.receipts_by_block(BlockHashOrNumber::Hash(hash)).unwrap_or_default()
             {
-                let receipt = receipts_by_block
-                    .into_iter()
-                    .map(|receipt| receipt.with_bloom())
-                    .collect::<Vec<_>>();
+                let receipt = receipts_by_block.into_iter().map(&transform).collect::<Vec<_>>();
 
                 total_bytes += receipt.length();
                 receipts.push(receipt);
@@ -224,7 +247,7 @@ where
             }
         }
 
-        let _ = response.send(Ok(Receipts(receipts)));
+        receipts
     }
 }
 
@@ -261,6 +284,9 @@ where
                     IncomingEthRequest::GetReceipts { peer_id, request, response } => {
                         this.on_receipts_request(peer_id, request, response)
                     }
+                    IncomingEthRequest::GetReceipts69 { peer_id, request, response } => {
+                        this.on_receipts69_request(peer_id, request, response)
+                    }
                 }
             },
         );
@@ -325,4 +351,15 @@ pub enum IncomingEthRequest {
         /// The channel sender for the response containing receipts.
         response: oneshot::Sender<RequestResult<Receipts>>,
     },
+    /// Request `eth/69` Receipts, without bloom filters, from the peer.
+    ///
+    /// The response should be sent through the channel.
+    GetReceipts69 {
+        /// The ID of the peer to request receipts from.
+        peer_id: PeerId,
+        /// The specific receipts requested.
+        request: GetReceipts,
+        /// The channel sender for the response containing receipts.
+        response: oneshot::Sender<RequestResult<Receipts69>>,
+    },
 }
@@ -183,6 +183,7 @@ where
             executor,
             hello_message,
             status,
+            block_range,
             fork_filter,
             dns_discovery_config,
             extra_protocols,
@@ -234,6 +235,7 @@ where
             sessions_config,
             executor,
             status,
+            block_range,
             hello_message,
             fork_filter,
             extra_protocols,
@@ -435,6 +437,13 @@ where
                     response,
                 })
             }
+            PeerRequest::GetReceipts69 { request, response } => {
+                self.delegate_eth_request(IncomingEthRequest::GetReceipts69 {
+                    peer_id,
+                    request,
+                    response,
+                })
+            }
             PeerRequest::GetPooledTransactions { request, response } => {
                 self.notify_tx_manager(NetworkTransactionEvent::GetPooledTransactions {
                     peer_id,
@@ -509,6 +518,9 @@ where
                     msg,
                 });
             }
+            PeerMessage::BlockRangeUpdate(range) => {
+                self.swarm.state_mut().on_block_range_update(peer_id, range);
+            }
             PeerMessage::EthRequest(req) => {
                 self.on_eth_request(peer_id, req);
             }
@@ -15,7 +15,7 @@ use reth_eth_wire::{
     capability::Capabilities,
     errors::{EthHandshakeError, EthStreamError, P2PStreamError},
     message::{EthBroadcastMessage, RequestPair},
-    DisconnectP2P, DisconnectReason, EthMessage,
+    DisconnectP2P, DisconnectReason, EthMessage, EthVersion,
 };
 use reth_metrics::common::mpsc::MeteredPollSender;
 use reth_network_p2p::error::RequestError;
@@ -170,10 +170,14 @@ impl ActiveSession {
         }
 
         match msg {
-            message @ EthMessage::Status(_) => OnIncomingMessageOutcome::BadMessage {
-                error: EthStreamError::EthHandshakeError(EthHandshakeError::StatusNotInHandshake),
-                message,
-            },
+            message @ (EthMessage::Status(_) | EthMessage::Status69(_)) => {
+                OnIncomingMessageOutcome::BadMessage {
+                    error: EthStreamError::EthHandshakeError(
+                        EthHandshakeError::StatusNotInHandshake,
+                    ),
+                    message,
+                }
+            }
             EthMessage::NewBlockHashes(msg) => {
                 self.try_emit_broadcast(PeerMessage::NewBlockHashes(msg)).into()
             }
@@ -226,11 +230,35 @@ impl ActiveSession {
                 on_response!(resp, GetNodeData)
             }
             EthMessage::GetReceipts(req) => {
-                on_request!(req, Receipts, GetReceipts)
+                if self.conn.version() >= EthVersion::Eth69 {
+                    on_request!(req, Receipts69, GetReceipts69)
+                } else {
+                    on_request!(req, Receipts, GetReceipts)
+                }
             }
             EthMessage::Receipts(resp) => {
                 on_response!(resp, GetReceipts)
             }
+            EthMessage::Receipts69(resp) => {
+                // eth/69 receipts omit the bloom, which is recomputed locally
+                let RequestPair { request_id, message } = resp;
+                let resp = RequestPair { request_id, message: message.into_receipts() };
+                on_response!(resp, GetReceipts)
+            }
+            EthMessage::BlockRangeUpdate(msg) => {
+                if msg.earliest > msg.latest {
+                    return OnIncomingMessageOutcome::BadMessage {
+                        error: EthStreamError::EthHandshakeError(
+                            EthHandshakeError::InvalidBlockRange {
+                                earliest: msg.earliest,
+                                latest: msg.latest,
+                            },
+                        ),
+                        message: EthMessage::BlockRangeUpdate(msg),
+                    }
+                }
+                self.try_emit_broadcast(PeerMessage::BlockRangeUpdate(msg)).into()
+            }
         }
     }
 
@@ -261,6 +289,11 @@ impl ActiveSession {
                     self.queued_outgoing.push_back(EthMessage::from(msg).into());
                 }
             }
+            PeerMessage::BlockRangeUpdate(msg) => {
+                if self.conn.version() >= EthVersion::Eth69 {
+                    self.queued_outgoing.push_back(EthMessage::BlockRangeUpdate(msg).into());
+                }
+            }
             PeerMessage::EthRequest(req) => {
                 let deadline = self.request_deadline();
                 self.on_internal_peer_request(req, deadline);
@@ -766,8 +799,8 @@ mod tests {
     use reth_chainspec::MAINNET;
     use reth_ecies::stream::ECIESStream;
     use reth_eth_wire::{
-        EthStream, GetBlockBodies, HelloMessageWithProtocols, P2PStream, Status, StatusBuilder,
-        UnauthedEthStream, UnauthedP2PStream,
+        BlockRangeUpdate, EthStream, GetBlockBodies, HelloMessageWithProtocols, P2PStream, Status,
+        StatusBuilder, UnauthedEthStream, UnauthedP2PStream,
     };
     use reth_network_peers::pk2id;
     use reth_primitives::{ForkFilter, Hardfork};
@@ -812,7 +845,7 @@ mod tests {
             F: FnOnce(EthStream<P2PStream<ECIESStream<TcpStream>>>) -> O + Send + 'static,
             O: Future<Output = ()> + Send + Sync,
         {
-            let status = self.status;
+            let mut status = self.status;
             let fork_filter = self.fork_filter.clone();
             let local_peer_id = self.local_peer_id;
             let mut hello = self.hello.clone();
@@ -823,6 +856,7 @@ mod tests {
                 let sink = ECIESStream::connect(outgoing, key, local_peer_id).await.unwrap();
 
                 let (p2p_stream, _) = UnauthedP2PStream::new(sink).handshake(hello).await.unwrap();
+                status.set_eth_version(p2p_stream.shared_capabilities().eth_version().unwrap());
 
                 let (client_stream, _) = UnauthedEthStream::new(p2p_stream)
                     .handshake(status, fork_filter)
@@ -847,6 +881,7 @@ mod tests {
                 self.secret_key,
                 self.hello.clone(),
                 self.status,
+                BlockRangeUpdate { latest_hash: self.status.blockhash, ..Default::default() },
                 self.fork_filter.clone(),
                 Default::default(),
             ));
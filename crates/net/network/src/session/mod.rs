@@ -10,8 +10,8 @@ use reth_ecies::{stream::ECIESStream, ECIESError};
 use reth_eth_wire::{
     capability::{Capabilities, CapabilityMessage},
     errors::EthStreamError,
-    DisconnectReason, EthVersion, HelloMessageWithProtocols, Status, UnauthedEthStream,
-    UnauthedP2PStream,
+    BlockRangeUpdate, DisconnectReason, EthVersion, HelloMessageWithProtocols, Status,
+    UnauthedEthStream, UnauthedP2PStream,
 };
 use reth_metrics::common::mpsc::MeteredPollSender;
 use reth_network_peers::PeerId;
@@ -74,6 +74,8 @@ pub struct SessionManager {
     secret_key: SecretKey,
     /// The `Status` message to send to peers.
     status: Status,
+    /// The range of blocks announced to `eth/69` peers.
+    block_range: BlockRangeUpdate,
     /// The `HelloMessage` message to send to peers.
     hello_message: HelloMessageWithProtocols,
     /// The [`ForkFilter`] used to validate the peer's `Status` message.
@@ -119,6 +121,7 @@ impl SessionManager {
         config: SessionsConfig,
         executor: Box<dyn TaskSpawner>,
         status: Status,
+        block_range: BlockRangeUpdate,
         hello_message: HelloMessageWithProtocols,
         fork_filter: ForkFilter,
         extra_protocols: RlpxSubProtocols,
@@ -135,6 +138,7 @@ impl SessionManager {
             pending_session_timeout: config.pending_session_timeout,
             secret_key,
             status,
+            block_range,
             hello_message,
             fork_filter,
             session_command_buffer: config.session_command_buffer,
@@ -168,6 +172,11 @@ impl SessionManager {
         self.status
     }
 
+    /// Returns the range of blocks announced to `eth/69` peers.
+    pub const fn block_range(&self) -> BlockRangeUpdate {
+        self.block_range
+    }
+
     /// Returns the secret key used for authenticating sessions.
     pub const fn secret_key(&self) -> SecretKey {
         self.secret_key
@@ -207,6 +216,19 @@ impl SessionManager {
         self.status.total_difficulty = head.total_difficulty;
         let transition = self.fork_filter.set_head(head);
         self.status.forkid = self.fork_filter.current();
+
+        // announce the new head to all peers that understand block range updates
+        self.block_range.latest = head.number;
+        self.block_range.latest_hash = head.hash;
+        let block_range = self.block_range;
+        for session in self.active_sessions.values_mut() {
+            if session.version >= EthVersion::Eth69 {
+                let _ = session
+                    .commands_to_session
+                    .try_send(SessionCommand::Message(PeerMessage::BlockRangeUpdate(block_range)));
+            }
+        }
+
         transition
     }
 
@@ -235,6 +257,7 @@ impl SessionManager {
         let secret_key = self.secret_key;
         let hello_message = self.hello_message.clone();
         let status = self.status;
+        let block_range = self.block_range;
         let fork_filter = self.fork_filter.clone();
         let extra_handlers = self.extra_protocols.on_incoming(remote_addr);
         self.spawn(pending_session_with_timeout(
@@ -252,6 +275,7 @@ impl SessionManager {
                 secret_key,
                 hello_message,
                 status,
+                block_range,
                 fork_filter,
                 extra_handlers,
             ),
@@ -277,6 +301,7 @@ impl SessionManager {
             let hello_message = self.hello_message.clone();
             let fork_filter = self.fork_filter.clone();
             let status = self.status;
+            let block_range = self.block_range;
             let extra_handlers = self.extra_protocols.on_outgoing(remote_addr, remote_peer_id);
             self.spawn(pending_session_with_timeout(
                 self.pending_session_timeout,
@@ -293,6 +318,7 @@ impl SessionManager {
                     secret_key,
                     hello_message,
                     status,
+                    block_range,
                     fork_filter,
                     extra_handlers,
                 ),
@@ -792,6 +818,7 @@ pub(crate) async fn start_pending_incoming_session(
     secret_key: SecretKey,
     hello: HelloMessageWithProtocols,
     status: Status,
+    block_range: BlockRangeUpdate,
     fork_filter: ForkFilter,
     extra_handlers: RlpxSubProtocolHandlers,
 ) {
@@ -805,6 +832,7 @@ pub(crate) async fn start_pending_incoming_session(
         Direction::Incoming,
         hello,
         status,
+        block_range,
         fork_filter,
         extra_handlers,
     )
@@ -823,6 +851,7 @@ async fn start_pending_outbound_session(
     secret_key: SecretKey,
     hello: HelloMessageWithProtocols,
     status: Status,
+    block_range: BlockRangeUpdate,
     fork_filter: ForkFilter,
     extra_handlers: RlpxSubProtocolHandlers,
 ) {
@@ -855,6 +884,7 @@ async fn start_pending_outbound_session(
         Direction::Outgoing(remote_peer_id),
         hello,
         status,
+        block_range,
         fork_filter,
         extra_handlers,
     )
@@ -873,6 +903,7 @@ async fn authenticate(
     direction: Direction,
     hello: HelloMessageWithProtocols,
     status: Status,
+    block_range: BlockRangeUpdate,
     fork_filter: ForkFilter,
     extra_handlers: RlpxSubProtocolHandlers,
 ) {
@@ -902,6 +933,7 @@ async fn authenticate(
         direction,
         hello,
         status,
+        block_range,
         fork_filter,
         extra_handlers,
     )
@@ -954,6 +986,7 @@ async fn authenticate_stream(
     direction: Direction,
     mut hello: HelloMessageWithProtocols,
     mut status: Status,
+    block_range: BlockRangeUpdate,
     fork_filter: ForkFilter,
     mut extra_handlers: RlpxSubProtocolHandlers,
 ) -> PendingSessionEvent {
@@ -986,12 +1019,12 @@ async fn authenticate_stream(
         }
     };
 
+    // Before trying status handshake, set up the version to negotiated shared version
+    status.set_eth_version(eth_version);
+
     let (conn, their_status) = if p2p_stream.shared_capabilities().len() == 1 {
         // if the hello handshake was successful we can try status handshake
-        //
-        // Before trying status handshake, set up the version to negotiated shared version
-        status.set_eth_version(eth_version);
-        let eth_unauthed = UnauthedEthStream::new(p2p_stream);
+        let eth_unauthed = UnauthedEthStream::new(p2p_stream).with_block_range(block_range);
         let (eth_stream, their_status) = match eth_unauthed.handshake(status, fork_filter).await {
             Ok(stream_res) => stream_res,
             Err(err) => {
@@ -1007,7 +1040,7 @@ async fn authenticate_stream(
     } else {
         // Multiplex the stream with the extra protocols
         let (mut multiplex_stream, their_status) = RlpxProtocolMultiplexer::new(p2p_stream)
-            .into_eth_satellite_stream(status, fork_filter)
+            .into_eth_satellite_stream_with_block_range(status, block_range, fork_filter)
             .await
             .unwrap();
 
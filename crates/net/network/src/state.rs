@@ -15,7 +15,8 @@ use crate::{
 use rand::seq::SliceRandom;
 
 use reth_eth_wire::{
-    capability::Capabilities, BlockHashNumber, DisconnectReason, NewBlockHashes, Status,
+    capability::Capabilities, BlockHashNumber, BlockRangeUpdate, DisconnectReason, NewBlockHashes,
+    Status,
 };
 use reth_network_api::PeerKind;
 use reth_network_peers::PeerId;
@@ -256,6 +257,14 @@ where
         }
     }
 
+    /// Invoked for a `BlockRangeUpdate` message from an `eth/69` peer.
+    pub(crate) fn on_block_range_update(&mut self, peer_id: PeerId, range: BlockRangeUpdate) {
+        if let Some(peer) = self.active_peers.get_mut(&peer_id) {
+            peer.blocks.insert(range.latest_hash);
+        }
+        self.update_peer_block(&peer_id, range.latest_hash, range.latest);
+    }
+
     /// Bans the [`IpAddr`] in the discovery service.
     pub(crate) fn ban_ip_discovery(&self, ip: IpAddr) {
         trace!(target: "net", ?ip, "Banning discovery");
@@ -38,6 +38,9 @@ pub mod error;
 /// Priority enum for `BlockHeader` and `BlockBody` requests
 pub mod priority;
 
+/// Traits for implementing `snap` state clients.
+pub mod snap;
+
 /// Syncing related traits.
 pub mod sync;
 
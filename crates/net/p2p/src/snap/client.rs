@@ -0,0 +1,28 @@
+use crate::{download::DownloadClient, error::PeerRequestResult};
+use futures::Future;
+use reth_eth_wire_types::snap::{
+    AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges, GetTrieNodes,
+    StorageRanges, TrieNodes,
+};
+use std::pin::Pin;
+
+/// The future type of `snap` requests.
+pub type SnapFut<T> = Pin<Box<dyn Future<Output = PeerRequestResult<T>> + Send + Sync>>;
+
+/// A client capable of downloading state over the `snap` protocol.
+///
+/// The request id of the requests is assigned by the client.
+#[auto_impl::auto_impl(&, Arc, Box)]
+pub trait SnapClient: DownloadClient {
+    /// Fetches a range of accounts of the state trie with the given root.
+    fn get_account_range(&self, request: GetAccountRange) -> SnapFut<AccountRange>;
+
+    /// Fetches the storage slots of the given accounts of the state trie with the given root.
+    fn get_storage_ranges(&self, request: GetStorageRanges) -> SnapFut<StorageRanges>;
+
+    /// Fetches the bytecodes for the requested code hashes.
+    fn get_byte_codes(&self, request: GetByteCodes) -> SnapFut<ByteCodes>;
+
+    /// Fetches trie nodes of the state trie with the given root by their paths.
+    fn get_trie_nodes(&self, request: GetTrieNodes) -> SnapFut<TrieNodes>;
+}
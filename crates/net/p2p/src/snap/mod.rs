@@ -0,0 +1,2 @@
+/// Traits and types for `snap` state clients.
+pub mod client;
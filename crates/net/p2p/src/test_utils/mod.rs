@@ -1,7 +1,9 @@
 mod bodies;
 mod full_block;
 mod headers;
+mod snap;
 
 pub use bodies::*;
 pub use full_block::*;
 pub use headers::*;
+pub use snap::*;
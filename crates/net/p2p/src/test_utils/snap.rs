@@ -0,0 +1,83 @@
+use crate::{
+    download::DownloadClient,
+    error::RequestError,
+    snap::client::{SnapClient, SnapFut},
+};
+use futures::future;
+use reth_eth_wire_types::snap::{
+    AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges, GetTrieNodes,
+    SnapProtocolMessage, StorageRanges, TrieNodes,
+};
+use reth_network_peers::{PeerId, WithPeerId};
+use std::fmt::{Debug, Formatter};
+
+/// A test client for fetching state over `snap`.
+pub struct TestSnapClient<F> {
+    /// The function that is called on each request, it returns the response message.
+    pub responder: F,
+}
+
+impl<F> TestSnapClient<F>
+where
+    F: Fn(SnapProtocolMessage) -> SnapProtocolMessage + Send + Sync,
+{
+    fn respond<T: Send + Sync + 'static>(
+        &self,
+        request: SnapProtocolMessage,
+        f: impl FnOnce(SnapProtocolMessage) -> Option<T>,
+    ) -> SnapFut<T> {
+        let response = f((self.responder)(request))
+            .map(|response| WithPeerId::new(PeerId::default(), response))
+            .ok_or(RequestError::BadResponse);
+        Box::pin(future::ready(response))
+    }
+}
+
+impl<F> Debug for TestSnapClient<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestSnapClient").finish_non_exhaustive()
+    }
+}
+
+impl<F: Sync + Send> DownloadClient for TestSnapClient<F> {
+    fn report_bad_message(&self, _peer_id: PeerId) {
+        // noop
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        0
+    }
+}
+
+impl<F> SnapClient for TestSnapClient<F>
+where
+    F: Fn(SnapProtocolMessage) -> SnapProtocolMessage + Send + Sync,
+{
+    fn get_account_range(&self, request: GetAccountRange) -> SnapFut<AccountRange> {
+        self.respond(SnapProtocolMessage::GetAccountRange(request), |msg| match msg {
+            SnapProtocolMessage::AccountRange(response) => Some(response),
+            _ => None,
+        })
+    }
+
+    fn get_storage_ranges(&self, request: GetStorageRanges) -> SnapFut<StorageRanges> {
+        self.respond(SnapProtocolMessage::GetStorageRanges(request), |msg| match msg {
+            SnapProtocolMessage::StorageRanges(response) => Some(response),
+            _ => None,
+        })
+    }
+
+    fn get_byte_codes(&self, request: GetByteCodes) -> SnapFut<ByteCodes> {
+        self.respond(SnapProtocolMessage::GetByteCodes(request), |msg| match msg {
+            SnapProtocolMessage::ByteCodes(response) => Some(response),
+            _ => None,
+        })
+    }
+
+    fn get_trie_nodes(&self, request: GetTrieNodes) -> SnapFut<TrieNodes> {
+        self.respond(SnapProtocolMessage::GetTrieNodes(request), |msg| match msg {
+            SnapProtocolMessage::TrieNodes(response) => Some(response),
+            _ => None,
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use alloy_rlp::{RlpDecodable, RlpEncodable};
+use reth_primitives::{constants::EMPTY_ROOT_HASH, Bytes, B256, KECCAK_EMPTY, U256};
+use reth_trie::TrieAccount;
+
+/// An account in the "slim" format used by the `snap` protocol.
+///
+/// This is the same as the [`TrieAccount`], except that the empty storage root and the empty code
+/// hash are replaced by empty bytes.
+#[derive(Clone, Debug, PartialEq, Eq, Default, RlpEncodable, RlpDecodable)]
+pub struct SlimAccount {
+    /// Account nonce.
+    pub nonce: u64,
+    /// Account balance.
+    pub balance: U256,
+    /// Account's storage root, empty if the storage is empty.
+    pub storage_root: Bytes,
+    /// Hash of the account's bytecode, empty if the account has no code.
+    pub code_hash: Bytes,
+}
+
+impl SlimAccount {
+    /// Returns the RLP encoded account, this is the account body sent over the wire.
+    pub fn encoded(&self) -> Bytes {
+        alloy_rlp::encode(self).into()
+    }
+}
+
+impl From<TrieAccount> for SlimAccount {
+    fn from(account: TrieAccount) -> Self {
+        let storage_root = if account.storage_root == EMPTY_ROOT_HASH {
+            Bytes::new()
+        } else {
+            account.storage_root.into()
+        };
+        let code_hash =
+            if account.code_hash == KECCAK_EMPTY { Bytes::new() } else { account.code_hash.into() };
+        Self { nonce: account.nonce, balance: account.balance, storage_root, code_hash }
+    }
+}
+
+impl TryFrom<SlimAccount> for TrieAccount {
+    type Error = alloy_rlp::Error;
+
+    fn try_from(account: SlimAccount) -> Result<Self, Self::Error> {
+        fn hash_or(bytes: &Bytes, empty: B256) -> Result<B256, alloy_rlp::Error> {
+            if bytes.is_empty() {
+                return Ok(empty)
+            }
+            B256::try_from(&bytes[..]).map_err(|_| alloy_rlp::Error::Custom("invalid hash length"))
+        }
+
+        Ok(Self {
+            nonce: account.nonce,
+            balance: account.balance,
+            storage_root: hash_or(&account.storage_root, EMPTY_ROOT_HASH)?,
+            code_hash: hash_or(&account.code_hash, KECCAK_EMPTY)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rlp::Decodable;
+
+    #[test]
+    fn slim_account_roundtrip() {
+        let empty = TrieAccount {
+            nonce: 1,
+            balance: U256::from(2),
+            storage_root: EMPTY_ROOT_HASH,
+            code_hash: KECCAK_EMPTY,
+        };
+        let slim = SlimAccount::from(empty);
+        assert!(slim.storage_root.is_empty());
+        assert!(slim.code_hash.is_empty());
+
+        let decoded = SlimAccount::decode(&mut &slim.encoded()[..]).unwrap();
+        assert_eq!(TrieAccount::try_from(decoded).unwrap(), empty);
+
+        let contract = TrieAccount {
+            nonce: 1,
+            balance: U256::ZERO,
+            storage_root: B256::repeat_byte(1),
+            code_hash: B256::repeat_byte(2),
+        };
+        let slim = SlimAccount::from(contract);
+        assert_eq!(TrieAccount::try_from(slim).unwrap(), contract);
+    }
+}
@@ -0,0 +1,175 @@
+//! A [`SnapClient`] that sends requests to the connected `snap` peers.
+
+use parking_lot::RwLock;
+use reth_eth_wire::snap::{
+    AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges, GetTrieNodes,
+    SnapProtocolMessage, StorageRanges, TrieNodes,
+};
+use reth_network::peers::PeersHandle;
+use reth_network_api::ReputationChangeKind;
+use reth_network_p2p::{
+    download::DownloadClient,
+    error::{RequestError, RequestResult},
+    snap::client::{SnapClient, SnapFut},
+};
+use reth_network_peers::{PeerId, WithPeerId};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// Timeout for a single `snap` request.
+pub const SNAP_REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval in which a request that has no peer to be sent to checks for new peers.
+const PEER_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A request to a `snap` peer.
+#[derive(Debug)]
+pub(crate) struct SnapPeerRequest {
+    /// The request message, the request id is assigned by the connection.
+    pub(crate) request: SnapProtocolMessage,
+    /// The channel sender for the response.
+    pub(crate) response: oneshot::Sender<RequestResult<SnapProtocolMessage>>,
+}
+
+/// The set of connected peers that support the `snap` protocol.
+///
+/// This is shared between the [`SnapProtocolHandler`](crate::SnapProtocolHandler), which registers
+/// the connections, and the [`SnapFetchClient`], which sends requests to them.
+#[derive(Debug, Clone, Default)]
+pub struct SnapPeers {
+    inner: Arc<RwLock<HashMap<PeerId, mpsc::UnboundedSender<SnapPeerRequest>>>>,
+    /// Used to distribute requests over all peers.
+    next_peer: Arc<AtomicUsize>,
+}
+
+impl SnapPeers {
+    /// Returns the number of connected `snap` peers.
+    pub fn len(&self) -> usize {
+        self.inner.read().len()
+    }
+
+    /// Returns `true` if there are no connected `snap` peers.
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().is_empty()
+    }
+
+    /// Registers the connection to the given peer.
+    pub(crate) fn insert(&self, peer_id: PeerId, requests: mpsc::UnboundedSender<SnapPeerRequest>) {
+        self.inner.write().insert(peer_id, requests);
+    }
+
+    /// Removes the connection to the given peer, unless it was replaced by a newer connection.
+    pub(crate) fn remove(
+        &self,
+        peer_id: &PeerId,
+        requests: &mpsc::UnboundedSender<SnapPeerRequest>,
+    ) {
+        let mut peers = self.inner.write();
+        if peers.get(peer_id).is_some_and(|tx| tx.same_channel(requests)) {
+            peers.remove(peer_id);
+        }
+    }
+
+    /// Returns the next peer to send a request to.
+    fn next_peer(&self) -> Option<(PeerId, mpsc::UnboundedSender<SnapPeerRequest>)> {
+        let peers = self.inner.read();
+        if peers.is_empty() {
+            return None
+        }
+        let idx = self.next_peer.fetch_add(1, Ordering::Relaxed) % peers.len();
+        peers.iter().nth(idx).map(|(peer_id, tx)| (*peer_id, tx.clone()))
+    }
+}
+
+/// A [`SnapClient`] that distributes requests over all connected `snap` peers.
+///
+/// Requests wait until a peer is available and time out after [`SNAP_REQUEST_TIMEOUT`].
+#[derive(Debug, Clone)]
+pub struct SnapFetchClient {
+    /// The connected `snap` peers.
+    peers: SnapPeers,
+    /// Handle used to penalize peers.
+    peers_handle: PeersHandle,
+}
+
+impl SnapFetchClient {
+    /// Creates a new client that sends requests to the given peers.
+    pub const fn new(peers: SnapPeers, peers_handle: PeersHandle) -> Self {
+        Self { peers, peers_handle }
+    }
+
+    /// Sends the request to the next available peer and returns the response message.
+    fn request<T>(
+        &self,
+        request: SnapProtocolMessage,
+        response: fn(SnapProtocolMessage) -> Option<T>,
+    ) -> SnapFut<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let peers = self.peers.clone();
+        Box::pin(async move {
+            let (peer_id, to_peer) = loop {
+                if let Some(peer) = peers.next_peer() {
+                    break peer
+                }
+                tokio::time::sleep(PEER_POLL_INTERVAL).await;
+            };
+
+            let (tx, rx) = oneshot::channel();
+            to_peer.send(SnapPeerRequest { request, response: tx })?;
+            let msg = tokio::time::timeout(SNAP_REQUEST_TIMEOUT, rx)
+                .await
+                .map_err(|_| RequestError::Timeout)?
+                .map_err(|_| RequestError::ConnectionDropped)??;
+            response(msg).map(|msg| WithPeerId::new(peer_id, msg)).ok_or(RequestError::BadResponse)
+        })
+    }
+}
+
+impl DownloadClient for SnapFetchClient {
+    fn report_bad_message(&self, peer_id: PeerId) {
+        self.peers_handle.reputation_change(peer_id, ReputationChangeKind::BadMessage);
+    }
+
+    fn num_connected_peers(&self) -> usize {
+        self.peers.len()
+    }
+}
+
+impl SnapClient for SnapFetchClient {
+    fn get_account_range(&self, request: GetAccountRange) -> SnapFut<AccountRange> {
+        self.request(SnapProtocolMessage::GetAccountRange(request), |msg| match msg {
+            SnapProtocolMessage::AccountRange(response) => Some(response),
+            _ => None,
+        })
+    }
+
+    fn get_storage_ranges(&self, request: GetStorageRanges) -> SnapFut<StorageRanges> {
+        self.request(SnapProtocolMessage::GetStorageRanges(request), |msg| match msg {
+            SnapProtocolMessage::StorageRanges(response) => Some(response),
+            _ => None,
+        })
+    }
+
+    fn get_byte_codes(&self, request: GetByteCodes) -> SnapFut<ByteCodes> {
+        self.request(SnapProtocolMessage::GetByteCodes(request), |msg| match msg {
+            SnapProtocolMessage::ByteCodes(response) => Some(response),
+            _ => None,
+        })
+    }
+
+    fn get_trie_nodes(&self, request: GetTrieNodes) -> SnapFut<TrieNodes> {
+        self.request(SnapProtocolMessage::GetTrieNodes(request), |msg| match msg {
+            SnapProtocolMessage::TrieNodes(response) => Some(response),
+            _ => None,
+        })
+    }
+}
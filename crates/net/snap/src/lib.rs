@@ -0,0 +1,32 @@
+//! Support for the `snap/1` protocol: <https://github.com/ethereum/devp2p/blob/master/caps/snap.md>
+//!
+//! The `snap` protocol runs side-by-side with `eth` and allows peers to retrieve contiguous ranges
+//! of the state trie together with the merkle proofs for the range boundaries.
+//!
+//! The [`SnapProtocolHandler`] is installed as an additional `RLPx` sub-protocol on the network and
+//! forwards all incoming requests to the [`SnapRequestHandler`], which serves them from the hashed
+//! state tables of the database. Outgoing requests are sent with the [`SnapFetchClient`], which
+//! distributes them over all connected [`SnapPeers`].
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+mod account;
+pub use account::SlimAccount;
+
+pub mod client;
+pub use client::{SnapFetchClient, SnapPeers, SNAP_REQUEST_TIMEOUT};
+
+pub mod protocol;
+pub use protocol::{snap_capability, snap_protocol, SnapProtocolHandler};
+
+pub mod server;
+pub use server::{
+    serve_request, IncomingSnapRequest, SnapRequestHandler, SNAP_REQUEST_CHANNEL_CAPACITY,
+    SNAP_SERVED_BLOCKS,
+};
@@ -0,0 +1,263 @@
+//! The `snap` `RLPx` sub-protocol.
+
+use crate::{
+    client::{SnapPeerRequest, SnapPeers},
+    server::{empty_response, IncomingSnapRequest},
+};
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use reth_eth_wire::{
+    capability::{Capability, SharedCapabilities},
+    multiplex::ProtocolConnection,
+    protocol::Protocol,
+    snap::{SnapMessageId, SnapProtocolMessage, SNAP_VERSION},
+};
+use reth_network::protocol::{ConnectionHandler, OnNotSupported, ProtocolHandler};
+use reth_network_api::Direction;
+use reth_network_p2p::error::RequestError;
+use reth_network_peers::PeerId;
+use reth_primitives::BytesMut;
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc, oneshot};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{debug, trace};
+
+/// Returns the `snap/1` capability.
+pub const fn snap_capability() -> Capability {
+    Capability::new_static("snap", SNAP_VERSION)
+}
+
+/// Returns the `snap/1` protocol.
+pub const fn snap_protocol() -> Protocol {
+    Protocol::new(snap_capability(), SnapMessageId::message_count())
+}
+
+/// The [`ProtocolHandler`] that announces the `snap` protocol.
+///
+/// All `snap` connections are registered in the [`SnapPeers`] so they can be used by the
+/// [`SnapFetchClient`](crate::SnapFetchClient). If a request handler is configured, incoming
+/// requests are forwarded to the [`SnapRequestHandler`](crate::SnapRequestHandler), otherwise they
+/// are answered with empty responses.
+#[derive(Debug, Clone)]
+pub struct SnapProtocolHandler {
+    /// The connected `snap` peers.
+    peers: SnapPeers,
+    /// Sender half for incoming requests of all connections.
+    to_request_handler: Option<mpsc::Sender<IncomingSnapRequest>>,
+}
+
+impl SnapProtocolHandler {
+    /// Creates a new handler that registers all connections in the given [`SnapPeers`].
+    pub const fn new(peers: SnapPeers) -> Self {
+        Self { peers, to_request_handler: None }
+    }
+
+    /// Forwards incoming requests to the given channel.
+    pub fn with_request_handler(
+        mut self,
+        to_request_handler: mpsc::Sender<IncomingSnapRequest>,
+    ) -> Self {
+        self.to_request_handler = Some(to_request_handler);
+        self
+    }
+
+    fn connection_handler(&self) -> SnapConnectionHandler {
+        SnapConnectionHandler {
+            peers: self.peers.clone(),
+            to_request_handler: self.to_request_handler.clone(),
+        }
+    }
+}
+
+impl ProtocolHandler for SnapProtocolHandler {
+    type ConnectionHandler = SnapConnectionHandler;
+
+    fn on_incoming(&self, _socket_addr: SocketAddr) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+
+    fn on_outgoing(
+        &self,
+        _socket_addr: SocketAddr,
+        _peer_id: PeerId,
+    ) -> Option<Self::ConnectionHandler> {
+        Some(self.connection_handler())
+    }
+}
+
+/// The [`ConnectionHandler`] for a single `snap` connection.
+#[derive(Debug)]
+pub struct SnapConnectionHandler {
+    peers: SnapPeers,
+    to_request_handler: Option<mpsc::Sender<IncomingSnapRequest>>,
+}
+
+impl ConnectionHandler for SnapConnectionHandler {
+    type Connection = SnapConnection;
+
+    fn protocol(&self) -> Protocol {
+        snap_protocol()
+    }
+
+    fn on_unsupported_by_peer(
+        self,
+        _supported: &SharedCapabilities,
+        _direction: Direction,
+        _peer_id: PeerId,
+    ) -> OnNotSupported {
+        // `snap` is a satellite protocol of `eth`, peers that don't support it are still useful
+        OnNotSupported::KeepAlive
+    }
+
+    fn into_connection(
+        self,
+        _direction: Direction,
+        peer_id: PeerId,
+        conn: ProtocolConnection,
+    ) -> Self::Connection {
+        let (to_connection, requests) = mpsc::unbounded_channel();
+        self.peers.insert(peer_id, to_connection.clone());
+        SnapConnection {
+            peer_id,
+            conn,
+            to_request_handler: self.to_request_handler,
+            pending_responses: Default::default(),
+            peers: self.peers,
+            to_connection,
+            requests: UnboundedReceiverStream::new(requests),
+            inflight_requests: Default::default(),
+            next_request_id: 0,
+        }
+    }
+}
+
+/// A `snap` connection with a peer.
+///
+/// Requests from the peer are delegated to the request handler and the responses are sent back
+/// once they are resolved. Requests to the peer are received from the [`SnapPeers`] registry.
+#[derive(Debug)]
+pub struct SnapConnection {
+    /// The remote peer.
+    peer_id: PeerId,
+    /// The underlying `RLPx` connection.
+    conn: ProtocolConnection,
+    /// Sender half for incoming requests.
+    to_request_handler: Option<mpsc::Sender<IncomingSnapRequest>>,
+    /// Responses that are currently being served.
+    pending_responses: FuturesUnordered<oneshot::Receiver<SnapProtocolMessage>>,
+    /// The registry this connection is registered in.
+    peers: SnapPeers,
+    /// The sender half registered in [`SnapPeers`], used to unregister the connection.
+    to_connection: mpsc::UnboundedSender<SnapPeerRequest>,
+    /// Requests that should be sent to the peer.
+    requests: UnboundedReceiverStream<SnapPeerRequest>,
+    /// Requests sent to the peer that are awaiting a response, by request id.
+    inflight_requests: HashMap<u64, SnapPeerRequest>,
+    /// The request id of the next request sent to the peer.
+    next_request_id: u64,
+}
+
+impl SnapConnection {
+    /// Handles a message received from the peer.
+    ///
+    /// Returns `false` if the peer violated the protocol and the connection should be closed.
+    fn on_message(&mut self, msg: BytesMut) -> bool {
+        let msg = match SnapProtocolMessage::decode_message(&mut &msg[..]) {
+            Ok(msg) => msg,
+            Err(err) => {
+                debug!(target: "net::snap", peer_id=%self.peer_id, %err, "Failed to decode snap message");
+                return false
+            }
+        };
+
+        if !msg.is_request() {
+            return self.on_response(msg)
+        }
+
+        let (response, rx) = oneshot::channel();
+        let Some(to_request_handler) = &self.to_request_handler else {
+            // we're not serving state
+            let _ = response.send(empty_response(&msg));
+            self.pending_responses.push(rx);
+            return true
+        };
+
+        let request = IncomingSnapRequest { peer_id: self.peer_id, request: msg, response };
+        if let Err(err) = to_request_handler.try_send(request) {
+            // the request handler is overloaded, the request is dropped and the peer will treat
+            // it as timed out
+            trace!(target: "net::snap", peer_id=%self.peer_id, %err, "Dropping snap request");
+        } else {
+            self.pending_responses.push(rx);
+        }
+
+        true
+    }
+
+    /// Resolves the inflight request the response belongs to.
+    ///
+    /// Returns `false` if the response was not requested.
+    fn on_response(&mut self, msg: SnapProtocolMessage) -> bool {
+        let Some(request) = self.inflight_requests.remove(&msg.request_id()) else {
+            debug!(target: "net::snap", peer_id=%self.peer_id, id=?msg.message_id(), "Received unsolicited snap response");
+            return false
+        };
+
+        // responses have the message id following the request's
+        let result = if msg.message_id() as u8 == request.request.message_id() as u8 + 1 {
+            Ok(msg)
+        } else {
+            Err(RequestError::BadResponse)
+        };
+        let _ = request.response.send(result);
+        true
+    }
+}
+
+impl Stream for SnapConnection {
+    type Item = BytesMut;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            // send out resolved responses first
+            if let Poll::Ready(Some(response)) = this.pending_responses.poll_next_unpin(cx) {
+                if let Ok(response) = response {
+                    return Poll::Ready(Some(response.encoded()))
+                }
+                continue
+            }
+
+            // send out requests to the peer
+            if let Poll::Ready(Some(mut request)) = this.requests.poll_next_unpin(cx) {
+                let request_id = this.next_request_id;
+                this.next_request_id += 1;
+                request.request.set_request_id(request_id);
+                let msg = request.request.encoded();
+                this.inflight_requests.insert(request_id, request);
+                return Poll::Ready(Some(msg))
+            }
+
+            match this.conn.poll_next_unpin(cx) {
+                Poll::Ready(Some(msg)) => {
+                    if !this.on_message(msg) {
+                        return Poll::Ready(None)
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Drop for SnapConnection {
+    fn drop(&mut self) {
+        self.peers.remove(&self.peer_id, &self.to_connection);
+    }
+}
@@ -0,0 +1,581 @@
+//! Serves `snap` requests from the database.
+
+use crate::SlimAccount;
+use futures::StreamExt;
+use reth_db::tables;
+use reth_db_api::{database::Database, transaction::DbTx};
+use reth_eth_wire::snap::{
+    AccountData, AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges,
+    GetTrieNodes, SnapProtocolMessage, StorageData, StorageRanges, TrieNodes,
+};
+use reth_execution_errors::StateRootError;
+use reth_network_peers::PeerId;
+use reth_primitives::{Bytes, B256, KECCAK_EMPTY};
+use reth_provider::{
+    BlockNumReader, DatabaseProviderFactory, DatabaseProviderRO, HeaderProvider, ProviderError,
+    ProviderResult, PruneCheckpointReader,
+};
+use reth_prune_types::PruneSegment;
+use reth_trie::{
+    hashed_cursor::{HashedCursor, HashedCursorFactory, HashedPostStateCursorFactory},
+    proof::Proof,
+    HashedPostState, Nibbles, TrieAccount,
+};
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::sync::{mpsc::Receiver, oneshot};
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::debug;
+
+// Limits: <https://github.com/ethereum/go-ethereum/blob/v1.14.3/eth/protocols/snap/handler.go#L34-L53>
+
+/// Hard limit for the size of a response in bytes, the requested response size is capped by this.
+pub const MAX_SNAP_RESPONSE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// The number of most recent blocks whose state is served.
+pub const SNAP_SERVED_BLOCKS: u64 = 128;
+
+/// Maximum number of byte codes to look up for a single request.
+pub const MAX_CODE_LOOKUPS: usize = 1024;
+
+/// Maximum number of trie nodes to look up for a single request.
+pub const MAX_TRIE_NODE_LOOKUPS: usize = 1024;
+
+/// The default capacity of the channel for incoming `snap` requests.
+pub const SNAP_REQUEST_CHANNEL_CAPACITY: usize = 256;
+
+/// Manages incoming `snap` requests.
+///
+/// This should be spawned or used as part of `tokio::select!`.
+#[derive(Debug)]
+#[must_use = "Manager does nothing unless polled."]
+pub struct SnapRequestHandler<Provider, DB> {
+    /// Provides access to the hashed state and the tries.
+    provider: Provider,
+    /// Incoming requests from all `snap` connections.
+    incoming_requests: ReceiverStream<IncomingSnapRequest>,
+    _db: PhantomData<DB>,
+}
+
+impl<Provider, DB> SnapRequestHandler<Provider, DB> {
+    /// Create a new instance
+    pub fn new(provider: Provider, incoming: Receiver<IncomingSnapRequest>) -> Self {
+        Self { provider, incoming_requests: ReceiverStream::new(incoming), _db: PhantomData }
+    }
+}
+
+impl<Provider, DB> SnapRequestHandler<Provider, DB>
+where
+    Provider: DatabaseProviderFactory<DB>,
+    DB: Database,
+{
+    /// Serves the request and sends the response back to the connection.
+    fn on_request(&self, request: IncomingSnapRequest) {
+        let IncomingSnapRequest { peer_id, request, response } = request;
+
+        let request_id = request.request_id();
+        let res = self
+            .provider
+            .database_provider_ro()
+            .and_then(|provider| serve_request(&provider, request));
+
+        let msg = match res {
+            Ok(msg) => msg,
+            Err(err) => {
+                debug!(target: "net::snap", %peer_id, request_id, %err, "Failed to serve snap request");
+                return
+            }
+        };
+
+        let _ = response.send(msg);
+    }
+}
+
+/// An endless future.
+///
+/// This should be spawned or used as part of `tokio::select!`.
+impl<Provider, DB> Future for SnapRequestHandler<Provider, DB>
+where
+    Provider: DatabaseProviderFactory<DB> + Unpin,
+    DB: Database,
+{
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            match this.incoming_requests.poll_next_unpin(cx) {
+                Poll::Ready(Some(request)) => this.on_request(request),
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// A `snap` request received from a peer.
+#[derive(Debug)]
+pub struct IncomingSnapRequest {
+    /// The peer that sent the request.
+    pub peer_id: PeerId,
+    /// The request message.
+    pub request: SnapProtocolMessage,
+    /// The channel sender for the response.
+    pub response: oneshot::Sender<SnapProtocolMessage>,
+}
+
+/// Serves the request from the state of one of the [`SNAP_SERVED_BLOCKS`] most recent blocks.
+///
+/// Requests for other roots are answered with an empty response.
+pub fn serve_request<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    request: SnapProtocolMessage,
+) -> ProviderResult<SnapProtocolMessage> {
+    let root_hash = match request {
+        SnapProtocolMessage::GetAccountRange(ref req) => req.root_hash,
+        SnapProtocolMessage::GetStorageRanges(ref req) => req.root_hash,
+        SnapProtocolMessage::GetTrieNodes(ref req) => req.root_hash,
+        // bytecodes don't depend on the state root
+        SnapProtocolMessage::GetByteCodes(req) => {
+            return get_byte_codes(provider, req).map(SnapProtocolMessage::ByteCodes)
+        }
+        msg => return Ok(empty_response(&msg)),
+    };
+    let Some(reverts) = state_reverts(provider, root_hash)? else {
+        return Ok(empty_response(&request))
+    };
+
+    let tx = provider.tx_ref();
+    let prefix_sets = reverts.construct_prefix_sets();
+    let reverts = reverts.into_sorted();
+    let cursors = HashedPostStateCursorFactory::new(tx, &reverts);
+    let state = ServedState {
+        cursors: cursors.clone(),
+        proof: Proof::new(tx).with_hashed_cursor_factory(cursors).with_prefix_sets(prefix_sets),
+    };
+
+    match request {
+        SnapProtocolMessage::GetAccountRange(req) => {
+            get_account_range(&state, req).map(SnapProtocolMessage::AccountRange)
+        }
+        SnapProtocolMessage::GetStorageRanges(req) => {
+            get_storage_ranges(&state, req).map(SnapProtocolMessage::StorageRanges)
+        }
+        SnapProtocolMessage::GetTrieNodes(req) => {
+            get_trie_nodes(&state, req).map(SnapProtocolMessage::TrieNodes)
+        }
+        msg => Ok(empty_response(&msg)),
+    }
+}
+
+/// Returns the reverts from the latest state to the state with the given root, if it is the state
+/// of one of the [`SNAP_SERVED_BLOCKS`] most recent blocks.
+///
+/// The reverts are empty for the latest block. Blocks whose changesets were pruned are not served.
+fn state_reverts<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    root: B256,
+) -> ProviderResult<Option<HashedPostState>> {
+    let best_block = provider.best_block_number()?;
+    for block in (best_block.saturating_sub(SNAP_SERVED_BLOCKS - 1)..=best_block).rev() {
+        let Some(header) = provider.header_by_number(block)? else { break };
+        if header.state_root != root {
+            continue
+        }
+
+        for segment in [PruneSegment::AccountHistory, PruneSegment::StorageHistory] {
+            let pruned = provider.get_prune_checkpoint(segment)?.and_then(|c| c.block_number);
+            if pruned.is_some_and(|pruned| pruned > block) {
+                return Ok(None)
+            }
+        }
+
+        let changesets = block + 1..=best_block;
+        let reverts = HashedPostState::from_reverts(
+            provider.account_changesets_range(changesets.clone())?.into_iter().map(Ok),
+            provider.storage_changesets_range(changesets)?.into_iter().map(Ok),
+        )?;
+        return Ok(Some(reverts))
+    }
+    Ok(None)
+}
+
+/// The state of a served block, the latest state overlaid with the reverts to the block.
+struct ServedState<'a, TX> {
+    /// The cursors over the hashed state of the block.
+    cursors: HashedPostStateCursorFactory<'a, &'a TX>,
+    /// Computes the storage roots and proofs of the block.
+    proof: Proof<'a, TX, HashedPostStateCursorFactory<'a, &'a TX>>,
+}
+
+/// Returns the empty response to the given request.
+pub(crate) const fn empty_response(request: &SnapProtocolMessage) -> SnapProtocolMessage {
+    let request_id = request.request_id();
+    match request {
+        SnapProtocolMessage::GetStorageRanges(_) => {
+            SnapProtocolMessage::StorageRanges(StorageRanges {
+                request_id,
+                slots: Vec::new(),
+                proof: Vec::new(),
+            })
+        }
+        SnapProtocolMessage::GetByteCodes(_) => {
+            SnapProtocolMessage::ByteCodes(ByteCodes { request_id, codes: Vec::new() })
+        }
+        SnapProtocolMessage::GetTrieNodes(_) => {
+            SnapProtocolMessage::TrieNodes(TrieNodes { request_id, nodes: Vec::new() })
+        }
+        _ => SnapProtocolMessage::AccountRange(AccountRange {
+            request_id,
+            accounts: Vec::new(),
+            proof: Vec::new(),
+        }),
+    }
+}
+
+/// Converts the request's hash bytes, empty bytes are treated as `default`.
+fn hash_or(bytes: &Bytes, default: B256) -> B256 {
+    if bytes.is_empty() {
+        return default
+    }
+    B256::try_from(&bytes[..]).unwrap_or(default)
+}
+
+/// Decodes a compact (hex-prefix) encoded trie path.
+fn decode_compact_path(compact: &[u8]) -> Option<Nibbles> {
+    let (&first, rest) = compact.split_first()?;
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    // odd length paths carry the first nibble in the flag byte
+    if first & 0x10 != 0 {
+        nibbles.push(first & 0x0f);
+    }
+    for byte in rest {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    Some(Nibbles::from_nibbles_unchecked(nibbles))
+}
+
+/// Returns the consecutive accounts starting at the requested hash, with the proofs for the first
+/// and the last hash of the range.
+fn get_account_range<TX: DbTx>(
+    state: &ServedState<'_, TX>,
+    request: GetAccountRange,
+) -> ProviderResult<AccountRange> {
+    let GetAccountRange { request_id, starting_hash, limit_hash, response_bytes, .. } = request;
+    let response_bytes = response_bytes.min(MAX_SNAP_RESPONSE_BYTES);
+
+    let mut accounts = Vec::new();
+    let mut size = 0;
+    let mut cursor = state.cursors.hashed_account_cursor()?;
+    let mut entry = cursor.seek(starting_hash)?;
+    while let Some((hashed_address, account)) = entry {
+        let storage_root = state.proof.storage_root(hashed_address).map_err(state_error)?;
+        let body = SlimAccount::from(TrieAccount::from((account, storage_root))).encoded();
+
+        size += (B256::len_bytes() + body.len()) as u64;
+        accounts.push(AccountData { hash: hashed_address, body });
+
+        // the first account after the limit is included to prove that the range is complete
+        if hashed_address >= limit_hash || size >= response_bytes {
+            break
+        }
+        entry = cursor.next()?;
+    }
+
+    let targets = std::iter::once(starting_hash)
+        .chain(accounts.last().map(|account| account.hash))
+        .map(Nibbles::unpack);
+    let proof =
+        state.proof.account_proof_nodes(targets).map_err(state_error)?.into_values().collect();
+
+    Ok(AccountRange { request_id, accounts, proof })
+}
+
+/// Returns the consecutive storage slots of the requested accounts.
+///
+/// The proof is only attached for the last storage range if it is incomplete or starts at a
+/// non-zero hash.
+fn get_storage_ranges<TX: DbTx>(
+    state: &ServedState<'_, TX>,
+    request: GetStorageRanges,
+) -> ProviderResult<StorageRanges> {
+    let GetStorageRanges {
+        request_id,
+        account_hashes,
+        starting_hash,
+        limit_hash,
+        response_bytes,
+        ..
+    } = request;
+    let response_bytes = response_bytes.min(MAX_SNAP_RESPONSE_BYTES);
+
+    let mut slots = Vec::new();
+    let mut proof = Vec::new();
+    let mut size = 0;
+    for (idx, hashed_address) in account_hashes.into_iter().enumerate() {
+        if size >= response_bytes {
+            break
+        }
+
+        // the range only applies to the first account
+        let (origin, limit) = if idx == 0 {
+            (hash_or(&starting_hash, B256::ZERO), hash_or(&limit_hash, B256::repeat_byte(0xff)))
+        } else {
+            (B256::ZERO, B256::repeat_byte(0xff))
+        };
+
+        let mut storage = Vec::new();
+        let mut aborted = false;
+        let mut cursor = state.cursors.hashed_storage_cursor(hashed_address)?;
+        let mut entry = cursor.seek(origin)?;
+        while let Some((hashed_slot, value)) = entry {
+            if size >= response_bytes {
+                aborted = true;
+                break
+            }
+
+            let data: Bytes = alloy_rlp::encode(value).into();
+            size += (B256::len_bytes() + data.len()) as u64;
+            storage.push(StorageData { hash: hashed_slot, data });
+
+            entry = cursor.next()?;
+            // the range is cut short by the limit if there are more slots after it
+            if hashed_slot >= limit {
+                aborted = entry.is_some();
+                break
+            }
+        }
+
+        if origin != B256::ZERO || aborted {
+            let targets = std::iter::once(origin)
+                .chain(storage.last().map(|slot| slot.hash))
+                .map(Nibbles::unpack);
+            proof = state
+                .proof
+                .storage_proof_nodes(hashed_address, targets)
+                .map_err(state_error)?
+                .into_values()
+                .collect();
+        }
+
+        slots.push(storage);
+
+        // a proof is only allowed for the last range
+        if !proof.is_empty() {
+            break
+        }
+    }
+
+    Ok(StorageRanges { request_id, slots, proof })
+}
+
+/// Returns the requested byte codes, stops at the first unknown code.
+fn get_byte_codes<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    request: GetByteCodes,
+) -> ProviderResult<ByteCodes> {
+    let GetByteCodes { request_id, hashes, response_bytes } = request;
+    let response_bytes = response_bytes.min(MAX_SNAP_RESPONSE_BYTES);
+
+    let tx = provider.tx_ref();
+    let mut codes = Vec::new();
+    let mut size = 0;
+    for hash in hashes.into_iter().take(MAX_CODE_LOOKUPS) {
+        let code = if hash == KECCAK_EMPTY {
+            Bytes::new()
+        } else {
+            let Some(code) = tx.get::<tables::Bytecodes>(hash)? else { break };
+            code.original_bytes()
+        };
+
+        size += code.len() as u64;
+        codes.push(code);
+
+        if size >= response_bytes {
+            break
+        }
+    }
+
+    Ok(ByteCodes { request_id, codes })
+}
+
+/// Returns the requested trie nodes, stops at the first unknown node.
+fn get_trie_nodes<TX: DbTx>(
+    state: &ServedState<'_, TX>,
+    request: GetTrieNodes,
+) -> ProviderResult<TrieNodes> {
+    let GetTrieNodes { request_id, paths, response_bytes, .. } = request;
+    let response_bytes = response_bytes.min(MAX_SNAP_RESPONSE_BYTES);
+
+    let proof = &state.proof;
+
+    // resolve all account trie nodes at once
+    let account_paths = paths
+        .iter()
+        .filter(|path_set| path_set.len() == 1)
+        .filter_map(|path_set| decode_compact_path(&path_set[0]))
+        .collect::<Vec<_>>();
+    let mut account_nodes = if account_paths.is_empty() {
+        Default::default()
+    } else {
+        proof.account_proof_nodes(account_paths).map_err(state_error)?
+    };
+
+    let mut nodes = Vec::new();
+    let mut size = 0;
+    'outer: for path_set in paths {
+        match path_set.as_slice() {
+            [] => break,
+            [account_path] => {
+                let Some(node) =
+                    decode_compact_path(account_path).and_then(|path| account_nodes.remove(&path))
+                else {
+                    break
+                };
+                size += node.len() as u64;
+                nodes.push(node);
+            }
+            [hashed_address, storage_paths @ ..] => {
+                let Ok(hashed_address) = B256::try_from(&hashed_address[..]) else { break };
+                let storage_paths =
+                    storage_paths.iter().map(|path| decode_compact_path(path)).collect::<Vec<_>>();
+                let mut storage_nodes = proof
+                    .storage_proof_nodes(hashed_address, storage_paths.iter().flatten().cloned())
+                    .map_err(state_error)?;
+                for path in storage_paths {
+                    let Some(node) = path.and_then(|path| storage_nodes.remove(&path)) else {
+                        break 'outer
+                    };
+                    size += node.len() as u64;
+                    nodes.push(node);
+                }
+            }
+        }
+
+        if nodes.len() >= MAX_TRIE_NODE_LOOKUPS || size >= response_bytes {
+            break
+        }
+    }
+
+    Ok(TrieNodes { request_id, nodes })
+}
+
+/// Converts a trie error into a [`ProviderError`].
+fn state_error(err: impl Into<StateRootError>) -> ProviderError {
+    ProviderError::Database(err.into().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rlp::Decodable;
+    use reth_db_api::transaction::DbTxMut;
+    use reth_primitives::{keccak256, Account, Header, StorageEntry, U256};
+    use reth_provider::test_utils::create_test_provider_factory;
+    use reth_trie::StateRoot;
+
+    /// Computes the trie of the hashed state and makes it the state of the genesis block.
+    fn commit_state<TX: DbTxMut + DbTx>(tx: &TX) -> B256 {
+        let (root, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        updates.flush(tx).unwrap();
+        let header = Header { state_root: root, ..Default::default() };
+        tx.put::<tables::CanonicalHeaders>(0, header.hash_slow()).unwrap();
+        tx.put::<tables::Headers>(0, header).unwrap();
+        root
+    }
+
+    #[test]
+    fn serve_account_range() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        for i in 1..=3u8 {
+            let account = Account { nonce: i as u64, balance: U256::from(i), bytecode_hash: None };
+            provider.tx_ref().put::<tables::HashedAccounts>(B256::repeat_byte(i), account).unwrap();
+        }
+        let root = commit_state(provider.tx_ref());
+        provider.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let request = GetAccountRange {
+            request_id: 1,
+            root_hash: root,
+            starting_hash: B256::ZERO,
+            limit_hash: B256::repeat_byte(2),
+            response_bytes: MAX_SNAP_RESPONSE_BYTES,
+        };
+        let Ok(SnapProtocolMessage::AccountRange(range)) =
+            serve_request(&provider, SnapProtocolMessage::GetAccountRange(request.clone()))
+        else {
+            panic!("unexpected response")
+        };
+
+        // the range ends with the first account at or after the limit
+        let hashes = range.accounts.iter().map(|account| account.hash).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![B256::repeat_byte(1), B256::repeat_byte(2)]);
+
+        let account = SlimAccount::decode(&mut &range.accounts[0].body[..]).unwrap();
+        assert_eq!(account.nonce, 1);
+        assert!(account.storage_root.is_empty());
+
+        // the proof contains the root node
+        assert!(range.proof.iter().any(|node| keccak256(node) == root));
+
+        // unknown roots are not served
+        let request = GetAccountRange { root_hash: B256::repeat_byte(0xff), ..request };
+        let Ok(SnapProtocolMessage::AccountRange(range)) =
+            serve_request(&provider, SnapProtocolMessage::GetAccountRange(request))
+        else {
+            panic!("unexpected response")
+        };
+        assert!(range.accounts.is_empty() && range.proof.is_empty());
+    }
+
+    #[test]
+    fn serve_storage_range_cut_by_limit() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let hashed_address = B256::repeat_byte(1);
+        provider
+            .tx_ref()
+            .put::<tables::HashedAccounts>(hashed_address, Account::default())
+            .unwrap();
+        for i in 1..=3u8 {
+            let entry = StorageEntry { key: B256::repeat_byte(i), value: U256::from(i) };
+            provider.tx_ref().put::<tables::HashedStorages>(hashed_address, entry).unwrap();
+        }
+        let root = commit_state(provider.tx_ref());
+        provider.commit().unwrap();
+
+        let provider = factory.provider().unwrap();
+        let request = |limit_hash: B256| {
+            let request = GetStorageRanges {
+                request_id: 1,
+                root_hash: root,
+                account_hashes: vec![hashed_address],
+                starting_hash: Bytes::new(),
+                limit_hash: Bytes::copy_from_slice(limit_hash.as_slice()),
+                response_bytes: MAX_SNAP_RESPONSE_BYTES,
+            };
+            match serve_request(&provider, SnapProtocolMessage::GetStorageRanges(request)) {
+                Ok(SnapProtocolMessage::StorageRanges(ranges)) => ranges,
+                res => panic!("unexpected response {res:?}"),
+            }
+        };
+
+        // the range is cut short by the limit, the proof is attached although it starts at zero
+        let ranges = request(B256::repeat_byte(2));
+        assert_eq!(ranges.slots[0].len(), 2);
+        assert!(!ranges.proof.is_empty());
+
+        // the complete storage doesn't need a proof
+        let ranges = request(B256::repeat_byte(3));
+        assert_eq!(ranges.slots[0].len(), 3);
+        assert!(ranges.proof.is_empty());
+    }
+}
@@ -0,0 +1,58 @@
+//! clap [Args](clap::Args) for execution extensions
+
+use clap::Args;
+
+/// Parameters for execution extensions
+#[derive(Debug, Clone, Args, PartialEq, Eq, Default)]
+#[command(next_help_heading = "ExEx")]
+pub struct ExExArgs {
+    /// Stream the notifications of the node to other processes over the local socket at the
+    /// given path.
+    ///
+    /// Clients subscribe from a block height and acknowledge the blocks they have processed,
+    /// which holds back pruning.
+    #[arg(long = "exex.remote-socket", value_name = "PATH", verbatim_doc_comment)]
+    pub remote_socket: Option<String>,
+
+    /// The number of blocks below the tip that are not pruned for remote `ExEx` clients, even
+    /// when no client is connected.
+    ///
+    /// Clients can only subscribe from heights whose history is not pruned.
+    #[arg(
+        long = "exex.remote-retention",
+        value_name = "BLOCKS",
+        default_value_t = 0,
+        verbatim_doc_comment
+    )]
+    pub remote_retention: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// A helper type to parse Args more easily
+    #[derive(Parser)]
+    struct CommandParser<T: Args> {
+        #[command(flatten)]
+        args: T,
+    }
+
+    #[test]
+    fn test_parse_exex_args() {
+        let args = CommandParser::<ExExArgs>::parse_from(["reth"]).args;
+        assert_eq!(args, ExExArgs::default());
+
+        let args = CommandParser::<ExExArgs>::parse_from([
+            "reth",
+            "--exex.remote-socket",
+            "/tmp/exex.ipc",
+            "--exex.remote-retention",
+            "64",
+        ])
+        .args;
+        assert_eq!(args.remote_socket.as_deref(), Some("/tmp/exex.ipc"));
+        assert_eq!(args.remote_retention, 64);
+    }
+}
@@ -47,6 +47,10 @@ pub use txpool::TxPoolArgs;
 mod dev;
 pub use dev::DevArgs;
 
+/// ExExArgs for configuring the execution extensions
+mod exex;
+pub use exex::ExExArgs;
+
 /// PruneArgs for configuring the pruning and full node
 mod pruning;
 pub use pruning::PruningArgs;
@@ -32,6 +32,9 @@ impl PruningArgs {
                     .map(|contract| PruneMode::Before(contract.block)),
                 account_history: Some(PruneMode::Distance(MINIMUM_PRUNING_DISTANCE)),
                 storage_history: Some(PruneMode::Distance(MINIMUM_PRUNING_DISTANCE)),
+                address_transactions: None,
+                account_history_retention: Default::default(),
+                storage_history_retention: Default::default(),
                 receipts_log_filter: ReceiptsLogPruneConfig(
                     chain_spec
                         .deposit_contract
@@ -2,7 +2,7 @@
 
 use crate::{
     args::{
-        DatabaseArgs, DatadirArgs, DebugArgs, DevArgs, NetworkArgs, PayloadBuilderArgs,
+        DatabaseArgs, DatadirArgs, DebugArgs, DevArgs, ExExArgs, NetworkArgs, PayloadBuilderArgs,
         PruningArgs, RpcServerArgs, TxPoolArgs,
     },
     dirs::{ChainPath, DataDirPath},
@@ -146,6 +146,9 @@ pub struct NodeConfig {
 
     /// All pruning related arguments
     pub pruning: PruningArgs,
+
+    /// All execution extension related arguments with --exex prefix
+    pub exex: ExExArgs,
 }
 
 impl NodeConfig {
@@ -240,6 +243,12 @@ impl NodeConfig {
         self
     }
 
+    /// Set the execution extension args for the node
+    pub fn with_exex(mut self, exex: ExExArgs) -> Self {
+        self.exex = exex;
+        self
+    }
+
     /// Returns pruning configuration.
     pub fn prune_config(&self) -> Option<PruneConfig> {
         self.pruning.prune_config(&self.chain)
@@ -422,6 +431,7 @@ impl Default for NodeConfig {
             db: DatabaseArgs::default(),
             dev: DevArgs::default(),
             pruning: PruningArgs::default(),
+            exex: ExExArgs::default(),
             datadir: DatadirArgs::default(),
         }
     }
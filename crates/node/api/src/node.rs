@@ -2,7 +2,7 @@
 
 use crate::{primitives::NodePrimitives, ConfigureEvm, EngineTypes};
 use reth_db_api::{
-    database::Database,
+    database::{Database, DatabaseCompact, DatabaseCopy},
     database_metrics::{DatabaseMetadata, DatabaseMetrics},
 };
 use reth_evm::execute::BlockExecutorProvider;
@@ -58,7 +58,14 @@ where
 /// Its types are configured by node internally and are not intended to be user configurable.
 pub trait FullNodeTypes: NodeTypes + 'static {
     /// Underlying database type used by the node to store and retrieve data.
-    type DB: Database + DatabaseMetrics + DatabaseMetadata + Clone + Unpin + 'static;
+    type DB: Database
+        + DatabaseCopy
+        + DatabaseCompact
+        + DatabaseMetrics
+        + DatabaseMetadata
+        + Clone
+        + Unpin
+        + 'static;
     /// The provider type used to interact with the node.
     type Provider: FullProvider<Self::DB>;
 }
@@ -107,7 +114,14 @@ impl<Types, DB, Provider> FullNodeTypes for FullNodeTypesAdapter<Types, DB, Prov
 where
     Types: NodeTypes,
     Provider: FullProvider<DB>,
-    DB: Database + DatabaseMetrics + DatabaseMetadata + Clone + Unpin + 'static,
+    DB: Database
+        + DatabaseCopy
+        + DatabaseCompact
+        + DatabaseMetrics
+        + DatabaseMetadata
+        + Clone
+        + Unpin
+        + 'static,
 {
     type DB = DB;
     type Provider = Provider;
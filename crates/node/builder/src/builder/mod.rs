@@ -17,13 +17,16 @@ use reth_db::{
     DatabaseEnv,
 };
 use reth_db_api::{
-    database::Database,
+    database::{Database, DatabaseCompact, DatabaseCopy},
     database_metrics::{DatabaseMetadata, DatabaseMetrics},
 };
 use reth_exex::ExExContext;
 use reth_network::{
     NetworkBuilder, NetworkConfig, NetworkConfigBuilder, NetworkHandle, NetworkManager,
 };
+use reth_network_snap::{
+    SnapPeers, SnapProtocolHandler, SnapRequestHandler, SNAP_REQUEST_CHANNEL_CAPACITY,
+};
 use reth_node_api::{FullNodeTypes, FullNodeTypesAdapter, NodeTypes};
 use reth_node_core::{
     args::{get_secret_key, DatadirArgs},
@@ -33,8 +36,14 @@ use reth_node_core::{
     primitives::{kzg::KzgSettings, Head},
     utils::write_peers_to_file,
 };
-use reth_primitives::constants::eip4844::MAINNET_KZG_TRUSTED_SETUP;
-use reth_provider::{providers::BlockchainProvider, ChainSpecProvider};
+use reth_primitives::{
+    constants::eip4844::MAINNET_KZG_TRUSTED_SETUP, BlockNumber, StaticFileSegment,
+};
+use reth_provider::{
+    providers::BlockchainProvider, ChainSpecProvider, DatabaseProviderFactory,
+    PruneCheckpointReader, StaticFileProviderFactory,
+};
+use reth_prune::{PrunePurpose, PruneSegment};
 use reth_tasks::TaskExecutor;
 use reth_transaction_pool::{PoolConfig, TransactionPool};
 use secp256k1::SecretKey;
@@ -43,6 +52,7 @@ use std::{
     net::{IpAddr, SocketAddr, SocketAddrV4, SocketAddrV6},
     sync::Arc,
 };
+use tokio::sync::mpsc;
 
 mod states;
 
@@ -198,7 +208,14 @@ impl<DB> NodeBuilder<DB> {
 
 impl<DB> NodeBuilder<DB>
 where
-    DB: Database + DatabaseMetrics + DatabaseMetadata + Clone + Unpin + 'static,
+    DB: Database
+        + DatabaseCopy
+        + DatabaseCompact
+        + DatabaseMetrics
+        + DatabaseMetadata
+        + Clone
+        + Unpin
+        + 'static,
 {
     /// Configures the types of the node.
     pub fn with_types<T>(self) -> NodeBuilderWithTypes<RethFullAdapter<DB, T>>
@@ -240,7 +257,14 @@ impl<Builder> WithLaunchContext<Builder> {
 
 impl<DB> WithLaunchContext<NodeBuilder<DB>>
 where
-    DB: Database + DatabaseMetrics + DatabaseMetadata + Clone + Unpin + 'static,
+    DB: Database
+        + DatabaseCopy
+        + DatabaseCompact
+        + DatabaseMetrics
+        + DatabaseMetadata
+        + Clone
+        + Unpin
+        + 'static,
 {
     /// Returns a reference to the node builder's config.
     pub const fn config(&self) -> &NodeConfig {
@@ -293,7 +317,14 @@ where
 
 impl<T, DB> WithLaunchContext<NodeBuilderWithTypes<RethFullAdapter<DB, T>>>
 where
-    DB: Database + DatabaseMetrics + DatabaseMetadata + Clone + Unpin + 'static,
+    DB: Database
+        + DatabaseCopy
+        + DatabaseCompact
+        + DatabaseMetrics
+        + DatabaseMetadata
+        + Clone
+        + Unpin
+        + 'static,
     T: NodeTypes,
 {
     /// Advances the state of the node builder to the next state where all components are configured
@@ -313,7 +344,14 @@ where
 
 impl<T, DB, CB> WithLaunchContext<NodeBuilderWithComponents<RethFullAdapter<DB, T>, CB>>
 where
-    DB: Database + DatabaseMetrics + DatabaseMetadata + Clone + Unpin + 'static,
+    DB: Database
+        + DatabaseCopy
+        + DatabaseCompact
+        + DatabaseMetrics
+        + DatabaseMetadata
+        + Clone
+        + Unpin
+        + 'static,
     T: NodeTypes,
     CB: NodeComponentsBuilder<RethFullAdapter<DB, T>>,
 {
@@ -414,17 +452,19 @@ pub struct BuilderContext<Node: FullNodeTypes> {
     pub(crate) executor: TaskExecutor,
     /// Config container
     pub(crate) config_container: WithConfigs,
+    /// The connected `snap` peers.
+    pub(crate) snap_peers: SnapPeers,
 }
 
 impl<Node: FullNodeTypes> BuilderContext<Node> {
     /// Create a new instance of [`BuilderContext`]
-    pub const fn new(
+    pub fn new(
         head: Head,
         provider: Node::Provider,
         executor: TaskExecutor,
         config_container: WithConfigs,
     ) -> Self {
-        Self { head, provider, executor, config_container }
+        Self { head, provider, executor, config_container, snap_peers: SnapPeers::default() }
     }
 
     /// Returns the configured provider to interact with the blockchain.
@@ -459,6 +499,12 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
         self.provider().chain_spec()
     }
 
+    /// Returns the `snap` peers that are registered by the network started with
+    /// [`Self::start_network`].
+    pub const fn snap_peers(&self) -> &SnapPeers {
+        &self.snap_peers
+    }
+
     /// Returns true if the node is configured as --dev
     pub const fn is_dev(&self) -> bool {
         self.config().dev.dev
@@ -492,12 +538,24 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
     /// connected to that network.
     pub fn start_network<Pool>(
         &self,
-        builder: NetworkBuilder<Node::Provider, (), ()>,
+        mut builder: NetworkBuilder<Node::Provider, (), ()>,
         pool: Pool,
     ) -> NetworkHandle
     where
         Pool: TransactionPool + Unpin + 'static,
     {
+        let snap_server = self.config().network.snap_server;
+        if snap_server || self.reth_config().stages.snap_sync.enabled {
+            let mut snap = SnapProtocolHandler::new(self.snap_peers.clone());
+            if snap_server {
+                let (tx, rx) = mpsc::channel(SNAP_REQUEST_CHANNEL_CAPACITY);
+                snap = snap.with_request_handler(tx);
+                let handler = SnapRequestHandler::<_, Node::DB>::new(self.provider().clone(), rx);
+                self.executor.spawn_critical("p2p snap request handler", handler);
+            }
+            builder.network_mut().add_rlpx_sub_protocol(snap);
+        }
+
         let (handle, network, txpool, eth) = builder
             .transactions(pool, Default::default())
             .request_handler(self.provider().clone())
@@ -530,12 +588,61 @@ impl<Node: FullNodeTypes> BuilderContext<Node> {
     pub fn network_config_builder(&self) -> eyre::Result<NetworkConfigBuilder> {
         let secret_key = self.network_secret(&self.config().datadir())?;
         let default_peers_path = self.config().datadir().known_peers();
-        Ok(self.config().network.network_config(
-            self.reth_config(),
-            self.config().chain.clone(),
-            secret_key,
-            default_peers_path,
-        ))
+        Ok(self
+            .config()
+            .network
+            .network_config(
+                self.reth_config(),
+                self.config().chain.clone(),
+                secret_key,
+                default_peers_path,
+            )
+            .set_earliest_block(self.earliest_available_block()?))
+    }
+
+    /// Returns the lowest block the node can serve headers, bodies and receipts for.
+    ///
+    /// Blocks below the lowest static file of a segment are not available, and neither are
+    /// receipts below the receipts prune checkpoint or the configured receipts prune target.
+    pub fn earliest_available_block(&self) -> eyre::Result<BlockNumber> {
+        let static_file_provider = self.provider().static_file_provider();
+        let mut earliest = 0;
+        for segment in [
+            StaticFileSegment::Headers,
+            StaticFileSegment::Transactions,
+            StaticFileSegment::Receipts,
+        ] {
+            if let Some(lowest) = static_file_provider.get_lowest_static_file_block(segment)? {
+                earliest = earliest.max(lowest);
+            }
+        }
+
+        let pruned = self
+            .provider()
+            .database_provider_ro()?
+            .get_prune_checkpoint(PruneSegment::Receipts)?
+            .and_then(|checkpoint| checkpoint.block_number);
+        let prune_target = self
+            .reth_config()
+            .prune
+            .clone()
+            .or_else(|| self.config().prune_config())
+            .and_then(|config| config.segments.receipts)
+            .map(|mode| {
+                mode.prune_target_block(
+                    self.head.number,
+                    PruneSegment::Receipts,
+                    PrunePurpose::User,
+                )
+            })
+            .transpose()?
+            .flatten()
+            .map(|(block_number, _)| block_number);
+        if let Some(block_number) = pruned.max(prune_target) {
+            earliest = earliest.max(block_number + 1);
+        }
+
+        Ok(earliest.min(self.head.number))
     }
 
     /// Get the network secret from the given data dir
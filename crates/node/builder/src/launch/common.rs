@@ -22,6 +22,7 @@ use reth_db_common::init::{init_genesis, InitDatabaseError};
 use reth_downloaders::{bodies::noop::NoopBodiesDownloader, headers::noop::NoopHeaderDownloader};
 use reth_evm::noop::NoopBlockExecutorProvider;
 use reth_network_p2p::headers::client::HeadersClient;
+use reth_network_snap::SnapPeers;
 use reth_node_api::FullNodeTypes;
 use reth_node_core::{
     dirs::{ChainPath, DataDirPath},
@@ -362,8 +363,10 @@ where
         let factory = ProviderFactory::new(
             self.right().clone(),
             self.chain_spec(),
-            StaticFileProvider::read_write(self.data_dir().static_files())?,
+            StaticFileProvider::read_write(self.data_dir().static_files())?
+                .with_compression(self.toml_config().static_files.compression),
         )
+        .with_prune_modes(self.prune_modes().unwrap_or_default())
         .with_static_files_metrics();
 
         let has_receipt_pruning =
@@ -667,6 +670,7 @@ where
             node_adapter,
             head,
             consensus,
+            snap_peers: builder_ctx.snap_peers,
         };
 
         let ctx = LaunchContextWith {
@@ -745,6 +749,11 @@ where
     pub const fn components(&self) -> &CB::Components {
         &self.node_adapter().components
     }
+
+    /// Returns the connected `snap` peers.
+    pub const fn snap_peers(&self) -> &SnapPeers {
+        &self.right().snap_peers
+    }
 }
 
 /// Joins two attachments together.
@@ -841,6 +850,7 @@ where
     node_adapter: NodeAdapter<T, CB::Components>,
     head: Head,
     consensus: Arc<dyn Consensus>,
+    snap_peers: SnapPeers,
 }
 
 #[cfg(test)]
@@ -2,7 +2,9 @@
 
 use crate::{common::WithConfigs, exex::BoxedLaunchExEx};
 use futures::future;
-use reth_exex::{ExExContext, ExExHandle, ExExManager, ExExManagerHandle};
+use reth_exex::{
+    ExExContext, ExExHandle, ExExManager, ExExManagerHandle, RemoteExEx, Wal, REMOTE_EXEX_ID,
+};
 use reth_node_api::FullNodeComponents;
 use reth_primitives::Head;
 use reth_provider::CanonStateSubscriptions;
@@ -32,12 +34,27 @@ impl<Node: FullNodeComponents + Clone> ExExLauncher<Node> {
     ///
     /// Spawns all extensions and returns the handle to the exex manager if any extensions are
     /// installed.
-    pub async fn launch(self) -> Option<ExExManagerHandle> {
-        let Self { head, extensions, components, config_container } = self;
+    ///
+    /// The notifications in the write-ahead log of the exex manager are replayed to the
+    /// extensions.
+    pub async fn launch(self) -> eyre::Result<Option<ExExManagerHandle>> {
+        let Self { head, mut extensions, components, config_container } = self;
+
+        if let Some(endpoint) = config_container.config.exex.remote_socket.clone() {
+            let retention = config_container.config.exex.remote_retention;
+            extensions.push((
+                REMOTE_EXEX_ID.to_string(),
+                Box::new(move |ctx: ExExContext<Node>| async move {
+                    eyre::Ok(
+                        RemoteExEx::from_context(ctx, endpoint).with_retention(retention).run(),
+                    )
+                }),
+            ));
+        }
 
         if extensions.is_empty() {
             // nothing to launch
-            return None
+            return Ok(None)
         }
 
         let mut exex_handles = Vec::with_capacity(extensions.len());
@@ -82,8 +99,10 @@ impl<Node: FullNodeComponents + Clone> ExExLauncher<Node> {
 
         // spawn exex manager
         debug!(target: "reth::cli", "spawning exex manager");
+        let wal_dir = config_container.config.datadir().exex_wal();
+        debug!(target: "reth::cli", ?wal_dir, "opening exex write-ahead log");
         // todo(onbjerg): rm magic number
-        let exex_manager = ExExManager::new(exex_handles, 1024);
+        let exex_manager = ExExManager::new(exex_handles, 1024).with_wal(Wal::new(wal_dir)?)?;
         let exex_manager_handle = exex_manager.handle();
         components.task_executor().spawn_critical("exex manager", async move {
             exex_manager.await.expect("exex manager crashed");
@@ -106,7 +125,7 @@ impl<Node: FullNodeComponents + Clone> ExExLauncher<Node> {
 
         info!(target: "reth::cli", "ExEx Manager started");
 
-        Some(exex_manager_handle)
+        Ok(Some(exex_manager_handle))
     }
 }
 
@@ -9,23 +9,28 @@ use crate::{
 };
 use futures::{future::Either, stream, stream_select, StreamExt};
 use reth_beacon_consensus::{
-    hooks::{EngineHooks, PruneHook, StaticFileHook},
+    hooks::{CompactHook, EngineHooks, PruneHook, StaticFileHook},
     BeaconConsensusEngine,
 };
 use reth_consensus_debug_client::{DebugConsensusClient, EtherscanBlockProvider, RpcBlockProvider};
 use reth_exex::ExExManagerHandle;
 use reth_network::NetworkEvents;
+use reth_network_p2p::snap::client::SnapClient;
+use reth_network_snap::SnapFetchClient;
 use reth_node_api::FullNodeTypes;
 use reth_node_core::{
     dirs::{ChainPath, DataDirPath},
     engine::EngineMessageStreamExt,
     exit::NodeExitFuture,
+    rpc::api::{AdminBackupApiServer, BlockSubmissionValidationApiServer},
     version::{CARGO_PKG_VERSION, CLIENT_CODE, NAME_CLIENT, VERGEN_GIT_SHA},
 };
 use reth_node_events::{cl::ConsensusLayerHealthEvents, node};
 
 use reth_primitives::format_ether;
 use reth_provider::providers::BlockchainProvider;
+use reth_rpc::{AdminBackupApi, DevApi, ValidationApi};
+use reth_rpc_builder::RethRpcModule;
 use reth_rpc_engine_api::EngineApi;
 use reth_rpc_types::engine::ClientVersionV1;
 use reth_tasks::TaskExecutor;
@@ -96,7 +101,7 @@ where
         let NodeBuilderWithComponents {
             adapter: NodeTypesAdapter { database },
             components_builder,
-            add_ons: NodeAddOns { hooks, rpc, exexs: installed_exex },
+            add_ons: NodeAddOns { hooks, mut rpc, exexs: installed_exex },
             config,
         } = target;
         let NodeHooks { on_component_initialized, on_node_started, .. } = hooks;
@@ -139,7 +144,7 @@ where
             ctx.configs().clone(),
         )
         .launch()
-        .await;
+        .await?;
 
         // create pipeline
         let network_client = ctx.components().network().fetch_client().await?;
@@ -190,6 +195,14 @@ where
             )
             .build();
 
+            // install the development namespaces, which are driven by the auto seal miner
+            let dev_api = DevApi::new(
+                ctx.blockchain_db().clone(),
+                ctx.components().pool().clone(),
+                task.handle(),
+            );
+            rpc.merge_if_module_configured(RethRpcModule::Dev, dev_api.into_rpc_module()?);
+
             let pipeline = crate::setup::build_networked_pipeline(
                 &ctx.toml_config().stages,
                 client.clone(),
@@ -202,6 +215,7 @@ where
                 static_file_producer,
                 ctx.components().block_executor().clone(),
                 pipeline_exex_handle,
+                None,
             )
             .await?;
 
@@ -212,6 +226,12 @@ where
 
             (pipeline, Either::Left(client))
         } else {
+            let snap_client = ctx.toml_config().stages.snap_sync.enabled.then(|| {
+                Box::new(SnapFetchClient::new(
+                    ctx.snap_peers().clone(),
+                    ctx.components().network().peers_handle().clone(),
+                )) as Box<dyn SnapClient>
+            });
             let pipeline = crate::setup::build_networked_pipeline(
                 &ctx.toml_config().stages,
                 network_client.clone(),
@@ -224,6 +244,7 @@ where
                 static_file_producer,
                 ctx.components().block_executor().clone(),
                 pipeline_exex_handle,
+                snap_client,
             )
             .await?;
 
@@ -247,6 +268,15 @@ where
         info!(target: "reth::cli", prune_config=?ctx.prune_config().unwrap_or_default(), "Pruner initialized");
         hooks.add(PruneHook::new(pruner, Box::new(ctx.task_executor().clone())));
 
+        if let Some(compaction_config) = ctx.toml_config().compaction {
+            info!(target: "reth::cli", ?compaction_config, "Database compaction scheduled");
+            hooks.add(CompactHook::new(
+                ctx.provider_factory().db_ref().clone(),
+                compaction_config,
+                Box::new(ctx.task_executor().clone()),
+            ));
+        }
+
         // Configure the consensus engine
         let (beacon_consensus_engine, beacon_engine_handle) = BeaconConsensusEngine::with_channel(
             client,
@@ -305,6 +335,25 @@ where
         );
         info!(target: "reth::cli", "Engine API handler initialized");
 
+        // install the block validation api, which needs the consensus and the block executor
+        let validation_api = ValidationApi::new(
+            ctx.blockchain_db().clone(),
+            ctx.chain_spec(),
+            ctx.consensus(),
+            ctx.components().block_executor().clone(),
+            Box::new(ctx.task_executor().clone()),
+        );
+        rpc.merge_if_module_configured(RethRpcModule::Flashbots, validation_api.into_rpc());
+
+        // install the database backup api, it writes to the node's host and is therefore only
+        // installed if the admin namespace is explicitly selected for the http or ws server
+        let backup_api = AdminBackupApi::new(
+            ctx.provider_factory().clone(),
+            ctx.data_dir().data_dir().to_path_buf(),
+            Box::new(ctx.task_executor().clone()),
+        );
+        rpc.merge_if_module_selected(RethRpcModule::Admin, backup_api.into_rpc());
+
         // extract the jwt secret from the args if possible
         let jwt_secret = ctx.auth_jwt_secret()?;
 
@@ -1,6 +1,7 @@
 //! Builder support for rpc components.
 
 use futures::TryFutureExt;
+use jsonrpsee::Methods;
 use reth_network::NetworkHandle;
 use reth_node_api::FullNodeComponents;
 use reth_node_core::{node_config::NodeConfig, rpc::api::EngineApiServer};
@@ -8,7 +9,7 @@ use reth_payload_builder::PayloadBuilderHandle;
 use reth_rpc_builder::{
     auth::{AuthRpcModule, AuthServerHandle},
     config::RethRpcServerConfig,
-    RethModuleRegistry, RpcModuleBuilder, RpcServerHandle, TransportRpcModules,
+    RethModuleRegistry, RethRpcModule, RpcModuleBuilder, RpcServerHandle, TransportRpcModules,
 };
 use reth_rpc_layer::JwtSecret;
 use reth_tasks::TaskExecutor;
@@ -78,6 +79,36 @@ impl<Node: FullNodeComponents> RpcHooks<Node> {
         self.set_extend_rpc_modules(hook);
         self
     }
+
+    /// Merges the given methods into all transports that are configured with the given module,
+    /// before the configured hook to extend the rpc modules is run.
+    pub(crate) fn merge_if_module_configured(
+        &mut self,
+        module: RethRpcModule,
+        methods: impl Into<Methods>,
+    ) -> &mut Self {
+        let methods = methods.into();
+        let extend_rpc_modules = std::mem::replace(&mut self.extend_rpc_modules, Box::new(()));
+        self.set_extend_rpc_modules(move |ctx: RpcContext<'_, Node>| {
+            ctx.modules.merge_if_module_configured(module, methods)?;
+            extend_rpc_modules.extend_rpc_modules(ctx)
+        })
+    }
+
+    /// Merges the given methods into the http and ws transports whose module selection explicitly
+    /// lists the given module, before the configured hook to extend the rpc modules is run.
+    pub(crate) fn merge_if_module_selected(
+        &mut self,
+        module: RethRpcModule,
+        methods: impl Into<Methods>,
+    ) -> &mut Self {
+        let methods = methods.into();
+        let extend_rpc_modules = std::mem::replace(&mut self.extend_rpc_modules, Box::new(()));
+        self.set_extend_rpc_modules(move |ctx: RpcContext<'_, Node>| {
+            ctx.modules.merge_if_module_selected(module, methods)?;
+            extend_rpc_modules.extend_rpc_modules(ctx)
+        })
+    }
 }
 
 impl<Node: FullNodeComponents> fmt::Debug for RpcHooks<Node> {
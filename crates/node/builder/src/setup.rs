@@ -12,10 +12,15 @@ use reth_exex::ExExManagerHandle;
 use reth_network_p2p::{
     bodies::{client::BodiesClient, downloader::BodyDownloader},
     headers::{client::HeadersClient, downloader::HeaderDownloader},
+    snap::client::SnapClient,
 };
 use reth_node_core::primitives::{BlockNumber, B256};
 use reth_provider::ProviderFactory;
-use reth_stages::{prelude::DefaultStages, stages::ExecutionStage, Pipeline, StageSet};
+use reth_stages::{
+    prelude::DefaultStages,
+    stages::{ExecutionStage, SnapSyncStage},
+    Pipeline, StageId, StageSet,
+};
 use reth_static_file::StaticFileProducer;
 use reth_tasks::TaskExecutor;
 use reth_tracing::tracing::debug;
@@ -23,6 +28,9 @@ use std::sync::Arc;
 use tokio::sync::watch;
 
 /// Constructs a [Pipeline] that's wired to the network
+///
+/// If a `snap_client` is provided, the pipeline bootstraps the state of a fresh node with the
+/// [`SnapSyncStage`].
 #[allow(clippy::too_many_arguments)]
 pub async fn build_networked_pipeline<DB, Client, Executor>(
     config: &StageConfig,
@@ -36,6 +44,7 @@ pub async fn build_networked_pipeline<DB, Client, Executor>(
     static_file_producer: StaticFileProducer<DB>,
     executor: Executor,
     exex_manager_handle: ExExManagerHandle,
+    snap_client: Option<Box<dyn SnapClient>>,
 ) -> eyre::Result<Pipeline<DB>>
 where
     DB: Database + Unpin + Clone + 'static,
@@ -63,6 +72,7 @@ where
         static_file_producer,
         executor,
         exex_manager_handle,
+        snap_client,
     )
     .await?;
 
@@ -70,6 +80,9 @@ where
 }
 
 /// Builds the [Pipeline] with the given [`ProviderFactory`] and downloaders.
+///
+/// The [`SnapSyncStage`] is added before the execution stage if a `snap_client` is provided. Snap
+/// sync doesn't download receipts, so it requires receipts pruning to be configured.
 #[allow(clippy::too_many_arguments)]
 pub async fn build_pipeline<DB, H, B, Executor>(
     provider_factory: ProviderFactory<DB>,
@@ -83,6 +96,7 @@ pub async fn build_pipeline<DB, H, B, Executor>(
     static_file_producer: StaticFileProducer<DB>,
     executor: Executor,
     exex_manager_handle: ExExManagerHandle,
+    snap_client: Option<Box<dyn SnapClient>>,
 ) -> eyre::Result<Pipeline<DB>>
 where
     DB: Database + Clone + 'static,
@@ -101,31 +115,41 @@ where
 
     let prune_modes = prune_config.map(|prune| prune.segments).unwrap_or_default();
 
+    if snap_client.is_some() && prune_modes.receipts.is_none() {
+        eyre::bail!("snap sync requires receipts pruning to be configured")
+    }
+
+    let mut stages = DefaultStages::new(
+        provider_factory.clone(),
+        tip_rx,
+        Arc::clone(&consensus),
+        header_downloader,
+        body_downloader,
+        executor.clone(),
+        stage_config.clone(),
+        prune_modes.clone(),
+    )
+    .set(
+        ExecutionStage::new(
+            executor,
+            stage_config.execution.into(),
+            stage_config.execution_external_clean_threshold(),
+            prune_modes,
+            exex_manager_handle,
+        )
+        .with_metrics_tx(metrics_tx.clone()),
+    );
+    if let Some(snap_client) = snap_client {
+        stages = stages.add_before(
+            SnapSyncStage::new(snap_client, stage_config.snap_sync),
+            StageId::Execution,
+        );
+    }
+
     let pipeline = builder
         .with_tip_sender(tip_tx)
-        .with_metrics_tx(metrics_tx.clone())
-        .add_stages(
-            DefaultStages::new(
-                provider_factory.clone(),
-                tip_rx,
-                Arc::clone(&consensus),
-                header_downloader,
-                body_downloader,
-                executor.clone(),
-                stage_config.clone(),
-                prune_modes.clone(),
-            )
-            .set(
-                ExecutionStage::new(
-                    executor,
-                    stage_config.execution.into(),
-                    stage_config.execution_external_clean_threshold(),
-                    prune_modes,
-                    exex_manager_handle,
-                )
-                .with_metrics_tx(metrics_tx),
-            ),
-        )
+        .with_metrics_tx(metrics_tx)
+        .add_stages(stages)
         .build(provider_factory, static_file_producer);
 
     Ok(pipeline)
@@ -248,8 +248,9 @@ impl<DB: Database> Pruner<DB> {
     }
 
     /// Returns pre-configured segments that needs to be pruned according to the highest
-    /// `static_files` for [`PruneSegment::Transactions`], [`PruneSegment::Headers`] and
-    /// [`PruneSegment::Receipts`].
+    /// `static_files` for [`PruneSegment::Transactions`], [`PruneSegment::Headers`],
+    /// [`PruneSegment::Receipts`], [`PruneSegment::AccountChangeSets`],
+    /// [`PruneSegment::StorageChangeSets`] and [`PruneSegment::SenderRecovery`].
     fn static_file_segments(&self) -> Vec<Box<dyn Segment<DB>>> {
         let mut segments = Vec::<Box<dyn Segment<DB>>>::new();
 
@@ -274,6 +275,30 @@ impl<DB: Database> Pruner<DB> {
             segments.push(Box::new(segments::Receipts::new(PruneMode::before_inclusive(to_block))))
         }
 
+        if let Some(to_block) =
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::AccountChangeSets)
+        {
+            segments.push(Box::new(segments::AccountChangeSets::new(PruneMode::before_inclusive(
+                to_block,
+            ))))
+        }
+
+        if let Some(to_block) =
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::StorageChangeSets)
+        {
+            segments.push(Box::new(segments::StorageChangeSets::new(PruneMode::before_inclusive(
+                to_block,
+            ))))
+        }
+
+        if let Some(to_block) = static_file_provider
+            .get_highest_static_file_block(StaticFileSegment::TransactionSenders)
+        {
+            segments.push(Box::new(segments::SenderRecovery::new(PruneMode::before_inclusive(
+                to_block,
+            ))))
+        }
+
         segments
     }
 
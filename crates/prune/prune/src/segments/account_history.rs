@@ -7,7 +7,9 @@ use crate::{
 use reth_db::tables;
 use reth_db_api::{database::Database, models::ShardedKey};
 use reth_provider::DatabaseProviderRW;
-use reth_prune_types::{PruneInterruptReason, PruneMode, PruneProgress, PruneSegment};
+use reth_prune_types::{
+    HistoryRetentionConfig, PruneInterruptReason, PruneMode, PruneProgress, PruneSegment,
+};
 use tracing::{instrument, trace};
 
 /// Number of account history tables to prune in one step.
@@ -19,11 +21,13 @@ const ACCOUNT_HISTORY_TABLES_TO_PRUNE: usize = 2;
 #[derive(Debug)]
 pub struct AccountHistory {
     mode: PruneMode,
+    /// Addresses whose changesets and history indices are never pruned.
+    retention: HistoryRetentionConfig,
 }
 
 impl AccountHistory {
-    pub const fn new(mode: PruneMode) -> Self {
-        Self { mode }
+    pub const fn new(mode: PruneMode, retention: HistoryRetentionConfig) -> Self {
+        Self { mode, retention }
     }
 }
 
@@ -68,7 +72,7 @@ impl<DB: Database> Segment<DB> for AccountHistory {
             .prune_table_with_range::<tables::AccountChangeSets>(
                 range,
                 &mut limiter,
-                |_| false,
+                |(_, account)| self.retention.contains(&account.address),
                 |row| last_changeset_pruned_block = Some(row.0),
             )?;
         trace!(target: "pruner", pruned = %pruned_changesets, %done, "Pruned account history (changesets)");
@@ -84,6 +88,7 @@ impl<DB: Database> Segment<DB> for AccountHistory {
             last_changeset_pruned_block,
             |a, b| a.key == b.key,
             |key| ShardedKey::last(key.key),
+            |key| self.retention.contains(&key.key),
         )?;
         trace!(target: "pruner", %processed, pruned = %pruned_indices, %done, "Pruned account history (history)");
 
@@ -111,14 +116,18 @@ mod tests {
     use reth_db::{tables, BlockNumberList};
     use reth_provider::PruneCheckpointReader;
     use reth_prune_types::{
-        PruneCheckpoint, PruneInterruptReason, PruneLimiter, PruneMode, PruneProgress, PruneSegment,
+        HistoryRetentionConfig, PruneCheckpoint, PruneInterruptReason, PruneLimiter, PruneMode,
+        PruneProgress, PruneSegment,
     };
     use reth_stages::test_utils::{StorageKind, TestStageDB};
     use reth_testing_utils::{
         generators,
         generators::{random_block_range, random_changeset_range, random_eoa_accounts},
     };
-    use std::{collections::BTreeMap, ops::AddAssign};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        ops::AddAssign,
+    };
 
     #[test]
     fn prune() {
@@ -172,7 +181,7 @@ mod tests {
                     to_block,
                     limiter: limiter.clone(),
                 };
-                let segment = AccountHistory::new(prune_mode);
+                let segment = AccountHistory::new(prune_mode, HistoryRetentionConfig::default());
 
                 let provider = db.factory.provider_rw().unwrap();
                 let result = segment.prune(&provider, input).unwrap();
@@ -278,4 +287,64 @@ mod tests {
         test_prune(998, 2, (PruneProgress::Finished, 998));
         test_prune(1400, 3, (PruneProgress::Finished, 804));
     }
+
+    #[test]
+    fn prune_with_retention() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 1..=2000, B256::ZERO, 0..1);
+        db.insert_blocks(blocks.iter(), StorageKind::Database(None)).expect("insert blocks");
+
+        let accounts = random_eoa_accounts(&mut rng, 2).into_iter().collect::<BTreeMap<_, _>>();
+        let retained = *accounts.keys().next().unwrap();
+
+        let (changesets, _) = random_changeset_range(
+            &mut rng,
+            blocks.iter(),
+            accounts.into_iter().map(|(addr, acc)| (addr, (acc, Vec::new()))),
+            0..0,
+            0..0,
+        );
+        db.insert_changesets(changesets.clone(), None).expect("insert changesets");
+        db.insert_history(changesets.clone(), None).expect("insert history");
+
+        let original_changesets = db.table::<tables::AccountChangeSets>().unwrap();
+        let original_shards = db.table::<tables::AccountsHistory>().unwrap();
+
+        let to_block = 1000;
+        let input =
+            PruneInput { previous_checkpoint: None, to_block, limiter: PruneLimiter::default() };
+        let segment = AccountHistory::new(
+            PruneMode::Before(to_block),
+            HistoryRetentionConfig(BTreeSet::from([retained])),
+        );
+
+        let provider = db.factory.provider_rw().unwrap();
+        let result = segment.prune(&provider, input).unwrap();
+        provider.commit().expect("commit");
+        assert!(result.progress.is_finished());
+
+        // Changesets of the retained account are kept in full
+        assert_eq!(
+            db.table::<tables::AccountChangeSets>().unwrap(),
+            original_changesets
+                .into_iter()
+                .filter(|(block_number, account)| {
+                    account.address == retained || *block_number > to_block
+                })
+                .collect::<Vec<_>>()
+        );
+
+        // History shards of the retained account are kept in full
+        let actual_shards = db.table::<tables::AccountsHistory>().unwrap();
+        assert_eq!(
+            actual_shards.iter().filter(|(key, _)| key.key == retained).collect::<Vec<_>>(),
+            original_shards.iter().filter(|(key, _)| key.key == retained).collect::<Vec<_>>()
+        );
+        assert!(actual_shards
+            .iter()
+            .filter(|(key, _)| key.key != retained)
+            .all(|(_, blocks)| blocks.iter().all(|block| block > to_block)));
+    }
 }
@@ -0,0 +1,268 @@
+use crate::{
+    segments::{
+        history::prune_history_indices, PruneInput, PruneOutput, PruneOutputCheckpoint, Segment,
+    },
+    PrunerError,
+};
+use reth_db::tables;
+use reth_db_api::{database::Database, models::ShardedKey};
+use reth_provider::{DatabaseProviderRW, TransactionsProvider};
+use reth_prune_types::{PruneInterruptReason, PruneMode, PruneProgress, PruneSegment};
+use tracing::{instrument, trace};
+
+#[derive(Debug)]
+pub struct AddressTransactions {
+    mode: PruneMode,
+}
+
+impl AddressTransactions {
+    pub const fn new(mode: PruneMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl<DB: Database> Segment<DB> for AddressTransactions {
+    fn segment(&self) -> PruneSegment {
+        PruneSegment::AddressTransactions
+    }
+
+    fn mode(&self) -> Option<PruneMode> {
+        Some(self.mode)
+    }
+
+    #[instrument(level = "trace", target = "pruner", skip(self, provider), ret)]
+    fn prune(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: PruneInput,
+    ) -> Result<PruneOutput, PrunerError> {
+        let tx_range_end = match input.get_next_tx_num_range(provider)? {
+            Some(range) => *range.end(),
+            None => {
+                trace!(target: "pruner", "No address transactions to prune");
+                return Ok(PruneOutput::done())
+            }
+        };
+
+        let mut limiter = input.limiter;
+        if limiter.is_limit_reached() {
+            return Ok(PruneOutput::not_done(
+                PruneInterruptReason::new(&limiter),
+                input.previous_checkpoint.map(|checkpoint| checkpoint.into()),
+            ))
+        }
+
+        // Shards of the address transactions index hold transaction numbers instead of block
+        // numbers, so the indices are pruned up to the last transaction in the range. Shards are
+        // keyed by address, so the whole table is walked to delete the shards that only hold
+        // pruned transactions.
+        let mut pruned_shards = 0;
+        let (_, done) = provider.prune_table_with_range::<tables::AddressTransactions>(
+            ..,
+            &mut limiter,
+            |(key, _)| key.highest_block_number > tx_range_end,
+            |_| pruned_shards += 1,
+        )?;
+        trace!(target: "pruner", pruned = %pruned_shards, %done, "Pruned address transactions (shards)");
+
+        if !done {
+            // Shards aren't ordered by transaction number, so the checkpoint only moves once all
+            // of them are pruned.
+            return Ok(PruneOutput {
+                progress: PruneProgress::new(done, &limiter),
+                pruned: pruned_shards,
+                checkpoint: input.previous_checkpoint.map(|checkpoint| checkpoint.into()),
+            })
+        }
+
+        // The remaining shards to prune hold transactions on both sides of the range end.
+        let (processed, pruned_indices) =
+            prune_history_indices::<DB, tables::AddressTransactions, _>(
+                provider,
+                tx_range_end,
+                |a, b| a.key == b.key,
+                |key| ShardedKey::last(key.key),
+                |_| false,
+            )?;
+        trace!(target: "pruner", %processed, pruned = %pruned_indices, "Pruned address transactions (indices)");
+
+        let last_pruned_block = provider
+            .transaction_block(tx_range_end)?
+            .ok_or(PrunerError::InconsistentData("Block for transaction is not found"))?;
+
+        Ok(PruneOutput {
+            progress: PruneProgress::Finished,
+            pruned: pruned_shards + pruned_indices,
+            checkpoint: Some(PruneOutputCheckpoint {
+                block_number: Some(last_pruned_block),
+                tx_number: Some(tx_range_end),
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::segments::{AddressTransactions, PruneInput, PruneOutput, Segment};
+    use alloy_primitives::{Address, BlockNumber, B256};
+    use assert_matches::assert_matches;
+    use reth_db::{tables, BlockNumberList};
+    use reth_db_api::{models::ShardedKey, transaction::DbTxMut};
+    use reth_provider::{HistoryWriter, PruneCheckpointReader};
+    use reth_prune_types::{
+        PruneCheckpoint, PruneInterruptReason, PruneLimiter, PruneMode, PruneProgress, PruneSegment,
+    };
+    use reth_stages::test_utils::{StorageKind, TestStageDB};
+    use reth_testing_utils::{generators, generators::random_block_range};
+
+    #[test]
+    fn prune() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 1..=10, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), StorageKind::Database(None)).expect("insert blocks");
+
+        let tx_count = blocks.iter().map(|block| block.body.len() as u64).sum::<u64>();
+        let provider = db.factory.provider_rw().unwrap();
+        let address_transactions = provider.address_transactions_by_tx_range(0..tx_count).unwrap();
+        provider.insert_address_transaction_index(address_transactions).unwrap();
+        provider.commit().expect("commit");
+
+        let test_prune = |to_block: BlockNumber| {
+            let prune_mode = PruneMode::Before(to_block);
+            let segment = AddressTransactions::new(prune_mode);
+            let input = PruneInput {
+                previous_checkpoint: db
+                    .factory
+                    .provider()
+                    .unwrap()
+                    .get_prune_checkpoint(PruneSegment::AddressTransactions)
+                    .unwrap(),
+                to_block,
+                limiter: PruneLimiter::default(),
+            };
+
+            let provider = db.factory.provider_rw().unwrap();
+            let result = segment.prune(&provider, input).unwrap();
+            assert_matches!(
+                result,
+                PruneOutput { progress: PruneProgress::Finished, checkpoint: Some(_), .. }
+            );
+
+            segment
+                .save_checkpoint(
+                    &provider,
+                    result.checkpoint.unwrap().as_prune_checkpoint(prune_mode),
+                )
+                .unwrap();
+            provider.commit().expect("commit");
+
+            let last_pruned_tx_number = blocks
+                .iter()
+                .take(to_block as usize)
+                .map(|block| block.body.len() as u64)
+                .sum::<u64>() -
+                1;
+
+            // No transactions at or below the last pruned one are left in the index.
+            let shards = db.table::<tables::AddressTransactions>().unwrap();
+            assert!(!shards.is_empty());
+            assert!(shards
+                .iter()
+                .all(|(_, list)| list.iter().all(|tx| tx > last_pruned_tx_number)));
+
+            assert_eq!(
+                db.factory
+                    .provider()
+                    .unwrap()
+                    .get_prune_checkpoint(PruneSegment::AddressTransactions)
+                    .unwrap(),
+                Some(PruneCheckpoint {
+                    block_number: Some(to_block),
+                    tx_number: Some(last_pruned_tx_number),
+                    prune_mode
+                })
+            );
+        };
+
+        test_prune(4);
+        test_prune(8);
+    }
+
+    #[test]
+    fn prune_with_limit() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        // blocks 1..=4 hold transactions 0..=7
+        let blocks = random_block_range(&mut rng, 1..=10, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), StorageKind::Database(None)).expect("insert blocks");
+
+        let shard = |address, highest_tx_number, tx_numbers: &[u64]| {
+            (ShardedKey::new(address, highest_tx_number), BlockNumberList::new(tx_numbers).unwrap())
+        };
+        let addresses = (0..3).map(Address::with_last_byte).collect::<Vec<_>>();
+        let mut shards = Vec::new();
+        for address in &addresses {
+            shards.push(shard(*address, 1, &[0, 1]));
+            shards.push(shard(*address, 3, &[2, 3]));
+            shards.push(shard(*address, u64::MAX, &[10, 15]));
+        }
+        // a shard that holds transactions on both sides of the last pruned one
+        shards.push(shard(addresses[0], 9, &[6, 9]));
+        db.commit(|tx| {
+            for (key, list) in shards {
+                tx.put::<tables::AddressTransactions>(key, list)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        let prune_mode = PruneMode::Before(4);
+        let segment = AddressTransactions::new(prune_mode);
+        let run = || {
+            let provider = db.factory.provider_rw().unwrap();
+            let input = PruneInput {
+                previous_checkpoint: provider
+                    .get_prune_checkpoint(PruneSegment::AddressTransactions)
+                    .unwrap(),
+                to_block: 4,
+                limiter: PruneLimiter::default().set_deleted_entries_limit(4),
+            };
+            let result = segment.prune(&provider, input).unwrap();
+            if let Some(checkpoint) = result.checkpoint {
+                segment
+                    .save_checkpoint(&provider, checkpoint.as_prune_checkpoint(prune_mode))
+                    .unwrap();
+            }
+            provider.commit().expect("commit");
+            result
+        };
+
+        // the limit is hit before all shards below the last pruned transaction are deleted
+        let result = run();
+        assert_eq!(
+            result.progress,
+            PruneProgress::HasMoreData(PruneInterruptReason::DeletedEntriesLimitReached)
+        );
+        assert_eq!(result.pruned, 4);
+        assert_eq!(result.checkpoint, None);
+
+        let result = run();
+        assert_eq!(result.progress, PruneProgress::Finished);
+        assert_eq!(result.pruned, 2);
+        assert_eq!(
+            db.factory
+                .provider()
+                .unwrap()
+                .get_prune_checkpoint(PruneSegment::AddressTransactions)
+                .unwrap(),
+            Some(PruneCheckpoint { block_number: Some(4), tx_number: Some(7), prune_mode })
+        );
+
+        let mut expected = vec![shard(addresses[0], 9, &[9])];
+        expected.extend(addresses.iter().map(|address| shard(*address, u64::MAX, &[10, 15])));
+        assert_eq!(db.table::<tables::AddressTransactions>().unwrap(), expected);
+    }
+}
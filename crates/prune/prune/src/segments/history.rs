@@ -12,12 +12,15 @@ use reth_provider::DatabaseProviderRW;
 
 /// Prune history indices up to the provided block, inclusive.
 ///
+/// Shards of keys for which `is_retained` returns `true` are left untouched.
+///
 /// Returns total number of processed (walked) and deleted entities.
 pub(crate) fn prune_history_indices<DB, T, SK>(
     provider: &DatabaseProviderRW<DB>,
     to_block: BlockNumber,
     key_matches: impl Fn(&T::Key, &T::Key) -> bool,
     last_key: impl Fn(&T::Key) -> T::Key,
+    is_retained: impl Fn(&T::Key) -> bool,
 ) -> Result<(usize, usize), DatabaseError>
 where
     DB: Database,
@@ -37,6 +40,15 @@ where
     while let Some(result) = cursor.next()? {
         let (key, blocks): (T::Key, BlockNumberList) = result;
 
+        // If the history of the key is retained, skip to the last shard for this key.
+        if is_retained(&key) {
+            if key.as_ref().highest_block_number != u64::MAX {
+                cursor.seek_exact(last_key(&key))?;
+            }
+            processed += 1;
+            continue
+        }
+
         // If shard consists only of block numbers less than the target one, delete shard
         // completely.
         if key.as_ref().highest_block_number <= to_block {
@@ -1,6 +1,6 @@
 use crate::segments::{
-    AccountHistory, Receipts, ReceiptsByLogs, Segment, SenderRecovery, StorageHistory,
-    TransactionLookup,
+    AccountHistory, AddressTransactions, Receipts, ReceiptsByLogs, Segment, SenderRecovery,
+    StorageHistory, TransactionLookup,
 };
 use reth_db_api::database::Database;
 use reth_prune_types::PruneModes;
@@ -44,14 +44,21 @@ impl<DB: Database> SegmentSet<DB> {
             receipts,
             account_history,
             storage_history,
+            address_transactions,
+            account_history_retention,
+            storage_history_retention,
             receipts_log_filter,
         } = prune_modes;
 
         Self::default()
             // Account history
-            .segment_opt(account_history.map(AccountHistory::new))
+            .segment_opt(
+                account_history.map(|mode| AccountHistory::new(mode, account_history_retention)),
+            )
             // Storage history
-            .segment_opt(storage_history.map(StorageHistory::new))
+            .segment_opt(
+                storage_history.map(|mode| StorageHistory::new(mode, storage_history_retention)),
+            )
             // Receipts
             .segment_opt(receipts.map(Receipts::new))
             // Receipts by logs
@@ -63,6 +70,8 @@ impl<DB: Database> SegmentSet<DB> {
             .segment_opt(transaction_lookup.map(TransactionLookup::new))
             // Sender recovery
             .segment_opt(sender_recovery.map(SenderRecovery::new))
+            // Address transactions
+            .segment_opt(address_transactions.map(AddressTransactions::new))
     }
 }
 
@@ -10,7 +10,9 @@ use reth_db_api::{
     models::{storage_sharded_key::StorageShardedKey, BlockNumberAddress},
 };
 use reth_provider::DatabaseProviderRW;
-use reth_prune_types::{PruneInterruptReason, PruneMode, PruneProgress, PruneSegment};
+use reth_prune_types::{
+    HistoryRetentionConfig, PruneInterruptReason, PruneMode, PruneProgress, PruneSegment,
+};
 use tracing::{instrument, trace};
 
 /// Number of storage history tables to prune in one step
@@ -22,11 +24,13 @@ const STORAGE_HISTORY_TABLES_TO_PRUNE: usize = 2;
 #[derive(Debug)]
 pub struct StorageHistory {
     mode: PruneMode,
+    /// Addresses whose changesets and history indices are never pruned.
+    retention: HistoryRetentionConfig,
 }
 
 impl StorageHistory {
-    pub const fn new(mode: PruneMode) -> Self {
-        Self { mode }
+    pub const fn new(mode: PruneMode, retention: HistoryRetentionConfig) -> Self {
+        Self { mode, retention }
     }
 }
 
@@ -71,7 +75,7 @@ impl<DB: Database> Segment<DB> for StorageHistory {
             .prune_table_with_range::<tables::StorageChangeSets>(
                 BlockNumberAddress::range(range),
                 &mut limiter,
-                |_| false,
+                |(key, _)| self.retention.contains(&key.address()),
                 |row| last_changeset_pruned_block = Some(row.0.block_number()),
             )?;
         trace!(target: "pruner", deleted = %pruned_changesets, %done, "Pruned storage history (changesets)");
@@ -87,6 +91,7 @@ impl<DB: Database> Segment<DB> for StorageHistory {
             last_changeset_pruned_block,
             |a, b| a.address == b.address && a.sharded_key.key == b.sharded_key.key,
             |key| StorageShardedKey::last(key.address, key.sharded_key.key),
+            |key| self.retention.contains(&key.address),
         )?;
         trace!(target: "pruner", %processed, deleted = %pruned_indices, %done, "Pruned storage history (history)");
 
@@ -113,13 +118,19 @@ mod tests {
     use assert_matches::assert_matches;
     use reth_db::{tables, BlockNumberList};
     use reth_provider::PruneCheckpointReader;
-    use reth_prune_types::{PruneCheckpoint, PruneLimiter, PruneMode, PruneProgress, PruneSegment};
+    use reth_prune_types::{
+        HistoryRetentionConfig, PruneCheckpoint, PruneLimiter, PruneMode, PruneProgress,
+        PruneSegment,
+    };
     use reth_stages::test_utils::{StorageKind, TestStageDB};
     use reth_testing_utils::{
         generators,
         generators::{random_block_range, random_changeset_range, random_eoa_accounts},
     };
-    use std::{collections::BTreeMap, ops::AddAssign};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        ops::AddAssign,
+    };
 
     #[test]
     fn prune() {
@@ -174,7 +185,7 @@ mod tests {
                 to_block,
                 limiter: limiter.clone(),
             };
-            let segment = StorageHistory::new(prune_mode);
+            let segment = StorageHistory::new(prune_mode, HistoryRetentionConfig::default());
 
             let provider = db.factory.provider_rw().unwrap();
             let result = segment.prune(&provider, input).unwrap();
@@ -287,4 +298,63 @@ mod tests {
         test_prune(998, 2, (PruneProgress::Finished, 499));
         test_prune(1200, 3, (PruneProgress::Finished, 202));
     }
+
+    #[test]
+    fn prune_with_retention() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=2000, B256::ZERO, 0..1);
+        db.insert_blocks(blocks.iter(), StorageKind::Database(None)).expect("insert blocks");
+
+        let accounts = random_eoa_accounts(&mut rng, 2).into_iter().collect::<BTreeMap<_, _>>();
+        let retained = *accounts.keys().next().unwrap();
+
+        let (changesets, _) = random_changeset_range(
+            &mut rng,
+            blocks.iter(),
+            accounts.into_iter().map(|(addr, acc)| (addr, (acc, Vec::new()))),
+            1..2,
+            1..2,
+        );
+        db.insert_changesets(changesets.clone(), None).expect("insert changesets");
+        db.insert_history(changesets.clone(), None).expect("insert history");
+
+        let original_changesets = db.table::<tables::StorageChangeSets>().unwrap();
+        let original_shards = db.table::<tables::StoragesHistory>().unwrap();
+        assert!(original_changesets.iter().any(|(key, _)| key.address() == retained));
+
+        let to_block = 1000;
+        let input =
+            PruneInput { previous_checkpoint: None, to_block, limiter: PruneLimiter::default() };
+        let segment = StorageHistory::new(
+            PruneMode::Before(to_block),
+            HistoryRetentionConfig(BTreeSet::from([retained])),
+        );
+
+        let provider = db.factory.provider_rw().unwrap();
+        let result = segment.prune(&provider, input).unwrap();
+        provider.commit().expect("commit");
+        assert!(result.progress.is_finished());
+
+        // Changesets of the retained account are kept in full
+        assert_eq!(
+            db.table::<tables::StorageChangeSets>().unwrap(),
+            original_changesets
+                .into_iter()
+                .filter(|(key, _)| key.address() == retained || key.block_number() > to_block)
+                .collect::<Vec<_>>()
+        );
+
+        // History shards of the retained account are kept in full
+        let actual_shards = db.table::<tables::StoragesHistory>().unwrap();
+        assert_eq!(
+            actual_shards.iter().filter(|(key, _)| key.address == retained).collect::<Vec<_>>(),
+            original_shards.iter().filter(|(key, _)| key.address == retained).collect::<Vec<_>>()
+        );
+        assert!(actual_shards
+            .iter()
+            .filter(|(key, _)| key.address != retained)
+            .all(|(_, blocks)| blocks.iter().all(|block| block > to_block)));
+    }
 }
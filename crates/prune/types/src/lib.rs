@@ -21,7 +21,7 @@ pub use limiter::PruneLimiter;
 pub use mode::PruneMode;
 pub use segment::{PrunePurpose, PruneSegment, PruneSegmentError};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 pub use target::{PruneModes, MINIMUM_PRUNING_DISTANCE};
 
 use alloy_primitives::{Address, BlockNumber};
@@ -102,6 +102,30 @@ impl ReceiptsLogPruneConfig {
     }
 }
 
+/// Configuration for retaining the history of the specified addresses when pruning account or
+/// storage history.
+///
+/// History of the addresses in the set is kept in full, while the history of all other addresses
+/// is pruned according to the [`PruneMode`] of the segment.
+///
+/// Changesets that were moved to static files before the history was pruned can't be pruned per
+/// address, and are kept for all addresses. The history indices of the other addresses are still
+/// pruned, so their history is reported as pruned.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HistoryRetentionConfig(pub BTreeSet<Address>);
+
+impl HistoryRetentionConfig {
+    /// Checks if the configuration is empty
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if the history of the address is retained.
+    pub fn contains(&self, address: &Address) -> bool {
+        self.0.contains(address)
+    }
+}
+
 /// Progress of pruning.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum PruneProgress {
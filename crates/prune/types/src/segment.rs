@@ -24,15 +24,27 @@ pub enum PruneSegment {
     Headers,
     /// Prune segment responsible for the `Transactions` table.
     Transactions,
+    /// Prune segment responsible for the `AddressTransactions` table.
+    AddressTransactions,
+    /// Prune segment responsible for the `AccountChangeSets` table, after its data has been moved
+    /// to static files. Unlike [`PruneSegment::AccountHistory`], leaves `AccountsHistory` intact.
+    AccountChangeSets,
+    /// Prune segment responsible for the `StorageChangeSets` table, after its data has been moved
+    /// to static files. Unlike [`PruneSegment::StorageHistory`], leaves `StoragesHistory` intact.
+    StorageChangeSets,
 }
 
 impl PruneSegment {
     /// Returns minimum number of blocks to left in the database for this segment.
     pub const fn min_blocks(&self, purpose: PrunePurpose) -> u64 {
         match self {
-            Self::SenderRecovery | Self::TransactionLookup | Self::Headers | Self::Transactions => {
-                0
-            }
+            Self::SenderRecovery |
+            Self::TransactionLookup |
+            Self::Headers |
+            Self::Transactions |
+            Self::AddressTransactions |
+            Self::AccountChangeSets |
+            Self::StorageChangeSets => 0,
             Self::Receipts if purpose.is_static_file() => 0,
             Self::ContractLogs | Self::AccountHistory | Self::StorageHistory => {
                 MINIMUM_PRUNING_DISTANCE
@@ -1,4 +1,4 @@
-use crate::{PruneMode, ReceiptsLogPruneConfig};
+use crate::{HistoryRetentionConfig, PruneMode, ReceiptsLogPruneConfig};
 use serde::{Deserialize, Deserializer, Serialize};
 
 /// Minimum distance from the tip necessary for the node to work correctly:
@@ -37,6 +37,15 @@ pub struct PruneModes {
         deserialize_with = "deserialize_opt_prune_mode_with_min_blocks::<MINIMUM_PRUNING_DISTANCE, _>"
     )]
     pub storage_history: Option<PruneMode>,
+    /// Address Transactions pruning configuration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_transactions: Option<PruneMode>,
+    /// Addresses whose account history is retained in full, regardless of `account_history`.
+    #[serde(skip_serializing_if = "HistoryRetentionConfig::is_empty")]
+    pub account_history_retention: HistoryRetentionConfig,
+    /// Addresses whose storage history is retained in full, regardless of `storage_history`.
+    #[serde(skip_serializing_if = "HistoryRetentionConfig::is_empty")]
+    pub storage_history_retention: HistoryRetentionConfig,
     /// Receipts pruning configuration by retaining only those receipts that contain logs emitted
     /// by the specified addresses, discarding others. This setting is overridden by `receipts`.
     ///
@@ -59,6 +68,9 @@ impl PruneModes {
             receipts: Some(PruneMode::Full),
             account_history: Some(PruneMode::Full),
             storage_history: Some(PruneMode::Full),
+            address_transactions: Some(PruneMode::Full),
+            account_history_retention: Default::default(),
+            storage_history_retention: Default::default(),
             receipts_log_filter: Default::default(),
         }
     }
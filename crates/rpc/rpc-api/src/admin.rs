@@ -1,6 +1,8 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use reth_db_api::models::BackupManifest;
 use reth_network_peers::{AnyNode, NodeRecord};
 use reth_rpc_types::{admin::NodeInfo, PeerInfo};
+use std::path::PathBuf;
 
 /// Admin namespace rpc interface that gives access to several non-standard RPC methods.
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
@@ -46,3 +48,18 @@ pub trait AdminApi {
     #[method(name = "nodeInfo")]
     async fn node_info(&self) -> RpcResult<NodeInfo>;
 }
+
+/// Admin namespace rpc interface for maintaining the node's database.
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+pub trait AdminBackupApi {
+    /// Takes a backup of the database and static files into the given directory on the node's
+    /// host, while the node keeps running.
+    ///
+    /// The directory must be an absolute path outside of the node's datadir, and must not exist
+    /// or be empty.
+    ///
+    /// Returns the manifest of the backup.
+    #[method(name = "backup")]
+    async fn backup(&self, dest: PathBuf) -> RpcResult<BackupManifest>;
+}
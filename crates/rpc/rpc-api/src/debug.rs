@@ -38,14 +38,21 @@ pub trait DebugApi {
     #[method(name = "getBadBlocks")]
     async fn bad_blocks(&self) -> RpcResult<Vec<RichBlock>>;
 
-    /// Returns the structured logs created during the execution of EVM between two blocks
-    /// (excluding start) as a JSON object.
-    #[method(name = "traceChain")]
+    /// Creates a subscription that emits the structured logs created during the execution of EVM
+    /// between two blocks (excluding start), one [BlockTraceResult] per block.
+    ///
+    /// For the third parameter see [GethDebugTracingOptions] reference.
+    #[subscription(
+        name = "traceChain" => "subscription",
+        unsubscribe = "unsubscribeTraceChain",
+        item = BlockTraceResult
+    )]
     async fn debug_trace_chain(
         &self,
         start_exclusive: BlockNumberOrTag,
         end_inclusive: BlockNumberOrTag,
-    ) -> RpcResult<Vec<BlockTraceResult>>;
+        opts: Option<GethDebugTracingOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult;
 
     /// The `debug_traceBlock` method will return a full stack trace of all invoked opcodes of all
     /// transaction that were included in this block.
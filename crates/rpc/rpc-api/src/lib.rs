@@ -41,13 +41,16 @@ pub use servers::*;
 /// Aggregates all server traits.
 pub mod servers {
     pub use crate::{
-        admin::AdminApiServer,
+        admin::{AdminApiServer, AdminBackupApiServer},
+        anvil::AnvilApiServer,
         bundle::{EthBundleApiServer, EthCallBundleApiServer},
         debug::DebugApiServer,
         engine::{EngineApiServer, EngineEthApiServer},
         eth::EthApiServer,
         eth_filter::EthFilterApiServer,
         eth_pubsub::EthPubSubApiServer,
+        ganache::GanacheApiServer,
+        hardhat::HardhatApiServer,
         mev::MevApiServer,
         net::NetApiServer,
         otterscan::OtterscanServer,
@@ -68,7 +71,7 @@ pub use clients::*;
 #[cfg(feature = "client")]
 pub mod clients {
     pub use crate::{
-        admin::AdminApiClient,
+        admin::{AdminApiClient, AdminBackupApiClient},
         anvil::AnvilApiClient,
         bundle::{EthBundleApiClient, EthCallBundleApiClient},
         debug::DebugApiClient,
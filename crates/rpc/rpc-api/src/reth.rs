@@ -1,5 +1,5 @@
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use reth_primitives::{Address, BlockId, U256};
+use reth_primitives::{Address, BlockId, BlockNumberOrTag, TxHash, U256};
 use std::collections::HashMap;
 
 /// Reth API namespace for reth-specific methods
@@ -12,4 +12,16 @@ pub trait RethApi {
         &self,
         block_id: BlockId,
     ) -> RpcResult<HashMap<Address, U256>>;
+
+    /// Returns the hashes of all transactions sent from or to the address within the given block
+    /// range, in ascending order.
+    ///
+    /// Requires the address transactions index to be enabled.
+    #[method(name = "getAddressTransactions")]
+    async fn reth_get_address_transactions(
+        &self,
+        address: Address,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+    ) -> RpcResult<Vec<TxHash>>;
 }
@@ -2,7 +2,7 @@
 
 use jsonrpsee::proc_macros::rpc;
 use reth_rpc_types::beacon::relay::{
-    BuilderBlockValidationRequest, BuilderBlockValidationRequestV2,
+    BuilderBlockValidationRequest, BuilderBlockValidationRequestV2, BuilderBlockValidationRequestV3,
 };
 
 /// Block validation rpc interface.
@@ -22,4 +22,11 @@ pub trait BlockSubmissionValidationApi {
         &self,
         request: BuilderBlockValidationRequestV2,
     ) -> jsonrpsee::core::RpcResult<()>;
+
+    /// A Request to validate a block submission.
+    #[method(name = "validateBuilderSubmissionV3")]
+    async fn validate_builder_submission_v3(
+        &self,
+        request: BuilderBlockValidationRequestV3,
+    ) -> jsonrpsee::core::RpcResult<()>;
 }
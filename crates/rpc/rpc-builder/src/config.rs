@@ -92,6 +92,7 @@ impl RethRpcServerConfig for RpcServerArgs {
             .max_blocks_per_filter(self.rpc_max_blocks_per_filter.unwrap_or_max())
             .max_logs_per_response(self.rpc_max_logs_per_response.unwrap_or_max() as usize)
             .rpc_gas_cap(self.rpc_gas_cap)
+            .eth_proof_window(self.rpc_eth_proof_window)
             .state_cache(self.state_cache_config())
             .gpo_config(self.gas_price_oracle_config())
     }
@@ -217,7 +218,10 @@ mod tests {
     use clap::{Args, Parser};
     use reth_node_core::args::RpcServerArgs;
     use reth_rpc::eth::RPC_DEFAULT_GAS_CAP;
-    use reth_rpc_server_types::{constants, RethRpcModule, RpcModuleSelection};
+    use reth_rpc_server_types::{
+        constants::{self, DEFAULT_ETH_PROOF_WINDOW, MAX_ETH_PROOF_WINDOW},
+        RethRpcModule, RpcModuleSelection,
+    };
     use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
 
     use crate::config::RethRpcServerConfig;
@@ -244,6 +248,27 @@ mod tests {
         assert!(args.is_err());
     }
 
+    #[test]
+    fn test_rpc_eth_proof_window() {
+        let args = CommandParser::<RpcServerArgs>::parse_from(["reth"]).args;
+        let config = args.eth_config();
+        assert_eq!(config.eth_proof_window, DEFAULT_ETH_PROOF_WINDOW);
+
+        let args =
+            CommandParser::<RpcServerArgs>::parse_from(["reth", "--rpc.eth-proof-window", "128"])
+                .args;
+        let config = args.eth_config();
+        assert_eq!(config.eth_proof_window, 128);
+
+        let window = (MAX_ETH_PROOF_WINDOW + 1).to_string();
+        let args = CommandParser::<RpcServerArgs>::try_parse_from([
+            "reth",
+            "--rpc.eth-proof-window",
+            window.as_str(),
+        ]);
+        assert!(args.is_err());
+    }
+
     #[test]
     fn test_transport_rpc_module_config() {
         let args = CommandParser::<RpcServerArgs>::parse_from([
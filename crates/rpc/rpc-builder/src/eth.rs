@@ -16,7 +16,8 @@ use reth_rpc::{
     EthApi, EthFilter, EthPubSub,
 };
 use reth_rpc_server_types::constants::{
-    default_max_tracing_requests, DEFAULT_MAX_BLOCKS_PER_FILTER, DEFAULT_MAX_LOGS_PER_RESPONSE,
+    default_max_tracing_requests, DEFAULT_ETH_PROOF_WINDOW, DEFAULT_MAX_BLOCKS_PER_FILTER,
+    DEFAULT_MAX_LOGS_PER_RESPONSE,
 };
 use reth_tasks::{pool::BlockingTaskPool, TaskSpawner};
 use reth_transaction_pool::TransactionPool;
@@ -200,6 +201,7 @@ where
             cache.clone(),
             gas_oracle,
             self.rpc_config.eth.rpc_gas_cap,
+            self.rpc_config.eth.eth_proof_window,
             Box::new(self.eth_handlers_config.executor.clone()),
             blocking_task_pool.clone(),
             fee_history_cache.clone(),
@@ -248,6 +250,8 @@ pub struct EthConfig {
     ///
     /// Defaults to [`RPC_DEFAULT_GAS_CAP`]
     pub rpc_gas_cap: u64,
+    /// The maximum number of blocks into the past for generating state proofs.
+    pub eth_proof_window: u64,
     ///
     /// Sets TTL for stale filters
     pub stale_filter_ttl: std::time::Duration,
@@ -277,6 +281,7 @@ impl Default for EthConfig {
             max_blocks_per_filter: DEFAULT_MAX_BLOCKS_PER_FILTER,
             max_logs_per_response: DEFAULT_MAX_LOGS_PER_RESPONSE,
             rpc_gas_cap: RPC_DEFAULT_GAS_CAP.into(),
+            eth_proof_window: DEFAULT_ETH_PROOF_WINDOW,
             stale_filter_ttl: DEFAULT_STALE_FILTER_TTL,
             fee_history_cache: FeeHistoryCacheConfig::default(),
         }
@@ -319,4 +324,10 @@ impl EthConfig {
         self.rpc_gas_cap = rpc_gas_cap;
         self
     }
+
+    /// Configures the maximum proof window for historical proof generation.
+    pub const fn eth_proof_window(mut self, window: u64) -> Self {
+        self.eth_proof_window = window;
+        self
+    }
 }
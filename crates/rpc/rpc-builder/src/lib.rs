@@ -20,8 +20,9 @@
 //! use reth_evm::ConfigureEvm;
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
-//!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     AccountHistoryReader, AccountReader, BadBlocksProvider, BlockReaderIdExt,
+//!     CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, EvmEnvProvider,
+//!     StageCheckpointReader, StateProviderFactory,
 //! };
 //! use reth_rpc_builder::{
 //!     RethRpcModule, RpcModuleBuilder, RpcServerConfig, ServerBuilder, TransportRpcModuleConfig,
@@ -39,6 +40,9 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + AccountHistoryReader
+//!         + StageCheckpointReader
+//!         + BadBlocksProvider
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -81,8 +85,9 @@
 //! use reth_evm::ConfigureEvm;
 //! use reth_network_api::{NetworkInfo, Peers};
 //! use reth_provider::{
-//!     AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-//!     ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+//!     AccountHistoryReader, AccountReader, BadBlocksProvider, BlockReaderIdExt,
+//!     CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, EvmEnvProvider,
+//!     StageCheckpointReader, StateProviderFactory,
 //! };
 //! use reth_rpc_api::EngineApiServer;
 //! use reth_rpc_builder::{
@@ -105,6 +110,9 @@
 //!         + BlockReaderIdExt
 //!         + ChainSpecProvider
 //!         + ChangeSetReader
+//!         + AccountHistoryReader
+//!         + StageCheckpointReader
+//!         + BadBlocksProvider
 //!         + StateProviderFactory
 //!         + EvmEnvProvider
 //!         + Clone
@@ -174,11 +182,12 @@ use reth_evm::ConfigureEvm;
 use reth_ipc::server::IpcServer;
 use reth_network_api::{noop::NoopNetwork, NetworkInfo, Peers};
 use reth_provider::{
-    AccountReader, BlockReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider,
-    ChangeSetReader, EvmEnvProvider, StateProviderFactory,
+    AccountHistoryReader, AccountReader, BadBlocksProvider, BlockReader, BlockReaderIdExt,
+    CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, EvmEnvProvider,
+    StageCheckpointReader, StateProviderFactory,
 };
 use reth_rpc::{
-    eth::{cache::EthStateCache, traits::RawTransactionForwarder, EthBundle},
+    eth::{cache::EthStateCache, traits::RawTransactionForwarder, EthBundle, EthSimBundle},
     AdminApi, DebugApi, EngineEthApi, EthApi, EthSubscriptionIdProvider, NetApi, OtterscanApi,
     RPCApi, RethApi, TraceApi, TxPoolApi, Web3Api,
 };
@@ -243,6 +252,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + BadBlocksProvider
         + Clone
         + Unpin
         + 'static,
@@ -434,6 +446,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + BadBlocksProvider
         + Clone
         + Unpin
         + 'static,
@@ -759,6 +774,9 @@ where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + BadBlocksProvider
         + Clone
         + Unpin
         + 'static,
@@ -933,6 +951,7 @@ where
                             self.provider.clone(),
                             eth_api.clone(),
                             self.blocking_pool_guard.clone(),
+                            Box::new(self.executor.clone()),
                         )
                         .into_rpc()
                         .into(),
@@ -966,7 +985,11 @@ where
                         )
                         .into_rpc()
                         .into(),
-                        RethRpcModule::Ots => OtterscanApi::new(eth_api.clone()).into_rpc().into(),
+                        RethRpcModule::Ots => {
+                            OtterscanApi::new(self.provider.clone(), eth_api.clone())
+                                .into_rpc()
+                                .into()
+                        }
                         RethRpcModule::Reth => {
                             RethApi::new(self.provider.clone(), Box::new(self.executor.clone()))
                                 .into_rpc()
@@ -977,6 +1000,20 @@ where
                                 .into_rpc()
                                 .into()
                         }
+                        // the development namespaces depend on the auto seal miner and are
+                        // installed by the node in `--dev` mode, see
+                        // `reth_rpc::DevApi`
+                        RethRpcModule::Dev => Methods::new(),
+                        // block validation requires the consensus and the block executor, so it's
+                        // installed by the node, see `reth_rpc::ValidationApi`
+                        RethRpcModule::Flashbots => Methods::new(),
+                        RethRpcModule::Mev => EthSimBundle::new(
+                            eth_api.clone(),
+                            self.provider.chain_spec(),
+                            self.blocking_pool_guard.clone(),
+                        )
+                        .into_rpc()
+                        .into(),
                     })
                     .clone()
             })
@@ -1062,14 +1099,26 @@ where
         EthBundle::new(eth_api, self.blocking_pool_guard.clone())
     }
 
+    /// Instantiates [`EthSimBundle`] Api
+    ///
+    /// # Panics
+    ///
+    /// If called outside of the tokio runtime. See also [`Self::eth_api`]
+    pub fn sim_bundle_api(&mut self) -> EthSimBundle<EthApi<Provider, Pool, Network, EvmConfig>> {
+        let eth_api = self.eth_api();
+        EthSimBundle::new(eth_api, self.provider.chain_spec(), self.blocking_pool_guard.clone())
+    }
+
     /// Instantiates `OtterscanApi`
     ///
     /// # Panics
     ///
     /// If called outside of the tokio runtime. See also [`Self::eth_api`]
-    pub fn otterscan_api(&mut self) -> OtterscanApi<EthApi<Provider, Pool, Network, EvmConfig>> {
+    pub fn otterscan_api(
+        &mut self,
+    ) -> OtterscanApi<Provider, EthApi<Provider, Pool, Network, EvmConfig>> {
         let eth_api = self.eth_api();
-        OtterscanApi::new(eth_api)
+        OtterscanApi::new(self.provider.clone(), eth_api)
     }
 
     /// Instantiates `DebugApi`
@@ -1079,7 +1128,12 @@ where
     /// If called outside of the tokio runtime. See also [`Self::eth_api`]
     pub fn debug_api(&mut self) -> DebugApi<Provider, EthApi<Provider, Pool, Network, EvmConfig>> {
         let eth_api = self.eth_api();
-        DebugApi::new(self.provider.clone(), eth_api, self.blocking_pool_guard.clone())
+        DebugApi::new(
+            self.provider.clone(),
+            eth_api,
+            self.blocking_pool_guard.clone(),
+            Box::new(self.executor.clone()),
+        )
     }
 
     /// Instantiates `NetApi`
@@ -1646,6 +1700,53 @@ impl TransportRpcModules {
         Ok(())
     }
 
+    /// Merge the given [Methods] in the methods of all transports that are configured with the
+    /// given [`RethRpcModule`].
+    ///
+    /// This is used for modules that can't be created by the [`RethModuleRegistry`].
+    ///
+    /// Fails if any of the methods in other is present already.
+    pub fn merge_if_module_configured(
+        &mut self,
+        module: RethRpcModule,
+        other: impl Into<Methods>,
+    ) -> Result<(), RegisterMethodError> {
+        let other = other.into();
+        if self.config.http().is_some_and(|http| http.contains(&module)) {
+            self.merge_http(other.clone())?;
+        }
+        if self.config.ws().is_some_and(|ws| ws.contains(&module)) {
+            self.merge_ws(other.clone())?;
+        }
+        if self.config.ipc().is_some_and(|ipc| ipc.contains(&module)) {
+            self.merge_ipc(other)?;
+        }
+        Ok(())
+    }
+
+    /// Merge the given [Methods] in the methods of the http and ws transports whose module
+    /// selection explicitly lists the given [`RethRpcModule`].
+    ///
+    /// Unlike [`Self::merge_if_module_configured`], the methods are not merged if the module is
+    /// only enabled by selecting all modules, and never merged into the ipc transport, which has
+    /// all modules enabled by default. This is used for methods that must be opted into.
+    ///
+    /// Fails if any of the methods in other is present already.
+    pub fn merge_if_module_selected(
+        &mut self,
+        module: RethRpcModule,
+        other: impl Into<Methods>,
+    ) -> Result<(), RegisterMethodError> {
+        let other = other.into();
+        if self.config.http().is_some_and(|http| http.contains_explicitly(&module)) {
+            self.merge_http(other.clone())?;
+        }
+        if self.config.ws().is_some_and(|ws| ws.contains_explicitly(&module)) {
+            self.merge_ws(other)?;
+        }
+        Ok(())
+    }
+
     /// Convenience function for starting a server
     pub async fn start_server(self, builder: RpcServerConfig) -> Result<RpcServerHandle, RpcError> {
         builder.start(self).await
@@ -1945,6 +2046,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn dev_module_selection() {
+        let selection = "eth,dev".parse::<RpcModuleSelection>().unwrap();
+        assert!(selection.contains(&RethRpcModule::Dev));
+        assert!(!RpcModuleSelection::Standard.contains(&RethRpcModule::Dev));
+        assert!(RpcModuleSelection::All.contains(&RethRpcModule::Dev));
+    }
+
+    #[test]
+    fn explicit_module_selection() {
+        let selection = "eth,admin".parse::<RpcModuleSelection>().unwrap();
+        assert!(selection.contains_explicitly(&RethRpcModule::Admin));
+        assert!(!selection.contains_explicitly(&RethRpcModule::Debug));
+        assert!(!RpcModuleSelection::All.contains_explicitly(&RethRpcModule::Admin));
+        assert!(!RpcModuleSelection::Standard.contains_explicitly(&RethRpcModule::Eth));
+    }
+
     #[test]
     fn parse_rpc_module_selection() {
         let selection = "all".parse::<RpcModuleSelection>().unwrap();
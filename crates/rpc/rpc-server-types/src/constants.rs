@@ -15,6 +15,15 @@ pub const DEFAULT_MAX_BLOCKS_PER_FILTER: u64 = 100_000;
 /// The default maximum of logs in a single response.
 pub const DEFAULT_MAX_LOGS_PER_RESPONSE: usize = 20_000;
 
+/// The default `eth_getProof` window, i.e. proofs are only served for the latest block.
+pub const DEFAULT_ETH_PROOF_WINDOW: u64 = 0;
+
+/// Maximum `eth_getProof` window in blocks.
+///
+/// Historical proofs are generated by reverting all changesets between the target block and the
+/// tip, so the window is bounded to roughly four weeks of blocks.
+pub const MAX_ETH_PROOF_WINDOW: u64 = 28 * 24 * 60 * 60 / 12;
+
 /// The default maximum number tracing requests we're allowing concurrently.
 /// Tracing is mostly CPU bound so we're limiting the number of concurrent requests to something
 /// lower that the number of cores, in order to minimize the impact on the rest of the system.
@@ -107,6 +107,23 @@ impl RpcModuleSelection {
         }
     }
 
+    /// Returns true if the given module is part of the selection.
+    pub fn contains(&self, module: &RethRpcModule) -> bool {
+        match self {
+            Self::All => true,
+            Self::Standard => Self::STANDARD_MODULES.contains(module),
+            Self::Selection(s) => s.contains(module),
+        }
+    }
+
+    /// Returns true if the selection explicitly lists the given module.
+    ///
+    /// Unlike [`Self::contains`], this is false for [`RpcModuleSelection::All`] and
+    /// [`RpcModuleSelection::Standard`].
+    pub fn contains_explicitly(&self, module: &RethRpcModule) -> bool {
+        matches!(self, Self::Selection(s) if s.contains(module))
+    }
+
     /// Clones the set of configured [`RethRpcModule`].
     pub fn to_selection(&self) -> HashSet<RethRpcModule> {
         match self {
@@ -260,6 +277,14 @@ pub enum RethRpcModule {
     /// This is separate from [`RethRpcModule::Eth`] because it is a non standardized call that
     /// should be opt-in.
     EthCallBundle,
+    /// `anvil_`, `hardhat_` and `evm_` development modules
+    ///
+    /// These are only available on `--dev` nodes.
+    Dev,
+    /// `flashbots_` module
+    Flashbots,
+    /// `mev_` module
+    Mev,
 }
 
 // === impl RethRpcModule ===
@@ -309,6 +334,9 @@ impl FromStr for RethRpcModule {
             "reth" => Self::Reth,
             "ots" => Self::Ots,
             "eth-call-bundle" | "eth_callBundle" => Self::EthCallBundle,
+            "dev" => Self::Dev,
+            "flashbots" => Self::Flashbots,
+            "mev" => Self::Mev,
             _ => return Err(ParseError::VariantNotFound),
         })
     }
@@ -82,6 +82,11 @@ pub enum BundleItem {
         /// If true, the transaction can revert without the bundle being considered invalid.
         can_revert: bool,
     },
+    /// A nested bundle, e.g. the bundle we are trying to backrun.
+    Bundle {
+        /// The nested bundle.
+        bundle: SendBundleRequest,
+    },
 }
 
 /// Requirements for the bundle to be included in the block.
@@ -829,6 +834,46 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    #[test]
+    fn can_deserialize_nested() {
+        let str = r#"
+        {
+            "version": "v0.1",
+            "inclusion": {
+                "block": "0x1",
+                "maxBlock": "0x3"
+            },
+            "body": [{
+                "bundle": {
+                    "version": "v0.1",
+                    "inclusion": {
+                        "block": "0x1"
+                    },
+                    "body": [{
+                        "hash": "0x5d1b2d9ea2f1b2c0e7bd2dd7c21ad7e8b3fba8fd4d2c5c3a1b1f3e5f4b5d6e7f"
+                    }],
+                    "validity": {
+                        "refund": [{ "bodyIdx": 0, "percent": 90 }]
+                    }
+                }
+            }, {
+                "tx": "0x02f86b0180843b9aca00852ecc889a0082520894c87037874aed04e51c29f582394217a0a2b89d808080c080a0a463985c616dd8ee17d7ef9112af4e6e06a27b071525b42182fe7b0b5c8b4925a00af5ca177ffef2ff28449292505d41be578bebb77110dfc09361d2fb56998260",
+                "canRevert": true
+            }]
+        }
+        "#;
+        let bundle: SendBundleRequest = serde_json::from_str(str).unwrap();
+        let BundleItem::Bundle { bundle: nested } = &bundle.bundle_body[0] else {
+            panic!("expected nested bundle")
+        };
+        assert!(matches!(nested.bundle_body[0], BundleItem::Hash { .. }));
+        assert_eq!(nested.validity.as_ref().unwrap().refund.as_ref().unwrap()[0].percent, 90);
+        assert!(matches!(bundle.bundle_body[1], BundleItem::Tx { can_revert: true, .. }));
+
+        let serialized = serde_json::to_value(&bundle).unwrap();
+        assert_eq!(serde_json::from_value::<SendBundleRequest>(serialized).unwrap(), bundle);
+    }
+
     #[test]
     fn can_serialize_complex() {
         let str = r#"
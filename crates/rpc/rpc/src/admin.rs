@@ -1,17 +1,27 @@
-use crate::result::ToRpcResult;
+use crate::result::{internal_rpc_err, invalid_params_rpc_err, ToRpcResult};
 use alloy_genesis::ChainConfig;
 use alloy_primitives::B256;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_chainspec::ChainSpec;
+use reth_db_api::{
+    database::{Database, DatabaseCopy},
+    models::BackupManifest,
+};
 use reth_network_api::{NetworkInfo, PeerKind, Peers};
 use reth_network_peers::{AnyNode, NodeRecord};
-use reth_rpc_api::AdminApiServer;
+use reth_provider::ProviderFactory;
+use reth_rpc_api::{AdminApiServer, AdminBackupApiServer};
 use reth_rpc_types::{
     admin::{EthProtocolInfo, NodeInfo, Ports, ProtocolInfo},
     PeerEthProtocolInfo, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
 };
-use std::sync::Arc;
+use reth_tasks::TaskSpawner;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::sync::oneshot;
 
 /// `admin` API implementation.
 ///
@@ -141,3 +151,125 @@ impl<N> std::fmt::Debug for AdminApi<N> {
         f.debug_struct("AdminApi").finish_non_exhaustive()
     }
 }
+
+/// `admin` API implementation for maintaining the node's database.
+///
+/// This type provides the functionality for handling `admin_backup` requests.
+///
+/// The backup is written to the node's host, so this is only installed if the `admin` namespace is
+/// explicitly selected for the http or ws server.
+pub struct AdminBackupApi<DB> {
+    /// The provider factory of the node's database and static files.
+    provider_factory: ProviderFactory<DB>,
+    /// The node's data directory, backups can't be written into it.
+    datadir: PathBuf,
+    /// The type that can spawn the blocking backup task.
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+impl<DB> AdminBackupApi<DB> {
+    /// Creates a new instance of `AdminBackupApi`.
+    pub fn new(
+        provider_factory: ProviderFactory<DB>,
+        datadir: PathBuf,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        Self { provider_factory, datadir, task_spawner }
+    }
+}
+
+#[async_trait]
+impl<DB> AdminBackupApiServer for AdminBackupApi<DB>
+where
+    DB: Database + DatabaseCopy + 'static,
+{
+    /// Handler for `admin_backup`
+    async fn backup(&self, dest: PathBuf) -> RpcResult<BackupManifest> {
+        let static_files = self.provider_factory.static_file_provider().directory().to_path_buf();
+        validate_backup_dest(&dest, &[&self.datadir, &static_files])
+            .map_err(|err| invalid_params_rpc_err(format!("invalid backup destination: {err}")))?;
+
+        let (tx, rx) = oneshot::channel();
+        let provider_factory = self.provider_factory.clone();
+        self.task_spawner.spawn_blocking(Box::pin(async move {
+            let _ = tx.send(reth_db_common::backup::backup(&provider_factory, &dest));
+        }));
+        rx.await.map_err(|_| internal_rpc_err("backup task was dropped"))?.to_rpc_result()
+    }
+}
+
+impl<DB> std::fmt::Debug for AdminBackupApi<DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminBackupApi").field("datadir", &self.datadir).finish_non_exhaustive()
+    }
+}
+
+/// Ensures the backup destination is an absolute path outside of the given directories, and that
+/// it's empty if it exists.
+///
+/// Symlinks and `..` components are resolved for the part of the path that exists.
+fn validate_backup_dest(dest: &Path, protected: &[&Path]) -> Result<(), String> {
+    if !dest.is_absolute() {
+        return Err(format!("{} is not an absolute path", dest.display()))
+    }
+
+    let resolved = resolve_path(dest);
+    for dir in protected {
+        if resolved.starts_with(resolve_path(dir)) {
+            return Err(format!("{} is inside {}", dest.display(), dir.display()))
+        }
+    }
+
+    if let Ok(mut entries) = std::fs::read_dir(dest) {
+        if entries.next().is_some() {
+            return Err(format!("{} is not empty", dest.display()))
+        }
+    } else if dest.exists() {
+        return Err(format!("{} is not a directory", dest.display()))
+    }
+    Ok(())
+}
+
+/// Canonicalizes the longest existing ancestor of the path, and appends the remaining components.
+fn resolve_path(path: &Path) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return missing.into_iter().rev().fold(canonical, |path, component| path.join(component))
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_backup_dest() {
+        let datadir = tempfile::tempdir().unwrap();
+        let backups = tempfile::tempdir().unwrap();
+        let protected = [datadir.path()];
+
+        assert!(validate_backup_dest(&backups.path().join("new"), &protected).is_ok());
+        assert!(validate_backup_dest(backups.path(), &protected).is_ok());
+        assert!(validate_backup_dest(Path::new("relative"), &protected).is_err());
+
+        // the datadir itself, a directory inside it, and a path escaping into it are rejected
+        assert!(validate_backup_dest(datadir.path(), &protected).is_err());
+        assert!(validate_backup_dest(&datadir.path().join("db/backup"), &protected).is_err());
+        let escaped = backups.path().join("..").join(datadir.path().file_name().unwrap());
+        assert!(validate_backup_dest(&escaped, &protected).is_err());
+
+        std::fs::write(backups.path().join("file"), b"").unwrap();
+        assert!(validate_backup_dest(backups.path(), &protected).is_err());
+        assert!(validate_backup_dest(&backups.path().join("file"), &protected).is_err());
+    }
+}
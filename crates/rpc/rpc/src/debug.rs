@@ -9,13 +9,17 @@ use crate::{
 };
 use alloy_rlp::{Decodable, Encodable};
 use async_trait::async_trait;
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::{
+    core::RpcResult, server::SubscriptionMessage, types::ErrorObject, PendingSubscriptionSink,
+    SubscriptionSink,
+};
 use reth_primitives::{
-    revm::env::tx_env_with_recovered, Address, Block, BlockId, BlockNumberOrTag, Bytes,
-    TransactionSignedEcRecovered, Withdrawals, B256, U256,
+    revm::env::tx_env_with_recovered, Address, Block, BlockId, BlockNumber, BlockNumberOrTag,
+    Bytes, TransactionSignedEcRecovered, Withdrawals, B256, U256,
 };
 use reth_provider::{
-    BlockReaderIdExt, ChainSpecProvider, HeaderProvider, StateProviderBox, TransactionVariant,
+    BadBlocksProvider, BlockReaderIdExt, ChainSpecProvider, HeaderProvider, StateProviderBox,
+    TransactionVariant,
 };
 use reth_revm::database::StateProviderDatabase;
 use reth_rpc_api::DebugApiServer;
@@ -27,7 +31,8 @@ use reth_rpc_types::{
     },
     BlockError, Bundle, RichBlock, StateContext, TransactionRequest,
 };
-use reth_tasks::pool::BlockingTaskGuard;
+use reth_rpc_types_compat::block::from_block;
+use reth_tasks::{pool::BlockingTaskGuard, TaskSpawner};
 use revm::{
     db::CacheDB,
     primitives::{db::DatabaseCommit, BlockEnv, CfgEnvWithHandlerCfg, Env, EnvWithHandlerCfg},
@@ -36,7 +41,7 @@ use revm_inspectors::tracing::{
     js::{JsInspector, TransactionContext},
     FourByteInspector, MuxInspector, TracingInspector, TracingInspectorConfig,
 };
-use std::sync::Arc;
+use std::{ops::RangeInclusive, sync::Arc};
 use tokio::sync::{AcquireError, OwnedSemaphorePermit};
 
 /// `debug` API implementation.
@@ -50,8 +55,14 @@ pub struct DebugApi<Provider, Eth> {
 
 impl<Provider, Eth> DebugApi<Provider, Eth> {
     /// Create a new instance of the [`DebugApi`]
-    pub fn new(provider: Provider, eth: Eth, blocking_task_guard: BlockingTaskGuard) -> Self {
-        let inner = Arc::new(DebugApiInner { provider, eth_api: eth, blocking_task_guard });
+    pub fn new(
+        provider: Provider,
+        eth: Eth,
+        blocking_task_guard: BlockingTaskGuard,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let inner =
+            Arc::new(DebugApiInner { provider, eth_api: eth, blocking_task_guard, task_spawner });
         Self { inner }
     }
 
@@ -65,7 +76,7 @@ impl<Provider, Eth> DebugApi<Provider, Eth> {
 
 impl<Provider, Eth> DebugApi<Provider, Eth>
 where
-    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + 'static,
+    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + BadBlocksProvider + 'static,
     Eth: EthTransactions + 'static,
 {
     /// Acquires a permit to execute a tracing call.
@@ -200,6 +211,42 @@ where
         .await
     }
 
+    /// Replays all blocks in the given range and sends the traces of each block to the sink.
+    ///
+    /// Stops early if the subscription is closed.
+    async fn trace_chain(
+        &self,
+        sink: SubscriptionSink,
+        range: RangeInclusive<BlockNumber>,
+        opts: GethDebugTracingOptions,
+    ) -> Result<(), ErrorObject<'static>> {
+        for number in range {
+            if sink.is_closed() {
+                break
+            }
+
+            let hash = self
+                .inner
+                .provider
+                .block_hash(number)
+                .map_err(EthApiError::from)?
+                .ok_or(EthApiError::UnknownBlockNumber)?;
+            let traces = {
+                let _permit = self.acquire_trace_permit().await;
+                self.debug_trace_block(hash.into(), opts.clone()).await?
+            };
+
+            let result = BlockTraceResult { block: U256::from(number), hash, traces };
+            let msg = SubscriptionMessage::from_json(&result)
+                .map_err(|err| internal_rpc_err(err.to_string()))?;
+            if sink.send(msg).await.is_err() {
+                break
+            }
+        }
+
+        Ok(())
+    }
+
     /// Trace the transaction according to the provided options.
     ///
     /// Ref: <https://geth.ethereum.org/docs/developers/evm-tracing/built-in-tracers>
@@ -614,7 +661,7 @@ where
 #[async_trait]
 impl<Provider, Eth> DebugApiServer for DebugApi<Provider, Eth>
 where
-    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + 'static,
+    Provider: BlockReaderIdExt + HeaderProvider + ChainSpecProvider + BadBlocksProvider + 'static,
     Eth: EthApiSpec + 'static,
 {
     /// Handler for `debug_getRawHeader`
@@ -692,16 +739,59 @@ where
 
     /// Handler for `debug_getBadBlocks`
     async fn bad_blocks(&self) -> RpcResult<Vec<RichBlock>> {
-        Err(internal_rpc_err("unimplemented"))
+        let mut blocks = Vec::new();
+        for block in self.inner.provider.bad_blocks().blocks() {
+            let block_hash = block.hash();
+            // bad blocks are not part of the chain, so the total difficulty is derived from the
+            // parent
+            let total_difficulty = self
+                .inner
+                .provider
+                .header_td(&block.parent_hash)
+                .to_rpc_result()?
+                .unwrap_or_default() +
+                block.difficulty;
+
+            // blocks with transactions that have invalid signatures can't be converted
+            let Some(block) = (*block).clone().unseal().with_recovered_senders() else { continue };
+
+            let block = from_block(block, total_difficulty, true.into(), Some(block_hash))
+                .map_err(EthApiError::from)?;
+            blocks.push(block.into());
+        }
+
+        Ok(blocks)
     }
 
     /// Handler for `debug_traceChain`
     async fn debug_trace_chain(
         &self,
-        _start_exclusive: BlockNumberOrTag,
-        _end_inclusive: BlockNumberOrTag,
-    ) -> RpcResult<Vec<BlockTraceResult>> {
-        Err(internal_rpc_err("unimplemented"))
+        pending: PendingSubscriptionSink,
+        start_exclusive: BlockNumberOrTag,
+        end_inclusive: BlockNumberOrTag,
+        opts: Option<GethDebugTracingOptions>,
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let start = self
+            .inner
+            .provider
+            .convert_block_number(start_exclusive)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        let end = self
+            .inner
+            .provider
+            .convert_block_number(end_inclusive)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        if start >= end {
+            return Err(EthApiError::InvalidBlockRange.into())
+        }
+
+        let sink = pending.accept().await?;
+        let this = self.clone();
+        self.inner.task_spawner.spawn(Box::pin(async move {
+            let _ = this.trace_chain(sink, start + 1..=end, opts.unwrap_or_default()).await;
+        }));
+
+        Ok(())
     }
 
     /// Handler for `debug_traceBlock`
@@ -996,4 +1086,6 @@ struct DebugApiInner<Provider, Eth> {
     eth_api: Eth,
     // restrict the number of concurrent calls to blocking calls
     blocking_task_guard: BlockingTaskGuard,
+    /// The type that can spawn tasks, e.g. the `debug_traceChain` subscriptions.
+    task_spawner: Box<dyn TaskSpawner>,
 }
@@ -0,0 +1,547 @@
+use crate::eth::error::{EthApiError, EthResult};
+use async_trait::async_trait;
+use jsonrpsee::{
+    core::{RegisterMethodError, RpcResult},
+    RpcModule,
+};
+use reth_auto_seal_consensus::AutoSealHandle;
+use reth_primitives::{Account, Address, Bytecode, Bytes, SealedHeader, StorageEntry, B256, U256};
+use reth_provider::{AccountReader, StateOverrideWriter, StateProviderFactory};
+use reth_rpc_api::{AnvilApiServer, GanacheApiServer, HardhatApiServer};
+use reth_rpc_types::{
+    anvil::{Forking, Metadata, MineOptions, NodeInfo},
+    Block,
+};
+use reth_transaction_pool::TransactionPool;
+use std::{sync::Arc, time::Duration};
+
+/// Maximum number of blocks that can be mined with a single call.
+const MAX_MINE_BLOCKS: u64 = 10_000;
+
+/// Methods of the development namespaces that are not installed by
+/// [`DevApi::into_rpc_module`].
+///
+/// Impersonation requires executing unsigned transactions, which the pool and the auto seal miner
+/// do not support. Forking, state dumps and changing the chain id or the pool's price limits are
+/// not supported by a node either.
+pub const UNSUPPORTED_DEV_METHODS: &[&str] = &[
+    "anvil_impersonateAccount",
+    "anvil_stopImpersonatingAccount",
+    "anvil_autoImpersonateAccount",
+    "anvil_reset",
+    "anvil_setRpcUrl",
+    "anvil_setChainId",
+    "anvil_setLoggingEnabled",
+    "anvil_setMinGasPrice",
+    "anvil_dumpState",
+    "anvil_loadState",
+    "anvil_nodeInfo",
+    "anvil_metadata",
+    "anvil_mine_detailed",
+    "anvil_enableTraces",
+    "hardhat_impersonateAccount",
+    "hardhat_stopImpersonatingAccount",
+    "hardhat_metadata",
+    "hardhat_reset",
+    "hardhat_setLoggingEnabled",
+    "hardhat_setMinGasPrice",
+];
+
+/// Error returned by the handlers of the [`UNSUPPORTED_DEV_METHODS`].
+const UNSUPPORTED: &str = "not supported by the development namespaces";
+
+/// Development API implementation, compatible with the `anvil`, `hardhat` and `evm` namespaces of
+/// Anvil, Hardhat and Ganache.
+///
+/// This is only available on `--dev` nodes, where blocks are produced by the auto seal miner.
+/// State overrides are recorded as changes of the next block, which is mined empty right after, so
+/// unwinding that block reverts them.
+///
+/// Reverting to a snapshot unwinds all blocks mined after it, which requires their changesets and
+/// therefore does not work on pruned nodes.
+pub struct DevApi<Provider, Pool> {
+    inner: Arc<DevApiInner<Provider, Pool>>,
+}
+
+// === impl DevApi ===
+
+impl<Provider, Pool> DevApi<Provider, Pool> {
+    /// Creates a new instance of the [`DevApi`].
+    pub fn new(provider: Provider, pool: Pool, miner: AutoSealHandle) -> Self {
+        Self { inner: Arc::new(DevApiInner { provider, pool, miner }) }
+    }
+}
+
+impl<Provider, Pool> DevApi<Provider, Pool>
+where
+    Provider: StateProviderFactory + StateOverrideWriter + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Returns the `anvil`, `hardhat` and `evm` namespaces, without the
+    /// [`UNSUPPORTED_DEV_METHODS`].
+    pub fn into_rpc_module(self) -> Result<RpcModule<()>, RegisterMethodError> {
+        let mut module = RpcModule::new(());
+        module.merge(AnvilApiServer::into_rpc(self.clone()))?;
+        module.merge(HardhatApiServer::into_rpc(self.clone()))?;
+        module.merge(GanacheApiServer::into_rpc(self))?;
+        for &method in UNSUPPORTED_DEV_METHODS {
+            module.remove_method(method);
+        }
+        Ok(module)
+    }
+
+    /// Mines `blocks` blocks, `interval` seconds apart if set.
+    async fn mine_blocks(
+        &self,
+        blocks: u64,
+        interval: Option<u64>,
+        include_transactions: bool,
+    ) -> EthResult<()> {
+        if blocks > MAX_MINE_BLOCKS {
+            return Err(EthApiError::InvalidParams(format!(
+                "cannot mine more than {MAX_MINE_BLOCKS} blocks at once"
+            )))
+        }
+        let mut parent: Option<SealedHeader> = None;
+        for _ in 0..blocks {
+            if let (Some(interval), Some(parent)) = (interval, &parent) {
+                self.inner.miner.set_next_block_timestamp(parent.timestamp + interval).await;
+            }
+            parent = Some(
+                self.inner
+                    .miner
+                    .mine(include_transactions)
+                    .await
+                    .ok_or(EthApiError::InternalEthError)?,
+            );
+        }
+        Ok(())
+    }
+
+    /// Modifies the account at `address` with `f` and applies the result, together with the
+    /// given bytecode and storage, on top of the tip of the chain.
+    ///
+    /// An empty block is mined afterwards, which the override is recorded in.
+    async fn override_account(
+        &self,
+        address: Address,
+        bytecode: Option<Bytecode>,
+        storage: Vec<StorageEntry>,
+        f: impl FnOnce(&mut Account),
+    ) -> EthResult<()> {
+        let mut account = self.inner.provider.latest()?.basic_account(address)?.unwrap_or_default();
+        f(&mut account);
+        self.inner.provider.override_state(address, account, bytecode, storage)?;
+        self.mine_blocks(1, None, false).await
+    }
+
+    /// Sets the balance of the account.
+    async fn set_balance(&self, address: Address, balance: U256) -> EthResult<()> {
+        self.override_account(address, None, Vec::new(), |account| account.balance = balance).await
+    }
+
+    /// Sets the code of the account.
+    async fn set_code(&self, address: Address, code: Bytes) -> EthResult<()> {
+        let bytecode = (!code.is_empty()).then(|| Bytecode::new_raw(code));
+        let bytecode_hash = bytecode.as_ref().map(Bytecode::hash_slow);
+        self.override_account(address, bytecode, Vec::new(), |account| {
+            account.bytecode_hash = bytecode_hash
+        })
+        .await
+    }
+
+    /// Sets the nonce of the account.
+    async fn set_nonce(&self, address: Address, nonce: U256) -> EthResult<()> {
+        let nonce = nonce.try_into().map_err(|_| EthApiError::InvalidParams("nonce".into()))?;
+        self.override_account(address, None, Vec::new(), |account| account.nonce = nonce).await
+    }
+
+    /// Sets a single storage slot of the account.
+    async fn set_storage_at(&self, address: Address, slot: U256, value: B256) -> EthResult<()> {
+        let entry = StorageEntry { key: B256::from(slot), value: U256::from_be_bytes(value.0) };
+        self.override_account(address, None, vec![entry], |_| {}).await
+    }
+
+    /// Sets the base fee of the next block.
+    async fn set_next_block_base_fee(&self, base_fee: U256) -> EthResult<()> {
+        let base_fee =
+            base_fee.try_into().map_err(|_| EthApiError::InvalidParams("base fee".into()))?;
+        self.inner.miner.set_next_block_base_fee(base_fee).await;
+        Ok(())
+    }
+
+    /// Removes the transaction from the pool, returning its hash if it was removed.
+    fn drop_transaction(&self, tx_hash: B256) -> Option<B256> {
+        self.inner.pool.remove_transactions(vec![tx_hash]).first().map(|tx| *tx.hash())
+    }
+
+    /// Sets the interval mining is performed at in seconds, `0` disables interval mining.
+    fn set_interval_mining(&self, interval: u64) {
+        let interval = (interval > 0).then(|| Duration::from_secs(interval));
+        self.inner.miner.set_interval_mining(interval);
+    }
+}
+
+#[async_trait]
+impl<Provider, Pool> AnvilApiServer for DevApi<Provider, Pool>
+where
+    Provider: StateProviderFactory + StateOverrideWriter + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Handler for `anvil_impersonateAccount`
+    async fn anvil_impersonate_account(&self, _address: Address) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_stopImpersonatingAccount`
+    async fn anvil_stop_impersonating_account(&self, _address: Address) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_autoImpersonateAccount`
+    async fn anvil_auto_impersonate_account(&self, _enabled: bool) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_getAutomine`
+    async fn anvil_get_automine(&self) -> RpcResult<bool> {
+        Ok(self.inner.miner.is_automine().await)
+    }
+
+    /// Handler for `anvil_mine`
+    async fn anvil_mine(&self, blocks: Option<U256>, interval: Option<U256>) -> RpcResult<()> {
+        let blocks = blocks.map(|blocks| blocks.saturating_to()).unwrap_or(1);
+        let interval = interval.map(|interval| interval.saturating_to());
+        Ok(self.mine_blocks(blocks, interval, true).await?)
+    }
+
+    /// Handler for `anvil_setAutomine`
+    async fn anvil_set_automine(&self, enabled: bool) -> RpcResult<()> {
+        self.inner.miner.set_automine(enabled);
+        Ok(())
+    }
+
+    /// Handler for `anvil_setIntervalMining`
+    async fn anvil_set_interval_mining(&self, interval: u64) -> RpcResult<()> {
+        self.set_interval_mining(interval);
+        Ok(())
+    }
+
+    /// Handler for `anvil_dropTransaction`
+    async fn anvil_drop_transaction(&self, tx_hash: B256) -> RpcResult<Option<B256>> {
+        Ok(self.drop_transaction(tx_hash))
+    }
+
+    /// Handler for `anvil_reset`
+    async fn anvil_reset(&self, _fork: Option<Forking>) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_setRpcUrl`
+    async fn anvil_set_rpc_url(&self, _url: String) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_setBalance`
+    async fn anvil_set_balance(&self, address: Address, balance: U256) -> RpcResult<()> {
+        Ok(self.set_balance(address, balance).await?)
+    }
+
+    /// Handler for `anvil_setCode`
+    async fn anvil_set_code(&self, address: Address, code: Bytes) -> RpcResult<()> {
+        Ok(self.set_code(address, code).await?)
+    }
+
+    /// Handler for `anvil_setNonce`
+    async fn anvil_set_nonce(&self, address: Address, nonce: U256) -> RpcResult<()> {
+        Ok(self.set_nonce(address, nonce).await?)
+    }
+
+    /// Handler for `anvil_setStorageAt`
+    async fn anvil_set_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        value: B256,
+    ) -> RpcResult<bool> {
+        self.set_storage_at(address, slot, value).await?;
+        Ok(true)
+    }
+
+    /// Handler for `anvil_setCoinbase`
+    async fn anvil_set_coinbase(&self, address: Address) -> RpcResult<()> {
+        self.inner.miner.set_coinbase(address).await;
+        Ok(())
+    }
+
+    /// Handler for `anvil_setChainId`
+    async fn anvil_set_chain_id(&self, _chain_id: u64) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_setLoggingEnabled`
+    async fn anvil_set_logging_enabled(&self, _enabled: bool) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_setMinGasPrice`
+    async fn anvil_set_min_gas_price(&self, _gas_price: U256) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_setNextBlockBaseFeePerGas`
+    async fn anvil_set_next_block_base_fee_per_gas(&self, base_fee: U256) -> RpcResult<()> {
+        Ok(self.set_next_block_base_fee(base_fee).await?)
+    }
+
+    /// Handler for `anvil_setTime`
+    async fn anvil_set_time(&self, timestamp: u64) -> RpcResult<u64> {
+        let offset = self.inner.miner.set_time(timestamp).await;
+        Ok(offset.max(0) as u64)
+    }
+
+    /// Handler for `anvil_dumpState`
+    async fn anvil_dump_state(&self) -> RpcResult<Bytes> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_loadState`
+    async fn anvil_load_state(&self, _state: Bytes) -> RpcResult<bool> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_nodeInfo`
+    async fn anvil_node_info(&self) -> RpcResult<NodeInfo> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_metadata`
+    async fn anvil_metadata(&self) -> RpcResult<Metadata> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_snapshot`
+    async fn anvil_snapshot(&self) -> RpcResult<U256> {
+        Ok(self.inner.miner.snapshot().await)
+    }
+
+    /// Handler for `anvil_revert`
+    async fn anvil_revert(&self, id: U256) -> RpcResult<bool> {
+        Ok(self.inner.miner.revert(id).await)
+    }
+
+    /// Handler for `anvil_increaseTime`
+    async fn anvil_increase_time(&self, seconds: U256) -> RpcResult<i64> {
+        Ok(self.inner.miner.increase_time(seconds.saturating_to()).await)
+    }
+
+    /// Handler for `anvil_setNextBlockTimestamp`
+    async fn anvil_set_next_block_timestamp(&self, seconds: u64) -> RpcResult<()> {
+        self.inner.miner.set_next_block_timestamp(seconds).await;
+        Ok(())
+    }
+
+    /// Handler for `anvil_setBlockGasLimit`
+    async fn anvil_set_block_gas_limit(&self, gas_limit: U256) -> RpcResult<bool> {
+        let gas_limit =
+            gas_limit.try_into().map_err(|_| EthApiError::InvalidParams("gas limit".into()))?;
+        self.inner.miner.set_block_gas_limit(gas_limit).await;
+        Ok(true)
+    }
+
+    /// Handler for `anvil_setBlockTimestampInterval`
+    async fn anvil_set_block_timestamp_interval(&self, seconds: u64) -> RpcResult<()> {
+        self.inner.miner.set_block_timestamp_interval(Some(seconds)).await;
+        Ok(())
+    }
+
+    /// Handler for `anvil_removeBlockTimestampInterval`
+    async fn anvil_remove_block_timestamp_interval(&self) -> RpcResult<bool> {
+        Ok(self.inner.miner.set_block_timestamp_interval(None).await)
+    }
+
+    /// Handler for `anvil_mine_detailed`
+    async fn anvil_mine_detailed(&self, _opts: Option<MineOptions>) -> RpcResult<Vec<Block>> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_enableTraces`
+    async fn anvil_enable_traces(&self) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `anvil_removePoolTransactions`
+    async fn anvil_remove_pool_transactions(&self, address: Address) -> RpcResult<()> {
+        let hashes = self
+            .inner
+            .pool
+            .get_transactions_by_sender(address)
+            .into_iter()
+            .map(|tx| *tx.hash())
+            .collect();
+        self.inner.pool.remove_transactions(hashes);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<Provider, Pool> HardhatApiServer for DevApi<Provider, Pool>
+where
+    Provider: StateProviderFactory + StateOverrideWriter + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Handler for `hardhat_dropTransaction`
+    async fn hardhat_drop_transaction(&self, tx_hash: B256) -> RpcResult<bool> {
+        Ok(self.drop_transaction(tx_hash).is_some())
+    }
+
+    /// Handler for `hardhat_impersonateAccount`
+    async fn hardhat_impersonate_account(&self, _address: Address) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `hardhat_getAutomine`
+    async fn hardhat_get_automine(&self) -> RpcResult<bool> {
+        Ok(self.inner.miner.is_automine().await)
+    }
+
+    /// Handler for `hardhat_metadata`
+    async fn hardhat_metadata(&self) -> RpcResult<Metadata> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `hardhat_mine`
+    async fn hardhat_mine(&self, blocks: Option<U256>, interval: Option<U256>) -> RpcResult<()> {
+        let blocks = blocks.map(|blocks| blocks.saturating_to()).unwrap_or(1);
+        let interval = interval.map(|interval| interval.saturating_to());
+        Ok(self.mine_blocks(blocks, interval, true).await?)
+    }
+
+    /// Handler for `hardhat_reset`
+    async fn hardhat_reset(&self, _fork: Option<Forking>) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `hardhat_setBalance`
+    async fn hardhat_set_balance(&self, address: Address, balance: U256) -> RpcResult<()> {
+        Ok(self.set_balance(address, balance).await?)
+    }
+
+    /// Handler for `hardhat_setCode`
+    async fn hardhat_set_code(&self, address: Address, code: Bytes) -> RpcResult<()> {
+        Ok(self.set_code(address, code).await?)
+    }
+
+    /// Handler for `hardhat_setCoinbase`
+    async fn hardhat_set_coinbase(&self, address: Address) -> RpcResult<()> {
+        self.inner.miner.set_coinbase(address).await;
+        Ok(())
+    }
+
+    /// Handler for `hardhat_setLoggingEnabled`
+    async fn hardhat_set_logging_enabled(&self, _enabled: bool) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `hardhat_setMinGasPrice`
+    async fn hardhat_set_min_gas_price(&self, _gas_price: U256) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+
+    /// Handler for `hardhat_setNextBlockBaseFeePerGas`
+    async fn hardhat_set_next_block_base_fee_per_gas(
+        &self,
+        base_fee_per_gas: U256,
+    ) -> RpcResult<()> {
+        Ok(self.set_next_block_base_fee(base_fee_per_gas).await?)
+    }
+
+    /// Handler for `hardhat_setPrevRandao`
+    async fn hardhat_set_prev_randao(&self, prev_randao: B256) -> RpcResult<()> {
+        self.inner.miner.set_prev_randao(prev_randao).await;
+        Ok(())
+    }
+
+    /// Handler for `hardhat_setNonce`
+    async fn hardhat_set_nonce(&self, address: Address, nonce: U256) -> RpcResult<()> {
+        Ok(self.set_nonce(address, nonce).await?)
+    }
+
+    /// Handler for `hardhat_setStorageAt`
+    async fn hardhat_set_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        value: B256,
+    ) -> RpcResult<()> {
+        Ok(self.set_storage_at(address, slot, value).await?)
+    }
+
+    /// Handler for `hardhat_stopImpersonatingAccount`
+    async fn hardhat_stop_impersonating_account(&self, _address: Address) -> RpcResult<()> {
+        Err(EthApiError::Unsupported(UNSUPPORTED).into())
+    }
+}
+
+#[async_trait]
+impl<Provider, Pool> GanacheApiServer for DevApi<Provider, Pool>
+where
+    Provider: StateProviderFactory + StateOverrideWriter + 'static,
+    Pool: TransactionPool + 'static,
+{
+    /// Handler for `evm_increaseTime`
+    async fn evm_increase_time(&self, seconds: U256) -> RpcResult<i64> {
+        Ok(self.inner.miner.increase_time(seconds.saturating_to()).await)
+    }
+
+    /// Handler for `evm_mine`
+    async fn evm_mine(&self, opts: Option<MineOptions>) -> RpcResult<String> {
+        let (timestamp, blocks) = match opts {
+            Some(MineOptions::Options { timestamp, blocks }) => (timestamp, blocks),
+            Some(MineOptions::Timestamp(timestamp)) => (timestamp, None),
+            None => (None, None),
+        };
+        if let Some(timestamp) = timestamp {
+            self.inner.miner.set_next_block_timestamp(timestamp).await;
+        }
+        self.mine_blocks(blocks.unwrap_or(1), None, true).await?;
+        Ok("0x0".to_string())
+    }
+
+    /// Handler for `evm_revert`
+    async fn evm_revert(&self, snapshot_id: U256) -> RpcResult<bool> {
+        Ok(self.inner.miner.revert(snapshot_id).await)
+    }
+
+    /// Handler for `evm_setTime`
+    async fn evm_set_time(&self, timestamp: u64) -> RpcResult<bool> {
+        self.inner.miner.set_time(timestamp).await;
+        Ok(true)
+    }
+
+    /// Handler for `evm_snapshot`
+    async fn evm_snapshot(&self) -> RpcResult<U256> {
+        Ok(self.inner.miner.snapshot().await)
+    }
+}
+
+impl<Provider, Pool> std::fmt::Debug for DevApi<Provider, Pool> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DevApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, Pool> Clone for DevApi<Provider, Pool> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+struct DevApiInner<Provider, Pool> {
+    /// The provider that can interact with the chain.
+    provider: Provider,
+    /// The transaction pool.
+    pool: Pool,
+    /// Controls block production of the auto seal miner.
+    miner: AutoSealHandle,
+}
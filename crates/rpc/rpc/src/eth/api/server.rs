@@ -3,10 +3,7 @@
 
 use super::EthApiSpec;
 use crate::{
-    eth::{
-        api::{EthApi, EthTransactions},
-        error::EthApiError,
-    },
+    eth::api::{EthApi, EthTransactions},
     result::{internal_rpc_err, ToRpcResult},
 };
 use alloy_dyn_abi::TypedData;
@@ -412,14 +409,7 @@ where
         block_number: Option<BlockId>,
     ) -> Result<EIP1186AccountProofResponse> {
         trace!(target: "rpc::eth", ?address, ?keys, ?block_number, "Serving eth_getProof");
-        let res = Self::get_proof(self, address, keys, block_number).await;
-
-        Ok(res.map_err(|e| match e {
-            EthApiError::InvalidBlockRange => {
-                internal_rpc_err("eth_getProof is unimplemented for historical blocks")
-            }
-            _ => e.into(),
-        })?)
+        Ok(Self::get_proof(self, address, keys, block_number).await?)
     }
 }
 
@@ -445,6 +435,7 @@ mod tests {
         BlockReader, BlockReaderIdExt, ChainSpecProvider, EvmEnvProvider, StateProviderFactory,
     };
     use reth_rpc_api::EthApiServer;
+    use reth_rpc_server_types::constants::DEFAULT_ETH_PROOF_WINDOW;
     use reth_rpc_types::FeeHistory;
     use reth_tasks::pool::BlockingTaskPool;
     use reth_testing_utils::{generators, generators::Rng};
@@ -474,6 +465,7 @@ mod tests {
             cache.clone(),
             GasPriceOracle::new(provider, Default::default(), cache),
             ETHEREUM_BLOCK_GAS_LIMIT,
+            DEFAULT_ETH_PROOF_WINDOW,
             BlockingTaskPool::build().expect("failed to build tracing pool"),
             fee_history_cache,
             evm_config,
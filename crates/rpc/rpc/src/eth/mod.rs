@@ -11,6 +11,7 @@ mod logs_utils;
 mod pubsub;
 pub mod revm_utils;
 mod signer;
+pub mod sim_bundle;
 pub mod traits;
 pub(crate) mod utils;
 
@@ -26,3 +27,4 @@ pub use bundle::EthBundle;
 pub use filter::{EthFilter, EthFilterConfig};
 pub use id_provider::EthSubscriptionIdProvider;
 pub use pubsub::EthPubSub;
+pub use sim_bundle::EthSimBundle;
@@ -0,0 +1,842 @@
+//! `Eth` Sim bundle implementation and helpers.
+
+use crate::eth::{
+    error::{EthApiError, EthResult},
+    revm_utils::FillableTransaction,
+    utils::recover_raw_transaction,
+    EthTransactions, TransactionSource,
+};
+use jsonrpsee::core::RpcResult;
+use reth_chainspec::ChainSpec;
+use reth_primitives::{
+    revm_primitives::db::{Database, DatabaseCommit},
+    Address, TransactionSignedEcRecovered, TxHash, U256,
+};
+use reth_revm::database::StateProviderDatabase;
+use reth_rpc_api::MevApiServer;
+use reth_rpc_types::{
+    BlockId, BundleItem, Inclusion, Log, RefundConfig, SendBundleRequest, SendBundleResponse,
+    SimBundleLogs, SimBundleOverrides, SimBundleResponse, Validity,
+};
+use reth_tasks::pool::BlockingTaskGuard;
+use revm::{
+    db::CacheDB,
+    primitives::{Account, EVMError, ResultAndState, TxEnv},
+};
+use revm_primitives::EnvWithHandlerCfg;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Maximum depth of nested bundles.
+const MAX_NESTED_BUNDLE_DEPTH: usize = 5;
+
+/// Maximum number of items in a single bundle body.
+const MAX_BUNDLE_BODY_SIZE: usize = 50;
+
+/// Default timeout of a simulation.
+const DEFAULT_SIM_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum timeout of a simulation that can be requested.
+const MAX_SIM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Gas used by the transfer that pays a refund to its recipient.
+const REFUND_TRANSFER_GAS: u64 = 21_000;
+
+/// `Eth` sim bundle implementation.
+///
+/// Implements the `mev_` namespace, so that MEV-share bundles can be simulated against the state
+/// of this node.
+pub struct EthSimBundle<Eth> {
+    /// All nested fields bundled together.
+    inner: Arc<EthSimBundleInner<Eth>>,
+}
+
+impl<Eth> EthSimBundle<Eth> {
+    /// Create a new `EthSimBundle` instance.
+    pub fn new(
+        eth_api: Eth,
+        chain_spec: Arc<ChainSpec>,
+        blocking_task_guard: BlockingTaskGuard,
+    ) -> Self {
+        Self { inner: Arc::new(EthSimBundleInner { eth_api, chain_spec, blocking_task_guard }) }
+    }
+}
+
+impl<Eth> EthSimBundle<Eth>
+where
+    Eth: EthTransactions + 'static,
+{
+    /// Simulates a MEV-share bundle, including nested bundles, on top of the parent block.
+    ///
+    /// Transactions are executed in order, a transaction that reverts fails the simulation unless
+    /// it's allowed to revert. Refunds requested by the bundle's validity are computed from the
+    /// value generated by the bundle's other items, and are paid from the coinbase to their
+    /// recipients before the next item is executed. The gas of the transfer that pays a refund is
+    /// deducted from the refund.
+    pub async fn sim_bundle(
+        &self,
+        request: SendBundleRequest,
+        overrides: SimBundleOverrides,
+    ) -> EthResult<SimBundleResponse> {
+        let SimBundleOverrides {
+            parent_block,
+            block_number,
+            coinbase,
+            timestamp,
+            gas_limit,
+            base_fee,
+            timeout,
+        } = overrides;
+
+        let timeout = timeout.map(Duration::from_secs).unwrap_or(DEFAULT_SIM_TIMEOUT);
+        if timeout > MAX_SIM_TIMEOUT {
+            return Err(EthApiError::InvalidParams(
+                EthSimBundleError::MaxTimeoutExceeded.to_string(),
+            ))
+        }
+
+        let deadline = Instant::now() + timeout;
+
+        let parent_block = parent_block.unwrap_or(BlockId::latest());
+        let (cfg, mut block_env, at) = self.inner.eth_api.evm_env_at(parent_block).await?;
+        let parent = self
+            .inner
+            .eth_api
+            .block_by_id(at)
+            .await?
+            .ok_or(EthApiError::UnknownBlockNumber)?
+            .header;
+
+        // derive the simulated block from the parent block
+        let state_block = block_env.number.to::<u64>();
+        let block_number = block_number.unwrap_or(state_block + 1);
+        block_env.number = U256::from(block_number);
+        if let Some(coinbase) = coinbase {
+            block_env.coinbase = coinbase;
+        }
+        block_env.timestamp =
+            timestamp.map(U256::from).unwrap_or(block_env.timestamp + U256::from(12));
+        if let Some(gas_limit) = gas_limit {
+            block_env.gas_limit = U256::from(gas_limit);
+        }
+        // the simulated block pays the base fee that follows from the parent block
+        let base_fee = base_fee.or_else(|| {
+            parent.next_block_base_fee(
+                self.inner
+                    .chain_spec
+                    .base_fee_params_at_timestamp(block_env.timestamp.saturating_to()),
+            )
+        });
+        if let Some(base_fee) = base_fee {
+            block_env.basefee = U256::from(base_fee);
+        }
+
+        let pool_transactions = self.fetch_matched_transactions(&request).await?;
+        let bundle = ResolvedBundle::new(request, block_number, &pool_transactions, 0)?;
+
+        let permit = self
+            .inner
+            .blocking_task_guard
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| EthApiError::InternalEthError)?;
+
+        let simulation = self.inner.eth_api.spawn_with_state_at_block(at, move |state| {
+            // the permit is held until the simulation finished, even if it timed out
+            let _permit = permit;
+            let gas_limit = block_env.gas_limit.saturating_to::<u64>();
+            let mut sim = BundleSimulator {
+                coinbase: block_env.coinbase,
+                gas_limit,
+                gas_used: 0,
+                log_index: 0,
+                deadline,
+                timeout,
+            };
+            let env = EnvWithHandlerCfg::new_with_cfg_env(cfg, block_env, TxEnv::default());
+            let db = CacheDB::new(StateProviderDatabase::new(state));
+            let mut evm = revm::Evm::builder().with_db(db).with_env_with_handler_cfg(env).build();
+
+            let res = match sim.simulate(&mut evm, &bundle) {
+                Ok(outcome) => SimBundleResponse {
+                    success: true,
+                    error: None,
+                    state_block,
+                    mev_gas_price: outcome
+                        .profit
+                        .checked_div(U256::from(sim.gas_used))
+                        .unwrap_or_default()
+                        .saturating_to(),
+                    profit: outcome.profit.saturating_to(),
+                    refundable_value: outcome.refundable_value.saturating_to(),
+                    gas_used: sim.gas_used,
+                    logs: Some(outcome.logs),
+                },
+                Err(SimFailure::Invalid(err)) => SimBundleResponse {
+                    success: false,
+                    error: Some(err),
+                    state_block,
+                    mev_gas_price: 0,
+                    profit: 0,
+                    refundable_value: 0,
+                    gas_used: sim.gas_used,
+                    logs: None,
+                },
+                Err(SimFailure::Eth(err)) => return Err(err),
+            };
+
+            Ok(res)
+        });
+
+        tokio::time::timeout(timeout, simulation)
+            .await
+            .map_err(|_| EthApiError::ExecutionTimedOut(timeout))?
+    }
+
+    /// Fetches all transactions that are referenced by hash in the bundle or its nested bundles
+    /// from the pool.
+    async fn fetch_matched_transactions(
+        &self,
+        request: &SendBundleRequest,
+    ) -> EthResult<HashMap<TxHash, TransactionSignedEcRecovered>> {
+        let mut hashes = Vec::new();
+        let mut bundles = vec![request];
+        while let Some(bundle) = bundles.pop() {
+            for item in &bundle.bundle_body {
+                match item {
+                    BundleItem::Hash { hash } => hashes.push(*hash),
+                    BundleItem::Bundle { bundle } => bundles.push(bundle),
+                    BundleItem::Tx { .. } => {}
+                }
+            }
+        }
+
+        let mut transactions = HashMap::with_capacity(hashes.len());
+        for hash in hashes {
+            // only pending transactions can be backrun
+            if let Some(TransactionSource::Pool(tx)) =
+                self.inner.eth_api.transaction_by_hash(hash).await?
+            {
+                transactions.insert(hash, tx);
+            }
+        }
+        Ok(transactions)
+    }
+}
+
+#[async_trait::async_trait]
+impl<Eth> MevApiServer for EthSimBundle<Eth>
+where
+    Eth: EthTransactions + 'static,
+{
+    /// Handler for `mev_sendBundle`
+    async fn send_bundle(&self, _request: SendBundleRequest) -> RpcResult<SendBundleResponse> {
+        Err(EthApiError::Unsupported("mev_sendBundle is not supported, use mev_simBundle").into())
+    }
+
+    /// Handler for `mev_simBundle`
+    async fn sim_bundle(
+        &self,
+        request: SendBundleRequest,
+        sim_overrides: SimBundleOverrides,
+    ) -> RpcResult<SimBundleResponse> {
+        Ok(Self::sim_bundle(self, request, sim_overrides).await?)
+    }
+}
+
+/// Container type for `EthSimBundle` internals
+#[derive(Debug)]
+struct EthSimBundleInner<Eth> {
+    /// Access to commonly used code of the `eth` namespace
+    eth_api: Eth,
+    /// The chain spec, used to derive the base fee of the simulated block
+    chain_spec: Arc<ChainSpec>,
+    // restrict the number of concurrent simulations.
+    blocking_task_guard: BlockingTaskGuard,
+}
+
+impl<Eth> std::fmt::Debug for EthSimBundle<Eth> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EthSimBundle").finish_non_exhaustive()
+    }
+}
+
+impl<Eth> Clone for EthSimBundle<Eth> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+/// A bundle whose transactions are decoded and matched, and whose constraints are validated.
+#[derive(Debug)]
+struct ResolvedBundle {
+    /// The items of the bundle body.
+    items: Vec<ResolvedBundleItem>,
+    /// The refunds requested by the bundle, as `(body index, percent)`.
+    refunds: Vec<(usize, u64)>,
+    /// The recipients of the refunds, the signer of the first transaction of the refunded item
+    /// if not set.
+    refund_config: Option<Vec<RefundConfig>>,
+}
+
+impl ResolvedBundle {
+    /// Resolves the bundle for the given block number.
+    fn new(
+        request: SendBundleRequest,
+        block_number: u64,
+        pool_transactions: &HashMap<TxHash, TransactionSignedEcRecovered>,
+        depth: usize,
+    ) -> EthResult<Self> {
+        let SendBundleRequest { inclusion, bundle_body, validity, .. } = request;
+
+        if depth > MAX_NESTED_BUNDLE_DEPTH {
+            return Err(EthApiError::InvalidParams(EthSimBundleError::MaxDepth.to_string()))
+        }
+        if bundle_body.is_empty() {
+            return Err(EthApiError::InvalidParams(EthSimBundleError::EmptyBundleBody.to_string()))
+        }
+        if bundle_body.len() > MAX_BUNDLE_BODY_SIZE {
+            return Err(EthApiError::InvalidParams(EthSimBundleError::MaxBodySize.to_string()))
+        }
+        validate_inclusion(&inclusion, block_number)?;
+
+        let Validity { refund, refund_config } = validity.unwrap_or_default();
+        let refunds = refund
+            .unwrap_or_default()
+            .into_iter()
+            .map(|refund| (refund.body_idx as usize, refund.percent))
+            .collect::<Vec<_>>();
+        validate_refunds(&refunds, refund_config.as_deref(), bundle_body.len())?;
+
+        let items = bundle_body
+            .into_iter()
+            .map(|item| {
+                Ok(match item {
+                    BundleItem::Hash { hash } => ResolvedBundleItem::Tx {
+                        tx: pool_transactions.get(&hash).cloned().ok_or_else(|| {
+                            EthApiError::InvalidParams(
+                                EthSimBundleError::UnmatchedTransaction(hash).to_string(),
+                            )
+                        })?,
+                        can_revert: false,
+                    },
+                    BundleItem::Tx { tx, can_revert } => {
+                        let (tx, signer) = recover_raw_transaction(tx)?.into_components();
+                        ResolvedBundleItem::Tx {
+                            tx: tx.into_ecrecovered_transaction(signer),
+                            can_revert,
+                        }
+                    }
+                    BundleItem::Bundle { bundle } => ResolvedBundleItem::Bundle(Self::new(
+                        bundle,
+                        block_number,
+                        pool_transactions,
+                        depth + 1,
+                    )?),
+                })
+            })
+            .collect::<EthResult<Vec<_>>>()?;
+
+        Ok(Self { items, refunds, refund_config })
+    }
+}
+
+/// An item of a [`ResolvedBundle`].
+#[derive(Debug)]
+enum ResolvedBundleItem {
+    /// A transaction.
+    Tx {
+        /// The recovered transaction.
+        tx: TransactionSignedEcRecovered,
+        /// Whether the transaction is allowed to revert.
+        can_revert: bool,
+    },
+    /// A nested bundle.
+    Bundle(ResolvedBundle),
+}
+
+impl ResolvedBundleItem {
+    /// Returns the signer of the first transaction of the item.
+    fn signer(&self) -> Address {
+        match self {
+            Self::Tx { tx, .. } => tx.signer(),
+            Self::Bundle(bundle) => bundle.items[0].signer(),
+        }
+    }
+}
+
+/// Ensures the bundle can be included in the given block.
+fn validate_inclusion(inclusion: &Inclusion, block_number: u64) -> EthResult<()> {
+    let max_block = inclusion.max_block_number().unwrap_or(inclusion.block_number());
+    if block_number < inclusion.block_number() || block_number > max_block {
+        return Err(EthApiError::InvalidParams(
+            EthSimBundleError::InvalidInclusion(block_number).to_string(),
+        ))
+    }
+    Ok(())
+}
+
+/// Ensures the refunds refer to items of the body and don't exceed the bundle's value.
+fn validate_refunds(
+    refunds: &[(usize, u64)],
+    refund_config: Option<&[RefundConfig]>,
+    body_len: usize,
+) -> EthResult<()> {
+    let invalid = || EthApiError::InvalidParams(EthSimBundleError::InvalidValidity.to_string());
+
+    let mut total_percent = 0u64;
+    for (body_idx, percent) in refunds {
+        if *body_idx >= body_len {
+            return Err(invalid())
+        }
+        total_percent = total_percent.saturating_add(*percent);
+    }
+    if total_percent > 100 {
+        return Err(invalid())
+    }
+
+    if let Some(config) = refund_config {
+        let total_percent =
+            config.iter().fold(0u64, |total, config| total.saturating_add(config.percent));
+        if total_percent > 100 {
+            return Err(invalid())
+        }
+    }
+    Ok(())
+}
+
+/// The outcome of a successfully simulated bundle.
+#[derive(Debug, Default)]
+struct BundleOutcome {
+    /// Value the bundle paid to the coinbase, minus the refunds of the bundle.
+    profit: U256,
+    /// Value paid to the coinbase by the items that don't receive a refund.
+    refundable_value: U256,
+    /// Logs of the bundle items.
+    logs: Vec<SimBundleLogs>,
+}
+
+/// Reasons a simulation can fail.
+#[derive(Debug)]
+enum SimFailure {
+    /// The bundle can't be included, this is reported in the response.
+    Invalid(String),
+    /// The simulation could not be performed.
+    Eth(EthApiError),
+}
+
+impl From<EthApiError> for SimFailure {
+    fn from(err: EthApiError) -> Self {
+        Self::Eth(err)
+    }
+}
+
+/// Executes bundles against the simulated block.
+#[derive(Debug)]
+struct BundleSimulator {
+    /// Beneficiary of the simulated block.
+    coinbase: Address,
+    /// Gas limit of the simulated block.
+    gas_limit: u64,
+    /// Gas used by all executed transactions.
+    gas_used: u64,
+    /// Index of the next log in the simulated block.
+    log_index: u64,
+    /// The simulation is aborted once the deadline passed.
+    deadline: Instant,
+    /// The timeout of the simulation, the deadline relative to its start.
+    timeout: Duration,
+}
+
+impl BundleSimulator {
+    /// Executes all items of the bundle in order and commits their state changes.
+    fn simulate<EXT, DB>(
+        &mut self,
+        evm: &mut revm::Evm<'_, EXT, DB>,
+        bundle: &ResolvedBundle,
+    ) -> Result<BundleOutcome, SimFailure>
+    where
+        DB: Database + DatabaseCommit,
+        DB::Error: Into<EthApiError>,
+    {
+        let mut item_values = Vec::with_capacity(bundle.items.len());
+        let mut logs = Vec::with_capacity(bundle.items.len());
+
+        for item in &bundle.items {
+            // the simulation runs on a blocking task, which isn't cancelled by the timeout
+            if Instant::now() >= self.deadline {
+                return Err(SimFailure::Eth(EthApiError::ExecutionTimedOut(self.timeout)))
+            }
+
+            match item {
+                ResolvedBundleItem::Tx { tx, can_revert } => {
+                    let balance_before = self.coinbase_balance(evm)?;
+
+                    tx.try_fill_tx_env(evm.tx_mut())?;
+                    let ResultAndState { result, state } = match evm.transact() {
+                        Ok(res) => res,
+                        Err(EVMError::Database(err)) => return Err(SimFailure::Eth(err.into())),
+                        Err(err) => {
+                            return Err(SimFailure::Invalid(format!(
+                                "transaction {} is invalid: {}",
+                                tx.hash(),
+                                EthApiError::from(err)
+                            )))
+                        }
+                    };
+
+                    if !result.is_success() && !can_revert {
+                        return Err(SimFailure::Invalid(format!(
+                            "transaction {} reverted",
+                            tx.hash()
+                        )))
+                    }
+
+                    self.gas_used += result.gas_used();
+                    if self.gas_used > self.gas_limit {
+                        return Err(SimFailure::Invalid("block gas limit exceeded".to_string()))
+                    }
+
+                    let tx_logs = result
+                        .into_logs()
+                        .into_iter()
+                        .map(|log| {
+                            let log = Log {
+                                inner: log,
+                                block_hash: None,
+                                block_number: None,
+                                block_timestamp: None,
+                                transaction_hash: Some(tx.hash()),
+                                transaction_index: None,
+                                log_index: Some(self.log_index),
+                                removed: false,
+                            };
+                            self.log_index += 1;
+                            log
+                        })
+                        .collect();
+
+                    evm.context.evm.db.commit(state);
+
+                    let balance_after = self.coinbase_balance(evm)?;
+                    item_values.push(balance_after.saturating_sub(balance_before));
+                    logs.push(SimBundleLogs { tx_logs: Some(tx_logs), bundle_logs: None });
+                }
+                ResolvedBundleItem::Bundle(nested) => {
+                    let outcome = self.simulate(evm, nested)?;
+                    item_values.push(outcome.profit);
+                    logs.push(SimBundleLogs { tx_logs: None, bundle_logs: Some(outcome.logs) });
+                }
+            }
+        }
+
+        let total_value = item_values.iter().fold(U256::ZERO, |total, value| total + value);
+        let refundable_value = item_values
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| !bundle.refunds.iter().any(|(body_idx, _)| body_idx == idx))
+            .fold(U256::ZERO, |total, (_, value)| total + value);
+
+        // every payout is a transfer from the coinbase, whose gas is paid from the refund
+        let transfer_fee = evm.block().basefee * U256::from(REFUND_TRANSFER_GAS);
+        let mut payouts = Vec::new();
+        for (body_idx, percent) in &bundle.refunds {
+            let refund = refundable_value * U256::from(*percent) / U256::from(100);
+            match &bundle.refund_config {
+                Some(config) => payouts.extend(config.iter().map(|config| {
+                    (config.address, refund * U256::from(config.percent) / U256::from(100))
+                })),
+                None => payouts.push((bundle.items[*body_idx].signer(), refund)),
+            }
+        }
+        let total_refund = self.pay_refunds(evm, &payouts, transfer_fee)?;
+
+        Ok(BundleOutcome {
+            profit: total_value.saturating_sub(total_refund),
+            refundable_value,
+            logs,
+        })
+    }
+
+    /// Pays the refunds from the coinbase to their recipients, commits the balance changes and
+    /// returns the total refund.
+    ///
+    /// Each recipient receives its refund minus the fee of the transfer that pays it, the
+    /// simulation fails if a refund doesn't cover that fee.
+    fn pay_refunds<EXT, DB>(
+        &self,
+        evm: &mut revm::Evm<'_, EXT, DB>,
+        payouts: &[(Address, U256)],
+        transfer_fee: U256,
+    ) -> Result<U256, SimFailure>
+    where
+        DB: Database + DatabaseCommit,
+        DB::Error: Into<EthApiError>,
+    {
+        let total_refund = payouts.iter().fold(U256::ZERO, |total, (_, refund)| total + refund);
+        if total_refund.is_zero() {
+            return Ok(total_refund)
+        }
+
+        let db = &mut evm.context.evm.db;
+        let mut accounts = HashMap::new();
+        for address in std::iter::once(self.coinbase).chain(payouts.iter().map(|(to, _)| *to)) {
+            if let Entry::Vacant(entry) = accounts.entry(address) {
+                entry.insert(
+                    db.basic(address)
+                        .map_err(|err| SimFailure::Eth(err.into()))?
+                        .unwrap_or_default(),
+                );
+            }
+        }
+
+        let coinbase = accounts.get_mut(&self.coinbase).expect("coinbase is loaded");
+        coinbase.balance = coinbase.balance.saturating_sub(total_refund);
+        for (to, refund) in payouts {
+            let Some(refund) = refund.checked_sub(transfer_fee) else {
+                return Err(SimFailure::Invalid(format!(
+                    "refund to {to} doesn't cover the transfer fee of {transfer_fee}"
+                )))
+            };
+            let recipient = accounts.get_mut(to).expect("recipient is loaded");
+            recipient.balance += refund;
+        }
+
+        db.commit(
+            accounts
+                .into_iter()
+                .map(|(address, info)| {
+                    let mut account = Account::from(info);
+                    account.mark_touch();
+                    (address, account)
+                })
+                .collect(),
+        );
+        Ok(total_refund)
+    }
+
+    /// Returns the current balance of the coinbase.
+    fn coinbase_balance<EXT, DB>(&self, evm: &mut revm::Evm<'_, EXT, DB>) -> EthResult<U256>
+    where
+        DB: Database,
+        DB::Error: Into<EthApiError>,
+    {
+        Ok(evm
+            .context
+            .evm
+            .db
+            .basic(self.coinbase)
+            .map_err(Into::into)?
+            .map(|acc| acc.balance)
+            .unwrap_or_default())
+    }
+}
+
+/// [`EthSimBundle`] specific errors.
+#[derive(Debug, thiserror::Error)]
+pub enum EthSimBundleError {
+    /// Thrown if a bundle body is empty.
+    #[error("bundle body is empty")]
+    EmptyBundleBody,
+    /// Thrown if bundles are nested deeper than [`MAX_NESTED_BUNDLE_DEPTH`].
+    #[error("bundle nesting exceeds the maximum depth of {MAX_NESTED_BUNDLE_DEPTH}")]
+    MaxDepth,
+    /// Thrown if a bundle body has more than [`MAX_BUNDLE_BODY_SIZE`] items.
+    #[error("bundle body exceeds the maximum size of {MAX_BUNDLE_BODY_SIZE}")]
+    MaxBodySize,
+    /// Thrown if the requested timeout exceeds [`MAX_SIM_TIMEOUT`].
+    #[error("timeout exceeds the maximum of {MAX_SIM_TIMEOUT:?}")]
+    MaxTimeoutExceeded,
+    /// Thrown if the simulated block is outside the bundle's inclusion range.
+    #[error("bundle can't be included in block {0}")]
+    InvalidInclusion(u64),
+    /// Thrown if the refunds or the refund config of a bundle are invalid.
+    #[error("invalid bundle validity")]
+    InvalidValidity,
+    /// Thrown if a transaction referenced by hash is not pending.
+    #[error("unmatched transaction {0}")]
+    UnmatchedTransaction(TxHash),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_errors::ProviderError;
+    use reth_primitives::{constants::ETH_TO_WEI, Transaction, TxKind, TxLegacy};
+    use reth_testing_utils::generators::{self, sign_tx_with_random_key_pair};
+    use revm::{db::EmptyDBTyped, primitives::AccountInfo};
+
+    /// Returns a transfer of a random sender, with the given gas price.
+    fn transfer(gas_price: u128) -> TransactionSignedEcRecovered {
+        let tx = Transaction::Legacy(TxLegacy {
+            chain_id: Some(1),
+            gas_price,
+            gas_limit: 21_000,
+            to: TxKind::Call(Address::random()),
+            value: U256::from(1),
+            ..Default::default()
+        });
+        sign_tx_with_random_key_pair(&mut generators::rng(), tx).into_ecrecovered().unwrap()
+    }
+
+    fn simulator(coinbase: Address, deadline: Instant) -> BundleSimulator {
+        BundleSimulator {
+            coinbase,
+            gas_limit: 30_000_000,
+            gas_used: 0,
+            log_index: 0,
+            deadline,
+            timeout: DEFAULT_SIM_TIMEOUT,
+        }
+    }
+
+    #[test]
+    fn validates_inclusion_range() {
+        let inclusion = Inclusion { block: 10, max_block: Some(12) };
+        assert!(validate_inclusion(&inclusion, 9).is_err());
+        assert!(validate_inclusion(&inclusion, 10).is_ok());
+        assert!(validate_inclusion(&inclusion, 12).is_ok());
+        assert!(validate_inclusion(&inclusion, 13).is_err());
+
+        // without a max block the bundle is only valid for a single block
+        assert!(validate_inclusion(&Inclusion::at_block(10), 11).is_err());
+    }
+
+    #[test]
+    fn validates_refunds() {
+        assert!(validate_refunds(&[(0, 50), (1, 50)], None, 2).is_ok());
+        assert!(validate_refunds(&[(0, 60), (1, 50)], None, 2).is_err());
+        assert!(validate_refunds(&[(2, 10)], None, 2).is_err());
+
+        let config = vec![
+            RefundConfig { address: Address::ZERO, percent: 70 },
+            RefundConfig { address: Address::with_last_byte(1), percent: 40 },
+        ];
+        assert!(validate_refunds(&[], Some(&config), 1).is_err());
+    }
+
+    #[test]
+    fn pays_refunds_to_refunded_signer() {
+        let coinbase = Address::random();
+        let user = transfer(1_000_000_000);
+        let searcher = transfer(2_000_000_000);
+
+        let mut db = CacheDB::new(EmptyDBTyped::<ProviderError>::new());
+        for tx in [&user, &searcher] {
+            db.insert_account_info(
+                tx.signer(),
+                AccountInfo { balance: U256::from(ETH_TO_WEI), ..Default::default() },
+            );
+        }
+        let mut evm = revm::Evm::builder()
+            .with_db(db)
+            .modify_block_env(|env| env.coinbase = coinbase)
+            .build();
+
+        // the user is refunded half of the value of the searcher's backrun
+        let bundle = ResolvedBundle {
+            items: vec![
+                ResolvedBundleItem::Tx { tx: user.clone(), can_revert: false },
+                ResolvedBundleItem::Tx { tx: searcher, can_revert: false },
+            ],
+            refunds: vec![(0, 50)],
+            refund_config: None,
+        };
+        let mut sim = simulator(coinbase, Instant::now() + DEFAULT_SIM_TIMEOUT);
+        let outcome = sim.simulate(&mut evm, &bundle).unwrap();
+
+        let gwei = U256::from(1_000_000_000);
+        assert_eq!(outcome.refundable_value, U256::from(42_000) * gwei);
+        assert_eq!(outcome.profit, U256::from(42_000) * gwei);
+
+        let mut balance = |address| evm.context.evm.db.basic(address).unwrap().unwrap().balance;
+        assert_eq!(balance(coinbase), U256::from(42_000) * gwei);
+        // the refund covers the user's fee
+        assert_eq!(balance(user.signer()), U256::from(ETH_TO_WEI - 1));
+    }
+
+    #[test]
+    fn deducts_refund_transfer_fee() {
+        let coinbase = Address::random();
+        let user = transfer(1_000_000_000);
+        let searcher = transfer(2_000_000_000);
+
+        let mut db = CacheDB::new(EmptyDBTyped::<ProviderError>::new());
+        for tx in [&user, &searcher] {
+            db.insert_account_info(
+                tx.signer(),
+                AccountInfo { balance: U256::from(ETH_TO_WEI), ..Default::default() },
+            );
+        }
+        let mut evm = revm::Evm::builder()
+            .with_db(db)
+            .modify_block_env(|env| {
+                env.coinbase = coinbase;
+                env.basefee = U256::from(500_000_000);
+            })
+            .build();
+
+        let bundle = ResolvedBundle {
+            items: vec![
+                ResolvedBundleItem::Tx { tx: user.clone(), can_revert: false },
+                ResolvedBundleItem::Tx { tx: searcher, can_revert: false },
+            ],
+            refunds: vec![(0, 50)],
+            refund_config: None,
+        };
+        let mut sim = simulator(coinbase, Instant::now() + DEFAULT_SIM_TIMEOUT);
+        let outcome = sim.simulate(&mut evm, &bundle).unwrap();
+
+        // the searcher tips 1.5 gwei, half of it is refunded to the user
+        let gwei = U256::from(1_000_000_000);
+        assert_eq!(outcome.refundable_value, U256::from(31_500) * gwei);
+        assert_eq!(outcome.profit, U256::from(42_000 - 15_750) * gwei);
+
+        let mut balance = |address| evm.context.evm.db.basic(address).unwrap().unwrap().balance;
+        assert_eq!(balance(coinbase), U256::from(42_000 - 15_750) * gwei);
+        // the refund is reduced by the fee of the transfer that pays it
+        assert_eq!(
+            balance(user.signer()),
+            U256::from(ETH_TO_WEI - 1) - U256::from(21_000 - 15_750 + 10_500) * gwei
+        );
+
+        // a refund that doesn't cover the transfer fee fails the simulation
+        let bundle = ResolvedBundle {
+            items: vec![
+                ResolvedBundleItem::Tx { tx: transfer(1_000_000_000), can_revert: false },
+                ResolvedBundleItem::Tx { tx: transfer(1_000_000_000), can_revert: false },
+            ],
+            refunds: vec![(0, 10)],
+            refund_config: None,
+        };
+        for item in &bundle.items {
+            evm.context.evm.db.insert_account_info(
+                item.signer(),
+                AccountInfo { balance: U256::from(ETH_TO_WEI), ..Default::default() },
+            );
+        }
+        assert!(matches!(sim.simulate(&mut evm, &bundle), Err(SimFailure::Invalid(_))));
+    }
+
+    #[test]
+    fn aborts_after_deadline() {
+        let mut evm = revm::Evm::builder()
+            .with_db(CacheDB::new(EmptyDBTyped::<ProviderError>::new()))
+            .build();
+        let bundle = ResolvedBundle {
+            items: vec![ResolvedBundleItem::Tx { tx: transfer(1), can_revert: false }],
+            refunds: Vec::new(),
+            refund_config: None,
+        };
+
+        let mut sim = simulator(Address::random(), Instant::now());
+        assert!(matches!(
+            sim.simulate(&mut evm, &bundle),
+            Err(SimFailure::Eth(EthApiError::ExecutionTimedOut(_)))
+        ));
+    }
+}
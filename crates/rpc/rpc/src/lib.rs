@@ -34,6 +34,7 @@ use tower as _;
 
 mod admin;
 mod debug;
+mod dev;
 mod engine;
 pub mod eth;
 mod net;
@@ -42,9 +43,11 @@ mod reth;
 mod rpc;
 mod trace;
 mod txpool;
+mod validation;
 mod web3;
-pub use admin::AdminApi;
+pub use admin::{AdminApi, AdminBackupApi};
 pub use debug::DebugApi;
+pub use dev::{DevApi, UNSUPPORTED_DEV_METHODS};
 pub use engine::{EngineApi, EngineEthApi};
 pub use eth::{EthApi, EthApiSpec, EthFilter, EthPubSub, EthSubscriptionIdProvider};
 pub use net::NetApi;
@@ -53,5 +56,6 @@ pub use reth::RethApi;
 pub use rpc::RPCApi;
 pub use trace::TraceApi;
 pub use txpool::TxPoolApi;
+pub use validation::{ValidationApi, ValidationApiError};
 pub use web3::Web3Api;
 pub mod result;
@@ -1,39 +1,228 @@
 use alloy_primitives::Bytes;
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
-use revm_inspectors::transfer::{TransferInspector, TransferKind};
+use revm_inspectors::{
+    tracing::{types::CallTraceNode, TracingInspectorConfig},
+    transfer::{TransferInspector, TransferKind},
+};
 use revm_primitives::ExecutionResult;
 
-use reth_primitives::{Address, BlockId, BlockNumberOrTag, TxHash, B256};
+use reth_primitives::{Address, BlockId, BlockNumber, BlockNumberOrTag, TxHash, B256};
+use reth_provider::{
+    AccountHistoryReader, BlockReaderIdExt, StageCheckpointReader, StateProviderFactory,
+};
 use reth_rpc_api::{EthApiServer, OtterscanServer};
 use reth_rpc_types::{
     trace::otterscan::{
         BlockDetails, ContractCreator, InternalOperation, OperationType, OtsBlockTransactions,
         OtsReceipt, OtsTransactionReceipt, TraceEntry, TransactionsWithReceipts,
     },
-    BlockTransactions, Transaction,
+    AnyTransactionReceipt, BlockTransactions, Transaction,
 };
+use reth_stages_types::StageId;
 
-use crate::{eth::EthTransactions, result::internal_rpc_err};
+use crate::{
+    eth::{
+        error::{EthApiError, EthResult},
+        EthTransactions,
+    },
+    result::internal_rpc_err,
+};
+use std::ops::RangeInclusive;
 
 const API_LEVEL: u64 = 8;
 
 /// Otterscan API.
 #[derive(Debug)]
-pub struct OtterscanApi<Eth> {
+pub struct OtterscanApi<Provider, Eth> {
+    provider: Provider,
     eth: Eth,
 }
 
-impl<Eth> OtterscanApi<Eth> {
+impl<Provider, Eth> OtterscanApi<Provider, Eth> {
     /// Creates a new instance of `Otterscan`.
-    pub const fn new(eth: Eth) -> Self {
-        Self { eth }
+    pub const fn new(provider: Provider, eth: Eth) -> Self {
+        Self { provider, eth }
+    }
+}
+
+impl<Provider, Eth> OtterscanApi<Provider, Eth>
+where
+    Provider: BlockReaderIdExt
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + StateProviderFactory
+        + 'static,
+    Eth: EthApiServer + EthTransactions,
+{
+    /// Resolves the block number of an `ots_searchTransactions*` request.
+    ///
+    /// Otterscan uses block `0` as a sentinel for "no block", so it is returned as `None`.
+    fn search_block_number(&self, block_number: BlockNumberOrTag) -> RpcResult<Option<u64>> {
+        let block_number = self
+            .provider
+            .convert_block_number(block_number)
+            .map_err(EthApiError::from)?
+            .ok_or(EthApiError::UnknownBlockNumber)?;
+        Ok((block_number != 0).then_some(block_number))
+    }
+
+    /// Returns the blocks in the given range that contain transactions for the given address, in
+    /// ascending order.
+    ///
+    /// Blocks are looked up in the address transactions index, blocks above its checkpoint are not
+    /// searched. Returns [`AddressBlocks::Changed`] if the index is not enabled.
+    fn address_transaction_blocks(
+        &self,
+        address: Address,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> EthResult<AddressBlocks> {
+        let Some(indexed) =
+            self.provider.get_stage_checkpoint(StageId::IndexAddressTransactions)?
+        else {
+            return Ok(AddressBlocks::Changed(
+                self.provider.account_history_blocks(address, block_range)?,
+            ))
+        };
+
+        let (from_block, to_block) =
+            (*block_range.start(), (*block_range.end()).min(indexed.block_number));
+        if from_block > to_block {
+            return Ok(AddressBlocks::Indexed(Vec::new()))
+        }
+
+        let from_tx = self
+            .provider
+            .block_body_indices(from_block)?
+            .ok_or(EthApiError::UnknownBlockNumber)?
+            .first_tx_num();
+        let to_tx = self
+            .provider
+            .block_body_indices(to_block)?
+            .ok_or(EthApiError::UnknownBlockNumber)?
+            .next_tx_num();
+        if from_tx == to_tx {
+            return Ok(AddressBlocks::Indexed(Vec::new()))
+        }
+
+        let mut blocks = Vec::new();
+        for tx_number in self.provider.address_transactions(address, from_tx..=to_tx - 1)? {
+            let block_number = self
+                .provider
+                .transaction_block(tx_number)?
+                .ok_or(EthApiError::TransactionNotFound)?;
+            if blocks.last() != Some(&block_number) {
+                blocks.push(block_number);
+            }
+        }
+        Ok(AddressBlocks::Indexed(blocks))
+    }
+
+    /// Returns `true` if the account has code in the state after the given block is executed.
+    fn has_code_after(&self, address: Address, block_number: BlockNumber) -> EthResult<bool> {
+        let state = self.provider.history_by_block_number(block_number)?;
+        Ok(state.basic_account(address)?.is_some_and(|account| account.has_bytecode()))
+    }
+
+    /// Returns the transactions of the given block that were sent by, sent to, or created the
+    /// given address, alongside their receipts, in descending order.
+    ///
+    /// If `trace` is set, the transactions are found by tracing the block, which includes the
+    /// transactions that only touch the address in internal calls.
+    async fn block_transactions_for_address(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        trace: bool,
+    ) -> RpcResult<(Vec<Transaction>, Vec<OtsTransactionReceipt>)> {
+        let block = self.eth.block_by_number(block_number.into(), true);
+        let receipts = self.eth.block_receipts(BlockId::number(block_number));
+        let (block, receipts) = futures::try_join!(block, receipts)?;
+
+        let touched = if trace {
+            self.eth
+                .trace_block_with(
+                    BlockId::number(block_number),
+                    TracingInspectorConfig::default_parity(),
+                    move |_tx_info, inspector, _, _, _| {
+                        Ok(inspector.into_traces().into_nodes().iter().any(
+                            |CallTraceNode { trace, .. }| {
+                                trace.caller == address || trace.address == address
+                            },
+                        ))
+                    },
+                )
+                .await?
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        let block = block.ok_or_else(|| internal_rpc_err("block not found"))?;
+        let receipts = receipts.ok_or_else(|| internal_rpc_err("receipts not found"))?;
+
+        let timestamp = Some(block.header.timestamp);
+        let BlockTransactions::Full(transactions) = block.inner.transactions else {
+            return Err(internal_rpc_err("block is not full"));
+        };
+
+        let (transactions, receipts): (Vec<_>, Vec<_>) = transactions
+            .into_iter()
+            .zip(receipts)
+            .enumerate()
+            .filter(|(idx, (tx, receipt))| {
+                tx.from == address ||
+                    tx.to == Some(address) ||
+                    receipt.inner.contract_address == Some(address) ||
+                    touched.get(*idx).copied().unwrap_or_default()
+            })
+            .map(|(_, (tx, receipt))| (tx, ots_receipt(receipt, timestamp)))
+            .rev()
+            .unzip();
+
+        Ok((transactions, receipts))
+    }
+
+    /// Collects the transactions for the given address from the given blocks, in the order of the
+    /// blocks, until at least `page_size` transactions are found.
+    ///
+    /// Returns `true` alongside the per-block transactions if all blocks were searched.
+    async fn collect_transactions_for_address(
+        &self,
+        blocks: impl IntoIterator<Item = BlockNumber>,
+        address: Address,
+        trace: bool,
+        page_size: usize,
+    ) -> RpcResult<(Vec<(Vec<Transaction>, Vec<OtsTransactionReceipt>)>, bool)> {
+        let mut collected = Vec::new();
+        let mut total = 0;
+
+        let mut blocks = blocks.into_iter().peekable();
+        while let Some(block_number) = blocks.next() {
+            let (txs, receipts) =
+                self.block_transactions_for_address(block_number, address, trace).await?;
+            total += txs.len();
+            collected.push((txs, receipts));
+
+            // Blocks are always returned in full, so the page may exceed the page size.
+            if total >= page_size {
+                break
+            }
+        }
+
+        let exhausted = blocks.peek().is_none();
+        Ok((collected, exhausted))
     }
 }
 
 #[async_trait]
-impl<Eth> OtterscanServer for OtterscanApi<Eth>
+impl<Provider, Eth> OtterscanServer for OtterscanApi<Provider, Eth>
 where
+    Provider: BlockReaderIdExt
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + StateProviderFactory
+        + 'static,
     Eth: EthApiServer + EthTransactions,
 {
     /// Handler for `ots_hasCode`
@@ -90,8 +279,34 @@ where
     }
 
     /// Handler for `ots_traceTransaction`
-    async fn trace_transaction(&self, _tx_hash: TxHash) -> RpcResult<TraceEntry> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn trace_transaction(&self, tx_hash: TxHash) -> RpcResult<Option<Vec<TraceEntry>>> {
+        let traces = self
+            .eth
+            .spawn_trace_transaction_in_block(
+                tx_hash,
+                TracingInspectorConfig::default_parity(),
+                move |_tx_info, inspector, _, _| Ok(inspector.into_traces().into_nodes()),
+            )
+            .await?
+            .map(|traces| {
+                traces
+                    .into_iter()
+                    .map(|CallTraceNode { trace, .. }| TraceEntry {
+                        r#type: if trace.is_selfdestruct() {
+                            "SELFDESTRUCT".to_string()
+                        } else {
+                            trace.kind.to_string()
+                        },
+                        depth: trace.depth as u32,
+                        from: trace.caller,
+                        to: trace.address,
+                        value: trace.value,
+                        input: trace.data,
+                        output: trace.output,
+                    })
+                    .collect::<Vec<_>>()
+            });
+        Ok(traces)
     }
 
     /// Handler for `ots_getBlockDetails`
@@ -156,44 +371,58 @@ where
         let timestamp = Some(block.header.timestamp);
         let receipts = receipts
             .drain(page_start..page_end)
-            .map(|receipt| {
-                let receipt = receipt.inner.map_inner(|receipt| OtsReceipt {
-                    status: receipt
-                        .inner
-                        .receipt
-                        .status
-                        .as_eip658()
-                        .expect("ETH API returned pre-EIP-658 status"),
-                    cumulative_gas_used: receipt.inner.receipt.cumulative_gas_used as u64,
-                    logs: None,
-                    logs_bloom: None,
-                    r#type: receipt.r#type,
-                });
-
-                OtsTransactionReceipt { receipt, timestamp }
-            })
+            .map(|receipt| ots_receipt(receipt, timestamp))
             .collect();
         Ok(OtsBlockTransactions { fullblock: block.inner.into(), receipts })
     }
 
     /// Handler for `searchTransactionsBefore`
+    ///
+    /// Uses the address transactions index if enabled, see `IndexAddressTransactions` stage.
+    /// Otherwise the blocks in which the account changed are traced, which misses transactions
+    /// that call the address without changing its account.
     async fn search_transactions_before(
         &self,
-        _address: Address,
-        _block_number: BlockNumberOrTag,
-        _page_size: usize,
+        address: Address,
+        block_number: BlockNumberOrTag,
+        page_size: usize,
     ) -> RpcResult<TransactionsWithReceipts> {
-        Err(internal_rpc_err("unimplemented"))
+        // Block `0` means the search starts at the tip, which makes this the first page.
+        let (end, first_page) = match self.search_block_number(block_number)? {
+            Some(block_number) => (block_number - 1, false),
+            None => (self.provider.best_block_number().map_err(EthApiError::from)?, true),
+        };
+
+        let (blocks, trace) = self.address_transaction_blocks(address, 0..=end)?.into_parts();
+        let (collected, exhausted) = self
+            .collect_transactions_for_address(blocks.into_iter().rev(), address, trace, page_size)
+            .await?;
+
+        Ok(transactions_with_receipts(collected, first_page, exhausted))
     }
 
     /// Handler for `searchTransactionsAfter`
+    ///
+    /// See [`Self::search_transactions_before`] for the blocks that are searched.
     async fn search_transactions_after(
         &self,
-        _address: Address,
-        _block_number: BlockNumberOrTag,
-        _page_size: usize,
+        address: Address,
+        block_number: BlockNumberOrTag,
+        page_size: usize,
     ) -> RpcResult<TransactionsWithReceipts> {
-        Err(internal_rpc_err("unimplemented"))
+        // Block `0` means the search starts at genesis, which makes this the last page.
+        let (start, last_page) = match self.search_block_number(block_number)? {
+            Some(block_number) => (block_number + 1, false),
+            None => (0, true),
+        };
+
+        let best = self.provider.best_block_number().map_err(EthApiError::from)?;
+        let (blocks, trace) = self.address_transaction_blocks(address, start..=best)?.into_parts();
+        let (collected, exhausted) =
+            self.collect_transactions_for_address(blocks, address, trace, page_size).await?;
+
+        // Otterscan expects transactions in descending order.
+        Ok(transactions_with_receipts(collected.into_iter().rev(), exhausted, last_page))
     }
 
     /// Handler for `getTransactionBySenderAndNonce`
@@ -206,7 +435,376 @@ where
     }
 
     /// Handler for `getContractCreator`
-    async fn get_contract_creator(&self, _address: Address) -> RpcResult<Option<ContractCreator>> {
-        Err(internal_rpc_err("unimplemented"))
+    async fn get_contract_creator(&self, address: Address) -> RpcResult<Option<ContractCreator>> {
+        if !self.has_code(address, None).await? {
+            return Ok(None)
+        }
+
+        // The contract was deployed in the first block of the account history after which the
+        // account has code, which is found with a binary search over the history.
+        let best = self.provider.best_block_number().map_err(EthApiError::from)?;
+        let blocks =
+            self.provider.account_history_blocks(address, 0..=best).map_err(EthApiError::from)?;
+        let (mut low, mut high) = (0, blocks.len());
+        while low < high {
+            let mid = (low + high) / 2;
+            if self.has_code_after(address, blocks[mid])? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        // Genesis contracts or contracts whose history was pruned have no known creator.
+        let Some(&creation_block) = blocks.get(low) else { return Ok(None) };
+        if creation_block == 0 || self.has_code_after(address, creation_block - 1)? {
+            return Ok(None)
+        }
+
+        let creators = self
+            .eth
+            .trace_block_with(
+                BlockId::number(creation_block),
+                TracingInspectorConfig::default_parity(),
+                move |tx_info, inspector, _, _, _| {
+                    Ok(inspector.into_traces().into_nodes().into_iter().find_map(
+                        |CallTraceNode { trace, .. }| {
+                            (trace.kind.is_any_create() && trace.address == address).then(|| {
+                                ContractCreator {
+                                    hash: tx_info.hash.unwrap_or_default(),
+                                    creator: trace.caller,
+                                }
+                            })
+                        },
+                    ))
+                },
+            )
+            .await?
+            .unwrap_or_default();
+
+        Ok(creators.into_iter().flatten().last())
+    }
+}
+
+/// The blocks that may contain transactions for an address.
+#[derive(Debug)]
+enum AddressBlocks {
+    /// Blocks from the address transactions index, which contain transactions sent by, sent to,
+    /// or creating the address.
+    Indexed(Vec<BlockNumber>),
+    /// Blocks in which the account changed, from the account history index. The transactions
+    /// for the address are found by tracing the blocks.
+    Changed(Vec<BlockNumber>),
+}
+
+impl AddressBlocks {
+    /// Returns the blocks and whether they have to be traced.
+    fn into_parts(self) -> (Vec<BlockNumber>, bool) {
+        match self {
+            Self::Indexed(blocks) => (blocks, false),
+            Self::Changed(blocks) => (blocks, true),
+        }
+    }
+}
+
+/// Flattens per-block transactions and receipts into a [`TransactionsWithReceipts`] page.
+fn transactions_with_receipts(
+    blocks: impl IntoIterator<Item = (Vec<Transaction>, Vec<OtsTransactionReceipt>)>,
+    first_page: bool,
+    last_page: bool,
+) -> TransactionsWithReceipts {
+    let (txs, receipts): (Vec<_>, Vec<_>) = blocks.into_iter().unzip();
+    TransactionsWithReceipts {
+        txs: txs.into_iter().flatten().collect(),
+        receipts: receipts.into_iter().flatten().collect(),
+        first_page,
+        last_page,
+    }
+}
+
+/// Converts a receipt into the [`OtsTransactionReceipt`] format, which omits logs and bloom.
+fn ots_receipt(receipt: AnyTransactionReceipt, timestamp: Option<u64>) -> OtsTransactionReceipt {
+    let receipt = receipt.inner.map_inner(|receipt| OtsReceipt {
+        status: receipt
+            .inner
+            .receipt
+            .status
+            .as_eip658()
+            .expect("ETH API returned pre-EIP-658 status"),
+        cumulative_gas_used: receipt.inner.receipt.cumulative_gas_used as u64,
+        logs: None,
+        logs_bloom: None,
+        r#type: receipt.r#type,
+    });
+
+    OtsTransactionReceipt { receipt, timestamp }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eth::{
+            cache::EthStateCache, gas_oracle::GasPriceOracle, FeeHistoryCache,
+            FeeHistoryCacheConfig,
+        },
+        EthApi,
+    };
+    use reth_blockchain_tree::noop::NoopBlockchainTree;
+    use reth_chainspec::MAINNET;
+    use reth_db::{tables, test_utils::TempDatabase, DatabaseEnv};
+    use reth_db_api::{models::AccountBeforeTx, transaction::DbTxMut};
+    use reth_evm_ethereum::EthEvmConfig;
+    use reth_network_api::noop::NoopNetwork;
+    use reth_primitives::{
+        constants::ETHEREUM_BLOCK_GAS_LIMIT, proofs, Account, Bytecode, Header, Receipt,
+        SealedBlock, Transaction as PrimitiveTransaction, TransactionSigned, TxKind, TxLegacy,
+        TxType, U256,
+    };
+    use reth_provider::{
+        providers::BlockchainProvider, test_utils::create_test_provider_factory_with_chain_spec,
+        BlockWriter, HistoryWriter, ProviderFactory, StageCheckpointWriter,
+    };
+    use reth_rpc_server_types::constants::DEFAULT_ETH_PROOF_WINDOW;
+    use reth_stages_types::StageCheckpoint;
+    use reth_tasks::pool::BlockingTaskPool;
+    use reth_testing_utils::generators::{
+        self, random_signed_tx, random_tx, sign_tx_with_random_key_pair,
+    };
+    use reth_transaction_pool::test_utils::{testing_pool, TestPool};
+    use std::{collections::BTreeMap, sync::Arc};
+
+    type TestProvider = BlockchainProvider<Arc<TempDatabase<DatabaseEnv>>>;
+    type TestOtterscanApi =
+        OtterscanApi<TestProvider, EthApi<TestProvider, TestPool, NoopNetwork, EthEvmConfig>>;
+
+    /// Returns a signed transaction to the given address.
+    fn tx_to<R: generators::Rng>(rng: &mut R, to: Address) -> TransactionSigned {
+        let mut transaction = random_tx(rng);
+        if let PrimitiveTransaction::Legacy(tx) = &mut transaction {
+            tx.to = TxKind::Call(to);
+        }
+        sign_tx_with_random_key_pair(rng, transaction)
+    }
+
+    /// Returns a chain of blocks with the given transactions, starting at genesis.
+    fn chain(bodies: Vec<Vec<TransactionSigned>>) -> Vec<SealedBlock> {
+        let mut blocks = Vec::<SealedBlock>::with_capacity(bodies.len());
+        for (number, body) in bodies.into_iter().enumerate() {
+            let header = Header {
+                number: number as BlockNumber,
+                parent_hash: blocks.last().map(|block| block.hash()).unwrap_or_default(),
+                gas_limit: ETHEREUM_BLOCK_GAS_LIMIT,
+                transactions_root: proofs::calculate_transaction_root(&body),
+                ..Default::default()
+            };
+            blocks.push(SealedBlock {
+                header: header.seal_slow(),
+                body,
+                ommers: Vec::new(),
+                withdrawals: None,
+                requests: None,
+            });
+        }
+        blocks
+    }
+
+    /// Inserts the blocks with successful receipts and indexes the transactions of all addresses.
+    fn insert_blocks(
+        factory: &ProviderFactory<Arc<TempDatabase<DatabaseEnv>>>,
+        blocks: &[SealedBlock],
+    ) {
+        let provider = factory.provider_rw().unwrap();
+        let mut tx_count = 0;
+        for block in blocks {
+            for index in 0..block.body.len() as u64 {
+                #[allow(clippy::needless_update)]
+                let receipt = Receipt {
+                    tx_type: TxType::Legacy,
+                    success: true,
+                    cumulative_gas_used: 21_000 * (index + 1),
+                    logs: Vec::new(),
+                    ..Default::default()
+                };
+                provider.tx_ref().put::<tables::Receipts>(tx_count, receipt).unwrap();
+                tx_count += 1;
+            }
+            provider.insert_block(block.clone().try_seal_with_senders().unwrap(), None).unwrap();
+        }
+
+        provider
+            .insert_address_transaction_index(
+                provider.address_transactions_by_tx_range(0..tx_count).unwrap(),
+            )
+            .unwrap();
+        let tip = blocks.last().unwrap().number;
+        for stage_id in [StageId::Finish, StageId::IndexAddressTransactions] {
+            provider.save_stage_checkpoint(stage_id, StageCheckpoint::new(tip)).unwrap();
+        }
+        provider.commit().unwrap();
+    }
+
+    fn otterscan_api(factory: ProviderFactory<Arc<TempDatabase<DatabaseEnv>>>) -> TestOtterscanApi {
+        let provider =
+            BlockchainProvider::new(factory, Arc::new(NoopBlockchainTree::default())).unwrap();
+        let evm_config = EthEvmConfig::default();
+        let cache = EthStateCache::spawn(provider.clone(), Default::default(), evm_config);
+        let fee_history_cache =
+            FeeHistoryCache::new(cache.clone(), FeeHistoryCacheConfig::default());
+        let eth = EthApi::new(
+            provider.clone(),
+            testing_pool(),
+            NoopNetwork::default(),
+            cache.clone(),
+            GasPriceOracle::new(provider.clone(), Default::default(), cache),
+            ETHEREUM_BLOCK_GAS_LIMIT,
+            DEFAULT_ETH_PROOF_WINDOW,
+            BlockingTaskPool::build().expect("failed to build tracing pool"),
+            fee_history_cache,
+            evm_config,
+            None,
+        );
+        OtterscanApi::new(provider, eth)
+    }
+
+    /// Returns the hashes of the transactions of the page, checking that the receipts match.
+    fn page_hashes(page: &TransactionsWithReceipts) -> Vec<B256> {
+        let hashes = page.txs.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+        assert_eq!(
+            page.receipts
+                .iter()
+                .map(|receipt| receipt.receipt.transaction_hash)
+                .collect::<Vec<_>>(),
+            hashes
+        );
+        hashes
+    }
+
+    #[tokio::test]
+    async fn search_transactions() {
+        let mut rng = generators::rng();
+        let address = Address::random();
+        let blocks = chain(vec![
+            vec![],
+            vec![tx_to(&mut rng, address), random_signed_tx(&mut rng)],
+            vec![random_signed_tx(&mut rng), tx_to(&mut rng, address), tx_to(&mut rng, address)],
+            vec![random_signed_tx(&mut rng)],
+            vec![tx_to(&mut rng, address)],
+            vec![random_signed_tx(&mut rng)],
+        ]);
+        let factory = create_test_provider_factory_with_chain_spec(MAINNET.clone());
+        insert_blocks(&factory, &blocks);
+        let api = otterscan_api(factory);
+
+        // transactions of the address in descending order
+        let expected = vec![
+            blocks[4].body[0].hash(),
+            blocks[2].body[2].hash(),
+            blocks[2].body[1].hash(),
+            blocks[1].body[0].hash(),
+        ];
+
+        let search_before = |block_number: BlockNumber, page_size| {
+            api.search_transactions_before(address, block_number.into(), page_size)
+        };
+        let page = search_before(0, 10).await.unwrap();
+        assert_eq!(page_hashes(&page), expected);
+        assert!(page.first_page && page.last_page);
+
+        // pages contain whole blocks, starting at the tip
+        let page = search_before(0, 1).await.unwrap();
+        assert_eq!(page_hashes(&page), expected[..1]);
+        assert!(page.first_page && !page.last_page);
+        let page = search_before(4, 1).await.unwrap();
+        assert_eq!(page_hashes(&page), expected[1..3]);
+        assert!(!page.first_page && !page.last_page);
+        let page = search_before(2, 1).await.unwrap();
+        assert_eq!(page_hashes(&page), expected[3..]);
+        assert!(!page.first_page && page.last_page);
+        let page = search_before(1, 1).await.unwrap();
+        assert!(page.txs.is_empty() && page.last_page);
+
+        let search_after = |block_number: BlockNumber, page_size| {
+            api.search_transactions_after(address, block_number.into(), page_size)
+        };
+        let page = search_after(0, 10).await.unwrap();
+        assert_eq!(page_hashes(&page), expected);
+        assert!(page.first_page && page.last_page);
+
+        // pages contain whole blocks, starting at genesis
+        let page = search_after(0, 2).await.unwrap();
+        assert_eq!(page_hashes(&page), expected[1..]);
+        assert!(!page.first_page && page.last_page);
+        let page = search_after(2, 2).await.unwrap();
+        assert_eq!(page_hashes(&page), expected[..1]);
+        assert!(page.first_page && !page.last_page);
+        let page = search_after(4, 2).await.unwrap();
+        assert!(page.txs.is_empty() && page.first_page);
+    }
+
+    #[tokio::test]
+    async fn get_contract_creator() {
+        let mut rng = generators::rng();
+        // returns the runtime code `0x00`
+        let init_code =
+            Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0x53, 0x60, 0x01, 0x60, 0x00, 0xf3]);
+        let create = sign_tx_with_random_key_pair(
+            &mut rng,
+            PrimitiveTransaction::Legacy(TxLegacy {
+                chain_id: Some(1),
+                nonce: 0,
+                gas_price: 0,
+                gas_limit: 100_000,
+                to: TxKind::Create,
+                value: U256::ZERO,
+                input: init_code,
+            }),
+        );
+        let creator = create.recover_signer().unwrap();
+        let contract = creator.create(0);
+
+        let blocks = chain(vec![vec![], vec![random_signed_tx(&mut rng)], vec![create.clone()]]);
+        let factory = create_test_provider_factory_with_chain_spec(MAINNET.clone());
+        insert_blocks(&factory, &blocks);
+
+        // the state after the contract was created in block 2
+        let provider = factory.provider_rw().unwrap();
+        let bytecode = Bytecode::new_raw(Bytes::from_static(&[0x00]));
+        let tx = provider.tx_ref();
+        tx.put::<tables::PlainAccountState>(creator, Account { nonce: 1, ..Default::default() })
+            .unwrap();
+        tx.put::<tables::PlainAccountState>(
+            contract,
+            Account { bytecode_hash: Some(bytecode.hash_slow()), ..Default::default() },
+        )
+        .unwrap();
+        tx.put::<tables::Bytecodes>(bytecode.hash_slow(), bytecode).unwrap();
+        for address in [creator, contract] {
+            tx.put::<tables::AccountChangeSets>(2, AccountBeforeTx { address, info: None })
+                .unwrap();
+        }
+        provider
+            .insert_account_history_index(BTreeMap::from([(creator, vec![2]), (contract, vec![2])]))
+            .unwrap();
+        provider.commit().unwrap();
+        let api = otterscan_api(factory.clone());
+
+        let contract_creator = api.get_contract_creator(contract).await.unwrap().unwrap();
+        assert_eq!((contract_creator.hash, contract_creator.creator), (create.hash(), creator));
+
+        // accounts without code have no creator
+        assert!(api.get_contract_creator(creator).await.unwrap().is_none());
+        assert!(api.get_contract_creator(Address::random()).await.unwrap().is_none());
+
+        // without the address transactions index, the blocks in which the account changed are
+        // traced
+        let provider = factory.provider_rw().unwrap();
+        provider
+            .tx_ref()
+            .delete::<tables::StageCheckpoints>(StageId::IndexAddressTransactions.to_string(), None)
+            .unwrap();
+        provider.commit().unwrap();
+        let page = api.search_transactions_before(creator, 0.into(), 10).await.unwrap();
+        assert_eq!(page_hashes(&page), vec![create.hash()]);
     }
 }
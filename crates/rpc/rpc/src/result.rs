@@ -102,6 +102,7 @@ impl_to_rpc_result!(PayloadError);
 impl_to_rpc_result!(reth_errors::RethError);
 impl_to_rpc_result!(reth_errors::ProviderError);
 impl_to_rpc_result!(reth_network_api::NetworkError);
+impl_to_rpc_result!(reth_db_common::backup::BackupError);
 
 /// Constructs an invalid params JSON-RPC error.
 pub(crate) fn invalid_params_rpc_err(
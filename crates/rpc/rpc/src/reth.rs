@@ -2,9 +2,13 @@ use crate::eth::error::{EthApiError, EthResult};
 use async_trait::async_trait;
 use jsonrpsee::core::RpcResult;
 use reth_errors::RethResult;
-use reth_primitives::{Address, BlockId, U256};
-use reth_provider::{BlockReaderIdExt, ChangeSetReader, StateProviderFactory};
+use reth_primitives::{Address, BlockId, BlockNumberOrTag, TxHash, U256};
+use reth_provider::{
+    AccountHistoryReader, BlockReaderIdExt, ChangeSetReader, StageCheckpointReader,
+    StateProviderFactory,
+};
 use reth_rpc_api::RethApiServer;
+use reth_stages_types::StageId;
 use reth_tasks::TaskSpawner;
 use std::{collections::HashMap, future::Future, sync::Arc};
 use tokio::sync::oneshot;
@@ -33,7 +37,12 @@ impl<Provider> RethApi<Provider> {
 
 impl<Provider> RethApi<Provider>
 where
-    Provider: BlockReaderIdExt + ChangeSetReader + StateProviderFactory + 'static,
+    Provider: BlockReaderIdExt
+        + ChangeSetReader
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + StateProviderFactory
+        + 'static,
 {
     /// Executes the future on a new blocking task.
     async fn on_blocking_task<C, F, R>(&self, c: C) -> EthResult<R>
@@ -81,12 +90,81 @@ where
         )?;
         Ok(hash_map)
     }
+
+    /// Returns the hashes of the transactions sent from or to the address within the block range.
+    pub async fn address_transactions(
+        &self,
+        address: Address,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+    ) -> EthResult<Vec<TxHash>> {
+        self.on_blocking_task(|this| async move {
+            this.try_address_transactions(address, from_block, to_block)
+        })
+        .await
+    }
+
+    fn try_address_transactions(
+        &self,
+        address: Address,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+    ) -> EthResult<Vec<TxHash>> {
+        let Some(indexed) =
+            self.provider().get_stage_checkpoint(StageId::IndexAddressTransactions)?
+        else {
+            return Err(EthApiError::Unsupported("address transactions index is not enabled"))
+        };
+
+        let (Some(from_block), Some(to_block)) = (
+            self.provider().convert_block_number(from_block)?,
+            self.provider().convert_block_number(to_block)?,
+        ) else {
+            return Err(EthApiError::UnknownBlockNumber)
+        };
+
+        // Blocks above the checkpoint of the index are not indexed yet.
+        let to_block = to_block.min(indexed.block_number);
+        if from_block > to_block {
+            return Ok(Vec::new())
+        }
+
+        let from_tx = self
+            .provider()
+            .block_body_indices(from_block)?
+            .ok_or(EthApiError::UnknownBlockNumber)?
+            .first_tx_num();
+        let to_tx = self
+            .provider()
+            .block_body_indices(to_block)?
+            .ok_or(EthApiError::UnknownBlockNumber)?
+            .next_tx_num();
+        if from_tx == to_tx {
+            return Ok(Vec::new())
+        }
+
+        self.provider()
+            .address_transactions(address, from_tx..=to_tx - 1)?
+            .into_iter()
+            .map(|tx_number| {
+                self.provider()
+                    .transaction_by_id(tx_number)?
+                    .map(|transaction| transaction.hash())
+                    .ok_or(EthApiError::TransactionNotFound)
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
 impl<Provider> RethApiServer for RethApi<Provider>
 where
-    Provider: BlockReaderIdExt + ChangeSetReader + StateProviderFactory + 'static,
+    Provider: BlockReaderIdExt
+        + ChangeSetReader
+        + AccountHistoryReader
+        + StageCheckpointReader
+        + StateProviderFactory
+        + 'static,
 {
     /// Handler for `reth_getBalanceChangesInBlock`
     async fn reth_get_balance_changes_in_block(
@@ -95,6 +173,16 @@ where
     ) -> RpcResult<HashMap<Address, U256>> {
         Ok(Self::balance_changes_in_block(self, block_id).await?)
     }
+
+    /// Handler for `reth_getAddressTransactions`
+    async fn reth_get_address_transactions(
+        &self,
+        address: Address,
+        from_block: BlockNumberOrTag,
+        to_block: BlockNumberOrTag,
+    ) -> RpcResult<Vec<TxHash>> {
+        Ok(Self::address_transactions(self, address, from_block, to_block).await?)
+    }
 }
 
 impl<Provider> std::fmt::Debug for RethApi<Provider> {
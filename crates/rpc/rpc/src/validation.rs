@@ -0,0 +1,751 @@
+use crate::result::{internal_rpc_err, invalid_params_rpc_err};
+use async_trait::async_trait;
+use jsonrpsee::{core::RpcResult, types::ErrorObject};
+use reth_chainspec::ChainSpec;
+use reth_consensus::{Consensus, ConsensusError, PostExecutionInput};
+use reth_evm::execute::{
+    BlockExecutionError, BlockExecutionOutput, BlockExecutorProvider, Executor,
+};
+use reth_payload_validator::ExecutionPayloadValidator;
+use reth_primitives::{
+    constants::{eip4844::MAINNET_KZG_TRUSTED_SETUP, GWEI_TO_WEI},
+    eip4844::kzg_to_versioned_hash,
+    BlobTransactionSidecar, BlobTransactionValidationError, GotExpected, Receipt, SealedBlock,
+    SealedBlockWithSenders, SealedHeader, B256, U256,
+};
+use reth_provider::{
+    BlockReaderIdExt, HeaderProvider, ProviderError, StateProviderFactory, StateRootProvider,
+};
+use reth_revm::database::StateProviderDatabase;
+use reth_rpc_api::BlockSubmissionValidationApiServer;
+use reth_rpc_types::{
+    beacon::relay::{
+        BidTrace, BuilderBlockValidationRequest, BuilderBlockValidationRequestV2,
+        BuilderBlockValidationRequestV3,
+    },
+    engine::{BlobsBundleV1, CancunPayloadFields},
+    ExecutionPayload, PayloadError,
+};
+use reth_tasks::TaskSpawner;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+/// The bound divisor of the gas limit, see also [EIP-1559](https://eips.ethereum.org/EIPS/eip-1559).
+const GAS_LIMIT_BOUND_DIVISOR: u64 = 1024;
+
+/// Errors that can occur when validating a builder submission.
+#[derive(Debug, thiserror::Error)]
+pub enum ValidationApiError {
+    /// The block hash does not match the bid.
+    #[error("block hash mismatch: {0}")]
+    BlockHashMismatch(GotExpected<B256>),
+    /// The parent hash does not match the bid.
+    #[error("parent hash mismatch: {0}")]
+    ParentHashMismatch(GotExpected<B256>),
+    /// The gas limit does not match the bid or the registered gas limit.
+    #[error("gas limit mismatch: {0}")]
+    GasLimitMismatch(GotExpected<u64>),
+    /// The gas used does not match the bid.
+    #[error("gas used mismatch: {0}")]
+    GasUsedMismatch(GotExpected<u64>),
+    /// The withdrawals root does not match the request.
+    #[error("withdrawals root mismatch: {0}")]
+    WithdrawalsRootMismatch(GotExpected<B256>),
+    /// The parent of the block is not known.
+    #[error("unknown parent block {0}")]
+    UnknownParent(B256),
+    /// The proposer was not paid the value of the bid.
+    #[error("proposer payment of {0} could not be verified")]
+    ProposerPayment(U256),
+    /// The blobs bundle has a different number of blobs, commitments and proofs.
+    #[error("invalid blobs bundle: {blobs} blobs, {commitments} commitments, {proofs} proofs")]
+    InvalidBlobsBundle {
+        /// Number of blobs.
+        blobs: usize,
+        /// Number of commitments.
+        commitments: usize,
+        /// Number of proofs.
+        proofs: usize,
+    },
+    /// The blobs do not match their commitments and proofs.
+    #[error(transparent)]
+    Blobs(#[from] BlobTransactionValidationError),
+    /// The payload is malformed.
+    #[error(transparent)]
+    Payload(#[from] PayloadError),
+    /// The block failed consensus validation.
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    /// The block failed execution.
+    #[error(transparent)]
+    Execution(#[from] BlockExecutionError),
+    /// Error while accessing the database.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// The validation task was dropped.
+    #[error("internal blocking task error")]
+    InternalBlockingTaskError,
+}
+
+impl From<ValidationApiError> for ErrorObject<'static> {
+    fn from(error: ValidationApiError) -> Self {
+        match error {
+            ValidationApiError::Provider(_) | ValidationApiError::InternalBlockingTaskError => {
+                internal_rpc_err(error.to_string())
+            }
+            err => invalid_params_rpc_err(err.to_string()),
+        }
+    }
+}
+
+/// `flashbots` block validation API implementation, used by relays to validate builder
+/// submissions.
+///
+/// A submission is valid if its block can be appended to its parent block: it's validated and
+/// executed like a new payload on top of the state of the parent, and the proposer must receive the
+/// value of the bid, either through a balance increase of the fee recipient or with a payment
+/// transaction at the end of the block.
+pub struct ValidationApi<Provider, E> {
+    inner: Arc<ValidationApiInner<Provider, E>>,
+}
+
+// === impl ValidationApi ===
+
+impl<Provider, E> ValidationApi<Provider, E> {
+    /// Create a new instance of the [`ValidationApi`]
+    pub fn new(
+        provider: Provider,
+        chain_spec: Arc<ChainSpec>,
+        consensus: Arc<dyn Consensus>,
+        executor_provider: E,
+        task_spawner: Box<dyn TaskSpawner>,
+    ) -> Self {
+        let payload_validator = ExecutionPayloadValidator::new(chain_spec);
+        let inner = Arc::new(ValidationApiInner {
+            provider,
+            payload_validator,
+            consensus,
+            executor_provider,
+            task_spawner,
+        });
+        Self { inner }
+    }
+}
+
+impl<Provider, E> ValidationApi<Provider, E>
+where
+    Provider: BlockReaderIdExt + HeaderProvider + StateProviderFactory + Clone + 'static,
+    E: BlockExecutorProvider,
+{
+    /// Validates the payload of a submission against the bid and its parent block.
+    ///
+    /// Validation is done on a blocking task, since the block is executed.
+    async fn validate_payload(
+        &self,
+        payload: ExecutionPayload,
+        cancun_fields: Option<CancunPayloadFields>,
+        message: BidTrace,
+        registered_gas_limit: u64,
+    ) -> Result<SealedBlock, ValidationApiError> {
+        let block = self
+            .inner
+            .payload_validator
+            .ensure_well_formed_payload(payload, cancun_fields.into())?;
+
+        let (tx, rx) = oneshot::channel();
+        let this = self.clone();
+        self.inner.task_spawner.spawn_blocking(Box::pin(async move {
+            let res = this.validate_block(block, message, registered_gas_limit);
+            let _ = tx.send(res);
+        }));
+        rx.await.map_err(|_| ValidationApiError::InternalBlockingTaskError)?
+    }
+
+    /// Validates the block against the bid, executes it on top of its parent and ensures the
+    /// proposer is paid.
+    fn validate_block(
+        &self,
+        block: SealedBlock,
+        message: BidTrace,
+        registered_gas_limit: u64,
+    ) -> Result<SealedBlock, ValidationApiError> {
+        validate_message_against_header(&block.header, &message)?;
+
+        let block = block
+            .try_seal_with_senders()
+            .map_err(|_| ConsensusError::TransactionSignerRecoveryError)?;
+
+        let parent = self
+            .inner
+            .provider
+            .header(&block.parent_hash)?
+            .map(|header| SealedHeader::new(header, block.parent_hash))
+            .ok_or(ValidationApiError::UnknownParent(block.parent_hash))?;
+        validate_gas_limit(registered_gas_limit, &parent, &block.header)?;
+
+        let total_difficulty = self
+            .inner
+            .provider
+            .header_td(&parent.hash())?
+            .ok_or_else(|| ProviderError::TotalDifficultyNotFound(parent.number))? +
+            block.difficulty;
+
+        let consensus = &self.inner.consensus;
+        consensus.validate_header_with_total_difficulty(&block.header, total_difficulty)?;
+        consensus.validate_header(&block.header)?;
+        consensus.validate_header_against_parent(&block.header, &parent)?;
+        consensus.validate_block_pre_execution(&block)?;
+
+        let state_provider = self.inner.provider.state_by_block_hash(parent.hash())?;
+        let db = StateProviderDatabase::new(&state_provider);
+        let unsealed = block.clone().unseal();
+        let output = self
+            .inner
+            .executor_provider
+            .executor(db)
+            .execute((&unsealed, total_difficulty).into())?;
+
+        consensus.validate_block_post_execution(
+            &unsealed,
+            PostExecutionInput::new(&output.receipts, &output.requests),
+        )?;
+
+        ensure_payment(&block, &output, &message)?;
+
+        let state_root = state_provider.state_root(&output.state)?;
+        if state_root != block.state_root {
+            return Err(ConsensusError::BodyStateRootDiff(
+                GotExpected { got: state_root, expected: block.state_root }.into(),
+            )
+            .into())
+        }
+
+        Ok(block.block)
+    }
+}
+
+/// Ensures the header matches the bid.
+fn validate_message_against_header(
+    header: &SealedHeader,
+    message: &BidTrace,
+) -> Result<(), ValidationApiError> {
+    if header.hash() != message.block_hash {
+        return Err(ValidationApiError::BlockHashMismatch(GotExpected {
+            got: message.block_hash,
+            expected: header.hash(),
+        }))
+    }
+    if header.parent_hash != message.parent_hash {
+        return Err(ValidationApiError::ParentHashMismatch(GotExpected {
+            got: message.parent_hash,
+            expected: header.parent_hash,
+        }))
+    }
+    if header.gas_limit != message.gas_limit {
+        return Err(ValidationApiError::GasLimitMismatch(GotExpected {
+            got: message.gas_limit,
+            expected: header.gas_limit,
+        }))
+    }
+    if header.gas_used != message.gas_used {
+        return Err(ValidationApiError::GasUsedMismatch(GotExpected {
+            got: message.gas_used,
+            expected: header.gas_used,
+        }))
+    }
+    Ok(())
+}
+
+/// Ensures the gas limit of the block moves as close to the gas limit registered by the proposer as
+/// the bound divisor allows.
+fn validate_gas_limit(
+    registered_gas_limit: u64,
+    parent: &SealedHeader,
+    header: &SealedHeader,
+) -> Result<(), ValidationApiError> {
+    let max_gas_limit = parent.gas_limit + parent.gas_limit / GAS_LIMIT_BOUND_DIVISOR - 1;
+    let min_gas_limit = parent.gas_limit - parent.gas_limit / GAS_LIMIT_BOUND_DIVISOR + 1;
+    let expected = registered_gas_limit.clamp(min_gas_limit, max_gas_limit);
+    if header.gas_limit != expected {
+        return Err(ValidationApiError::GasLimitMismatch(GotExpected {
+            got: header.gas_limit,
+            expected,
+        }))
+    }
+    Ok(())
+}
+
+/// Ensures the proposer fee recipient receives the value of the bid.
+///
+/// The payment is either the balance increase of the fee recipient, not counting withdrawals, or
+/// the last transaction of the block, which must be a plain transfer of the value to the fee
+/// recipient.
+fn ensure_payment(
+    block: &SealedBlockWithSenders,
+    output: &BlockExecutionOutput<Receipt>,
+    message: &BidTrace,
+) -> Result<(), ValidationApiError> {
+    let fee_recipient = message.proposer_fee_recipient;
+    let (mut balance_before, balance_after) = output
+        .state
+        .state
+        .get(&fee_recipient)
+        .map(|account| {
+            (
+                account.original_info.as_ref().map(|info| info.balance).unwrap_or_default(),
+                account.info.as_ref().map(|info| info.balance).unwrap_or_default(),
+            )
+        })
+        .unwrap_or_default();
+
+    if let Some(withdrawals) = &block.withdrawals {
+        for withdrawal in
+            withdrawals.iter().filter(|withdrawal| withdrawal.address == fee_recipient)
+        {
+            balance_before += U256::from(withdrawal.amount) * U256::from(GWEI_TO_WEI);
+        }
+    }
+
+    if balance_after >= balance_before + message.value {
+        return Ok(())
+    }
+
+    let error = ValidationApiError::ProposerPayment(message.value);
+    let Some((receipt, tx)) = output.receipts.last().zip(block.body.last()) else {
+        return Err(error)
+    };
+    if !receipt.success ||
+        tx.to() != Some(fee_recipient) ||
+        tx.value() != message.value ||
+        !tx.input().is_empty()
+    {
+        return Err(error)
+    }
+    // the payment transaction must not pay the fee recipient a tip as coinbase
+    if tx.effective_tip_per_gas(block.base_fee_per_gas).unwrap_or_default() != 0 {
+        return Err(error)
+    }
+
+    Ok(())
+}
+
+/// Validates the blobs against their commitments and proofs, returning their versioned hashes.
+fn validate_blobs_bundle(blobs_bundle: BlobsBundleV1) -> Result<Vec<B256>, ValidationApiError> {
+    let BlobsBundleV1 { commitments, proofs, blobs } = blobs_bundle;
+    if commitments.len() != proofs.len() || commitments.len() != blobs.len() {
+        return Err(ValidationApiError::InvalidBlobsBundle {
+            blobs: blobs.len(),
+            commitments: commitments.len(),
+            proofs: proofs.len(),
+        })
+    }
+
+    let versioned_hashes = commitments
+        .iter()
+        .map(|commitment| kzg_to_versioned_hash(commitment.as_slice()))
+        .collect::<Vec<_>>();
+    let sidecar = BlobTransactionSidecar { blobs, commitments, proofs };
+    sidecar.validate(&versioned_hashes, &MAINNET_KZG_TRUSTED_SETUP)?;
+
+    Ok(versioned_hashes)
+}
+
+#[async_trait]
+impl<Provider, E> BlockSubmissionValidationApiServer for ValidationApi<Provider, E>
+where
+    Provider: BlockReaderIdExt + HeaderProvider + StateProviderFactory + Clone + 'static,
+    E: BlockExecutorProvider,
+{
+    /// Handler for `flashbots_validateBuilderSubmissionV1`
+    async fn validate_builder_submission_v1(
+        &self,
+        request: BuilderBlockValidationRequest,
+    ) -> RpcResult<()> {
+        self.validate_payload(
+            ExecutionPayload::V1(request.request.execution_payload),
+            None,
+            request.request.message,
+            request.registered_gas_limit,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Handler for `flashbots_validateBuilderSubmissionV2`
+    async fn validate_builder_submission_v2(
+        &self,
+        request: BuilderBlockValidationRequestV2,
+    ) -> RpcResult<()> {
+        let block = self
+            .validate_payload(
+                ExecutionPayload::V2(request.request.execution_payload),
+                None,
+                request.request.message,
+                request.registered_gas_limit,
+            )
+            .await?;
+        let withdrawals_root = block.withdrawals_root.unwrap_or_default();
+        if withdrawals_root != request.withdrawals_root {
+            return Err(ValidationApiError::WithdrawalsRootMismatch(GotExpected {
+                got: request.withdrawals_root,
+                expected: withdrawals_root,
+            })
+            .into())
+        }
+        Ok(())
+    }
+
+    /// Handler for `flashbots_validateBuilderSubmissionV3`
+    async fn validate_builder_submission_v3(
+        &self,
+        request: BuilderBlockValidationRequestV3,
+    ) -> RpcResult<()> {
+        let versioned_hashes = validate_blobs_bundle(request.request.blobs_bundle)?;
+        let cancun_fields = CancunPayloadFields {
+            parent_beacon_block_root: request.parent_beacon_block_root,
+            versioned_hashes,
+        };
+        self.validate_payload(
+            ExecutionPayload::V3(request.request.execution_payload),
+            Some(cancun_fields),
+            request.request.message,
+            request.registered_gas_limit,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+impl<Provider, E> std::fmt::Debug for ValidationApi<Provider, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ValidationApi").finish_non_exhaustive()
+    }
+}
+
+impl<Provider, E> Clone for ValidationApi<Provider, E> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+struct ValidationApiInner<Provider, E> {
+    /// The provider that can interact with the chain.
+    provider: Provider,
+    /// Validates that payloads are well formed.
+    payload_validator: ExecutionPayloadValidator,
+    /// Consensus rules the block must satisfy.
+    consensus: Arc<dyn Consensus>,
+    /// Executes the block on top of the state of its parent.
+    executor_provider: E,
+    /// The type that can spawn tasks which would otherwise block.
+    task_spawner: Box<dyn TaskSpawner>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_genesis::Genesis;
+    use reth_blockchain_tree::noop::NoopBlockchainTree;
+    use reth_chainspec::{ChainSpecBuilder, MAINNET};
+    use reth_db_common::init::init_genesis;
+    use reth_ethereum_consensus::EthBeaconConsensus;
+    use reth_evm_ethereum::execute::EthExecutorProvider;
+    use reth_primitives::{
+        constants::{
+            eip4844::DATA_GAS_PER_BLOB, EMPTY_OMMER_ROOT_HASH, EMPTY_ROOT_HASH,
+            ETHEREUM_BLOCK_GAS_LIMIT, ETH_TO_WEI,
+        },
+        kzg::{Blob, BYTES_PER_BLOB},
+        proofs,
+        transaction::generate_blob_sidecar,
+        Address, Block, Header, Transaction, TxEip1559, TxEip4844, TxKind,
+    };
+    use reth_provider::{
+        providers::BlockchainProvider, test_utils::create_test_provider_factory_with_chain_spec,
+        BlockWriter, StageCheckpointWriter,
+    };
+    use reth_rpc_types::beacon::relay::SignedBidSubmissionV3;
+    use reth_rpc_types_compat::engine::payload::{block_to_payload_v1, block_to_payload_v3};
+    use reth_stages_types::{StageCheckpoint, StageId};
+    use reth_tasks::TokioTaskExecutor;
+    use reth_testing_utils::{
+        generators::{self, sign_tx_with_key_pair},
+        GenesisAllocator,
+    };
+    use secp256k1::Keypair;
+
+    /// Builds the chain spec with an account that is funded in the genesis, and returns the key
+    /// pair of the account.
+    fn funded_chain_spec(builder: ChainSpecBuilder) -> (Arc<ChainSpec>, Keypair) {
+        let mut rng = generators::rng();
+        let mut allocator = GenesisAllocator::default().with_rng(&mut rng);
+        let (key_pair, _) = allocator.new_funded_account(U256::from(ETH_TO_WEI));
+        let genesis = Genesis { gas_limit: ETHEREUM_BLOCK_GAS_LIMIT as u128, ..Default::default() }
+            .extend_accounts(allocator.build());
+        (Arc::new(builder.chain(MAINNET.chain).genesis(genesis).build()), key_pair)
+    }
+
+    /// Executes the block on top of its parent, fills in the execution results of its header and
+    /// seals it.
+    fn execute_and_seal<P: StateProviderFactory>(
+        provider: &P,
+        executor_provider: &EthExecutorProvider,
+        mut block: Block,
+    ) -> SealedBlock {
+        let state_provider = provider.state_by_block_hash(block.header.parent_hash).unwrap();
+        let output = executor_provider
+            .executor(StateProviderDatabase::new(&state_provider))
+            .execute((&block.clone().with_recovered_senders().unwrap(), U256::ZERO).into())
+            .unwrap();
+        block.header.gas_used = output.gas_used;
+        block.header.receipts_root = proofs::calculate_receipt_root(
+            &output.receipts.iter().cloned().map(Receipt::with_bloom).collect::<Vec<_>>(),
+        );
+        block.header.state_root = state_provider.state_root(&output.state).unwrap();
+        block.seal_slow()
+    }
+
+    #[tokio::test]
+    async fn validates_block_on_top_of_parent() {
+        let (chain_spec, key_pair) =
+            funded_chain_spec(ChainSpecBuilder::default().paris_activated());
+
+        let factory = create_test_provider_factory_with_chain_spec(chain_spec.clone());
+        init_genesis(factory.clone()).unwrap();
+
+        // the child of genesis that is the tip of the chain
+        let parent = chain_spec.sealed_genesis_header();
+        let timestamp = parent.timestamp + 12;
+        let header = Header {
+            parent_hash: parent.hash(),
+            number: 1,
+            gas_limit: parent.gas_limit,
+            timestamp,
+            base_fee_per_gas: parent
+                .next_block_base_fee(chain_spec.base_fee_params_at_timestamp(timestamp)),
+            ommers_hash: EMPTY_OMMER_ROOT_HASH,
+            transactions_root: EMPTY_ROOT_HASH,
+            receipts_root: EMPTY_ROOT_HASH,
+            state_root: parent.state_root,
+            ..Default::default()
+        };
+        let tip = SealedBlock { header: header.clone().seal_slow(), ..Default::default() };
+        let provider_rw = factory.provider_rw().unwrap();
+        provider_rw.insert_block(tip.clone().try_seal_with_senders().unwrap(), None).unwrap();
+        provider_rw.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(1)).unwrap();
+        provider_rw.commit().unwrap();
+
+        let provider =
+            BlockchainProvider::new(factory, Arc::new(NoopBlockchainTree::default())).unwrap();
+        assert_eq!(provider.latest_header().unwrap().unwrap().hash(), tip.hash());
+
+        // a sibling of the tip that pays the proposer with its last transaction
+        let fee_recipient = Address::random();
+        let value = U256::from(ETH_TO_WEI / 10);
+        let payment = sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: chain_spec.chain.id(),
+                nonce: 0,
+                gas_limit: 21_000,
+                max_fee_per_gas: header.base_fee_per_gas.unwrap() as u128,
+                max_priority_fee_per_gas: 0,
+                to: TxKind::Call(fee_recipient),
+                value,
+                ..Default::default()
+            }),
+        );
+        let block = Block {
+            header: Header {
+                coinbase: Address::random(),
+                transactions_root: proofs::calculate_transaction_root(&[&payment]),
+                ..header
+            },
+            body: vec![payment],
+            ommers: Vec::new(),
+            withdrawals: None,
+            requests: None,
+        };
+        let executor_provider = EthExecutorProvider::ethereum(chain_spec.clone());
+        let block = execute_and_seal(&provider, &executor_provider, block);
+        assert_ne!(block.hash(), tip.hash());
+
+        let api = ValidationApi::new(
+            provider,
+            chain_spec.clone(),
+            Arc::new(EthBeaconConsensus::new(chain_spec)),
+            executor_provider,
+            Box::new(TokioTaskExecutor::default()),
+        );
+        let message = BidTrace {
+            slot: 1,
+            parent_hash: block.parent_hash,
+            block_hash: block.hash(),
+            builder_pubkey: Default::default(),
+            proposer_pubkey: Default::default(),
+            proposer_fee_recipient: fee_recipient,
+            gas_limit: block.gas_limit,
+            gas_used: block.gas_used,
+            value,
+        };
+        let payload = ExecutionPayload::V1(block_to_payload_v1(block.clone()));
+
+        let validated = api
+            .validate_payload(payload.clone(), None, message.clone(), block.gas_limit)
+            .await
+            .unwrap();
+        assert_eq!(validated.hash(), block.hash());
+
+        assert!(matches!(
+            api.validate_payload(
+                payload,
+                None,
+                BidTrace { value: value + U256::from(1), ..message },
+                block.gas_limit
+            )
+            .await,
+            Err(ValidationApiError::ProposerPayment(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn validates_v3_submission_with_blobs() {
+        let (chain_spec, key_pair) =
+            funded_chain_spec(ChainSpecBuilder::default().cancun_activated());
+        let factory = create_test_provider_factory_with_chain_spec(chain_spec.clone());
+        init_genesis(factory.clone()).unwrap();
+        let provider =
+            BlockchainProvider::new(factory, Arc::new(NoopBlockchainTree::default())).unwrap();
+
+        let sidecar = generate_blob_sidecar(vec![Blob::from_bytes(&[0; BYTES_PER_BLOB]).unwrap()]);
+        let versioned_hashes = sidecar
+            .commitments
+            .iter()
+            .map(|commitment| kzg_to_versioned_hash(commitment.as_slice()))
+            .collect::<Vec<_>>();
+
+        // a child of genesis with a blob transaction, that pays the proposer with its last
+        // transaction
+        let parent = chain_spec.sealed_genesis_header();
+        let timestamp = parent.timestamp + 12;
+        let base_fee =
+            parent.next_block_base_fee(chain_spec.base_fee_params_at_timestamp(timestamp)).unwrap();
+        let fee_recipient = Address::random();
+        let value = U256::from(ETH_TO_WEI / 10);
+        let blob_tx = sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Eip4844(TxEip4844 {
+                chain_id: chain_spec.chain.id(),
+                nonce: 0,
+                gas_limit: 21_000,
+                max_fee_per_gas: base_fee as u128,
+                max_priority_fee_per_gas: 0,
+                to: Address::random(),
+                blob_versioned_hashes: versioned_hashes,
+                max_fee_per_blob_gas: parent.next_block_blob_fee().unwrap(),
+                ..Default::default()
+            }),
+        );
+        let payment = sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Eip1559(TxEip1559 {
+                chain_id: chain_spec.chain.id(),
+                nonce: 1,
+                gas_limit: 21_000,
+                max_fee_per_gas: base_fee as u128,
+                max_priority_fee_per_gas: 0,
+                to: TxKind::Call(fee_recipient),
+                value,
+                ..Default::default()
+            }),
+        );
+        let parent_beacon_block_root = B256::random();
+        let block = Block {
+            header: Header {
+                parent_hash: parent.hash(),
+                coinbase: Address::random(),
+                number: 1,
+                gas_limit: parent.gas_limit,
+                timestamp,
+                base_fee_per_gas: Some(base_fee),
+                ommers_hash: EMPTY_OMMER_ROOT_HASH,
+                transactions_root: proofs::calculate_transaction_root(&[&blob_tx, &payment]),
+                withdrawals_root: Some(EMPTY_ROOT_HASH),
+                blob_gas_used: Some(DATA_GAS_PER_BLOB),
+                excess_blob_gas: parent.next_block_excess_blob_gas(),
+                parent_beacon_block_root: Some(parent_beacon_block_root),
+                ..Default::default()
+            },
+            body: vec![blob_tx, payment],
+            ommers: Vec::new(),
+            withdrawals: Some(Default::default()),
+            requests: None,
+        };
+        let executor_provider = EthExecutorProvider::ethereum(chain_spec.clone());
+        let block = execute_and_seal(&provider, &executor_provider, block);
+
+        let api = ValidationApi::new(
+            provider,
+            chain_spec.clone(),
+            Arc::new(EthBeaconConsensus::new(chain_spec)),
+            executor_provider,
+            Box::new(TokioTaskExecutor::default()),
+        );
+        let request = BuilderBlockValidationRequestV3 {
+            request: SignedBidSubmissionV3 {
+                message: BidTrace {
+                    slot: 1,
+                    parent_hash: block.parent_hash,
+                    block_hash: block.hash(),
+                    builder_pubkey: Default::default(),
+                    proposer_pubkey: Default::default(),
+                    proposer_fee_recipient: fee_recipient,
+                    gas_limit: block.gas_limit,
+                    gas_used: block.gas_used,
+                    value,
+                },
+                execution_payload: block_to_payload_v3(block.clone()).0,
+                blobs_bundle: BlobsBundleV1 {
+                    commitments: sidecar.commitments.clone(),
+                    proofs: sidecar.proofs.clone(),
+                    blobs: sidecar.blobs.clone(),
+                },
+                signature: Default::default(),
+            },
+            registered_gas_limit: block.gas_limit,
+            parent_beacon_block_root,
+        };
+        api.validate_builder_submission_v3(request.clone()).await.unwrap();
+
+        // the blobs must match their commitments
+        let mut request = request;
+        request.request.blobs_bundle.blobs[0][31] = 1;
+        assert!(api.validate_builder_submission_v3(request).await.is_err());
+    }
+
+    #[test]
+    fn gas_limit_moves_towards_registered() {
+        let parent = Header { gas_limit: 30_000_000, ..Default::default() }.seal_slow();
+        let max = 30_000_000 + 30_000_000 / GAS_LIMIT_BOUND_DIVISOR - 1;
+
+        let header = Header { gas_limit: max, ..Default::default() }.seal_slow();
+        assert!(validate_gas_limit(36_000_000, &parent, &header).is_ok());
+
+        let header = Header { gas_limit: 30_000_000, ..Default::default() }.seal_slow();
+        assert!(validate_gas_limit(30_000_000, &parent, &header).is_ok());
+        assert!(validate_gas_limit(36_000_000, &parent, &header).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_blobs_bundle() {
+        let bundle = BlobsBundleV1 {
+            commitments: vec![Default::default()],
+            proofs: Vec::new(),
+            blobs: Vec::new(),
+        };
+        assert!(matches!(
+            validate_blobs_bundle(bundle),
+            Err(ValidationApiError::InvalidBlobsBundle { blobs: 0, commitments: 1, proofs: 0 })
+        ));
+    }
+}
@@ -244,6 +244,11 @@ where
     ///   [`StageId::Execution`]
     /// - [`StaticFileSegment::Transactions`](reth_static_file_types::StaticFileSegment::Transactions)
     ///   -> [`StageId::Bodies`]
+    /// - [`StaticFileSegment::AccountChangeSets`](reth_static_file_types::StaticFileSegment::AccountChangeSets),
+    ///   [`StaticFileSegment::StorageChangeSets`](reth_static_file_types::StaticFileSegment::StorageChangeSets)
+    ///   and [`StaticFileSegment::TransactionSenders`](reth_static_file_types::StaticFileSegment::TransactionSenders)
+    ///   -> [`StageId::Finish`], since they're still read by the hashing, history and merkle
+    ///   stages after being written.
     ///
     /// CAUTION: This method locks the static file producer Mutex, hence can block the thread if the
     /// lock is occupied.
@@ -253,17 +258,21 @@ where
         // Copies data from database to static files
         let lowest_static_file_height = {
             let provider = self.provider_factory.provider()?;
-            let stages_checkpoints = [StageId::Headers, StageId::Execution, StageId::Bodies]
-                .into_iter()
-                .map(|stage| {
-                    provider.get_stage_checkpoint(stage).map(|c| c.map(|c| c.block_number))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
+            let stages_checkpoints =
+                [StageId::Headers, StageId::Execution, StageId::Bodies, StageId::Finish]
+                    .into_iter()
+                    .map(|stage| {
+                        provider.get_stage_checkpoint(stage).map(|c| c.map(|c| c.block_number))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
 
             let targets = static_file_producer.get_static_file_targets(HighestStaticFiles {
                 headers: stages_checkpoints[0],
                 receipts: stages_checkpoints[1],
                 transactions: stages_checkpoints[2],
+                account_change_sets: stages_checkpoints[3],
+                storage_change_sets: stages_checkpoints[3],
+                transaction_senders: stages_checkpoints[3],
             })?;
             static_file_producer.run(targets)?;
             stages_checkpoints.into_iter().min().expect("exists")
@@ -36,8 +36,8 @@
 use crate::{
     stages::{
         AccountHashingStage, BodyStage, ExecutionStage, FinishStage, HeaderStage,
-        IndexAccountHistoryStage, IndexStorageHistoryStage, MerkleStage, SenderRecoveryStage,
-        StorageHashingStage, TransactionLookupStage,
+        IndexAccountHistoryStage, IndexAddressTransactionsStage, IndexStorageHistoryStage,
+        MerkleStage, SenderRecoveryStage, StorageHashingStage, TransactionLookupStage,
     },
     StageSet, StageSetBuilder,
 };
@@ -49,6 +49,7 @@ use reth_network_p2p::{bodies::downloader::BodyDownloader, headers::downloader::
 use reth_primitives::B256;
 use reth_provider::HeaderSyncGapProvider;
 use reth_prune_types::PruneModes;
+use reth_stages_api::StageId;
 use std::sync::Arc;
 use tokio::sync::watch;
 
@@ -72,6 +73,7 @@ use tokio::sync::watch;
 /// - [`TransactionLookupStage`]
 /// - [`IndexStorageHistoryStage`]
 /// - [`IndexAccountHistoryStage`]
+/// - [`IndexAddressTransactionsStage`] (disabled unless enabled in the [`StageConfig`])
 /// - [`FinishStage`]
 #[derive(Debug)]
 pub struct DefaultStages<Provider, H, B, EF> {
@@ -373,15 +375,34 @@ impl<DB: Database> StageSet<DB> for HistoryIndexingStages {
                 self.stages_config.etl.clone(),
                 self.prune_modes.transaction_lookup,
             ))
+            // The prune mode makes the stage skip the prunable range by moving its checkpoint,
+            // which skips the range for all addresses at once. With retention rules,
+            // the history of the retained addresses must be indexed in full, so the
+            // range is indexed for every address and the pruner deletes the shards of
+            // the other addresses on its next run. This only costs extra work and space
+            // until then, and it only happens while the stage catches up
+            // with the prunable range, i.e. on the initial sync.
             .add_stage(IndexStorageHistoryStage::new(
                 self.stages_config.index_storage_history,
                 self.stages_config.etl.clone(),
-                self.prune_modes.account_history,
+                self.prune_modes
+                    .storage_history
+                    .filter(|_| self.prune_modes.storage_history_retention.is_empty()),
             ))
             .add_stage(IndexAccountHistoryStage::new(
                 self.stages_config.index_account_history,
                 self.stages_config.etl.clone(),
-                self.prune_modes.storage_history,
+                self.prune_modes
+                    .account_history
+                    .filter(|_| self.prune_modes.account_history_retention.is_empty()),
             ))
+            .add_stage(IndexAddressTransactionsStage::new(
+                self.stages_config.index_address_transactions,
+                self.stages_config.etl.clone(),
+                self.prune_modes.address_transactions,
+            ))
+            .disable_if(StageId::IndexAddressTransactions, || {
+                !self.stages_config.index_address_transactions.enabled
+            })
     }
 }
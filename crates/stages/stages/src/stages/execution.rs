@@ -1,22 +1,28 @@
+use super::{ensure_unwind_above_snap_sync_pivot, unwind_prune_checkpoint};
 use crate::stages::MERKLE_STAGE_DEFAULT_CLEAN_THRESHOLD;
 use num_traits::Zero;
 use reth_config::config::ExecutionConfig;
 use reth_db::{static_file::HeaderMask, tables};
-use reth_db_api::{cursor::DbCursorRO, database::Database, transaction::DbTx};
+use reth_db_api::{
+    cursor::{DbCursorRO, DbDupCursorRW},
+    database::Database,
+    models::BlockNumberAddress,
+    transaction::{DbTx, DbTxMut},
+};
 use reth_evm::execute::{BatchExecutor, BlockExecutorProvider};
 use reth_execution_types::{Chain, ExecutionOutcome};
 use reth_exex::{ExExManagerHandle, ExExNotification};
 use reth_primitives::{
     constants::gas_units::{GIGAGAS, KILOGAS, MEGAGAS},
-    BlockNumber, Header, StaticFileSegment,
+    keccak256, BlockNumber, Header, StaticFileSegment, StorageEntry, B256,
 };
 use reth_provider::{
     providers::{StaticFileProvider, StaticFileProviderRWRefMut, StaticFileWriter},
-    BlockReader, DatabaseProviderRW, HeaderProvider, LatestStateProviderRef, OriginalValuesKnown,
-    ProviderError, StateWriter, StatsReader, TransactionVariant,
+    BlockReader, DatabaseProviderRW, HashingWriter, HeaderProvider, LatestStateProviderRef,
+    OriginalValuesKnown, ProviderError, StateWriter, StatsReader, TransactionVariant,
 };
-use reth_prune_types::PruneModes;
-use reth_revm::database::StateProviderDatabase;
+use reth_prune_types::{PruneModes, PruneSegment};
+use reth_revm::{database::StateProviderDatabase, db::BundleState};
 use reth_stages_api::{
     BlockErrorKind, CheckpointBlockRange, EntitiesCheckpoint, ExecInput, ExecOutput,
     ExecutionCheckpoint, MetricEvent, MetricEventsSender, Stage, StageCheckpoint, StageError,
@@ -220,10 +226,13 @@ where
             None
         };
 
-        let db = StateProviderDatabase(LatestStateProviderRef::new(
-            provider.tx_ref(),
-            provider.static_file_provider().clone(),
-        ));
+        // The plain state of snap synced nodes is incomplete, the state is read from the hashed
+        // state tables instead.
+        let snap_synced = provider.snap_sync_pivot()?.is_some();
+        let db = StateProviderDatabase(
+            LatestStateProviderRef::new(provider.tx_ref(), provider.static_file_provider().clone())
+                .with_hashed_state(snap_synced),
+        );
         let mut executor = self.executor_provider.batch_executor(db, prune_modes);
         executor.set_tip(max_block);
 
@@ -335,6 +344,10 @@ where
         }
 
         let time = Instant::now();
+        // The next batch reads the hashed state, so it can't wait for the hashing stages.
+        if snap_synced {
+            write_hashed_state(provider, &state.bundle)?;
+        }
         // write output
         state.write_to_storage(
             provider.tx_ref(),
@@ -377,6 +390,8 @@ where
         provider: &DatabaseProviderRW<DB>,
         input: UnwindInput,
     ) -> Result<UnwindOutput, StageError> {
+        ensure_unwind_above_snap_sync_pivot(provider, input.unwind_to)?;
+
         let (range, unwind_to, _) =
             input.unwind_block_range_with_threshold(self.thresholds.max_blocks.unwrap_or(u64::MAX));
         if range.is_empty() {
@@ -385,11 +400,43 @@ where
             })
         }
 
+        // The hashed state of snap synced nodes is written by this stage, it has to be unwound
+        // from the changesets before they are removed.
+        if provider.snap_sync_pivot()?.is_some() {
+            provider.unwind_account_hashing(range.clone())?;
+            provider.unwind_storage_hashing(BlockNumberAddress::range(range.clone()))?;
+        }
+
         // Unwind account and storage changesets, as well as receipts.
         //
         // This also updates `PlainStorageState` and `PlainAccountState`.
         let bundle_state_with_receipts = provider.unwind_or_peek_state::<true>(range.clone())?;
 
+        // Changesets which have already been moved to static files are only read by
+        // `unwind_or_peek_state`, so they have to be pruned from static files here.
+        let static_file_provider = provider.static_file_provider();
+        for segment in [StaticFileSegment::AccountChangeSets, StaticFileSegment::StorageChangeSets]
+        {
+            let Some(highest_block) = static_file_provider.get_highest_static_file_block(segment)
+            else {
+                continue
+            };
+            if highest_block < *range.start() {
+                continue
+            }
+
+            let to_delete = highest_block - *range.start() + 1;
+            let mut writer = static_file_provider.latest_writer(segment)?;
+            let prune_segment = if segment == StaticFileSegment::AccountChangeSets {
+                writer.prune_account_changesets(to_delete)?;
+                PruneSegment::AccountChangeSets
+            } else {
+                writer.prune_storage_changesets(to_delete)?;
+                PruneSegment::StorageChangeSets
+            };
+            unwind_prune_checkpoint(provider, prune_segment, range.start().checked_sub(1), None)?;
+        }
+
         // Prepare the input for post unwind commit hook, where an `ExExNotification` will be sent.
         if self.exex_manager_handle.has_exexs() {
             // Get the blocks for the unwound range.
@@ -456,6 +503,40 @@ where
     }
 }
 
+/// Writes the accounts and storage slots changed in the bundle to the hashed state tables.
+///
+/// The hashed state of snap synced nodes is read during execution, so it has to be up to date
+/// before the hashing stages run. The hashing stages overwrite it with the same values.
+fn write_hashed_state<DB: Database>(
+    provider: &DatabaseProviderRW<DB>,
+    bundle: &BundleState,
+) -> Result<(), StageError> {
+    let mut hashed_storages = provider.tx_ref().cursor_dup_write::<tables::HashedStorages>()?;
+    for (address, account) in &bundle.state {
+        if account.status.was_destroyed() &&
+            hashed_storages.seek_exact(keccak256(address))?.is_some()
+        {
+            hashed_storages.delete_current_duplicates()?;
+        }
+    }
+
+    provider.insert_account_for_hashing(
+        bundle
+            .state
+            .iter()
+            .map(|(address, account)| (*address, account.info.clone().map(Into::into))),
+    )?;
+    provider.insert_storage_for_hashing(bundle.state.iter().map(|(address, account)| {
+        let storage = account.storage.iter().map(|(slot, value)| StorageEntry {
+            key: B256::new(slot.to_be_bytes()),
+            value: value.present_value,
+        });
+        (*address, storage)
+    }))?;
+
+    Ok(())
+}
+
 fn execution_checkpoint(
     provider: &StaticFileProvider,
     start_block: BlockNumber,
@@ -708,7 +789,10 @@ mod tests {
     use alloy_rlp::Decodable;
     use assert_matches::assert_matches;
     use reth_chainspec::ChainSpecBuilder;
-    use reth_db_api::{models::AccountBeforeTx, transaction::DbTxMut};
+    use reth_db_api::{
+        models::{AccountBeforeTx, BlockAccountChangeSets, BlockStorageChangeSets},
+        transaction::DbTxMut,
+    };
     use reth_evm_ethereum::execute::EthExecutorProvider;
     use reth_execution_errors::BlockValidationError;
     use reth_primitives::{
@@ -716,10 +800,10 @@ mod tests {
         StorageEntry, B256, U256,
     };
     use reth_provider::{
-        test_utils::create_test_provider_factory, AccountReader, ReceiptProvider,
-        StaticFileProviderFactory,
+        test_utils::create_test_provider_factory, AccountReader, ChangeSetReader,
+        PruneCheckpointReader, PruneCheckpointWriter, ReceiptProvider, StaticFileProviderFactory,
     };
-    use reth_prune_types::{PruneMode, ReceiptsLogPruneConfig};
+    use reth_prune_types::{PruneCheckpoint, PruneMode, ReceiptsLogPruneConfig};
     use reth_stages_api::StageUnitCheckpoint;
     use std::collections::BTreeMap;
 
@@ -1181,6 +1265,126 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn unwind_changesets_in_static_files() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+        let mut genesis_rlp = hex!("f901faf901f5a00000000000000000000000000000000000000000000000000000000000000000a01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa045571b40ae66ca7480791bbb2887286e4e4c4b1b298b191c889d6959023a32eda056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421a056e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421b901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000808502540be400808000a00000000000000000000000000000000000000000000000000000000000000000880000000000000000c0c0").as_slice();
+        let genesis = SealedBlock::decode(&mut genesis_rlp).unwrap();
+        let mut block_rlp = hex!("f90262f901f9a075c371ba45999d87f4542326910a11af515897aebce5265d3f6acd1f1161f82fa01dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347942adc25665018aa1fe0e6bc666dac8fc2697ff9baa098f2dcd87c8ae4083e7017a05456c14eea4b1db2032126e27b3b1563d57d7cc0a08151d548273f6683169524b66ca9fe338b9ce42bc3540046c828fd939ae23bcba03f4e5c2ec5b2170b711d97ee755c160457bb58d8daa338e835ec02ae6860bbabb901000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000083020000018502540be40082a8798203e800a00000000000000000000000000000000000000000000000000000000000000000880000000000000000f863f861800a8405f5e10094100000000000000000000000000000000000000080801ba07e09e26678ed4fac08a249ebe8ed680bf9051a5e14ad223e4b2b9d26e0208f37a05f6e3f188e3e6eab7d7d3b6568f5eac7d687b08d307d3154ccd8c87b4630509bc0").as_slice();
+        let block = SealedBlock::decode(&mut block_rlp).unwrap();
+        provider.insert_historical_block(genesis.try_seal_with_senders().unwrap(), None).unwrap();
+        provider
+            .insert_historical_block(block.clone().try_seal_with_senders().unwrap(), None)
+            .unwrap();
+        provider
+            .static_file_provider()
+            .latest_writer(StaticFileSegment::Headers)
+            .unwrap()
+            .commit()
+            .unwrap();
+        {
+            let mut receipts_writer =
+                provider.static_file_provider().latest_writer(StaticFileSegment::Receipts).unwrap();
+            receipts_writer.increment_block(StaticFileSegment::Receipts, 0).unwrap();
+            receipts_writer.commit().unwrap();
+        }
+
+        let code = hex!("5a465a905090036002900360015500");
+        let code_hash = keccak256(code);
+        let acc1 = address!("1000000000000000000000000000000000000000");
+        let acc1_info = Account { nonce: 0, balance: U256::ZERO, bytecode_hash: Some(code_hash) };
+        let acc2 = address!("a94f5374fce5edbc8e2a8697c15331677e6ebf0b");
+        let acc2_info = Account {
+            nonce: 0,
+            balance: U256::from(0x3635c9adc5dea00000u128),
+            bytecode_hash: None,
+        };
+        let db_tx = provider.tx_ref();
+        db_tx.put::<tables::PlainAccountState>(acc1, acc1_info).unwrap();
+        db_tx.put::<tables::PlainAccountState>(acc2, acc2_info).unwrap();
+        db_tx.put::<tables::Bytecodes>(code_hash, Bytecode::new_raw(code.to_vec().into())).unwrap();
+        provider.commit().unwrap();
+
+        let provider = factory.provider_rw().unwrap();
+        let result =
+            stage().execute(&provider, ExecInput { target: Some(1), checkpoint: None }).unwrap();
+
+        // Move the changesets to static files and prune them from the database, like the static
+        // file producer and the pruner do
+        let static_file_provider = provider.static_file_provider();
+        {
+            let mut writer =
+                static_file_provider.latest_writer(StaticFileSegment::AccountChangeSets).unwrap();
+            for block_number in 0..=1 {
+                let changeset = provider.account_block_changeset(block_number).unwrap();
+                writer
+                    .append_account_changeset(block_number, BlockAccountChangeSets(changeset))
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+        {
+            let mut writer =
+                static_file_provider.latest_writer(StaticFileSegment::StorageChangeSets).unwrap();
+            for block_number in 0..=1 {
+                let changeset = provider
+                    .storage_changesets_range(block_number..=block_number)
+                    .unwrap()
+                    .into_iter()
+                    .map(|(block_address, entry)| (block_address.address(), entry))
+                    .collect::<Vec<_>>();
+                writer
+                    .append_storage_changeset(block_number, BlockStorageChangeSets(changeset))
+                    .unwrap();
+            }
+            writer.commit().unwrap();
+        }
+        assert!(!provider.storage_changesets_range(1..=1).unwrap().is_empty());
+        provider.tx_ref().clear::<tables::AccountChangeSets>().unwrap();
+        provider.tx_ref().clear::<tables::StorageChangeSets>().unwrap();
+        for segment in [PruneSegment::AccountChangeSets, PruneSegment::StorageChangeSets] {
+            provider
+                .save_prune_checkpoint(
+                    segment,
+                    PruneCheckpoint {
+                        block_number: Some(1),
+                        tx_number: None,
+                        prune_mode: PruneMode::Before(2),
+                    },
+                )
+                .unwrap();
+        }
+        provider.commit().unwrap();
+
+        let provider = factory.provider_rw().unwrap();
+        stage()
+            .unwind(
+                &provider,
+                UnwindInput { checkpoint: result.checkpoint, unwind_to: 0, bad_block: None },
+            )
+            .unwrap();
+
+        // The state is unwound with the changesets from static files
+        assert_eq!(provider.basic_account(acc1), Ok(Some(acc1_info)));
+        assert_eq!(provider.basic_account(acc2), Ok(Some(acc2_info)));
+        assert_eq!(provider.tx_ref().entries::<tables::PlainStorageState>().unwrap(), 0);
+
+        // The unwound changesets are pruned from static files, and the prune checkpoints are
+        // unwound, so that the changesets written to the database again are pruned
+        static_file_provider.commit().unwrap();
+        for (segment, prune_segment) in [
+            (StaticFileSegment::AccountChangeSets, PruneSegment::AccountChangeSets),
+            (StaticFileSegment::StorageChangeSets, PruneSegment::StorageChangeSets),
+        ] {
+            assert_eq!(static_file_provider.get_highest_static_file_block(segment), Some(0));
+            assert_eq!(
+                provider.get_prune_checkpoint(prune_segment).unwrap().unwrap().block_number,
+                Some(0)
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_selfdestruct() {
         let test_db = TestStageDB::default();
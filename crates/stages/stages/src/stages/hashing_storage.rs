@@ -1,3 +1,4 @@
+use super::ensure_unwind_above_snap_sync_pivot;
 use itertools::Itertools;
 use reth_config::config::{EtlConfig, HashingConfig};
 use reth_db::tables;
@@ -85,7 +86,11 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
         // account otherwise take changesets aggregate the sets and apply hashing to
         // AccountHashing table. Also, if we start from genesis, we need to hash from scratch, as
         // genesis accounts are not in changeset, along with their storages.
-        if to_block - from_block > self.clean_threshold || from_block == 1 {
+        //
+        // The plain state of snap synced nodes is incomplete, their hashed state is only updated
+        // from the changesets.
+        let snap_synced = provider.snap_sync_pivot()?.is_some();
+        if (to_block - from_block > self.clean_threshold && !snap_synced) || from_block == 1 {
             // clear table, load all accounts and hash it
             tx.clear::<tables::HashedStorages>()?;
 
@@ -167,6 +172,8 @@ impl<DB: Database> Stage<DB> for StorageHashingStage {
         provider: &DatabaseProviderRW<DB>,
         input: UnwindInput,
     ) -> Result<UnwindOutput, StageError> {
+        ensure_unwind_above_snap_sync_pivot(provider, input.unwind_to)?;
+
         let (range, unwind_progress, _) =
             input.unwind_block_range_with_threshold(self.commit_threshold);
 
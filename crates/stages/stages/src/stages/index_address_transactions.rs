@@ -0,0 +1,316 @@
+use super::load_history_indices;
+use reth_config::config::{EtlConfig, IndexAddressTransactionsConfig};
+use reth_db::{tables, BlockNumberList};
+use reth_db_api::{database::Database, models::ShardedKey, table::Decode, transaction::DbTxMut};
+use reth_etl::Collector;
+use reth_primitives::Address;
+use reth_provider::{
+    BlockReader, DatabaseProviderRW, HistoryWriter, PruneCheckpointReader, PruneCheckpointWriter,
+};
+use reth_prune_types::{PruneCheckpoint, PruneMode, PrunePurpose, PruneSegment};
+use reth_stages_api::{
+    ExecInput, ExecOutput, Stage, StageCheckpoint, StageError, StageId, UnwindInput, UnwindOutput,
+};
+use reth_storage_errors::provider::ProviderError;
+use tracing::info;
+
+/// Stage is indexing the transactions sent from, sent to or creating each address. For more
+/// information on index sharding take a look at [`tables::AddressTransactions`].
+///
+/// The index is not required by the node itself, so this stage is disabled by default.
+#[derive(Debug)]
+pub struct IndexAddressTransactionsStage {
+    /// The maximum number of transactions to process before pushing them to the
+    /// [`reth_etl::Collector`].
+    pub chunk_size: u64,
+    /// Pruning configuration.
+    pub prune_mode: Option<PruneMode>,
+    /// ETL configuration
+    pub etl_config: EtlConfig,
+}
+
+impl IndexAddressTransactionsStage {
+    /// Create new instance of [`IndexAddressTransactionsStage`].
+    pub const fn new(
+        config: IndexAddressTransactionsConfig,
+        etl_config: EtlConfig,
+        prune_mode: Option<PruneMode>,
+    ) -> Self {
+        Self { chunk_size: config.chunk_size, etl_config, prune_mode }
+    }
+}
+
+impl Default for IndexAddressTransactionsStage {
+    fn default() -> Self {
+        Self { chunk_size: 5_000_000, prune_mode: None, etl_config: EtlConfig::default() }
+    }
+}
+
+impl<DB: Database> Stage<DB> for IndexAddressTransactionsStage {
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        StageId::IndexAddressTransactions
+    }
+
+    /// Execute the stage.
+    fn execute(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        mut input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        if let Some((target_prunable_block, prune_mode)) = self
+            .prune_mode
+            .map(|mode| {
+                mode.prune_target_block(
+                    input.target(),
+                    PruneSegment::AddressTransactions,
+                    PrunePurpose::User,
+                )
+            })
+            .transpose()?
+            .flatten()
+        {
+            if target_prunable_block > input.checkpoint().block_number {
+                input.checkpoint = Some(StageCheckpoint::new(target_prunable_block));
+
+                // Save prune checkpoint only if we don't have one already.
+                // Otherwise, pruner may skip the unpruned range of blocks.
+                if provider.get_prune_checkpoint(PruneSegment::AddressTransactions)?.is_none() {
+                    let target_prunable_tx_number = provider
+                        .block_body_indices(target_prunable_block)?
+                        .ok_or(ProviderError::BlockBodyIndicesNotFound(target_prunable_block))?
+                        .last_tx_num();
+
+                    provider.save_prune_checkpoint(
+                        PruneSegment::AddressTransactions,
+                        PruneCheckpoint {
+                            block_number: Some(target_prunable_block),
+                            tx_number: Some(target_prunable_tx_number),
+                            prune_mode,
+                        },
+                    )?;
+                }
+            }
+        }
+
+        if input.target_reached() {
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
+        // On first sync we clear the table since it's faster to rebuild from scratch.
+        let first_sync = input.checkpoint().block_number == 0;
+        if first_sync {
+            provider.tx_ref().clear::<tables::AddressTransactions>()?;
+        }
+
+        let mut collector = Collector::new(self.etl_config.file_size, self.etl_config.dir.clone());
+
+        info!(target: "sync::stages::index_address_transactions::exec", ?first_sync, "Collecting indices");
+        loop {
+            let (tx_range, block_range, is_final_range) =
+                input.next_block_range_with_transaction_threshold(provider, self.chunk_size)?;
+
+            info!(target: "sync::stages::index_address_transactions::exec", ?tx_range, "Collecting address transactions");
+
+            for (address, tx_numbers) in provider.address_transactions_by_tx_range(tx_range)? {
+                let last = *tx_numbers.last().expect("at least one transaction per address");
+                collector.insert(
+                    ShardedKey::new(address, last),
+                    BlockNumberList::new_pre_sorted(tx_numbers),
+                )?;
+            }
+
+            input.checkpoint = Some(StageCheckpoint::new(*block_range.end()));
+
+            if is_final_range {
+                break
+            }
+        }
+
+        info!(target: "sync::stages::index_address_transactions::exec", "Loading indices into database");
+        load_history_indices::<_, tables::AddressTransactions, _>(
+            provider.tx_ref(),
+            collector,
+            first_sync,
+            ShardedKey::new,
+            ShardedKey::<Address>::decode,
+            |key| key.key,
+        )?;
+
+        Ok(ExecOutput { checkpoint: StageCheckpoint::new(input.target()), done: true })
+    }
+
+    /// Unwind the stage.
+    fn unwind(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        provider.unwind_address_transaction_indices(input.unwind_block_range())?;
+
+        Ok(UnwindOutput { checkpoint: StageCheckpoint::new(input.unwind_to) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        stage_test_suite_ext, ExecuteStageTestRunner, StageTestRunner, StorageKind,
+        TestRunnerError, TestStageDB, UnwindStageTestRunner,
+    };
+    use assert_matches::assert_matches;
+    use reth_db_api::{cursor::DbCursorRO, transaction::DbTx};
+    use reth_primitives::{BlockNumber, SealedBlock, B256};
+    use reth_provider::{AccountHistoryReader, TransactionsProvider};
+    use reth_testing_utils::{generators, generators::random_block_range};
+
+    // Implement stage test suite.
+    stage_test_suite_ext!(IndexAddressTransactionsTestRunner, index_address_transactions);
+
+    #[tokio::test]
+    async fn execute_in_chunks() {
+        let (previous_stage, stage_progress) = (100, 0);
+        let mut rng = generators::rng();
+
+        // Set up the runner with a chunk size lower than the number of transactions
+        let mut runner = IndexAddressTransactionsTestRunner::default();
+        runner.chunk_size = 10;
+        let input = ExecInput {
+            target: Some(previous_stage),
+            checkpoint: Some(StageCheckpoint::new(stage_progress)),
+        };
+
+        let blocks =
+            random_block_range(&mut rng, stage_progress..=previous_stage, B256::ZERO, 1..3);
+        runner.db.insert_blocks(blocks.iter(), StorageKind::Static).expect("insert blocks");
+
+        let rx = runner.execute(input);
+
+        // Assert the successful result
+        let result = rx.await.unwrap();
+        assert_matches!(
+            result,
+            Ok(ExecOutput { checkpoint: StageCheckpoint { block_number, .. }, done: true })
+                if block_number == previous_stage
+        );
+
+        // Validate the stage execution
+        assert!(runner.validate_execution(input, result.ok()).is_ok(), "execution validation");
+    }
+
+    struct IndexAddressTransactionsTestRunner {
+        db: TestStageDB,
+        chunk_size: u64,
+        etl_config: EtlConfig,
+        prune_mode: Option<PruneMode>,
+    }
+
+    impl Default for IndexAddressTransactionsTestRunner {
+        fn default() -> Self {
+            Self {
+                db: TestStageDB::default(),
+                chunk_size: 1000,
+                etl_config: EtlConfig::default(),
+                prune_mode: None,
+            }
+        }
+    }
+
+    impl IndexAddressTransactionsTestRunner {
+        /// # Panics
+        ///
+        /// If there are any transactions in the [`tables::AddressTransactions`] table above the
+        /// last transaction of the given block number.
+        fn ensure_no_index_by_block(&self, number: BlockNumber) -> Result<(), TestRunnerError> {
+            let body = self.db.factory.provider()?.block_body_indices(number)?;
+            let shards = self.db.table::<tables::AddressTransactions>()?;
+            match body {
+                Some(body) => assert!(shards
+                    .iter()
+                    .all(|(_, list)| list.iter().all(|tx| tx < body.next_tx_num()))),
+                None => assert!(shards.is_empty()),
+            }
+
+            Ok(())
+        }
+    }
+
+    impl StageTestRunner for IndexAddressTransactionsTestRunner {
+        type S = IndexAddressTransactionsStage;
+
+        fn db(&self) -> &TestStageDB {
+            &self.db
+        }
+
+        fn stage(&self) -> Self::S {
+            IndexAddressTransactionsStage {
+                chunk_size: self.chunk_size,
+                etl_config: self.etl_config.clone(),
+                prune_mode: self.prune_mode,
+            }
+        }
+    }
+
+    impl ExecuteStageTestRunner for IndexAddressTransactionsTestRunner {
+        type Seed = Vec<SealedBlock>;
+
+        fn seed_execution(&mut self, input: ExecInput) -> Result<Self::Seed, TestRunnerError> {
+            let stage_progress = input.checkpoint().block_number;
+            let end = input.target();
+            let mut rng = generators::rng();
+
+            let blocks = random_block_range(&mut rng, stage_progress + 1..=end, B256::ZERO, 0..2);
+            self.db.insert_blocks(blocks.iter(), StorageKind::Static)?;
+            Ok(blocks)
+        }
+
+        fn validate_execution(
+            &self,
+            input: ExecInput,
+            output: Option<ExecOutput>,
+        ) -> Result<(), TestRunnerError> {
+            match output {
+                Some(output) => {
+                    let provider = self.db.factory.provider()?;
+
+                    let start_block = input.next_block();
+                    let end_block = output.checkpoint.block_number;
+
+                    if start_block > end_block {
+                        return Ok(())
+                    }
+
+                    let mut body_cursor =
+                        provider.tx_ref().cursor_read::<tables::BlockBodyIndices>()?;
+                    body_cursor.seek_exact(start_block)?;
+
+                    while let Some((_, body)) = body_cursor.next()? {
+                        for tx_id in body.tx_num_range() {
+                            let transaction =
+                                provider.transaction_by_id(tx_id)?.expect("no transaction entry");
+                            let sender = transaction.recover_signer().expect("valid signature");
+                            let to = transaction
+                                .to()
+                                .unwrap_or_else(|| sender.create(transaction.nonce()));
+                            for address in [sender, to] {
+                                assert_eq!(
+                                    provider.address_transactions(address, tx_id..=tx_id)?,
+                                    vec![tx_id]
+                                );
+                            }
+                        }
+                    }
+                }
+                None => self.ensure_no_index_by_block(input.checkpoint().block_number)?,
+            };
+            Ok(())
+        }
+    }
+
+    impl UnwindStageTestRunner for IndexAddressTransactionsTestRunner {
+        fn validate_unwind(&self, input: UnwindInput) -> Result<(), TestRunnerError> {
+            self.ensure_no_index_by_block(input.unwind_to)
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use super::ensure_unwind_above_snap_sync_pivot;
 use reth_codecs::Compact;
 use reth_consensus::ConsensusError;
 use reth_db::tables;
@@ -14,7 +15,10 @@ use reth_stages_api::{
     BlockErrorKind, EntitiesCheckpoint, ExecInput, ExecOutput, MerkleCheckpoint, Stage,
     StageCheckpoint, StageError, StageId, UnwindInput, UnwindOutput,
 };
-use reth_trie::{IntermediateStateRootState, StateRoot, StateRootProgress, StoredSubNode};
+use reth_trie::{
+    prefix_set::PrefixSetLoader, IntermediateStateRootState, StateRoot, StateRootProgress,
+    StoredSubNode,
+};
 use std::fmt::Debug;
 use tracing::*;
 
@@ -287,6 +291,8 @@ impl<DB: Database> Stage<DB> for MerkleStage {
         provider: &DatabaseProviderRW<DB>,
         input: UnwindInput,
     ) -> Result<UnwindOutput, StageError> {
+        ensure_unwind_above_snap_sync_pivot(provider, input.unwind_to)?;
+
         let tx = provider.tx_ref();
         let range = input.unwind_block_range();
         if matches!(self, Self::Execution { .. }) {
@@ -315,7 +321,14 @@ impl<DB: Database> Stage<DB> for MerkleStage {
 
         // Unwind trie only if there are transitions
         if !range.is_empty() {
-            let (block_root, updates) = StateRoot::incremental_root_with_updates(tx, range)
+            // The changesets of the unwound blocks may have been moved to static files already.
+            let prefix_sets = PrefixSetLoader::new(tx).load_changesets(
+                provider.account_changesets_range(range.clone())?.into_iter().map(Ok),
+                provider.storage_changesets_range(range)?.into_iter().map(Ok),
+            )?;
+            let (block_root, updates) = StateRoot::from_tx(tx)
+                .with_prefix_sets(prefix_sets)
+                .root_with_updates()
                 .map_err(|e| StageError::Fatal(Box::new(e)))?;
 
             // Validate the calculated state root
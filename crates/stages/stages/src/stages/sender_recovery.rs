@@ -1,3 +1,4 @@
+use super::unwind_prune_checkpoint;
 use reth_config::config::SenderRecoveryConfig;
 use reth_consensus::ConsensusError;
 use reth_db::{static_file::TransactionMask, tables, RawValue};
@@ -8,8 +9,8 @@ use reth_db_api::{
 };
 use reth_primitives::{Address, StaticFileSegment, TransactionSignedNoHash, TxNumber};
 use reth_provider::{
-    BlockReader, DatabaseProviderRW, HeaderProvider, ProviderError, PruneCheckpointReader,
-    StatsReader,
+    providers::StaticFileWriter, BlockReader, DatabaseProviderRW, HeaderProvider, ProviderError,
+    PruneCheckpointReader, StatsReader,
 };
 use reth_prune_types::PruneSegment;
 use reth_stages_api::{
@@ -123,6 +124,24 @@ impl<DB: Database> Stage<DB> for SenderRecoveryStage {
             .last_tx_num();
         provider.unwind_table_by_num::<tables::TransactionSenders>(latest_tx_id)?;
 
+        // Senders which have already been moved to static files have to be pruned from them too.
+        let static_file_provider = provider.static_file_provider();
+        if let Some(highest_tx) =
+            static_file_provider.get_highest_static_file_tx(StaticFileSegment::TransactionSenders)
+        {
+            if highest_tx > latest_tx_id {
+                static_file_provider
+                    .latest_writer(StaticFileSegment::TransactionSenders)?
+                    .prune_transaction_senders(highest_tx - latest_tx_id, unwind_to)?;
+                unwind_prune_checkpoint(
+                    provider,
+                    PruneSegment::SenderRecovery,
+                    Some(unwind_to),
+                    Some(latest_tx_id),
+                )?;
+            }
+        }
+
         Ok(UnwindOutput {
             checkpoint: StageCheckpoint::new(unwind_to)
                 .with_entities_stage_checkpoint(stage_checkpoint(provider)?),
@@ -245,11 +264,16 @@ fn stage_checkpoint<DB: Database>(
         .get_prune_checkpoint(PruneSegment::SenderRecovery)?
         .and_then(|checkpoint| checkpoint.tx_number)
         .unwrap_or_default();
+    // Senders that were moved to static files are pruned from the `TransactionSenders` table with
+    // the same prune checkpoint, so they're counted only once.
+    let static_file_entries =
+        provider.static_file_provider().count_entries::<tables::TransactionSenders>()? as u64;
     Ok(EntitiesCheckpoint {
         // If `TransactionSenders` table was pruned, we will have a number of entries in it not
         // matching the actual number of processed transactions. To fix that, we add the
         // number of pruned `TransactionSenders` entries.
-        processed: provider.count_entries::<tables::TransactionSenders>()? as u64 + pruned_entries,
+        processed: provider.tx_ref().entries::<tables::TransactionSenders>()? as u64 +
+            pruned_entries.max(static_file_entries),
         // Count only static files entries. If we count the database entries too, we may have
         // duplicates. We're sure that the static files have all entries that database has,
         // because we run the `StaticFileProducer` before starting the pipeline.
@@ -282,8 +306,8 @@ mod tests {
     use reth_db_api::cursor::DbCursorRO;
     use reth_primitives::{BlockNumber, SealedBlock, TransactionSigned, B256};
     use reth_provider::{
-        providers::StaticFileWriter, PruneCheckpointWriter, StaticFileProviderFactory,
-        TransactionsProvider,
+        providers::StaticFileWriter, PruneCheckpointReader, PruneCheckpointWriter,
+        StaticFileProviderFactory, TransactionsProvider,
     };
     use reth_prune_types::{PruneCheckpoint, PruneMode};
     use reth_stages_api::StageUnitCheckpoint;
@@ -482,6 +506,100 @@ mod tests {
         );
     }
 
+    #[test]
+    fn senders_in_static_files() {
+        let db = TestStageDB::default();
+        let mut rng = generators::rng();
+
+        let blocks = random_block_range(&mut rng, 0..=100, B256::ZERO, 1..10);
+        db.insert_blocks(blocks.iter(), StorageKind::Static).expect("insert blocks");
+
+        let (max_static_file_block, max_processed_block) = (50, 70);
+        let mut senders = Vec::new();
+        {
+            let static_file_provider = db.factory.static_file_provider();
+            let mut writer =
+                static_file_provider.latest_writer(StaticFileSegment::TransactionSenders).unwrap();
+            for block in &blocks[..=max_processed_block] {
+                if block.number <= max_static_file_block {
+                    writer
+                        .increment_block(StaticFileSegment::TransactionSenders, block.number)
+                        .unwrap();
+                }
+                for transaction in &block.body {
+                    let sender = (senders.len() as u64, transaction.recover_signer().unwrap());
+                    if block.number <= max_static_file_block {
+                        writer.append_transaction_sender(sender.0, sender.1).unwrap();
+                    } else {
+                        db.insert_transaction_senders([sender]).unwrap();
+                    }
+                    senders.push(sender);
+                }
+            }
+            writer.commit().unwrap();
+        }
+
+        // The pruner deletes the senders that were moved to static files from the database
+        let provider = db.factory.provider_rw().unwrap();
+        let last_static_file_tx =
+            provider.block_body_indices(max_static_file_block).unwrap().unwrap().last_tx_num();
+        provider
+            .save_prune_checkpoint(
+                PruneSegment::SenderRecovery,
+                PruneCheckpoint {
+                    block_number: Some(max_static_file_block),
+                    tx_number: Some(last_static_file_tx),
+                    prune_mode: PruneMode::Before(max_static_file_block + 1),
+                },
+            )
+            .unwrap();
+        provider.commit().unwrap();
+
+        // Senders are read from static files and the database
+        let provider = db.factory.provider_rw().unwrap();
+        assert_eq!(
+            provider.senders_by_tx_range(0..senders.len() as u64).unwrap(),
+            senders.iter().map(|(_, sender)| *sender).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            provider.transaction_sender(last_static_file_tx).unwrap(),
+            Some(senders[last_static_file_tx as usize].1)
+        );
+        assert_eq!(stage_checkpoint(&provider).unwrap().processed, senders.len() as u64);
+
+        // Unwinding below the static files prunes them and the prune checkpoint
+        let unwind_to = 30;
+        SenderRecoveryStage::default()
+            .unwind(
+                &provider,
+                UnwindInput {
+                    checkpoint: StageCheckpoint::new(max_processed_block as BlockNumber),
+                    unwind_to,
+                    bad_block: None,
+                },
+            )
+            .unwrap();
+        provider.static_file_provider().commit().unwrap();
+
+        let last_tx = provider.block_body_indices(unwind_to).unwrap().unwrap().last_tx_num();
+        assert_eq!(
+            provider
+                .static_file_provider()
+                .get_highest_static_file_tx(StaticFileSegment::TransactionSenders),
+            Some(last_tx)
+        );
+        assert_eq!(provider.tx_ref().entries::<tables::TransactionSenders>().unwrap(), 0);
+        assert_eq!(
+            provider.get_prune_checkpoint(PruneSegment::SenderRecovery).unwrap(),
+            Some(PruneCheckpoint {
+                block_number: Some(unwind_to),
+                tx_number: Some(last_tx),
+                prune_mode: PruneMode::Before(max_static_file_block + 1),
+            })
+        );
+        assert_eq!(stage_checkpoint(&provider).unwrap().processed, last_tx + 1);
+    }
+
     struct SenderRecoveryTestRunner {
         db: TestStageDB,
         threshold: u64,
@@ -0,0 +1,1652 @@
+use alloy_rlp::Decodable;
+use futures_util::{stream::FuturesUnordered, FutureExt, StreamExt};
+use reth_config::config::SnapSyncConfig;
+use reth_db::tables;
+use reth_db_api::{
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+    database::Database,
+    transaction::{DbTx, DbTxMut},
+};
+use reth_eth_wire_types::snap::{
+    AccountRange, ByteCodes, GetAccountRange, GetByteCodes, GetStorageRanges, GetTrieNodes,
+    StorageRanges, TrieNodes,
+};
+use reth_network_p2p::{error::PeerRequestResult, snap::client::SnapClient};
+use reth_network_peers::WithPeerId;
+use reth_network_snap::SlimAccount;
+use reth_primitives::{
+    constants::EMPTY_ROOT_HASH, keccak256, Account, BlockNumber, Bytecode, Bytes, StorageEntry,
+    B256, KECCAK_EMPTY, U256,
+};
+use reth_provider::{
+    DatabaseProviderRW, HeaderProvider, ProviderError, StageCheckpointReader, StageCheckpointWriter,
+};
+use reth_stages_api::{
+    ExecInput, ExecOutput, Stage, StageCheckpoint, StageError, StageId, UnwindInput, UnwindOutput,
+};
+use reth_trie::{
+    prefix_set::{PrefixSetMut, TriePrefixSets},
+    proof::Proof,
+    HashBuilder, Nibbles, StateRoot, TrieAccount,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tracing::*;
+
+/// The number of accounts whose storage is requested at once.
+const STORAGE_BATCH_SIZE: usize = 128;
+
+/// The number of bytecodes requested at once.
+const BYTECODE_BATCH_SIZE: usize = 64;
+
+/// The number of trie nodes requested at once while healing.
+const TRIE_NODE_BATCH_SIZE: usize = 128;
+
+/// The number of consecutive empty responses after which the pivot state is considered to be no
+/// longer served by peers.
+const STALE_PIVOT_THRESHOLD: usize = 32;
+
+/// The stages whose work is done by the snap sync, their checkpoints are moved to the pivot.
+const SNAP_SYNCED_STAGES: [StageId; 5] = [
+    StageId::Execution,
+    StageId::MerkleUnwind,
+    StageId::AccountHashing,
+    StageId::StorageHashing,
+    StageId::MerkleExecute,
+];
+
+/// The snap sync stage downloads the state at the sync target from peers over the `snap`
+/// protocol, instead of executing all blocks from genesis.
+///
+/// The stage only runs on a fresh node, i.e. if no block has been executed yet and the sync target
+/// is at least [`SnapSyncConfig::min_distance`] blocks ahead of genesis and after the Cancun
+/// hardfork. Otherwise it is a no-op.
+///
+/// The sync target is used as the pivot block. The stage
+///
+/// 1. downloads all account ranges, storage ranges and bytecodes of the pivot state,
+/// 2. computes the state root and compares it to the root of the pivot header,
+/// 3. heals the trie if the roots don't match, by comparing the trie nodes of peers with the local
+///    trie and downloading the subtries that differ again.
+///
+/// Once the roots match, the checkpoints of the stages that derive the state (execution, hashing
+/// and merkle) are moved to the pivot, so the pipeline continues with regular staged sync from the
+/// pivot.
+///
+/// Account and storage ranges are verified with the boundary proofs of the responses against the
+/// state root and the storage roots, trie nodes are verified against the hashes referenced by their
+/// parent nodes. Peers sending invalid responses are reported.
+///
+/// If peers stop serving the pivot state before the download is complete, or the roots still don't
+/// match after [`SnapSyncConfig::max_heal_rounds`] rounds of healing, the stage finishes without
+/// progress and continues with the next sync target as the pivot. The state that was already
+/// downloaded is kept and repaired by the healing phase.
+///
+/// # Tables
+///
+/// The downloaded state is written to
+///
+/// - [`HashedAccounts`][reth_db::tables::HashedAccounts]
+/// - [`HashedStorages`][reth_db::tables::HashedStorages]
+/// - [`Bytecodes`][reth_db::tables::Bytecodes]
+/// - [`AccountsTrie`][reth_db::tables::AccountsTrie] and
+///   [`StoragesTrie`][reth_db::tables::StoragesTrie]
+///
+/// The `snap` protocol only serves hashed keys, so the plain state can't be restored. Once the
+/// roots match, the pivot is recorded with [`DatabaseProvider::save_snap_sync_pivot`]. From then
+/// on the state is read from the hashed state tables, which the
+/// [`ExecutionStage`](crate::stages::ExecutionStage) keeps up to date. The plain state only
+/// contains the accounts and storage slots that changed after the pivot.
+///
+/// The pivot has to be after the Cancun hardfork: before it, `SELFDESTRUCT` wipes the storage of
+/// existing contracts, whose slots are unknown without the plain state.
+///
+/// The state before the pivot is not available, the stages that derive the state can't be unwound
+/// below it.
+///
+/// [`DatabaseProvider::save_snap_sync_pivot`]: reth_provider::DatabaseProvider::save_snap_sync_pivot
+#[derive(Debug)]
+pub struct SnapSyncStage<C> {
+    /// The client used to download the state.
+    client: C,
+    /// The stage configuration.
+    config: SnapSyncConfig,
+    /// The snap sync in progress, if any.
+    sync: Option<SnapSync>,
+}
+
+impl<C> SnapSyncStage<C> {
+    /// Create new instance of [`SnapSyncStage`].
+    pub const fn new(client: C, config: SnapSyncConfig) -> Self {
+        Self { client, config, sync: None }
+    }
+
+    /// Returns `true` if the node is fresh and the sync target is far enough ahead to be snap
+    /// synced.
+    fn should_snap_sync<DB: Database>(
+        &self,
+        provider: &DatabaseProviderRW<DB>,
+        input: ExecInput,
+    ) -> Result<bool, StageError> {
+        let executed =
+            provider.get_stage_checkpoint(StageId::Execution)?.unwrap_or_default().block_number;
+        if input.checkpoint().block_number != 0 ||
+            executed != 0 ||
+            input.target() < self.config.min_distance
+        {
+            return Ok(false)
+        }
+
+        let pivot = provider
+            .header_by_number(input.target())?
+            .ok_or_else(|| ProviderError::HeaderNotFound(input.target().into()))?;
+        Ok(provider.chain_spec().is_cancun_active_at_timestamp(pivot.timestamp))
+    }
+}
+
+impl<C, DB> Stage<DB> for SnapSyncStage<C>
+where
+    C: SnapClient + 'static,
+    DB: Database,
+{
+    /// Return the id of the stage
+    fn id(&self) -> StageId {
+        StageId::SnapSync
+    }
+
+    fn poll_execute_ready(
+        &mut self,
+        cx: &mut Context<'_>,
+        _input: ExecInput,
+    ) -> Poll<Result<(), StageError>> {
+        let Some(sync) = &mut self.sync else { return Poll::Ready(Ok(())) };
+
+        loop {
+            if !sync.is_stale() {
+                while sync.inflight.len() < self.config.max_concurrent_requests {
+                    let Some(task) = sync.pending.pop_front() else { break };
+                    sync.inflight.push(send_task(
+                        &self.client,
+                        sync.root,
+                        self.config.response_bytes,
+                        task,
+                    ));
+                }
+            }
+
+            if sync.buffered >= self.config.commit_threshold {
+                return Poll::Ready(Ok(()))
+            }
+
+            match ready!(sync.inflight.poll_next_unpin(cx)) {
+                Some((task, root, Ok(response))) => {
+                    sync.buffered += response.data().len().max(1) as u64;
+                    sync.responses.push((task, root, response));
+                }
+                Some((task, _, Err(error))) => {
+                    debug!(target: "sync::stages::snap_sync", %error, ?task, "Snap request failed");
+                    sync.pending.push_back(task);
+                }
+                None => return Poll::Ready(Ok(())),
+            }
+        }
+    }
+
+    /// Write the downloaded state into the database.
+    fn execute(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: ExecInput,
+    ) -> Result<ExecOutput, StageError> {
+        if input.target_reached() {
+            return Ok(ExecOutput::done(input.checkpoint()))
+        }
+
+        let Some(sync) = &mut self.sync else {
+            if !self.should_snap_sync(provider, input)? {
+                return Ok(ExecOutput::done(StageCheckpoint::new(input.target())))
+            }
+
+            info!(target: "sync::stages::snap_sync", pivot = input.target(), "Starting snap sync");
+            self.sync =
+                Some(SnapSync::new(provider, input.target(), self.config.max_concurrent_requests)?);
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+        };
+
+        if sync.pivot != input.target() {
+            sync.set_pivot(provider, input.target())?;
+        }
+
+        for (task, root, response) in std::mem::take(&mut sync.responses) {
+            let (peer_id, response) = response.split();
+            if !sync.on_response(provider, task.clone(), root, response)? {
+                debug!(target: "sync::stages::snap_sync", ?peer_id, ?task, "Bad snap response");
+                self.client.report_bad_message(peer_id);
+                sync.pending.push_back(task);
+            }
+        }
+        sync.buffered = 0;
+        sync.flush_batches();
+
+        let downloading = !sync.inflight.is_empty() || !sync.pending.is_empty();
+        if sync.is_stale() && sync.inflight.is_empty() {
+            info!(target: "sync::stages::snap_sync", pivot = sync.pivot, "Pivot state is no longer served by peers, waiting for a new sync target");
+            sync.stale_responses = 0;
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: true })
+        }
+        if downloading {
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+        }
+
+        let root = sync.state_root(provider)?;
+        if root == sync.root {
+            info!(target: "sync::stages::snap_sync", pivot = sync.pivot, ?root, "Snap sync complete");
+            let pivot = sync.pivot;
+            self.sync = None;
+            provider.save_snap_sync_pivot(pivot)?;
+            for stage_id in SNAP_SYNCED_STAGES {
+                provider.save_stage_checkpoint(stage_id, StageCheckpoint::new(pivot))?;
+            }
+            provider.save_stage_checkpoint_progress(StageId::MerkleExecute, vec![])?;
+            return Ok(ExecOutput::done(StageCheckpoint::new(pivot)))
+        }
+
+        sync.heal_rounds += 1;
+        if sync.heal_rounds > self.config.max_heal_rounds {
+            // the healing is continued against the state of the next sync target
+            warn!(target: "sync::stages::snap_sync", pivot = sync.pivot, rounds = self.config.max_heal_rounds, "State root still doesn't match after healing, waiting for a new sync target");
+            sync.heal_rounds = 0;
+            return Ok(ExecOutput { checkpoint: input.checkpoint(), done: true })
+        }
+        info!(target: "sync::stages::snap_sync", got = ?root, expected = ?sync.root, round = sync.heal_rounds, "State root mismatch, healing trie");
+        sync.pending
+            .push_back(SnapTask::TrieNodes { paths: vec![(Nibbles::default(), sync.root)] });
+
+        Ok(ExecOutput { checkpoint: input.checkpoint(), done: false })
+    }
+
+    /// The downloaded state can't be unwound, the stages building on top of it unwind their
+    /// changes down to the pivot.
+    fn unwind(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        input: UnwindInput,
+    ) -> Result<UnwindOutput, StageError> {
+        ensure_unwind_above_snap_sync_pivot(provider, input.unwind_to)?;
+        Ok(UnwindOutput { checkpoint: StageCheckpoint::new(input.unwind_to) })
+    }
+}
+
+/// Errors of the [`SnapSyncStage`].
+#[derive(Debug, thiserror::Error)]
+pub enum SnapSyncError {
+    /// The state was requested to be unwound below the pivot, which has no changesets.
+    #[error("can't unwind the snap synced state below the pivot {pivot} to {unwind_to}")]
+    UnwindBelowPivot {
+        /// The snap sync pivot.
+        pivot: BlockNumber,
+        /// The requested unwind target.
+        unwind_to: BlockNumber,
+    },
+}
+
+/// Returns an error if the state was snap synced and the unwind target is below the pivot.
+///
+/// There are no changesets before the pivot, so the stages that derive the state can't be unwound
+/// below it.
+pub(crate) fn ensure_unwind_above_snap_sync_pivot<DB: Database>(
+    provider: &DatabaseProviderRW<DB>,
+    unwind_to: BlockNumber,
+) -> Result<(), StageError> {
+    match provider.snap_sync_pivot()? {
+        Some(pivot) if unwind_to < pivot => {
+            Err(StageError::Fatal(SnapSyncError::UnwindBelowPivot { pivot, unwind_to }.into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A unit of work of the snap sync.
+#[derive(Debug, Clone)]
+enum SnapTask {
+    /// Download the accounts in the range.
+    Accounts { origin: B256, limit: B256 },
+    /// Download the storage of the accounts, starting at `origin` for the first account. The
+    /// accounts are given by their hashed address and storage root.
+    Storages { accounts: Vec<(B256, B256)>, origin: B256 },
+    /// Download the bytecodes.
+    ByteCodes { hashes: Vec<B256> },
+    /// Download the account trie nodes at the paths, to compare them with the local trie. The
+    /// paths are given together with the hash of the node referenced by the parent node.
+    TrieNodes { paths: Vec<(Nibbles, B256)> },
+}
+
+/// A response to a [`SnapTask`].
+#[derive(Debug)]
+enum SnapResponse {
+    AccountRange(AccountRange),
+    StorageRanges(StorageRanges),
+    ByteCodes(ByteCodes),
+    TrieNodes(TrieNodes),
+}
+
+impl SnapResponse {
+    /// Returns the number of state entries in the response.
+    fn len(&self) -> usize {
+        match self {
+            Self::AccountRange(response) => response.accounts.len(),
+            Self::StorageRanges(response) => response.slots.iter().map(Vec::len).sum(),
+            Self::ByteCodes(response) => response.codes.len(),
+            Self::TrieNodes(response) => response.nodes.len(),
+        }
+    }
+}
+
+type SnapTaskFut =
+    Pin<Box<dyn Future<Output = (SnapTask, B256, PeerRequestResult<SnapResponse>)> + Send + Sync>>;
+
+/// Sends the request for the task, the response is returned together with the requested state
+/// root.
+fn send_task<C: SnapClient>(
+    client: &C,
+    root_hash: B256,
+    response_bytes: u64,
+    task: SnapTask,
+) -> SnapTaskFut {
+    let fut: Pin<Box<dyn Future<Output = PeerRequestResult<SnapResponse>> + Send + Sync>> =
+        match &task {
+            SnapTask::Accounts { origin, limit } => Box::pin(
+                client
+                    .get_account_range(GetAccountRange {
+                        request_id: 0,
+                        root_hash,
+                        starting_hash: *origin,
+                        limit_hash: *limit,
+                        response_bytes,
+                    })
+                    .map(|res| res.map(|res| res.map(SnapResponse::AccountRange))),
+            ),
+            SnapTask::Storages { accounts, origin } => Box::pin(
+                client
+                    .get_storage_ranges(GetStorageRanges {
+                        request_id: 0,
+                        root_hash,
+                        account_hashes: accounts.iter().map(|(hash, _)| *hash).collect(),
+                        starting_hash: Bytes::copy_from_slice(origin.as_slice()),
+                        limit_hash: Bytes::new(),
+                        response_bytes,
+                    })
+                    .map(|res| res.map(|res| res.map(SnapResponse::StorageRanges))),
+            ),
+            SnapTask::ByteCodes { hashes } => Box::pin(
+                client
+                    .get_byte_codes(GetByteCodes {
+                        request_id: 0,
+                        hashes: hashes.clone(),
+                        response_bytes,
+                    })
+                    .map(|res| res.map(|res| res.map(SnapResponse::ByteCodes))),
+            ),
+            SnapTask::TrieNodes { paths } => Box::pin(
+                client
+                    .get_trie_nodes(GetTrieNodes {
+                        request_id: 0,
+                        root_hash,
+                        paths: paths
+                            .iter()
+                            .map(|(path, _)| {
+                                vec![Bytes::copy_from_slice(&path.encode_path_leaf(false))]
+                            })
+                            .collect(),
+                        response_bytes,
+                    })
+                    .map(|res| res.map(|res| res.map(SnapResponse::TrieNodes))),
+            ),
+        };
+    Box::pin(fut.map(move |res| (task, root_hash, res)))
+}
+
+/// The progress of a snap sync to a pivot block.
+struct SnapSync {
+    /// The pivot block.
+    pivot: BlockNumber,
+    /// The state root of the pivot block.
+    root: B256,
+    /// Tasks that have not been requested yet.
+    pending: VecDeque<SnapTask>,
+    /// Requests in flight.
+    inflight: FuturesUnordered<SnapTaskFut>,
+    /// Responses that have not been written yet, together with the requested state root.
+    responses: Vec<(SnapTask, B256, WithPeerId<SnapResponse>)>,
+    /// The number of state entries in the buffered responses.
+    buffered: u64,
+    /// Accounts whose storage will be requested with the next storage task, with their storage
+    /// roots.
+    storage_batch: Vec<(B256, B256)>,
+    /// Bytecodes that will be requested with the next bytecode task.
+    code_batch: Vec<B256>,
+    /// Trie nodes that will be requested with the next trie node task.
+    trie_node_batch: Vec<(Nibbles, B256)>,
+    /// Hashed addresses that changed since the trie was computed.
+    touched: PrefixSetMut,
+    /// Whether the trie tables have been computed, subsequent state root computations are
+    /// incremental.
+    trie_computed: bool,
+    /// The number of healing rounds.
+    heal_rounds: usize,
+    /// The number of consecutive empty responses.
+    stale_responses: usize,
+}
+
+impl fmt::Debug for SnapSync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SnapSync")
+            .field("pivot", &self.pivot)
+            .field("root", &self.root)
+            .field("pending", &self.pending.len())
+            .field("inflight", &self.inflight.len())
+            .field("heal_rounds", &self.heal_rounds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SnapSync {
+    /// Starts a new snap sync to the pivot, the hash space is split into `ranges` account ranges.
+    ///
+    /// This clears the existing state, which is replaced by the downloaded state.
+    fn new<DB: Database>(
+        provider: &DatabaseProviderRW<DB>,
+        pivot: BlockNumber,
+        ranges: usize,
+    ) -> Result<Self, StageError> {
+        let tx = provider.tx_ref();
+        tx.clear::<tables::PlainAccountState>()?;
+        tx.clear::<tables::PlainStorageState>()?;
+        tx.clear::<tables::HashedAccounts>()?;
+        tx.clear::<tables::HashedStorages>()?;
+        tx.clear::<tables::AccountsTrie>()?;
+        tx.clear::<tables::StoragesTrie>()?;
+
+        let ranges = ranges.max(1) as u64;
+        let step = U256::MAX / U256::from(ranges);
+        let pending = (0..ranges)
+            .map(|idx| {
+                let origin = step * U256::from(idx);
+                let limit = if idx + 1 == ranges {
+                    U256::MAX
+                } else {
+                    step * U256::from(idx + 1) - U256::from(1)
+                };
+                SnapTask::Accounts {
+                    origin: B256::new(origin.to_be_bytes()),
+                    limit: B256::new(limit.to_be_bytes()),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            pivot,
+            root: pivot_root(provider, pivot)?,
+            pending,
+            inflight: Default::default(),
+            responses: Vec::new(),
+            buffered: 0,
+            storage_batch: Vec::new(),
+            code_batch: Vec::new(),
+            trie_node_batch: Vec::new(),
+            touched: PrefixSetMut::default(),
+            trie_computed: false,
+            heal_rounds: 0,
+            stale_responses: 0,
+        })
+    }
+
+    /// Moves the sync to a new pivot, the state downloaded so far is healed once the download is
+    /// complete.
+    ///
+    /// The storage roots and trie node hashes of the pending tasks belong to the old pivot state.
+    /// The accounts of the storage tasks are downloaded again to get their new storage roots, and
+    /// the healing restarts at the root once the download is complete.
+    fn set_pivot<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        pivot: BlockNumber,
+    ) -> Result<(), StageError> {
+        info!(target: "sync::stages::snap_sync", old = self.pivot, new = pivot, "Moving snap sync pivot");
+        self.root = pivot_root(provider, pivot)?;
+        self.pivot = pivot;
+        self.heal_rounds = 0;
+        self.stale_responses = 0;
+
+        self.trie_node_batch.clear();
+        let storage_batch = std::mem::take(&mut self.storage_batch);
+        let pending = std::mem::take(&mut self.pending);
+        for task in pending {
+            self.requeue_for_pivot(task);
+        }
+        self.requeue_for_pivot(SnapTask::Storages { accounts: storage_batch, origin: B256::ZERO });
+        Ok(())
+    }
+
+    /// Schedules a task of a previous pivot against the current pivot state.
+    ///
+    /// The accounts of storage tasks are downloaded again, together with their storage. Trie node
+    /// tasks are dropped, the healing restarts at the root.
+    fn requeue_for_pivot(&mut self, task: SnapTask) {
+        match task {
+            SnapTask::Storages { accounts, .. } => self.pending.extend(
+                accounts
+                    .into_iter()
+                    .map(|(hash, _)| SnapTask::Accounts { origin: hash, limit: hash }),
+            ),
+            SnapTask::TrieNodes { .. } => {}
+            task => self.pending.push_back(task),
+        }
+    }
+
+    /// Returns `true` if peers stopped serving the pivot state.
+    const fn is_stale(&self) -> bool {
+        self.stale_responses >= STALE_PIVOT_THRESHOLD
+    }
+
+    /// Writes the response to the database and schedules the follow-up tasks.
+    ///
+    /// Returns `false` if the response is invalid.
+    fn on_response<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        task: SnapTask,
+        root: B256,
+        response: SnapResponse,
+    ) -> Result<bool, StageError> {
+        if root != self.root {
+            // the response was requested before the pivot moved
+            self.requeue_for_pivot(task);
+            return Ok(true)
+        }
+
+        if response.len() == 0 {
+            // an empty response either means that the peer doesn't serve the pivot state anymore
+            // or that the requested range is empty, which is signaled with a proof
+            let proven = match &response {
+                SnapResponse::AccountRange(response) => !response.proof.is_empty(),
+                SnapResponse::StorageRanges(response) => !response.proof.is_empty(),
+                _ => false,
+            };
+            if !proven {
+                self.stale_responses += 1;
+                self.pending.push_back(task);
+                return Ok(true)
+            }
+        }
+        self.stale_responses = 0;
+
+        match (task, response) {
+            (SnapTask::Accounts { origin, limit }, SnapResponse::AccountRange(response)) => {
+                self.on_account_range(provider, origin, limit, response)
+            }
+            (SnapTask::Storages { accounts, origin }, SnapResponse::StorageRanges(response)) => {
+                self.on_storage_ranges(provider, accounts, origin, response)
+            }
+            (SnapTask::ByteCodes { hashes }, SnapResponse::ByteCodes(response)) => {
+                self.on_byte_codes(provider, hashes, response)
+            }
+            (SnapTask::TrieNodes { paths }, SnapResponse::TrieNodes(response)) => {
+                self.on_trie_nodes(provider, paths, response)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn on_account_range<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        origin: B256,
+        limit: B256,
+        response: AccountRange,
+    ) -> Result<bool, StageError> {
+        let Ok(accounts) = response
+            .accounts
+            .iter()
+            .map(|entry| {
+                SlimAccount::decode(&mut &entry.body[..])
+                    .and_then(TrieAccount::try_from)
+                    .map(|account| (entry.hash, account))
+            })
+            .collect::<Result<Vec<_>, _>>()
+        else {
+            return Ok(false)
+        };
+
+        let leaves = accounts
+            .iter()
+            .map(|(hash, account)| (*hash, alloy_rlp::encode(account)))
+            .collect::<Vec<_>>();
+        let Some(more) = verify_range_proof(self.root, origin, &leaves, &response.proof) else {
+            return Ok(false)
+        };
+        let covered = !more || accounts.last().is_some_and(|(hash, _)| *hash >= limit);
+
+        let tx = provider.tx_ref();
+        let mut hashed_accounts = tx.cursor_write::<tables::HashedAccounts>()?;
+
+        let mut last = None;
+        for (hash, account) in accounts {
+            // the first account after the range may be included to prove the range
+            if hash > limit {
+                break
+            }
+
+            if account.storage_root != EMPTY_ROOT_HASH {
+                self.storage_batch.push((hash, account.storage_root));
+                if self.storage_batch.len() >= STORAGE_BATCH_SIZE {
+                    let accounts = std::mem::take(&mut self.storage_batch);
+                    self.pending.push_back(SnapTask::Storages { accounts, origin: B256::ZERO });
+                }
+            }
+            if account.code_hash != KECCAK_EMPTY &&
+                tx.get::<tables::Bytecodes>(account.code_hash)?.is_none()
+            {
+                self.code_batch.push(account.code_hash);
+                if self.code_batch.len() >= BYTECODE_BATCH_SIZE {
+                    let hashes = std::mem::take(&mut self.code_batch);
+                    self.pending.push_back(SnapTask::ByteCodes { hashes });
+                }
+            }
+
+            hashed_accounts.upsert(
+                hash,
+                Account {
+                    nonce: account.nonce,
+                    balance: account.balance,
+                    bytecode_hash: (account.code_hash != KECCAK_EMPTY).then_some(account.code_hash),
+                },
+            )?;
+            self.touched.insert(Nibbles::unpack(hash));
+            last = Some(hash);
+        }
+
+        // continue with the rest of the range, if the response didn't cover it
+        if let Some(next) = last.filter(|_| !covered).and_then(next_hash) {
+            self.pending.push_back(SnapTask::Accounts { origin: next, limit });
+        }
+
+        Ok(true)
+    }
+
+    fn on_storage_ranges<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        accounts: Vec<(B256, B256)>,
+        origin: B256,
+        response: StorageRanges,
+    ) -> Result<bool, StageError> {
+        let StorageRanges { mut slots, proof, .. } = response;
+        // the proof without slots proves that the first account has no slots after the origin
+        if slots.is_empty() && !proof.is_empty() {
+            slots.push(Vec::new());
+        }
+        if slots.len() > accounts.len() {
+            return Ok(false)
+        }
+
+        // the proof is attached to the last range if it is incomplete or didn't start at the
+        // beginning of the storage, all other ranges have to be complete
+        let mut ranges = Vec::with_capacity(slots.len());
+        let mut continue_from = None;
+        for (idx, ((_, storage_root), range)) in accounts.iter().zip(&slots).enumerate() {
+            let Ok(range) = range
+                .iter()
+                .map(|slot| U256::decode(&mut &slot.data[..]).map(|value| (slot.hash, value)))
+                .collect::<Result<Vec<_>, _>>()
+            else {
+                return Ok(false)
+            };
+
+            let leaves = range
+                .iter()
+                .map(|(hash, value)| (*hash, alloy_rlp::encode(value)))
+                .collect::<Vec<_>>();
+            let origin = if idx == 0 { origin } else { B256::ZERO };
+            let proof = if idx + 1 == slots.len() { &proof[..] } else { &[] };
+            let Some(more) = verify_range_proof(*storage_root, origin, &leaves, proof) else {
+                return Ok(false)
+            };
+            if more {
+                continue_from = range.last().and_then(|(hash, _)| next_hash(*hash));
+            }
+            ranges.push(range);
+        }
+
+        let mut hashed_storages = provider.tx_ref().cursor_dup_write::<tables::HashedStorages>()?;
+        for ((hashed_address, _), range) in accounts.iter().zip(ranges) {
+            for (key, value) in range {
+                if hashed_storages
+                    .seek_by_key_subkey(*hashed_address, key)?
+                    .filter(|entry| entry.key == key)
+                    .is_some()
+                {
+                    hashed_storages.delete_current()?;
+                }
+                hashed_storages.upsert(*hashed_address, StorageEntry { key, value })?;
+            }
+        }
+
+        let served = slots.len();
+        if let Some(next) = continue_from {
+            self.pending.push_back(SnapTask::Storages {
+                accounts: vec![accounts[served - 1]],
+                origin: next,
+            });
+        }
+        if served < accounts.len() {
+            let origin = if served == 0 { origin } else { B256::ZERO };
+            self.pending
+                .push_back(SnapTask::Storages { accounts: accounts[served..].to_vec(), origin });
+        }
+
+        Ok(true)
+    }
+
+    fn on_byte_codes<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        hashes: Vec<B256>,
+        response: ByteCodes,
+    ) -> Result<bool, StageError> {
+        let mut codes = response
+            .codes
+            .into_iter()
+            .map(|code| (keccak256(&code), code))
+            .collect::<HashMap<_, _>>();
+        if codes.len() > hashes.len() {
+            return Ok(false)
+        }
+
+        let mut missing = Vec::new();
+        for hash in hashes {
+            match codes.remove(&hash) {
+                Some(code) => {
+                    provider.tx_ref().put::<tables::Bytecodes>(hash, Bytecode::new_raw(code))?
+                }
+                None => missing.push(hash),
+            }
+        }
+        if !codes.is_empty() {
+            // unrequested codes
+            return Ok(false)
+        }
+
+        if !missing.is_empty() {
+            self.pending.push_back(SnapTask::ByteCodes { hashes: missing });
+        }
+
+        Ok(true)
+    }
+
+    /// Compares the trie nodes of the peer with the local trie nodes at the same paths.
+    ///
+    /// The nodes have to match the hashes referenced by their parent nodes, starting with the
+    /// state root. If the nodes are branch nodes, the differing children are compared next.
+    /// Otherwise the accounts under the path are downloaded again.
+    fn on_trie_nodes<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        paths: Vec<(Nibbles, B256)>,
+        response: TrieNodes,
+    ) -> Result<bool, StageError> {
+        let served = response.nodes.len();
+        if served > paths.len() ||
+            paths.iter().zip(&response.nodes).any(|((_, hash), node)| keccak256(node) != *hash)
+        {
+            return Ok(false)
+        }
+
+        let local = Proof::new(provider.tx_ref())
+            .account_proof_nodes(paths[..served].iter().map(|(path, _)| path.clone()))
+            .map_err(|err| StageError::Fatal(Box::new(err)))?;
+
+        for ((path, _), node) in paths.iter().zip(response.nodes) {
+            let local_node = local.get(path);
+            if local_node == Some(&node) {
+                continue
+            }
+
+            if let Some(children) = branch_children(&node).filter(|_| path.len() < 64) {
+                let local_children =
+                    local_node.and_then(|node| branch_children(node)).unwrap_or_default();
+                for (nibble, child) in children.into_iter().enumerate() {
+                    if local_children.get(nibble).copied().flatten() == child {
+                        continue
+                    }
+
+                    let mut child_path = path.clone();
+                    child_path.push(nibble as u8);
+                    match child.and_then(node_hash) {
+                        Some(hash) => {
+                            self.trie_node_batch.push((child_path, hash));
+                            if self.trie_node_batch.len() >= TRIE_NODE_BATCH_SIZE {
+                                let paths = std::mem::take(&mut self.trie_node_batch);
+                                self.pending.push_back(SnapTask::TrieNodes { paths });
+                            }
+                        }
+                        None => {
+                            self.delete_subtrie(provider, &child_path)?;
+                            // embedded nodes can't be requested by hash, the subtrie is
+                            // downloaded again instead
+                            if child.is_some() {
+                                let (origin, limit) = prefix_range(&child_path);
+                                self.pending.push_back(SnapTask::Accounts { origin, limit });
+                            }
+                        }
+                    }
+                }
+                continue
+            }
+
+            // the subtrie differs, download it again
+            self.delete_subtrie(provider, path)?;
+            let (origin, limit) = prefix_range(path);
+            self.pending.push_back(SnapTask::Accounts { origin, limit });
+        }
+
+        if served < paths.len() {
+            self.pending.push_back(SnapTask::TrieNodes { paths: paths[served..].to_vec() });
+        }
+
+        Ok(true)
+    }
+
+    /// Deletes all accounts under the path, together with their storage.
+    fn delete_subtrie<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+        path: &Nibbles,
+    ) -> Result<(), StageError> {
+        let tx = provider.tx_ref();
+        let mut hashed_accounts = tx.cursor_write::<tables::HashedAccounts>()?;
+        let mut hashed_storages = tx.cursor_dup_write::<tables::HashedStorages>()?;
+        let mut storage_tries = tx.cursor_dup_write::<tables::StoragesTrie>()?;
+
+        let (origin, limit) = prefix_range(path);
+        let mut walker = hashed_accounts.walk_range(origin..=limit)?;
+        while let Some((hashed_address, _)) = walker.next().transpose()? {
+            if hashed_storages.seek_exact(hashed_address)?.is_some() {
+                hashed_storages.delete_current_duplicates()?;
+            }
+            if storage_tries.seek_exact(hashed_address)?.is_some() {
+                storage_tries.delete_current_duplicates()?;
+            }
+            walker.delete_current()?;
+            self.touched.insert(Nibbles::unpack(hashed_address));
+        }
+
+        Ok(())
+    }
+
+    /// Schedules the tasks for the accounts, bytecodes and trie nodes that were collected into
+    /// incomplete batches.
+    fn flush_batches(&mut self) {
+        if !self.storage_batch.is_empty() {
+            let accounts = std::mem::take(&mut self.storage_batch);
+            self.pending.push_back(SnapTask::Storages { accounts, origin: B256::ZERO });
+        }
+        if !self.code_batch.is_empty() {
+            let hashes = std::mem::take(&mut self.code_batch);
+            self.pending.push_back(SnapTask::ByteCodes { hashes });
+        }
+        if !self.trie_node_batch.is_empty() {
+            let paths = std::mem::take(&mut self.trie_node_batch);
+            self.pending.push_back(SnapTask::TrieNodes { paths });
+        }
+    }
+
+    /// Computes the state root of the downloaded state and writes the trie tables.
+    ///
+    /// The first computation rebuilds the trie, subsequent ones only update the changed accounts.
+    fn state_root<DB: Database>(
+        &mut self,
+        provider: &DatabaseProviderRW<DB>,
+    ) -> Result<B256, StageError> {
+        let tx = provider.tx_ref();
+        let touched = std::mem::take(&mut self.touched);
+        let state_root = if self.trie_computed {
+            StateRoot::from_tx(tx).with_prefix_sets(TriePrefixSets {
+                account_prefix_set: touched.freeze(),
+                ..Default::default()
+            })
+        } else {
+            tx.clear::<tables::AccountsTrie>()?;
+            tx.clear::<tables::StoragesTrie>()?;
+            StateRoot::from_tx(tx)
+        };
+
+        let (root, updates) =
+            state_root.root_with_updates().map_err(|err| StageError::Fatal(Box::new(err)))?;
+        updates.flush(tx)?;
+        self.trie_computed = true;
+
+        Ok(root)
+    }
+}
+
+/// Returns the state root of the pivot block.
+fn pivot_root<DB: Database>(
+    provider: &DatabaseProviderRW<DB>,
+    pivot: BlockNumber,
+) -> Result<B256, StageError> {
+    Ok(provider
+        .header_by_number(pivot)?
+        .ok_or_else(|| ProviderError::HeaderNotFound(pivot.into()))?
+        .state_root)
+}
+
+/// Returns the first and the last hash with the given nibble prefix.
+fn prefix_range(prefix: &Nibbles) -> (B256, B256) {
+    let mut origin = [0u8; 64];
+    let mut limit = [0x0fu8; 64];
+    origin[..prefix.len()].copy_from_slice(prefix);
+    limit[..prefix.len()].copy_from_slice(prefix);
+    (
+        B256::from_slice(&Nibbles::from_nibbles_unchecked(origin).pack()),
+        B256::from_slice(&Nibbles::from_nibbles_unchecked(limit).pack()),
+    )
+}
+
+/// Returns the hash following the given hash, if any.
+fn next_hash(hash: B256) -> Option<B256> {
+    U256::from_be_bytes(hash.0).checked_add(U256::from(1)).map(|next| B256::new(next.to_be_bytes()))
+}
+
+/// Returns the RLP encoded items of the node.
+fn node_items(node: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut buf = node;
+    let header = alloy_rlp::Header::decode(&mut buf).ok()?;
+    if !header.list || buf.len() < header.payload_length {
+        return None
+    }
+
+    let mut payload = &buf[..header.payload_length];
+    let mut items = Vec::with_capacity(17);
+    while !payload.is_empty() {
+        let item = payload;
+        let item_header = alloy_rlp::Header::decode(&mut payload).ok()?;
+        if payload.len() < item_header.payload_length {
+            return None
+        }
+        payload = &payload[item_header.payload_length..];
+        items.push(&item[..item.len() - payload.len()]);
+    }
+    Some(items)
+}
+
+/// Returns the RLP encoded references to the 16 children, if the node is a branch node.
+fn branch_children(node: &[u8]) -> Option<Vec<Option<&[u8]>>> {
+    let mut items = node_items(node)?;
+
+    // a branch node has 16 children and a value
+    (items.len() == 17).then(|| {
+        items.truncate(16);
+        items
+            .into_iter()
+            .map(|item| (item != [alloy_rlp::EMPTY_STRING_CODE]).then_some(item))
+            .collect()
+    })
+}
+
+/// Returns the hash of the referenced node, if the node is not embedded into the reference.
+fn node_hash(reference: &[u8]) -> Option<B256> {
+    (reference.len() == 33 && reference[0] == alloy_rlp::EMPTY_STRING_CODE + 32)
+        .then(|| B256::from_slice(&reference[1..]))
+}
+
+/// Verifies that the leaves are all leaves of the trie with the given root from `origin` up to the
+/// last leaf, using the proofs of the range boundaries.
+///
+/// Without a proof, the leaves have to be the complete trie.
+///
+/// Returns whether the trie has more leaves after the range, or `None` if the range is invalid.
+fn verify_range_proof(
+    root: B256,
+    origin: B256,
+    leaves: &[(B256, Vec<u8>)],
+    proof: &[Bytes],
+) -> Option<bool> {
+    if leaves.first().is_some_and(|(first, _)| *first < origin) ||
+        leaves.windows(2).any(|pair| pair[0].0 >= pair[1].0)
+    {
+        return None
+    }
+
+    if root == EMPTY_ROOT_HASH {
+        return leaves.is_empty().then_some(false)
+    }
+
+    if proof.is_empty() {
+        let mut hash_builder = HashBuilder::default();
+        for (key, value) in leaves {
+            hash_builder.add_leaf(Nibbles::unpack(key), value);
+        }
+        return (origin == B256::ZERO && hash_builder.root() == root).then_some(false)
+    }
+
+    // collect the references to the subtries on either side of the range from the proof, the
+    // subtries inside of the range are rebuilt from the leaves
+    let nodes = proof.iter().map(|node| (keccak256(node), &node[..])).collect::<HashMap<_, _>>();
+    let left = Nibbles::unpack(origin);
+    let right = Nibbles::unpack(leaves.last().map_or(origin, |(last, _)| *last));
+    let mut outside = Vec::new();
+    let root_reference = alloy_rlp::encode(root);
+    collect_outside_subtries(
+        &nodes,
+        &root_reference,
+        Nibbles::default(),
+        &left,
+        &right,
+        &mut outside,
+    )?;
+
+    let more = outside.iter().any(|(_, _, after)| *after);
+    if outside.first().is_some_and(|(path, ..)| path.is_empty()) {
+        // the root node itself is outside of the range
+        return leaves.is_empty().then_some(more)
+    }
+    // an empty range is only valid if there are no leaves after the origin
+    if leaves.is_empty() && more {
+        return None
+    }
+
+    let mut hash_builder = HashBuilder::default();
+    let mut outside = outside.into_iter().peekable();
+    for (key, value) in leaves {
+        let key = Nibbles::unpack(key);
+        while let Some((path, hash, _)) = outside.next_if(|(path, ..)| *path < key) {
+            hash_builder.add_branch(path, hash, false);
+        }
+        hash_builder.add_leaf(key, value);
+    }
+    for (path, hash, _) in outside {
+        hash_builder.add_branch(path, hash, false);
+    }
+
+    (hash_builder.root() == root).then_some(more)
+}
+
+/// Walks the proof nodes along the paths to the range boundaries `left` and `right`, and collects
+/// the paths and hashes of the subtries outside of the range, together with whether they are after
+/// the range.
+///
+/// Returns `None` if a node on the boundary paths is missing or invalid. Embedded nodes outside of
+/// the range are not supported, which doesn't happen in the secure tries of the state.
+fn collect_outside_subtries(
+    nodes: &HashMap<B256, &[u8]>,
+    reference: &[u8],
+    path: Nibbles,
+    left: &Nibbles,
+    right: &Nibbles,
+    outside: &mut Vec<(Nibbles, B256, bool)>,
+) -> Option<()> {
+    if !left.starts_with(&path) && !right.starts_with(&path) {
+        if path < *left || path > *right {
+            outside.push((path.clone(), node_hash(reference)?, path > *right));
+        }
+        return Some(())
+    }
+
+    let node = match node_hash(reference) {
+        Some(hash) => *nodes.get(&hash)?,
+        None => reference,
+    };
+    let items = node_items(node)?;
+    match items.len() {
+        17 => {
+            for (nibble, child) in items[..16].iter().enumerate() {
+                if *child == [alloy_rlp::EMPTY_STRING_CODE] {
+                    continue
+                }
+                let mut child_path = path.clone();
+                child_path.push(nibble as u8);
+                collect_outside_subtries(nodes, child, child_path, left, right, outside)?;
+            }
+        }
+        2 => {
+            let (key, is_leaf) = decode_node_path(items[0])?;
+            let full_path = Nibbles::from_nibbles_unchecked([&path[..], &key[..]].concat());
+            if !is_leaf && (left.starts_with(&full_path) || right.starts_with(&full_path)) {
+                collect_outside_subtries(nodes, items[1], full_path, left, right, outside)?;
+            } else if full_path < *left || full_path > *right {
+                outside.push((path, node_hash(reference)?, full_path > *right));
+            }
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Decodes the hex prefix encoded path of a leaf or extension node, returns the path and whether
+/// the node is a leaf.
+fn decode_node_path(item: &[u8]) -> Option<(Nibbles, bool)> {
+    let encoded = Bytes::decode(&mut &item[..]).ok()?;
+    let (&first, rest) = encoded.split_first()?;
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    // odd length paths carry the first nibble in the flag byte
+    if first & 0x10 != 0 {
+        nibbles.push(first & 0x0f);
+    }
+    for byte in rest {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    Some((Nibbles::from_nibbles_unchecked(nibbles), first & 0x20 != 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stages::{ExecutionStage, ExecutionStageThresholds, MERKLE_STAGE_DEFAULT_CLEAN_THRESHOLD},
+        test_utils::TestStageDB,
+    };
+    use assert_matches::assert_matches;
+    use reth_chainspec::ChainSpecBuilder;
+    use reth_db::{test_utils::TempDatabase, DatabaseEnv};
+    use reth_db_api::models::{AccountBeforeTx, BlockNumberAddress};
+    use reth_eth_wire_types::snap::{AccountData, SnapProtocolMessage, StorageData};
+    use reth_evm_ethereum::execute::EthExecutorProvider;
+    use reth_exex::ExExManagerHandle;
+    use reth_network_p2p::test_utils::TestSnapClient;
+    use reth_network_snap::serve_request;
+    use reth_primitives::{
+        proofs::calculate_receipt_root, public_key_to_address, Address, Header, Receipt,
+        SealedBlock, SealedHeader, Transaction, TxKind, TxLegacy, TxType,
+    };
+    use reth_provider::{AccountReader, StateProvider};
+    use reth_prune_types::{PruneMode, PruneModes};
+    use reth_stages_api::StageExt;
+    use reth_testing_utils::generators::{self, sign_tx_with_key_pair};
+    use reth_trie::test_utils::{state_root, storage_root};
+    use reth_trie_common::proof::ProofRetainer;
+    use std::{collections::BTreeMap, sync::Arc};
+
+    /// The state served by the test client, keyed by hashed address.
+    type HashedState = BTreeMap<B256, (Account, BTreeMap<B256, U256>, Bytes)>;
+
+    /// Returns the proof nodes for the targets in the trie of the sorted leaves.
+    fn proof_nodes(leaves: &[(B256, Vec<u8>)], targets: &[B256]) -> Vec<Bytes> {
+        let retainer = ProofRetainer::from_iter(targets.iter().copied().map(Nibbles::unpack));
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(retainer);
+        for (key, value) in leaves {
+            hash_builder.add_leaf(Nibbles::unpack(key), value);
+        }
+        let _ = hash_builder.root();
+        hash_builder.take_proofs().into_values().collect()
+    }
+
+    /// Serves complete ranges of the given state, account ranges are proven.
+    fn serve(state: &HashedState, request: SnapProtocolMessage) -> SnapProtocolMessage {
+        match request {
+            SnapProtocolMessage::GetAccountRange(request) => {
+                let leaves = state
+                    .iter()
+                    .map(|(hash, (account, storage, _))| {
+                        let storage_root =
+                            storage_root(storage.iter().map(|(slot, value)| (*slot, *value)));
+                        (*hash, alloy_rlp::encode(TrieAccount::from((*account, storage_root))))
+                    })
+                    .collect::<Vec<_>>();
+                let accounts = leaves
+                    .iter()
+                    .filter(|(hash, _)| (request.starting_hash..=request.limit_hash).contains(hash))
+                    .map(|(hash, account)| AccountData {
+                        hash: *hash,
+                        body: SlimAccount::from(TrieAccount::decode(&mut &account[..]).unwrap())
+                            .encoded(),
+                    })
+                    .collect::<Vec<AccountData>>();
+                let targets = std::iter::once(request.starting_hash)
+                    .chain(accounts.last().map(|account| account.hash))
+                    .collect::<Vec<_>>();
+                let proof = proof_nodes(&leaves, &targets);
+                SnapProtocolMessage::AccountRange(AccountRange { request_id: 0, accounts, proof })
+            }
+            SnapProtocolMessage::GetStorageRanges(request) => {
+                let slots = request
+                    .account_hashes
+                    .iter()
+                    .map(|hash| {
+                        state[hash]
+                            .1
+                            .iter()
+                            .map(|(slot, value)| StorageData {
+                                hash: keccak256(slot),
+                                data: alloy_rlp::encode(value).into(),
+                            })
+                            .collect::<BTreeMap<_, _>>()
+                    })
+                    .map(|slots| slots.into_values().collect())
+                    .collect();
+                SnapProtocolMessage::StorageRanges(StorageRanges {
+                    request_id: 0,
+                    slots,
+                    proof: Vec::new(),
+                })
+            }
+            SnapProtocolMessage::GetByteCodes(request) => {
+                let codes = state
+                    .values()
+                    .map(|(_, _, code)| code.clone())
+                    .filter(|code| request.hashes.contains(&keccak256(code)))
+                    .collect();
+                SnapProtocolMessage::ByteCodes(ByteCodes { request_id: 0, codes })
+            }
+            _ => unreachable!("unexpected request"),
+        }
+    }
+
+    /// Returns the state served by the test client, all accounts with code share the given code.
+    fn hashed_state(
+        accounts: &[(Address, (Account, BTreeMap<B256, U256>))],
+        code: &Bytes,
+    ) -> HashedState {
+        accounts
+            .iter()
+            .map(|(address, (account, storage))| {
+                let code =
+                    if account.bytecode_hash.is_some() { code.clone() } else { Bytes::new() };
+                (keccak256(address), (*account, storage.clone(), code))
+            })
+            .collect()
+    }
+
+    /// Snap syncs to the pivot from a client serving the given state.
+    async fn snap_sync(db: &TestStageDB, state: HashedState, pivot: BlockNumber) -> ExecOutput {
+        snap_sync_from(
+            db,
+            TestSnapClient { responder: move |request| serve(&state, request) },
+            pivot,
+        )
+        .await
+    }
+
+    /// Snap syncs to the pivot with the given client.
+    async fn snap_sync_from<C: SnapClient + 'static>(
+        db: &TestStageDB,
+        client: C,
+        pivot: BlockNumber,
+    ) -> ExecOutput {
+        let config = SnapSyncConfig { enabled: true, min_distance: 0, ..Default::default() };
+        let mut stage = SnapSyncStage::new(client, config);
+
+        let mut input = ExecInput { target: Some(pivot), checkpoint: None };
+        loop {
+            StageExt::<Arc<TempDatabase<DatabaseEnv>>>::execute_ready(&mut stage, input)
+                .await
+                .unwrap();
+            let provider = db.factory.provider_rw().unwrap();
+            let output = stage.execute(&provider, input).unwrap();
+            provider.commit().unwrap();
+            if output.done {
+                return output
+            }
+            input.checkpoint = Some(output.checkpoint);
+        }
+    }
+
+    #[tokio::test]
+    async fn snap_sync_pivot_state() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().cancun_activated().build());
+        let db = TestStageDB::new_with_chain_spec(chain_spec);
+
+        let code = Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xf3]);
+        let accounts = (1..=10u8)
+            .map(|i| {
+                let address = Address::repeat_byte(i);
+                let storage = (1..=i)
+                    .map(|slot| (B256::with_last_byte(slot), U256::from(slot)))
+                    .collect::<BTreeMap<_, _>>();
+                let account = Account {
+                    nonce: i as u64,
+                    balance: U256::from(i),
+                    bytecode_hash: (i % 2 == 0).then(|| keccak256(&code)),
+                };
+                (address, (account, storage))
+            })
+            .collect::<Vec<_>>();
+
+        // the pivot header commits to the served state
+        let genesis = SealedHeader::default();
+        let pivot = Header {
+            number: 1,
+            parent_hash: genesis.hash(),
+            state_root: state_root(accounts.clone()),
+            ..Default::default()
+        }
+        .seal_slow();
+        db.insert_headers([&genesis, &pivot]).unwrap();
+
+        let output = snap_sync(&db, hashed_state(&accounts, &code), 1).await;
+        assert_eq!(output.checkpoint, StageCheckpoint::new(1));
+
+        assert_eq!(db.table::<tables::HashedAccounts>().unwrap().len(), 10);
+        assert_eq!(db.table::<tables::HashedStorages>().unwrap().len(), 55);
+        assert_eq!(
+            db.table::<tables::Bytecodes>().unwrap(),
+            vec![(keccak256(&code), Bytecode::new_raw(code))]
+        );
+
+        // the plain state can't be restored, the state is read from the hashed state instead
+        assert!(db.table_is_empty::<tables::PlainAccountState>().unwrap());
+        let provider = db.factory.provider().unwrap();
+        assert_eq!(provider.snap_sync_pivot().unwrap(), Some(1));
+        for stage_id in SNAP_SYNCED_STAGES {
+            assert_eq!(
+                provider.get_stage_checkpoint(stage_id).unwrap(),
+                Some(StageCheckpoint::new(1))
+            );
+        }
+        let latest = db.factory.latest().unwrap();
+        for (address, (account, storage)) in &accounts {
+            assert_eq!(latest.basic_account(*address).unwrap(), Some(*account));
+            for (slot, value) in storage {
+                assert_eq!(latest.storage(*address, *slot).unwrap(), Some(*value));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn snap_sync_from_server_below_tip() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().cancun_activated().build());
+
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let slot = B256::with_last_byte(1);
+        let old_a = Account { nonce: 1, ..Default::default() };
+        let new_a = Account { nonce: 2, ..Default::default() };
+        let account_b = Account { nonce: 1, balance: U256::from(10), bytecode_hash: None };
+        let account_c = Account { balance: U256::from(1), ..Default::default() };
+        let pivot_state = vec![
+            (a, (old_a, BTreeMap::from([(slot, U256::from(1))]))),
+            (b, (account_b, BTreeMap::new())),
+        ];
+        let tip_state = vec![
+            (a, (new_a, BTreeMap::from([(slot, U256::from(2))]))),
+            (b, (account_b, BTreeMap::new())),
+            (c, (account_c, BTreeMap::new())),
+        ];
+
+        let genesis = SealedHeader::default();
+        let pivot = Header {
+            number: 1,
+            parent_hash: genesis.hash(),
+            state_root: state_root(pivot_state),
+            ..Default::default()
+        }
+        .seal_slow();
+        let tip = Header {
+            number: 2,
+            parent_hash: pivot.hash(),
+            state_root: state_root(tip_state.clone()),
+            ..Default::default()
+        }
+        .seal_slow();
+
+        // the server is at the tip, the pivot state is served from the reverts of the tip block
+        let server = TestStageDB::new_with_chain_spec(chain_spec.clone());
+        server.insert_headers([&genesis, &pivot, &tip]).unwrap();
+        let provider = server.factory.provider_rw().unwrap();
+        let tx = provider.tx_ref();
+        for (address, (account, storage)) in &tip_state {
+            tx.put::<tables::HashedAccounts>(keccak256(address), *account).unwrap();
+            for (slot, value) in storage {
+                let entry = StorageEntry { key: keccak256(slot), value: *value };
+                tx.put::<tables::HashedStorages>(keccak256(address), entry).unwrap();
+            }
+        }
+        tx.put::<tables::AccountChangeSets>(2, AccountBeforeTx { address: a, info: Some(old_a) })
+            .unwrap();
+        tx.put::<tables::AccountChangeSets>(2, AccountBeforeTx { address: c, info: None }).unwrap();
+        tx.put::<tables::StorageChangeSets>(
+            BlockNumberAddress((2, a)),
+            StorageEntry { key: slot, value: U256::from(1) },
+        )
+        .unwrap();
+        let (root, updates) = StateRoot::from_tx(tx).root_with_updates().unwrap();
+        assert_eq!(root, tip.state_root);
+        updates.flush(tx).unwrap();
+        provider.save_stage_checkpoint(StageId::Finish, StageCheckpoint::new(2)).unwrap();
+        provider.commit().unwrap();
+
+        // the range proofs of the server are verified against the pivot root
+        let db = TestStageDB::new_with_chain_spec(chain_spec);
+        db.insert_headers([&genesis, &pivot]).unwrap();
+        let factory = server.factory.clone();
+        let client = TestSnapClient {
+            responder: move |request| serve_request(&factory.provider().unwrap(), request).unwrap(),
+        };
+        let output = snap_sync_from(&db, client, 1).await;
+        assert_eq!(output.checkpoint, StageCheckpoint::new(1));
+
+        let provider = db.factory.provider().unwrap();
+        assert_eq!(StateRoot::from_tx(provider.tx_ref()).root().unwrap(), pivot.state_root);
+        assert_eq!(provider.snap_sync_pivot().unwrap(), Some(1));
+    }
+
+    #[tokio::test]
+    async fn snap_sync_before_cancun() {
+        let db = TestStageDB::default();
+        let genesis = SealedHeader::default();
+        let pivot = Header { number: 1, parent_hash: genesis.hash(), ..Default::default() };
+        db.insert_headers([&genesis, &pivot.seal_slow()]).unwrap();
+
+        // the pivot is before Cancun on mainnet, the stage is a no-op
+        let output = snap_sync(&db, HashedState::default(), 1).await;
+        assert_eq!(output.checkpoint, StageCheckpoint::new(1));
+        assert_eq!(db.factory.provider().unwrap().snap_sync_pivot().unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn execute_after_snap_sync() {
+        let chain_spec = Arc::new(ChainSpecBuilder::mainnet().cancun_activated().build());
+        let db = TestStageDB::new_with_chain_spec(chain_spec.clone());
+        let mut rng = generators::rng();
+
+        // a contract that increments slot 0
+        let code =
+            Bytes::from_static(&[0x60, 0x00, 0x54, 0x60, 0x01, 0x01, 0x60, 0x00, 0x55, 0x00]);
+        let contract = Address::repeat_byte(0xc0);
+        let contract_account =
+            Account { nonce: 1, balance: U256::ZERO, bytecode_hash: Some(keccak256(&code)) };
+        let key_pair = generators::generate_keys(&mut rng, 1)[0];
+        let sender = public_key_to_address(key_pair.public_key());
+        let balance = U256::from(10u128.pow(18));
+        let pre_state = vec![
+            (sender, (Account { nonce: 0, balance, bytecode_hash: None }, BTreeMap::new())),
+            (contract, (contract_account, BTreeMap::from([(B256::ZERO, U256::from(5))]))),
+        ];
+
+        // 21000 intrinsic gas, 2100 for the cold SLOAD, 2900 for the SSTORE of a non-zero slot
+        // and 12 for the pushes and the addition.
+        let gas_used = 26_012;
+        let transaction = sign_tx_with_key_pair(
+            key_pair,
+            Transaction::Legacy(TxLegacy {
+                chain_id: Some(chain_spec.chain.id()),
+                nonce: 0,
+                gas_price: 1,
+                gas_limit: 100_000,
+                to: TxKind::Call(contract),
+                value: U256::ZERO,
+                input: Bytes::new(),
+            }),
+        );
+        let receipt = Receipt {
+            tx_type: TxType::Legacy,
+            success: true,
+            cumulative_gas_used: gas_used,
+            ..Default::default()
+        };
+
+        let genesis = SealedBlock::default();
+        let pivot = SealedBlock {
+            header: Header {
+                number: 1,
+                parent_hash: genesis.hash(),
+                state_root: state_root(pre_state.clone()),
+                ..Default::default()
+            }
+            .seal_slow(),
+            ..Default::default()
+        };
+        let beneficiary = Address::repeat_byte(0xbe);
+        let block = SealedBlock {
+            header: Header {
+                number: 2,
+                parent_hash: pivot.hash(),
+                beneficiary,
+                gas_limit: 30_000_000,
+                gas_used,
+                base_fee_per_gas: Some(0),
+                receipts_root: calculate_receipt_root(&[receipt.with_bloom()]),
+                blob_gas_used: Some(0),
+                excess_blob_gas: Some(0),
+                parent_beacon_block_root: Some(B256::ZERO),
+                ..Default::default()
+            }
+            .seal_slow(),
+            body: vec![transaction],
+            ..Default::default()
+        };
+        let provider = db.factory.provider_rw().unwrap();
+        for block in [genesis, pivot, block] {
+            provider.insert_historical_block(block.try_seal_with_senders().unwrap(), None).unwrap();
+        }
+        provider.commit().unwrap();
+
+        snap_sync(&db, hashed_state(&pre_state, &code), 1).await;
+        assert!(db.table_is_empty::<tables::PlainStorageState>().unwrap());
+
+        // snap sync doesn't download receipts, so they have to be pruned
+        let prune_modes =
+            PruneModes { receipts: Some(PruneMode::Distance(64)), ..PruneModes::none() };
+        let mut stage = ExecutionStage::new(
+            EthExecutorProvider::ethereum(chain_spec),
+            ExecutionStageThresholds::default(),
+            MERKLE_STAGE_DEFAULT_CLEAN_THRESHOLD,
+            prune_modes,
+            ExExManagerHandle::empty(),
+        );
+        let provider = db.factory.provider_rw().unwrap();
+        let input = ExecInput { target: Some(2), checkpoint: Some(StageCheckpoint::new(1)) };
+        let output = stage.execute(&provider, input).unwrap();
+        assert!(output.done);
+        provider.commit().unwrap();
+
+        // the pre-existing slot was read from the hashed state, otherwise the gas used would differ
+        assert_eq!(
+            db.table::<tables::PlainStorageState>().unwrap(),
+            vec![(contract, StorageEntry { key: B256::ZERO, value: U256::from(6) })]
+        );
+
+        // the hashed state is updated by the execution
+        let post_state = vec![
+            (
+                sender,
+                (
+                    Account {
+                        nonce: 1,
+                        balance: balance - U256::from(gas_used),
+                        bytecode_hash: None,
+                    },
+                    BTreeMap::new(),
+                ),
+            ),
+            (contract, (contract_account, BTreeMap::from([(B256::ZERO, U256::from(6))]))),
+            (
+                beneficiary,
+                (Account { balance: U256::from(gas_used), ..Default::default() }, BTreeMap::new()),
+            ),
+        ];
+        let provider = db.factory.provider_rw().unwrap();
+        assert_eq!(StateRoot::from_tx(provider.tx_ref()).root().unwrap(), state_root(post_state));
+
+        // the state can be unwound to the pivot, but not below it
+        assert_matches!(
+            stage.unwind(
+                &provider,
+                UnwindInput { checkpoint: output.checkpoint, unwind_to: 0, bad_block: None }
+            ),
+            Err(StageError::Fatal(_))
+        );
+        stage
+            .unwind(
+                &provider,
+                UnwindInput { checkpoint: output.checkpoint, unwind_to: 1, bad_block: None },
+            )
+            .unwrap();
+        assert_eq!(StateRoot::from_tx(provider.tx_ref()).root().unwrap(), state_root(pre_state));
+    }
+
+    #[test]
+    fn verify_range_proofs() {
+        let leaves = (0..64u64)
+            .map(|i| (keccak256(i.to_be_bytes()), alloy_rlp::encode(U256::from(i + 1))))
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut hash_builder = HashBuilder::default();
+        for (key, value) in &leaves {
+            hash_builder.add_leaf(Nibbles::unpack(key), value);
+        }
+        let root = hash_builder.root();
+
+        // a range in the middle of the trie
+        let origin = next_hash(leaves[9].0).unwrap();
+        let range = &leaves[10..=20];
+        let proof = proof_nodes(&leaves, &[origin, range.last().unwrap().0]);
+        assert_eq!(verify_range_proof(root, origin, range, &proof), Some(true));
+
+        // a missing leaf, a modified value or a leaf before the origin are rejected
+        let mut missing = range.to_vec();
+        missing.remove(5);
+        assert_eq!(verify_range_proof(root, origin, &missing, &proof), None);
+        let mut modified = range.to_vec();
+        modified[5].1 = alloy_rlp::encode(U256::ZERO);
+        assert_eq!(verify_range_proof(root, origin, &modified, &proof), None);
+        assert_eq!(verify_range_proof(root, origin, &leaves[9..=20], &proof), None);
+
+        // the range up to the last leaf
+        let range = &leaves[10..];
+        let proof = proof_nodes(&leaves, &[origin, range.last().unwrap().0]);
+        assert_eq!(verify_range_proof(root, origin, range, &proof), Some(false));
+
+        // an empty range is only valid after the last leaf
+        let after_last = next_hash(leaves.last().unwrap().0).unwrap();
+        let proof = proof_nodes(&leaves, &[after_last]);
+        assert_eq!(verify_range_proof(root, after_last, &[], &proof), Some(false));
+        let proof = proof_nodes(&leaves, &[origin]);
+        assert_eq!(verify_range_proof(root, origin, &[], &proof), None);
+
+        // without a proof, the range has to be the complete trie
+        assert_eq!(verify_range_proof(root, B256::ZERO, &leaves, &[]), Some(false));
+        assert_eq!(verify_range_proof(root, B256::ZERO, &leaves[..63], &[]), None);
+        assert_eq!(verify_range_proof(EMPTY_ROOT_HASH, B256::ZERO, &[], &[]), Some(false));
+    }
+
+    #[test]
+    fn prefix_hash_range() {
+        let (origin, limit) = prefix_range(&Nibbles::from_nibbles_unchecked([0xa, 0xb]));
+        assert_eq!(origin.0[0], 0xab);
+        assert!(origin.0[1..].iter().all(|byte| *byte == 0));
+        assert_eq!(limit.0[0], 0xab);
+        assert!(limit.0[1..].iter().all(|byte| *byte == 0xff));
+
+        let mut next = origin;
+        next.0[31] = 1;
+        assert_eq!(next_hash(origin), Some(next));
+        assert_eq!(next_hash(B256::repeat_byte(0xff)), None);
+    }
+}
@@ -9,11 +9,37 @@ use reth_db_api::{
     DatabaseError,
 };
 use reth_etl::Collector;
-use reth_primitives::BlockNumber;
+use reth_primitives::{BlockNumber, TxNumber};
+use reth_provider::{PruneCheckpointReader, PruneCheckpointWriter};
+use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_api::StageError;
+use reth_storage_errors::provider::ProviderResult;
 use std::{collections::HashMap, hash::Hash, ops::RangeBounds};
 use tracing::info;
 
+/// Unwinds the prune checkpoint of `segment` to the provided block and transaction numbers, if it's
+/// ahead of them.
+///
+/// The pruner deletes data that has been moved to static files from the database according to this
+/// checkpoint, so it has to be lowered when such static file data is unwound. Otherwise the data
+/// written to the database again is never deleted.
+pub(crate) fn unwind_prune_checkpoint<P: PruneCheckpointReader + PruneCheckpointWriter>(
+    provider: &P,
+    segment: PruneSegment,
+    block_number: Option<BlockNumber>,
+    tx_number: Option<TxNumber>,
+) -> ProviderResult<()> {
+    if let Some(checkpoint) = provider.get_prune_checkpoint(segment)? {
+        if checkpoint.block_number > block_number {
+            provider.save_prune_checkpoint(
+                segment,
+                PruneCheckpoint { block_number, tx_number, ..checkpoint },
+            )?;
+        }
+    }
+    Ok(())
+}
+
 /// Number of blocks before pushing indices from cache to [`Collector`]
 const DEFAULT_CACHE_THRESHOLD: u64 = 100_000;
 
@@ -1,4 +1,4 @@
-use reth_chainspec::MAINNET;
+use reth_chainspec::{ChainSpec, MAINNET};
 use reth_db::{
     tables,
     test_utils::{
@@ -38,27 +38,32 @@ pub struct TestStageDB {
 impl Default for TestStageDB {
     /// Create a new instance of [`TestStageDB`]
     fn default() -> Self {
+        Self::new_with_chain_spec(MAINNET.clone())
+    }
+}
+
+impl TestStageDB {
+    pub fn new(path: &Path) -> Self {
         let (static_dir, static_dir_path) = create_test_static_files_dir();
+
         Self {
             temp_static_files_dir: static_dir,
             factory: ProviderFactory::new(
-                create_test_rw_db(),
+                create_test_rw_db_with_path(path),
                 MAINNET.clone(),
                 StaticFileProvider::read_write(static_dir_path).unwrap(),
             ),
         }
     }
-}
 
-impl TestStageDB {
-    pub fn new(path: &Path) -> Self {
+    /// Create a new instance of [`TestStageDB`] with the given chain spec.
+    pub fn new_with_chain_spec(chain_spec: Arc<ChainSpec>) -> Self {
         let (static_dir, static_dir_path) = create_test_static_files_dir();
-
         Self {
             temp_static_files_dir: static_dir,
             factory: ProviderFactory::new(
-                create_test_rw_db_with_path(path),
-                MAINNET.clone(),
+                create_test_rw_db(),
+                chain_spec,
                 StaticFileProvider::read_write(static_dir_path).unwrap(),
             ),
         }
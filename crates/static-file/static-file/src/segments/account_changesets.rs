@@ -0,0 +1,80 @@
+use crate::segments::{create_block_static_file, Segment};
+use alloy_primitives::BlockNumber;
+use reth_db::tables;
+use reth_db_api::{
+    cursor::DbDupCursorRO, database::Database, models::BlockAccountChangeSets, table::Compress,
+    transaction::DbTx,
+};
+use reth_provider::{
+    providers::{StaticFileProvider, StaticFileWriter},
+    DatabaseProviderRO,
+};
+use reth_static_file_types::{SegmentConfig, StaticFileSegment};
+use reth_storage_errors::provider::ProviderResult;
+use std::{ops::RangeInclusive, path::Path};
+
+/// Static File segment responsible for [`StaticFileSegment::AccountChangeSets`] part of data.
+#[derive(Debug, Default)]
+pub struct AccountChangeSets;
+
+impl AccountChangeSets {
+    /// Returns the account changes of a single block from the database.
+    fn block_changeset<DB: Database>(
+        provider: &DatabaseProviderRO<DB>,
+        block: BlockNumber,
+    ) -> ProviderResult<BlockAccountChangeSets> {
+        let mut cursor = provider.tx_ref().cursor_dup_read::<tables::AccountChangeSets>()?;
+        let changeset = cursor
+            .walk_dup(Some(block), None)?
+            .map(|result| result.map(|(_, account_before)| account_before))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(BlockAccountChangeSets(changeset))
+    }
+}
+
+impl<DB: Database> Segment<DB> for AccountChangeSets {
+    fn segment(&self) -> StaticFileSegment {
+        StaticFileSegment::AccountChangeSets
+    }
+
+    fn copy_to_static_files(
+        &self,
+        provider: DatabaseProviderRO<DB>,
+        static_file_provider: StaticFileProvider,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let mut static_file_writer = static_file_provider
+            .get_writer(*block_range.start(), StaticFileSegment::AccountChangeSets)?;
+
+        for block in block_range {
+            let _static_file_block = static_file_writer
+                .append_account_changeset(block, Self::block_changeset(&provider, block)?)?;
+            debug_assert_eq!(_static_file_block, block);
+        }
+
+        Ok(())
+    }
+
+    fn create_static_file_file(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: &Path,
+        config: SegmentConfig,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let rows = block_range
+            .clone()
+            .map(|block| Ok(Self::block_changeset(provider, block)?.compress().into()))
+            .collect::<ProviderResult<Vec<Vec<u8>>>>()?;
+
+        create_block_static_file(
+            provider,
+            directory,
+            StaticFileSegment::AccountChangeSets,
+            config,
+            block_range,
+            rows,
+        )
+    }
+}
@@ -9,8 +9,17 @@ pub use headers::Headers;
 mod receipts;
 pub use receipts::Receipts;
 
+mod account_changesets;
+pub use account_changesets::AccountChangeSets;
+
+mod storage_changesets;
+pub use storage_changesets::StorageChangeSets;
+
+mod transaction_senders;
+pub use transaction_senders::TransactionSenders;
+
 use alloy_primitives::BlockNumber;
-use reth_db::{RawKey, RawTable};
+use reth_db::{tables, RawKey, RawTable};
 use reth_db_api::{cursor::DbCursorRO, database::Database, table::Table, transaction::DbTx};
 use reth_nippy_jar::NippyJar;
 use reth_provider::{
@@ -61,11 +70,10 @@ pub(crate) fn prepare_jar<DB: Database, const COLUMNS: usize>(
     total_rows: usize,
     prepare_compression: impl Fn() -> ProviderResult<Rows<COLUMNS>>,
 ) -> ProviderResult<NippyJar<SegmentHeader>> {
-    let tx_range = match segment {
-        StaticFileSegment::Headers => None,
-        StaticFileSegment::Receipts | StaticFileSegment::Transactions => {
-            Some(provider.transaction_range_by_block_range(block_range.clone())?.into())
-        }
+    let tx_range = if segment.is_tx_based() {
+        Some(provider.transaction_range_by_block_range(block_range.clone())?.into())
+    } else {
+        None
     };
 
     let mut nippy_jar = NippyJar::new(
@@ -115,3 +123,45 @@ pub(crate) fn dataset_for_compression<DB: Database, T: Table<Key = u64>>(
         .map(|row| row.map(|(_key, value)| value.into_value()).expect("should exist"))
         .collect::<Vec<_>>())
 }
+
+/// Creates a static file for a block based segment whose rows are already encoded, one row per
+/// block in `block_range`.
+///
+/// Used by segments whose rows can't be straightforwardly walked from a single table, eg.
+/// changesets which are grouped per block.
+pub(crate) fn create_block_static_file<DB: Database>(
+    provider: &DatabaseProviderRO<DB>,
+    directory: &Path,
+    segment: StaticFileSegment,
+    config: SegmentConfig,
+    block_range: RangeInclusive<BlockNumber>,
+    rows: Vec<Vec<u8>>,
+) -> ProviderResult<()> {
+    debug_assert!(segment.is_block_based());
+
+    let range_len = block_range.clone().count();
+    let mut jar = prepare_jar::<DB, 1>(
+        provider,
+        directory,
+        segment,
+        config,
+        block_range.clone(),
+        range_len,
+        || Ok([rows.iter().rev().take(range_len.min(1000)).cloned().collect()]),
+    )?;
+
+    // Generate list of hashes for filters & PHF
+    if config.filters.has_filters() {
+        let mut cursor = provider.tx_ref().cursor_read::<RawTable<tables::CanonicalHeaders>>()?;
+        let hashes = cursor
+            .walk(Some(RawKey::from(*block_range.start())))?
+            .take(range_len)
+            .map(|row| row.map(|(_key, value)| value.into_value()).map_err(|e| e.into()));
+        jar.prepare_index(hashes, range_len).map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+    }
+
+    jar.freeze(vec![rows.into_iter().map(Ok)], range_len as u64)
+        .map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use crate::segments::{create_block_static_file, Segment};
+use alloy_primitives::BlockNumber;
+use reth_db::tables;
+use reth_db_api::{
+    cursor::DbCursorRO,
+    database::Database,
+    models::{BlockNumberAddress, BlockStorageChangeSets},
+    table::Compress,
+    transaction::DbTx,
+};
+use reth_provider::{
+    providers::{StaticFileProvider, StaticFileWriter},
+    DatabaseProviderRO,
+};
+use reth_static_file_types::{SegmentConfig, StaticFileSegment};
+use reth_storage_errors::provider::ProviderResult;
+use std::{ops::RangeInclusive, path::Path};
+
+/// Static File segment responsible for [`StaticFileSegment::StorageChangeSets`] part of data.
+#[derive(Debug, Default)]
+pub struct StorageChangeSets;
+
+impl StorageChangeSets {
+    /// Returns the storage changes of a single block from the database.
+    fn block_changeset<DB: Database>(
+        provider: &DatabaseProviderRO<DB>,
+        block: BlockNumber,
+    ) -> ProviderResult<BlockStorageChangeSets> {
+        let mut cursor = provider.tx_ref().cursor_read::<tables::StorageChangeSets>()?;
+        let changeset = cursor
+            .walk_range(BlockNumberAddress::range(block..=block))?
+            .map(|result| result.map(|(key, entry)| (key.address(), entry)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(BlockStorageChangeSets(changeset))
+    }
+}
+
+impl<DB: Database> Segment<DB> for StorageChangeSets {
+    fn segment(&self) -> StaticFileSegment {
+        StaticFileSegment::StorageChangeSets
+    }
+
+    fn copy_to_static_files(
+        &self,
+        provider: DatabaseProviderRO<DB>,
+        static_file_provider: StaticFileProvider,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let mut static_file_writer = static_file_provider
+            .get_writer(*block_range.start(), StaticFileSegment::StorageChangeSets)?;
+
+        for block in block_range {
+            let _static_file_block = static_file_writer
+                .append_storage_changeset(block, Self::block_changeset(&provider, block)?)?;
+            debug_assert_eq!(_static_file_block, block);
+        }
+
+        Ok(())
+    }
+
+    fn create_static_file_file(
+        &self,
+        provider: &DatabaseProviderRO<DB>,
+        directory: &Path,
+        config: SegmentConfig,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<()> {
+        let rows = block_range
+            .clone()
+            .map(|block| Ok(Self::block_changeset(provider, block)?.compress().into()))
+            .collect::<ProviderResult<Vec<Vec<u8>>>>()?;
+
+        create_block_static_file(
+            provider,
+            directory,
+            StaticFileSegment::StorageChangeSets,
+            config,
+            block_range,
+            rows,
+        )
+    }
+}
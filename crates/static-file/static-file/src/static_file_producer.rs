@@ -57,17 +57,25 @@ pub struct StaticFileProducerInner<DB> {
 }
 
 /// Static File targets, per data part, measured in [`BlockNumber`].
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct StaticFileTargets {
     headers: Option<RangeInclusive<BlockNumber>>,
     receipts: Option<RangeInclusive<BlockNumber>>,
     transactions: Option<RangeInclusive<BlockNumber>>,
+    account_change_sets: Option<RangeInclusive<BlockNumber>>,
+    storage_change_sets: Option<RangeInclusive<BlockNumber>>,
+    transaction_senders: Option<RangeInclusive<BlockNumber>>,
 }
 
 impl StaticFileTargets {
     /// Returns `true` if any of the targets are [Some].
     pub const fn any(&self) -> bool {
-        self.headers.is_some() || self.receipts.is_some() || self.transactions.is_some()
+        self.headers.is_some() ||
+            self.receipts.is_some() ||
+            self.transactions.is_some() ||
+            self.account_change_sets.is_some() ||
+            self.storage_change_sets.is_some() ||
+            self.transaction_senders.is_some()
     }
 
     // Returns `true` if all targets are either [`None`] or has beginning of the range equal to the
@@ -77,6 +85,9 @@ impl StaticFileTargets {
             (self.headers.as_ref(), static_files.headers),
             (self.receipts.as_ref(), static_files.receipts),
             (self.transactions.as_ref(), static_files.transactions),
+            (self.account_change_sets.as_ref(), static_files.account_change_sets),
+            (self.storage_change_sets.as_ref(), static_files.storage_change_sets),
+            (self.transaction_senders.as_ref(), static_files.transaction_senders),
         ]
         .iter()
         .all(|(target_block_range, highest_static_fileted_block)| {
@@ -135,6 +146,15 @@ impl<DB: Database> StaticFileProducerInner<DB> {
         if let Some(block_range) = targets.receipts.clone() {
             segments.push((Box::new(segments::Receipts), block_range));
         }
+        if let Some(block_range) = targets.account_change_sets.clone() {
+            segments.push((Box::new(segments::AccountChangeSets), block_range));
+        }
+        if let Some(block_range) = targets.storage_change_sets.clone() {
+            segments.push((Box::new(segments::StorageChangeSets), block_range));
+        }
+        if let Some(block_range) = targets.transaction_senders.clone() {
+            segments.push((Box::new(segments::TransactionSenders), block_range));
+        }
 
         segments.par_iter().try_for_each(|(segment, block_range)| -> ProviderResult<()> {
             debug!(target: "static_file", segment = %segment.segment(), ?block_range, "StaticFileProducer segment");
@@ -200,6 +220,38 @@ impl<DB: Database> StaticFileProducerInner<DB> {
                     finalized_block_number,
                 )
             }),
+            // StaticFile changesets and senders only if they're not pruned according to the user
+            // configuration
+            account_change_sets: if self.prune_modes.account_history.is_none() {
+                finalized_block_numbers.account_change_sets.and_then(|finalized_block_number| {
+                    self.get_static_file_target(
+                        highest_static_files.account_change_sets,
+                        finalized_block_number,
+                    )
+                })
+            } else {
+                None
+            },
+            storage_change_sets: if self.prune_modes.storage_history.is_none() {
+                finalized_block_numbers.storage_change_sets.and_then(|finalized_block_number| {
+                    self.get_static_file_target(
+                        highest_static_files.storage_change_sets,
+                        finalized_block_number,
+                    )
+                })
+            } else {
+                None
+            },
+            transaction_senders: if self.prune_modes.sender_recovery.is_none() {
+                finalized_block_numbers.transaction_senders.and_then(|finalized_block_number| {
+                    self.get_static_file_target(
+                        highest_static_files.transaction_senders,
+                        finalized_block_number,
+                    )
+                })
+            } else {
+                None
+            },
         };
 
         trace!(
@@ -231,7 +283,7 @@ mod tests {
     };
     use alloy_primitives::{B256, U256};
     use assert_matches::assert_matches;
-    use reth_db::{test_utils::TempDatabase, DatabaseEnv};
+    use reth_db::{tables, test_utils::TempDatabase, DatabaseEnv};
     use reth_db_api::{database::Database, transaction::DbTx};
     use reth_provider::{
         providers::StaticFileWriter, ProviderError, ProviderFactory, StaticFileProviderFactory,
@@ -241,9 +293,12 @@ mod tests {
     use reth_static_file_types::{HighestStaticFiles, StaticFileSegment};
     use reth_testing_utils::{
         generators,
-        generators::{random_block_range, random_receipt},
+        generators::{
+            random_block_range, random_changeset_range, random_eoa_accounts, random_receipt,
+        },
     };
     use std::{
+        collections::BTreeSet,
         sync::{mpsc::channel, Arc},
         time::Duration,
     };
@@ -296,6 +351,7 @@ mod tests {
                 headers: Some(1),
                 receipts: Some(1),
                 transactions: Some(1),
+                ..Default::default()
             })
             .expect("get static file targets");
         assert_eq!(
@@ -303,13 +359,19 @@ mod tests {
             StaticFileTargets {
                 headers: Some(0..=1),
                 receipts: Some(0..=1),
-                transactions: Some(0..=1)
+                transactions: Some(0..=1),
+                ..Default::default()
             }
         );
         assert_matches!(static_file_producer.run(targets), Ok(_));
         assert_eq!(
             provider_factory.static_file_provider().get_highest_static_files(),
-            HighestStaticFiles { headers: Some(1), receipts: Some(1), transactions: Some(1) }
+            HighestStaticFiles {
+                headers: Some(1),
+                receipts: Some(1),
+                transactions: Some(1),
+                ..Default::default()
+            }
         );
 
         let targets = static_file_producer
@@ -317,6 +379,7 @@ mod tests {
                 headers: Some(3),
                 receipts: Some(3),
                 transactions: Some(3),
+                ..Default::default()
             })
             .expect("get static file targets");
         assert_eq!(
@@ -324,13 +387,19 @@ mod tests {
             StaticFileTargets {
                 headers: Some(2..=3),
                 receipts: Some(2..=3),
-                transactions: Some(2..=3)
+                transactions: Some(2..=3),
+                ..Default::default()
             }
         );
         assert_matches!(static_file_producer.run(targets), Ok(_));
         assert_eq!(
             provider_factory.static_file_provider().get_highest_static_files(),
-            HighestStaticFiles { headers: Some(3), receipts: Some(3), transactions: Some(3) }
+            HighestStaticFiles {
+                headers: Some(3),
+                receipts: Some(3),
+                transactions: Some(3),
+                ..Default::default()
+            }
         );
 
         let targets = static_file_producer
@@ -338,6 +407,7 @@ mod tests {
                 headers: Some(4),
                 receipts: Some(4),
                 transactions: Some(4),
+                ..Default::default()
             })
             .expect("get static file targets");
         assert_eq!(
@@ -345,7 +415,8 @@ mod tests {
             StaticFileTargets {
                 headers: Some(4..=4),
                 receipts: Some(4..=4),
-                transactions: Some(4..=4)
+                transactions: Some(4..=4),
+                ..Default::default()
             }
         );
         assert_matches!(
@@ -354,7 +425,113 @@ mod tests {
         );
         assert_eq!(
             provider_factory.static_file_provider().get_highest_static_files(),
-            HighestStaticFiles { headers: Some(3), receipts: Some(3), transactions: Some(3) }
+            HighestStaticFiles {
+                headers: Some(3),
+                receipts: Some(3),
+                transactions: Some(3),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn run_changesets_and_senders() {
+        let mut rng = generators::rng();
+        let db = TestStageDB::default();
+
+        let blocks = random_block_range(&mut rng, 0..=3, B256::ZERO, 2..3);
+        db.insert_blocks(blocks.iter(), StorageKind::Database(None)).expect("insert blocks");
+
+        let senders = blocks
+            .iter()
+            .flat_map(|block| &block.body)
+            .enumerate()
+            .map(|(tx_number, transaction)| {
+                (tx_number as u64, transaction.recover_signer().expect("recover signer"))
+            })
+            .collect::<Vec<_>>();
+        db.insert_transaction_senders(senders.clone()).expect("insert transaction senders");
+
+        let (changesets, _) = random_changeset_range(
+            &mut rng,
+            blocks.iter(),
+            random_eoa_accounts(&mut rng, 3)
+                .into_iter()
+                .map(|(address, account)| (address, (account, Vec::new()))),
+            1..3,
+            0..256,
+        );
+        db.insert_changesets(changesets, None).expect("insert changesets");
+
+        let provider_factory = db.factory.clone();
+        let static_file_producer =
+            StaticFileProducerInner::new(provider_factory.clone(), PruneModes::default());
+
+        let targets = static_file_producer
+            .get_static_file_targets(HighestStaticFiles {
+                account_change_sets: Some(3),
+                storage_change_sets: Some(3),
+                transaction_senders: Some(3),
+                ..Default::default()
+            })
+            .expect("get static file targets");
+        assert_eq!(
+            targets,
+            StaticFileTargets {
+                account_change_sets: Some(0..=3),
+                storage_change_sets: Some(0..=3),
+                transaction_senders: Some(0..=3),
+                ..Default::default()
+            }
+        );
+        assert_matches!(static_file_producer.run(targets), Ok(_));
+
+        let static_file_provider = provider_factory.static_file_provider();
+        assert_eq!(
+            static_file_provider.get_highest_static_files(),
+            HighestStaticFiles {
+                account_change_sets: Some(3),
+                storage_change_sets: Some(3),
+                transaction_senders: Some(3),
+                ..Default::default()
+            }
+        );
+
+        // The static files have the same data as the database tables
+        assert_eq!(
+            static_file_provider.account_changesets_range(0..=3).unwrap(),
+            db.table::<tables::AccountChangeSets>().unwrap()
+        );
+        let storage_changesets = db.table::<tables::StorageChangeSets>().unwrap();
+        assert!(!storage_changesets.is_empty());
+        assert_eq!(
+            static_file_provider
+                .storage_changesets_range(0..=3)
+                .unwrap()
+                .into_iter()
+                .map(|(block_address, entry)| (block_address, entry.key, entry.value))
+                .collect::<BTreeSet<_>>(),
+            storage_changesets
+                .iter()
+                .map(|(block_address, entry)| (*block_address, entry.key, entry.value))
+                .collect::<BTreeSet<_>>()
+        );
+        for (block_address, entry) in storage_changesets {
+            assert_eq!(
+                static_file_provider
+                    .storage_changeset_entry(
+                        block_address.block_number(),
+                        block_address.address(),
+                        entry.key
+                    )
+                    .unwrap()
+                    .map(|entry| entry.key),
+                Some(entry.key)
+            );
+        }
+        assert_eq!(
+            static_file_provider.transaction_senders_range(0..senders.len() as u64).unwrap(),
+            senders
         );
     }
 
@@ -382,6 +559,7 @@ mod tests {
                         headers: Some(1),
                         receipts: Some(1),
                         transactions: Some(1),
+                        ..Default::default()
                     })
                     .expect("get static file targets");
                 assert_matches!(locked_producer.run(targets.clone()), Ok(_));
@@ -1,8 +1,11 @@
+use crate::StaticFileSegment;
+use serde::{Deserialize, Serialize};
 use strum::AsRefStr;
 
 /// Static File compression types.
-#[derive(Debug, Copy, Clone, Default, AsRefStr)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, AsRefStr, Serialize, Deserialize)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
 pub enum Compression {
     /// LZ4 compression algorithm.
     #[strum(serialize = "lz4")]
@@ -18,3 +21,64 @@ pub enum Compression {
     #[default]
     Uncompressed,
 }
+
+/// Compression used for newly created static files of each [`StaticFileSegment`].
+///
+/// Existing static files keep the compression they were created with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SegmentsCompression {
+    /// Compression of [`StaticFileSegment::Headers`].
+    pub headers: Compression,
+    /// Compression of [`StaticFileSegment::Transactions`].
+    pub transactions: Compression,
+    /// Compression of [`StaticFileSegment::Receipts`].
+    pub receipts: Compression,
+    /// Compression of [`StaticFileSegment::AccountChangeSets`].
+    pub account_change_sets: Compression,
+    /// Compression of [`StaticFileSegment::StorageChangeSets`].
+    pub storage_change_sets: Compression,
+    /// Compression of [`StaticFileSegment::TransactionSenders`].
+    pub transaction_senders: Compression,
+}
+
+impl Default for SegmentsCompression {
+    fn default() -> Self {
+        // Transactions and receipts already have zstd compression with dictionaries natively in
+        // their encoding, and senders are addresses which don't compress.
+        Self {
+            headers: Compression::Lz4,
+            transactions: Compression::Uncompressed,
+            receipts: Compression::Uncompressed,
+            account_change_sets: Compression::Lz4,
+            storage_change_sets: Compression::Lz4,
+            transaction_senders: Compression::Uncompressed,
+        }
+    }
+}
+
+impl SegmentsCompression {
+    /// Returns the compression of the segment.
+    pub const fn get(&self, segment: StaticFileSegment) -> Compression {
+        match segment {
+            StaticFileSegment::Headers => self.headers,
+            StaticFileSegment::Transactions => self.transactions,
+            StaticFileSegment::Receipts => self.receipts,
+            StaticFileSegment::AccountChangeSets => self.account_change_sets,
+            StaticFileSegment::StorageChangeSets => self.storage_change_sets,
+            StaticFileSegment::TransactionSenders => self.transaction_senders,
+        }
+    }
+
+    /// Sets the compression of the segment.
+    pub fn set(&mut self, segment: StaticFileSegment, compression: Compression) {
+        match segment {
+            StaticFileSegment::Headers => self.headers = compression,
+            StaticFileSegment::Transactions => self.transactions = compression,
+            StaticFileSegment::Receipts => self.receipts = compression,
+            StaticFileSegment::AccountChangeSets => self.account_change_sets = compression,
+            StaticFileSegment::StorageChangeSets => self.storage_change_sets = compression,
+            StaticFileSegment::TransactionSenders => self.transaction_senders = compression,
+        }
+    }
+}
@@ -13,7 +13,7 @@ mod filters;
 mod segment;
 
 use alloy_primitives::BlockNumber;
-pub use compression::Compression;
+pub use compression::{Compression, SegmentsCompression};
 pub use filters::{Filters, InclusionFilter, PerfectHashingFunction};
 pub use segment::{SegmentConfig, SegmentHeader, SegmentRangeInclusive, StaticFileSegment};
 
@@ -32,6 +32,15 @@ pub struct HighestStaticFiles {
     /// Highest static file block of transactions, inclusive.
     /// If [`None`], no static file is available.
     pub transactions: Option<BlockNumber>,
+    /// Highest static file block of account changesets, inclusive.
+    /// If [`None`], no static file is available.
+    pub account_change_sets: Option<BlockNumber>,
+    /// Highest static file block of storage changesets, inclusive.
+    /// If [`None`], no static file is available.
+    pub storage_change_sets: Option<BlockNumber>,
+    /// Highest static file block of transaction senders, inclusive.
+    /// If [`None`], no static file is available.
+    pub transaction_senders: Option<BlockNumber>,
 }
 
 impl HighestStaticFiles {
@@ -41,6 +50,9 @@ impl HighestStaticFiles {
             StaticFileSegment::Headers => self.headers,
             StaticFileSegment::Transactions => self.transactions,
             StaticFileSegment::Receipts => self.receipts,
+            StaticFileSegment::AccountChangeSets => self.account_change_sets,
+            StaticFileSegment::StorageChangeSets => self.storage_change_sets,
+            StaticFileSegment::TransactionSenders => self.transaction_senders,
         }
     }
 
@@ -50,12 +62,25 @@ impl HighestStaticFiles {
             StaticFileSegment::Headers => &mut self.headers,
             StaticFileSegment::Transactions => &mut self.transactions,
             StaticFileSegment::Receipts => &mut self.receipts,
+            StaticFileSegment::AccountChangeSets => &mut self.account_change_sets,
+            StaticFileSegment::StorageChangeSets => &mut self.storage_change_sets,
+            StaticFileSegment::TransactionSenders => &mut self.transaction_senders,
         }
     }
 
     /// Returns the maximum block of all segments.
     pub fn max(&self) -> Option<u64> {
-        [self.headers, self.transactions, self.receipts].iter().filter_map(|&option| option).max()
+        [
+            self.headers,
+            self.transactions,
+            self.receipts,
+            self.account_change_sets,
+            self.storage_change_sets,
+            self.transaction_senders,
+        ]
+        .iter()
+        .filter_map(|&option| option)
+        .max()
     }
 }
 
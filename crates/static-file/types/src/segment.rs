@@ -34,6 +34,15 @@ pub enum StaticFileSegment {
     #[strum(serialize = "receipts")]
     /// Static File segment responsible for the `Receipts` table.
     Receipts,
+    #[strum(serialize = "account-change-sets")]
+    /// Static File segment responsible for the `AccountChangeSets` table.
+    AccountChangeSets,
+    #[strum(serialize = "storage-change-sets")]
+    /// Static File segment responsible for the `StorageChangeSets` table.
+    StorageChangeSets,
+    #[strum(serialize = "transaction-senders")]
+    /// Static File segment responsible for the `TransactionSenders` table.
+    TransactionSenders,
 }
 
 impl StaticFileSegment {
@@ -43,6 +52,9 @@ impl StaticFileSegment {
             Self::Headers => "headers",
             Self::Transactions => "transactions",
             Self::Receipts => "receipts",
+            Self::AccountChangeSets => "account-change-sets",
+            Self::StorageChangeSets => "storage-change-sets",
+            Self::TransactionSenders => "transaction-senders",
         }
     }
 
@@ -57,7 +69,12 @@ impl StaticFileSegment {
         };
 
         match self {
-            Self::Headers | Self::Transactions | Self::Receipts => default_config,
+            Self::Headers |
+            Self::Transactions |
+            Self::Receipts |
+            Self::AccountChangeSets |
+            Self::StorageChangeSets |
+            Self::TransactionSenders => default_config,
         }
     }
 
@@ -65,7 +82,11 @@ impl StaticFileSegment {
     pub const fn columns(&self) -> usize {
         match self {
             Self::Headers => 3,
-            Self::Transactions | Self::Receipts => 1,
+            Self::Transactions |
+            Self::Receipts |
+            Self::AccountChangeSets |
+            Self::StorageChangeSets |
+            Self::TransactionSenders => 1,
         }
     }
 
@@ -138,6 +159,22 @@ impl StaticFileSegment {
     pub const fn is_receipts(&self) -> bool {
         matches!(self, Self::Receipts)
     }
+
+    /// Returns `true` if the segment is `StaticFileSegment::AccountChangeSets` or
+    /// `StaticFileSegment::StorageChangeSets`.
+    pub const fn is_change_sets(&self) -> bool {
+        matches!(self, Self::AccountChangeSets | Self::StorageChangeSets)
+    }
+
+    /// Returns `true` if the segment has one row per block.
+    pub const fn is_block_based(&self) -> bool {
+        matches!(self, Self::Headers | Self::AccountChangeSets | Self::StorageChangeSets)
+    }
+
+    /// Returns `true` if the segment has one row per transaction.
+    pub const fn is_tx_based(&self) -> bool {
+        !self.is_block_based()
+    }
 }
 
 /// A segment header that contains information common to all segments. Used for storage.
@@ -238,38 +275,30 @@ impl SegmentHeader {
 
     /// Increments tx end range depending on segment
     pub fn increment_tx(&mut self) {
-        match self.segment {
-            StaticFileSegment::Headers => (),
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
-                if let Some(tx_range) = &mut self.tx_range {
-                    tx_range.end += 1;
-                } else {
-                    self.tx_range = Some(SegmentRangeInclusive::new(0, 0));
-                }
+        if self.segment.is_tx_based() {
+            if let Some(tx_range) = &mut self.tx_range {
+                tx_range.end += 1;
+            } else {
+                self.tx_range = Some(SegmentRangeInclusive::new(0, 0));
             }
         }
     }
 
     /// Removes `num` elements from end of tx or block range.
     pub fn prune(&mut self, num: u64) {
-        match self.segment {
-            StaticFileSegment::Headers => {
-                if let Some(range) = &mut self.block_range {
-                    if num > range.end {
-                        self.block_range = None;
-                    } else {
-                        range.end = range.end.saturating_sub(num);
-                    }
-                };
-            }
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
-                if let Some(range) = &mut self.tx_range {
-                    if num > range.end {
-                        self.tx_range = None;
-                    } else {
-                        range.end = range.end.saturating_sub(num);
-                    }
-                };
+        if self.segment.is_block_based() {
+            if let Some(range) = &mut self.block_range {
+                if num > range.end {
+                    self.block_range = None;
+                } else {
+                    range.end = range.end.saturating_sub(num);
+                }
+            };
+        } else if let Some(range) = &mut self.tx_range {
+            if num > range.end {
+                self.tx_range = None;
+            } else {
+                range.end = range.end.saturating_sub(num);
             }
         };
     }
@@ -296,9 +325,10 @@ impl SegmentHeader {
 
     /// Returns the row offset which depends on whether the segment is block or transaction based.
     pub fn start(&self) -> Option<u64> {
-        match self.segment {
-            StaticFileSegment::Headers => self.block_start(),
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => self.tx_start(),
+        if self.segment.is_block_based() {
+            self.block_start()
+        } else {
+            self.tx_start()
         }
     }
 }
@@ -371,6 +401,12 @@ mod tests {
         let test_vectors = [
             (StaticFileSegment::Headers, 2..=30, "static_file_headers_2_30", None),
             (StaticFileSegment::Receipts, 30..=300, "static_file_receipts_30_300", None),
+            (
+                StaticFileSegment::AccountChangeSets,
+                500_000..=999_999,
+                "static_file_account-change-sets_500000_999999",
+                None,
+            ),
             (
                 StaticFileSegment::Transactions,
                 1_123_233..=11_223_233,
@@ -3,7 +3,12 @@ use crate::{
     transaction::{DbTx, DbTxMut},
     DatabaseError,
 };
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
 /// Main Database trait that can open read-only and read-write transactions.
 ///
@@ -76,3 +81,138 @@ impl<DB: Database> Database for &DB {
         <DB as Database>::tx_mut(self)
     }
 }
+
+/// A database that can be copied while it is in use.
+pub trait DatabaseCopy {
+    /// Copies a consistent snapshot of the database into the directory at `dest`, which is created
+    /// if it doesn't exist.
+    ///
+    /// If `compact` is set, the copy omits free space and is smaller than the database.
+    fn copy_to(&self, dest: &Path, compact: bool) -> Result<(), DatabaseError>;
+}
+
+impl<DB: DatabaseCopy> DatabaseCopy for Arc<DB> {
+    fn copy_to(&self, dest: &Path, compact: bool) -> Result<(), DatabaseError> {
+        <DB as DatabaseCopy>::copy_to(self, dest, compact)
+    }
+}
+
+/// A database that can be compacted while it is in use.
+///
+/// A compaction is split into two steps, so that the database can still be written to while the
+/// compacted copy is written:
+/// 1. [`DatabaseCompact::copy_compacted`] writes a compacted copy of the database under a read
+///    transaction.
+/// 2. [`DatabaseCompact::swap_compacted`] swaps the copy in, if nothing was committed since the
+///    copy was taken. Otherwise the copy is discarded.
+pub trait DatabaseCompact {
+    /// Returns the free space of the database file, which a compaction would reclaim.
+    fn free_space(&self) -> Result<FreeSpace, DatabaseError>;
+
+    /// Returns the ID of the last committed write transaction.
+    fn last_txn_id(&self) -> Result<u64, DatabaseError>;
+
+    /// Writes a copy of the database without free space next to it.
+    fn copy_compacted(&self) -> Result<CompactedCopy, DatabaseError>;
+
+    /// Swaps the compacted copy in.
+    ///
+    /// New transactions are blocked while the copy is swapped in, which waits for up to
+    /// `swap_timeout` for the open transactions to finish. The swap is aborted if the database was
+    /// written to since the copy was taken, or if other processes have the database open.
+    fn swap_compacted(
+        &self,
+        copy: CompactedCopy,
+        swap_timeout: Duration,
+    ) -> Result<CompactionOutcome, DatabaseError>;
+
+    /// Discards the compacted copy without swapping it in.
+    fn discard_compacted(&self, copy: CompactedCopy) -> Result<(), DatabaseError>;
+
+    /// Rewrites the database without free space, and swaps the compacted database in.
+    ///
+    /// The database must not be written to while it's compacted, otherwise the compaction is
+    /// aborted.
+    fn compact(&self, swap_timeout: Duration) -> Result<CompactionOutcome, DatabaseError> {
+        let copy = self.copy_compacted()?;
+        self.swap_compacted(copy, swap_timeout)
+    }
+}
+
+impl<DB: DatabaseCompact> DatabaseCompact for Arc<DB> {
+    fn free_space(&self) -> Result<FreeSpace, DatabaseError> {
+        <DB as DatabaseCompact>::free_space(self)
+    }
+
+    fn last_txn_id(&self) -> Result<u64, DatabaseError> {
+        <DB as DatabaseCompact>::last_txn_id(self)
+    }
+
+    fn copy_compacted(&self) -> Result<CompactedCopy, DatabaseError> {
+        <DB as DatabaseCompact>::copy_compacted(self)
+    }
+
+    fn swap_compacted(
+        &self,
+        copy: CompactedCopy,
+        swap_timeout: Duration,
+    ) -> Result<CompactionOutcome, DatabaseError> {
+        <DB as DatabaseCompact>::swap_compacted(self, copy, swap_timeout)
+    }
+
+    fn discard_compacted(&self, copy: CompactedCopy) -> Result<(), DatabaseError> {
+        <DB as DatabaseCompact>::discard_compacted(self, copy)
+    }
+
+    fn compact(&self, swap_timeout: Duration) -> Result<CompactionOutcome, DatabaseError> {
+        <DB as DatabaseCompact>::compact(self, swap_timeout)
+    }
+}
+
+/// A compacted copy of a database, written by [`DatabaseCompact::copy_compacted`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompactedCopy {
+    /// Path of the copied database file.
+    pub path: PathBuf,
+    /// ID of the last write transaction included in the copy.
+    pub txn_id: u64,
+    /// Size of the database file when the copy was taken, in bytes.
+    pub size_before: u64,
+    /// Size of the copied database file, in bytes.
+    pub size_after: u64,
+}
+
+/// Free space of a database file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FreeSpace {
+    /// Size of the free pages and of the preallocated space at the end of the file, in bytes.
+    pub free: u64,
+    /// Size of the database file, in bytes.
+    pub total: u64,
+}
+
+impl FreeSpace {
+    /// Returns the share of the database file that is free, in percent.
+    pub const fn percent(&self) -> u8 {
+        if self.total == 0 {
+            return 0
+        }
+        (self.free.saturating_mul(100) / self.total) as u8
+    }
+}
+
+/// The outcome of a [`DatabaseCompact::compact`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactionOutcome {
+    /// Size of the database file before the compaction, in bytes.
+    pub size_before: u64,
+    /// Size of the database file after the compaction, in bytes.
+    pub size_after: u64,
+}
+
+impl CompactionOutcome {
+    /// Returns the number of bytes returned to the file system.
+    pub const fn reclaimed(&self) -> u64 {
+        self.size_before.saturating_sub(self.size_after)
+    }
+}
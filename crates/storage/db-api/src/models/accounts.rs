@@ -8,7 +8,7 @@ use crate::{
     DatabaseError,
 };
 use reth_codecs::{derive_arbitrary, Compact};
-use reth_primitives::{Account, Address, BlockNumber, Buf, StorageKey};
+use reth_primitives::{Account, Address, BlockNumber, Buf, StorageEntry, StorageKey, B256};
 use serde::{Deserialize, Serialize};
 
 /// Account as it is saved in the database.
@@ -155,10 +155,180 @@ impl Decode for AddressStorageKey {
 
 impl_fixed_arbitrary!((BlockNumberAddress, 28), (AddressStorageKey, 52));
 
+/// Account changes of a block, as they are stored in the `AccountChangeSets` static file
+/// segment.
+///
+/// Every entry is prefixed by its length, so the changes of a block fit in a single row.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockAccountChangeSets(pub Vec<AccountBeforeTx>);
+
+impl Compact for BlockAccountChangeSets {
+    fn to_compact<B>(self, buf: &mut B) -> usize
+    where
+        B: bytes::BufMut + AsMut<[u8]>,
+    {
+        self.0.into_iter().map(|entry| to_compact_prefixed(buf, |buf| entry.to_compact(buf))).sum()
+    }
+
+    fn from_compact(buf: &[u8], len: usize) -> (Self, &[u8]) {
+        let (entries, buf) = from_compact_prefixed(buf, len, AccountBeforeTx::from_compact);
+        (Self(entries), buf)
+    }
+}
+
+/// Storage changes of a block, as they are stored in the `StorageChangeSets` static file segment.
+///
+/// The changes are ordered by address and storage key. So that the changes of a single account can
+/// be read with [`BlockStorageChangeSets::find`] without decoding the whole row, the row is
+/// encoded as:
+/// - the number of changed accounts, as a big endian `u32`
+/// - for every account, its address and the end offset of its changes, as a big endian `u32`
+/// - the changes, every change prefixed by its length
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockStorageChangeSets(pub Vec<(Address, StorageEntry)>);
+
+/// Size of an account in the index of [`BlockStorageChangeSets`].
+const STORAGE_CHANGESET_INDEX_ENTRY_SIZE: usize = 24;
+
+impl BlockStorageChangeSets {
+    /// Returns the change of the storage `key` of `address` from a compact encoded
+    /// [`BlockStorageChangeSets`].
+    ///
+    /// The account is found with a binary search over the index, and only its changes are
+    /// decoded.
+    pub fn find(buf: &[u8], address: Address, key: B256) -> Option<StorageEntry> {
+        if buf.is_empty() {
+            return None
+        }
+
+        let (index, changes) = split_storage_changeset_index(buf);
+        let index_entry = |position: usize| {
+            let mut entry = &index[position * STORAGE_CHANGESET_INDEX_ENTRY_SIZE..
+                (position + 1) * STORAGE_CHANGESET_INDEX_ENTRY_SIZE];
+            let address = Address::from_slice(&entry[..20]);
+            entry.advance(20);
+            (address, entry.get_u32() as usize)
+        };
+
+        let (mut low, mut high) = (0, index.len() / STORAGE_CHANGESET_INDEX_ENTRY_SIZE);
+        while low < high {
+            let middle = (low + high) / 2;
+            if index_entry(middle).0 < address {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        if low == index.len() / STORAGE_CHANGESET_INDEX_ENTRY_SIZE || index_entry(low).0 != address
+        {
+            return None
+        }
+
+        let start = if low == 0 { 0 } else { index_entry(low - 1).1 };
+        let end = index_entry(low).1;
+        let (entries, _) =
+            from_compact_prefixed(&changes[start..end], end - start, StorageEntry::from_compact);
+        entries.into_iter().find(|entry| entry.key == key)
+    }
+}
+
+impl Compact for BlockStorageChangeSets {
+    fn to_compact<B>(mut self, buf: &mut B) -> usize
+    where
+        B: bytes::BufMut + AsMut<[u8]>,
+    {
+        if self.0.is_empty() {
+            return 0
+        }
+        self.0.sort_unstable_by_key(|(address, entry)| (*address, entry.key));
+
+        let mut index = Vec::<(Address, usize)>::new();
+        let mut changes = Vec::new();
+        for (address, entry) in self.0 {
+            to_compact_prefixed(&mut changes, |buf| entry.to_compact(buf));
+            match index.last_mut() {
+                Some((last, end)) if *last == address => *end = changes.len(),
+                _ => index.push((address, changes.len())),
+            }
+        }
+
+        buf.put_u32(index.len() as u32);
+        for (address, end) in &index {
+            buf.put_slice(address.as_slice());
+            buf.put_u32(*end as u32);
+        }
+        buf.put_slice(&changes);
+
+        4 + index.len() * STORAGE_CHANGESET_INDEX_ENTRY_SIZE + changes.len()
+    }
+
+    fn from_compact(buf: &[u8], len: usize) -> (Self, &[u8]) {
+        if len == 0 {
+            return (Self::default(), buf)
+        }
+
+        let (index, changes) = split_storage_changeset_index(&buf[..len]);
+        let mut entries = Vec::new();
+        let mut start = 0;
+        for mut index_entry in index.chunks_exact(STORAGE_CHANGESET_INDEX_ENTRY_SIZE) {
+            let address = Address::from_slice(&index_entry[..20]);
+            index_entry.advance(20);
+            let end = index_entry.get_u32() as usize;
+            let (changes, _) = from_compact_prefixed(
+                &changes[start..end],
+                end - start,
+                StorageEntry::from_compact,
+            );
+            entries.extend(changes.into_iter().map(|entry| (address, entry)));
+            start = end;
+        }
+
+        (Self(entries), &buf[len..])
+    }
+}
+
+/// Splits a compact encoded [`BlockStorageChangeSets`] into its account index and its changes.
+fn split_storage_changeset_index(mut buf: &[u8]) -> (&[u8], &[u8]) {
+    let accounts = buf.get_u32() as usize;
+    buf.split_at(accounts * STORAGE_CHANGESET_INDEX_ENTRY_SIZE)
+}
+
+/// Writes the entry encoded by `encode` to `buf`, prefixed by its length as a big endian `u32`.
+///
+/// Returns the number of bytes written.
+fn to_compact_prefixed<B>(buf: &mut B, encode: impl FnOnce(&mut Vec<u8>) -> usize) -> usize
+where
+    B: bytes::BufMut + AsMut<[u8]>,
+{
+    let mut entry = Vec::new();
+    let len = encode(&mut entry);
+    buf.put_u32(len as u32);
+    buf.put_slice(&entry);
+    len + 4
+}
+
+/// Reads the length prefixed entries from the first `len` bytes of `buf`.
+fn from_compact_prefixed<T>(
+    mut buf: &[u8],
+    len: usize,
+    decode: impl Fn(&[u8], usize) -> (T, &[u8]),
+) -> (Vec<T>, &[u8]) {
+    let (mut entries, mut remaining) = (Vec::new(), len);
+    while remaining > 0 {
+        let entry_len = buf.get_u32() as usize;
+        let (entry, _) = decode(&buf[..entry_len], entry_len);
+        entries.push(entry);
+        buf.advance(entry_len);
+        remaining -= entry_len + 4;
+    }
+    (entries, buf)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rand::{thread_rng, Rng};
+    use reth_primitives::U256;
     use std::str::FromStr;
 
     #[test]
@@ -178,6 +348,58 @@ mod tests {
         assert_eq!(decoded, key);
     }
 
+    #[test]
+    fn test_block_changesets_compact() {
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        let account_changesets = BlockAccountChangeSets(vec![
+            AccountBeforeTx { address: Address::with_last_byte(1), info: Some(account) },
+            AccountBeforeTx { address: Address::with_last_byte(2), info: None },
+        ]);
+        let mut buf = Vec::new();
+        let len = account_changesets.clone().to_compact(&mut buf);
+        assert_eq!(len, buf.len());
+        assert_eq!(BlockAccountChangeSets::from_compact(&buf, len).0, account_changesets);
+
+        let storage_changesets = BlockStorageChangeSets(vec![
+            (Address::with_last_byte(1), StorageEntry { key: B256::ZERO, value: U256::ZERO }),
+            (
+                Address::with_last_byte(1),
+                StorageEntry { key: B256::with_last_byte(1), value: U256::from(3) },
+            ),
+            (
+                Address::with_last_byte(3),
+                StorageEntry { key: B256::with_last_byte(2), value: U256::from(4) },
+            ),
+        ]);
+        let mut buf = Vec::new();
+        let len = storage_changesets.clone().to_compact(&mut buf);
+        assert_eq!(len, buf.len());
+        assert_eq!(BlockStorageChangeSets::from_compact(&buf, len).0, storage_changesets);
+
+        // Changes are sorted on encoding
+        let mut reversed = storage_changesets.clone();
+        reversed.0.reverse();
+        let mut reversed_buf = Vec::new();
+        reversed.to_compact(&mut reversed_buf);
+        assert_eq!(reversed_buf, buf);
+
+        for (address, entry) in &storage_changesets.0 {
+            assert_eq!(BlockStorageChangeSets::find(&buf, *address, entry.key), Some(*entry));
+        }
+        assert_eq!(
+            BlockStorageChangeSets::find(&buf, Address::with_last_byte(1), B256::with_last_byte(2)),
+            None
+        );
+        for address in [Address::ZERO, Address::with_last_byte(2), Address::with_last_byte(4)] {
+            assert_eq!(BlockStorageChangeSets::find(&buf, address, B256::ZERO), None);
+        }
+        assert_eq!(BlockStorageChangeSets::find(&[], Address::ZERO, B256::ZERO), None);
+
+        let mut buf = Vec::new();
+        assert_eq!(BlockAccountChangeSets::default().to_compact(&mut buf), 0);
+        assert_eq!(BlockAccountChangeSets::from_compact(&buf, 0).0, Default::default());
+    }
+
     #[test]
     fn test_block_number_address_rand() {
         let mut bytes = [0u8; 28];
@@ -0,0 +1,25 @@
+//! Backup manifest model.
+
+use reth_primitives::{BlockNumber, B256};
+use serde::{Deserialize, Serialize};
+
+/// The manifest of a database backup, written next to the backed up database and static files.
+#[derive(Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupManifest {
+    /// The highest block that both the database and the static files of the backup contain.
+    ///
+    /// A node started from the backup resumes syncing from this block.
+    pub tip: BlockNumber,
+    /// The hash of the tip block.
+    pub tip_hash: B256,
+    /// The highest block that is fully synced in the database copy.
+    pub database_tip: BlockNumber,
+    /// The highest block that is contained in the static files, at the time the backup was
+    /// completed.
+    pub static_files_tip: Option<BlockNumber>,
+    /// The names of the static files in the backup.
+    pub static_files: Vec<String>,
+    /// Unix timestamp of the time the backup was taken, in seconds.
+    pub timestamp: u64,
+}
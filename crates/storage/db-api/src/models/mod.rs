@@ -11,6 +11,7 @@ use reth_stages_types::StageCheckpoint;
 use reth_trie_common::{StoredNibbles, StoredNibblesSubKey, *};
 
 pub mod accounts;
+pub mod backup;
 pub mod blocks;
 pub mod client_version;
 pub mod integer_list;
@@ -18,6 +19,7 @@ pub mod sharded_key;
 pub mod storage_sharded_key;
 
 pub use accounts::*;
+pub use backup::BackupManifest;
 pub use blocks::*;
 pub use client_version::ClientVersion;
 pub use sharded_key::ShardedKey;
@@ -216,6 +218,8 @@ impl_compression_for_compact!(
     StoredBlockWithdrawals,
     Bytecode,
     AccountBeforeTx,
+    BlockAccountChangeSets,
+    BlockStorageChangeSets,
     TransactionSignedNoHash,
     CompactU256,
     StageCheckpoint,
@@ -0,0 +1,325 @@
+//! Hot backups of a node's database and static files.
+
+use reth_db::{
+    mdbx::{DatabaseArguments, DatabaseEnv, DatabaseEnvKind},
+    tables,
+    version::create_db_version_file,
+};
+use reth_db_api::{
+    database::{Database, DatabaseCopy},
+    models::{BackupManifest, ClientVersion},
+    transaction::DbTx,
+    DatabaseError,
+};
+use reth_fs_util::FsPathError;
+use reth_primitives::{static_file::find_fixed_range, BlockNumber, StaticFileSegment};
+use reth_provider::{
+    providers::StaticFileProvider, BlockHashReader, ProviderError, ProviderFactory,
+    StaticFileProviderFactory,
+};
+use reth_prune_types::PruneSegment;
+use reth_stages_types::StageId;
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::info;
+
+/// The name of the database directory inside the backup directory.
+pub const BACKUP_DB_DIR: &str = "db";
+
+/// The name of the static files directory inside the backup directory.
+pub const BACKUP_STATIC_FILES_DIR: &str = "static_files";
+
+/// The name of the manifest file inside the backup directory.
+pub const BACKUP_MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// The extension of the static file configuration files.
+const STATIC_FILE_CONFIG_EXTENSION: &str = "conf";
+
+/// Static file segments that are written up to the tip of the chain.
+const TIP_SEGMENTS: [StaticFileSegment; 3] =
+    [StaticFileSegment::Headers, StaticFileSegment::Transactions, StaticFileSegment::Receipts];
+
+/// Static file segments that are moved from the database by the static file producer, with the
+/// prune segment that records up to which block they were deleted from the database.
+const MOVED_SEGMENTS: [(StaticFileSegment, PruneSegment); 3] = [
+    (StaticFileSegment::AccountChangeSets, PruneSegment::AccountChangeSets),
+    (StaticFileSegment::StorageChangeSets, PruneSegment::StorageChangeSets),
+    (StaticFileSegment::TransactionSenders, PruneSegment::SenderRecovery),
+];
+
+/// Backup error type.
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    /// The backup destination already exists and is not empty.
+    #[error("backup destination {0} is not empty")]
+    DestinationNotEmpty(PathBuf),
+    /// Provider error.
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    /// Filesystem error.
+    #[error(transparent)]
+    Fs(#[from] FsPathError),
+    /// I/O error.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Failed to serialize the manifest.
+    #[error(transparent)]
+    Manifest(#[from] serde_json::Error),
+}
+
+impl From<DatabaseError> for BackupError {
+    fn from(error: DatabaseError) -> Self {
+        Self::Provider(ProviderError::Database(error))
+    }
+}
+
+/// Takes a backup of the database and the static files into `dest`, while the node is running.
+///
+/// The backup has the same layout as a datadir, so a node can be started from it directly:
+///
+/// - `db`: a compacted copy of the database, taken under a read transaction.
+/// - `static_files`: the static files. Jars that are complete are hard-linked if possible, the jars
+///   that are still being appended to are copied.
+/// - `manifest.json`: the [`BackupManifest`], recording the tip block both halves agree on.
+///
+/// The database is copied before the static files. Static files can be truncated by unwinds while
+/// they're copied, so the highest blocks of the static files are read before and after copying,
+/// as well as from the copied static files, and the backup tip is the lowest block that all of
+/// them and the database copy contain.
+pub fn backup<DB>(
+    provider_factory: &ProviderFactory<DB>,
+    dest: &Path,
+) -> Result<BackupManifest, BackupError>
+where
+    DB: Database + DatabaseCopy,
+{
+    if dest.exists() && reth_fs_util::read_dir(dest)?.next().is_some() {
+        return Err(BackupError::DestinationNotEmpty(dest.to_path_buf()))
+    }
+
+    let static_file_provider = provider_factory.static_file_provider();
+    let highest_before = static_file_provider.get_highest_static_files();
+
+    let db_dest = dest.join(BACKUP_DB_DIR);
+    info!(target: "reth::db::backup", path = ?db_dest, "Copying database");
+    provider_factory.db_ref().copy_to(&db_dest, true)?;
+    create_db_version_file(&db_dest)?;
+    let database = DatabaseEnv::open(
+        &db_dest,
+        DatabaseEnvKind::RO,
+        DatabaseArguments::new(ClientVersion::default()),
+    )?;
+    let database_tip = database_tip(&database)?;
+
+    let static_files_dest = dest.join(BACKUP_STATIC_FILES_DIR);
+    info!(target: "reth::db::backup", path = ?static_files_dest, "Copying static files");
+    let static_files = copy_static_files(&static_file_provider, &static_files_dest)?;
+    let highest_after = static_file_provider.get_highest_static_files();
+    let copied_static_file_provider = StaticFileProvider::read_only(&static_files_dest)?;
+    let highest_copied = copied_static_file_provider.get_highest_static_files();
+
+    let static_files_tip = static_files_tip(&database, |segment| {
+        [&highest_before, &highest_after, &highest_copied]
+            .into_iter()
+            .map(|highest| highest.highest(segment))
+            .min()
+            .flatten()
+    })?;
+    let tip = static_files_tip
+        .map_or(database_tip, |static_files_tip| database_tip.min(static_files_tip));
+    let tip_hash = copied_static_file_provider
+        .block_hash(tip)?
+        .ok_or_else(|| ProviderError::HeaderNotFound(tip.into()))?;
+
+    let manifest = BackupManifest {
+        tip,
+        tip_hash,
+        database_tip,
+        static_files_tip,
+        static_files,
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+    };
+    reth_fs_util::write(
+        dest.join(BACKUP_MANIFEST_FILE_NAME),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+
+    info!(target: "reth::db::backup", tip, ?tip_hash, "Backup complete");
+    Ok(manifest)
+}
+
+/// Returns the highest fully synced block of the database copy.
+fn database_tip(database: &DatabaseEnv) -> Result<BlockNumber, BackupError> {
+    let tip = database
+        .tx()?
+        .get::<tables::StageCheckpoints>(StageId::Finish.to_string())?
+        .unwrap_or_default()
+        .block_number;
+    Ok(tip)
+}
+
+/// Returns the highest block that the static files of the backup contain, given the highest block
+/// of each segment.
+///
+/// The [`TIP_SEGMENTS`] contain every block up to their highest block, except for receipts if
+/// they're written to the database because they're pruned. The blocks above the
+/// highest block of the [`MOVED_SEGMENTS`] are still in the database, so these segments only
+/// limit the tip if they're behind the prune checkpoint of the database copy, which means that
+/// they were truncated while the backup was taken.
+fn static_files_tip<DB: Database>(
+    database: &DB,
+    highest: impl Fn(StaticFileSegment) -> Option<BlockNumber>,
+) -> Result<Option<BlockNumber>, BackupError> {
+    let tx = database.tx()?;
+    let receipts_in_database = tx.entries::<tables::Receipts>()? > 0;
+    let mut tip = TIP_SEGMENTS
+        .into_iter()
+        .filter(|segment| !(segment.is_receipts() && receipts_in_database))
+        .filter_map(&highest)
+        .min();
+    for (segment, prune_segment) in MOVED_SEGMENTS {
+        let Some(highest) = highest(segment) else { continue };
+        let pruned = tx
+            .get::<tables::PruneCheckpoints>(prune_segment)?
+            .and_then(|checkpoint| checkpoint.block_number);
+        if pruned.is_some_and(|pruned| pruned > highest) {
+            tip = Some(tip.map_or(highest, |tip| tip.min(highest)));
+        }
+    }
+    Ok(tip)
+}
+
+/// Copies the static files into `dest` and returns their names.
+fn copy_static_files(
+    static_file_provider: &StaticFileProvider,
+    dest: &Path,
+) -> Result<Vec<String>, BackupError> {
+    reth_fs_util::create_dir_all(dest)?;
+
+    let mut files = Vec::new();
+    for entry in reth_fs_util::read_dir(static_file_provider.directory())? {
+        let path = entry?.path();
+        let Some((segment, range)) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(StaticFileSegment::parse_filename)
+        else {
+            continue
+        };
+        files.push((path, segment, range));
+    }
+
+    // the configuration of a jar records the number of committed rows, it's copied before the data
+    // that it describes, so the copied data is never behind its configuration
+    files.sort_by_key(|(path, _, _)| {
+        path.extension().map_or(true, |extension| extension != STATIC_FILE_CONFIG_EXTENSION)
+    });
+
+    let mut names = Vec::with_capacity(files.len());
+    for (path, segment, range) in files {
+        let Some(name) = path.file_name() else { continue };
+        let target = dest.join(name);
+
+        // only the jar with the highest block range of a segment is still being written to
+        let is_latest = static_file_provider
+            .get_highest_static_file_block(segment)
+            .map_or(true, |highest| find_fixed_range(highest).start() <= range.start());
+        if is_latest || fs::hard_link(&path, &target).is_err() {
+            fs::copy(&path, &target)?;
+        }
+
+        names.push(name.to_string_lossy().into_owned());
+    }
+    names.sort_unstable();
+
+    Ok(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init::init_genesis;
+    use reth_chainspec::MAINNET;
+    use reth_db_api::transaction::DbTxMut;
+    use reth_primitives::{Receipt, MAINNET_GENESIS_HASH};
+    use reth_provider::{
+        test_utils::{create_test_provider_factory, create_test_provider_factory_with_chain_spec},
+        HeaderProvider,
+    };
+    use reth_prune_types::{PruneCheckpoint, PruneMode};
+
+    #[test]
+    fn static_files_tip_segments() {
+        let factory = create_test_provider_factory();
+        let db = factory.db_ref();
+        let highest = |segment| match segment {
+            StaticFileSegment::Headers => Some(10),
+            StaticFileSegment::Transactions => Some(9),
+            StaticFileSegment::Receipts => Some(8),
+            StaticFileSegment::AccountChangeSets | StaticFileSegment::StorageChangeSets => Some(5),
+            StaticFileSegment::TransactionSenders => None,
+        };
+
+        // the changesets above the static files are still in the database
+        assert_eq!(static_files_tip(db, highest).unwrap(), Some(8));
+
+        // receipts are in the database
+        db.update(|tx| tx.put::<tables::Receipts>(0, Receipt::default())).unwrap().unwrap();
+        assert_eq!(static_files_tip(db, highest).unwrap(), Some(9));
+
+        // the changesets were truncated below the blocks that were deleted from the database
+        db.update(|tx| {
+            tx.put::<tables::PruneCheckpoints>(
+                PruneSegment::AccountChangeSets,
+                PruneCheckpoint {
+                    block_number: Some(7),
+                    tx_number: None,
+                    prune_mode: PruneMode::Before(8),
+                },
+            )
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(static_files_tip(db, highest).unwrap(), Some(5));
+    }
+
+    #[test]
+    fn backup_genesis() {
+        let factory = create_test_provider_factory_with_chain_spec(MAINNET.clone());
+        init_genesis(factory.clone()).unwrap();
+
+        let dest = tempfile::tempdir().unwrap();
+        let manifest = backup(&factory, dest.path()).unwrap();
+        assert_eq!(manifest.tip, 0);
+        assert_eq!(manifest.tip_hash, MAINNET_GENESIS_HASH);
+        assert!(manifest.static_files.iter().any(|name| name.starts_with("static_file_headers")));
+
+        // the backup can't be taken into a non-empty directory
+        assert!(matches!(backup(&factory, dest.path()), Err(BackupError::DestinationNotEmpty(_))));
+
+        let written: BackupManifest = serde_json::from_slice(
+            &reth_fs_util::read(dest.path().join(BACKUP_MANIFEST_FILE_NAME)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(written, manifest);
+
+        // the backup can be opened as a datadir
+        let backup_factory = ProviderFactory::new(
+            DatabaseEnv::open(
+                &dest.path().join(BACKUP_DB_DIR),
+                DatabaseEnvKind::RO,
+                DatabaseArguments::new(ClientVersion::default()),
+            )
+            .unwrap(),
+            MAINNET.clone(),
+            StaticFileProvider::read_only(dest.path().join(BACKUP_STATIC_FILES_DIR)).unwrap(),
+        );
+        assert_eq!(
+            backup_factory.sealed_header(0).unwrap().map(|header| header.hash()),
+            Some(MAINNET_GENESIS_HASH)
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Consistency checks between the database and static files.
+//!
+//! A crash can leave static files and the database at different heights, since they are
+//! committed separately. [`check_consistency`] cross-checks them without modifying either, and
+//! reports what has to be repaired.
+
+use reth_db::tables;
+use reth_db_api::{cursor::DbCursorRO, database::Database, transaction::DbTx};
+use reth_primitives::{BlockNumber, StaticFileSegment, TxNumber};
+use reth_provider::{
+    BlockReader, ProviderFactory, ProviderResult, PruneCheckpointWriter, StageCheckpointReader,
+    StaticFileProviderFactory, StaticFileWriter,
+};
+use reth_prune_types::{PruneCheckpoint, PruneSegment};
+use reth_stages_types::StageId;
+use std::fmt;
+use tracing::info;
+
+/// An inconsistency between the database and the static files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// A static file segment is ahead of the checkpoint of the stage that writes it, the database
+    /// commit of the stage failed after the static files were committed.
+    StaticFileAhead {
+        /// The static file segment.
+        segment: StaticFileSegment,
+        /// The highest block of the segment.
+        static_file_block: BlockNumber,
+        /// The checkpoint of the stage that writes the segment.
+        checkpoint: BlockNumber,
+    },
+    /// A static file segment is behind the checkpoint of the stage that writes it, static file
+    /// data was lost.
+    StaticFileBehind {
+        /// The static file segment.
+        segment: StaticFileSegment,
+        /// The highest block of the segment.
+        static_file_block: BlockNumber,
+        /// The checkpoint of the stage that writes the segment.
+        checkpoint: BlockNumber,
+    },
+    /// The transactions of a static file segment don't match the `BlockBodyIndices` of its
+    /// highest block.
+    StaticFileTransactions {
+        /// The static file segment.
+        segment: StaticFileSegment,
+        /// The number of transactions in the segment.
+        static_file_txs: TxNumber,
+        /// The number of transactions up to the highest block of the segment, according to
+        /// `BlockBodyIndices`.
+        expected_txs: TxNumber,
+        /// The highest block whose transactions are all in the segment.
+        consistent_block: BlockNumber,
+    },
+    /// The highest block of `BlockBodyIndices` does not match the `Bodies` checkpoint.
+    BodyIndices {
+        /// The highest block of `BlockBodyIndices`.
+        last_block: BlockNumber,
+        /// The `Bodies` checkpoint.
+        checkpoint: BlockNumber,
+    },
+    /// The highest entry of `TransactionBlocks` does not match `BlockBodyIndices`.
+    TransactionBlocks {
+        /// The highest entry of `TransactionBlocks`.
+        last_entry: Option<(TxNumber, BlockNumber)>,
+        /// The last transaction of the highest non-empty block of `BlockBodyIndices`.
+        expected_entry: Option<(TxNumber, BlockNumber)>,
+    },
+    /// A prune checkpoint is ahead of the data it was pruned from.
+    PruneCheckpointAhead {
+        /// The prune segment.
+        segment: PruneSegment,
+        /// The highest pruned block.
+        pruned_block: BlockNumber,
+        /// The highest block of the data the segment prunes.
+        highest_block: BlockNumber,
+    },
+}
+
+impl Inconsistency {
+    /// Returns the block that the node has to be unwound to for repairing the inconsistency, if
+    /// any.
+    pub fn unwind_target(&self) -> Option<BlockNumber> {
+        match *self {
+            Self::StaticFileBehind { static_file_block, .. } => Some(static_file_block),
+            Self::StaticFileTransactions { consistent_block, .. } => Some(consistent_block),
+            Self::BodyIndices { last_block, checkpoint } => Some(last_block.min(checkpoint)),
+            Self::TransactionBlocks { last_entry, expected_entry } => {
+                let last_block = last_entry.map_or(0, |(_, block)| block);
+                let expected_block = expected_entry.map_or(0, |(_, block)| block);
+                Some(last_block.min(expected_block).saturating_sub(1))
+            }
+            Self::StaticFileAhead { .. } | Self::PruneCheckpointAhead { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StaticFileAhead { segment, static_file_block, checkpoint } => write!(
+                f,
+                "{segment} static files end at block {static_file_block}, ahead of the stage checkpoint {checkpoint}"
+            ),
+            Self::StaticFileBehind { segment, static_file_block, checkpoint } => write!(
+                f,
+                "{segment} static files end at block {static_file_block}, behind the stage checkpoint {checkpoint}"
+            ),
+            Self::StaticFileTransactions { segment, static_file_txs, expected_txs, .. } => write!(
+                f,
+                "{segment} static files contain {static_file_txs} transactions, block body indices expect {expected_txs}"
+            ),
+            Self::BodyIndices { last_block, checkpoint } => write!(
+                f,
+                "block body indices end at block {last_block}, the bodies checkpoint is {checkpoint}"
+            ),
+            Self::TransactionBlocks { last_entry, expected_entry } => write!(
+                f,
+                "last transaction block entry is {last_entry:?}, block body indices expect {expected_entry:?}"
+            ),
+            Self::PruneCheckpointAhead { segment, pruned_block, highest_block } => write!(
+                f,
+                "{segment} is pruned up to block {pruned_block}, beyond its data which ends at block {highest_block}"
+            ),
+        }
+    }
+}
+
+/// The heights of a static file segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StaticFileHeight {
+    /// The static file segment.
+    pub segment: StaticFileSegment,
+    /// The highest block of the segment.
+    pub block: Option<BlockNumber>,
+    /// The highest transaction of the segment, if the segment is transaction based.
+    pub tx: Option<TxNumber>,
+}
+
+/// The result of [`check_consistency`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    /// The heights of the checked static file segments.
+    pub static_files: Vec<StaticFileHeight>,
+    /// The stage checkpoints.
+    pub checkpoints: Vec<(StageId, BlockNumber)>,
+    /// The prune checkpoints.
+    pub prune_checkpoints: Vec<(PruneSegment, PruneCheckpoint)>,
+    /// The inconsistencies found.
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl ConsistencyReport {
+    /// Returns `true` if no inconsistencies were found.
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+
+    /// Returns the highest block that all inconsistencies can be repaired at, if an unwind is
+    /// required.
+    pub fn unwind_target(&self) -> Option<BlockNumber> {
+        self.inconsistencies.iter().filter_map(Inconsistency::unwind_target).min()
+    }
+
+    fn checkpoint(&self, stage: StageId) -> BlockNumber {
+        self.checkpoints
+            .iter()
+            .find_map(|(id, checkpoint)| (*id == stage).then_some(*checkpoint))
+            .unwrap_or_default()
+    }
+}
+
+/// Cross-checks every static file segment against `BlockBodyIndices`, `TransactionBlocks`, the
+/// stage checkpoints and the prune checkpoints, without modifying any of them.
+///
+/// Receipts are only checked if `has_receipt_pruning` is `false`, since pruned nodes store
+/// receipts in the database.
+pub fn check_consistency<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    has_receipt_pruning: bool,
+) -> ProviderResult<ConsistencyReport> {
+    let provider = provider_factory.provider()?;
+    let static_file_provider = provider_factory.static_file_provider();
+    let mut report = ConsistencyReport::default();
+
+    for stage in StageId::ALL {
+        let checkpoint = provider.get_stage_checkpoint(stage)?.unwrap_or_default().block_number;
+        report.checkpoints.push((stage, checkpoint));
+    }
+
+    for segment in
+        [StaticFileSegment::Headers, StaticFileSegment::Transactions, StaticFileSegment::Receipts]
+    {
+        if has_receipt_pruning && segment.is_receipts() {
+            continue
+        }
+
+        let height = StaticFileHeight {
+            segment,
+            block: static_file_provider.get_highest_static_file_block(segment),
+            tx: static_file_provider.get_highest_static_file_tx(segment),
+        };
+        report.static_files.push(height);
+
+        let static_file_block = height.block.unwrap_or_default();
+        let checkpoint = report.checkpoint(match segment {
+            StaticFileSegment::Headers => StageId::Headers,
+            StaticFileSegment::Transactions => StageId::Bodies,
+            StaticFileSegment::Receipts |
+            StaticFileSegment::AccountChangeSets |
+            StaticFileSegment::StorageChangeSets => StageId::Execution,
+            StaticFileSegment::TransactionSenders => StageId::SenderRecovery,
+        });
+        if static_file_block > checkpoint {
+            report.inconsistencies.push(Inconsistency::StaticFileAhead {
+                segment,
+                static_file_block,
+                checkpoint,
+            });
+        } else if static_file_block < checkpoint {
+            report.inconsistencies.push(Inconsistency::StaticFileBehind {
+                segment,
+                static_file_block,
+                checkpoint,
+            });
+        }
+
+        if segment.is_headers() {
+            continue
+        }
+
+        // the segment has to contain exactly the transactions of its blocks, if the indices of
+        // its highest block are missing, the segment is ahead of the database which is reported
+        // above
+        let static_file_txs = height.tx.map_or(0, |tx| tx + 1);
+        let Some(indices) = provider.block_body_indices(static_file_block)? else { continue };
+        if indices.next_tx_num() != static_file_txs {
+            let mut consistent_block = static_file_block;
+            while consistent_block > 0 {
+                consistent_block -= 1;
+                if provider
+                    .block_body_indices(consistent_block)?
+                    .map_or(true, |indices| indices.next_tx_num() <= static_file_txs)
+                {
+                    break
+                }
+            }
+            report.inconsistencies.push(Inconsistency::StaticFileTransactions {
+                segment,
+                static_file_txs,
+                expected_txs: indices.next_tx_num(),
+                consistent_block,
+            });
+        }
+    }
+
+    let bodies_checkpoint = report.checkpoint(StageId::Bodies);
+    let mut body_indices = provider.tx_ref().cursor_read::<tables::BlockBodyIndices>()?;
+    let last_block = body_indices.last()?.map(|(block, _)| block).unwrap_or_default();
+    if last_block != bodies_checkpoint {
+        report
+            .inconsistencies
+            .push(Inconsistency::BodyIndices { last_block, checkpoint: bodies_checkpoint });
+    }
+
+    let mut expected_entry = None;
+    for entry in body_indices.walk_back(None)? {
+        let (block, indices) = entry?;
+        if !indices.is_empty() {
+            expected_entry = Some((indices.last_tx_num(), block));
+            break
+        }
+    }
+    let last_entry = provider.tx_ref().cursor_read::<tables::TransactionBlocks>()?.last()?;
+    if last_entry != expected_entry {
+        report
+            .inconsistencies
+            .push(Inconsistency::TransactionBlocks { last_entry, expected_entry });
+    }
+
+    for entry in provider.tx_ref().cursor_read::<tables::PruneCheckpoints>()?.walk(None)? {
+        let (segment, prune_checkpoint) = entry?;
+        report.prune_checkpoints.push((segment, prune_checkpoint));
+
+        let Some(pruned_block) = prune_checkpoint.block_number else { continue };
+        let highest_block = match segment {
+            PruneSegment::SenderRecovery => report.checkpoint(StageId::SenderRecovery),
+            PruneSegment::TransactionLookup => report.checkpoint(StageId::TransactionLookup),
+            PruneSegment::Receipts | PruneSegment::ContractLogs => {
+                report.checkpoint(StageId::Execution)
+            }
+            PruneSegment::AccountHistory => report.checkpoint(StageId::IndexAccountHistory),
+            PruneSegment::StorageHistory => report.checkpoint(StageId::IndexStorageHistory),
+            PruneSegment::AddressTransactions => {
+                report.checkpoint(StageId::IndexAddressTransactions)
+            }
+            PruneSegment::Headers => static_file_provider
+                .get_highest_static_file_block(StaticFileSegment::Headers)
+                .unwrap_or_default(),
+            PruneSegment::Transactions => static_file_provider
+                .get_highest_static_file_block(StaticFileSegment::Transactions)
+                .unwrap_or_default(),
+            PruneSegment::AccountChangeSets => static_file_provider
+                .get_highest_static_file_block(StaticFileSegment::AccountChangeSets)
+                .unwrap_or_default(),
+            PruneSegment::StorageChangeSets => static_file_provider
+                .get_highest_static_file_block(StaticFileSegment::StorageChangeSets)
+                .unwrap_or_default(),
+        };
+        if pruned_block > highest_block {
+            report.inconsistencies.push(Inconsistency::PruneCheckpointAhead {
+                segment,
+                pruned_block,
+                highest_block,
+            });
+        }
+    }
+
+    Ok(report)
+}
+
+/// Truncates the static file segments that are ahead of their stage checkpoints.
+pub fn truncate_static_files<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    report: &ConsistencyReport,
+) -> ProviderResult<()> {
+    let provider = provider_factory.provider()?;
+    let static_file_provider = provider_factory.static_file_provider();
+
+    for inconsistency in &report.inconsistencies {
+        let Inconsistency::StaticFileAhead { segment, static_file_block, checkpoint } =
+            *inconsistency
+        else {
+            continue
+        };
+        info!(target: "reth::db::check", ?segment, from = static_file_block, to = checkpoint, "Truncating static files");
+
+        let mut writer = static_file_provider.latest_writer(segment)?;
+        if segment.is_headers() {
+            writer.prune_headers(static_file_block - checkpoint)?;
+        } else if let Some(indices) = provider.block_body_indices(checkpoint)? {
+            let static_file_txs =
+                static_file_provider.get_highest_static_file_tx(segment).map_or(0, |tx| tx + 1);
+            let to_delete = static_file_txs.saturating_sub(indices.next_tx_num());
+            if segment.is_receipts() {
+                writer.prune_receipts(to_delete, checkpoint)?;
+            } else {
+                writer.prune_transactions(to_delete, checkpoint)?;
+            }
+        }
+        writer.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Lowers the prune checkpoints that are ahead of their data to the highest block of the data.
+///
+/// The blocks between the two are synced again and are pruned on the next pruner run.
+pub fn reset_prune_checkpoints<DB: Database>(
+    provider_factory: &ProviderFactory<DB>,
+    report: &ConsistencyReport,
+) -> ProviderResult<()> {
+    let provider = provider_factory.provider_rw()?;
+
+    for inconsistency in &report.inconsistencies {
+        let Inconsistency::PruneCheckpointAhead { segment, highest_block, .. } = *inconsistency
+        else {
+            continue
+        };
+        let Some((_, prune_checkpoint)) =
+            report.prune_checkpoints.iter().find(|(prune_segment, _)| *prune_segment == segment)
+        else {
+            continue
+        };
+        info!(target: "reth::db::check", ?segment, block = highest_block, "Resetting prune checkpoint");
+
+        let tx_number = match prune_checkpoint.tx_number {
+            Some(_) => provider.block_body_indices(highest_block)?.map(|i| i.last_tx_num()),
+            None => None,
+        };
+        provider.save_prune_checkpoint(
+            segment,
+            PruneCheckpoint {
+                block_number: Some(highest_block),
+                tx_number,
+                prune_mode: prune_checkpoint.prune_mode,
+            },
+        )?;
+    }
+
+    provider.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::init::init_genesis;
+    use reth_chainspec::MAINNET;
+    use reth_provider::{
+        test_utils::create_test_provider_factory_with_chain_spec, StageCheckpointWriter,
+    };
+    use reth_prune_types::PruneMode;
+    use reth_stages_types::StageCheckpoint;
+
+    #[test]
+    fn check_genesis() {
+        let factory = create_test_provider_factory_with_chain_spec(MAINNET.clone());
+        init_genesis(factory.clone()).unwrap();
+
+        let report = check_consistency(&factory, false).unwrap();
+        assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+        assert_eq!(report.static_files.len(), 3);
+        assert_eq!(check_consistency(&factory, true).unwrap().static_files.len(), 2);
+    }
+
+    #[test]
+    fn check_and_repair_checkpoints() {
+        let factory = create_test_provider_factory_with_chain_spec(MAINNET.clone());
+        init_genesis(factory.clone()).unwrap();
+
+        let provider = factory.provider_rw().unwrap();
+        provider.save_stage_checkpoint(StageId::Headers, StageCheckpoint::new(10)).unwrap();
+        provider
+            .save_prune_checkpoint(
+                PruneSegment::SenderRecovery,
+                PruneCheckpoint {
+                    block_number: Some(5),
+                    tx_number: None,
+                    prune_mode: PruneMode::Full,
+                },
+            )
+            .unwrap();
+        provider.commit().unwrap();
+
+        let report = check_consistency(&factory, false).unwrap();
+        assert_eq!(
+            report.inconsistencies,
+            vec![
+                Inconsistency::StaticFileBehind {
+                    segment: StaticFileSegment::Headers,
+                    static_file_block: 0,
+                    checkpoint: 10
+                },
+                Inconsistency::PruneCheckpointAhead {
+                    segment: PruneSegment::SenderRecovery,
+                    pruned_block: 5,
+                    highest_block: 0
+                },
+            ]
+        );
+        assert_eq!(report.unwind_target(), Some(0));
+
+        reset_prune_checkpoints(&factory, &report).unwrap();
+        let report = check_consistency(&factory, false).unwrap();
+        assert_eq!(report.inconsistencies.len(), 1);
+    }
+}
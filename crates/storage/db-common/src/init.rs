@@ -536,7 +536,9 @@ mod tests {
     };
     use reth_primitives::{GOERLI_GENESIS_HASH, MAINNET_GENESIS_HASH, SEPOLIA_GENESIS_HASH};
     use reth_primitives_traits::IntegerList;
-    use reth_provider::test_utils::create_test_provider_factory_with_chain_spec;
+    use reth_provider::test_utils::{
+        create_test_provider_factory_in_memory, create_test_provider_factory_with_chain_spec,
+    };
 
     fn collect_table_entries<DB, T>(
         tx: &<DB as Database>::TX,
@@ -557,6 +559,18 @@ mod tests {
         assert_eq!(genesis_hash, MAINNET_GENESIS_HASH);
     }
 
+    #[test]
+    fn success_init_genesis_in_memory() {
+        let factory = create_test_provider_factory_in_memory(MAINNET.clone());
+        let genesis_hash = init_genesis(factory.clone()).unwrap();
+
+        // actual, expected
+        assert_eq!(genesis_hash, MAINNET_GENESIS_HASH);
+        assert_eq!(factory.block_hash(0).unwrap(), Some(MAINNET_GENESIS_HASH));
+        // already initialized
+        assert_eq!(init_genesis(factory).unwrap(), MAINNET_GENESIS_HASH);
+    }
+
     #[test]
     fn success_init_genesis_goerli() {
         let genesis_hash =
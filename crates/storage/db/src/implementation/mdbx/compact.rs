@@ -0,0 +1,253 @@
+//! Compaction of the MDBX environment.
+
+use super::{DatabaseEnv, DatabaseEnvKind, MDBX_DATA_FILE};
+use crate::DatabaseError;
+use reth_db_api::database::{CompactedCopy, CompactionOutcome, DatabaseCompact, FreeSpace};
+use reth_tracing::tracing::{debug, info};
+use std::{
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+/// The name of the directory, inside the database directory, the compacted copy is written to.
+const COMPACT_DIR: &str = "compact";
+
+/// How often the open transactions are checked while waiting to swap the compacted copy in.
+const SWAP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+impl DatabaseCompact for DatabaseEnv {
+    fn free_space(&self) -> Result<FreeSpace, DatabaseError> {
+        let env = self.env()?;
+        let total = file_size(&self.path.join(MDBX_DATA_FILE))?;
+        let page_size = env.stat().map_err(|e| DatabaseError::Stats(e.into()))?.page_size() as u64;
+        let freelist = env.freelist().map_err(|e| DatabaseError::Stats(e.into()))? as u64;
+        // Page numbers are 0 based
+        let allocated =
+            env.info().map_err(|e| DatabaseError::Stats(e.into()))?.last_pgno() as u64 + 1;
+
+        let used = allocated.saturating_sub(freelist) * page_size;
+        Ok(FreeSpace { free: total.saturating_sub(used), total })
+    }
+
+    fn last_txn_id(&self) -> Result<u64, DatabaseError> {
+        Ok(self.env()?.info().map_err(|e| DatabaseError::Stats(e.into()))?.last_txnid() as u64)
+    }
+
+    /// Copies the environment without free pages into a directory next to the database file.
+    ///
+    /// The copy is taken under a read transaction, so it doesn't block writes.
+    fn copy_compacted(&self) -> Result<CompactedCopy, DatabaseError> {
+        let env = self.env()?;
+        if env.is_read_only() {
+            return Err(DatabaseError::Other("cannot compact a read-only database".to_string()))
+        }
+
+        let compact_dir = self.path.join(COMPACT_DIR);
+        let path = compact_dir.join(MDBX_DATA_FILE);
+
+        let size_before = file_size(&self.path.join(MDBX_DATA_FILE))?;
+        let txn_id = env.info().map_err(|e| DatabaseError::Stats(e.into()))?.last_txnid() as u64;
+
+        // Leftovers of an interrupted compaction
+        if compact_dir.exists() {
+            reth_fs_util::remove_dir_all(&compact_dir)
+                .map_err(|e| DatabaseError::Other(e.to_string()))?;
+        }
+        reth_fs_util::create_dir_all(&compact_dir)
+            .map_err(|e| DatabaseError::Other(e.to_string()))?;
+
+        debug!(target: "storage::db::mdbx", ?path, "Copying database without free pages");
+        if let Err(err) = env.copy(&path, true) {
+            let _ = reth_fs_util::remove_dir_all(&compact_dir);
+            return Err(DatabaseError::Copy(err.into()))
+        }
+
+        let size_after = file_size(&path)?;
+        Ok(CompactedCopy { path, txn_id, size_before, size_after })
+    }
+
+    /// Atomically renames the compacted copy over the database file, and reopens the environment.
+    ///
+    /// The swap is aborted if a write transaction was committed since the copy was taken, or if
+    /// the lock file has readers of other processes, e.g. a `--secondary` node or a `reth db`
+    /// command. Those would keep reading the replaced file.
+    fn swap_compacted(
+        &self,
+        copy: CompactedCopy,
+        swap_timeout: Duration,
+    ) -> Result<CompactionOutcome, DatabaseError> {
+        let compact_dir = self.path.join(COMPACT_DIR);
+        let abort = |reason: String| {
+            let _ = reth_fs_util::remove_dir_all(&compact_dir);
+            Err(DatabaseError::Other(format!("database compaction aborted: {reason}")))
+        };
+
+        // Blocks new transactions until the compacted copy is swapped in
+        let mut inner = self.inner.write();
+        let Some(env) = inner.as_ref() else { return abort("environment is closed".to_string()) };
+
+        let start = Instant::now();
+        while env.handles() > 1 {
+            if start.elapsed() > swap_timeout {
+                return abort(format!("transactions are still open after {swap_timeout:?}"))
+            }
+            sleep(SWAP_POLL_INTERVAL);
+        }
+
+        if env.info().map_err(|e| DatabaseError::Stats(e.into()))?.last_txnid() as u64 !=
+            copy.txn_id
+        {
+            return abort("database was written to during the compaction".to_string())
+        }
+
+        let pid = std::process::id();
+        let other_readers = env
+            .reader_pids()
+            .map_err(|e| DatabaseError::Stats(e.into()))?
+            .into_iter()
+            .filter(|reader| *reader != pid)
+            .collect::<Vec<_>>();
+        if !other_readers.is_empty() {
+            return abort(format!("database is open in other processes: {other_readers:?}"))
+        }
+
+        // Closes the environment, it has no other handles left
+        inner.take();
+
+        // If the rename fails, the environment is reopened with the original file
+        let renamed = reth_fs_util::rename(&copy.path, self.path.join(MDBX_DATA_FILE));
+        *inner = Some(Self::open_environment(&self.path, &DatabaseEnvKind::RW, &self.args)?);
+        drop(inner);
+
+        if let Err(err) = renamed {
+            return abort(err.to_string())
+        }
+        reth_fs_util::remove_dir_all(&compact_dir)
+            .map_err(|e| DatabaseError::Other(e.to_string()))?;
+
+        let outcome =
+            CompactionOutcome { size_before: copy.size_before, size_after: copy.size_after };
+        info!(
+            target: "storage::db::mdbx",
+            size_before = outcome.size_before,
+            size_after = outcome.size_after,
+            reclaimed = outcome.reclaimed(),
+            swap_duration = ?start.elapsed(),
+            "Compacted database"
+        );
+
+        Ok(outcome)
+    }
+
+    fn discard_compacted(&self, _copy: CompactedCopy) -> Result<(), DatabaseError> {
+        reth_fs_util::remove_dir_all(self.path.join(COMPACT_DIR))
+            .map_err(|e| DatabaseError::Other(e.to_string()))
+    }
+}
+
+fn file_size(path: &std::path::Path) -> Result<u64, DatabaseError> {
+    reth_fs_util::metadata(path)
+        .map(|metadata| metadata.len())
+        .map_err(|e| DatabaseError::Other(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        mdbx::DatabaseArguments,
+        tables::{CanonicalHeaders, Headers},
+    };
+    use reth_db_api::{
+        cursor::DbCursorRO,
+        database::Database,
+        models::ClientVersion,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{Header, B256};
+
+    fn open_db(path: &std::path::Path) -> DatabaseEnv {
+        let env = DatabaseEnv::open(
+            path,
+            DatabaseEnvKind::RW,
+            DatabaseArguments::new(ClientVersion::default()),
+        )
+        .unwrap();
+        env.create_tables().unwrap();
+        env
+    }
+
+    #[test]
+    fn compact_reclaims_free_pages() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = open_db(dir.path());
+
+        // Fill the database and delete most of it, so that its pages end up on the freelist
+        let tx = env.tx_mut().unwrap();
+        for number in 0..20_000 {
+            tx.put::<Headers>(
+                number,
+                Header { number, extra_data: vec![0; 256].into(), ..Default::default() },
+            )
+            .unwrap();
+            tx.put::<CanonicalHeaders>(number, B256::with_last_byte(number as u8)).unwrap();
+        }
+        tx.commit().unwrap();
+        let tx = env.tx_mut().unwrap();
+        tx.clear::<Headers>().unwrap();
+        tx.commit().unwrap();
+
+        let free_space = env.free_space().unwrap();
+        assert!(free_space.free > 0);
+
+        let outcome = env.compact(Duration::from_secs(1)).unwrap();
+        assert_eq!(outcome.size_before, free_space.total);
+        assert!(outcome.reclaimed() > 0);
+        assert!(!dir.path().join(COMPACT_DIR).exists());
+
+        // The swapped in environment has the remaining data and can be written to
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.entries::<Headers>().unwrap(), 0);
+        assert_eq!(
+            tx.cursor_read::<CanonicalHeaders>().unwrap().walk(None).unwrap().count(),
+            20_000
+        );
+        drop(tx);
+        let tx = env.tx_mut().unwrap();
+        tx.put::<Headers>(0, Header::default()).unwrap();
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn compact_aborts_with_open_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = open_db(dir.path());
+
+        let tx = env.tx().unwrap();
+        assert!(env.compact(Duration::from_millis(50)).is_err());
+        assert!(!dir.path().join(COMPACT_DIR).exists());
+
+        // The environment is still usable
+        drop(tx);
+        env.tx().unwrap();
+    }
+
+    #[test]
+    fn compact_discards_stale_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = open_db(dir.path());
+
+        let copy = env.copy_compacted().unwrap();
+        assert!(copy.path.exists());
+
+        // A write committed after the copy was taken is not in the copy
+        let tx = env.tx_mut().unwrap();
+        tx.put::<Headers>(0, Header::default()).unwrap();
+        tx.commit().unwrap();
+        assert!(env.last_txn_id().unwrap() > copy.txn_id);
+
+        assert!(env.swap_compacted(copy, Duration::from_secs(1)).is_err());
+        assert!(!dir.path().join(COMPACT_DIR).exists());
+        assert_eq!(env.tx().unwrap().entries::<Headers>().unwrap(), 1);
+    }
+}
@@ -9,9 +9,10 @@ use crate::{
 };
 use eyre::Context;
 use metrics::{gauge, Label};
+use parking_lot::RwLock;
 use reth_db_api::{
     cursor::{DbCursorRO, DbCursorRW},
-    database::Database,
+    database::{Database, DatabaseCopy},
     database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics},
     models::client_version::ClientVersion,
     transaction::{DbTx, DbTxMut},
@@ -23,13 +24,13 @@ use reth_libmdbx::{
 use reth_storage_errors::db::LogLevel;
 use reth_tracing::tracing::error;
 use std::{
-    ops::Deref,
-    path::Path,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
 use tx::Tx;
 
+mod compact;
 pub mod cursor;
 pub mod tx;
 
@@ -43,6 +44,9 @@ const DEFAULT_MAX_READERS: u64 = 32_000;
 /// See [`reth_libmdbx::EnvironmentBuilder::set_handle_slow_readers`] for more information.
 const MAX_SAFE_READER_SPACE: usize = 10 * GIGABYTE;
 
+/// The name of the MDBX data file inside the database directory.
+const MDBX_DATA_FILE: &str = "mdbx.dat";
+
 /// Environment used when opening a MDBX environment. RO/RW.
 #[derive(Debug)]
 pub enum DatabaseEnvKind {
@@ -133,7 +137,14 @@ impl DatabaseArguments {
 #[derive(Debug)]
 pub struct DatabaseEnv {
     /// Libmdbx-sys environment.
-    inner: Environment,
+    ///
+    /// It's only `None` if reopening the environment failed after a compaction, see
+    /// [`DatabaseCompact`](reth_db_api::database::DatabaseCompact).
+    inner: RwLock<Option<Environment>>,
+    /// Path of the database directory.
+    path: PathBuf,
+    /// Arguments the environment was opened with, used to reopen it after a compaction.
+    args: DatabaseArguments,
     /// Cache for metric handles. If `None`, metrics are not recorded.
     metrics: Option<Arc<DatabaseEnvMetrics>>,
     /// Write lock for when dealing with a read-write environment.
@@ -146,7 +157,7 @@ impl Database for DatabaseEnv {
 
     fn tx(&self) -> Result<Self::TX, DatabaseError> {
         Tx::new_with_metrics(
-            self.inner.begin_ro_txn().map_err(|e| DatabaseError::InitTx(e.into()))?,
+            self.env()?.begin_ro_txn().map_err(|e| DatabaseError::InitTx(e.into()))?,
             self.metrics.as_ref().cloned(),
         )
         .map_err(|e| DatabaseError::InitTx(e.into()))
@@ -154,7 +165,7 @@ impl Database for DatabaseEnv {
 
     fn tx_mut(&self) -> Result<Self::TXMut, DatabaseError> {
         Tx::new_with_metrics(
-            self.inner.begin_rw_txn().map_err(|e| DatabaseError::InitTx(e.into()))?,
+            self.env()?.begin_rw_txn().map_err(|e| DatabaseError::InitTx(e.into()))?,
             self.metrics.as_ref().cloned(),
         )
         .map_err(|e| DatabaseError::InitTx(e.into()))
@@ -220,19 +231,21 @@ impl DatabaseMetrics for DatabaseEnv {
             })
             .map_err(|error| error!(%error, "Failed to read db table stats"));
 
+        let Ok(env) = self.env() else { return metrics };
+
         if let Ok(freelist) =
-            self.freelist().map_err(|error| error!(%error, "Failed to read db.freelist"))
+            env.freelist().map_err(|error| error!(%error, "Failed to read db.freelist"))
         {
             metrics.push(("db.freelist", freelist as f64, vec![]));
         }
 
-        if let Ok(stat) = self.stat().map_err(|error| error!(%error, "Failed to read db.stat")) {
+        if let Ok(stat) = env.stat().map_err(|error| error!(%error, "Failed to read db.stat")) {
             metrics.push(("db.page_size", stat.page_size() as f64, vec![]));
         }
 
         metrics.push((
             "db.timed_out_not_aborted_transactions",
-            self.timed_out_not_aborted_transactions() as f64,
+            env.timed_out_not_aborted_transactions() as f64,
             vec![],
         ));
 
@@ -242,7 +255,16 @@ impl DatabaseMetrics for DatabaseEnv {
 
 impl DatabaseMetadata for DatabaseEnv {
     fn metadata(&self) -> DatabaseMetadataValue {
-        DatabaseMetadataValue::new(self.freelist().ok())
+        DatabaseMetadataValue::new(self.env().ok().and_then(|env| env.freelist().ok()))
+    }
+}
+
+impl DatabaseCopy for DatabaseEnv {
+    fn copy_to(&self, dest: &Path, compact: bool) -> Result<(), DatabaseError> {
+        reth_fs_util::create_dir_all(dest).map_err(|e| DatabaseError::Other(e.to_string()))?;
+        self.env()?
+            .copy(&dest.join(MDBX_DATA_FILE), compact)
+            .map_err(|e| DatabaseError::Copy(e.into()))
     }
 }
 
@@ -264,6 +286,23 @@ impl DatabaseEnv {
             None
         };
 
+        let env = Self {
+            inner: RwLock::new(Some(Self::open_environment(path, &kind, &args)?)),
+            path: path.to_path_buf(),
+            args,
+            metrics: None,
+            _lock_file,
+        };
+
+        Ok(env)
+    }
+
+    /// Opens the libmdbx environment at the specified path with the given `EnvKind`.
+    fn open_environment(
+        path: &Path,
+        kind: &DatabaseEnvKind,
+        args: &DatabaseArguments,
+    ) -> Result<Environment, DatabaseError> {
         let mut inner_env = Environment::builder();
 
         let mode = match kind {
@@ -402,13 +441,14 @@ impl DatabaseEnv {
             inner_env.set_max_read_transaction_duration(max_read_transaction_duration);
         }
 
-        let env = Self {
-            inner: inner_env.open(path).map_err(|e| DatabaseError::Open(e.into()))?,
-            metrics: None,
-            _lock_file,
-        };
+        inner_env.open(path).map_err(|e| DatabaseError::Open(e.into()))
+    }
 
-        Ok(env)
+    /// Returns the libmdbx environment.
+    pub fn env(&self) -> Result<Environment, DatabaseError> {
+        self.inner.read().clone().ok_or_else(|| {
+            DatabaseError::Other("database environment was closed by a failed compaction".into())
+        })
     }
 
     /// Enables metrics on the database.
@@ -419,7 +459,7 @@ impl DatabaseEnv {
 
     /// Creates all the defined tables, if necessary.
     pub fn create_tables(&self) -> Result<(), DatabaseError> {
-        let tx = self.inner.begin_rw_txn().map_err(|e| DatabaseError::InitTx(e.into()))?;
+        let tx = self.env()?.begin_rw_txn().map_err(|e| DatabaseError::InitTx(e.into()))?;
 
         for table in Tables::ALL {
             let flags = match table.table_type() {
@@ -458,14 +498,6 @@ impl DatabaseEnv {
     }
 }
 
-impl Deref for DatabaseEnv {
-    type Target = Environment;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,299 @@
+//! Cursors of the in-memory database.
+
+use super::{is_dupsort, tx::update_table, write_error, Entry, MemTable, Snapshot};
+use crate::{tables::utils::decoder, DatabaseError};
+use parking_lot::RwLock;
+use reth_db_api::{
+    common::{PairResult, ValueOnlyResult},
+    cursor::{
+        DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW, DupWalker, RangeWalker,
+        ReverseWalker, Walker,
+    },
+    table::{Compress, DupSort, Encode, Table},
+};
+use reth_storage_errors::db::{DatabaseErrorInfo, DatabaseWriteOperation};
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    sync::Arc,
+};
+
+/// Position of a [`MemCursor`].
+#[derive(Debug, Clone)]
+enum Position {
+    /// The cursor hasn't been positioned yet.
+    Unset,
+    /// The cursor is at the entry, which may have been deleted since.
+    At(Entry),
+    /// The cursor moved past the last entry, or a seek didn't find an entry.
+    End,
+}
+
+/// Cursor over a table of the in-memory database.
+///
+/// The cursor remembers the entry it is positioned at, rather than an index into the table, so
+/// entries can be inserted and deleted while walking the table. After the current entry is deleted,
+/// [`DbCursorRO::next`] returns the entry that followed it, like in MDBX.
+///
+/// The cursor operates on the tables of the transaction that created it. Writing with a cursor of a
+/// read-only transaction only changes the tables of that transaction, the changes are never
+/// committed.
+#[derive(Debug)]
+pub struct MemCursor<T: Table> {
+    state: Arc<RwLock<Snapshot>>,
+    position: Position,
+    _table: PhantomData<T>,
+}
+
+impl<T: Table> MemCursor<T> {
+    pub(crate) const fn new(state: Arc<RwLock<Snapshot>>) -> Self {
+        Self { state, position: Position::Unset, _table: PhantomData }
+    }
+
+    /// Finds an entry of the table.
+    fn find(&self, f: impl for<'a> FnOnce(&'a MemTable) -> Option<&'a Entry>) -> Option<Entry> {
+        self.state.read().get(T::NAME).and_then(|table| f(table)).cloned()
+    }
+
+    /// Moves the cursor to the found entry, or to the end of the table if there is none.
+    fn move_to(
+        &mut self,
+        f: impl for<'a> FnOnce(&'a MemTable) -> Option<&'a Entry>,
+    ) -> PairResult<T> {
+        let entry = self.find(f);
+        self.position = entry.clone().map_or(Position::End, Position::At);
+        decode::<T>(entry)
+    }
+
+    /// Moves the cursor to the found entry, and leaves the cursor in place if there is none.
+    fn move_to_existing(
+        &mut self,
+        f: impl for<'a> FnOnce(&'a MemTable) -> Option<&'a Entry>,
+    ) -> PairResult<T> {
+        let entry = self.find(f);
+        if let Some(entry) = &entry {
+            self.position = Position::At(entry.clone());
+        }
+        decode::<T>(entry)
+    }
+
+    /// Returns the entry the cursor is positioned at.
+    fn position(&self) -> Option<Entry> {
+        match &self.position {
+            Position::At(entry) => Some(entry.clone()),
+            Position::Unset | Position::End => None,
+        }
+    }
+
+    /// Applies a write to the table, and positions the cursor at the written entry.
+    fn write(
+        &mut self,
+        operation: DatabaseWriteOperation,
+        key: T::Key,
+        value: T::Value,
+        f: impl FnOnce(&mut MemTable, Vec<u8>, Vec<u8>) -> Result<(), &'static str>,
+    ) -> Result<(), DatabaseError> {
+        let key: Vec<u8> = key.encode().into();
+        let value: Vec<u8> = value.compress().into();
+        update_table::<T, _>(&self.state, |table| f(table, key.clone(), value.clone()))
+            .map_err(|message| write_error::<T>(operation, key.clone(), message))?;
+        self.position = Position::At((key, value));
+        Ok(())
+    }
+
+    /// Deletes the entries selected by `f` from the entry the cursor is positioned at.
+    fn delete(&mut self, f: impl FnOnce(&mut MemTable, &Entry)) -> Result<(), DatabaseError> {
+        let Some(entry) = self.position() else {
+            return Err(DatabaseError::Delete(DatabaseErrorInfo {
+                message: "cursor is not positioned".to_string(),
+                code: -1,
+            }))
+        };
+        update_table::<T, _>(&self.state, |table| f(table, &entry));
+        Ok(())
+    }
+}
+
+/// Decodes an entry of table `T`.
+fn decode<T: Table>(entry: Option<Entry>) -> PairResult<T> {
+    entry.map(|(key, value)| decoder::<T>((Cow::Owned(key), Cow::Owned(value)))).transpose()
+}
+
+impl<T: Table> DbCursorRO<T> for MemCursor<T> {
+    fn first(&mut self) -> PairResult<T> {
+        self.move_to(|table| table.first())
+    }
+
+    fn seek_exact(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode();
+        self.move_to(|table| table.seek_exact(key.as_ref()))
+    }
+
+    fn seek(&mut self, key: T::Key) -> PairResult<T> {
+        let key = key.encode();
+        self.move_to(|table| table.seek(key.as_ref()))
+    }
+
+    fn next(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset => self.first(),
+            Position::At(entry) => self.move_to(|table| table.after(&entry)),
+            Position::End => Ok(None),
+        }
+    }
+
+    fn prev(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset | Position::End => self.last(),
+            Position::At(entry) => self.move_to_existing(|table| table.before(&entry)),
+        }
+    }
+
+    fn last(&mut self) -> PairResult<T> {
+        self.move_to(|table| table.last())
+    }
+
+    fn current(&mut self) -> PairResult<T> {
+        let Some(entry) = self.position() else { return Ok(None) };
+        decode::<T>(self.find(|table| table.get(&entry)))
+    }
+
+    fn walk(&mut self, start_key: Option<T::Key>) -> Result<Walker<'_, T, Self>, DatabaseError> {
+        let start = if let Some(start_key) = start_key {
+            self.seek(start_key).transpose()
+        } else {
+            self.first().transpose()
+        };
+
+        Ok(Walker::new(self, start))
+    }
+
+    fn walk_range(
+        &mut self,
+        range: impl RangeBounds<T::Key>,
+    ) -> Result<RangeWalker<'_, T, Self>, DatabaseError> {
+        let start = match range.start_bound().cloned() {
+            Bound::Included(key) => self.seek(key),
+            Bound::Excluded(_key) => {
+                unreachable!("Rust doesn't allow for Bound::Excluded in starting bounds");
+            }
+            Bound::Unbounded => self.first(),
+        }
+        .transpose();
+
+        Ok(RangeWalker::new(self, start, range.end_bound().cloned()))
+    }
+
+    fn walk_back(
+        &mut self,
+        start_key: Option<T::Key>,
+    ) -> Result<ReverseWalker<'_, T, Self>, DatabaseError> {
+        let start =
+            if let Some(start_key) = start_key { self.seek(start_key) } else { self.last() }
+                .transpose();
+
+        Ok(ReverseWalker::new(self, start))
+    }
+}
+
+impl<T: DupSort> DbDupCursorRO<T> for MemCursor<T> {
+    fn next_dup(&mut self) -> PairResult<T> {
+        let Some(entry) = self.position() else { return Ok(None) };
+        self.move_to_existing(|table| table.after(&entry).filter(|(key, _)| key == &entry.0))
+    }
+
+    fn next_no_dup(&mut self) -> PairResult<T> {
+        match self.position.clone() {
+            Position::Unset => self.first(),
+            Position::At((key, _)) => self.move_to(|table| table.next_key(&key)),
+            Position::End => Ok(None),
+        }
+    }
+
+    fn next_dup_val(&mut self) -> ValueOnlyResult<T> {
+        Ok(self.next_dup()?.map(|(_, value)| value))
+    }
+
+    fn seek_by_key_subkey(&mut self, key: T::Key, subkey: T::SubKey) -> ValueOnlyResult<T> {
+        let (key, subkey) = (key.encode(), subkey.encode());
+        Ok(self
+            .move_to(|table| table.seek_dup(key.as_ref(), subkey.as_ref()))?
+            .map(|(_, value)| value))
+    }
+
+    /// Depending on its arguments, returns an iterator starting at:
+    /// - Some(key), Some(subkey): a `key` item whose data is >= than `subkey`
+    /// - Some(key), None: first item of a specified `key`
+    /// - None, Some(subkey): like first case, but in the first key
+    /// - None, None: first item in the table of a DUPSORT table.
+    fn walk_dup(
+        &mut self,
+        key: Option<T::Key>,
+        subkey: Option<T::SubKey>,
+    ) -> Result<DupWalker<'_, T, Self>, DatabaseError> {
+        let start = match (key, subkey) {
+            (Some(key), Some(subkey)) => {
+                let (key, subkey) = (key.encode(), subkey.encode());
+                self.move_to(|table| table.seek_dup(key.as_ref(), subkey.as_ref()))
+            }
+            (Some(key), None) => self.seek_exact(key),
+            (None, Some(subkey)) => {
+                let subkey = subkey.encode();
+                match self.first() {
+                    Ok(Some(_)) => {
+                        let key = self.position().map(|(key, _)| key).unwrap_or_default();
+                        self.move_to(|table| table.seek_dup(&key, subkey.as_ref()))
+                    }
+                    res => res,
+                }
+            }
+            (None, None) => self.first(),
+        }
+        .transpose();
+
+        Ok(DupWalker { cursor: self, start })
+    }
+}
+
+impl<T: Table> DbCursorRW<T> for MemCursor<T> {
+    /// For a DUPSORT table, `upsert` adds the value to the values of the key, like in MDBX.
+    fn upsert(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let dupsort = is_dupsort::<T>();
+        self.write(DatabaseWriteOperation::CursorUpsert, key, value, |table, key, value| {
+            table.upsert(key, value, dupsort);
+            Ok(())
+        })
+    }
+
+    fn insert(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        self.write(DatabaseWriteOperation::CursorInsert, key, value, MemTable::insert)
+    }
+
+    /// Appends the data to the end of the table. Consequently, the append operation
+    /// will fail if the inserted key is less than the last table key
+    fn append(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let dupsort = is_dupsort::<T>();
+        self.write(DatabaseWriteOperation::CursorAppend, key, value, |table, key, value| {
+            table.append(key, value, dupsort)
+        })
+    }
+
+    fn delete_current(&mut self) -> Result<(), DatabaseError> {
+        self.delete(|table, entry| {
+            table.remove(entry);
+        })
+    }
+}
+
+impl<T: DupSort> DbDupCursorRW<T> for MemCursor<T> {
+    fn delete_current_duplicates(&mut self) -> Result<(), DatabaseError> {
+        self.delete(|table, (key, _)| {
+            table.remove_key(key);
+        })
+    }
+
+    fn append_dup(&mut self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        self.write(DatabaseWriteOperation::CursorAppendDup, key, value, MemTable::append_dup)
+    }
+}
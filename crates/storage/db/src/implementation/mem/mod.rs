@@ -0,0 +1,646 @@
+//! In-memory implementation of the database abstraction.
+//!
+//! Every table is an ordered set of `(key, value)` pairs of the encoded key and the compressed
+//! value, so the entries of a table are ordered by key, and the duplicates of a `DUPSORT` table are
+//! ordered by value, like in MDBX.
+//!
+//! Transactions operate on a snapshot of the tables. There is a single writer at a time, and the
+//! changes of a write transaction are kept in an overlay over the entries of each table, so readers
+//! never block writers and the changes of a write transaction are only visible to transactions that
+//! are opened after it has been committed. On commit, the overlays are merged into the entries,
+//! see [`MemTable::compact`].
+
+use crate::{tables::Tables, DatabaseError};
+use parking_lot::{Condvar, Mutex, RwLock};
+use reth_db_api::{
+    database::{CompactedCopy, CompactionOutcome, Database, DatabaseCompact, FreeSpace},
+    database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics},
+    table::Table,
+};
+use reth_storage_errors::db::{DatabaseErrorInfo, DatabaseWriteError, DatabaseWriteOperation};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+
+pub mod cursor;
+pub mod tx;
+
+pub use cursor::MemCursor;
+pub use tx::{MemTx, MemTxMut};
+
+/// An encoded key and its compressed value.
+pub(crate) type Entry = (Vec<u8>, Vec<u8>);
+
+/// The tables of the database at a point in time, by table name.
+pub(crate) type Snapshot = BTreeMap<&'static str, Arc<MemTable>>;
+
+/// Database that keeps all tables in memory.
+///
+/// Cloning the database is cheap, the clones share the same tables. The data is lost once the last
+/// clone is dropped.
+#[derive(Debug, Clone, Default)]
+pub struct MemDatabase {
+    inner: Arc<MemDatabaseInner>,
+}
+
+#[derive(Debug, Default)]
+struct MemDatabaseInner {
+    /// The tables as of the last committed write transaction.
+    committed: RwLock<Snapshot>,
+    /// Whether a write transaction is open.
+    writer: Mutex<bool>,
+    /// Notified when the write transaction is closed.
+    writer_released: Condvar,
+}
+
+impl MemDatabase {
+    /// Creates a new empty in-memory database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of entries of every non-empty table.
+    pub fn table_entries(&self) -> Vec<(&'static str, usize)> {
+        self.inner.committed.read().iter().map(|(name, table)| (*name, table.len())).collect()
+    }
+
+    /// Returns the committed tables.
+    pub(crate) fn snapshot(&self) -> Snapshot {
+        self.inner.committed.read().clone()
+    }
+
+    /// Replaces the committed tables, and compacts them.
+    pub(crate) fn commit(&self, mut snapshot: Snapshot) {
+        let mut committed = self.inner.committed.write();
+        // Release the previous tables first, so the tables that aren't read by any transaction can
+        // be compacted in place.
+        committed.clear();
+        for table in snapshot.values_mut() {
+            MemTable::compact(table);
+        }
+        *committed = snapshot;
+    }
+
+    /// Blocks until no other write transaction is open, and marks a write transaction as open.
+    fn acquire_writer(&self) -> WriterGuard {
+        let mut writer = self.inner.writer.lock();
+        while *writer {
+            self.inner.writer_released.wait(&mut writer);
+        }
+        *writer = true;
+        WriterGuard { db: self.clone() }
+    }
+}
+
+impl Database for MemDatabase {
+    type TX = MemTx;
+    type TXMut = MemTxMut;
+
+    fn tx(&self) -> Result<Self::TX, DatabaseError> {
+        Ok(MemTx::new(self.snapshot()))
+    }
+
+    fn tx_mut(&self) -> Result<Self::TXMut, DatabaseError> {
+        // The snapshot is taken after the writer is acquired, so it includes the changes of the
+        // previous write transaction.
+        let writer = self.acquire_writer();
+        Ok(MemTxMut::new(self.snapshot(), writer))
+    }
+}
+
+impl DatabaseMetrics for MemDatabase {
+    fn gauge_metrics(&self) -> Vec<(&'static str, f64, Vec<metrics::Label>)> {
+        self.table_entries()
+            .into_iter()
+            .map(|(table, entries)| {
+                ("db.table_entries", entries as f64, vec![metrics::Label::new("table", table)])
+            })
+            .collect()
+    }
+}
+
+impl DatabaseMetadata for MemDatabase {
+    fn metadata(&self) -> DatabaseMetadataValue {
+        DatabaseMetadataValue::new(None)
+    }
+}
+
+#[cfg(feature = "mdbx")]
+impl reth_db_api::database::DatabaseCopy for MemDatabase {
+    /// Writes the committed tables into a new MDBX database at `dest`.
+    fn copy_to(&self, dest: &std::path::Path, _compact: bool) -> Result<(), DatabaseError> {
+        use reth_libmdbx::WriteFlags;
+
+        let env =
+            crate::mdbx::init_db(dest, crate::mdbx::DatabaseArguments::new(Default::default()))
+                .map_err(|err| DatabaseError::Other(err.to_string()))?;
+        let tx = env.env()?.begin_rw_txn().map_err(|err| DatabaseError::InitTx(err.into()))?;
+        for (name, table) in self.snapshot() {
+            let db = tx.open_db(Some(name)).map_err(|err| DatabaseError::Open(err.into()))?;
+            for (key, value) in table.iter() {
+                tx.put(db.dbi(), key, value, WriteFlags::empty())
+                    .map_err(|err| DatabaseError::Copy(err.into()))?;
+            }
+        }
+        tx.commit().map_err(|err| DatabaseError::Commit(err.into()))?;
+        Ok(())
+    }
+}
+
+/// The tables are compacted on every commit, so there's never any free space to reclaim and
+/// compactions do nothing.
+impl DatabaseCompact for MemDatabase {
+    fn free_space(&self) -> Result<FreeSpace, DatabaseError> {
+        Ok(FreeSpace { free: 0, total: 0 })
+    }
+
+    fn last_txn_id(&self) -> Result<u64, DatabaseError> {
+        Ok(0)
+    }
+
+    fn copy_compacted(&self) -> Result<CompactedCopy, DatabaseError> {
+        Ok(CompactedCopy { path: PathBuf::new(), txn_id: 0, size_before: 0, size_after: 0 })
+    }
+
+    fn swap_compacted(
+        &self,
+        copy: CompactedCopy,
+        _swap_timeout: Duration,
+    ) -> Result<CompactionOutcome, DatabaseError> {
+        Ok(CompactionOutcome { size_before: copy.size_before, size_after: copy.size_after })
+    }
+
+    fn discard_compacted(&self, _copy: CompactedCopy) -> Result<(), DatabaseError> {
+        Ok(())
+    }
+}
+
+/// Marks the write transaction of a [`MemDatabase`] as closed when dropped.
+#[derive(Debug)]
+pub(crate) struct WriterGuard {
+    db: MemDatabase,
+}
+
+impl WriterGuard {
+    /// Returns the database that the write transaction belongs to.
+    pub(crate) const fn db(&self) -> &MemDatabase {
+        &self.db
+    }
+}
+
+impl Drop for WriterGuard {
+    fn drop(&mut self) {
+        *self.db.inner.writer.lock() = false;
+        self.db.inner.writer_released.notify_one();
+    }
+}
+
+/// The entries of a table, ordered by key and then by value.
+///
+/// The entries are shared by the transactions that read the table, and the changes since the last
+/// [compaction](Self::compact) are kept aside, so cloning the table of a snapshot for writing only
+/// copies those changes.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemTable {
+    /// The entries as of the last compaction.
+    base: Arc<BTreeSet<Entry>>,
+    /// The entries inserted since the last compaction, none of them are in `base`.
+    inserted: BTreeSet<Entry>,
+    /// The entries of `base` removed since the last compaction.
+    removed: BTreeSet<Entry>,
+}
+
+impl MemTable {
+    /// Merges the changes into the entries of the table.
+    ///
+    /// The entries are updated in place if no other transaction reads them. Otherwise they are
+    /// copied, but only once the changes amount to half of the entries, so that the cost of the
+    /// copy is spread over the writes since the last compaction.
+    pub(crate) fn compact(table: &mut Arc<Self>) {
+        if table.is_compact() {
+            return
+        }
+        let shared = Arc::strong_count(table) > 1 || Arc::strong_count(&table.base) > 1;
+        if shared && (table.inserted.len() + table.removed.len()) * 2 < table.base.len() {
+            return
+        }
+
+        let table = Arc::make_mut(table);
+        let base = Arc::make_mut(&mut table.base);
+        for entry in std::mem::take(&mut table.removed) {
+            base.remove(&entry);
+        }
+        base.append(&mut table.inserted);
+    }
+
+    /// Returns `true` if there are no changes since the last compaction.
+    pub(crate) fn is_compact(&self) -> bool {
+        self.inserted.is_empty() && self.removed.is_empty()
+    }
+
+    /// Returns the number of entries, counting every duplicate of a key.
+    pub(crate) fn len(&self) -> usize {
+        self.base.len() - self.removed.len() + self.inserted.len()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Entry> {
+        let mut base =
+            self.base.iter().filter(move |entry| !self.removed.contains(*entry)).peekable();
+        let mut inserted = self.inserted.iter().peekable();
+        std::iter::from_fn(move || {
+            // `base` and `inserted` are disjoint, so the entries are never equal.
+            let from_base = match (base.peek(), inserted.peek()) {
+                (Some(a), Some(b)) => a < b,
+                (a, _) => a.is_some(),
+            };
+            if from_base {
+                base.next()
+            } else {
+                inserted.next()
+            }
+        })
+    }
+
+    /// Returns the entry if it is in the table.
+    pub(crate) fn get(&self, entry: &Entry) -> Option<&Entry> {
+        self.inserted
+            .get(entry)
+            .or_else(|| self.base.get(entry).filter(|entry| !self.removed.contains(*entry)))
+    }
+
+    /// Returns the first entry after the `lower` bound.
+    fn first_from(&self, lower: Bound<&Entry>) -> Option<&Entry> {
+        let base =
+            self.base.range((lower, Bound::Unbounded)).find(|entry| !self.removed.contains(*entry));
+        let inserted = self.inserted.range((lower, Bound::Unbounded)).next();
+        match (base, inserted) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Returns the last entry before the `upper` bound.
+    fn last_before(&self, upper: Bound<&Entry>) -> Option<&Entry> {
+        let base = self
+            .base
+            .range((Bound::Unbounded, upper))
+            .rev()
+            .find(|entry| !self.removed.contains(*entry));
+        let inserted = self.inserted.range((Bound::Unbounded, upper)).next_back();
+        match (base, inserted) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    pub(crate) fn first(&self) -> Option<&Entry> {
+        self.first_from(Bound::Unbounded)
+    }
+
+    pub(crate) fn last(&self) -> Option<&Entry> {
+        self.last_before(Bound::Unbounded)
+    }
+
+    /// Returns the entry that follows `entry`, which doesn't need to be in the table.
+    pub(crate) fn after(&self, entry: &Entry) -> Option<&Entry> {
+        self.first_from(Bound::Excluded(entry))
+    }
+
+    /// Returns the entry that precedes `entry`, which doesn't need to be in the table.
+    pub(crate) fn before(&self, entry: &Entry) -> Option<&Entry> {
+        self.last_before(Bound::Excluded(entry))
+    }
+
+    /// Returns the first entry with a key greater than or equal to `key`.
+    pub(crate) fn seek(&self, key: &[u8]) -> Option<&Entry> {
+        self.first_from(Bound::Included(&(key.to_vec(), Vec::new())))
+    }
+
+    /// Returns the first entry of `key`.
+    pub(crate) fn seek_exact(&self, key: &[u8]) -> Option<&Entry> {
+        self.seek_dup(key, &[])
+    }
+
+    /// Returns the first entry of `key` with a value greater than or equal to `subkey`.
+    pub(crate) fn seek_dup(&self, key: &[u8], subkey: &[u8]) -> Option<&Entry> {
+        self.first_from(Bound::Included(&(key.to_vec(), subkey.to_vec()))).filter(|(k, _)| k == key)
+    }
+
+    /// Returns the first entry with a key greater than `key`.
+    pub(crate) fn next_key(&self, key: &[u8]) -> Option<&Entry> {
+        // Any key greater than `key` is greater than or equal to `key` followed by a zero byte.
+        let mut next = key.to_vec();
+        next.push(0);
+        self.first_from(Bound::Included(&(next, Vec::new())))
+    }
+
+    /// Returns the last entry of `key`.
+    pub(crate) fn last_dup(&self, key: &[u8]) -> Option<&Entry> {
+        self.next_key(key)
+            .map_or_else(|| self.last(), |next| self.before(next))
+            .filter(|(k, _)| k == key)
+    }
+
+    /// Returns all entries of `key`.
+    pub(crate) fn dups<'a>(&'a self, key: &'a [u8]) -> impl Iterator<Item = &'a Entry> + 'a {
+        std::iter::successors(self.seek_exact(key), move |entry| self.after(entry))
+            .take_while(move |(k, _)| k == key)
+    }
+
+    /// Adds the entry to the table.
+    fn add(&mut self, entry: Entry) {
+        if self.base.contains(&entry) {
+            self.removed.remove(&entry);
+        } else {
+            self.inserted.insert(entry);
+        }
+    }
+
+    /// Removes `entry`, returns `true` if it was in the table.
+    pub(crate) fn remove(&mut self, entry: &Entry) -> bool {
+        self.inserted.remove(entry) ||
+            (self.base.contains(entry) && self.removed.insert(entry.clone()))
+    }
+
+    /// Removes all entries of `key`, returns `true` if there were any.
+    pub(crate) fn remove_key(&mut self, key: &[u8]) -> bool {
+        let dups = self.dups(key).cloned().collect::<Vec<_>>();
+        for entry in &dups {
+            self.remove(entry);
+        }
+        !dups.is_empty()
+    }
+
+    /// Inserts the entry, overwriting the value of the key unless the table is a `DUPSORT` table.
+    pub(crate) fn upsert(&mut self, key: Vec<u8>, value: Vec<u8>, dupsort: bool) {
+        if !dupsort {
+            self.remove_key(&key);
+        }
+        self.add((key, value));
+    }
+
+    /// Inserts the entry, fails if the key already exists.
+    pub(crate) fn insert(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<(), &'static str> {
+        if self.seek_exact(&key).is_some() {
+            return Err("key already exists")
+        }
+        self.add((key, value));
+        Ok(())
+    }
+
+    /// Inserts the entry, fails if it doesn't go after the last entry of the table.
+    ///
+    /// For a `DUPSORT` table, the key may be equal to the last key, as long as the value goes after
+    /// the last value.
+    pub(crate) fn append(
+        &mut self,
+        key: Vec<u8>,
+        value: Vec<u8>,
+        dupsort: bool,
+    ) -> Result<(), &'static str> {
+        if let Some((last_key, last_value)) = self.last() {
+            let in_order =
+                if dupsort { (&key, &value) > (last_key, last_value) } else { &key > last_key };
+            if !in_order {
+                return Err("entry is not after the last entry of the table")
+            }
+        }
+        self.add((key, value));
+        Ok(())
+    }
+
+    /// Inserts the entry, fails if the value doesn't go after the last value of the key.
+    pub(crate) fn append_dup(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<(), &'static str> {
+        if self.last_dup(&key).is_some_and(|(_, last_value)| &value < last_value) {
+            return Err("value is not after the last value of the key")
+        }
+        self.add((key, value));
+        Ok(())
+    }
+}
+
+/// Returns `true` if the table is a known `DUPSORT` table.
+pub(crate) fn is_dupsort<T: Table>() -> bool {
+    T::NAME.parse::<Tables>().is_ok_and(|table| table.is_dupsort())
+}
+
+/// Creates the error of a failed write to table `T`.
+pub(crate) fn write_error<T: Table>(
+    operation: DatabaseWriteOperation,
+    key: Vec<u8>,
+    message: &str,
+) -> DatabaseError {
+    DatabaseWriteError {
+        info: DatabaseErrorInfo { message: message.to_string(), code: -1 },
+        operation,
+        table_name: T::NAME,
+        key,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::{AccountChangeSets, CanonicalHeaders, PlainStorageState};
+    use reth_db_api::{
+        cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
+        models::AccountBeforeTx,
+        table::TableImporter,
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{Account, Address, StorageEntry, B256, U256};
+
+    fn storage(key: u8, value: u64) -> StorageEntry {
+        StorageEntry { key: B256::with_last_byte(key), value: U256::from(value) }
+    }
+
+    #[test]
+    fn put_get_and_isolation() {
+        let db = MemDatabase::new();
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(1, B256::with_last_byte(1)).unwrap();
+        tx.put::<CanonicalHeaders>(1, B256::with_last_byte(2)).unwrap();
+        assert_eq!(tx.get::<CanonicalHeaders>(1).unwrap(), Some(B256::with_last_byte(2)));
+
+        // not visible before the commit
+        let reader = db.tx().unwrap();
+        assert_eq!(reader.get::<CanonicalHeaders>(1).unwrap(), None);
+        tx.commit().unwrap();
+        assert_eq!(reader.get::<CanonicalHeaders>(1).unwrap(), None);
+        assert_eq!(
+            db.tx().unwrap().get::<CanonicalHeaders>(1).unwrap(),
+            Some(B256::with_last_byte(2))
+        );
+
+        // aborted changes are discarded
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(2, B256::with_last_byte(3)).unwrap();
+        assert!(tx.delete::<CanonicalHeaders>(1, None).unwrap());
+        tx.abort();
+        let tx = db.tx().unwrap();
+        assert_eq!(tx.get::<CanonicalHeaders>(1).unwrap(), Some(B256::with_last_byte(2)));
+        assert_eq!(tx.get::<CanonicalHeaders>(2).unwrap(), None);
+        assert_eq!(tx.entries::<CanonicalHeaders>().unwrap(), 1);
+    }
+
+    #[test]
+    fn cursor_walk_range_and_delete() {
+        let db = MemDatabase::new();
+        let tx = db.tx_mut().unwrap();
+        let mut cursor = tx.cursor_write::<CanonicalHeaders>().unwrap();
+        for block in 0..10 {
+            cursor.append(block, B256::with_last_byte(block as u8)).unwrap();
+        }
+        assert!(cursor.append(5, B256::ZERO).is_err());
+        assert!(cursor.insert(5, B256::ZERO).is_err());
+
+        let blocks = |cursor: &mut MemCursor<CanonicalHeaders>, range| {
+            cursor.walk_range(range).unwrap().map(|entry| entry.unwrap().0).collect::<Vec<_>>()
+        };
+        assert_eq!(blocks(&mut cursor, 3..6), vec![3, 4, 5]);
+        assert_eq!(cursor.walk_range(..=2).unwrap().count(), 3);
+        assert_eq!(cursor.walk_range(20..).unwrap().count(), 0);
+
+        // delete every even block while walking
+        let mut walker = cursor.walk(None).unwrap();
+        while let Some((block, _)) = walker.next().transpose().unwrap() {
+            if block % 2 == 0 {
+                walker.delete_current().unwrap();
+            }
+        }
+        assert_eq!(blocks(&mut cursor, 0..10), vec![1, 3, 5, 7, 9]);
+
+        let reversed =
+            cursor.walk_back(Some(6)).unwrap().map(|entry| entry.unwrap().0).collect::<Vec<_>>();
+        assert_eq!(reversed, vec![7, 5, 3, 1]);
+        assert_eq!(cursor.seek(4).unwrap().map(|(block, _)| block), Some(5));
+        assert_eq!(cursor.seek_exact(4).unwrap(), None);
+        assert_eq!(cursor.last().unwrap().map(|(block, _)| block), Some(9));
+    }
+
+    #[test]
+    fn dupsort_cursor() {
+        let db = MemDatabase::new();
+        let address = Address::with_last_byte(1);
+        let other = Address::with_last_byte(2);
+
+        let tx = db.tx_mut().unwrap();
+        tx.put::<PlainStorageState>(address, storage(3, 30)).unwrap();
+        tx.put::<PlainStorageState>(address, storage(1, 10)).unwrap();
+        tx.put::<PlainStorageState>(other, storage(1, 11)).unwrap();
+        let mut cursor = tx.cursor_dup_write::<PlainStorageState>().unwrap();
+        cursor.append_dup(address, storage(5, 50)).unwrap();
+        assert!(cursor.append_dup(address, storage(4, 40)).is_err());
+        assert_eq!(tx.entries::<PlainStorageState>().unwrap(), 4);
+
+        let walk = |cursor: &mut MemCursor<PlainStorageState>, key, subkey| {
+            cursor
+                .walk_dup(key, subkey)
+                .unwrap()
+                .map(|entry| entry.unwrap().1.value.to::<u64>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(walk(&mut cursor, Some(address), None), vec![10, 30, 50]);
+        assert_eq!(walk(&mut cursor, Some(address), Some(B256::with_last_byte(2))), vec![30, 50]);
+        assert_eq!(walk(&mut cursor, Some(other), None), vec![11]);
+        assert_eq!(walk(&mut cursor, None, None), vec![10, 30, 50]);
+        assert_eq!(walk(&mut cursor, Some(Address::with_last_byte(3)), None), Vec::<u64>::new());
+
+        assert_eq!(
+            cursor.seek_by_key_subkey(address, B256::with_last_byte(4)).unwrap(),
+            Some(storage(5, 50))
+        );
+        assert_eq!(cursor.seek_by_key_subkey(address, B256::with_last_byte(6)).unwrap(), None);
+
+        cursor.seek_exact(address).unwrap();
+        assert_eq!(cursor.next_dup_val().unwrap(), Some(storage(3, 30)));
+        assert_eq!(cursor.next_no_dup().unwrap().map(|(key, _)| key), Some(other));
+        assert_eq!(cursor.next_dup().unwrap(), None);
+
+        // deleting a single value of a key
+        assert!(tx.delete::<PlainStorageState>(address, Some(storage(3, 30))).unwrap());
+        assert_eq!(walk(&mut cursor, Some(address), None), vec![10, 50]);
+
+        cursor.seek_exact(address).unwrap();
+        cursor.delete_current_duplicates().unwrap();
+        assert_eq!(walk(&mut cursor, None, None), vec![11]);
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn changes_are_compacted_once_unshared() {
+        let db = MemDatabase::new();
+        let tx = db.tx_mut().unwrap();
+        for block in 0..10 {
+            tx.put::<CanonicalHeaders>(block, B256::with_last_byte(block as u8)).unwrap();
+        }
+        tx.commit().unwrap();
+        let is_compact = |db: &MemDatabase| db.snapshot()[CanonicalHeaders::NAME].is_compact();
+        assert!(is_compact(&db));
+
+        // the changes are kept aside while the entries are read by another transaction
+        let reader = db.tx().unwrap();
+        let tx = db.tx_mut().unwrap();
+        assert!(tx.delete::<CanonicalHeaders>(3, None).unwrap());
+        tx.put::<CanonicalHeaders>(5, B256::ZERO).unwrap();
+        tx.put::<CanonicalHeaders>(20, B256::with_last_byte(20)).unwrap();
+        assert!(tx.delete::<CanonicalHeaders>(20, None).unwrap());
+        tx.put::<CanonicalHeaders>(30, B256::with_last_byte(30)).unwrap();
+        assert_eq!(tx.entries::<CanonicalHeaders>().unwrap(), 10);
+        tx.commit().unwrap();
+        assert!(!is_compact(&db));
+
+        let blocks = |tx: &MemTx| {
+            tx.cursor_read::<CanonicalHeaders>()
+                .unwrap()
+                .walk(None)
+                .unwrap()
+                .map(|entry| entry.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(blocks(&reader), (0..10).collect::<Vec<_>>());
+        assert_eq!(reader.get::<CanonicalHeaders>(5).unwrap(), Some(B256::with_last_byte(5)));
+        let tx = db.tx().unwrap();
+        assert_eq!(blocks(&tx), vec![0, 1, 2, 4, 5, 6, 7, 8, 9, 30]);
+        assert_eq!(tx.get::<CanonicalHeaders>(5).unwrap(), Some(B256::ZERO));
+        drop((reader, tx));
+
+        // once no transaction reads the entries, the next commit merges the changes in place
+        let tx = db.tx_mut().unwrap();
+        tx.put::<CanonicalHeaders>(3, B256::with_last_byte(3)).unwrap();
+        tx.commit().unwrap();
+        assert!(is_compact(&db));
+        let tx = db.tx().unwrap();
+        assert_eq!(blocks(&tx), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 30]);
+        assert_eq!(db.table_entries(), vec![(CanonicalHeaders::NAME, 11)]);
+    }
+
+    #[test]
+    fn import_table() {
+        let db = MemDatabase::new();
+        let tx = db.tx_mut().unwrap();
+        for block in 0..3 {
+            tx.put::<AccountChangeSets>(
+                block,
+                AccountBeforeTx {
+                    address: Address::with_last_byte(1),
+                    info: Some(Account::default()),
+                },
+            )
+            .unwrap();
+        }
+        tx.commit().unwrap();
+
+        let copy = MemDatabase::new();
+        let tx = copy.tx_mut().unwrap();
+        tx.import_dupsort::<AccountChangeSets, _>(&db.tx().unwrap()).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(copy.table_entries(), vec![(AccountChangeSets::NAME, 3)]);
+    }
+}
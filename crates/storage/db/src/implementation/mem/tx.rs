@@ -0,0 +1,172 @@
+//! Transactions of the in-memory database.
+
+use super::{cursor::MemCursor, MemTable, Snapshot, WriterGuard};
+use crate::{tables::utils::decode_one, DatabaseError};
+use parking_lot::RwLock;
+use reth_db_api::{
+    table::{Compress, DupSort, Encode, Table, TableImporter},
+    transaction::{DbTx, DbTxMut},
+};
+use std::{borrow::Cow, sync::Arc};
+
+/// Read-only transaction of the in-memory database.
+///
+/// The transaction sees the tables as of the last write transaction that was committed before it
+/// was opened.
+#[derive(Debug)]
+pub struct MemTx {
+    /// The tables of the transaction, shared with its cursors.
+    pub(crate) state: Arc<RwLock<Snapshot>>,
+}
+
+impl MemTx {
+    pub(crate) fn new(snapshot: Snapshot) -> Self {
+        Self { state: Arc::new(RwLock::new(snapshot)) }
+    }
+
+    fn cursor<T: Table>(&self) -> MemCursor<T> {
+        MemCursor::new(self.state.clone())
+    }
+}
+
+impl DbTx for MemTx {
+    type Cursor<T: Table> = MemCursor<T>;
+    type DupCursor<T: DupSort> = MemCursor<T>;
+
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, DatabaseError> {
+        let key = key.encode();
+        let state = self.state.read();
+        state
+            .get(T::NAME)
+            .and_then(|table| table.seek_exact(key.as_ref()))
+            .map(|(_, value)| decode_one::<T>(Cow::Borrowed(value)))
+            .transpose()
+    }
+
+    fn commit(self) -> Result<bool, DatabaseError> {
+        Ok(true)
+    }
+
+    fn abort(self) {}
+
+    fn cursor_read<T: Table>(&self) -> Result<Self::Cursor<T>, DatabaseError> {
+        Ok(self.cursor())
+    }
+
+    fn cursor_dup_read<T: DupSort>(&self) -> Result<Self::DupCursor<T>, DatabaseError> {
+        Ok(self.cursor())
+    }
+
+    fn entries<T: Table>(&self) -> Result<usize, DatabaseError> {
+        Ok(self.state.read().get(T::NAME).map_or(0, |table| table.len()))
+    }
+
+    fn disable_long_read_transaction_safety(&mut self) {}
+}
+
+/// Read-write transaction of the in-memory database.
+///
+/// Only one write transaction can be open at a time. The changes are kept aside from the entries
+/// shared with other transactions, and the tables replace the tables of the database on commit.
+#[derive(Debug)]
+pub struct MemTxMut {
+    tx: MemTx,
+    writer: WriterGuard,
+}
+
+impl MemTxMut {
+    pub(crate) fn new(snapshot: Snapshot, writer: WriterGuard) -> Self {
+        Self { tx: MemTx::new(snapshot), writer }
+    }
+
+    /// Applies `f` to table `T`, see [`update_table`].
+    fn update<T: Table, R>(&self, f: impl FnOnce(&mut MemTable) -> R) -> R {
+        update_table::<T, R>(&self.tx.state, f)
+    }
+}
+
+/// Applies `f` to table `T` of the transaction state.
+///
+/// If the table is shared with other transactions, only its changes since the last compaction are
+/// copied first, its entries stay shared.
+pub(crate) fn update_table<T: Table, R>(
+    state: &RwLock<Snapshot>,
+    f: impl FnOnce(&mut MemTable) -> R,
+) -> R {
+    let mut state = state.write();
+    f(Arc::make_mut(state.entry(T::NAME).or_default()))
+}
+
+impl DbTx for MemTxMut {
+    type Cursor<T: Table> = MemCursor<T>;
+    type DupCursor<T: DupSort> = MemCursor<T>;
+
+    fn get<T: Table>(&self, key: T::Key) -> Result<Option<T::Value>, DatabaseError> {
+        self.tx.get::<T>(key)
+    }
+
+    fn commit(self) -> Result<bool, DatabaseError> {
+        // The tables are moved out of the transaction, so they are only shared with other
+        // transactions and can be compacted in place otherwise.
+        let snapshot = std::mem::take(&mut *self.tx.state.write());
+        self.writer.db().commit(snapshot);
+        Ok(true)
+    }
+
+    fn abort(self) {}
+
+    fn cursor_read<T: Table>(&self) -> Result<Self::Cursor<T>, DatabaseError> {
+        self.tx.cursor_read()
+    }
+
+    fn cursor_dup_read<T: DupSort>(&self) -> Result<Self::DupCursor<T>, DatabaseError> {
+        self.tx.cursor_dup_read()
+    }
+
+    fn entries<T: Table>(&self) -> Result<usize, DatabaseError> {
+        self.tx.entries::<T>()
+    }
+
+    fn disable_long_read_transaction_safety(&mut self) {}
+}
+
+impl DbTxMut for MemTxMut {
+    type CursorMut<T: Table> = MemCursor<T>;
+    type DupCursorMut<T: DupSort> = MemCursor<T>;
+
+    fn put<T: Table>(&self, key: T::Key, value: T::Value) -> Result<(), DatabaseError> {
+        let dupsort = super::is_dupsort::<T>();
+        self.update::<T, _>(|table| {
+            table.upsert(key.encode().into(), value.compress().into(), dupsort)
+        });
+        Ok(())
+    }
+
+    fn delete<T: Table>(
+        &self,
+        key: T::Key,
+        value: Option<T::Value>,
+    ) -> Result<bool, DatabaseError> {
+        let key = key.encode().into();
+        let value = value.map(|value| value.compress().into());
+        Ok(self.update::<T, _>(|table| match value {
+            Some(value) => table.remove(&(key, value)),
+            None => table.remove_key(&key),
+        }))
+    }
+
+    fn clear<T: Table>(&self) -> Result<(), DatabaseError> {
+        self.tx.state.write().remove(T::NAME);
+        Ok(())
+    }
+
+    fn cursor_write<T: Table>(&self) -> Result<Self::CursorMut<T>, DatabaseError> {
+        Ok(self.tx.cursor())
+    }
+
+    fn cursor_dup_write<T: DupSort>(&self) -> Result<Self::DupCursorMut<T>, DatabaseError> {
+        Ok(self.tx.cursor())
+    }
+}
+
+impl TableImporter for MemTxMut {}
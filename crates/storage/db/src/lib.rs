@@ -17,6 +17,7 @@
 
 mod implementation;
 pub mod lockfile;
+pub mod mem;
 mod metrics;
 pub mod static_file;
 pub mod tables;
@@ -26,6 +27,7 @@ pub mod version;
 #[cfg(feature = "mdbx")]
 pub mod mdbx;
 
+pub use mem::MemDatabase;
 pub use reth_storage_errors::db::{DatabaseError, DatabaseWriteOperation};
 pub use tables::*;
 pub use utils::is_database_empty;
@@ -41,7 +43,9 @@ pub mod test_utils {
     use super::*;
     use crate::mdbx::DatabaseArguments;
     use reth_db_api::{
-        database::Database,
+        database::{
+            CompactedCopy, CompactionOutcome, Database, DatabaseCompact, DatabaseCopy, FreeSpace,
+        },
         database_metrics::{DatabaseMetadata, DatabaseMetadataValue, DatabaseMetrics},
         models::ClientVersion,
     };
@@ -50,6 +54,7 @@ pub mod test_utils {
     use std::{
         path::{Path, PathBuf},
         sync::Arc,
+        time::Duration,
     };
     use tempfile::TempDir;
 
@@ -121,6 +126,38 @@ pub mod test_utils {
         }
     }
 
+    impl<DB: DatabaseCopy> DatabaseCopy for TempDatabase<DB> {
+        fn copy_to(&self, dest: &Path, compact: bool) -> Result<(), DatabaseError> {
+            self.db().copy_to(dest, compact)
+        }
+    }
+
+    impl<DB: DatabaseCompact> DatabaseCompact for TempDatabase<DB> {
+        fn free_space(&self) -> Result<FreeSpace, DatabaseError> {
+            self.db().free_space()
+        }
+
+        fn last_txn_id(&self) -> Result<u64, DatabaseError> {
+            self.db().last_txn_id()
+        }
+
+        fn copy_compacted(&self) -> Result<CompactedCopy, DatabaseError> {
+            self.db().copy_compacted()
+        }
+
+        fn swap_compacted(
+            &self,
+            copy: CompactedCopy,
+            swap_timeout: Duration,
+        ) -> Result<CompactionOutcome, DatabaseError> {
+            self.db().swap_compacted(copy, swap_timeout)
+        }
+
+        fn discard_compacted(&self, copy: CompactedCopy) -> Result<(), DatabaseError> {
+            self.db().discard_compacted(copy)
+        }
+    }
+
     /// Create `static_files` path for testing
     pub fn create_test_static_files_dir() -> (TempDir, PathBuf) {
         let temp_dir = TempDir::with_prefix("reth-test-static-").expect(ERROR_TEMPDIR);
@@ -0,0 +1,3 @@
+//! In-memory implementation of the database, for tests and ephemeral nodes.
+
+pub use crate::implementation::mem::*;
@@ -361,6 +361,14 @@ tables! {
     /// Code example can be found in `reth_provider::HistoricalStateProviderRef`
     table StoragesHistory<Key = StorageShardedKey, Value = BlockNumberList>;
 
+    /// Stores pointers to the transactions that were sent from, sent to or created an address.
+    ///
+    /// Shards are laid out the same way as in [`AccountsHistory`], except that the lists hold
+    /// [`TxNumber`]s and the key of each shard contains the highest [`TxNumber`] in it.
+    ///
+    /// This table is only populated if the optional `IndexAddressTransactions` stage is enabled.
+    table AddressTransactions<Key = ShardedKey<Address>, Value = BlockNumberList>;
+
     /// Stores the state of an account before a certain transaction changed it.
     /// Change on state can be: account is created, selfdestructed, touched while empty
     /// or changed balance,nonce.
@@ -0,0 +1,90 @@
+//! The epoch accumulator of an Era1 file.
+
+use crate::{Era1Error, MAX_BLOCKS_PER_ERA1};
+use reth_primitives::{B256, U256};
+use sha2::{Digest, Sha256};
+
+/// The depth of the merkle tree of an epoch accumulator, `log2(MAX_BLOCKS_PER_ERA1)`.
+const ACCUMULATOR_DEPTH: usize = MAX_BLOCKS_PER_ERA1.trailing_zeros() as usize;
+
+/// Computes the epoch accumulator root of the given `(block hash, total difficulty)` records.
+///
+/// This is the SSZ `hash_tree_root` of a `List[HeaderRecord, 8192]`, where
+///
+/// ```text
+/// HeaderRecord := { block_hash: Bytes32, total_difficulty: uint256 }
+/// ```
+pub fn compute_accumulator<I>(records: I) -> Result<B256, Era1Error>
+where
+    I: IntoIterator<Item = (B256, U256)>,
+{
+    let mut layer = records
+        .into_iter()
+        .map(|(block_hash, total_difficulty)| {
+            hash_pair(&block_hash.0, &total_difficulty.to_le_bytes::<32>())
+        })
+        .collect::<Vec<_>>();
+    let len = layer.len();
+    if len > MAX_BLOCKS_PER_ERA1 {
+        return Err(Era1Error::TooManyBlocks(len))
+    }
+
+    let mut zero_hash = [0u8; 32];
+    for _ in 0..ACCUMULATOR_DEPTH {
+        if layer.is_empty() {
+            layer.push(zero_hash);
+        }
+        if layer.len() % 2 == 1 {
+            layer.push(zero_hash);
+        }
+        layer = layer.chunks_exact(2).map(|pair| hash_pair(&pair[0], &pair[1])).collect();
+        zero_hash = hash_pair(&zero_hash, &zero_hash);
+    }
+
+    // mix in the length of the list
+    let mut length = [0u8; 32];
+    length[..8].copy_from_slice(&(len as u64).to_le_bytes());
+    Ok(B256::new(hash_pair(&layer[0], &length)))
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_accumulator() {
+        // the root of an empty list is the zero hash of the tree mixed with a zero length
+        let mut zero_hash = [0u8; 32];
+        for _ in 0..ACCUMULATOR_DEPTH {
+            zero_hash = hash_pair(&zero_hash, &zero_hash);
+        }
+        assert_eq!(
+            compute_accumulator(std::iter::empty()).unwrap(),
+            B256::new(hash_pair(&zero_hash, &[0u8; 32]))
+        );
+    }
+
+    #[test]
+    fn accumulator_depends_on_records() {
+        let records = (0..3u64).map(|i| (B256::with_last_byte(i as u8), U256::from(i)));
+        let root = compute_accumulator(records.clone()).unwrap();
+        assert_ne!(root, compute_accumulator(records.clone().take(2)).unwrap());
+        assert_ne!(
+            root,
+            compute_accumulator(records.map(|(hash, td)| (hash, td + U256::from(1)))).unwrap()
+        );
+    }
+
+    #[test]
+    fn accumulator_rejects_oversized_epoch() {
+        let records = (0..=MAX_BLOCKS_PER_ERA1).map(|_| (B256::ZERO, U256::ZERO));
+        assert!(matches!(compute_accumulator(records), Err(Era1Error::TooManyBlocks(8193))));
+    }
+}
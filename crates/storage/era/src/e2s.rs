@@ -0,0 +1,177 @@
+//! The e2store container format.
+//!
+//! An e2store file is a sequence of type-length-value entries:
+//!
+//! ```text
+//! entry  := header | data
+//! header := type | length | reserved
+//! ```
+//!
+//! where `type` is 2 bytes, `length` is the little-endian `u32` length of `data` and `reserved` is
+//! 2 zero bytes.
+//!
+//! See also <https://github.com/status-im/nimbus-eth2/blob/stable/docs/e2store.md>
+
+use crate::Era1Error;
+use std::io::{self, Read, Write};
+
+/// The size of an entry header in bytes.
+pub const HEADER_SIZE: usize = 8;
+
+/// A single e2store entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    /// The type of the entry.
+    pub entry_type: u16,
+    /// The value of the entry.
+    pub data: Vec<u8>,
+}
+
+impl Entry {
+    /// Creates a new entry.
+    pub const fn new(entry_type: u16, data: Vec<u8>) -> Self {
+        Self { entry_type, data }
+    }
+
+    /// Returns the length of the encoded entry, including its header.
+    pub fn encoded_len(&self) -> u64 {
+        (HEADER_SIZE + self.data.len()) as u64
+    }
+}
+
+/// Reads [`Entry`]s from an e2store file.
+#[derive(Debug)]
+pub struct E2StoreReader<R> {
+    inner: R,
+    /// The number of bytes read so far.
+    position: u64,
+}
+
+impl<R: Read> E2StoreReader<R> {
+    /// Creates a new reader.
+    pub const fn new(inner: R) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Returns the offset of the next entry.
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Reads the next entry.
+    ///
+    /// Returns `None` if the end of the file is reached at an entry boundary.
+    pub fn read_entry(&mut self) -> Result<Option<Entry>, Era1Error> {
+        let mut header = [0u8; HEADER_SIZE];
+        match self.inner.read_exact(&mut header[..1]) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+        self.inner.read_exact(&mut header[1..])?;
+
+        let entry_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = u32::from_le_bytes([header[2], header[3], header[4], header[5]]);
+        if header[6..] != [0, 0] {
+            return Err(Era1Error::ReservedNotZero { position: self.position })
+        }
+
+        // The length isn't trusted, so the buffer only grows with the data that is actually read.
+        let mut data = Vec::new();
+        self.inner.by_ref().take(u64::from(length)).read_to_end(&mut data)?;
+        if data.len() != length as usize {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+        }
+
+        let entry = Entry::new(entry_type, data);
+        self.position += entry.encoded_len();
+        Ok(Some(entry))
+    }
+}
+
+/// Writes [`Entry`]s to an e2store file.
+#[derive(Debug)]
+pub struct E2StoreWriter<W> {
+    inner: W,
+    /// The number of bytes written so far.
+    position: u64,
+}
+
+impl<W: Write> E2StoreWriter<W> {
+    /// Creates a new writer.
+    pub const fn new(inner: W) -> Self {
+        Self { inner, position: 0 }
+    }
+
+    /// Returns the offset of the next entry.
+    pub const fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Writes an entry.
+    pub fn write_entry(&mut self, entry: &Entry) -> Result<(), Era1Error> {
+        let length = u32::try_from(entry.data.len())
+            .map_err(|_| Era1Error::EntryTooLarge(entry.data.len()))?;
+
+        let mut header = [0u8; HEADER_SIZE];
+        header[..2].copy_from_slice(&entry.entry_type.to_le_bytes());
+        header[2..6].copy_from_slice(&length.to_le_bytes());
+        self.inner.write_all(&header)?;
+        self.inner.write_all(&entry.data)?;
+
+        self.position += entry.encoded_len();
+        Ok(())
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<(), Era1Error> {
+        Ok(self.inner.flush()?)
+    }
+
+    /// Consumes the writer and returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_roundtrip() {
+        let entries = [Entry::new(0x3265, vec![]), Entry::new(0x03, vec![1, 2, 3])];
+
+        let mut writer = E2StoreWriter::new(Vec::new());
+        for entry in &entries {
+            writer.write_entry(entry).unwrap();
+        }
+        assert_eq!(writer.position(), 19);
+        let buf = writer.into_inner();
+        assert_eq!(&buf[..HEADER_SIZE], &[0x65, 0x32, 0, 0, 0, 0, 0, 0]);
+
+        let mut reader = E2StoreReader::new(buf.as_slice());
+        assert_eq!(reader.read_entry().unwrap(), Some(entries[0].clone()));
+        assert_eq!(reader.read_entry().unwrap(), Some(entries[1].clone()));
+        assert_eq!(reader.read_entry().unwrap(), None);
+        assert_eq!(reader.position(), 19);
+    }
+
+    #[test]
+    fn reserved_bytes_must_be_zero() {
+        let buf = [0x03, 0, 0, 0, 0, 0, 1, 0];
+        let mut reader = E2StoreReader::new(buf.as_slice());
+        assert!(matches!(reader.read_entry(), Err(Era1Error::ReservedNotZero { position: 0 })));
+    }
+
+    #[test]
+    fn truncated_entry() {
+        // the header claims an entry of `u32::MAX` bytes, but only 3 follow
+        let buf = [0x03, 0, 0xff, 0xff, 0xff, 0xff, 0, 0, 1, 2, 3];
+        let mut reader = E2StoreReader::new(buf.as_slice());
+        assert!(matches!(
+            reader.read_entry(),
+            Err(Era1Error::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+}
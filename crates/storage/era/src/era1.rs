@@ -0,0 +1,437 @@
+use crate::{
+    compute_accumulator,
+    e2s::{E2StoreReader, E2StoreWriter, Entry},
+    Era1Error,
+};
+use alloy_rlp::Decodable;
+use reth_primitives::{
+    hex,
+    proofs::{calculate_ommers_root, calculate_receipt_root, calculate_transaction_root},
+    BlockBody, BlockNumber, Header, ReceiptWithBloom, SealedHeader, B256, U256,
+};
+use std::io::{Read, Write};
+
+/// The maximum number of blocks of an Era1 file, the length of an epoch.
+pub const MAX_BLOCKS_PER_ERA1: usize = 8192;
+
+/// The extension of Era1 files.
+pub const ERA1_FILE_EXTENSION: &str = "era1";
+
+/// The e2store version entry, `"e2"`.
+const VERSION: u16 = 0x3265;
+/// A snappy framed, RLP encoded header.
+const COMPRESSED_HEADER: u16 = 0x03;
+/// A snappy framed, RLP encoded body.
+const COMPRESSED_BODY: u16 = 0x04;
+/// Snappy framed, RLP encoded receipts.
+const COMPRESSED_RECEIPTS: u16 = 0x05;
+/// A little-endian `uint256` total difficulty.
+const TOTAL_DIFFICULTY: u16 = 0x06;
+/// The epoch accumulator root.
+const ACCUMULATOR: u16 = 0x07;
+/// The offsets of the blocks of the file.
+const BLOCK_INDEX: u16 = 0x3266;
+
+/// A block of an Era1 file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Era1Block {
+    /// The sealed header of the block.
+    pub header: SealedHeader,
+    /// The body of the block.
+    pub body: BlockBody,
+    /// The receipts of the block's transactions.
+    pub receipts: Vec<ReceiptWithBloom>,
+    /// The total difficulty of the chain up to and including the block.
+    pub total_difficulty: U256,
+}
+
+impl Era1Block {
+    /// Returns the number of the block.
+    pub const fn number(&self) -> BlockNumber {
+        self.header.number
+    }
+}
+
+/// The content of an Era1 file, a single epoch of blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Era1File {
+    /// The blocks of the epoch.
+    pub blocks: Vec<Era1Block>,
+    /// The accumulator root of the epoch.
+    pub accumulator: B256,
+}
+
+impl Era1File {
+    /// Creates a new Era1 file from the given blocks and computes its accumulator root.
+    pub fn new(blocks: Vec<Era1Block>) -> Result<Self, Era1Error> {
+        if blocks.is_empty() {
+            return Err(Era1Error::Empty)
+        }
+        let accumulator = compute_accumulator(
+            blocks.iter().map(|block| (block.header.hash(), block.total_difficulty)),
+        )?;
+        Ok(Self { blocks, accumulator })
+    }
+
+    /// Returns the number of the first block of the file.
+    pub fn start_block(&self) -> BlockNumber {
+        self.blocks.first().map(Era1Block::number).unwrap_or_default()
+    }
+
+    /// Returns the number of the last block of the file.
+    pub fn end_block(&self) -> BlockNumber {
+        self.blocks.last().map(Era1Block::number).unwrap_or_default()
+    }
+
+    /// Returns the epoch of the file.
+    pub fn epoch(&self) -> u64 {
+        self.start_block() / MAX_BLOCKS_PER_ERA1 as u64
+    }
+
+    /// Returns the name of the file for the given network, see [`era1_file_name`].
+    pub fn file_name(&self, network: &str) -> String {
+        era1_file_name(network, self.epoch(), self.accumulator)
+    }
+
+    /// Verifies that the blocks of the file are a contiguous chain within a single epoch, that
+    /// their bodies and receipts match their headers and that the accumulator root matches the
+    /// blocks.
+    pub fn verify(&self) -> Result<(), Era1Error> {
+        if self.blocks.is_empty() {
+            return Err(Era1Error::Empty)
+        }
+        if self.blocks.len() > MAX_BLOCKS_PER_ERA1 {
+            return Err(Era1Error::TooManyBlocks(self.blocks.len()))
+        }
+
+        let epoch = self.epoch();
+        let mut parent: Option<&Era1Block> = None;
+        for block in &self.blocks {
+            let number = block.number();
+            if number / MAX_BLOCKS_PER_ERA1 as u64 != epoch {
+                return Err(Era1Error::BlockOutsideEpoch(number))
+            }
+
+            if let Some(parent) = parent {
+                if number != parent.number() + 1 {
+                    return Err(Era1Error::BlockNumberMismatch {
+                        expected: parent.number() + 1,
+                        got: number,
+                    })
+                }
+                if block.header.parent_hash != parent.header.hash() {
+                    return Err(Era1Error::ParentHashMismatch(number))
+                }
+                if block.total_difficulty != parent.total_difficulty + block.header.difficulty {
+                    return Err(Era1Error::TotalDifficultyMismatch(number))
+                }
+            }
+
+            if calculate_transaction_root(&block.body.transactions) !=
+                block.header.transactions_root
+            {
+                return Err(Era1Error::TransactionsRootMismatch(number))
+            }
+            if calculate_ommers_root(&block.body.ommers) != block.header.ommers_hash {
+                return Err(Era1Error::OmmersHashMismatch(number))
+            }
+            if calculate_receipt_root(&block.receipts) != block.header.receipts_root {
+                return Err(Era1Error::ReceiptsRootMismatch(number))
+            }
+
+            parent = Some(block);
+        }
+
+        let accumulator = compute_accumulator(
+            self.blocks.iter().map(|block| (block.header.hash(), block.total_difficulty)),
+        )?;
+        if accumulator != self.accumulator {
+            return Err(Era1Error::AccumulatorMismatch {
+                expected: self.accumulator,
+                got: accumulator,
+            })
+        }
+
+        Ok(())
+    }
+
+    /// Reads an Era1 file.
+    ///
+    /// This only checks the structure of the file, see [`Era1File::verify`] for verifying its
+    /// content.
+    pub fn read<R: Read>(reader: R) -> Result<Self, Era1Error> {
+        let mut reader = E2StoreReader::new(reader);
+        read_entry(&mut reader, VERSION)?;
+
+        let mut blocks = Vec::new();
+        let mut offsets = Vec::new();
+        let accumulator = loop {
+            let position = reader.position();
+            let entry = reader.read_entry()?.ok_or(Era1Error::UnexpectedEof(ACCUMULATOR))?;
+            match entry.entry_type {
+                COMPRESSED_HEADER => {
+                    let header = Header::decode(&mut decompress(&entry.data)?.as_slice())?;
+                    let body = read_entry(&mut reader, COMPRESSED_BODY)?;
+                    let body = BlockBody::decode(&mut decompress(&body)?.as_slice())?;
+                    let receipts = read_entry(&mut reader, COMPRESSED_RECEIPTS)?;
+                    let receipts =
+                        Vec::<ReceiptWithBloom>::decode(&mut decompress(&receipts)?.as_slice())?;
+                    let total_difficulty = read_entry(&mut reader, TOTAL_DIFFICULTY)?;
+                    let total_difficulty =
+                        U256::from_le_slice(&fixed_bytes(TOTAL_DIFFICULTY, &total_difficulty)?);
+
+                    offsets.push(position);
+                    blocks.push(Era1Block {
+                        header: header.seal_slow(),
+                        body,
+                        receipts,
+                        total_difficulty,
+                    });
+                }
+                ACCUMULATOR => break B256::new(fixed_bytes(ACCUMULATOR, &entry.data)?),
+                got => return Err(Era1Error::UnexpectedEntry { expected: COMPRESSED_HEADER, got }),
+            }
+        };
+
+        let index_position = reader.position();
+        let index = read_entry(&mut reader, BLOCK_INDEX)?;
+        if index.len() < 16 || (index.len() - 16) % 8 != 0 {
+            return Err(Era1Error::InvalidEntryLength {
+                entry_type: BLOCK_INDEX,
+                length: index.len(),
+            })
+        }
+        let mut words = index
+            .chunks_exact(8)
+            .map(|word| u64::from_le_bytes(word.try_into().expect("chunks are 8 bytes long")));
+        let start = words.next().unwrap_or_default();
+        let count = words.next_back().unwrap_or_default();
+        let block_offsets = words.map(|offset| index_position.wrapping_add(offset));
+        if blocks.is_empty() ||
+            count != blocks.len() as u64 ||
+            start != blocks[0].number() ||
+            !block_offsets.eq(offsets.iter().copied())
+        {
+            return Err(Era1Error::BlockIndexMismatch)
+        }
+
+        Ok(Self { blocks, accumulator })
+    }
+
+    /// Writes the Era1 file.
+    pub fn write<W: Write>(&self, writer: W) -> Result<(), Era1Error> {
+        if self.blocks.is_empty() {
+            return Err(Era1Error::Empty)
+        }
+
+        let mut writer = E2StoreWriter::new(writer);
+        writer.write_entry(&Entry::new(VERSION, Vec::new()))?;
+
+        let mut offsets = Vec::with_capacity(self.blocks.len());
+        for block in &self.blocks {
+            offsets.push(writer.position());
+            writer.write_entry(&Entry::new(
+                COMPRESSED_HEADER,
+                compress(&alloy_rlp::encode(block.header.header()))?,
+            ))?;
+            writer.write_entry(&Entry::new(
+                COMPRESSED_BODY,
+                compress(&alloy_rlp::encode(&block.body))?,
+            ))?;
+            writer.write_entry(&Entry::new(
+                COMPRESSED_RECEIPTS,
+                compress(&alloy_rlp::encode(&block.receipts))?,
+            ))?;
+            writer.write_entry(&Entry::new(
+                TOTAL_DIFFICULTY,
+                block.total_difficulty.to_le_bytes::<32>().to_vec(),
+            ))?;
+        }
+        writer.write_entry(&Entry::new(ACCUMULATOR, self.accumulator.to_vec()))?;
+
+        // the offsets of the blocks are relative to the start of the block index
+        let index_position = writer.position();
+        let mut index = Vec::with_capacity(16 + offsets.len() * 8);
+        index.extend_from_slice(&self.start_block().to_le_bytes());
+        for offset in offsets {
+            index.extend_from_slice(&offset.wrapping_sub(index_position).to_le_bytes());
+        }
+        index.extend_from_slice(&(self.blocks.len() as u64).to_le_bytes());
+        writer.write_entry(&Entry::new(BLOCK_INDEX, index))?;
+
+        writer.flush()
+    }
+}
+
+/// Returns the name of the Era1 file of the given epoch:
+/// `<network>-<epoch>-<first 4 bytes of the accumulator root>.era1`.
+pub fn era1_file_name(network: &str, epoch: u64, accumulator: B256) -> String {
+    format!("{network}-{epoch:05}-{}.{ERA1_FILE_EXTENSION}", hex::encode(&accumulator[..4]))
+}
+
+/// Parses the network and the epoch from the name of an Era1 file.
+pub fn parse_era1_file_name(name: &str) -> Option<(&str, u64)> {
+    let stem = name.strip_suffix(ERA1_FILE_EXTENSION)?.strip_suffix('.')?;
+    let mut parts = stem.rsplitn(3, '-');
+    let _short_root = parts.next().filter(|root| root.len() == 8)?;
+    let epoch = parts.next()?.parse().ok()?;
+    let network = parts.next()?;
+    Some((network, epoch))
+}
+
+/// Reads the next entry, which must be of the given type, and returns its value.
+fn read_entry<R: Read>(
+    reader: &mut E2StoreReader<R>,
+    entry_type: u16,
+) -> Result<Vec<u8>, Era1Error> {
+    let entry = reader.read_entry()?.ok_or(Era1Error::UnexpectedEof(entry_type))?;
+    if entry.entry_type != entry_type {
+        return Err(Era1Error::UnexpectedEntry { expected: entry_type, got: entry.entry_type })
+    }
+    Ok(entry.data)
+}
+
+/// Returns the value of a 32 byte entry.
+fn fixed_bytes(entry_type: u16, data: &[u8]) -> Result<[u8; 32], Era1Error> {
+    data.try_into().map_err(|_| Era1Error::InvalidEntryLength { entry_type, length: data.len() })
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, Era1Error> {
+    let mut encoder = snap::write::FrameEncoder::new(Vec::new());
+    encoder.write_all(data)?;
+    encoder.into_inner().map_err(|err| err.into_error().into())
+}
+
+fn decompress(data: &[u8]) -> Result<Vec<u8>, Era1Error> {
+    let mut decompressed = Vec::new();
+    snap::read::FrameDecoder::new(data).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::b256;
+    use reth_testing_utils::generators::{self, random_block, random_receipt};
+    use std::{fs::File, io::BufReader, path::Path};
+
+    /// The accumulator root of the first mainnet epoch, blocks `0..8192`.
+    const MAINNET_EPOCH_0_ACCUMULATOR: B256 =
+        b256!("5ec1ffb8c3b146f42606c74ced973dc16ec5a107c0345858c343fc94780b4218");
+
+    /// Returns an epoch of `len` blocks, starting at `start`, with consistent roots.
+    fn random_epoch(start: BlockNumber, len: u64) -> Vec<Era1Block> {
+        let mut rng = generators::rng();
+        let mut blocks: Vec<Era1Block> = Vec::new();
+        for number in start..start + len {
+            let parent = blocks.last().map(|block| block.header.hash());
+            let block = random_block(&mut rng, number, parent, Some(2), Some(1));
+            let receipts = block
+                .body
+                .iter()
+                .map(|tx| random_receipt(&mut rng, tx, Some(1)).with_bloom())
+                .collect::<Vec<_>>();
+
+            let mut header = block.header.unseal();
+            header.difficulty = U256::from(number + 1);
+            header.receipts_root = calculate_receipt_root(&receipts);
+            let total_difficulty =
+                blocks.last().map(|block| block.total_difficulty).unwrap_or_default() +
+                    header.difficulty;
+
+            blocks.push(Era1Block {
+                header: header.seal_slow(),
+                body: BlockBody {
+                    transactions: block.body,
+                    ommers: block.ommers,
+                    withdrawals: None,
+                    requests: None,
+                },
+                receipts,
+                total_difficulty,
+            });
+        }
+        blocks
+    }
+
+    #[test]
+    fn era1_roundtrip() {
+        let file = Era1File::new(random_epoch(8192, 5)).unwrap();
+        file.verify().unwrap();
+        assert_eq!(file.epoch(), 1);
+        assert_eq!(file.end_block(), 8196);
+
+        let mut buf = Vec::new();
+        file.write(&mut buf).unwrap();
+        let read = Era1File::read(buf.as_slice()).unwrap();
+        assert_eq!(read, file);
+        read.verify().unwrap();
+    }
+
+    #[test]
+    fn era1_verify_detects_tampering() {
+        let mut file = Era1File::new(random_epoch(0, 3)).unwrap();
+        file.blocks[1].receipts.pop();
+        assert!(matches!(file.verify(), Err(Era1Error::ReceiptsRootMismatch(1))));
+
+        let mut file = Era1File::new(random_epoch(0, 3)).unwrap();
+        file.blocks[2].total_difficulty += U256::from(1);
+        assert!(matches!(file.verify(), Err(Era1Error::TotalDifficultyMismatch(2))));
+
+        let mut file = Era1File::new(random_epoch(0, 3)).unwrap();
+        file.accumulator = B256::ZERO;
+        assert!(matches!(file.verify(), Err(Era1Error::AccumulatorMismatch { .. })));
+
+        let file = Era1File::new(random_epoch(8190, 3)).unwrap();
+        assert!(matches!(file.verify(), Err(Era1Error::BlockOutsideEpoch(8192))));
+    }
+
+    #[test]
+    fn era1_read_rejects_corrupt_index() {
+        let file = Era1File::new(random_epoch(0, 2)).unwrap();
+        let mut buf = Vec::new();
+        file.write(&mut buf).unwrap();
+
+        // the last word of the index is the block count
+        let len = buf.len();
+        buf[len - 8] = 3;
+        assert!(matches!(Era1File::read(buf.as_slice()), Err(Era1Error::BlockIndexMismatch)));
+    }
+
+    #[test]
+    fn era1_file_names() {
+        let name = era1_file_name("mainnet", 12, B256::repeat_byte(0xab));
+        assert_eq!(name, "mainnet-00012-abababab.era1");
+        assert_eq!(parse_era1_file_name(&name), Some(("mainnet", 12)));
+        assert_eq!(parse_era1_file_name("mainnet-00012-abab.era1"), None);
+        assert_eq!(parse_era1_file_name("mainnet-00012-abababab.era"), None);
+    }
+
+    #[test]
+    fn mainnet_epoch_0_file_name() {
+        assert_eq!(
+            era1_file_name("mainnet", 0, MAINNET_EPOCH_0_ACCUMULATOR),
+            "mainnet-00000-5ec1ffb8.era1"
+        );
+    }
+
+    /// Reads `mainnet-00000-5ec1ffb8.era1` from the directory in the `ERA1_DIR` environment
+    /// variable, and checks the accumulator root computed from its blocks.
+    #[test]
+    #[ignore]
+    fn mainnet_epoch_0_accumulator() {
+        let dir = std::env::var("ERA1_DIR").expect("ERA1_DIR is not set");
+        let file = File::open(Path::new(&dir).join("mainnet-00000-5ec1ffb8.era1")).unwrap();
+        let file = Era1File::read(BufReader::new(file)).unwrap();
+        assert_eq!(file.blocks.len(), MAX_BLOCKS_PER_ERA1);
+        assert_eq!(file.accumulator, MAINNET_EPOCH_0_ACCUMULATOR);
+        assert_eq!(
+            compute_accumulator(
+                file.blocks.iter().map(|block| (block.header.hash(), block.total_difficulty))
+            )
+            .unwrap(),
+            MAINNET_EPOCH_0_ACCUMULATOR
+        );
+        file.verify().unwrap();
+    }
+}
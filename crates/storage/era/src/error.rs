@@ -0,0 +1,85 @@
+use reth_primitives::{BlockNumber, B256};
+use std::io;
+
+/// Errors that can occur when reading, writing or verifying Era1 files.
+#[derive(Debug, thiserror::Error)]
+pub enum Era1Error {
+    /// I/O error.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// Failed to decode an RLP encoded value.
+    #[error(transparent)]
+    Rlp(#[from] alloy_rlp::Error),
+    /// The reserved bytes of an entry header are not zero.
+    #[error("reserved bytes of the entry at offset {position} are not zero")]
+    ReservedNotZero {
+        /// The offset of the entry.
+        position: u64,
+    },
+    /// The value of an entry does not fit into the entry length.
+    #[error("entry of {0} bytes is too large")]
+    EntryTooLarge(usize),
+    /// The file ended before the expected entry.
+    #[error("unexpected end of file, expected entry of type {0:#06x}")]
+    UnexpectedEof(u16),
+    /// An entry of an unexpected type was found.
+    #[error("unexpected entry of type {got:#06x}, expected {expected:#06x}")]
+    UnexpectedEntry {
+        /// The expected entry type.
+        expected: u16,
+        /// The entry type found.
+        got: u16,
+    },
+    /// An entry has an invalid length.
+    #[error("invalid length {length} of entry of type {entry_type:#06x}")]
+    InvalidEntryLength {
+        /// The entry type.
+        entry_type: u16,
+        /// The length of the entry value.
+        length: usize,
+    },
+    /// The file does not contain any blocks.
+    #[error("era1 file contains no blocks")]
+    Empty,
+    /// The file contains more blocks than fit into an epoch.
+    #[error("{0} blocks don't fit into an epoch")]
+    TooManyBlocks(usize),
+    /// The block index does not match the blocks of the file.
+    #[error("block index does not match the blocks of the file")]
+    BlockIndexMismatch,
+    /// The blocks of the file are not contiguous.
+    #[error("expected block {expected}, got {got}")]
+    BlockNumberMismatch {
+        /// The expected block number.
+        expected: BlockNumber,
+        /// The block number found.
+        got: BlockNumber,
+    },
+    /// The blocks of the file span more than one epoch.
+    #[error("block {0} is outside the epoch of the file")]
+    BlockOutsideEpoch(BlockNumber),
+    /// The parent hash of a block does not match the hash of the previous block.
+    #[error("parent hash of block {0} does not match the previous block")]
+    ParentHashMismatch(BlockNumber),
+    /// The transactions of a block don't match its transactions root.
+    #[error("transactions of block {0} don't match the transactions root")]
+    TransactionsRootMismatch(BlockNumber),
+    /// The ommers of a block don't match its ommers hash.
+    #[error("ommers of block {0} don't match the ommers hash")]
+    OmmersHashMismatch(BlockNumber),
+    /// The receipts of a block don't match its receipts root.
+    #[error("receipts of block {0} don't match the receipts root")]
+    ReceiptsRootMismatch(BlockNumber),
+    /// The total difficulty of a block is not the total difficulty of its parent plus its
+    /// difficulty.
+    #[error("total difficulty of block {0} is inconsistent")]
+    TotalDifficultyMismatch(BlockNumber),
+    /// The accumulator root of the file does not match its blocks.
+    #[error("accumulator root mismatch: expected {expected}, got {got}")]
+    AccumulatorMismatch {
+        /// The accumulator root recorded in the file.
+        expected: B256,
+        /// The accumulator root computed from the blocks.
+        got: B256,
+    },
+}
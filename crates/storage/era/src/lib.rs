@@ -0,0 +1,33 @@
+//! Reading and writing of Era1 history archives.
+//!
+//! Era1 files store pre-merge history, one epoch of [`MAX_BLOCKS_PER_ERA1`] blocks per file, in
+//! the [e2store](e2s) container format:
+//!
+//! ```text
+//! era1        := Version | block-tuple* | Accumulator | BlockIndex
+//! block-tuple := CompressedHeader | CompressedBody | CompressedReceipts | TotalDifficulty
+//! ```
+//!
+//! See also <https://github.com/ethereum/go-ethereum/blob/master/internal/era/era.go>
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/paradigmxyz/reth/main/assets/reth-docs.png",
+    html_favicon_url = "https://avatars0.githubusercontent.com/u/97369466?s=256",
+    issue_tracker_base_url = "https://github.com/paradigmxyz/reth/issues/"
+)]
+#![cfg_attr(not(test), warn(unused_crate_dependencies))]
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+mod accumulator;
+pub use accumulator::compute_accumulator;
+
+pub mod e2s;
+
+mod era1;
+pub use era1::{
+    era1_file_name, parse_era1_file_name, Era1Block, Era1File, ERA1_FILE_EXTENSION,
+    MAX_BLOCKS_PER_ERA1,
+};
+
+mod error;
+pub use error::Era1Error;
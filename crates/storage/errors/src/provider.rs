@@ -111,6 +111,24 @@ pub enum ProviderError {
     /// State is not available for the given block number because it is pruned.
     #[error("state at block #{0} is pruned")]
     StateAtBlockPruned(BlockNumber),
+    /// Account history is not available for the given block number because it is pruned, and the
+    /// address is not retained by the account history prune configuration.
+    #[error("account history of {address} at block #{block_number} is pruned")]
+    AccountHistoryPruned {
+        /// The requested block number.
+        block_number: BlockNumber,
+        /// The account address.
+        address: Address,
+    },
+    /// Storage history is not available for the given block number because it is pruned, and the
+    /// address is not retained by the storage history prune configuration.
+    #[error("storage history of {address} at block #{block_number} is pruned")]
+    StorageHistoryPruned {
+        /// The requested block number.
+        block_number: BlockNumber,
+        /// The account address.
+        address: Address,
+    },
     /// Provider does not support this particular request.
     #[error("this provider does not support this request")]
     UnsupportedProvider,
@@ -80,6 +80,15 @@ impl Environment {
         !self.inner.env_kind.is_write_map()
     }
 
+    /// Returns the number of handles to the environment, including the ones held by open
+    /// transactions and cursors.
+    ///
+    /// The environment is closed once all of them are dropped.
+    #[inline]
+    pub fn handles(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
     /// Returns the transaction manager.
     #[inline]
     pub(crate) fn txn_manager(&self) -> &TxnManager {
@@ -152,6 +161,62 @@ impl Environment {
         mdbx_result(unsafe { ffi::mdbx_env_sync_ex(self.env_ptr(), force, false) })
     }
 
+    /// Copies the environment into the file at `dest`, which must not exist yet.
+    ///
+    /// The copy is taken under a read transaction, so it is consistent and can be taken while the
+    /// environment is being written to. If `compact` is set, free pages are omitted and all pages
+    /// are renumbered sequentially, which makes the copy smaller than the source.
+    ///
+    /// No lock file is created, it's recreated when the copy is opened.
+    pub fn copy(&self, dest: &Path, compact: bool) -> Result<()> {
+        let dest = path_to_cstring(dest)?;
+        let flags = if compact {
+            ffi::MDBX_CP_COMPACT | ffi::MDBX_CP_FORCE_DYNAMIC_SIZE
+        } else {
+            ffi::MDBX_CP_DEFAULTS
+        };
+        mdbx_result(unsafe { ffi::mdbx_env_copy(self.env_ptr(), dest.as_ptr(), flags) })?;
+        Ok(())
+    }
+
+    /// Returns the process IDs of the readers registered in the lock file, including the current
+    /// process.
+    ///
+    /// Reader slots of processes that no longer exist are cleared first. A process keeps its slots
+    /// while it has the environment open, even if it has no read transaction open.
+    pub fn reader_pids(&self) -> Result<Vec<u32>> {
+        unsafe extern "C" fn collect_pid(
+            ctx: *mut std::ffi::c_void,
+            _num: std::ffi::c_int,
+            _slot: std::ffi::c_int,
+            pid: ffi::mdbx_pid_t,
+            _thread: ffi::mdbx_tid_t,
+            _txnid: u64,
+            _lag: u64,
+            _bytes_used: usize,
+            _bytes_retained: usize,
+        ) -> std::ffi::c_int {
+            let pids = &mut *(ctx as *mut Vec<u32>);
+            pids.push(pid as u32);
+            0
+        }
+
+        let mut dead = 0;
+        mdbx_result(unsafe { ffi::mdbx_reader_check(self.env_ptr(), &mut dead) })?;
+
+        let mut pids = Vec::new();
+        mdbx_result(unsafe {
+            ffi::mdbx_reader_list(
+                self.env_ptr(),
+                Some(collect_pid),
+                &mut pids as *mut Vec<u32> as *mut std::ffi::c_void,
+            )
+        })?;
+        pids.sort_unstable();
+        pids.dedup();
+        Ok(pids)
+    }
+
     /// Retrieves statistics about this environment.
     pub fn stat(&self) -> Result<Stat> {
         unsafe {
@@ -225,6 +290,25 @@ impl Environment {
     }
 }
 
+#[cfg(unix)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_ref().as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
+    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
+    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
+    // really help.
+    path.as_ref().to_string_lossy().to_string().into_bytes()
+}
+
+/// Converts the path into a [`CString`], the path may not contain the null character.
+fn path_to_cstring(path: &Path) -> Result<CString> {
+    CString::new(path_to_bytes(path)).map_err(|_| Error::Invalid)
+}
+
 /// Container type for Environment internals.
 ///
 /// This holds the raw pointer to the MDBX environment and the transaction manager.
@@ -667,24 +751,7 @@ impl EnvironmentBuilder {
                     ))?;
                 }
 
-                #[cfg(unix)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    use std::os::unix::ffi::OsStrExt;
-                    path.as_ref().as_os_str().as_bytes().to_vec()
-                }
-
-                #[cfg(windows)]
-                fn path_to_bytes<P: AsRef<Path>>(path: P) -> Vec<u8> {
-                    // On Windows, could use std::os::windows::ffi::OsStrExt to encode_wide(),
-                    // but we end up with a Vec<u16> instead of a Vec<u8>, so that doesn't
-                    // really help.
-                    path.as_ref().to_string_lossy().to_string().into_bytes()
-                }
-
-                let path = match CString::new(path_to_bytes(path)) {
-                    Ok(path) => path,
-                    Err(_) => return Err(Error::Invalid),
-                };
+                let path = path_to_cstring(path)?;
                 mdbx_result(ffi::mdbx_env_open(
                     env,
                     path.as_ptr(),
@@ -37,6 +37,28 @@ fn test_begin_txn() {
     }
 }
 
+#[test]
+fn test_copy() {
+    let dir = tempdir().unwrap();
+    let env = Environment::builder().open(dir.path()).unwrap();
+
+    let txn = env.begin_rw_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    txn.put(db.dbi(), b"key", b"value", WriteFlags::empty()).unwrap();
+    txn.commit().unwrap();
+
+    let copy_dir = tempdir().unwrap();
+    env.copy(&copy_dir.path().join("mdbx.dat"), true).unwrap();
+
+    // copying into an existing file should fail
+    assert!(env.copy(&copy_dir.path().join("mdbx.dat"), true).is_err());
+
+    let copy = Environment::builder().open(copy_dir.path()).unwrap();
+    let txn = copy.begin_ro_txn().unwrap();
+    let db = txn.open_db(None).unwrap();
+    assert_eq!(txn.get(db.dbi(), b"key").unwrap(), Some(*b"value"));
+}
+
 #[test]
 fn test_open_db() {
     let dir = tempdir().unwrap();
@@ -129,6 +151,17 @@ fn test_info() {
     assert_eq!(info.num_readers(), 0);
 }
 
+#[test]
+fn test_reader_pids() {
+    let dir = tempdir().unwrap();
+    let env = Environment::builder().open(dir.path()).unwrap();
+    assert!(env.reader_pids().unwrap().is_empty());
+
+    let txn = env.begin_ro_txn().unwrap();
+    assert_eq!(env.reader_pids().unwrap(), vec![std::process::id()]);
+    drop(txn);
+}
+
 #[test]
 fn test_freelist() {
     let dir = tempdir().unwrap();
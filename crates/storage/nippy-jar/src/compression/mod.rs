@@ -2,7 +2,9 @@ use crate::NippyJarError;
 use serde::{Deserialize, Serialize};
 
 mod zstd;
-pub use self::zstd::{DecoderDictionary, Decompressor, Zstd, ZstdState};
+pub use self::zstd::{
+    DecoderDictionary, Decompressor, EncoderDictionaries, EncoderDictionary, Zstd, ZstdState,
+};
 mod lz4;
 pub use self::lz4::Lz4;
 
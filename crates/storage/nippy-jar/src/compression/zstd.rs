@@ -8,7 +8,10 @@ use std::{
 };
 use tracing::*;
 use zstd::bulk::Compressor;
-pub use zstd::{bulk::Decompressor, dict::DecoderDictionary};
+pub use zstd::{
+    bulk::Decompressor,
+    dict::{DecoderDictionary, EncoderDictionary},
+};
 
 type RawDictionary = Vec<u8>;
 
@@ -56,6 +59,79 @@ impl Zstd {
         self
     }
 
+    /// Sets already trained dictionaries, one per column, and marks the compressor as ready.
+    ///
+    /// Allows sharing dictionaries trained once for a whole segment across many jars.
+    pub fn with_dictionaries(mut self, dictionaries: Vec<RawDictionary>) -> Self {
+        debug_assert_eq!(dictionaries.len(), self.columns);
+        self.use_dict = true;
+        self.dictionaries = Some(Arc::new(ZstdDictionaries::load(dictionaries)));
+        self.state = ZstdState::Ready;
+        self
+    }
+
+    /// Returns the raw dictionaries, one per column, if using dictionaries.
+    pub fn raw_dictionaries(&self) -> Option<Vec<RawDictionary>> {
+        self.dictionaries
+            .as_ref()
+            .map(|dictionaries| dictionaries.iter().map(|dict| dict.raw().clone()).collect())
+    }
+
+    /// Trains a dictionary of at most `max_dict_size` bytes from a list of samples.
+    pub fn train_dictionary(
+        samples: impl IntoIterator<Item = Vec<u8>>,
+        max_dict_size: usize,
+    ) -> Result<RawDictionary, NippyJarError> {
+        // ZSTD requires all training data to be continuous in memory, alongside the size of
+        // each entry
+        let mut sizes = vec![];
+        let data: Vec<_> = samples
+            .into_iter()
+            .flat_map(|data| {
+                sizes.push(data.len());
+                data
+            })
+            .collect();
+
+        Ok(zstd::dict::from_continuous(&data, &sizes, max_dict_size)?)
+    }
+
+    /// If using dictionaries, creates a list of [`EncoderDictionary`] to compress each column
+    /// with, using the configured level.
+    pub fn encoder_dictionaries(&self) -> Result<Option<EncoderDictionaries>, NippyJarError> {
+        match self.state {
+            ZstdState::PendingDictionary => Err(NippyJarError::CompressorNotReady),
+            ZstdState::Ready => {
+                Ok(self.dictionaries.as_ref().filter(|_| self.use_dict).map(|dictionaries| {
+                    EncoderDictionaries(
+                        dictionaries
+                            .iter()
+                            .map(|dict| EncoderDictionary::copy(dict.raw(), self.level))
+                            .collect(),
+                    )
+                }))
+            }
+        }
+    }
+
+    /// Appends `src` compressed with a prepared dictionary to `dest`.
+    ///
+    /// Returns number of bytes written to `dest`.
+    pub fn compress_with_prepared_dictionary(
+        src: &[u8],
+        dest: &mut Vec<u8>,
+        dictionary: &EncoderDictionary<'_>,
+    ) -> Result<usize, NippyJarError> {
+        let before = dest.len();
+
+        let mut encoder = zstd::Encoder::with_prepared_dictionary(dest, dictionary)?;
+        encoder.write_all(src)?;
+
+        let dest = encoder.finish()?;
+
+        Ok(dest.len() - before)
+    }
+
     /// Creates a list of [`Decompressor`] if using dictionaries.
     pub fn decompressors(&self) -> Result<Vec<Decompressor<'_>>, NippyJarError> {
         if let Some(dictionaries) = &self.dictionaries {
@@ -211,18 +287,7 @@ impl Compression for Zstd {
         // TODO: parallel calculation
         let mut dictionaries = vec![];
         for column in columns {
-            // ZSTD requires all training data to be continuous in memory, alongside the size of
-            // each entry
-            let mut sizes = vec![];
-            let data: Vec<_> = column
-                .into_iter()
-                .flat_map(|data| {
-                    sizes.push(data.len());
-                    data
-                })
-                .collect();
-
-            dictionaries.push(zstd::dict::from_continuous(&data, &sizes, self.max_dict_size)?);
+            dictionaries.push(Self::train_dictionary(column, self.max_dict_size)?);
         }
 
         debug_assert_eq!(dictionaries.len(), self.columns);
@@ -280,11 +345,7 @@ impl<'a> ZstdDictionaries<'a> {
 
     /// Loads a list [`RawDictionary`] into a list of [`ZstdDictionary::Loaded`].
     pub(crate) fn load(raw: Vec<RawDictionary>) -> Self {
-        Self(
-            raw.into_iter()
-                .map(|dict| ZstdDictionary::Loaded(DecoderDictionary::copy(&dict)))
-                .collect(),
-        )
+        Self(raw.into_iter().map(ZstdDictionary::load).collect())
     }
 
     /// Creates a list of decompressors from a list of [`ZstdDictionary::Loaded`].
@@ -299,32 +360,36 @@ impl<'a> ZstdDictionaries<'a> {
             .collect::<Result<Vec<_>, _>>()?)
     }
 
-    /// Creates a list of compressors from a list of [`ZstdDictionary::Raw`].
+    /// Creates a list of compressors from the raw dictionaries.
     pub(crate) fn compressors(&self) -> Result<Vec<Compressor<'_>>, NippyJarError> {
         Ok(self
             .iter()
-            .flat_map(|dict| {
-                dict.raw()
-                    .ok_or(NippyJarError::CompressorNotAllowed)
-                    .map(|dict| Compressor::with_dictionary(0, dict))
-            })
+            .map(|dict| Compressor::with_dictionary(0, dict.raw()))
             .collect::<Result<Vec<_>, _>>()?)
     }
 }
 
 /// A Zstd dictionary. It's created and serialized with [`ZstdDictionary::Raw`], and deserialized as
 /// [`ZstdDictionary::Loaded`].
+///
+/// [`ZstdDictionary::Loaded`] keeps the raw dictionary as well, so a jar opened from disk can still
+/// be appended to.
 pub(crate) enum ZstdDictionary<'a> {
     Raw(RawDictionary),
-    Loaded(DecoderDictionary<'a>),
+    Loaded(RawDictionary, DecoderDictionary<'a>),
 }
 
 impl<'a> ZstdDictionary<'a> {
-    /// Returns a reference to the expected `RawDictionary`
-    pub(crate) const fn raw(&self) -> Option<&RawDictionary> {
+    /// Creates a [`ZstdDictionary::Loaded`] from a [`RawDictionary`].
+    pub(crate) fn load(raw: RawDictionary) -> Self {
+        let loaded = DecoderDictionary::copy(&raw);
+        ZstdDictionary::Loaded(raw, loaded)
+    }
+
+    /// Returns a reference to the `RawDictionary`
+    pub(crate) const fn raw(&self) -> &RawDictionary {
         match self {
-            ZstdDictionary::Raw(dict) => Some(dict),
-            ZstdDictionary::Loaded(_) => None,
+            ZstdDictionary::Raw(dict) | ZstdDictionary::Loaded(dict, _) => dict,
         }
     }
 
@@ -332,7 +397,7 @@ impl<'a> ZstdDictionary<'a> {
     pub(crate) const fn loaded(&self) -> Option<&DecoderDictionary<'_>> {
         match self {
             ZstdDictionary::Raw(_) => None,
-            ZstdDictionary::Loaded(dict) => Some(dict),
+            ZstdDictionary::Loaded(_, dict) => Some(dict),
         }
     }
 }
@@ -343,7 +408,7 @@ impl<'de, 'a> Deserialize<'de> for ZstdDictionary<'a> {
         D: Deserializer<'de>,
     {
         let dict = RawDictionary::deserialize(deserializer)?;
-        Ok(Self::Loaded(DecoderDictionary::copy(&dict)))
+        Ok(Self::load(dict))
     }
 }
 
@@ -352,19 +417,23 @@ impl<'a> Serialize for ZstdDictionary<'a> {
     where
         S: Serializer,
     {
-        match self {
-            ZstdDictionary::Raw(r) => r.serialize(serializer),
-            ZstdDictionary::Loaded(_) => unreachable!(),
-        }
+        self.raw().serialize(serializer)
+    }
+}
+
+/// List of [`EncoderDictionary`], one per column.
+#[derive(Deref)]
+pub struct EncoderDictionaries(Vec<EncoderDictionary<'static>>);
+
+impl std::fmt::Debug for EncoderDictionaries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncoderDictionaries").field("num", &self.len()).finish_non_exhaustive()
     }
 }
 
 #[cfg(test)]
 impl<'a> PartialEq for ZstdDictionary<'a> {
     fn eq(&self, other: &Self) -> bool {
-        if let (Self::Raw(a), Self::Raw(b)) = (self, &other) {
-            return a == b
-        }
-        unimplemented!("`DecoderDictionary` can't be compared. So comparison should be done after decompressing a value.");
+        self.raw() == other.raw()
     }
 }
@@ -182,6 +182,14 @@ impl<H: NippyJarHeader> NippyJar<H> {
         self
     }
 
+    /// Adds [`compression::Zstd`] compression with already trained dictionaries, one per column.
+    pub fn with_zstd_dictionaries(mut self, dictionaries: Vec<Vec<u8>>) -> Self {
+        self.compressor = Some(Compressors::Zstd(
+            compression::Zstd::new(true, 0, self.columns).with_dictionaries(dictionaries),
+        ));
+        self
+    }
+
     /// Adds [`compression::Lz4`] compression.
     pub fn with_lz4(mut self) -> Self {
         self.compressor = Some(Compressors::Lz4(compression::Lz4::default()));
@@ -785,6 +793,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_zstd_with_trained_dictionaries_append() {
+        let (col1, col2) = test_data(None);
+        let num_columns = 2;
+        let file_path = tempfile::NamedTempFile::new().unwrap();
+
+        let dictionaries = vec![
+            compression::Zstd::train_dictionary(col1.clone(), 5000).unwrap(),
+            compression::Zstd::train_dictionary(col2.clone(), 5000).unwrap(),
+        ];
+
+        // Writes the first half of the rows with a fresh jar
+        let half = col1.len() / 2;
+        {
+            let nippy = NippyJar::new_without_header(num_columns, file_path.path())
+                .with_zstd_dictionaries(dictionaries.clone());
+            let mut writer = NippyJarWriter::new(nippy, ConsistencyFailStrategy::Heal).unwrap();
+            for (value1, value2) in col1.iter().zip(&col2).take(half) {
+                writer.append_column(Some(Ok(value1))).unwrap();
+                writer.append_column(Some(Ok(value2))).unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        // Appends the remaining rows to the jar loaded from disk, which has to keep the raw
+        // dictionaries around to be able to compress
+        {
+            let nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+            if let Some(Compressors::Zstd(zstd)) = nippy.compressor() {
+                assert_eq!(zstd.raw_dictionaries(), Some(dictionaries));
+            } else {
+                panic!("Expected Zstd compressor")
+            }
+
+            let mut writer = NippyJarWriter::new(nippy, ConsistencyFailStrategy::Heal).unwrap();
+            for (value1, value2) in col1.iter().zip(&col2).skip(half) {
+                writer.append_column(Some(Ok(value1))).unwrap();
+                writer.append_column(Some(Ok(value2))).unwrap();
+            }
+            writer.commit().unwrap();
+        }
+
+        let loaded_nippy = NippyJar::load_without_header(file_path.path()).unwrap();
+        assert_eq!(loaded_nippy.rows, col1.len());
+
+        let mut cursor = NippyJarCursor::new(&loaded_nippy).unwrap();
+        let mut row_index = 0usize;
+        while let Some(row) = cursor.next_row().unwrap() {
+            assert_eq!((row[0], row[1]), (col1[row_index].as_slice(), col2[row_index].as_slice()));
+            row_index += 1;
+        }
+        assert_eq!(row_index, col1.len());
+    }
+
     #[test]
     fn test_lz4() {
         let (col1, col2) = test_data(None);
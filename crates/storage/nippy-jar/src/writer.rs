@@ -1,4 +1,7 @@
-use crate::{compression::Compression, ColumnResult, NippyJar, NippyJarError, NippyJarHeader};
+use crate::{
+    compression::{Compression, Compressors, EncoderDictionaries, Zstd},
+    ColumnResult, NippyJar, NippyJarError, NippyJarHeader,
+};
 use std::{
     cmp::Ordering,
     fs::{File, OpenOptions},
@@ -33,6 +36,8 @@ pub struct NippyJarWriter<H: NippyJarHeader = ()> {
     offsets_file: BufWriter<File>,
     /// Temporary buffer to reuse when compressing data.
     tmp_buf: Vec<u8>,
+    /// Prepared per column dictionaries, if the jar is compressed with [`Zstd`] dictionaries.
+    encoder_dictionaries: Option<EncoderDictionaries>,
     /// Used to find the maximum uncompressed size of a row in a jar.
     uncompressed_row_size: usize,
     /// Partial offset list which hasn't been flushed to disk.
@@ -58,11 +63,17 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
         // Makes sure we don't have dangling data and offset files
         jar.freeze_config()?;
 
+        let encoder_dictionaries = match jar.compressor() {
+            Some(Compressors::Zstd(zstd)) => zstd.encoder_dictionaries()?,
+            _ => None,
+        };
+
         let mut writer = Self {
             jar,
             data_file: BufWriter::new(data_file),
             offsets_file: BufWriter::new(offsets_file),
             tmp_buf: Vec::with_capacity(1_000_000),
+            encoder_dictionaries,
             uncompressed_row_size: 0,
             offsets: Vec::with_capacity(1_000_000),
             column: 0,
@@ -315,7 +326,16 @@ impl<H: NippyJarHeader> NippyJarWriter<H> {
     /// Writes column to data file. If it's the last column of the row, call `finalize_row()`
     fn write_column(&mut self, value: &[u8]) -> Result<usize, NippyJarError> {
         self.uncompressed_row_size += value.len();
-        let len = if let Some(compression) = &self.jar.compressor {
+        let len = if let Some(dictionaries) = &self.encoder_dictionaries {
+            let before = self.tmp_buf.len();
+            let len = Zstd::compress_with_prepared_dictionary(
+                value,
+                &mut self.tmp_buf,
+                &dictionaries[self.column],
+            )?;
+            self.data_file.write_all(&self.tmp_buf[before..before + len])?;
+            len
+        } else if let Some(compression) = &self.jar.compressor {
             let before = self.tmp_buf.len();
             let len = compression.compress_to(value, &mut self.tmp_buf)?;
             self.data_file.write_all(&self.tmp_buf[before..before + len])?;
@@ -1,7 +1,7 @@
 use rayon::slice::ParallelSliceMut;
 use reth_db::tables;
 use reth_db_api::{
-    cursor::{DbCursorRO, DbDupCursorRO, DbDupCursorRW},
+    cursor::{DbCursorRO, DbCursorRW, DbDupCursorRO, DbDupCursorRW},
     models::{AccountBeforeTx, BlockNumberAddress},
     transaction::{DbTx, DbTxMut},
 };
@@ -23,6 +23,10 @@ impl From<PlainStateReverts> for StateReverts {
 impl StateReverts {
     /// Write reverts to database.
     ///
+    /// Changesets that already exist for a block were recorded by state overrides before the block
+    /// was executed, see `DatabaseProvider::write_state_override`. They hold the state before the
+    /// override and are kept over the reverts of the block.
+    ///
     /// `Note::` Reverts will delete all wiped storage from plain state.
     pub fn write_to_db<TX: DbTxMut + DbTx>(
         self,
@@ -63,9 +67,22 @@ impl StateReverts {
                     }
                 }
 
+                let mut overridden = Vec::new();
+                if let Some((_, entry)) = storage_changeset_cursor.seek_exact(storage_id)? {
+                    overridden.push(entry.key);
+                    while let Some(entry) = storage_changeset_cursor.next_dup_val()? {
+                        overridden.push(entry.key)
+                    }
+                }
+
                 tracing::trace!(target: "provider::reverts", ?address, ?storage, "Writing storage reverts");
                 for (key, value) in StorageRevertsIter::new(storage, wiped_storage) {
-                    storage_changeset_cursor.append_dup(storage_id, StorageEntry { key, value })?;
+                    if overridden.is_empty() {
+                        storage_changeset_cursor
+                            .append_dup(storage_id, StorageEntry { key, value })?;
+                    } else if !overridden.contains(&key) {
+                        storage_changeset_cursor.upsert(storage_id, StorageEntry { key, value })?;
+                    }
                 }
             }
         }
@@ -79,11 +96,18 @@ impl StateReverts {
             // Sort accounts by address.
             account_block_reverts.par_sort_by_key(|a| a.0);
 
+            let has_overrides = account_changeset_cursor.seek_exact(block_number)?.is_some();
             for (address, info) in account_block_reverts {
-                account_changeset_cursor.append_dup(
-                    block_number,
-                    AccountBeforeTx { address, info: info.map(Into::into) },
-                )?;
+                let changeset = AccountBeforeTx { address, info: info.map(Into::into) };
+                if !has_overrides {
+                    account_changeset_cursor.append_dup(block_number, changeset)?;
+                } else if account_changeset_cursor
+                    .seek_by_key_subkey(block_number, address)?
+                    .filter(|entry| entry.address == address)
+                    .is_none()
+                {
+                    account_changeset_cursor.upsert(block_number, changeset)?;
+                }
             }
         }
 
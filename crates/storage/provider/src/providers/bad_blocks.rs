@@ -0,0 +1,87 @@
+use parking_lot::RwLock;
+use reth_primitives::SealedBlock;
+use std::{collections::VecDeque, sync::Arc};
+
+/// The default number of bad blocks to keep track of.
+pub const DEFAULT_BAD_BLOCKS_LIMIT: usize = 10;
+
+/// Keeps track of the most recent blocks that failed validation.
+///
+/// Clones share the same underlying storage, so blocks recorded by the consensus engine can be
+/// served over RPC.
+#[derive(Debug, Clone)]
+pub struct BadBlocks {
+    inner: Arc<RwLock<VecDeque<Arc<SealedBlock>>>>,
+    limit: usize,
+}
+
+impl BadBlocks {
+    /// Creates a new store that keeps at most `limit` blocks.
+    pub fn new(limit: usize) -> Self {
+        Self { inner: Arc::new(RwLock::new(VecDeque::with_capacity(limit))), limit }
+    }
+
+    /// Records a block that failed validation, evicting the oldest one if the limit is reached.
+    ///
+    /// Blocks that are already recorded are ignored.
+    pub fn insert(&self, block: SealedBlock) {
+        let mut blocks = self.inner.write();
+        if blocks.iter().any(|existing| existing.hash() == block.hash()) {
+            return
+        }
+        blocks.push_front(Arc::new(block));
+        blocks.truncate(self.limit);
+    }
+
+    /// Returns all recorded blocks, most recent first.
+    pub fn blocks(&self) -> Vec<Arc<SealedBlock>> {
+        self.inner.read().iter().cloned().collect()
+    }
+
+    /// Returns the number of recorded blocks.
+    pub fn len(&self) -> usize {
+        self.inner.read().len()
+    }
+
+    /// Returns `true` if no blocks are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.inner.read().is_empty()
+    }
+}
+
+impl Default for BadBlocks {
+    fn default() -> Self {
+        Self::new(DEFAULT_BAD_BLOCKS_LIMIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth_primitives::Header;
+
+    fn block(number: u64) -> SealedBlock {
+        SealedBlock {
+            header: Header { number, ..Default::default() }.seal_slow(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn insert_evicts_oldest() {
+        let bad_blocks = BadBlocks::new(2);
+        bad_blocks.insert(block(1));
+        bad_blocks.insert(block(1));
+        assert_eq!(bad_blocks.len(), 1);
+
+        bad_blocks.insert(block(2));
+        bad_blocks.insert(block(3));
+        let numbers = bad_blocks.blocks().iter().map(|block| block.number).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![3, 2]);
+
+        // clones share the same storage
+        let shared = bad_blocks.clone();
+        shared.insert(block(4));
+        assert_eq!(bad_blocks.blocks()[0].number, 4);
+    }
+}
@@ -4,8 +4,9 @@ use crate::{
     traits::{BlockSource, ReceiptProvider},
     BlockHashReader, BlockNumReader, BlockReader, ChainSpecProvider, DatabaseProviderFactory,
     EvmEnvProvider, HeaderProvider, HeaderSyncGap, HeaderSyncGapProvider, ProviderError,
-    PruneCheckpointReader, RequestsProvider, StageCheckpointReader, StateProviderBox,
-    StaticFileProviderFactory, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
+    PruneCheckpointReader, RequestsProvider, StageCheckpointReader, StateOverrideWriter,
+    StateProviderBox, StaticFileProviderFactory, TransactionVariant, TransactionsProvider,
+    WithdrawalsProvider,
 };
 use reth_chainspec::{ChainInfo, ChainSpec};
 use reth_db::{init_db, mdbx::DatabaseArguments, DatabaseEnv};
@@ -13,12 +14,12 @@ use reth_db_api::{database::Database, models::StoredBlockBodyIndices};
 use reth_errors::{RethError, RethResult};
 use reth_evm::ConfigureEvmEnv;
 use reth_primitives::{
-    Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Header, Receipt,
-    SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment, TransactionMeta,
-    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256,
-    U256,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Bytecode,
+    Header, Receipt, SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment,
+    StorageEntry, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
+    Withdrawal, Withdrawals, B256, U256,
 };
-use reth_prune_types::{PruneCheckpoint, PruneSegment};
+use reth_prune_types::{PruneCheckpoint, PruneModes, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
 use reth_storage_errors::provider::ProviderResult;
 use revm::primitives::{BlockEnv, CfgEnvWithHandlerCfg};
@@ -46,6 +47,8 @@ pub struct ProviderFactory<DB> {
     chain_spec: Arc<ChainSpec>,
     /// Static File Provider
     static_file_provider: StaticFileProvider,
+    /// Pruning configuration
+    prune_modes: PruneModes,
 }
 
 impl<DB> ProviderFactory<DB> {
@@ -55,7 +58,14 @@ impl<DB> ProviderFactory<DB> {
         chain_spec: Arc<ChainSpec>,
         static_file_provider: StaticFileProvider,
     ) -> Self {
-        Self { db: Arc::new(db), chain_spec, static_file_provider }
+        Self { db: Arc::new(db), chain_spec, static_file_provider, prune_modes: PruneModes::none() }
+    }
+
+    /// Sets the pruning configuration, so that the state providers know which history was
+    /// retained by the pruner.
+    pub fn with_prune_modes(mut self, prune_modes: PruneModes) -> Self {
+        self.prune_modes = prune_modes;
+        self
     }
 
     /// Enables metrics on the static file provider.
@@ -89,6 +99,7 @@ impl ProviderFactory<DatabaseEnv> {
             db: Arc::new(init_db(path, args).map_err(RethError::msg)?),
             chain_spec,
             static_file_provider,
+            prune_modes: PruneModes::none(),
         })
     }
 }
@@ -103,6 +114,7 @@ impl<DB: Database> ProviderFactory<DB> {
             self.db.tx()?,
             self.chain_spec.clone(),
             self.static_file_provider.clone(),
+            self.prune_modes.clone(),
         ))
     }
 
@@ -116,6 +128,7 @@ impl<DB: Database> ProviderFactory<DB> {
             self.db.tx_mut()?,
             self.chain_spec.clone(),
             self.static_file_provider.clone(),
+            self.prune_modes.clone(),
         )))
     }
 
@@ -123,7 +136,12 @@ impl<DB: Database> ProviderFactory<DB> {
     #[track_caller]
     pub fn latest(&self) -> ProviderResult<StateProviderBox> {
         trace!(target: "providers::db", "Returning latest state provider");
-        Ok(Box::new(LatestStateProvider::new(self.db.tx()?, self.static_file_provider())))
+        let provider = self.provider()?;
+        let hashed_state = provider.snap_sync_pivot()?.is_some();
+        Ok(Box::new(
+            LatestStateProvider::new(provider.into_tx(), self.static_file_provider())
+                .with_hashed_state(hashed_state),
+        ))
     }
 
     /// Storage provider for state at that given block
@@ -156,6 +174,22 @@ impl<DB: Database> DatabaseProviderFactory<DB> for ProviderFactory<DB> {
     }
 }
 
+impl<DB: Database> StateOverrideWriter for ProviderFactory<DB> {
+    fn override_state(
+        &self,
+        address: Address,
+        account: Account,
+        bytecode: Option<Bytecode>,
+        storage: Vec<StorageEntry>,
+    ) -> ProviderResult<BlockNumber> {
+        let provider = self.provider_rw()?;
+        let block = provider.last_block_number()? + 1;
+        provider.write_state_override(block, address, account, bytecode, storage)?;
+        provider.commit()?;
+        Ok(block)
+    }
+}
+
 impl<DB> StaticFileProviderFactory for ProviderFactory<DB> {
     /// Returns static file provider
     fn static_file_provider(&self) -> StaticFileProvider {
@@ -584,6 +618,7 @@ impl<DB> Clone for ProviderFactory<DB> {
             db: Arc::clone(&self.db),
             chain_spec: self.chain_spec.clone(),
             static_file_provider: self.static_file_provider.clone(),
+            prune_modes: self.prune_modes.clone(),
         }
     }
 }
@@ -593,7 +628,8 @@ mod tests {
     use crate::{
         providers::{StaticFileProvider, StaticFileWriter},
         test_utils::create_test_provider_factory,
-        BlockHashReader, BlockNumReader, BlockWriter, HeaderSyncGapProvider, TransactionsProvider,
+        AccountReader, BlockHashReader, BlockNumReader, BlockWriter, ChangeSetReader,
+        HeaderSyncGapProvider, StateProvider, StateReverts, TransactionsProvider,
     };
     use alloy_rlp::Decodable;
     use assert_matches::assert_matches;
@@ -604,13 +640,22 @@ mod tests {
         tables,
         test_utils::{create_test_static_files_dir, ERROR_TEMPDIR},
     };
-    use reth_primitives::{hex_literal::hex, SealedBlock, StaticFileSegment, TxNumber, B256, U256};
+    use reth_db_api::{
+        cursor::DbDupCursorRO,
+        models::{AccountBeforeTx, BlockNumberAddress},
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_primitives::{
+        hex_literal::hex, keccak256, Account, Address, Bytecode, Bytes, SealedBlock,
+        StaticFileSegment, StorageEntry, TxNumber, B256, U256,
+    };
     use reth_prune_types::{PruneMode, PruneModes};
     use reth_storage_errors::provider::ProviderError;
     use reth_testing_utils::{
         generators,
         generators::{random_block, random_header},
     };
+    use revm::db::states::{PlainStateReverts, PlainStorageRevert, RevertToSlot};
     use std::{ops::RangeInclusive, sync::Arc};
     use tokio::sync::watch;
 
@@ -770,4 +815,165 @@ mod tests {
         assert_eq!(gap.local_head, head);
         assert_eq!(gap.target.tip(), consensus_tip.into());
     }
+    #[test]
+    fn snap_synced_state_providers() {
+        let factory = create_test_provider_factory();
+
+        let address = Address::random();
+        let slot = B256::random();
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        let provider = factory.provider_rw().unwrap();
+        provider.tx_ref().put::<tables::HashedAccounts>(keccak256(address), account).unwrap();
+        provider
+            .tx_ref()
+            .put::<tables::HashedStorages>(
+                keccak256(address),
+                StorageEntry { key: keccak256(slot), value: U256::from(3) },
+            )
+            .unwrap();
+        provider.save_snap_sync_pivot(5).unwrap();
+        provider.commit().unwrap();
+
+        // the plain state is empty, the state is read from the hashed state tables
+        let latest = factory.latest().unwrap();
+        assert_eq!(latest.basic_account(address).unwrap(), Some(account));
+        assert_eq!(latest.storage(address, slot).unwrap(), Some(U256::from(3)));
+
+        let historical = factory.provider().unwrap().state_provider_by_block_number(5).unwrap();
+        assert_eq!(historical.basic_account(address).unwrap(), Some(account));
+        assert_eq!(historical.storage(address, slot).unwrap(), Some(U256::from(3)));
+
+        // there is no state before the pivot
+        assert_matches!(
+            factory.provider().unwrap().state_provider_by_block_number(4).err(),
+            Some(ProviderError::StateAtBlockPruned(4))
+        );
+    }
+
+    #[test]
+    fn write_state_override() {
+        let factory = create_test_provider_factory();
+        let provider = factory.provider_rw().unwrap();
+
+        let address = Address::random();
+        let (changed_slot, new_slot) = (B256::with_last_byte(1), B256::with_last_byte(2));
+        let account = Account { nonce: 1, balance: U256::from(2), bytecode_hash: None };
+        provider.tx_ref().put::<tables::PlainAccountState>(address, account).unwrap();
+        provider
+            .tx_ref()
+            .put::<tables::PlainStorageState>(
+                address,
+                StorageEntry { key: changed_slot, value: U256::from(3) },
+            )
+            .unwrap();
+
+        let bytecode = Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00]));
+        let overridden = Account { bytecode_hash: Some(bytecode.hash_slow()), ..account };
+        provider
+            .write_state_override(
+                1,
+                address,
+                overridden,
+                Some(bytecode.clone()),
+                vec![
+                    StorageEntry { key: changed_slot, value: U256::ZERO },
+                    StorageEntry { key: new_slot, value: U256::from(4) },
+                ],
+            )
+            .unwrap();
+
+        // the plain and hashed state contain the override
+        let tx = provider.tx_ref();
+        assert_eq!(tx.get::<tables::PlainAccountState>(address).unwrap(), Some(overridden));
+        assert_eq!(tx.get::<tables::Bytecodes>(bytecode.hash_slow()).unwrap(), Some(bytecode));
+        assert_eq!(
+            tx.cursor_dup_read::<tables::PlainStorageState>()
+                .unwrap()
+                .walk_dup(Some(address), None)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![(address, StorageEntry { key: new_slot, value: U256::from(4) })]
+        );
+        assert_eq!(tx.get::<tables::HashedAccounts>(keccak256(address)).unwrap(), Some(overridden));
+        assert_eq!(
+            tx.cursor_dup_read::<tables::HashedStorages>()
+                .unwrap()
+                .walk_dup(Some(keccak256(address)), None)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            vec![(
+                keccak256(address),
+                StorageEntry { key: keccak256(new_slot), value: U256::from(4) }
+            )]
+        );
+
+        // overriding again in the same block keeps the state before the first override
+        provider
+            .write_state_override(
+                1,
+                address,
+                Account { nonce: 5, ..overridden },
+                None,
+                vec![StorageEntry { key: changed_slot, value: U256::from(6) }],
+            )
+            .unwrap();
+
+        // the previous state is recorded in the changesets of the block, the history indices are
+        // written with the block
+        assert_eq!(
+            provider.account_block_changeset(1).unwrap(),
+            vec![AccountBeforeTx { address, info: Some(account) }]
+        );
+        let storage_changeset = || {
+            tx.cursor_dup_read::<tables::StorageChangeSets>()
+                .unwrap()
+                .walk_dup(Some(BlockNumberAddress((1, address))), None)
+                .unwrap()
+                .map(|entry| entry.map(|(_, entry)| entry))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        };
+        assert_eq!(
+            storage_changeset(),
+            vec![
+                StorageEntry { key: changed_slot, value: U256::from(3) },
+                StorageEntry { key: new_slot, value: U256::ZERO },
+            ]
+        );
+        assert_eq!(tx.entries::<tables::AccountsHistory>().unwrap(), 0);
+        assert_eq!(tx.entries::<tables::StoragesHistory>().unwrap(), 0);
+
+        // the reverts of the block keep the state before the override
+        let (first, other_slot) = (Address::ZERO, B256::with_last_byte(3));
+        StateReverts(PlainStateReverts {
+            accounts: vec![vec![(address, Some(overridden.into())), (first, None)]],
+            storage: vec![vec![PlainStorageRevert {
+                address,
+                wiped: false,
+                storage_revert: vec![
+                    (U256::from(1), RevertToSlot::Some(U256::from(6))),
+                    (U256::from(3), RevertToSlot::Some(U256::from(7))),
+                ],
+            }]],
+        })
+        .write_to_db(tx, 1)
+        .unwrap();
+        assert_eq!(
+            provider.account_block_changeset(1).unwrap(),
+            vec![
+                AccountBeforeTx { address: first, info: None },
+                AccountBeforeTx { address, info: Some(account) }
+            ]
+        );
+        assert_eq!(
+            storage_changeset(),
+            vec![
+                StorageEntry { key: changed_slot, value: U256::from(3) },
+                StorageEntry { key: new_slot, value: U256::ZERO },
+                StorageEntry { key: other_slot, value: U256::from(7) },
+            ]
+        );
+    }
 }
@@ -3,7 +3,8 @@ use crate::{
     providers::{database::metrics, static_file::StaticFileWriter, StaticFileProvider},
     to_range,
     traits::{
-        AccountExtReader, BlockSource, ChangeSetReader, ReceiptProvider, StageCheckpointWriter,
+        AccountExtReader, AccountHistoryReader, BlockSource, ChangeSetReader, ReceiptProvider,
+        StageCheckpointWriter,
     },
     AccountReader, BlockExecutionWriter, BlockHashReader, BlockNumReader, BlockReader, BlockWriter,
     EvmEnvProvider, FinalizedBlockReader, FinalizedBlockWriter, HashingWriter, HeaderProvider,
@@ -34,7 +35,7 @@ use reth_network_p2p::headers::downloader::SyncTarget;
 use reth_primitives::{
     keccak256,
     revm::{config::revm_spec, env::fill_block_env},
-    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders,
+    Account, Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Bytecode,
     GotExpected, Head, Header, Receipt, Requests, SealedBlock, SealedBlockWithSenders,
     SealedHeader, StaticFileSegment, StorageEntry, TransactionMeta, TransactionSigned,
     TransactionSignedEcRecovered, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal,
@@ -46,7 +47,7 @@ use reth_storage_errors::provider::{ProviderResult, RootMismatch};
 use reth_trie::{
     prefix_set::{PrefixSet, PrefixSetMut, TriePrefixSets},
     updates::TrieUpdates,
-    HashedPostState, Nibbles, StateRoot,
+    HashedPostState, HashedStorage, Nibbles, StateRoot,
 };
 use revm::primitives::{BlockEnv, CfgEnvWithHandlerCfg, SpecId};
 use std::{
@@ -106,6 +107,8 @@ pub struct DatabaseProvider<TX> {
     chain_spec: Arc<ChainSpec>,
     /// Static File provider
     static_file_provider: StaticFileProvider,
+    /// Pruning configuration
+    prune_modes: PruneModes,
 }
 
 impl<TX> DatabaseProvider<TX> {
@@ -121,8 +124,31 @@ impl<TX: DbTxMut> DatabaseProvider<TX> {
         tx: TX,
         chain_spec: Arc<ChainSpec>,
         static_file_provider: StaticFileProvider,
+        prune_modes: PruneModes,
     ) -> Self {
-        Self { tx, chain_spec, static_file_provider }
+        Self { tx, chain_spec, static_file_provider, prune_modes }
+    }
+}
+
+impl<TX: DbTx> DatabaseProvider<TX> {
+    /// Returns the block the state was snap synced to, if any.
+    ///
+    /// The plain state of a snap synced node only contains the accounts and storage slots that
+    /// changed after the pivot, so the state has to be read from the hashed state tables. No
+    /// history is available before the pivot.
+    pub fn snap_sync_pivot(&self) -> ProviderResult<Option<BlockNumber>> {
+        Ok(self
+            .get_stage_checkpoint_progress(StageId::SnapSync)?
+            .and_then(|progress| progress.try_into().ok())
+            .map(BlockNumber::from_be_bytes))
+    }
+}
+
+impl<TX: DbTxMut> DatabaseProvider<TX> {
+    /// Marks the state as snap synced to the given pivot, see
+    /// [`DatabaseProvider::snap_sync_pivot`].
+    pub fn save_snap_sync_pivot(&self, pivot: BlockNumber) -> ProviderResult<()> {
+        self.save_stage_checkpoint_progress(StageId::SnapSync, pivot.to_be_bytes().to_vec())
     }
 }
 
@@ -132,10 +158,20 @@ impl<TX: DbTx + 'static> DatabaseProvider<TX> {
         self,
         mut block_number: BlockNumber,
     ) -> ProviderResult<StateProviderBox> {
+        let snap_sync_pivot = self.snap_sync_pivot()?;
+
         if block_number == self.best_block_number().unwrap_or_default() &&
             block_number == self.last_block_number().unwrap_or_default()
         {
-            return Ok(Box::new(LatestStateProvider::new(self.tx, self.static_file_provider)))
+            return Ok(Box::new(
+                LatestStateProvider::new(self.tx, self.static_file_provider)
+                    .with_hashed_state(snap_sync_pivot.is_some()),
+            ))
+        }
+
+        // The state before the snap sync pivot was never downloaded.
+        if snap_sync_pivot.is_some_and(|pivot| block_number < pivot) {
+            return Err(ProviderError::StateAtBlockPruned(block_number))
         }
 
         // +1 as the changeset that we want is the one that was applied after this block.
@@ -147,23 +183,35 @@ impl<TX: DbTx + 'static> DatabaseProvider<TX> {
             self.get_prune_checkpoint(PruneSegment::StorageHistory)?;
 
         let mut state_provider =
-            HistoricalStateProvider::new(self.tx, block_number, self.static_file_provider);
+            HistoricalStateProvider::new(self.tx, block_number, self.static_file_provider)
+                .with_hashed_state(snap_sync_pivot.is_some());
+
+        // The history of the retained addresses doesn't reach below the snap sync pivot, so the
+        // retention configuration only applies to fully synced nodes.
+        if snap_sync_pivot.is_none() {
+            state_provider = state_provider
+                .with_account_history_retention(self.prune_modes.account_history_retention)
+                .with_storage_history_retention(self.prune_modes.storage_history_retention);
+        }
 
         // If we pruned account or storage history, we can't return state on every historical block.
         // Instead, we should cap it at the latest prune checkpoint for corresponding prune segment.
-        if let Some(prune_checkpoint_block_number) =
-            account_history_prune_checkpoint.and_then(|checkpoint| checkpoint.block_number)
+        // Snap synced nodes don't have any history before the pivot.
+        let lowest_available_block_number = |checkpoint: Option<PruneCheckpoint>| {
+            checkpoint
+                .and_then(|checkpoint| checkpoint.block_number)
+                .max(snap_sync_pivot)
+                .map(|block_number| block_number + 1)
+        };
+        if let Some(block_number) = lowest_available_block_number(account_history_prune_checkpoint)
         {
-            state_provider = state_provider.with_lowest_available_account_history_block_number(
-                prune_checkpoint_block_number + 1,
-            );
+            state_provider =
+                state_provider.with_lowest_available_account_history_block_number(block_number);
         }
-        if let Some(prune_checkpoint_block_number) =
-            storage_history_prune_checkpoint.and_then(|checkpoint| checkpoint.block_number)
+        if let Some(block_number) = lowest_available_block_number(storage_history_prune_checkpoint)
         {
-            state_provider = state_provider.with_lowest_available_storage_history_block_number(
-                prune_checkpoint_block_number + 1,
-            );
+            state_provider =
+                state_provider.with_lowest_available_storage_history_block_number(block_number);
         }
 
         Ok(Box::new(state_provider))
@@ -258,8 +306,9 @@ impl<TX: DbTx> DatabaseProvider<TX> {
         tx: TX,
         chain_spec: Arc<ChainSpec>,
         static_file_provider: StaticFileProvider,
+        prune_modes: PruneModes,
     ) -> Self {
-        Self { tx, chain_spec, static_file_provider }
+        Self { tx, chain_spec, static_file_provider, prune_modes }
     }
 
     /// Consume `DbTx` or `DbTxMut`.
@@ -304,6 +353,95 @@ impl<TX: DbTx> DatabaseProvider<TX> {
             .collect::<Result<Vec<_>, DatabaseError>>()
     }
 
+    /// Splits a range of blocks or transactions at the end of the static files of the segment.
+    ///
+    /// Returns the part of the range that was moved to static files, and the part that's still in
+    /// the database.
+    fn split_static_file_range(
+        &self,
+        segment: StaticFileSegment,
+        range: impl RangeBounds<u64>,
+    ) -> (Range<u64>, Range<u64>) {
+        let range = to_range(range);
+        let highest = if segment.is_block_based() {
+            self.static_file_provider.get_highest_static_file_block(segment)
+        } else {
+            self.static_file_provider.get_highest_static_file_tx(segment)
+        };
+        let split =
+            highest.map_or(range.start, |highest| (highest + 1).min(range.end).max(range.start));
+        (range.start..split, split..range.end)
+    }
+
+    /// Returns the account changesets of the block range, reading the blocks that were moved to
+    /// static files from the [`StaticFileSegment::AccountChangeSets`] segment.
+    pub fn account_changesets_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+        let (static_file_range, db_range) =
+            self.split_static_file_range(StaticFileSegment::AccountChangeSets, range);
+        let mut changesets = if static_file_range.is_empty() {
+            Vec::new()
+        } else {
+            self.static_file_provider.account_changesets_range(static_file_range)?
+        };
+        if !db_range.is_empty() {
+            for entry in self.tx.cursor_read::<tables::AccountChangeSets>()?.walk_range(db_range)? {
+                changesets.push(entry?);
+            }
+        }
+        Ok(changesets)
+    }
+
+    /// Returns the storage changesets of the block range, reading the blocks that were moved to
+    /// static files from the [`StaticFileSegment::StorageChangeSets`] segment.
+    pub fn storage_changesets_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+        let (static_file_range, db_range) =
+            self.split_static_file_range(StaticFileSegment::StorageChangeSets, range);
+        let mut changesets = if static_file_range.is_empty() {
+            Vec::new()
+        } else {
+            self.static_file_provider.storage_changesets_range(static_file_range)?
+        };
+        if !db_range.is_empty() {
+            let db_range = BlockNumberAddress((db_range.start, Address::ZERO))..
+                BlockNumberAddress((db_range.end, Address::ZERO));
+            for entry in self.tx.cursor_read::<tables::StorageChangeSets>()?.walk_range(db_range)? {
+                changesets.push(entry?);
+            }
+        }
+        Ok(changesets)
+    }
+
+    /// Returns the senders stored for the transaction range, reading the transactions that were
+    /// moved to static files from the [`StaticFileSegment::TransactionSenders`] segment.
+    ///
+    /// Senders that were pruned are missing from the result.
+    pub fn transaction_senders_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<(TxNumber, Address)>> {
+        let (static_file_range, db_range) =
+            self.split_static_file_range(StaticFileSegment::TransactionSenders, range);
+        let mut senders = if static_file_range.is_empty() {
+            Vec::new()
+        } else {
+            self.static_file_provider.transaction_senders_range(static_file_range)?
+        };
+        if !db_range.is_empty() {
+            for entry in
+                self.tx.cursor_read::<tables::TransactionSenders>()?.walk_range(db_range)?
+            {
+                senders.push(entry?);
+            }
+        }
+        Ok(senders)
+    }
+
     /// Iterates over read only values in the given table and collects them into a vector.
     ///
     /// Early-returns if the range is empty, without opening a cursor transaction.
@@ -479,7 +617,6 @@ impl<TX: DbTx> DatabaseProvider<TX> {
         ) -> ProviderResult<B>,
     {
         let mut tx_cursor = self.tx.cursor_read::<tables::Transactions>()?;
-        let mut senders_cursor = self.tx.cursor_read::<tables::TransactionSenders>()?;
 
         self.block_range(range, headers_range, |header, tx_range, ommers, withdrawals, requests| {
             let (body, senders) = if tx_range.is_empty() {
@@ -491,10 +628,10 @@ impl<TX: DbTx> DatabaseProvider<TX> {
                     .map(Into::into)
                     .collect::<Vec<TransactionSigned>>();
                 // fetch senders from the senders table
-                let known_senders =
-                    senders_cursor
-                        .walk_range(tx_range.clone())?
-                        .collect::<Result<HashMap<_, _>, _>>()?;
+                let known_senders = self
+                    .transaction_senders_range(tx_range.clone())?
+                    .into_iter()
+                    .collect::<HashMap<_, _>>();
 
                 let mut senders = Vec::with_capacity(body.len());
                 for (tx_num, tx) in tx_range.zip(body.iter()) {
@@ -516,6 +653,88 @@ impl<TX: DbTx> DatabaseProvider<TX> {
             assemble_block(header, body, ommers, withdrawals, requests, senders)
         })
     }
+
+    /// Returns the addresses that sent, received or were created by the transactions in the given
+    /// range, mapped to the numbers of these transactions in ascending order.
+    ///
+    /// Senders that are missing from [`tables::TransactionSenders`] (e.g. because they were pruned)
+    /// are recovered from the transaction signatures.
+    pub fn address_transactions_by_tx_range(
+        &self,
+        tx_range: Range<TxNumber>,
+    ) -> ProviderResult<BTreeMap<Address, Vec<TxNumber>>> {
+        let transactions = self.transactions_by_tx_range(tx_range.clone())?;
+        let known_senders = self
+            .transaction_senders_range(tx_range.clone())?
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+
+        let mut addresses = BTreeMap::<Address, Vec<TxNumber>>::new();
+        let mut rlp_buf = Vec::with_capacity(128);
+        for (tx_num, transaction) in tx_range.zip(transactions) {
+            let sender = match known_senders.get(&tx_num) {
+                Some(sender) => *sender,
+                None => transaction
+                    .encode_and_recover_unchecked(&mut rlp_buf)
+                    .ok_or(ProviderError::SenderRecoveryError)?,
+            };
+            addresses.entry(sender).or_default().push(tx_num);
+
+            let to = transaction.to().unwrap_or_else(|| sender.create(transaction.nonce()));
+            if to != sender {
+                addresses.entry(to).or_default().push(tx_num);
+            }
+        }
+
+        Ok(addresses)
+    }
+
+    /// Returns the range of numbers of the transactions included in the given block range.
+    fn tx_num_range_by_block_range(
+        &self,
+        block_range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Range<TxNumber>> {
+        let from = self
+            .block_body_indices(*block_range.start())?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(*block_range.start()))?
+            .first_tx_num();
+        let to = self
+            .block_body_indices(*block_range.end())?
+            .ok_or(ProviderError::BlockBodyIndicesNotFound(*block_range.end()))?
+            .next_tx_num();
+        Ok(from..to)
+    }
+
+    /// Returns all indices within the given range from the shards of the address in table `T`,
+    /// in ascending order.
+    fn address_sharded_indices<T>(
+        &self,
+        address: Address,
+        range: RangeInclusive<u64>,
+    ) -> ProviderResult<Vec<u64>>
+    where
+        T: Table<Key = ShardedKey<Address>, Value = BlockNumberList>,
+    {
+        let mut cursor = self.tx.cursor_read::<T>()?;
+        let mut indices = Vec::new();
+
+        // Shards are keyed by the highest index they contain, so the first shard that may contain
+        // indices from the range is the one with the key at or after the start of the range.
+        for entry in cursor.walk(Some(ShardedKey::new(address, *range.start())))? {
+            let (key, list) = entry?;
+            if key.key != address {
+                break
+            }
+
+            indices.extend(list.iter().filter(|index| range.contains(index)));
+
+            if key.highest_block_number >= *range.end() {
+                break
+            }
+        }
+
+        Ok(indices)
+    }
 }
 
 impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
@@ -524,6 +743,81 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
         Ok(self.tx.commit()?)
     }
 
+    /// Overrides the state of an account and the given storage slots on top of the tip of the
+    /// chain.
+    ///
+    /// The previous values are recorded in the changesets of `block`, which must be the block after
+    /// the tip, so the override becomes part of the changes of the next block and unwinding that
+    /// block reverts it. The history indices are written together with the block. Hashed state and
+    /// intermediate trie nodes are updated as well, so the state root of the next block includes
+    /// the override.
+    ///
+    /// This is intended for development nodes only.
+    pub fn write_state_override(
+        &self,
+        block: BlockNumber,
+        address: Address,
+        account: Account,
+        bytecode: Option<Bytecode>,
+        storage: Vec<StorageEntry>,
+    ) -> ProviderResult<()> {
+        // Record the previous account state, unless the block already changed the account.
+        let mut account_changesets = self.tx.cursor_dup_write::<tables::AccountChangeSets>()?;
+        let mut plain_accounts = self.tx.cursor_write::<tables::PlainAccountState>()?;
+        let has_account_changeset = account_changesets
+            .seek_by_key_subkey(block, address)?
+            .is_some_and(|entry| entry.address == address);
+        if !has_account_changeset {
+            let info = plain_accounts.seek_exact(address)?.map(|(_, account)| account);
+            account_changesets.upsert(block, AccountBeforeTx { address, info })?;
+        }
+        plain_accounts.upsert(address, account)?;
+
+        if let Some(bytecode) = bytecode {
+            self.tx.put::<tables::Bytecodes>(bytecode.hash_slow(), bytecode)?;
+        }
+
+        // Record the previous storage values, unless the block already changed the slot.
+        let mut storage_changesets = self.tx.cursor_dup_write::<tables::StorageChangeSets>()?;
+        let mut plain_storage = self.tx.cursor_dup_write::<tables::PlainStorageState>()?;
+        let mut hashed_storage = HashedStorage::new(false);
+        for entry in storage {
+            let changeset_key = BlockNumberAddress((block, address));
+            let has_storage_changeset = storage_changesets
+                .seek_by_key_subkey(changeset_key, entry.key)?
+                .is_some_and(|previous| previous.key == entry.key);
+            let previous = plain_storage
+                .seek_by_key_subkey(address, entry.key)?
+                .filter(|previous| previous.key == entry.key);
+            if !has_storage_changeset {
+                let value = previous.map(|previous| previous.value).unwrap_or_default();
+                storage_changesets.upsert(changeset_key, StorageEntry { key: entry.key, value })?;
+            }
+
+            if previous.is_some() {
+                plain_storage.delete_current()?;
+            }
+            if entry.value != U256::ZERO {
+                plain_storage.upsert(address, entry)?;
+            }
+            hashed_storage.storage.insert(keccak256(entry.key), entry.value);
+        }
+
+        // Update the hashed state and the intermediate trie nodes, so the state root of the next
+        // block includes the override.
+        let hashed_address = keccak256(address);
+        let hashed_state = HashedPostState::default()
+            .with_accounts([(hashed_address, Some(account))])
+            .with_storages([(hashed_address, hashed_storage)]);
+        let (_, trie_updates) = hashed_state
+            .state_root_with_updates(&self.tx)
+            .map_err(Into::<reth_db::DatabaseError>::into)?;
+        HashedStateChanges(hashed_state).write_to_db(&self.tx)?;
+        trie_updates.flush(&self.tx)?;
+
+        Ok(())
+    }
+
     // TODO(joshie) TEMPORARY should be moved to trait providers
     /// Unwind or peek at last N blocks of state recreating the [`ExecutionOutcome`].
     ///
@@ -567,11 +861,8 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
         let to_transaction_num =
             block_bodies.last().expect("already checked if there are blocks").1.last_tx_num();
 
-        let storage_range = BlockNumberAddress::range(range.clone());
-
-        let storage_changeset =
-            self.get_or_take::<tables::StorageChangeSets, TAKE>(storage_range)?;
-        let account_changeset = self.get_or_take::<tables::AccountChangeSets, TAKE>(range)?;
+        let storage_changeset = self.get_or_take_storage_changesets::<TAKE>(range.clone())?;
+        let account_changeset = self.get_or_take_account_changesets::<TAKE>(range)?;
 
         // iterate previous value and get plain state value to create changeset
         // Double option around Account represent if Account state is know (first option) and
@@ -724,6 +1015,41 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
         }
     }
 
+    /// Returns the account changesets of the block range.
+    ///
+    /// If TAKE is true, the changesets are deleted from the database. The changesets of blocks that
+    /// were moved to static files are only read, and have to be pruned from the static files by the
+    /// caller.
+    pub fn get_or_take_account_changesets<const TAKE: bool>(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+        let (static_file_range, db_range) =
+            self.split_static_file_range(StaticFileSegment::AccountChangeSets, range);
+        let mut changesets = self.account_changesets_range(static_file_range)?;
+        changesets.extend(self.get_or_take::<tables::AccountChangeSets, TAKE>(db_range)?);
+        Ok(changesets)
+    }
+
+    /// Returns the storage changesets of the block range.
+    ///
+    /// If TAKE is true, the changesets are deleted from the database. The changesets of blocks that
+    /// were moved to static files are only read, and have to be pruned from the static files by the
+    /// caller.
+    pub fn get_or_take_storage_changesets<const TAKE: bool>(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+        let (static_file_range, db_range) =
+            self.split_static_file_range(StaticFileSegment::StorageChangeSets, range);
+        let mut changesets = self.storage_changesets_range(static_file_range)?;
+        changesets.extend(self.get_or_take::<tables::StorageChangeSets, TAKE>(
+            BlockNumberAddress((db_range.start, Address::ZERO))..
+                BlockNumberAddress((db_range.end, Address::ZERO)),
+        )?);
+        Ok(changesets)
+    }
+
     /// Get requested blocks transaction with signer
     pub(crate) fn get_take_block_transaction_range<const TAKE: bool>(
         &self,
@@ -752,9 +1078,12 @@ impl<TX: DbTxMut + DbTx> DatabaseProvider<TX> {
             .map(|(id, tx)| (id, tx.into()))
             .collect::<Vec<(u64, TransactionSigned)>>();
 
-        let mut senders = self.get_or_take::<tables::TransactionSenders, TAKE>(
+        let (static_file_range, db_range) = self.split_static_file_range(
+            StaticFileSegment::TransactionSenders,
             first_transaction..=last_transaction,
-        )?;
+        );
+        let mut senders = self.transaction_senders_range(static_file_range)?;
+        senders.extend(self.get_or_take::<tables::TransactionSenders, TAKE>(db_range)?);
 
         // Recover senders manually if not found in db
         // NOTE: Transactions are always guaranteed to be in the database whereas
@@ -1216,13 +1545,11 @@ impl<TX: DbTx> AccountExtReader for DatabaseProvider<TX> {
         &self,
         range: impl RangeBounds<BlockNumber>,
     ) -> ProviderResult<BTreeSet<Address>> {
-        self.tx
-            .cursor_read::<tables::AccountChangeSets>()?
-            .walk_range(range)?
-            .map(|entry| {
-                entry.map(|(_, account_before)| account_before.address).map_err(Into::into)
-            })
-            .collect()
+        Ok(self
+            .account_changesets_range(range)?
+            .into_iter()
+            .map(|(_, account_before)| account_before.address)
+            .collect())
     }
 
     fn basic_accounts(
@@ -1240,16 +1567,13 @@ impl<TX: DbTx> AccountExtReader for DatabaseProvider<TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<BTreeMap<Address, Vec<u64>>> {
-        let mut changeset_cursor = self.tx.cursor_read::<tables::AccountChangeSets>()?;
-
-        let account_transitions = changeset_cursor.walk_range(range)?.try_fold(
+        let account_transitions = self.account_changesets_range(range)?.into_iter().fold(
             BTreeMap::new(),
-            |mut accounts: BTreeMap<Address, Vec<u64>>, entry| -> ProviderResult<_> {
-                let (index, account) = entry?;
+            |mut accounts: BTreeMap<Address, Vec<u64>>, (index, account)| {
                 accounts.entry(account.address).or_default().push(index);
-                Ok(accounts)
+                accounts
             },
-        )?;
+        );
 
         Ok(account_transitions)
     }
@@ -1260,15 +1584,43 @@ impl<TX: DbTx> ChangeSetReader for DatabaseProvider<TX> {
         &self,
         block_number: BlockNumber,
     ) -> ProviderResult<Vec<AccountBeforeTx>> {
-        let range = block_number..=block_number;
-        self.tx
-            .cursor_read::<tables::AccountChangeSets>()?
-            .walk_range(range)?
-            .map(|result| -> ProviderResult<_> {
-                let (_, account_before) = result?;
-                Ok(account_before)
-            })
-            .collect()
+        self.static_file_provider
+            .get_with_static_file_or_database(
+                StaticFileSegment::AccountChangeSets,
+                block_number,
+                |static_file| static_file.account_block_changeset(block_number).map(Some),
+                || {
+                    let range = block_number..=block_number;
+                    self.tx
+                        .cursor_read::<tables::AccountChangeSets>()?
+                        .walk_range(range)?
+                        .map(|result| -> ProviderResult<_> {
+                            let (_, account_before) = result?;
+                            Ok(account_before)
+                        })
+                        .collect::<ProviderResult<_>>()
+                        .map(Some)
+                },
+            )
+            .map(Option::unwrap_or_default)
+    }
+}
+
+impl<TX: DbTx> AccountHistoryReader for DatabaseProvider<TX> {
+    fn account_history_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.address_sharded_indices::<tables::AccountsHistory>(address, range)
+    }
+
+    fn address_transactions(
+        &self,
+        address: Address,
+        range: RangeInclusive<TxNumber>,
+    ) -> ProviderResult<Vec<TxNumber>> {
+        self.address_sharded_indices::<tables::AddressTransactions>(address, range)
     }
 }
 
@@ -1866,11 +2218,30 @@ impl<TX: DbTx> TransactionsProvider for DatabaseProvider<TX> {
         &self,
         range: impl RangeBounds<TxNumber>,
     ) -> ProviderResult<Vec<Address>> {
-        self.cursor_read_collect::<tables::TransactionSenders>(range).map_err(Into::into)
+        self.static_file_provider.get_range_with_static_file_or_database(
+            StaticFileSegment::TransactionSenders,
+            to_range(range),
+            |static_file, range, _| {
+                Ok(static_file
+                    .transaction_senders_range(range)?
+                    .into_iter()
+                    .map(|(_, sender)| sender)
+                    .collect())
+            },
+            |range, _| self.cursor_read_collect::<tables::TransactionSenders>(range),
+            |_| true,
+        )
     }
 
     fn transaction_sender(&self, id: TxNumber) -> ProviderResult<Option<Address>> {
-        Ok(self.tx.get::<tables::TransactionSenders>(id)?)
+        self.static_file_provider.get_with_static_file_or_database(
+            StaticFileSegment::TransactionSenders,
+            id,
+            |static_file| {
+                Ok(static_file.transaction_senders_range(id..=id)?.pop().map(|(_, sender)| sender))
+            },
+            || Ok(self.tx.get::<tables::TransactionSenders>(id)?),
+        )
     }
 }
 
@@ -2151,36 +2522,32 @@ impl<TX: DbTx> StorageReader for DatabaseProvider<TX> {
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<BTreeMap<Address, BTreeSet<B256>>> {
-        self.tx
-            .cursor_read::<tables::StorageChangeSets>()?
-            .walk_range(BlockNumberAddress::range(range))?
+        Ok(self
+            .storage_changesets_range(range)?
+            .into_iter()
             // fold all storages and save its old state so we can remove it from HashedStorage
             // it is needed as it is dup table.
-            .try_fold(BTreeMap::new(), |mut accounts: BTreeMap<Address, BTreeSet<B256>>, entry| {
-                let (BlockNumberAddress((_, address)), storage_entry) = entry?;
+            .fold(BTreeMap::new(), |mut accounts: BTreeMap<Address, BTreeSet<B256>>, entry| {
+                let (BlockNumberAddress((_, address)), storage_entry) = entry;
                 accounts.entry(address).or_default().insert(storage_entry.key);
-                Ok(accounts)
-            })
+                accounts
+            }))
     }
 
     fn changed_storages_and_blocks_with_range(
         &self,
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<BTreeMap<(Address, B256), Vec<u64>>> {
-        let mut changeset_cursor = self.tx.cursor_read::<tables::StorageChangeSets>()?;
-
-        let storage_changeset_lists =
-            changeset_cursor.walk_range(BlockNumberAddress::range(range))?.try_fold(
-                BTreeMap::new(),
-                |mut storages: BTreeMap<(Address, B256), Vec<u64>>, entry| -> ProviderResult<_> {
-                    let (index, storage) = entry?;
-                    storages
-                        .entry((index.address(), storage.key))
-                        .or_default()
-                        .push(index.block_number());
-                    Ok(storages)
-                },
-            )?;
+        let storage_changeset_lists = self.storage_changesets_range(range)?.into_iter().fold(
+            BTreeMap::new(),
+            |mut storages: BTreeMap<(Address, B256), Vec<u64>>, (index, storage)| {
+                storages
+                    .entry((index.address(), storage.key))
+                    .or_default()
+                    .push(index.block_number());
+                storages
+            },
+        );
 
         Ok(storage_changeset_lists)
     }
@@ -2195,12 +2562,9 @@ impl<TX: DbTxMut + DbTx> HashingWriter for DatabaseProvider<TX> {
         // Note that collecting and then reversing the order is necessary to ensure that the
         // changes are applied in the correct order.
         let hashed_accounts = self
-            .tx
-            .cursor_read::<tables::AccountChangeSets>()?
-            .walk_range(range)?
-            .map(|entry| entry.map(|(_, e)| (keccak256(e.address), e.info)))
-            .collect::<Result<Vec<_>, _>>()?
+            .account_changesets_range(range)?
             .into_iter()
+            .map(|(_, e)| (keccak256(e.address), e.info))
             .rev()
             .collect::<BTreeMap<_, _>>();
 
@@ -2239,15 +2603,14 @@ impl<TX: DbTxMut + DbTx> HashingWriter for DatabaseProvider<TX> {
         range: Range<BlockNumberAddress>,
     ) -> ProviderResult<HashMap<B256, BTreeSet<B256>>> {
         // Aggregate all block changesets and make list of accounts that have been changed.
-        let mut changesets = self.tx.cursor_read::<tables::StorageChangeSets>()?;
-        let mut hashed_storages = changesets
-            .walk_range(range)?
-            .map(|entry| {
-                entry.map(|(BlockNumberAddress((_, address)), storage_entry)| {
-                    (keccak256(address), keccak256(storage_entry.key), storage_entry.value)
-                })
+        let mut hashed_storages = self
+            .storage_changesets_range(range.start.block_number()..=range.end.block_number())?
+            .into_iter()
+            .filter(|(key, _)| range.contains(key))
+            .map(|(BlockNumberAddress((_, address)), storage_entry)| {
+                (keccak256(address), keccak256(storage_entry.key), storage_entry.value)
             })
-            .collect::<Result<Vec<_>, _>>()?;
+            .collect::<Vec<_>>();
         hashed_storages.sort_by_key(|(ha, hk, _)| (*ha, *hk));
 
         // Apply values to HashedState, and remove the account if it's None.
@@ -2397,11 +2760,10 @@ impl<TX: DbTxMut + DbTx> HistoryWriter for DatabaseProvider<TX> {
         range: RangeInclusive<BlockNumber>,
     ) -> ProviderResult<usize> {
         let mut last_indices = self
-            .tx
-            .cursor_read::<tables::AccountChangeSets>()?
-            .walk_range(range)?
-            .map(|entry| entry.map(|(index, account)| (account.address, index)))
-            .collect::<Result<Vec<_>, _>>()?;
+            .account_changesets_range(range)?
+            .into_iter()
+            .map(|(index, account)| (account.address, index))
+            .collect::<Vec<_>>();
         last_indices.sort_by_key(|(a, _)| *a);
 
         // Unwind the account history index.
@@ -2443,13 +2805,11 @@ impl<TX: DbTxMut + DbTx> HistoryWriter for DatabaseProvider<TX> {
         range: Range<BlockNumberAddress>,
     ) -> ProviderResult<usize> {
         let mut storage_changesets = self
-            .tx
-            .cursor_read::<tables::StorageChangeSets>()?
-            .walk_range(range)?
-            .map(|entry| {
-                entry.map(|(BlockNumberAddress((bn, address)), storage)| (address, storage.key, bn))
-            })
-            .collect::<Result<Vec<_>, _>>()?;
+            .storage_changesets_range(range.start.block_number()..=range.end.block_number())?
+            .into_iter()
+            .filter(|(key, _)| range.contains(key))
+            .map(|(BlockNumberAddress((bn, address)), storage)| (address, storage.key, bn))
+            .collect::<Vec<_>>();
         storage_changesets.sort_by_key(|(address, key, _)| (*address, *key));
 
         let mut cursor = self.tx.cursor_write::<tables::StoragesHistory>()?;
@@ -2490,6 +2850,51 @@ impl<TX: DbTxMut + DbTx> HistoryWriter for DatabaseProvider<TX> {
         )
     }
 
+    fn unwind_address_transaction_indices(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<usize> {
+        if range.is_empty() {
+            return Ok(0)
+        }
+
+        let tx_range = self.tx_num_range_by_block_range(range)?;
+        let transactions = tx_range.end - tx_range.start;
+        let address_transactions = self.address_transactions_by_tx_range(tx_range)?;
+
+        let mut cursor = self.tx.cursor_write::<tables::AddressTransactions>()?;
+        for (address, tx_numbers) in address_transactions {
+            let rem_index = *tx_numbers.first().expect("at least one transaction per address");
+            let partial_shard = unwind_history_shards::<_, tables::AddressTransactions, _>(
+                &mut cursor,
+                ShardedKey::last(address),
+                rem_index,
+                |sharded_key| sharded_key.key == address,
+            )?;
+
+            // Check the last returned partial shard.
+            // If it's not empty, the shard needs to be reinserted.
+            if !partial_shard.is_empty() {
+                cursor.insert(
+                    ShardedKey::last(address),
+                    BlockNumberList::new_pre_sorted(partial_shard),
+                )?;
+            }
+        }
+
+        Ok(transactions as usize)
+    }
+
+    fn insert_address_transaction_index(
+        &self,
+        address_transactions: BTreeMap<Address, Vec<TxNumber>>,
+    ) -> ProviderResult<()> {
+        self.append_history_index::<_, tables::AddressTransactions>(
+            address_transactions,
+            ShardedKey::new,
+        )
+    }
+
     fn update_history_indices(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<()> {
         // account history stage
         {
@@ -2546,6 +2951,20 @@ impl<TX: DbTxMut + DbTx> BlockExecutionWriter for DatabaseProvider<TX> {
             // Unwind storage history indices.
             self.unwind_storage_history_indices(storage_range)?;
 
+            // Unwind address transaction indices, if they were built for the range.
+            if let Some(checkpoint) = self
+                .get_stage_checkpoint(StageId::IndexAddressTransactions)?
+                .filter(|checkpoint| checkpoint.block_number >= *range.start())
+            {
+                self.unwind_address_transaction_indices(
+                    *range.start()..=checkpoint.block_number.min(*range.end()),
+                )?;
+                self.save_stage_checkpoint(
+                    StageId::IndexAddressTransactions,
+                    StageCheckpoint::new(range.start().saturating_sub(1)),
+                )?;
+            }
+
             // Calculate the reverted merkle root.
             // This is the same as `StateRoot::incremental_root_with_updates`, only the prefix sets
             // are pre-loaded.
@@ -2785,6 +3204,22 @@ impl<TX: DbTxMut + DbTx> BlockWriter for DatabaseProvider<TX> {
         self.update_history_indices(first_number..=last_block_number)?;
         durations_recorder.record_relative(metrics::Action::InsertHistoryIndices);
 
+        // The address transactions index is optional, so it's only extended if it's enabled and
+        // already covers all blocks before the appended ones.
+        if self
+            .get_stage_checkpoint(StageId::IndexAddressTransactions)?
+            .is_some_and(|checkpoint| checkpoint.block_number + 1 == first_number)
+        {
+            let tx_range = self.tx_num_range_by_block_range(first_number..=last_block_number)?;
+            self.insert_address_transaction_index(
+                self.address_transactions_by_tx_range(tx_range)?,
+            )?;
+            self.save_stage_checkpoint(
+                StageId::IndexAddressTransactions,
+                StageCheckpoint::new(last_block_number),
+            )?;
+        }
+
         // Update pipeline progress
         self.update_pipeline_stages(last_block_number, false)?;
         durations_recorder.record_relative(metrics::Action::UpdatePipelineStages);
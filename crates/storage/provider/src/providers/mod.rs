@@ -1,11 +1,12 @@
 use crate::{
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    BlockSource, BlockchainTreePendingStateProvider, CanonChainTracker, CanonStateNotifications,
-    CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, DatabaseProviderFactory,
-    EvmEnvProvider, FullExecutionDataProvider, HeaderProvider, ProviderError,
-    PruneCheckpointReader, ReceiptProvider, ReceiptProviderIdExt, RequestsProvider,
-    StageCheckpointReader, StateProviderBox, StateProviderFactory, StaticFileProviderFactory,
-    TransactionVariant, TransactionsProvider, TreeViewer, WithdrawalsProvider,
+    AccountHistoryReader, AccountReader, BadBlocksProvider, BlockHashReader, BlockIdReader,
+    BlockNumReader, BlockReader, BlockReaderIdExt, BlockSource, BlockchainTreePendingStateProvider,
+    CanonChainTracker, CanonStateNotifications, CanonStateSubscriptions, ChainSpecProvider,
+    ChangeSetReader, DatabaseProviderFactory, EvmEnvProvider, FullExecutionDataProvider,
+    HeaderProvider, ProviderError, PruneCheckpointReader, ReceiptProvider, ReceiptProviderIdExt,
+    RequestsProvider, StageCheckpointReader, StateOverrideWriter, StateProviderBox,
+    StateProviderFactory, StaticFileProviderFactory, TransactionVariant, TransactionsProvider,
+    TreeViewer, WithdrawalsProvider,
 };
 use reth_blockchain_tree_api::{
     error::{CanonicalError, InsertBlockError},
@@ -20,9 +21,9 @@ use reth_db_api::{
 use reth_evm::ConfigureEvmEnv;
 use reth_primitives::{
     Account, Address, Block, BlockHash, BlockHashOrNumber, BlockId, BlockNumHash, BlockNumber,
-    BlockNumberOrTag, BlockWithSenders, Header, Receipt, SealedBlock, SealedBlockWithSenders,
-    SealedHeader, TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber,
-    Withdrawal, Withdrawals, B256, U256,
+    BlockNumberOrTag, BlockWithSenders, Bytecode, Header, Receipt, SealedBlock,
+    SealedBlockWithSenders, SealedHeader, StorageEntry, TransactionMeta, TransactionSigned,
+    TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256, U256,
 };
 use reth_prune_types::{PruneCheckpoint, PruneSegment};
 use reth_stages_types::{StageCheckpoint, StageId};
@@ -54,6 +55,9 @@ pub use state::{
 mod bundle_state_provider;
 pub use bundle_state_provider::BundleStateProvider;
 
+mod bad_blocks;
+pub use bad_blocks::{BadBlocks, DEFAULT_BAD_BLOCKS_LIMIT};
+
 mod chain_info;
 use chain_info::ChainInfoTracker;
 
@@ -74,6 +78,8 @@ pub struct BlockchainProvider<DB> {
     tree: Arc<dyn TreeViewer>,
     /// Tracks the chain info wrt forkchoice updates
     chain_info: ChainInfoTracker,
+    /// Recent blocks that failed validation
+    bad_blocks: BadBlocks,
 }
 
 impl<DB> Clone for BlockchainProvider<DB> {
@@ -82,6 +88,7 @@ impl<DB> Clone for BlockchainProvider<DB> {
             database: self.database.clone(),
             tree: self.tree.clone(),
             chain_info: self.chain_info.clone(),
+            bad_blocks: self.bad_blocks.clone(),
         }
     }
 }
@@ -94,7 +101,12 @@ impl<DB> BlockchainProvider<DB> {
         tree: Arc<dyn TreeViewer>,
         latest: SealedHeader,
     ) -> Self {
-        Self { database, tree, chain_info: ChainInfoTracker::new(latest) }
+        Self {
+            database,
+            tree,
+            chain_info: ChainInfoTracker::new(latest),
+            bad_blocks: BadBlocks::default(),
+        }
     }
 
     /// Sets the treeviewer for the provider.
@@ -798,6 +810,30 @@ where
     }
 }
 
+impl<DB> BadBlocksProvider for BlockchainProvider<DB>
+where
+    DB: Send + Sync,
+{
+    fn bad_blocks(&self) -> BadBlocks {
+        self.bad_blocks.clone()
+    }
+}
+
+impl<DB> StateOverrideWriter for BlockchainProvider<DB>
+where
+    DB: Database,
+{
+    fn override_state(
+        &self,
+        address: Address,
+        account: Account,
+        bytecode: Option<Bytecode>,
+        storage: Vec<StorageEntry>,
+    ) -> ProviderResult<BlockNumber> {
+        self.database.override_state(address, account, bytecode, storage)
+    }
+}
+
 impl<DB> BlockReaderIdExt for BlockchainProvider<DB>
 where
     Self: BlockReader + BlockIdReader + ReceiptProviderIdExt,
@@ -910,12 +946,35 @@ where
     }
 }
 
+impl<DB> AccountHistoryReader for BlockchainProvider<DB>
+where
+    DB: Database,
+{
+    fn account_history_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        self.database.provider()?.account_history_blocks(address, range)
+    }
+
+    fn address_transactions(
+        &self,
+        address: Address,
+        range: RangeInclusive<TxNumber>,
+    ) -> ProviderResult<Vec<TxNumber>> {
+        self.database.provider()?.address_transactions(address, range)
+    }
+}
+
 impl<DB> AccountReader for BlockchainProvider<DB>
 where
     DB: Database + Sync + Send,
 {
     /// Get basic account information.
+    ///
+    /// Reads the latest state, which isn't the plain state for snap synced nodes.
     fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
-        self.database.provider()?.basic_account(address)
+        self.database.latest()?.basic_account(address)
     }
 }
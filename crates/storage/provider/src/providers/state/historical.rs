@@ -1,18 +1,20 @@
 use crate::{
     providers::{state::macros::delegate_provider_impls, StaticFileProvider},
-    AccountReader, BlockHashReader, ProviderError, StateProvider, StateRootProvider,
+    AccountReader, BlockHashReader, ChangeSetReader, ProviderError, StateProvider,
+    StateRootProvider,
 };
 use reth_db::{tables, BlockNumberList};
 use reth_db_api::{
     cursor::{DbCursorRO, DbDupCursorRO},
-    models::{storage_sharded_key::StorageShardedKey, ShardedKey},
+    models::{storage_sharded_key::StorageShardedKey, BlockNumberAddress, ShardedKey},
     table::Table,
     transaction::DbTx,
 };
 use reth_primitives::{
-    constants::EPOCH_SLOTS, Account, Address, BlockNumber, Bytecode, StaticFileSegment, StorageKey,
-    StorageValue, B256,
+    constants::EPOCH_SLOTS, keccak256, Account, Address, BlockNumber, Bytecode, StaticFileSegment,
+    StorageKey, StorageValue, B256,
 };
+use reth_prune_types::HistoryRetentionConfig;
 use reth_storage_errors::provider::ProviderResult;
 use reth_trie::{updates::TrieUpdates, AccountProof, HashedPostState};
 use revm::db::BundleState;
@@ -29,6 +31,15 @@ use std::fmt::Debug;
 /// - [`tables::StoragesHistory`]
 /// - [`tables::AccountChangeSets`]
 /// - [`tables::StorageChangeSets`]
+///
+/// Changesets of blocks that were moved to static files are read from the
+/// [`StaticFileSegment::AccountChangeSets`] and [`StaticFileSegment::StorageChangeSets`] segments.
+///
+/// If the history is pruned, the history of the addresses retained by the prune configuration is
+/// still available at every block. Changesets that were moved to static files are kept for every
+/// address, but the history indices of the other addresses are pruned, so their history below the
+/// pruned block is reported as pruned. Reverting the state, which the state root relies on, needs
+/// the changesets of every address, so it's never available below the pruned block.
 #[derive(Debug)]
 pub struct HistoricalStateProviderRef<'b, TX: DbTx> {
     /// Transaction
@@ -37,6 +48,12 @@ pub struct HistoricalStateProviderRef<'b, TX: DbTx> {
     block_number: BlockNumber,
     /// Lowest blocks at which different parts of the state are available.
     lowest_available_blocks: LowestAvailableBlocks,
+    /// Addresses whose account history is not pruned.
+    account_history_retention: Option<&'b HistoryRetentionConfig>,
+    /// Addresses whose storage history is not pruned.
+    storage_history_retention: Option<&'b HistoryRetentionConfig>,
+    /// Whether the plain state is read from the hashed state tables.
+    hashed_state: bool,
     /// Static File provider
     static_file_provider: StaticFileProvider,
 }
@@ -56,7 +73,12 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         block_number: BlockNumber,
         static_file_provider: StaticFileProvider,
     ) -> Self {
-        Self { tx, block_number, lowest_available_blocks: Default::default(), static_file_provider }
+        Self::new_with_lowest_available_blocks(
+            tx,
+            block_number,
+            Default::default(),
+            static_file_provider,
+        )
     }
 
     /// Create new `StateProvider` for historical block number and lowest block numbers at which
@@ -67,44 +89,113 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
         lowest_available_blocks: LowestAvailableBlocks,
         static_file_provider: StaticFileProvider,
     ) -> Self {
-        Self { tx, block_number, lowest_available_blocks, static_file_provider }
+        Self {
+            tx,
+            block_number,
+            lowest_available_blocks,
+            account_history_retention: None,
+            storage_history_retention: None,
+            hashed_state: false,
+            static_file_provider,
+        }
+    }
+
+    /// Read the state that is not in changesets from the hashed state tables instead of the plain
+    /// state, see
+    /// [`LatestStateProviderRef::with_hashed_state`](crate::LatestStateProviderRef::with_hashed_state).
+    pub const fn with_hashed_state(mut self, hashed_state: bool) -> Self {
+        self.hashed_state = hashed_state;
+        self
+    }
+
+    /// Sets the addresses whose account and storage history is available at every block, even if
+    /// it's pruned for all other addresses.
+    pub const fn with_history_retention(
+        mut self,
+        account_history_retention: &'b HistoryRetentionConfig,
+        storage_history_retention: &'b HistoryRetentionConfig,
+    ) -> Self {
+        self.account_history_retention = Some(account_history_retention);
+        self.storage_history_retention = Some(storage_history_retention);
+        self
     }
 
     /// Lookup an account in the `AccountsHistory` table
+    ///
+    /// Returns [`ProviderError::AccountHistoryPruned`] if the history is pruned at the block and
+    /// the address isn't retained, or [`ProviderError::StateAtBlockPruned`] if no addresses are
+    /// retained.
     pub fn account_history_lookup(&self, address: Address) -> ProviderResult<HistoryInfo> {
-        if !self.lowest_available_blocks.is_account_history_available(self.block_number) {
-            return Err(ProviderError::StateAtBlockPruned(self.block_number))
-        }
+        let lowest_available_block_number = match self.account_history_retention {
+            // History of retained addresses is never pruned.
+            Some(retention) if retention.contains(&address) => None,
+            retention => {
+                if !self.lowest_available_blocks.is_account_history_available(self.block_number) {
+                    return Err(match retention {
+                        Some(retention) if !retention.is_empty() => {
+                            ProviderError::AccountHistoryPruned {
+                                block_number: self.block_number,
+                                address,
+                            }
+                        }
+                        _ => ProviderError::StateAtBlockPruned(self.block_number),
+                    })
+                }
+                self.lowest_available_blocks.account_history_block_number
+            }
+        };
 
         // history key to search IntegerList of block number changesets.
         let history_key = ShardedKey::new(address, self.block_number);
         self.history_info::<tables::AccountsHistory, _>(
             history_key,
             |key| key.key == address,
-            self.lowest_available_blocks.account_history_block_number,
+            lowest_available_block_number,
         )
     }
 
     /// Lookup a storage key in the `StoragesHistory` table
+    ///
+    /// Returns [`ProviderError::StorageHistoryPruned`] if the history is pruned at the block and
+    /// the address isn't retained, or [`ProviderError::StateAtBlockPruned`] if no addresses are
+    /// retained.
     pub fn storage_history_lookup(
         &self,
         address: Address,
         storage_key: StorageKey,
     ) -> ProviderResult<HistoryInfo> {
-        if !self.lowest_available_blocks.is_storage_history_available(self.block_number) {
-            return Err(ProviderError::StateAtBlockPruned(self.block_number))
-        }
+        let lowest_available_block_number = match self.storage_history_retention {
+            // History of retained addresses is never pruned.
+            Some(retention) if retention.contains(&address) => None,
+            retention => {
+                if !self.lowest_available_blocks.is_storage_history_available(self.block_number) {
+                    return Err(match retention {
+                        Some(retention) if !retention.is_empty() => {
+                            ProviderError::StorageHistoryPruned {
+                                block_number: self.block_number,
+                                address,
+                            }
+                        }
+                        _ => ProviderError::StateAtBlockPruned(self.block_number),
+                    })
+                }
+                self.lowest_available_blocks.storage_history_block_number
+            }
+        };
 
         // history key to search IntegerList of block number changesets.
         let history_key = StorageShardedKey::new(address, storage_key, self.block_number);
         self.history_info::<tables::StoragesHistory, _>(
             history_key,
             |key| key.address == address && key.sharded_key.key == storage_key,
-            self.lowest_available_blocks.storage_history_block_number,
+            lowest_available_block_number,
         )
     }
 
     /// Retrieve revert hashed state for this history provider.
+    ///
+    /// Retained addresses don't make the reverts available, since they need the changesets of
+    /// every address.
     fn revert_state(&self) -> ProviderResult<HashedPostState> {
         if !self.lowest_available_blocks.is_account_history_available(self.block_number) ||
             !self.lowest_available_blocks.is_storage_history_available(self.block_number)
@@ -130,7 +221,49 @@ impl<'b, TX: DbTx> HistoricalStateProviderRef<'b, TX> {
             );
         }
 
-        Ok(HashedPostState::from_revert_range(self.tx, self.block_number..=tip)?)
+        let range = self.block_number..=tip;
+        let in_static_files = |segment| {
+            self.static_file_provider
+                .get_highest_static_file_block(segment)
+                .is_some_and(|highest| highest >= self.block_number)
+        };
+        if !in_static_files(StaticFileSegment::AccountChangeSets) &&
+            !in_static_files(StaticFileSegment::StorageChangeSets)
+        {
+            return Ok(HashedPostState::from_revert_range(self.tx, range)?)
+        }
+
+        let account_changesets = self.static_file_provider.get_range_with_static_file_or_database(
+            StaticFileSegment::AccountChangeSets,
+            self.block_number..tip + 1,
+            |static_file, range, _| static_file.account_changesets_range(range),
+            |range, _| {
+                self.tx
+                    .cursor_read::<tables::AccountChangeSets>()?
+                    .walk_range(range)?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+            |_| true,
+        )?;
+        let storage_changesets = self.static_file_provider.get_range_with_static_file_or_database(
+            StaticFileSegment::StorageChangeSets,
+            self.block_number..tip + 1,
+            |static_file, range, _| static_file.storage_changesets_range(range),
+            |range, _| {
+                self.tx
+                    .cursor_read::<tables::StorageChangeSets>()?
+                    .walk_range(BlockNumberAddress::range(range.start..=range.end - 1))?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(Into::into)
+            },
+            |_| true,
+        )?;
+
+        Ok(HashedPostState::from_reverts(
+            account_changesets.into_iter().map(Ok),
+            storage_changesets.into_iter().map(Ok),
+        )?)
     }
 
     fn history_info<T, K>(
@@ -203,15 +336,34 @@ impl<'b, TX: DbTx> AccountReader for HistoricalStateProviderRef<'b, TX> {
         match self.account_history_lookup(address)? {
             HistoryInfo::NotYetWritten => Ok(None),
             HistoryInfo::InChangeset(changeset_block_number) => Ok(self
-                .tx
-                .cursor_dup_read::<tables::AccountChangeSets>()?
-                .seek_by_key_subkey(changeset_block_number, address)?
-                .filter(|acc| acc.address == address)
+                .static_file_provider
+                .get_with_static_file_or_database(
+                    StaticFileSegment::AccountChangeSets,
+                    changeset_block_number,
+                    |static_file| {
+                        let changeset =
+                            static_file.account_block_changeset(changeset_block_number)?;
+                        Ok(changeset
+                            .binary_search_by_key(&address, |account| account.address)
+                            .ok()
+                            .map(|index| changeset[index].clone()))
+                    },
+                    || {
+                        Ok(self
+                            .tx
+                            .cursor_dup_read::<tables::AccountChangeSets>()?
+                            .seek_by_key_subkey(changeset_block_number, address)?
+                            .filter(|acc| acc.address == address))
+                    },
+                )?
                 .ok_or(ProviderError::AccountChangesetNotFound {
                     block_number: changeset_block_number,
                     address,
                 })?
                 .info),
+            HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState if self.hashed_state => {
+                Ok(self.tx.get::<tables::HashedAccounts>(keccak256(address))?)
+            }
             HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState => {
                 Ok(self.tx.get::<tables::PlainAccountState>(address)?)
             }
@@ -281,10 +433,28 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
         match self.storage_history_lookup(address, storage_key)? {
             HistoryInfo::NotYetWritten => Ok(None),
             HistoryInfo::InChangeset(changeset_block_number) => Ok(Some(
-                self.tx
-                    .cursor_dup_read::<tables::StorageChangeSets>()?
-                    .seek_by_key_subkey((changeset_block_number, address).into(), storage_key)?
-                    .filter(|entry| entry.key == storage_key)
+                self.static_file_provider
+                    .get_with_static_file_or_database(
+                        StaticFileSegment::StorageChangeSets,
+                        changeset_block_number,
+                        |static_file| {
+                            static_file.storage_changeset_entry(
+                                changeset_block_number,
+                                address,
+                                storage_key,
+                            )
+                        },
+                        || {
+                            Ok(self
+                                .tx
+                                .cursor_dup_read::<tables::StorageChangeSets>()?
+                                .seek_by_key_subkey(
+                                    (changeset_block_number, address).into(),
+                                    storage_key,
+                                )?
+                                .filter(|entry| entry.key == storage_key))
+                        },
+                    )?
                     .ok_or_else(|| ProviderError::StorageChangesetNotFound {
                         block_number: changeset_block_number,
                         address,
@@ -292,6 +462,16 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
                     })?
                     .value,
             )),
+            HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState if self.hashed_state => {
+                let (hashed_address, hashed_slot) = (keccak256(address), keccak256(storage_key));
+                Ok(self
+                    .tx
+                    .cursor_dup_read::<tables::HashedStorages>()?
+                    .seek_by_key_subkey(hashed_address, hashed_slot)?
+                    .filter(|entry| entry.key == hashed_slot)
+                    .map(|entry| entry.value)
+                    .or(Some(StorageValue::ZERO)))
+            }
             HistoryInfo::InPlainState | HistoryInfo::MaybeInPlainState => Ok(self
                 .tx
                 .cursor_dup_read::<tables::PlainStorageState>()?
@@ -308,8 +488,14 @@ impl<'b, TX: DbTx> StateProvider for HistoricalStateProviderRef<'b, TX> {
     }
 
     /// Get account and storage proofs.
-    fn proof(&self, _address: Address, _keys: &[B256]) -> ProviderResult<AccountProof> {
-        Err(ProviderError::StateRootNotAvailableForHistoricalBlock)
+    ///
+    /// The proof is generated by overlaying the reverted state of all changesets from the target
+    /// block up to the tip onto the hashed state and trie tables.
+    fn proof(&self, address: Address, keys: &[B256]) -> ProviderResult<AccountProof> {
+        let revert_state = self.revert_state()?;
+        revert_state
+            .account_proof(self.tx, address, keys)
+            .map_err(|err| ProviderError::Database(err.into()))
     }
 }
 
@@ -323,6 +509,12 @@ pub struct HistoricalStateProvider<TX: DbTx> {
     block_number: BlockNumber,
     /// Lowest blocks at which different parts of the state are available.
     lowest_available_blocks: LowestAvailableBlocks,
+    /// Addresses whose account history is not pruned.
+    account_history_retention: HistoryRetentionConfig,
+    /// Addresses whose storage history is not pruned.
+    storage_history_retention: HistoryRetentionConfig,
+    /// Whether the plain state is read from the hashed state tables.
+    hashed_state: bool,
     /// Static File provider
     static_file_provider: StaticFileProvider,
 }
@@ -334,7 +526,22 @@ impl<TX: DbTx> HistoricalStateProvider<TX> {
         block_number: BlockNumber,
         static_file_provider: StaticFileProvider,
     ) -> Self {
-        Self { tx, block_number, lowest_available_blocks: Default::default(), static_file_provider }
+        Self {
+            tx,
+            block_number,
+            lowest_available_blocks: Default::default(),
+            account_history_retention: Default::default(),
+            storage_history_retention: Default::default(),
+            hashed_state: false,
+            static_file_provider,
+        }
+    }
+
+    /// Read the state that is not in changesets from the hashed state tables, see
+    /// [`HistoricalStateProviderRef::with_hashed_state`].
+    pub const fn with_hashed_state(mut self, hashed_state: bool) -> Self {
+        self.hashed_state = hashed_state;
+        self
     }
 
     /// Set the lowest block number at which the account history is available.
@@ -355,6 +562,20 @@ impl<TX: DbTx> HistoricalStateProvider<TX> {
         self
     }
 
+    /// Set the addresses whose account history is available at every block, even if it's pruned
+    /// for all other addresses.
+    pub fn with_account_history_retention(mut self, retention: HistoryRetentionConfig) -> Self {
+        self.account_history_retention = retention;
+        self
+    }
+
+    /// Set the addresses whose storage history is available at every block, even if it's pruned
+    /// for all other addresses.
+    pub fn with_storage_history_retention(mut self, retention: HistoryRetentionConfig) -> Self {
+        self.storage_history_retention = retention;
+        self
+    }
+
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref(&self) -> HistoricalStateProviderRef<'_, TX> {
@@ -364,6 +585,8 @@ impl<TX: DbTx> HistoricalStateProvider<TX> {
             self.lowest_available_blocks,
             self.static_file_provider.clone(),
         )
+        .with_history_retention(&self.account_history_retention, &self.storage_history_retention)
+        .with_hashed_state(self.hashed_state)
     }
 }
 
@@ -401,18 +624,28 @@ impl LowestAvailableBlocks {
 #[cfg(test)]
 mod tests {
     use crate::{
-        providers::state::historical::{HistoryInfo, LowestAvailableBlocks},
+        providers::{
+            state::historical::{HistoryInfo, LowestAvailableBlocks},
+            StaticFileWriter,
+        },
         test_utils::create_test_provider_factory,
         AccountReader, HistoricalStateProvider, HistoricalStateProviderRef, StateProvider,
-        StaticFileProviderFactory,
+        StateRootProvider, StaticFileProviderFactory,
     };
     use reth_db::{tables, BlockNumberList};
     use reth_db_api::{
-        models::{storage_sharded_key::StorageShardedKey, AccountBeforeTx, ShardedKey},
+        models::{
+            storage_sharded_key::StorageShardedKey, AccountBeforeTx, BlockAccountChangeSets,
+            BlockStorageChangeSets, ShardedKey,
+        },
         transaction::{DbTx, DbTxMut},
     };
-    use reth_primitives::{address, b256, Account, Address, StorageEntry, B256, U256};
+    use reth_primitives::{
+        address, b256, Account, Address, StaticFileSegment, StorageEntry, B256, U256,
+    };
+    use reth_prune_types::HistoryRetentionConfig;
     use reth_storage_errors::provider::ProviderError;
+    use revm::db::BundleState;
 
     const ADDRESS: Address = address!("0000000000000000000000000000000000000001");
     const HIGHER_ADDRESS: Address = address!("0000000000000000000000000000000000000005");
@@ -655,6 +888,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn history_provider_reads_changesets_from_static_files() {
+        let factory = create_test_provider_factory();
+        let tx = factory.provider_rw().unwrap().into_tx();
+        let static_file_provider = factory.static_file_provider();
+
+        tx.put::<tables::AccountsHistory>(
+            ShardedKey { key: ADDRESS, highest_block_number: u64::MAX },
+            BlockNumberList::new([3, 7]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::StoragesHistory>(
+            StorageShardedKey {
+                address: ADDRESS,
+                sharded_key: ShardedKey { key: STORAGE, highest_block_number: u64::MAX },
+            },
+            BlockNumberList::new([3, 7]).unwrap(),
+        )
+        .unwrap();
+
+        let acc_plain = Account { nonce: 100, balance: U256::ZERO, bytecode_hash: None };
+        let acc_at7 = Account { nonce: 7, balance: U256::ZERO, bytecode_hash: None };
+        let acc_at3 = Account { nonce: 3, balance: U256::ZERO, bytecode_hash: None };
+        let entry_plain = StorageEntry { key: STORAGE, value: U256::from(100) };
+        let entry_at7 = StorageEntry { key: STORAGE, value: U256::from(7) };
+        let entry_at3 = StorageEntry { key: STORAGE, value: U256::from(3) };
+
+        // Changesets up to block 5 are in static files, the rest in the database
+        let mut account_writer =
+            static_file_provider.latest_writer(StaticFileSegment::AccountChangeSets).unwrap();
+        let mut storage_writer =
+            static_file_provider.latest_writer(StaticFileSegment::StorageChangeSets).unwrap();
+        for block in 0..=5 {
+            let (account_changeset, storage_changeset) = if block == 3 {
+                (
+                    vec![
+                        AccountBeforeTx { address: ADDRESS, info: Some(acc_at3) },
+                        AccountBeforeTx { address: HIGHER_ADDRESS, info: None },
+                    ],
+                    vec![
+                        (ADDRESS, StorageEntry { key: B256::ZERO, value: U256::from(1) }),
+                        (ADDRESS, entry_at3),
+                        (HIGHER_ADDRESS, StorageEntry { key: STORAGE, value: U256::from(2) }),
+                    ],
+                )
+            } else {
+                Default::default()
+            };
+            account_writer
+                .append_account_changeset(block, BlockAccountChangeSets(account_changeset))
+                .unwrap();
+            storage_writer
+                .append_storage_changeset(block, BlockStorageChangeSets(storage_changeset))
+                .unwrap();
+        }
+        account_writer.commit().unwrap();
+        storage_writer.commit().unwrap();
+        drop((account_writer, storage_writer));
+
+        tx.put::<tables::AccountChangeSets>(
+            7,
+            AccountBeforeTx { address: ADDRESS, info: Some(acc_at7) },
+        )
+        .unwrap();
+        tx.put::<tables::StorageChangeSets>((7, ADDRESS).into(), entry_at7).unwrap();
+        tx.put::<tables::PlainAccountState>(ADDRESS, acc_plain).unwrap();
+        tx.put::<tables::PlainStorageState>(ADDRESS, entry_plain).unwrap();
+        tx.commit().unwrap();
+
+        let tx = factory.provider().unwrap().into_tx();
+        let provider = |block_number| {
+            HistoricalStateProviderRef::new(&tx, block_number, static_file_provider.clone())
+        };
+
+        // From static files
+        assert_eq!(provider(1).basic_account(ADDRESS), Ok(Some(acc_at3)));
+        assert_eq!(provider(3).storage(ADDRESS, STORAGE), Ok(Some(entry_at3.value)));
+        // From the database
+        assert_eq!(provider(4).basic_account(ADDRESS), Ok(Some(acc_at7)));
+        assert_eq!(provider(7).storage(ADDRESS, STORAGE), Ok(Some(entry_at7.value)));
+        // From the plain state
+        assert_eq!(provider(8).basic_account(ADDRESS), Ok(Some(acc_plain)));
+        assert_eq!(provider(8).storage(ADDRESS, STORAGE), Ok(Some(entry_plain.value)));
+    }
+
     #[test]
     fn history_provider_unavailable() {
         let factory = create_test_provider_factory();
@@ -715,4 +1033,136 @@ mod tests {
             Ok(HistoryInfo::MaybeInPlainState)
         );
     }
+
+    #[test]
+    fn history_provider_retention() {
+        let factory = create_test_provider_factory();
+        let tx = factory.provider_rw().unwrap().into_tx();
+        let static_file_provider = factory.static_file_provider();
+
+        let retention = HistoryRetentionConfig([ADDRESS].into());
+        let provider = HistoricalStateProviderRef::new_with_lowest_available_blocks(
+            &tx,
+            2,
+            LowestAvailableBlocks {
+                account_history_block_number: Some(3),
+                storage_history_block_number: Some(3),
+            },
+            static_file_provider,
+        )
+        .with_history_retention(&retention, &retention);
+
+        // history of the retained address is available below the lowest available block number
+        assert_eq!(provider.account_history_lookup(ADDRESS), Ok(HistoryInfo::NotYetWritten));
+        assert_eq!(
+            provider.storage_history_lookup(ADDRESS, STORAGE),
+            Ok(HistoryInfo::NotYetWritten)
+        );
+
+        // history of other addresses is pruned
+        assert_eq!(
+            provider.account_history_lookup(HIGHER_ADDRESS),
+            Err(ProviderError::AccountHistoryPruned { block_number: 2, address: HIGHER_ADDRESS })
+        );
+        assert_eq!(
+            provider.storage_history_lookup(HIGHER_ADDRESS, STORAGE),
+            Err(ProviderError::StorageHistoryPruned { block_number: 2, address: HIGHER_ADDRESS })
+        );
+    }
+
+    #[test]
+    fn history_provider_retention_with_static_file_changesets() {
+        let factory = create_test_provider_factory();
+        let tx = factory.provider_rw().unwrap().into_tx();
+        let static_file_provider = factory.static_file_provider();
+
+        let acc_plain = Account { nonce: 100, balance: U256::ZERO, bytecode_hash: None };
+        let acc_at3 = Account { nonce: 3, balance: U256::ZERO, bytecode_hash: None };
+        let entry_plain = StorageEntry { key: STORAGE, value: U256::from(100) };
+        let entry_at3 = StorageEntry { key: STORAGE, value: U256::from(3) };
+
+        // Changesets up to block 5 are in static files, for both addresses
+        let mut account_writer =
+            static_file_provider.latest_writer(StaticFileSegment::AccountChangeSets).unwrap();
+        let mut storage_writer =
+            static_file_provider.latest_writer(StaticFileSegment::StorageChangeSets).unwrap();
+        for block in 0..=5 {
+            let (account_changeset, storage_changeset) = if block == 3 {
+                (
+                    vec![
+                        AccountBeforeTx { address: ADDRESS, info: Some(acc_at3) },
+                        AccountBeforeTx { address: HIGHER_ADDRESS, info: None },
+                    ],
+                    vec![
+                        (ADDRESS, entry_at3),
+                        (HIGHER_ADDRESS, StorageEntry { key: STORAGE, value: U256::from(2) }),
+                    ],
+                )
+            } else {
+                Default::default()
+            };
+            account_writer
+                .append_account_changeset(block, BlockAccountChangeSets(account_changeset))
+                .unwrap();
+            storage_writer
+                .append_storage_changeset(block, BlockStorageChangeSets(storage_changeset))
+                .unwrap();
+        }
+        account_writer.commit().unwrap();
+        storage_writer.commit().unwrap();
+        drop((account_writer, storage_writer));
+
+        // History up to block 5 is pruned, except for the history indices of the retained address
+        tx.put::<tables::AccountsHistory>(
+            ShardedKey { key: ADDRESS, highest_block_number: u64::MAX },
+            BlockNumberList::new([3]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::StoragesHistory>(
+            StorageShardedKey {
+                address: ADDRESS,
+                sharded_key: ShardedKey { key: STORAGE, highest_block_number: u64::MAX },
+            },
+            BlockNumberList::new([3]).unwrap(),
+        )
+        .unwrap();
+        tx.put::<tables::PlainAccountState>(ADDRESS, acc_plain).unwrap();
+        tx.put::<tables::PlainAccountState>(HIGHER_ADDRESS, acc_plain).unwrap();
+        tx.put::<tables::PlainStorageState>(ADDRESS, entry_plain).unwrap();
+        tx.put::<tables::PlainStorageState>(HIGHER_ADDRESS, entry_plain).unwrap();
+        tx.commit().unwrap();
+
+        let tx = factory.provider().unwrap().into_tx();
+        let retention = HistoryRetentionConfig([ADDRESS].into());
+        let provider = HistoricalStateProviderRef::new_with_lowest_available_blocks(
+            &tx,
+            1,
+            LowestAvailableBlocks {
+                account_history_block_number: Some(6),
+                storage_history_block_number: Some(6),
+            },
+            static_file_provider,
+        )
+        .with_history_retention(&retention, &retention);
+
+        // history of the retained address is read from static files
+        assert_eq!(provider.basic_account(ADDRESS), Ok(Some(acc_at3)));
+        assert_eq!(provider.storage(ADDRESS, STORAGE), Ok(Some(entry_at3.value)));
+
+        // history of other addresses is pruned, even though their changesets are in static files
+        assert_eq!(
+            provider.basic_account(HIGHER_ADDRESS),
+            Err(ProviderError::AccountHistoryPruned { block_number: 1, address: HIGHER_ADDRESS })
+        );
+        assert_eq!(
+            provider.storage(HIGHER_ADDRESS, STORAGE),
+            Err(ProviderError::StorageHistoryPruned { block_number: 1, address: HIGHER_ADDRESS })
+        );
+
+        // reverting the state needs the history of every address
+        assert_eq!(
+            provider.state_root(&BundleState::default()),
+            Err(ProviderError::StateAtBlockPruned(1))
+        );
+    }
 }
@@ -8,7 +8,8 @@ use reth_db_api::{
     transaction::DbTx,
 };
 use reth_primitives::{
-    Account, Address, BlockNumber, Bytecode, StaticFileSegment, StorageKey, StorageValue, B256,
+    keccak256, Account, Address, BlockNumber, Bytecode, StaticFileSegment, StorageKey,
+    StorageValue, B256,
 };
 use reth_storage_errors::provider::{ProviderError, ProviderResult};
 use reth_trie::{proof::Proof, updates::TrieUpdates, AccountProof, HashedPostState};
@@ -21,18 +22,33 @@ pub struct LatestStateProviderRef<'b, TX: DbTx> {
     tx: &'b TX,
     /// Static File provider
     static_file_provider: StaticFileProvider,
+    /// Whether accounts and storage are read from the hashed state tables.
+    hashed_state: bool,
 }
 
 impl<'b, TX: DbTx> LatestStateProviderRef<'b, TX> {
     /// Create new state provider
     pub const fn new(tx: &'b TX, static_file_provider: StaticFileProvider) -> Self {
-        Self { tx, static_file_provider }
+        Self { tx, static_file_provider, hashed_state: false }
+    }
+
+    /// Read accounts and storage from [`tables::HashedAccounts`] and [`tables::HashedStorages`]
+    /// instead of the plain state.
+    ///
+    /// This is required for snap synced nodes, the plain state of those only contains the accounts
+    /// and storage slots that changed after the snap sync pivot.
+    pub const fn with_hashed_state(mut self, hashed_state: bool) -> Self {
+        self.hashed_state = hashed_state;
+        self
     }
 }
 
 impl<'b, TX: DbTx> AccountReader for LatestStateProviderRef<'b, TX> {
     /// Get basic account information.
     fn basic_account(&self, address: Address) -> ProviderResult<Option<Account>> {
+        if self.hashed_state {
+            return self.tx.get::<tables::HashedAccounts>(keccak256(address)).map_err(Into::into)
+        }
         self.tx.get::<tables::PlainAccountState>(address).map_err(Into::into)
     }
 }
@@ -97,6 +113,16 @@ impl<'b, TX: DbTx> StateProvider for LatestStateProviderRef<'b, TX> {
         account: Address,
         storage_key: StorageKey,
     ) -> ProviderResult<Option<StorageValue>> {
+        if self.hashed_state {
+            let (hashed_address, hashed_slot) = (keccak256(account), keccak256(storage_key));
+            return Ok(self
+                .tx
+                .cursor_dup_read::<tables::HashedStorages>()?
+                .seek_by_key_subkey(hashed_address, hashed_slot)?
+                .filter(|entry| entry.key == hashed_slot)
+                .map(|entry| entry.value))
+        }
+
         let mut cursor = self.tx.cursor_dup_read::<tables::PlainStorageState>()?;
         if let Some(entry) = cursor.seek_by_key_subkey(account, storage_key)? {
             if entry.key == storage_key {
@@ -125,18 +151,28 @@ pub struct LatestStateProvider<TX: DbTx> {
     db: TX,
     /// Static File provider
     static_file_provider: StaticFileProvider,
+    /// Whether accounts and storage are read from the hashed state tables.
+    hashed_state: bool,
 }
 
 impl<TX: DbTx> LatestStateProvider<TX> {
     /// Create new state provider
     pub const fn new(db: TX, static_file_provider: StaticFileProvider) -> Self {
-        Self { db, static_file_provider }
+        Self { db, static_file_provider, hashed_state: false }
+    }
+
+    /// Read accounts and storage from the hashed state tables, see
+    /// [`LatestStateProviderRef::with_hashed_state`].
+    pub const fn with_hashed_state(mut self, hashed_state: bool) -> Self {
+        self.hashed_state = hashed_state;
+        self
     }
 
     /// Returns a new provider that takes the `TX` as reference
     #[inline(always)]
     fn as_ref(&self) -> LatestStateProviderRef<'_, TX> {
         LatestStateProviderRef::new(&self.db, self.static_file_provider.clone())
+            .with_hashed_state(self.hashed_state)
     }
 }
 
@@ -3,37 +3,48 @@ use super::{
     StaticFileProviderRWRefMut, BLOCKS_PER_STATIC_FILE,
 };
 use crate::{
-    to_range, BlockHashReader, BlockNumReader, BlockReader, BlockSource, DatabaseProvider,
-    HeaderProvider, ReceiptProvider, RequestsProvider, StageCheckpointReader, StatsReader,
-    TransactionVariant, TransactionsProvider, TransactionsProviderExt, WithdrawalsProvider,
+    to_range, BlockHashReader, BlockNumReader, BlockReader, BlockSource, ChangeSetReader,
+    DatabaseProvider, HeaderProvider, ReceiptProvider, RequestsProvider, StageCheckpointReader,
+    StatsReader, TransactionVariant, TransactionsProvider, TransactionsProviderExt,
+    WithdrawalsProvider,
 };
 use dashmap::{mapref::entry::Entry as DashMapEntry, DashMap};
 use parking_lot::RwLock;
 use reth_chainspec::ChainInfo;
 use reth_db::{
     lockfile::StorageLock,
-    static_file::{iter_static_files, HeaderMask, ReceiptMask, StaticFileCursor, TransactionMask},
+    static_file::{
+        iter_static_files, AccountChangeSetMask, ColumnSelectorOne, HeaderMask, ReceiptMask,
+        StaticFileCursor, StorageChangeSetMask, TransactionMask, TransactionSenderMask,
+    },
     tables,
 };
 use reth_db_api::{
     cursor::DbCursorRO,
-    models::{CompactU256, StoredBlockBodyIndices},
+    models::{
+        AccountBeforeTx, BlockAccountChangeSets, BlockNumberAddress, BlockStorageChangeSets,
+        CompactU256, StoredBlockBodyIndices,
+    },
     table::Table,
     transaction::DbTx,
 };
 use reth_nippy_jar::NippyJar;
 use reth_primitives::{
     keccak256,
-    static_file::{find_fixed_range, HighestStaticFiles, SegmentHeader, SegmentRangeInclusive},
+    static_file::{
+        find_fixed_range, Compression, HighestStaticFiles, SegmentHeader, SegmentRangeInclusive,
+        SegmentsCompression,
+    },
     Address, Block, BlockHash, BlockHashOrNumber, BlockNumber, BlockWithSenders, Header, Receipt,
-    SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment, TransactionMeta,
-    TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal, Withdrawals, B256,
-    U256,
+    SealedBlock, SealedBlockWithSenders, SealedHeader, StaticFileSegment, StorageEntry,
+    TransactionMeta, TransactionSigned, TransactionSignedNoHash, TxHash, TxNumber, Withdrawal,
+    Withdrawals, B256, U256,
 };
 use reth_stages_types::{PipelineTarget, StageId};
 use reth_storage_errors::provider::{ProviderError, ProviderResult};
 use std::{
     collections::{hash_map::Entry, BTreeMap, HashMap},
+    convert::identity,
     ops::{Deref, Range, RangeBounds, RangeInclusive},
     path::{Path, PathBuf},
     sync::{mpsc, Arc},
@@ -68,6 +79,9 @@ impl StaticFileAccess {
     }
 }
 
+/// Name of the directory, inside the static files directory, holding trained zstd dictionaries.
+const DICTIONARIES_DIR: &str = "dictionaries";
+
 /// [`StaticFileProvider`] manages all existing [`StaticFileJarProvider`].
 #[derive(Debug, Default, Clone)]
 pub struct StaticFileProvider(pub(crate) Arc<StaticFileProviderInner>);
@@ -119,6 +133,8 @@ pub struct StaticFileProviderInner {
     metrics: Option<Arc<StaticFileProviderMetrics>>,
     /// Access rights of the provider.
     access: StaticFileAccess,
+    /// Compression of newly created static files, per segment.
+    compression: SegmentsCompression,
     /// Write lock for when access is [`StaticFileAccess::RW`].
     _lock_file: Option<StorageLock>,
 }
@@ -141,6 +157,7 @@ impl StaticFileProviderInner {
             load_filters: false,
             metrics: None,
             access,
+            compression: SegmentsCompression::default(),
             _lock_file,
         };
 
@@ -169,6 +186,60 @@ impl StaticFileProvider {
         Self(Arc::new(provider))
     }
 
+    /// Sets the compression of newly created static files, per segment.
+    pub fn with_compression(self, compression: SegmentsCompression) -> Self {
+        let mut provider =
+            Arc::try_unwrap(self.0).expect("should be called when initializing only");
+        provider.compression = compression;
+        Self(Arc::new(provider))
+    }
+
+    /// Returns the compression of newly created static files of the segment.
+    pub fn compression(&self, segment: StaticFileSegment) -> Compression {
+        self.compression.get(segment)
+    }
+
+    /// Returns the path of the trained zstd dictionary for a column of the segment.
+    pub fn dictionary_path(&self, segment: StaticFileSegment, column: usize) -> PathBuf {
+        self.path.join(DICTIONARIES_DIR).join(format!("{}-{column}.zstd-dict", segment.as_str()))
+    }
+
+    /// Returns the trained zstd dictionaries of the segment, one per column, if all of them exist.
+    pub fn dictionaries(&self, segment: StaticFileSegment) -> ProviderResult<Option<Vec<Vec<u8>>>> {
+        let mut dictionaries = Vec::with_capacity(segment.columns());
+        for column in 0..segment.columns() {
+            let path = self.dictionary_path(segment, column);
+            if !path.exists() {
+                return Ok(None)
+            }
+            dictionaries.push(reth_fs_util::read(path)?);
+        }
+        Ok(Some(dictionaries))
+    }
+
+    /// Writes the trained zstd dictionaries of the segment, one per column.
+    ///
+    /// Only static files created afterwards use them, existing ones keep their own dictionaries.
+    pub fn write_dictionaries(
+        &self,
+        segment: StaticFileSegment,
+        dictionaries: &[Vec<u8>],
+    ) -> ProviderResult<()> {
+        if dictionaries.len() != segment.columns() {
+            return Err(ProviderError::NippyJar(format!(
+                "expected {} dictionaries for {segment}, got {}",
+                segment.columns(),
+                dictionaries.len()
+            )))
+        }
+
+        reth_fs_util::create_dir_all(self.path.join(DICTIONARIES_DIR))?;
+        for (column, dictionary) in dictionaries.iter().enumerate() {
+            reth_fs_util::write(self.dictionary_path(segment, column), dictionary)?;
+        }
+        Ok(())
+    }
+
     /// Reports metrics for the static files.
     pub fn report_metrics(&self) -> ProviderResult<()> {
         let Some(metrics) = &self.metrics else { return Ok(()) };
@@ -443,12 +514,7 @@ impl StaticFileProvider {
                 } else if tx_index.get(&segment).map(|index| index.len()) == Some(1) {
                     // Only happens if we unwind all the txs/receipts from the first static file.
                     // Should only happen in test scenarios.
-                    if jar.user_header().expected_block_start() == 0 &&
-                        matches!(
-                            segment,
-                            StaticFileSegment::Receipts | StaticFileSegment::Transactions
-                        )
-                    {
+                    if jar.user_header().expected_block_start() == 0 && segment.is_tx_based() {
                         tx_index.remove(&segment);
                     }
                 }
@@ -469,10 +535,14 @@ impl StaticFileProvider {
     }
 
     /// Initializes the inner transaction and block index
+    ///
+    /// A read-only provider can call this again to pick up the static files that another process
+    /// appended to or truncated since.
     pub fn initialize_index(&self) -> ProviderResult<()> {
         let mut max_block = self.static_files_max_block.write();
         let mut tx_index = self.static_files_tx_index.write();
 
+        max_block.clear();
         tx_index.clear();
 
         for (segment, ranges) in
@@ -500,9 +570,50 @@ impl StaticFileProvider {
             }
         }
 
+        // Cached providers may have been loaded before the static files changed on disk
+        self.map.clear();
+
         Ok(())
     }
 
+    /// Returns `true` if the highest block of any segment on disk differs from the index, because
+    /// another process appended to or truncated the static files since the index was initialized.
+    ///
+    /// Only the configuration of the static file holding the indexed highest block and of the one
+    /// following it are read.
+    pub fn is_index_stale(&self) -> ProviderResult<bool> {
+        for segment in StaticFileSegment::iter() {
+            let highest = self.get_highest_static_file_block(segment);
+            let fixed_range = find_fixed_range(highest.unwrap_or_default());
+            let next_fixed_range = find_fixed_range(fixed_range.end() + 1);
+
+            if self.read_static_file_block_end(segment, &fixed_range)? != highest ||
+                self.read_static_file_block_end(segment, &next_fixed_range)?.is_some()
+            {
+                return Ok(true)
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Reads the highest block of the static file with the given fixed range from disk, or `None`
+    /// if the file doesn't exist or holds no blocks.
+    fn read_static_file_block_end(
+        &self,
+        segment: StaticFileSegment,
+        fixed_range: &SegmentRangeInclusive,
+    ) -> ProviderResult<Option<BlockNumber>> {
+        let path = self.path.join(segment.filename(fixed_range));
+        if !path.with_extension("conf").exists() {
+            return Ok(None)
+        }
+
+        let jar = NippyJar::<SegmentHeader>::load(&path)
+            .map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+        Ok(jar.user_header().block_end())
+    }
+
     /// Ensures that any broken invariants which cannot be healed on the spot return a pipeline
     /// target to unwind to.
     ///
@@ -547,6 +658,18 @@ impl StaticFileProvider {
                 continue
             }
 
+            if matches!(
+                segment,
+                StaticFileSegment::AccountChangeSets |
+                    StaticFileSegment::StorageChangeSets |
+                    StaticFileSegment::TransactionSenders
+            ) && self.get_highest_static_file_block(segment).is_none()
+            {
+                // Changesets and senders are only moved to static files if they're not pruned, so
+                // their database tables don't necessarily start at the genesis block.
+                continue
+            }
+
             let initial_highest_block = self.get_highest_static_file_block(segment);
 
             //  File consistency is broken if:
@@ -559,7 +682,7 @@ impl StaticFileProvider {
             //   accordingly.
             self.ensure_file_consistency(segment)?;
 
-            // Only applies to block-based static files. (Headers)
+            // Only applies to block-based static files. (Headers & Changesets)
             //
             // The updated `highest_block` may have decreased if we healed from a pruning
             // interruption.
@@ -575,7 +698,7 @@ impl StaticFileProvider {
                 update_unwind_target(highest_block.unwrap_or_default());
             }
 
-            // Only applies to transaction-based static files. (Receipts & Transactions)
+            // Only applies to transaction-based static files. (Receipts, Transactions & Senders)
             //
             // Make sure the last transaction matches the last block from its indices, since a heal
             // from a pruning interruption might have decreased the number of transactions without
@@ -617,6 +740,7 @@ impl StaticFileProvider {
                     segment,
                     highest_block,
                     highest_block,
+                    identity,
                 )?,
                 StaticFileSegment::Transactions => self
                     .ensure_invariants::<_, tables::Transactions>(
@@ -624,13 +748,39 @@ impl StaticFileProvider {
                         segment,
                         highest_tx,
                         highest_block,
+                        identity,
                     )?,
                 StaticFileSegment::Receipts => self.ensure_invariants::<_, tables::Receipts>(
                     provider,
                     segment,
                     highest_tx,
                     highest_block,
+                    identity,
                 )?,
+                StaticFileSegment::AccountChangeSets => self
+                    .ensure_invariants::<_, tables::AccountChangeSets>(
+                        provider,
+                        segment,
+                        highest_block,
+                        highest_block,
+                        identity,
+                    )?,
+                StaticFileSegment::StorageChangeSets => self
+                    .ensure_invariants::<_, tables::StorageChangeSets>(
+                        provider,
+                        segment,
+                        highest_block,
+                        highest_block,
+                        |key| key.block_number(),
+                    )?,
+                StaticFileSegment::TransactionSenders => self
+                    .ensure_invariants::<_, tables::TransactionSenders>(
+                        provider,
+                        segment,
+                        highest_tx,
+                        highest_block,
+                        identity,
+                    )?,
             } {
                 update_unwind_target(unwind);
             }
@@ -648,18 +798,23 @@ impl StaticFileProvider {
     ///   * If the checkpoint block is higher, then request a pipeline unwind to the static file
     ///     block.
     ///   * If the checkpoint block is lower, then heal by removing rows from the static file.
-    fn ensure_invariants<TX: DbTx, T: Table<Key = u64>>(
+    ///
+    /// `entry_number` maps a key of the table to the block or transaction number of its static file
+    /// row.
+    fn ensure_invariants<TX: DbTx, T: Table>(
         &self,
         provider: &DatabaseProvider<TX>,
         segment: StaticFileSegment,
         highest_static_file_entry: Option<u64>,
         highest_static_file_block: Option<BlockNumber>,
+        entry_number: impl Fn(T::Key) -> u64,
     ) -> ProviderResult<Option<BlockNumber>> {
         let highest_static_file_entry = highest_static_file_entry.unwrap_or_default();
         let highest_static_file_block = highest_static_file_block.unwrap_or_default();
         let mut db_cursor = provider.tx_ref().cursor_read::<T>()?;
 
         if let Some((db_first_entry, _)) = db_cursor.first()? {
+            let db_first_entry = entry_number(db_first_entry);
             // If there is a gap between the entry found in static file and
             // database, then we have most likely lost static file data and need to unwind so we can
             // load it again
@@ -678,7 +833,7 @@ impl StaticFileProvider {
             }
 
             if let Some((db_last_entry, _)) = db_cursor.last()? {
-                if db_last_entry > highest_static_file_entry {
+                if entry_number(db_last_entry) > highest_static_file_entry {
                     return Ok(None)
                 }
             }
@@ -690,7 +845,10 @@ impl StaticFileProvider {
             .get_stage_checkpoint(match segment {
                 StaticFileSegment::Headers => StageId::Headers,
                 StaticFileSegment::Transactions => StageId::Bodies,
-                StaticFileSegment::Receipts => StageId::Execution,
+                StaticFileSegment::Receipts |
+                StaticFileSegment::AccountChangeSets |
+                StaticFileSegment::StorageChangeSets => StageId::Execution,
+                StaticFileSegment::TransactionSenders => StageId::SenderRecovery,
             })?
             .unwrap_or_default()
             .block_number;
@@ -719,14 +877,27 @@ impl StaticFileProvider {
                 "Unwinding static file segment."
             );
             let mut writer = self.latest_writer(segment)?;
-            if segment.is_headers() {
-                writer.prune_headers(highest_static_file_block - checkpoint_block_number)?;
+            if segment.is_block_based() {
+                let number = highest_static_file_block - checkpoint_block_number;
+                match segment {
+                    StaticFileSegment::AccountChangeSets => {
+                        writer.prune_account_changesets(number)?
+                    }
+                    StaticFileSegment::StorageChangeSets => {
+                        writer.prune_storage_changesets(number)?
+                    }
+                    _ => writer.prune_headers(number)?,
+                }
             } else if let Some(block) = provider.block_body_indices(checkpoint_block_number)? {
                 let number = highest_static_file_entry - block.last_tx_num();
-                if segment.is_receipts() {
-                    writer.prune_receipts(number, checkpoint_block_number)?;
-                } else {
-                    writer.prune_transactions(number, checkpoint_block_number)?;
+                match segment {
+                    StaticFileSegment::Receipts => {
+                        writer.prune_receipts(number, checkpoint_block_number)?
+                    }
+                    StaticFileSegment::TransactionSenders => {
+                        writer.prune_transaction_senders(number, checkpoint_block_number)?
+                    }
+                    _ => writer.prune_transactions(number, checkpoint_block_number)?,
                 }
             }
             writer.commit()?;
@@ -740,6 +911,21 @@ impl StaticFileProvider {
         self.static_files_max_block.read().get(&segment).copied()
     }
 
+    /// Gets the lowest static file block if it exists for a static file segment.
+    ///
+    /// The index only tracks the highest blocks, so this reads the static files on disk.
+    pub fn get_lowest_static_file_block(
+        &self,
+        segment: StaticFileSegment,
+    ) -> ProviderResult<Option<BlockNumber>> {
+        let static_files =
+            iter_static_files(&self.path).map_err(|e| ProviderError::NippyJar(e.to_string()))?;
+        Ok(static_files
+            .get(&segment)
+            .and_then(|ranges| ranges.first())
+            .map(|(block_range, _)| block_range.start()))
+    }
+
     /// Gets the highest static file transaction.
     pub fn get_highest_static_file_tx(&self, segment: StaticFileSegment) -> Option<TxNumber> {
         self.static_files_tx_index
@@ -754,6 +940,12 @@ impl StaticFileProvider {
             headers: self.get_highest_static_file_block(StaticFileSegment::Headers),
             receipts: self.get_highest_static_file_block(StaticFileSegment::Receipts),
             transactions: self.get_highest_static_file_block(StaticFileSegment::Transactions),
+            account_change_sets: self
+                .get_highest_static_file_block(StaticFileSegment::AccountChangeSets),
+            storage_change_sets: self
+                .get_highest_static_file_block(StaticFileSegment::StorageChangeSets),
+            transaction_senders: self
+                .get_highest_static_file_block(StaticFileSegment::TransactionSenders),
         }
     }
 
@@ -796,11 +988,10 @@ impl StaticFileProvider {
         F: FnMut(&mut StaticFileCursor<'_>, u64) -> ProviderResult<Option<T>>,
         P: FnMut(&T) -> bool,
     {
-        let get_provider = |start: u64| match segment {
-            StaticFileSegment::Headers => {
+        let get_provider = |start: u64| {
+            if segment.is_block_based() {
                 self.get_segment_provider_from_block(segment, start, None)
-            }
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
+            } else {
                 self.get_segment_provider_from_transaction(segment, start, None)
             }
         };
@@ -835,7 +1026,7 @@ impl StaticFileProvider {
                                 "Could not find block or tx number on a range request"
                             );
 
-                            let err = if segment.is_headers() {
+                            let err = if segment.is_block_based() {
                                 ProviderError::MissingStaticFileBlock(segment, number)
                             } else {
                                 ProviderError::MissingStaticFileTx(segment, number)
@@ -866,11 +1057,10 @@ impl StaticFileProvider {
         F: Fn(&mut StaticFileCursor<'_>, u64) -> ProviderResult<Option<T>> + 'a,
         T: std::fmt::Debug,
     {
-        let get_provider = move |start: u64| match segment {
-            StaticFileSegment::Headers => {
+        let get_provider = move |start: u64| {
+            if segment.is_block_based() {
                 self.get_segment_provider_from_block(segment, start, None)
-            }
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
+            } else {
                 self.get_segment_provider_from_transaction(segment, start, None)
             }
         };
@@ -913,11 +1103,10 @@ impl StaticFileProvider {
         FD: Fn() -> ProviderResult<Option<T>>,
     {
         // If there is, check the maximum block or transaction number of the segment.
-        let static_file_upper_bound = match segment {
-            StaticFileSegment::Headers => self.get_highest_static_file_block(segment),
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
-                self.get_highest_static_file_tx(segment)
-            }
+        let static_file_upper_bound = if segment.is_block_based() {
+            self.get_highest_static_file_block(segment)
+        } else {
+            self.get_highest_static_file_tx(segment)
         };
 
         if static_file_upper_bound
@@ -955,12 +1144,12 @@ impl StaticFileProvider {
         let mut data = Vec::new();
 
         // If there is, check the maximum block or transaction number of the segment.
-        if let Some(static_file_upper_bound) = match segment {
-            StaticFileSegment::Headers => self.get_highest_static_file_block(segment),
-            StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
-                self.get_highest_static_file_tx(segment)
-            }
-        } {
+        let static_file_upper_bound = if segment.is_block_based() {
+            self.get_highest_static_file_block(segment)
+        } else {
+            self.get_highest_static_file_tx(segment)
+        };
+        if let Some(static_file_upper_bound) = static_file_upper_bound {
             if block_or_tx_range.start <= static_file_upper_bound {
                 let end = block_or_tx_range.end.min(static_file_upper_bound + 1);
                 data.extend(fetch_from_static_file(
@@ -979,6 +1168,130 @@ impl StaticFileProvider {
         Ok(data)
     }
 
+    /// Returns the storage changes of a block from static files, ordered by address and storage
+    /// key.
+    pub fn storage_block_changeset(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Vec<(Address, StorageEntry)>> {
+        self.get_segment_provider_from_block(
+            StaticFileSegment::StorageChangeSets,
+            block_number,
+            None,
+        )
+        .and_then(|provider| {
+            provider
+                .cursor()?
+                .get_one::<StorageChangeSetMask<BlockStorageChangeSets>>(block_number.into())
+        })
+        .map(|changeset| changeset.map(|changeset| changeset.0).unwrap_or_default())
+        .or_else(|err| {
+            if let ProviderError::MissingStaticFileBlock(_, _) = err {
+                Ok(Vec::new())
+            } else {
+                Err(err)
+            }
+        })
+    }
+
+    /// Returns the change of the storage `key` of `address` in a block from static files.
+    ///
+    /// Unlike [`StaticFileProvider::storage_block_changeset`], only the changes of `address` are
+    /// decoded, see [`BlockStorageChangeSets::find`].
+    pub fn storage_changeset_entry(
+        &self,
+        block_number: BlockNumber,
+        address: Address,
+        key: B256,
+    ) -> ProviderResult<Option<StorageEntry>> {
+        let provider = match self.get_segment_provider_from_block(
+            StaticFileSegment::StorageChangeSets,
+            block_number,
+            None,
+        ) {
+            Ok(provider) => provider,
+            Err(ProviderError::MissingStaticFileBlock(_, _)) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+
+        let mut cursor = provider.cursor()?;
+        Ok(cursor
+            .get(
+                block_number.into(),
+                <StorageChangeSetMask<BlockStorageChangeSets> as ColumnSelectorOne>::MASK,
+            )?
+            .and_then(|row| BlockStorageChangeSets::find(row[0], address, key)))
+    }
+
+    /// Returns the account changesets of a block range from static files, in the same shape as
+    /// they are stored in [`tables::AccountChangeSets`].
+    pub fn account_changesets_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumber, AccountBeforeTx)>> {
+        let range = to_range(range);
+        let changesets = self.fetch_range_with_predicate(
+            StaticFileSegment::AccountChangeSets,
+            range.clone(),
+            |cursor, number| {
+                cursor.get_one::<AccountChangeSetMask<BlockAccountChangeSets>>(number.into())
+            },
+            |_| true,
+        )?;
+
+        Ok(range
+            .zip(changesets)
+            .flat_map(|(block_number, changeset)| {
+                changeset.0.into_iter().map(move |account| (block_number, account))
+            })
+            .collect())
+    }
+
+    /// Returns the storage changesets of a block range from static files, in the same shape as
+    /// they are stored in [`tables::StorageChangeSets`].
+    pub fn storage_changesets_range(
+        &self,
+        range: impl RangeBounds<BlockNumber>,
+    ) -> ProviderResult<Vec<(BlockNumberAddress, StorageEntry)>> {
+        let range = to_range(range);
+        let changesets = self.fetch_range_with_predicate(
+            StaticFileSegment::StorageChangeSets,
+            range.clone(),
+            |cursor, number| {
+                cursor.get_one::<StorageChangeSetMask<BlockStorageChangeSets>>(number.into())
+            },
+            |_| true,
+        )?;
+
+        Ok(range
+            .zip(changesets)
+            .flat_map(|(block_number, changeset)| {
+                changeset.0.into_iter().map(move |(address, entry)| {
+                    (BlockNumberAddress((block_number, address)), entry)
+                })
+            })
+            .collect())
+    }
+
+    /// Returns the senders of a transaction range from the transaction senders static files.
+    ///
+    /// Unlike [`TransactionsProvider::senders_by_tx_range`], the senders aren't recovered from the
+    /// transactions.
+    pub fn transaction_senders_range(
+        &self,
+        range: impl RangeBounds<TxNumber>,
+    ) -> ProviderResult<Vec<(TxNumber, Address)>> {
+        let range = to_range(range);
+        let senders = self.fetch_range_with_predicate(
+            StaticFileSegment::TransactionSenders,
+            range.clone(),
+            |cursor, number| cursor.get_one::<TransactionSenderMask<Address>>(number.into()),
+            |_| true,
+        )?;
+
+        Ok(range.zip(senders).collect())
+    }
+
     #[cfg(any(test, feature = "test-utils"))]
     /// Returns `static_files` directory
     pub fn path(&self) -> &Path {
@@ -1177,6 +1490,32 @@ impl BlockHashReader for StaticFileProvider {
     }
 }
 
+impl ChangeSetReader for StaticFileProvider {
+    fn account_block_changeset(
+        &self,
+        block_number: BlockNumber,
+    ) -> ProviderResult<Vec<AccountBeforeTx>> {
+        self.get_segment_provider_from_block(
+            StaticFileSegment::AccountChangeSets,
+            block_number,
+            None,
+        )
+        .and_then(|provider| {
+            provider
+                .cursor()?
+                .get_one::<AccountChangeSetMask<BlockAccountChangeSets>>(block_number.into())
+        })
+        .map(|changeset| changeset.map(|changeset| changeset.0).unwrap_or_default())
+        .or_else(|err| {
+            if let ProviderError::MissingStaticFileBlock(_, _) = err {
+                Ok(Vec::new())
+            } else {
+                Err(err)
+            }
+        })
+    }
+}
+
 impl ReceiptProvider for StaticFileProvider {
     fn receipt(&self, num: TxNumber) -> ProviderResult<Option<Receipt>> {
         self.get_segment_provider_from_transaction(StaticFileSegment::Receipts, num, None)
@@ -1522,6 +1861,10 @@ impl StatsReader for StaticFileProvider {
                 .get_highest_static_file_tx(StaticFileSegment::Transactions)
                 .map(|txs| txs + 1)
                 .unwrap_or_default() as usize),
+            tables::TransactionSenders::NAME => Ok(self
+                .get_highest_static_file_tx(StaticFileSegment::TransactionSenders)
+                .map(|senders| senders + 1)
+                .unwrap_or_default() as usize),
             _ => Err(ProviderError::UnsupportedProvider),
         }
     }
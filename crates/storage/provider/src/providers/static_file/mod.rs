@@ -57,7 +57,9 @@ impl Deref for LoadedJar {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{test_utils::create_test_provider_factory, HeaderProvider};
+    use crate::{
+        test_utils::create_test_provider_factory, HeaderProvider, StaticFileProviderFactory,
+    };
     use rand::seq::SliceRandom;
     use reth_db::{
         static_file::create_static_file_T1_T2_T3, CanonicalHeaders, HeaderNumbers,
@@ -179,4 +181,41 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn lowest_static_file_block() {
+        let factory = create_test_provider_factory();
+        let static_file_provider = factory.static_file_provider();
+        assert_eq!(
+            static_file_provider.get_lowest_static_file_block(StaticFileSegment::Headers).unwrap(),
+            None
+        );
+
+        // headers that start in the second static file
+        let start = find_fixed_range(0).end() + 1;
+        let headers = random_header_range(&mut generators::rng(), start..start + 2, B256::random());
+        let mut writer =
+            static_file_provider.get_writer(start, StaticFileSegment::Headers).unwrap();
+        for header in headers {
+            let hash = header.hash();
+            writer.append_header(header.unseal(), U256::ZERO, hash).unwrap();
+        }
+        writer.commit().unwrap();
+        drop(writer);
+
+        assert_eq!(
+            static_file_provider.get_lowest_static_file_block(StaticFileSegment::Headers).unwrap(),
+            Some(start)
+        );
+        assert_eq!(
+            static_file_provider.get_highest_static_file_block(StaticFileSegment::Headers),
+            Some(start + 1)
+        );
+        assert_eq!(
+            static_file_provider
+                .get_lowest_static_file_block(StaticFileSegment::Transactions)
+                .unwrap(),
+            None
+        );
+    }
 }
@@ -5,12 +5,12 @@ use super::{
 };
 use dashmap::mapref::one::RefMut;
 use reth_codecs::Compact;
-use reth_db_api::models::CompactU256;
+use reth_db_api::models::{BlockAccountChangeSets, BlockStorageChangeSets, CompactU256};
 use reth_nippy_jar::{ConsistencyFailStrategy, NippyJar, NippyJarError, NippyJarWriter};
 use reth_primitives::{
-    static_file::{find_fixed_range, SegmentHeader, SegmentRangeInclusive},
-    BlockHash, BlockNumber, Header, Receipt, StaticFileSegment, TransactionSignedNoHash, TxNumber,
-    U256,
+    static_file::{find_fixed_range, Compression, SegmentHeader, SegmentRangeInclusive},
+    Address, BlockHash, BlockNumber, Header, Receipt, StaticFileSegment, TransactionSignedNoHash,
+    TxNumber, U256,
 };
 use reth_storage_errors::provider::{ProviderError, ProviderResult};
 use std::{
@@ -18,7 +18,7 @@ use std::{
     sync::{Arc, Weak},
     time::Instant,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// Mutable reference to a dashmap element of [`StaticFileProviderRW`].
 pub type StaticFileProviderRWRefMut<'a> = RefMut<'a, StaticFileSegment, StaticFileProviderRW>;
@@ -85,7 +85,13 @@ impl StaticFileProviderRW {
             ),
             Err(ProviderError::MissingStaticFileBlock(_, _)) => {
                 let path = static_file_provider.directory().join(segment.filename(&block_range));
-                (create_jar(segment, &path, block_range), path)
+                let compression = static_file_provider.compression(segment);
+                let dictionaries = if compression == Compression::ZstdWithDictionary {
+                    static_file_provider.dictionaries(segment)?
+                } else {
+                    None
+                };
+                (create_jar(segment, &path, block_range, compression, dictionaries), path)
             }
             Err(err) => return Err(err),
         };
@@ -145,7 +151,7 @@ impl StaticFileProviderRW {
         })?;
 
         // If we have lost rows (in this run or previous), we need to update the [SegmentHeader].
-        let expected_rows = if self.user_header().segment().is_headers() {
+        let expected_rows = if self.user_header().segment().is_block_based() {
             self.user_header().block_len().unwrap_or_default()
         } else {
             self.user_header().tx_len().unwrap_or_default()
@@ -178,6 +184,14 @@ impl StaticFileProviderRW {
                 StaticFileSegment::Receipts => {
                     self.prune_receipt_data(to_delete, last_block_number.expect("should exist"))?
                 }
+                segment @ (StaticFileSegment::AccountChangeSets |
+                StaticFileSegment::StorageChangeSets) => {
+                    self.prune_change_set_data(segment, to_delete)?
+                }
+                StaticFileSegment::TransactionSenders => self.prune_transaction_sender_data(
+                    to_delete,
+                    last_block_number.expect("should exist"),
+                )?,
             }
         }
 
@@ -347,13 +361,10 @@ impl StaticFileProviderRW {
     ) -> ProviderResult<()> {
         let mut remaining_rows = num_rows;
         while remaining_rows > 0 {
-            let len = match segment {
-                StaticFileSegment::Headers => {
-                    self.writer.user_header().block_len().unwrap_or_default()
-                }
-                StaticFileSegment::Transactions | StaticFileSegment::Receipts => {
-                    self.writer.user_header().tx_len().unwrap_or_default()
-                }
+            let len = if segment.is_block_based() {
+                self.writer.user_header().block_len().unwrap_or_default()
+            } else {
+                self.writer.user_header().tx_len().unwrap_or_default()
             };
 
             if remaining_rows >= len {
@@ -385,7 +396,7 @@ impl StaticFileProviderRW {
             }
         }
 
-        // Only Transactions and Receipts
+        // Only transaction based segments
         if let Some(last_block) = last_block {
             let mut expected_block_start = self.writer.user_header().expected_block_start();
 
@@ -585,6 +596,89 @@ impl StaticFileProviderRW {
         Ok(Some(tx_number))
     }
 
+    /// Appends the account changes of a block to static file.
+    ///
+    /// It **CALLS** `increment_block()` since the number of account changesets is equal to the
+    /// number of blocks.
+    ///
+    /// Returns the current [`BlockNumber`] as seen in the static file.
+    pub fn append_account_changeset(
+        &mut self,
+        block_number: BlockNumber,
+        changeset: BlockAccountChangeSets,
+    ) -> ProviderResult<BlockNumber> {
+        self.append_change_set(StaticFileSegment::AccountChangeSets, block_number, changeset)
+    }
+
+    /// Appends the storage changes of a block to static file.
+    ///
+    /// It **CALLS** `increment_block()` since the number of storage changesets is equal to the
+    /// number of blocks.
+    ///
+    /// Returns the current [`BlockNumber`] as seen in the static file.
+    pub fn append_storage_changeset(
+        &mut self,
+        block_number: BlockNumber,
+        changeset: BlockStorageChangeSets,
+    ) -> ProviderResult<BlockNumber> {
+        self.append_change_set(StaticFileSegment::StorageChangeSets, block_number, changeset)
+    }
+
+    /// Appends a changeset to a block-based changeset static file.
+    fn append_change_set<V: Compact>(
+        &mut self,
+        segment: StaticFileSegment,
+        block_number: BlockNumber,
+        changeset: V,
+    ) -> ProviderResult<BlockNumber> {
+        let start = Instant::now();
+        self.ensure_no_queued_prune()?;
+
+        debug_assert!(self.writer.user_header().segment() == segment);
+
+        let block_number = self.increment_block(segment, block_number)?;
+
+        self.append_column(changeset)?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_segment_operation(
+                segment,
+                StaticFileProviderOperation::Append,
+                Some(start.elapsed()),
+            );
+        }
+
+        Ok(block_number)
+    }
+
+    /// Appends transaction sender to static file.
+    ///
+    /// It **DOES NOT** call `increment_block()`, it should be handled elsewhere. There might be
+    /// empty blocks and this function wouldn't be called.
+    ///
+    /// Returns the current [`TxNumber`] as seen in the static file.
+    pub fn append_transaction_sender(
+        &mut self,
+        tx_num: TxNumber,
+        sender: Address,
+    ) -> ProviderResult<TxNumber> {
+        let start = Instant::now();
+        self.ensure_no_queued_prune()?;
+
+        let result =
+            self.append_with_tx_number(StaticFileSegment::TransactionSenders, tx_num, sender)?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_segment_operation(
+                StaticFileSegment::TransactionSenders,
+                StaticFileProviderOperation::Append,
+                Some(start.elapsed()),
+            );
+        }
+
+        Ok(result)
+    }
+
     /// Adds an instruction to prune `to_delete`transactions during commit.
     ///
     /// Note: `last_block` refers to the block the unwinds ends at.
@@ -615,6 +709,35 @@ impl StaticFileProviderRW {
         self.queue_prune(to_delete, None)
     }
 
+    /// Adds an instruction to prune the account changesets of the last `to_delete` blocks during
+    /// commit.
+    pub fn prune_account_changesets(&mut self, to_delete: u64) -> ProviderResult<()> {
+        debug_assert_eq!(self.writer.user_header().segment(), StaticFileSegment::AccountChangeSets);
+        self.queue_prune(to_delete, None)
+    }
+
+    /// Adds an instruction to prune the storage changesets of the last `to_delete` blocks during
+    /// commit.
+    pub fn prune_storage_changesets(&mut self, to_delete: u64) -> ProviderResult<()> {
+        debug_assert_eq!(self.writer.user_header().segment(), StaticFileSegment::StorageChangeSets);
+        self.queue_prune(to_delete, None)
+    }
+
+    /// Adds an instruction to prune `to_delete` transaction senders during commit.
+    ///
+    /// Note: `last_block` refers to the block the unwinds ends at.
+    pub fn prune_transaction_senders(
+        &mut self,
+        to_delete: u64,
+        last_block: BlockNumber,
+    ) -> ProviderResult<()> {
+        debug_assert_eq!(
+            self.writer.user_header().segment(),
+            StaticFileSegment::TransactionSenders
+        );
+        self.queue_prune(to_delete, Some(last_block))
+    }
+
     /// Adds an instruction to prune `to_delete` elements during commit.
     ///
     /// Note: `last_block` refers to the block the unwinds ends at if dealing with transaction-based
@@ -707,6 +830,53 @@ impl StaticFileProviderRW {
         Ok(())
     }
 
+    /// Prunes the changesets of the last `to_delete` blocks from the data file.
+    fn prune_change_set_data(
+        &mut self,
+        segment: StaticFileSegment,
+        to_delete: u64,
+    ) -> ProviderResult<()> {
+        let start = Instant::now();
+
+        debug_assert!(self.writer.user_header().segment() == segment);
+
+        self.truncate(segment, to_delete, None)?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_segment_operation(
+                segment,
+                StaticFileProviderOperation::Prune,
+                Some(start.elapsed()),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Prunes the last `to_delete` transaction senders from the data file.
+    fn prune_transaction_sender_data(
+        &mut self,
+        to_delete: u64,
+        last_block: BlockNumber,
+    ) -> ProviderResult<()> {
+        let start = Instant::now();
+
+        let segment = StaticFileSegment::TransactionSenders;
+        debug_assert!(self.writer.user_header().segment() == segment);
+
+        self.truncate(segment, to_delete, Some(last_block))?;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_segment_operation(
+                segment,
+                StaticFileProviderOperation::Prune,
+                Some(start.elapsed()),
+            );
+        }
+
+        Ok(())
+    }
+
     fn reader(&self) -> StaticFileProvider {
         Self::upgrade_provider_to_strong_reference(&self.reader)
     }
@@ -752,18 +922,31 @@ fn create_jar(
     segment: StaticFileSegment,
     path: &Path,
     expected_block_range: SegmentRangeInclusive,
+    compression: Compression,
+    dictionaries: Option<Vec<Vec<u8>>>,
 ) -> NippyJar<SegmentHeader> {
-    let mut jar = NippyJar::new(
+    let jar = NippyJar::new(
         segment.columns(),
         path,
         SegmentHeader::new(expected_block_range, None, None, segment),
     );
 
-    // Transaction and Receipt already have the compression scheme used natively in its encoding.
-    // (zstd-dictionary)
-    if segment.is_headers() {
-        jar = jar.with_lz4();
+    // By default, Transaction and Receipt already have the compression scheme used natively in
+    // its encoding (zstd-dictionary), and senders are addresses which don't compress.
+    match compression {
+        Compression::Lz4 => jar.with_lz4(),
+        Compression::Zstd => jar.with_zstd(false, 0),
+        Compression::ZstdWithDictionary => match dictionaries {
+            Some(dictionaries) => jar.with_zstd_dictionaries(dictionaries),
+            None => {
+                warn!(
+                    target: "provider::static_file",
+                    ?segment,
+                    "No trained zstd dictionaries found, falling back to zstd without dictionary"
+                );
+                jar.with_zstd(false, 0)
+            }
+        },
+        Compression::Uncompressed => jar,
     }
-
-    jar
 }
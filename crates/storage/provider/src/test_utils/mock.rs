@@ -1,7 +1,8 @@
 use crate::{
     traits::{BlockSource, ReceiptProvider},
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, FullExecutionDataProvider, HeaderProvider,
+    AccountHistoryReader, AccountReader, BadBlocks, BadBlocksProvider, BlockHashReader,
+    BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt, ChainSpecProvider,
+    ChangeSetReader, EvmEnvProvider, FullExecutionDataProvider, HeaderProvider,
     ReceiptProviderIdExt, RequestsProvider, StateProvider, StateProviderBox, StateProviderFactory,
     StateRootProvider, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
@@ -704,3 +705,27 @@ impl ChangeSetReader for MockEthProvider {
         Ok(Vec::default())
     }
 }
+
+impl AccountHistoryReader for MockEthProvider {
+    fn account_history_blocks(
+        &self,
+        _address: Address,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
+
+    fn address_transactions(
+        &self,
+        _address: Address,
+        _range: RangeInclusive<TxNumber>,
+    ) -> ProviderResult<Vec<TxNumber>> {
+        Ok(Vec::default())
+    }
+}
+
+impl BadBlocksProvider for MockEthProvider {
+    fn bad_blocks(&self) -> BadBlocks {
+        BadBlocks::default()
+    }
+}
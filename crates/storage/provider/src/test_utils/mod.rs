@@ -2,7 +2,7 @@ use crate::{providers::StaticFileProvider, ProviderFactory};
 use reth_chainspec::{ChainSpec, MAINNET};
 use reth_db::{
     test_utils::{create_test_rw_db, create_test_static_files_dir, TempDatabase},
-    DatabaseEnv,
+    DatabaseEnv, MemDatabase,
 };
 use std::sync::Arc;
 
@@ -32,3 +32,15 @@ pub fn create_test_provider_factory_with_chain_spec(
         StaticFileProvider::read_write(static_dir.into_path()).expect("static file provider"),
     )
 }
+
+/// Creates test provider factory with provided chain spec, that keeps the database in memory.
+pub fn create_test_provider_factory_in_memory(
+    chain_spec: Arc<ChainSpec>,
+) -> ProviderFactory<MemDatabase> {
+    let (static_dir, _) = create_test_static_files_dir();
+    ProviderFactory::new(
+        MemDatabase::new(),
+        chain_spec,
+        StaticFileProvider::read_write(static_dir.into_path()).expect("static file provider"),
+    )
+}
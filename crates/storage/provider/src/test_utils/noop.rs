@@ -1,10 +1,10 @@
 use crate::{
     traits::{BlockSource, ReceiptProvider},
-    AccountReader, BlockHashReader, BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt,
-    ChainSpecProvider, ChangeSetReader, EvmEnvProvider, HeaderProvider, PruneCheckpointReader,
-    ReceiptProviderIdExt, RequestsProvider, StageCheckpointReader, StateProvider, StateProviderBox,
-    StateProviderFactory, StateRootProvider, TransactionVariant, TransactionsProvider,
-    WithdrawalsProvider,
+    AccountHistoryReader, AccountReader, BadBlocks, BadBlocksProvider, BlockHashReader,
+    BlockIdReader, BlockNumReader, BlockReader, BlockReaderIdExt, ChainSpecProvider,
+    ChangeSetReader, EvmEnvProvider, HeaderProvider, PruneCheckpointReader, ReceiptProviderIdExt,
+    RequestsProvider, StageCheckpointReader, StateProvider, StateProviderBox, StateProviderFactory,
+    StateRootProvider, TransactionVariant, TransactionsProvider, WithdrawalsProvider,
 };
 use reth_chainspec::{ChainInfo, ChainSpec, MAINNET};
 use reth_db_api::models::{AccountBeforeTx, StoredBlockBodyIndices};
@@ -300,6 +300,24 @@ impl ChangeSetReader for NoopProvider {
     }
 }
 
+impl AccountHistoryReader for NoopProvider {
+    fn account_history_blocks(
+        &self,
+        _address: Address,
+        _range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>> {
+        Ok(Vec::default())
+    }
+
+    fn address_transactions(
+        &self,
+        _address: Address,
+        _range: RangeInclusive<TxNumber>,
+    ) -> ProviderResult<Vec<TxNumber>> {
+        Ok(Vec::default())
+    }
+}
+
 impl StateRootProvider for NoopProvider {
     fn state_root(&self, _state: &BundleState) -> ProviderResult<B256> {
         Ok(B256::default())
@@ -473,3 +491,9 @@ impl PruneCheckpointReader for NoopProvider {
         Ok(None)
     }
 }
+
+impl BadBlocksProvider for NoopProvider {
+    fn bad_blocks(&self) -> BadBlocks {
+        BadBlocks::default()
+    }
+}
@@ -1,4 +1,6 @@
+use crate::BadBlocks;
 use alloy_rpc_types_engine::ForkchoiceState;
+use auto_impl::auto_impl;
 use reth_primitives::SealedHeader;
 use std::time::Instant;
 
@@ -27,3 +29,13 @@ pub trait CanonChainTracker: Send + Sync {
     /// Sets the finalized block of the chain.
     fn set_finalized(&self, header: SealedHeader);
 }
+
+/// A type that keeps track of blocks that failed validation.
+#[auto_impl(&, Arc)]
+pub trait BadBlocksProvider: Send + Sync {
+    /// Returns the store of recent bad blocks.
+    ///
+    /// The returned [`BadBlocks`] shares its storage with the provider, so blocks inserted into it
+    /// are visible to every other holder.
+    fn bad_blocks(&self) -> BadBlocks;
+}
@@ -1,8 +1,9 @@
 //! Helper provider traits to encapsulate all provider traits for simplicity.
 
 use crate::{
-    AccountReader, BlockReaderIdExt, CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader,
-    DatabaseProviderFactory, EvmEnvProvider, StageCheckpointReader, StateProviderFactory,
+    AccountHistoryReader, AccountReader, BadBlocksProvider, BlockReaderIdExt,
+    CanonStateSubscriptions, ChainSpecProvider, ChangeSetReader, DatabaseProviderFactory,
+    EvmEnvProvider, PruneCheckpointReader, StageCheckpointReader, StateProviderFactory,
     StaticFileProviderFactory,
 };
 use reth_db_api::database::Database;
@@ -17,8 +18,11 @@ pub trait FullProvider<DB: Database>:
     + EvmEnvProvider
     + ChainSpecProvider
     + ChangeSetReader
+    + AccountHistoryReader
     + CanonStateSubscriptions
     + StageCheckpointReader
+    + PruneCheckpointReader
+    + BadBlocksProvider
     + Clone
     + Unpin
     + 'static
@@ -34,8 +38,11 @@ impl<T, DB: Database> FullProvider<DB> for T where
         + EvmEnvProvider
         + ChainSpecProvider
         + ChangeSetReader
+        + AccountHistoryReader
         + CanonStateSubscriptions
         + StageCheckpointReader
+        + PruneCheckpointReader
+        + BadBlocksProvider
         + Clone
         + Unpin
         + 'static
@@ -1,6 +1,6 @@
 use auto_impl::auto_impl;
 use reth_db_api::models::BlockNumberAddress;
-use reth_primitives::{Address, BlockNumber, B256};
+use reth_primitives::{Address, BlockNumber, TxNumber, B256};
 use reth_storage_errors::provider::ProviderResult;
 use std::{
     collections::BTreeMap,
@@ -38,6 +38,20 @@ pub trait HistoryWriter: Send + Sync {
         storage_transitions: BTreeMap<(Address, B256), Vec<u64>>,
     ) -> ProviderResult<()>;
 
+    /// Unwind and clear address transaction indices.
+    ///
+    /// Returns number of transactions walked.
+    fn unwind_address_transaction_indices(
+        &self,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<usize>;
+
+    /// Insert address transaction index to database. Used inside IndexAddressTransactions stage
+    fn insert_address_transaction_index(
+        &self,
+        address_transactions: BTreeMap<Address, Vec<TxNumber>>,
+    ) -> ProviderResult<()>;
+
     /// Read account/storage changesets and update account/storage history indices.
     fn update_history_indices(&self, range: RangeInclusive<BlockNumber>) -> ProviderResult<()>;
 }
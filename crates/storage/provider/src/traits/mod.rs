@@ -10,7 +10,7 @@ mod block;
 pub use block::*;
 
 mod chain_info;
-pub use chain_info::CanonChainTracker;
+pub use chain_info::{BadBlocksProvider, CanonChainTracker};
 
 mod header_sync_gap;
 pub use header_sync_gap::{HeaderSyncGap, HeaderSyncGapProvider};
@@ -18,6 +18,9 @@ pub use header_sync_gap::{HeaderSyncGap, HeaderSyncGapProvider};
 mod state;
 pub use state::StateWriter;
 
+mod state_override;
+pub use state_override::StateOverrideWriter;
+
 mod chain;
 pub use chain::{
     CanonStateNotification, CanonStateNotificationSender, CanonStateNotificationStream,
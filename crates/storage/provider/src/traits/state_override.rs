@@ -0,0 +1,22 @@
+use auto_impl::auto_impl;
+use reth_primitives::{Account, Address, BlockNumber, Bytecode, StorageEntry};
+use reth_storage_errors::provider::ProviderResult;
+
+/// Functionality to override the state of accounts on top of the tip of the chain.
+///
+/// This bypasses block execution and is intended for development nodes only.
+#[auto_impl(&, Arc)]
+pub trait StateOverrideWriter: Send + Sync {
+    /// Sets the account, its bytecode and the given storage slots on top of the tip of the chain.
+    ///
+    /// The override is visible in the latest state right away. It is recorded as a change of the
+    /// next block, whose number is returned, and only becomes part of a state root once that block
+    /// is built.
+    fn override_state(
+        &self,
+        address: Address,
+        account: Account,
+        bytecode: Option<Bytecode>,
+        storage: Vec<StorageEntry>,
+    ) -> ProviderResult<BlockNumber>;
+}
@@ -1,6 +1,6 @@
 use auto_impl::auto_impl;
 use reth_db_api::models::AccountBeforeTx;
-use reth_primitives::{Account, Address, BlockNumber};
+use reth_primitives::{Account, Address, BlockNumber, TxNumber};
 use reth_storage_errors::provider::ProviderResult;
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -53,3 +53,30 @@ pub trait ChangeSetReader: Send + Sync {
         block_number: BlockNumber,
     ) -> ProviderResult<Vec<AccountBeforeTx>>;
 }
+
+/// Account history index reader
+#[auto_impl(&, Arc, Box)]
+pub trait AccountHistoryReader: Send + Sync {
+    /// Returns the numbers of all blocks within the given range in which the account was changed,
+    /// in ascending order.
+    ///
+    /// This is served from the account history index, so only blocks in which the account info
+    /// (nonce, balance or bytecode) changed are returned. Blocks that were pruned from the index
+    /// are not returned.
+    fn account_history_blocks(
+        &self,
+        address: Address,
+        range: RangeInclusive<BlockNumber>,
+    ) -> ProviderResult<Vec<BlockNumber>>;
+
+    /// Returns the numbers of all transactions within the given range that were sent from or to
+    /// the address, in ascending order.
+    ///
+    /// This is served from the address transactions index, which is only populated if the
+    /// `IndexAddressTransactions` stage is enabled.
+    fn address_transactions(
+        &self,
+        address: Address,
+        range: RangeInclusive<TxNumber>,
+    ) -> ProviderResult<Vec<TxNumber>>;
+}
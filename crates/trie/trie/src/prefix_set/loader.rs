@@ -27,15 +27,33 @@ impl<'a, TX> PrefixSetLoader<'a, TX> {
 impl<'a, TX: DbTx> PrefixSetLoader<'a, TX> {
     /// Load all account and storage changes for the given block range.
     pub fn load(self, range: RangeInclusive<BlockNumber>) -> Result<TriePrefixSets, DatabaseError> {
+        let mut account_changeset_cursor = self.cursor_read::<tables::AccountChangeSets>()?;
+        let mut storage_cursor = self.cursor_dup_read::<tables::StorageChangeSets>()?;
+        let storage_range = BlockNumberAddress::range(range.clone());
+        self.load_changesets(
+            account_changeset_cursor.walk_range(range)?,
+            storage_cursor.walk_range(storage_range)?,
+        )
+    }
+
+    /// Load the prefix sets of the given account and storage changesets.
+    pub fn load_changesets(
+        self,
+        account_changesets: impl IntoIterator<
+            Item = Result<(BlockNumber, AccountBeforeTx), DatabaseError>,
+        >,
+        storage_changesets: impl IntoIterator<
+            Item = Result<(BlockNumberAddress, StorageEntry), DatabaseError>,
+        >,
+    ) -> Result<TriePrefixSets, DatabaseError> {
         // Initialize prefix sets.
         let mut account_prefix_set = PrefixSetMut::default();
         let mut storage_prefix_sets = HashMap::<B256, PrefixSetMut>::default();
         let mut destroyed_accounts = HashSet::default();
 
         // Walk account changeset and insert account prefixes.
-        let mut account_changeset_cursor = self.cursor_read::<tables::AccountChangeSets>()?;
         let mut account_plain_state_cursor = self.cursor_read::<tables::PlainAccountState>()?;
-        for account_entry in account_changeset_cursor.walk_range(range.clone())? {
+        for account_entry in account_changesets {
             let (_, AccountBeforeTx { address, .. }) = account_entry?;
             let hashed_address = keccak256(address);
             account_prefix_set.insert(Nibbles::unpack(hashed_address));
@@ -47,9 +65,7 @@ impl<'a, TX: DbTx> PrefixSetLoader<'a, TX> {
 
         // Walk storage changeset and insert storage prefixes as well as account prefixes if missing
         // from the account prefix set.
-        let mut storage_cursor = self.cursor_dup_read::<tables::StorageChangeSets>()?;
-        let storage_range = BlockNumberAddress::range(range);
-        for storage_entry in storage_cursor.walk_range(storage_range)? {
+        for storage_entry in storage_changesets {
             let (BlockNumberAddress((_, address)), StorageEntry { key, .. }) = storage_entry?;
             let hashed_address = keccak256(address);
             account_prefix_set.insert(Nibbles::unpack(hashed_address));
@@ -1,7 +1,7 @@
 use crate::{
     hashed_cursor::{HashedCursorFactory, HashedStorageCursor},
     node_iter::{TrieElement, TrieNodeIter},
-    prefix_set::PrefixSetMut,
+    prefix_set::{PrefixSetMut, TriePrefixSets},
     trie_cursor::{DatabaseAccountTrieCursor, DatabaseStorageTrieCursor},
     walker::TrieWalker,
     HashBuilder, Nibbles,
@@ -10,8 +10,9 @@ use alloy_rlp::{BufMut, Encodable};
 use reth_db::tables;
 use reth_db_api::transaction::DbTx;
 use reth_execution_errors::{StateRootError, StorageRootError};
-use reth_primitives::{constants::EMPTY_ROOT_HASH, keccak256, Address, B256};
+use reth_primitives::{constants::EMPTY_ROOT_HASH, keccak256, Address, Bytes, B256};
 use reth_trie_common::{proof::ProofRetainer, AccountProof, StorageProof, TrieAccount};
+use std::collections::BTreeMap;
 /// A struct for generating merkle proofs.
 ///
 /// Proof generator adds the target address and slots to the prefix set, enables the proof retainer
@@ -23,12 +24,31 @@ pub struct Proof<'a, TX, H> {
     tx: &'a TX,
     /// The factory for hashed cursors.
     hashed_cursor_factory: H,
+    /// A set of prefix sets that have changes relative to the database trie.
+    prefix_sets: TriePrefixSets,
 }
 
 impl<'a, TX> Proof<'a, TX, &'a TX> {
     /// Create a new [Proof] instance.
-    pub const fn new(tx: &'a TX) -> Self {
-        Self { tx, hashed_cursor_factory: tx }
+    pub fn new(tx: &'a TX) -> Self {
+        Self { tx, hashed_cursor_factory: tx, prefix_sets: TriePrefixSets::default() }
+    }
+}
+
+impl<'a, TX, H> Proof<'a, TX, H> {
+    /// Set the hashed cursor factory.
+    pub fn with_hashed_cursor_factory<HF>(self, hashed_cursor_factory: HF) -> Proof<'a, TX, HF> {
+        Proof { tx: self.tx, hashed_cursor_factory, prefix_sets: self.prefix_sets }
+    }
+
+    /// Set the prefix sets.
+    ///
+    /// Trie nodes stored in the database are only reused for paths that are not present in the
+    /// prefix sets. This must be set whenever the hashed cursor factory overlays the database
+    /// state, e.g. with reverts of historical changesets.
+    pub fn with_prefix_sets(mut self, prefix_sets: TriePrefixSets) -> Self {
+        self.prefix_sets = prefix_sets;
+        self
     }
 }
 
@@ -52,7 +72,8 @@ where
             DatabaseAccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
 
         // Create the walker.
-        let mut prefix_set = PrefixSetMut::default();
+        let mut prefix_set =
+            PrefixSetMut::from(self.prefix_sets.account_prefix_set.iter().cloned());
         prefix_set.insert(target_nibbles.clone());
         let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
 
@@ -94,6 +115,102 @@ where
         Ok(account_proof)
     }
 
+    /// Generate the proof nodes for the given paths in the account trie.
+    ///
+    /// The returned map contains every trie node on the path from the root to any of the targets,
+    /// keyed by the path of the node. Targets can be full hashed addresses or partial paths.
+    pub fn account_proof_nodes(
+        &self,
+        targets: impl IntoIterator<Item = Nibbles>,
+    ) -> Result<BTreeMap<Nibbles, Bytes>, StateRootError> {
+        let targets = targets.into_iter().collect::<Vec<_>>();
+
+        let hashed_account_cursor = self.hashed_cursor_factory.hashed_account_cursor()?;
+        let trie_cursor =
+            DatabaseAccountTrieCursor::new(self.tx.cursor_read::<tables::AccountsTrie>()?);
+
+        let mut prefix_set =
+            PrefixSetMut::from(self.prefix_sets.account_prefix_set.iter().cloned());
+        targets.iter().cloned().for_each(|target| prefix_set.insert(target));
+        let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
+
+        let retainer = ProofRetainer::from_iter(targets);
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(retainer);
+
+        let mut account_rlp = Vec::with_capacity(128);
+        let mut account_node_iter = TrieNodeIter::new(walker, hashed_account_cursor);
+        while let Some(account_node) = account_node_iter.try_next()? {
+            match account_node {
+                TrieElement::Branch(node) => {
+                    hash_builder.add_branch(node.key, node.value, node.children_are_in_trie);
+                }
+                TrieElement::Leaf(hashed_address, account) => {
+                    let storage_root = self.storage_root(hashed_address)?;
+
+                    account_rlp.clear();
+                    let account = TrieAccount::from((account, storage_root));
+                    account.encode(&mut account_rlp as &mut dyn BufMut);
+
+                    hash_builder.add_leaf(Nibbles::unpack(hashed_address), &account_rlp);
+                }
+            }
+        }
+
+        let _ = hash_builder.root();
+
+        Ok(hash_builder.take_proofs())
+    }
+
+    /// Generate the proof nodes for the given paths in the storage trie of the account.
+    ///
+    /// See also [`Self::account_proof_nodes`].
+    pub fn storage_proof_nodes(
+        &self,
+        hashed_address: B256,
+        targets: impl IntoIterator<Item = Nibbles>,
+    ) -> Result<BTreeMap<Nibbles, Bytes>, StorageRootError> {
+        let mut hashed_storage_cursor =
+            self.hashed_cursor_factory.hashed_storage_cursor(hashed_address)?;
+
+        // short circuit on empty storage
+        if hashed_storage_cursor.is_storage_empty()? {
+            return Ok(BTreeMap::new())
+        }
+
+        let targets = targets.into_iter().collect::<Vec<_>>();
+        let mut prefix_set = PrefixSetMut::from(targets.clone());
+        if let Some(storage_prefix_set) = self.prefix_sets.storage_prefix_sets.get(&hashed_address)
+        {
+            storage_prefix_set.iter().cloned().for_each(|nibbles| prefix_set.insert(nibbles));
+        }
+        let trie_cursor = DatabaseStorageTrieCursor::new(
+            self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
+            hashed_address,
+        );
+        let walker = TrieWalker::new(trie_cursor, prefix_set.freeze());
+
+        let retainer = ProofRetainer::from_iter(targets);
+        let mut hash_builder = HashBuilder::default().with_proof_retainer(retainer);
+        let mut storage_node_iter = TrieNodeIter::new(walker, hashed_storage_cursor);
+        while let Some(node) = storage_node_iter.try_next()? {
+            match node {
+                TrieElement::Branch(node) => {
+                    hash_builder.add_branch(node.key, node.value, node.children_are_in_trie);
+                }
+                TrieElement::Leaf(hashed_slot, value) => {
+                    hash_builder.add_leaf(
+                        Nibbles::unpack(hashed_slot),
+                        alloy_rlp::encode_fixed_size(&value).as_ref(),
+                    );
+                }
+            }
+        }
+
+        let _ = hash_builder.root();
+
+        Ok(hash_builder.take_proofs())
+    }
+
     /// Compute storage root.
     pub fn storage_root(&self, hashed_address: B256) -> Result<B256, StorageRootError> {
         let (storage_root, _) = self.storage_root_with_proofs(hashed_address, &[])?;
@@ -117,7 +234,12 @@ where
         }
 
         let target_nibbles = proofs.iter().map(|p| p.nibbles.clone()).collect::<Vec<_>>();
-        let prefix_set = PrefixSetMut::from(target_nibbles.clone()).freeze();
+        let mut prefix_set = PrefixSetMut::from(target_nibbles.clone());
+        if let Some(storage_prefix_set) = self.prefix_sets.storage_prefix_sets.get(&hashed_address)
+        {
+            storage_prefix_set.iter().cloned().for_each(|nibbles| prefix_set.insert(nibbles));
+        }
+        let prefix_set = prefix_set.freeze();
         let trie_cursor = DatabaseStorageTrieCursor::new(
             self.tx.cursor_dup_read::<tables::StoragesTrie>()?,
             hashed_address,
@@ -162,7 +284,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::StateRoot;
+    use crate::{HashedPostState, StateRoot};
     use once_cell::sync::Lazy;
     use reth_chainspec::{Chain, ChainSpec, HOLESKY, MAINNET};
     use reth_db_api::database::Database;
@@ -291,6 +413,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn testspec_overlay_proofs() {
+        // Create test database and insert genesis accounts.
+        let factory = create_test_provider_factory();
+        insert_genesis(&factory, TEST_SPEC.clone()).unwrap();
+
+        let changed = Address::from_str("0x2031f89b3ea8014eb51a78c316e42af3e0d7695f").unwrap();
+        let unchanged = Address::from_str("0x62b0dd4aab2b1a0a04e279e2b828791a10755528").unwrap();
+        let account = Account { nonce: 1, balance: U256::from(1), bytecode_hash: None };
+        let hashed_state =
+            HashedPostState::default().with_accounts([(keccak256(changed), Some(account))]);
+
+        let provider = factory.provider().unwrap();
+        let root = hashed_state.state_root(provider.tx_ref()).unwrap();
+        for target in [changed, unchanged] {
+            let account_proof = hashed_state.account_proof(provider.tx_ref(), target, &[]).unwrap();
+            assert_eq!(account_proof.verify(root), Ok(()));
+        }
+
+        let account_proof = hashed_state.account_proof(provider.tx_ref(), changed, &[]).unwrap();
+        assert_eq!(account_proof.info, Some(account));
+    }
+
     #[test]
     fn testspec_empty_storage_proof() {
         // Create test database and insert genesis accounts.
@@ -361,6 +506,34 @@ mod tests {
         assert_eq!(account_proof.verify(root), Ok(()));
     }
 
+    #[test]
+    fn mainnet_genesis_account_proof_nodes() {
+        // Create test database and insert genesis accounts.
+        let factory = create_test_provider_factory();
+        insert_genesis(&factory, MAINNET.clone()).unwrap();
+
+        let existing = Address::from_str("0x000d836201318ec6899a67540690382780743280").unwrap();
+        let nonexistent = Address::from_str("0x000d836201318ec6899a67540690382780743281").unwrap();
+
+        let provider = factory.provider().unwrap();
+        let proof = Proof::new(provider.tx_ref());
+        let nodes = proof
+            .account_proof_nodes([existing, nonexistent].map(|a| Nibbles::unpack(keccak256(a))))
+            .unwrap();
+
+        // the nodes of a multiproof are the union of the single account proofs
+        for target in [existing, nonexistent] {
+            for node in proof.account_proof(target, &[]).unwrap().proof {
+                assert!(nodes.values().any(|n| *n == node));
+            }
+        }
+
+        // the root node can be requested by the empty path
+        let root_node = proof.account_proof_nodes([Nibbles::default()]).unwrap();
+        assert_eq!(root_node.len(), 1);
+        assert_eq!(root_node.get(&Nibbles::default()), nodes.get(&Nibbles::default()));
+    }
+
     #[test]
     fn holesky_deposit_contract_proof() {
         // Create test database and insert genesis accounts.
@@ -1,6 +1,7 @@
 use crate::{
     hashed_cursor::HashedPostStateCursorFactory,
     prefix_set::{PrefixSetMut, TriePrefixSets},
+    proof::Proof,
     updates::TrieUpdates,
     Nibbles, StateRoot,
 };
@@ -12,7 +13,8 @@ use reth_db_api::{
     transaction::DbTx,
 };
 use reth_execution_errors::StateRootError;
-use reth_primitives::{keccak256, Account, Address, BlockNumber, B256, U256};
+use reth_primitives::{keccak256, Account, Address, BlockNumber, StorageEntry, B256, U256};
+use reth_trie_common::AccountProof;
 use revm::db::BundleAccount;
 use std::{
     collections::{hash_map, HashMap, HashSet},
@@ -68,11 +70,31 @@ impl HashedPostState {
     pub fn from_revert_range<TX: DbTx>(
         tx: &TX,
         range: RangeInclusive<BlockNumber>,
+    ) -> Result<Self, DatabaseError> {
+        let mut account_changesets_cursor = tx.cursor_read::<tables::AccountChangeSets>()?;
+        let mut storage_changesets_cursor = tx.cursor_read::<tables::StorageChangeSets>()?;
+        Self::from_reverts(
+            account_changesets_cursor.walk_range(range.clone())?,
+            storage_changesets_cursor.walk_range(BlockNumberAddress::range(range))?,
+        )
+    }
+
+    /// Initialize [`HashedPostState`] from the account and storage changesets of a block range,
+    /// ordered by block number.
+    ///
+    /// NOTE: In order to have the resulting [`HashedPostState`] be a correct
+    /// overlay of the plain state, the changesets must end at the current tip.
+    pub fn from_reverts(
+        account_changesets: impl IntoIterator<
+            Item = Result<(BlockNumber, AccountBeforeTx), DatabaseError>,
+        >,
+        storage_changesets: impl IntoIterator<
+            Item = Result<(BlockNumberAddress, StorageEntry), DatabaseError>,
+        >,
     ) -> Result<Self, DatabaseError> {
         // Iterate over account changesets and record value before first occurring account change.
         let mut accounts = HashMap::<Address, Option<Account>>::default();
-        let mut account_changesets_cursor = tx.cursor_read::<tables::AccountChangeSets>()?;
-        for entry in account_changesets_cursor.walk_range(range.clone())? {
+        for entry in account_changesets {
             let (_, AccountBeforeTx { address, info }) = entry?;
             if let hash_map::Entry::Vacant(entry) = accounts.entry(address) {
                 entry.insert(info);
@@ -81,8 +103,7 @@ impl HashedPostState {
 
         // Iterate over storage changesets and record value before first occurring storage change.
         let mut storages = HashMap::<Address, HashMap<B256, U256>>::default();
-        let mut storage_changesets_cursor = tx.cursor_read::<tables::StorageChangeSets>()?;
-        for entry in storage_changesets_cursor.walk_range(BlockNumberAddress::range(range))? {
+        for entry in storage_changesets {
             let (BlockNumberAddress((_, address)), storage) = entry?;
             let account_storage = storages.entry(address).or_default();
             if let hash_map::Entry::Vacant(entry) = account_storage.entry(storage.key) {
@@ -255,6 +276,25 @@ impl HashedPostState {
             .with_prefix_sets(prefix_sets)
             .root_with_updates()
     }
+
+    /// Generates the account proof for the target address and storage slots against the state
+    /// of the database overlaid with this [`HashedPostState`].
+    ///
+    /// This is used to generate proofs for historical blocks by overlaying the reverts of all
+    /// changesets between the target block and the database tip.
+    pub fn account_proof<TX: DbTx>(
+        &self,
+        tx: &TX,
+        address: Address,
+        slots: &[B256],
+    ) -> Result<AccountProof, StateRootError> {
+        let sorted = self.clone().into_sorted();
+        let prefix_sets = self.construct_prefix_sets();
+        Proof::new(tx)
+            .with_hashed_cursor_factory(HashedPostStateCursorFactory::new(tx, &sorted))
+            .with_prefix_sets(prefix_sets)
+            .account_proof(address, slots)
+    }
 }
 
 /// Representation of in-memory hashed storage.